@@ -0,0 +1,76 @@
+use alpha_g_analysis::midas::{events, Bank, BankFormat, Event, MidasWriter};
+use alpha_g_analysis::{open, open_with_checksum};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+// Large enough that reading it is not dominated by opening the file.
+const NUM_EVENTS: u32 = 10_000;
+const BANK_SIZE: usize = 4096;
+
+// Write the same MIDAS run as an uncompressed and an lz4 compressed file.
+fn synthetic_files(dir: &Path) -> [PathBuf; 2] {
+    let data: Vec<u8> = (0..BANK_SIZE).map(|i| (i * 7 + i / 13) as u8).collect();
+    let mut writer = MidasWriter::new(Vec::new(), 11186, 100, b"{}").unwrap();
+    for serial_number in 0..NUM_EVENTS {
+        writer
+            .write_event(&Event {
+                id: 1,
+                trigger_mask: 0,
+                serial_number,
+                timestamp: 100,
+                bank_format: BankFormat::Bank16,
+                banks: vec![Bank {
+                    name: *b"PC00",
+                    data_type: 1,
+                    data: &data,
+                }],
+            })
+            .unwrap();
+    }
+    let contents = writer.finish(200, b"{}").unwrap();
+
+    let mid = dir.join("run.mid");
+    std::fs::write(&mid, &contents).unwrap();
+    let lz4 = dir.join("run.mid.lz4");
+    let mut encoder = lz4::EncoderBuilder::new()
+        .build(std::fs::File::create(&lz4).unwrap())
+        .unwrap();
+    encoder.write_all(&contents).unwrap();
+    let (_, result) = encoder.finish();
+    result.unwrap();
+
+    [mid, lz4]
+}
+
+fn read_path(c: &mut Criterion) {
+    let dir = std::env::temp_dir().join(format!(
+        "alpha_g_analysis_{}_checksum_bench",
+        std::process::id()
+    ));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    for path in synthetic_files(&dir) {
+        let name = path.file_name().unwrap().to_string_lossy().into_owned();
+        let mut group = c.benchmark_group(name);
+        // Same as the binaries: open the file and go over all its events.
+        group.bench_function("plain", |b| {
+            b.iter(|| {
+                let contents = open(black_box(&path)).unwrap();
+                events(&contents).unwrap().len()
+            })
+        });
+        group.bench_function("hashed", |b| {
+            b.iter(|| {
+                let (contents, checksum) = open_with_checksum(black_box(&path)).unwrap();
+                (events(&contents).unwrap().len(), checksum.xxh64)
+            })
+        });
+        group.finish();
+    }
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+criterion_group!(benches, read_path);
+criterion_main!(benches);
@@ -0,0 +1,50 @@
+use std::path::Path;
+use std::process::Command;
+
+// Capture build information that is reported by the `--version-json` flag of
+// all binaries.
+fn main() {
+    // Outside a git checkout (e.g. `cargo install` from crates.io) there is
+    // nothing to describe.
+    let git_describe = Command::new("git")
+        .args(["describe", "--always", "--dirty", "--tags"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|describe| describe.trim().to_string())
+        .filter(|describe| !describe.is_empty())
+        .unwrap_or_else(|| String::from("unknown"));
+    println!("cargo:rustc-env=ALPHA_G_GIT_DESCRIBE={git_describe}");
+
+    let mut features: Vec<_> = std::env::vars()
+        .filter_map(|(key, _)| {
+            key.strip_prefix("CARGO_FEATURE_")
+                .map(|feature| feature.to_lowercase().replace('_', "-"))
+        })
+        .collect();
+    features.sort_unstable();
+    println!("cargo:rustc-env=ALPHA_G_FEATURES={}", features.join(","));
+
+    println!("cargo:rerun-if-changed=build.rs");
+    // A new commit on the current branch doesn't change `HEAD` itself, only
+    // the ref it points to (or `packed-refs`). New tags also change the
+    // description.
+    let mut paths = vec![
+        String::from("../.git/HEAD"),
+        String::from("../.git/index"),
+        String::from("../.git/packed-refs"),
+        String::from("../.git/refs/tags"),
+    ];
+    if let Some(head_ref) = std::fs::read_to_string("../.git/HEAD")
+        .ok()
+        .and_then(|head| Some(head.strip_prefix("ref:")?.trim().to_string()))
+    {
+        paths.push(format!("../.git/{head_ref}"));
+    }
+    for path in paths {
+        if Path::new(&path).exists() {
+            println!("cargo:rerun-if-changed={path}");
+        }
+    }
+}
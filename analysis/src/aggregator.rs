@@ -0,0 +1,318 @@
+use alpha_g_detector::alpha16::TryAdcPacketFromSliceError;
+use alpha_g_detector::padwing::TryPwbPacketFromSliceError;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+use std::sync::Mutex;
+
+/// Type of a data packet with a version field.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PacketKind {
+    /// Alpha16 ADC packet.
+    Adc,
+    /// Padwing PWB packet.
+    Pwb,
+}
+
+impl fmt::Display for PacketKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Adc => write!(f, "ADC"),
+            Self::Pwb => write!(f, "PWB"),
+        }
+    }
+}
+
+/// Number of errors caused by an unknown version of a packet type.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UnknownVersion {
+    /// Type of the packet.
+    pub packet: PacketKind,
+    /// Version found in the packet.
+    pub version: u8,
+    /// Number of errors with this packet type and version.
+    pub count: usize,
+}
+
+impl fmt::Display for UnknownVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "unknown {} packet version `{}`",
+            self.packet, self.version
+        )
+    }
+}
+
+/// What to do with an error after adding it to an [`ErrorAggregator`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Emission {
+    /// The error is not caused by an unknown packet version. It should be
+    /// handled as usual.
+    Other,
+    /// First error caused by this unknown packet version. It should be
+    /// reported prominently.
+    Warn(UnknownVersion),
+    /// Repeated error caused by an already reported unknown packet version. It
+    /// has been counted, and it should not be reported again.
+    Suppress,
+}
+
+// Find an unknown packet version error anywhere in the chain of sources.
+fn unknown_version(error: &(dyn Error + 'static)) -> Option<(PacketKind, u8)> {
+    let mut current = Some(error);
+    while let Some(error) = current {
+        if let Some(TryAdcPacketFromSliceError::UnknownVersion { found }) = error.downcast_ref() {
+            return Some((PacketKind::Adc, *found));
+        }
+        if let Some(TryPwbPacketFromSliceError::UnknownVersion { found }) = error.downcast_ref() {
+            return Some((PacketKind::Pwb, *found));
+        }
+        current = error.source();
+    }
+
+    None
+}
+
+// Name of an error (i.e. the name of the enum variant or struct) taken from its
+// `Debug` representation. Unlike `Display`, this doesn't include any of the
+// values in the error.
+fn debug_name(error: &dyn Error) -> String {
+    let debug = format!("{error:?}");
+    let end = debug
+        .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .unwrap_or(debug.len());
+    debug[..end].to_string()
+}
+
+/// Return the kind of an error: the name of the error (e.g. the enum variant)
+/// followed by the names of all its sources, e.g. `BadAlpha16/UnknownType`.
+/// It doesn't depend on any value in the error.
+pub fn error_kind(error: &(dyn Error + 'static)) -> String {
+    let mut names = vec![debug_name(error)];
+    let mut current = error.source();
+    while let Some(error) = current {
+        names.push(debug_name(error));
+        current = error.source();
+    }
+
+    names.join("/")
+}
+
+// Errors are counted separately for each kind.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum ErrorKind {
+    UnknownVersion(PacketKind, u8),
+    // Name of the error followed by the names of all its sources, e.g.
+    // `BadAlpha16/UnknownType`.
+    Other(String),
+}
+
+impl ErrorKind {
+    fn of(error: &(dyn Error + 'static)) -> Self {
+        if let Some((packet, version)) = unknown_version(error) {
+            return Self::UnknownVersion(packet, version);
+        }
+        Self::Other(error_kind(error))
+    }
+}
+
+/// Deduplicate errors caused by unknown packet versions.
+///
+/// When new firmware is deployed, every single packet from the updated boards
+/// fails with the same error. Instead of reporting each of these errors, only
+/// the first one per `(packet type, version)` pair should be reported, and the
+/// rest are counted silently. All other errors are left to the caller, but
+/// they are still counted by kind (see [`ErrorAggregator::other_errors`]).
+#[derive(Clone, Debug, Default)]
+pub struct ErrorAggregator {
+    unknown_versions: BTreeMap<(PacketKind, u8), usize>,
+    other_errors: BTreeMap<String, usize>,
+}
+
+impl ErrorAggregator {
+    /// Add an error to the aggregator.
+    ///
+    /// The error is classified by searching its whole chain of sources, so
+    /// any error that wraps a packet error (e.g.
+    /// [`TryMainEventFromDataBanksError`](alpha_g_physics::TryMainEventFromDataBanksError))
+    /// can be added directly.
+    pub fn add(&mut self, error: &(dyn Error + 'static)) -> Emission {
+        self.add_kind(ErrorKind::of(error))
+    }
+
+    fn add_kind(&mut self, kind: ErrorKind) -> Emission {
+        let (packet, version) = match kind {
+            ErrorKind::UnknownVersion(packet, version) => (packet, version),
+            ErrorKind::Other(name) => {
+                *self.other_errors.entry(name).or_default() += 1;
+                return Emission::Other;
+            }
+        };
+        let count = self.unknown_versions.entry((packet, version)).or_default();
+        *count += 1;
+        if *count == 1 {
+            Emission::Warn(UnknownVersion {
+                packet,
+                version,
+                count: *count,
+            })
+        } else {
+            Emission::Suppress
+        }
+    }
+
+    /// Add all the errors counted by `other` to this aggregator.
+    pub fn merge(&mut self, other: ErrorAggregator) {
+        for (key, count) in other.unknown_versions {
+            *self.unknown_versions.entry(key).or_default() += count;
+        }
+        for (kind, count) in other.other_errors {
+            *self.other_errors.entry(kind).or_default() += count;
+        }
+    }
+
+    /// Return the total number of errors of each kind (see [`error_kind`])
+    /// that are not caused by an unknown packet version, sorted by kind.
+    pub fn other_errors(&self) -> impl Iterator<Item = (&str, usize)> + '_ {
+        self.other_errors
+            .iter()
+            .map(|(kind, &count)| (kind.as_str(), count))
+    }
+
+    /// Return the total number of errors for each unknown packet version,
+    /// sorted by packet type and version.
+    pub fn unknown_versions(&self) -> impl Iterator<Item = UnknownVersion> + '_ {
+        self.unknown_versions
+            .iter()
+            .map(|(&(packet, version), &count)| UnknownVersion {
+                packet,
+                version,
+                count,
+            })
+    }
+}
+
+/// What to do with an error after adding it to an [`ErrorSampler`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Sample {
+    /// First error caused by this unknown packet version. It should be
+    /// reported prominently.
+    Warn(UnknownVersion),
+    /// The error is within the sample of its kind. It should be reported.
+    Report,
+    /// The sample of this kind of error is full. It has been counted, and it
+    /// should not be reported.
+    Suppress,
+}
+
+#[derive(Debug, Default)]
+struct Shard {
+    aggregator: ErrorAggregator,
+    // Kinds whose sample is known to be full.
+    full: HashSet<ErrorKind>,
+    // Suppressed errors (other than unknown versions) since the last call to
+    // `take_suppressed`.
+    suppressed: HashMap<String, usize>,
+}
+
+/// Thread-safe [`ErrorAggregator`] that reports only a sample of the errors.
+///
+/// Errors caused by an unknown packet version are reported once per
+/// `(packet type, version)` pair, exactly as with an [`ErrorAggregator`]. Any
+/// other error is reported at most `limit` times per kind (see
+/// [`ErrorAggregator::other_errors`]), and the rest are suppressed. All errors
+/// are counted regardless of the sampling.
+///
+/// Errors are counted in a separate shard for each thread in the current rayon
+/// thread pool, so threads don't contend on a single lock. The count of
+/// reported errors is shared, but a thread only looks at it until it finds the
+/// sample of a kind full.
+#[derive(Debug)]
+pub struct ErrorSampler {
+    limit: Option<usize>,
+    shards: Vec<Mutex<Shard>>,
+    reported: Mutex<HashMap<ErrorKind, usize>>,
+}
+
+impl ErrorSampler {
+    /// Create a new sampler that reports at most `limit` errors of each kind.
+    /// A limit of [`None`] reports all errors.
+    pub fn new(limit: Option<usize>) -> Self {
+        Self {
+            limit,
+            shards: (0..rayon::current_num_threads())
+                .map(|_| Mutex::default())
+                .collect(),
+            reported: Mutex::default(),
+        }
+    }
+
+    /// Add an error to the sampler. See [`ErrorAggregator::add`] for how
+    /// errors are classified.
+    pub fn add(&self, error: &(dyn Error + 'static)) -> Sample {
+        let kind = ErrorKind::of(error);
+        // Threads outside the pool (e.g. the main thread) share the first
+        // shard.
+        let index = rayon::current_thread_index().unwrap_or(0) % self.shards.len();
+        let mut shard = self.shards[index].lock().unwrap();
+        shard.aggregator.add_kind(kind.clone());
+
+        let limit = match kind {
+            ErrorKind::UnknownVersion(..) => 1,
+            ErrorKind::Other(_) => match self.limit {
+                Some(limit) => limit,
+                None => return Sample::Report,
+            },
+        };
+        if !shard.full.contains(&kind) {
+            let mut reported = self.reported.lock().unwrap();
+            let count = reported.entry(kind.clone()).or_default();
+            if *count < limit {
+                *count += 1;
+                return match kind {
+                    ErrorKind::UnknownVersion(packet, version) => Sample::Warn(UnknownVersion {
+                        packet,
+                        version,
+                        count: 1,
+                    }),
+                    ErrorKind::Other(_) => Sample::Report,
+                };
+            }
+            drop(reported);
+            shard.full.insert(kind.clone());
+        }
+        if let ErrorKind::Other(name) = kind {
+            *shard.suppressed.entry(name).or_default() += 1;
+        }
+
+        Sample::Suppress
+    }
+
+    /// Return the number of suppressed errors of each kind (other than
+    /// unknown packet versions) since the last call to this method, sorted by
+    /// kind. Kinds without suppressed errors are omitted.
+    pub fn take_suppressed(&self) -> Vec<(String, usize)> {
+        let mut suppressed = BTreeMap::new();
+        for shard in &self.shards {
+            for (kind, count) in shard.lock().unwrap().suppressed.drain() {
+                *suppressed.entry(kind).or_default() += count;
+            }
+        }
+
+        suppressed.into_iter().collect()
+    }
+
+    /// Return an [`ErrorAggregator`] with all the errors added to the sampler.
+    pub fn into_aggregator(self) -> ErrorAggregator {
+        let mut aggregator = ErrorAggregator::default();
+        for shard in self.shards {
+            aggregator.merge(shard.into_inner().unwrap().aggregator);
+        }
+
+        aggregator
+    }
+}
+
+#[cfg(test)]
+mod tests;
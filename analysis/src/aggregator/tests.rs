@@ -0,0 +1,260 @@
+use super::*;
+use alpha_g_detector::padwing::TryPwbPacketFromChunksError;
+use alpha_g_physics::TryMainEventFromDataBanksError;
+
+fn adc_unknown_version(found: u8) -> TryMainEventFromDataBanksError {
+    TryMainEventFromDataBanksError::BadAlpha16(TryAdcPacketFromSliceError::UnknownVersion { found })
+}
+
+fn pwb_unknown_version(found: u8) -> TryMainEventFromDataBanksError {
+    TryMainEventFromDataBanksError::BadPadwing(TryPwbPacketFromChunksError::BadPayload(
+        TryPwbPacketFromSliceError::UnknownVersion { found },
+    ))
+}
+
+#[test]
+fn error_aggregator_other_errors() {
+    let mut aggregator = ErrorAggregator::default();
+
+    assert_eq!(
+        aggregator.add(&TryMainEventFromDataBanksError::MissingTrgBank),
+        Emission::Other
+    );
+    assert_eq!(
+        aggregator.add(&TryAdcPacketFromSliceError::UnknownType { found: 1 }),
+        Emission::Other
+    );
+    assert_eq!(
+        aggregator.add(&TryMainEventFromDataBanksError::MissingTrgBank),
+        Emission::Other
+    );
+    assert_eq!(aggregator.unknown_versions().count(), 0);
+}
+
+#[test]
+fn error_aggregator_mixed_errors() {
+    let mut aggregator = ErrorAggregator::default();
+
+    assert_eq!(
+        aggregator.add(&adc_unknown_version(4)),
+        Emission::Warn(UnknownVersion {
+            packet: PacketKind::Adc,
+            version: 4,
+            count: 1,
+        })
+    );
+    assert_eq!(aggregator.add(&adc_unknown_version(4)), Emission::Suppress);
+    assert_eq!(
+        aggregator.add(&TryMainEventFromDataBanksError::MissingTrgBank),
+        Emission::Other
+    );
+    // Same version, different packet type.
+    assert_eq!(
+        aggregator.add(&pwb_unknown_version(4)),
+        Emission::Warn(UnknownVersion {
+            packet: PacketKind::Pwb,
+            version: 4,
+            count: 1,
+        })
+    );
+    // Same packet type, different version.
+    assert_eq!(
+        aggregator.add(&adc_unknown_version(5)),
+        Emission::Warn(UnknownVersion {
+            packet: PacketKind::Adc,
+            version: 5,
+            count: 1,
+        })
+    );
+    assert_eq!(aggregator.add(&adc_unknown_version(4)), Emission::Suppress);
+    assert_eq!(aggregator.add(&pwb_unknown_version(4)), Emission::Suppress);
+    // Not wrapped in any other error.
+    assert_eq!(
+        aggregator.add(&TryPwbPacketFromSliceError::UnknownVersion { found: 4 }),
+        Emission::Suppress
+    );
+
+    assert_eq!(
+        aggregator.unknown_versions().collect::<Vec<_>>(),
+        [
+            UnknownVersion {
+                packet: PacketKind::Adc,
+                version: 4,
+                count: 3,
+            },
+            UnknownVersion {
+                packet: PacketKind::Adc,
+                version: 5,
+                count: 1,
+            },
+            UnknownVersion {
+                packet: PacketKind::Pwb,
+                version: 4,
+                count: 3,
+            },
+        ]
+    );
+}
+
+#[test]
+fn unknown_version_display() {
+    let unknown = UnknownVersion {
+        packet: PacketKind::Pwb,
+        version: 3,
+        count: 10,
+    };
+
+    assert_eq!(unknown.to_string(), "unknown PWB packet version `3`");
+}
+
+#[test]
+fn error_aggregator_other_errors_by_kind() {
+    let mut aggregator = ErrorAggregator::default();
+    aggregator.add(&TryMainEventFromDataBanksError::MissingTrgBank);
+    aggregator.add(&TryMainEventFromDataBanksError::BadAlpha16(
+        TryAdcPacketFromSliceError::UnknownType { found: 1 },
+    ));
+    // Same kind, different values.
+    aggregator.add(&TryMainEventFromDataBanksError::BadAlpha16(
+        TryAdcPacketFromSliceError::UnknownType { found: 2 },
+    ));
+    aggregator.add(&TryMainEventFromDataBanksError::MissingTrgBank);
+    aggregator.add(&adc_unknown_version(4));
+
+    assert_eq!(
+        aggregator.other_errors().collect::<Vec<_>>(),
+        [("BadAlpha16/UnknownType", 2), ("MissingTrgBank", 2)]
+    );
+}
+
+#[test]
+fn error_kind_of_unknown_version() {
+    assert_eq!(
+        error_kind(&pwb_unknown_version(3)),
+        "BadPadwing/BadPayload/UnknownVersion"
+    );
+    assert_eq!(
+        error_kind(&TryMainEventFromDataBanksError::MissingTrgBank),
+        "MissingTrgBank"
+    );
+}
+
+#[test]
+fn error_aggregator_merge() {
+    let mut first = ErrorAggregator::default();
+    first.add(&adc_unknown_version(4));
+    first.add(&TryMainEventFromDataBanksError::MissingTrgBank);
+    let mut second = ErrorAggregator::default();
+    second.add(&adc_unknown_version(4));
+    second.add(&pwb_unknown_version(3));
+    second.add(&TryMainEventFromDataBanksError::MissingTrgBank);
+    first.merge(second);
+
+    assert_eq!(
+        first.unknown_versions().collect::<Vec<_>>(),
+        [
+            UnknownVersion {
+                packet: PacketKind::Adc,
+                version: 4,
+                count: 2,
+            },
+            UnknownVersion {
+                packet: PacketKind::Pwb,
+                version: 3,
+                count: 1,
+            },
+        ]
+    );
+    assert_eq!(
+        first.other_errors().collect::<Vec<_>>(),
+        [("MissingTrgBank", 2)]
+    );
+}
+
+#[test]
+fn error_sampler_limit() {
+    let sampler = ErrorSampler::new(Some(2));
+    let missing_trg = TryMainEventFromDataBanksError::MissingTrgBank;
+
+    assert_eq!(sampler.add(&missing_trg), Sample::Report);
+    assert_eq!(sampler.add(&missing_trg), Sample::Report);
+    assert_eq!(sampler.add(&missing_trg), Sample::Suppress);
+    assert_eq!(sampler.add(&missing_trg), Sample::Suppress);
+    // Independent of other kinds.
+    assert_eq!(
+        sampler.add(&TryAdcPacketFromSliceError::UnknownType { found: 1 }),
+        Sample::Report
+    );
+    // Unknown versions are always reported only once.
+    assert_eq!(
+        sampler.add(&adc_unknown_version(4)),
+        Sample::Warn(UnknownVersion {
+            packet: PacketKind::Adc,
+            version: 4,
+            count: 1,
+        })
+    );
+    assert_eq!(sampler.add(&adc_unknown_version(4)), Sample::Suppress);
+
+    assert_eq!(
+        sampler.take_suppressed(),
+        [(String::from("MissingTrgBank"), 2)]
+    );
+    assert!(sampler.take_suppressed().is_empty());
+    assert_eq!(sampler.add(&missing_trg), Sample::Suppress);
+    assert_eq!(
+        sampler.take_suppressed(),
+        [(String::from("MissingTrgBank"), 1)]
+    );
+
+    let aggregator = sampler.into_aggregator();
+    assert_eq!(
+        aggregator.other_errors().collect::<Vec<_>>(),
+        [("MissingTrgBank", 5), ("UnknownType", 1)]
+    );
+    assert_eq!(aggregator.unknown_versions().next().unwrap().count, 2);
+}
+
+#[test]
+fn error_sampler_no_limit() {
+    let sampler = ErrorSampler::new(None);
+    for _ in 0..100 {
+        assert_eq!(
+            sampler.add(&TryMainEventFromDataBanksError::MissingTrgBank),
+            Sample::Report
+        );
+    }
+    assert!(sampler.take_suppressed().is_empty());
+}
+
+#[test]
+fn error_sampler_silent() {
+    let sampler = ErrorSampler::new(Some(0));
+    assert_eq!(
+        sampler.add(&TryMainEventFromDataBanksError::MissingTrgBank),
+        Sample::Suppress
+    );
+    assert!(matches!(
+        sampler.add(&pwb_unknown_version(4)),
+        Sample::Warn(_)
+    ));
+}
+
+#[test]
+fn error_sampler_parallel() {
+    use rayon::prelude::*;
+
+    let sampler = ErrorSampler::new(Some(10));
+    let reported = (0..100_000)
+        .into_par_iter()
+        .filter(|_| sampler.add(&TryMainEventFromDataBanksError::MissingTrgBank) == Sample::Report)
+        .count();
+    let suppressed = sampler.take_suppressed();
+
+    assert_eq!(reported, 10);
+    assert_eq!(suppressed, [(String::from("MissingTrgBank"), 100_000 - 10)]);
+    assert_eq!(
+        sampler.into_aggregator().other_errors().collect::<Vec<_>>(),
+        [("MissingTrgBank", 100_000)]
+    );
+}
@@ -0,0 +1,397 @@
+use crate::scalers::ScalersRow;
+use crate::statistics::median;
+use std::collections::VecDeque;
+use std::ops::Range;
+
+/// Settings to find anomalies in the event rate of a run (see
+/// [`find_anomalies`]).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AnomalySettings {
+    /// Longest time (in seconds) between consecutive events that is not
+    /// reported as a silent period.
+    pub max_silence: f64,
+    /// Width (in seconds) of the bins in which the rates are computed.
+    pub bin_width: f64,
+    /// Number of previous (normal) bins used as the baseline of a bin.
+    pub window: usize,
+    /// Number of standard deviations from the baseline at which a bin is
+    /// anomalous.
+    pub threshold: f64,
+}
+
+impl Default for AnomalySettings {
+    fn default() -> Self {
+        Self {
+            max_silence: 5.0,
+            bin_width: 1.0,
+            window: 60,
+            threshold: 5.0,
+        }
+    }
+}
+
+/// Kind of [`Anomaly`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AnomalyKind {
+    /// No events at all.
+    Silence,
+    /// Average event rate (in events per second) far away from the rate of the
+    /// baseline.
+    Rate { rate: f64, baseline: f64 },
+    /// Average fraction of input triggers that didn't make it to the TRG
+    /// output far above the fraction of the baseline.
+    ///
+    /// This is not an exact dead time (e.g. it includes triggers rejected by
+    /// the drift veto and the scaledown), but a spike means that triggers are
+    /// being lost.
+    DeadTime { fraction: f64, baseline: f64 },
+}
+
+/// Anomalous period of a run.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Anomaly {
+    /// Kind of anomaly.
+    pub kind: AnomalyKind,
+    /// Start time (in seconds) of the period. Same clock as the `trg_time` of
+    /// the events.
+    pub start: f64,
+    /// Duration (in seconds) of the period.
+    pub duration: f64,
+    /// Serial number of the last event before the period.
+    pub before: Option<u32>,
+    /// Serial number of the first event after the period.
+    pub after: Option<u32>,
+}
+
+/// Find all the silent periods, rate excursions, and dead time spikes in the
+/// TRG scalers of a run (see [`TrgScalers`](crate::TrgScalers)). Anomalies
+/// are sorted by start time.
+///
+/// A bin is anomalous if it is more than `threshold` standard deviations away
+/// from the mean of the previous `window` bins that were not anomalous.
+/// Consecutive anomalous bins are merged into a single anomaly. Bins are not
+/// tested until there is a full window, and the last (incomplete) bin is never
+/// tested. Bins within a silent period are not tested for rate excursions.
+pub fn find_anomalies(rows: &[ScalersRow], settings: &AnomalySettings) -> Vec<Anomaly> {
+    let events: Vec<_> = rows
+        .iter()
+        .filter_map(|row| Some((row.serial_number, row.trg_time?)))
+        .collect();
+    let Some(&(_, t0)) = events.first() else {
+        return Vec::new();
+    };
+
+    let silences = silent_periods(&events, settings.max_silence);
+    let mut rates: Vec<_> = event_counts(&events, settings.bin_width)
+        .into_iter()
+        .map(|count| Some(count as f64 / settings.bin_width))
+        .collect();
+    for silence in &silences {
+        let first = ((silence.start - t0) / settings.bin_width).ceil() as usize;
+        let end = ((silence.start + silence.duration - t0) / settings.bin_width) as usize;
+        for rate in rates.iter_mut().take(end).skip(first) {
+            *rate = None;
+        }
+    }
+    // Poisson fluctuations of the counts in a bin.
+    let min_sigma = |rate: f64| (rate / settings.bin_width).sqrt();
+    let rate_excursions = excursions(&rates, settings.window, settings.threshold, min_sigma)
+        .into_iter()
+        .map(|(bins, baseline)| {
+            let rate = mean(rates[bins.clone()].iter().flatten().copied());
+            let kind = AnomalyKind::Rate { rate, baseline };
+            anomaly_in_bins(kind, &events, t0, settings.bin_width, bins)
+        });
+
+    let fractions = dead_time_fractions(rows, t0, settings.bin_width);
+    let dead_time_spikes = excursions(&fractions, settings.window, settings.threshold, |_| {
+        MIN_DEAD_TIME_SIGMA
+    })
+    .into_iter()
+    .filter_map(|(bins, baseline)| {
+        let fraction = mean(fractions[bins.clone()].iter().flatten().copied());
+        let kind = AnomalyKind::DeadTime { fraction, baseline };
+        (fraction > baseline).then(|| anomaly_in_bins(kind, &events, t0, settings.bin_width, bins))
+    });
+
+    let mut anomalies: Vec<_> = silences
+        .into_iter()
+        .chain(rate_excursions)
+        .chain(dead_time_spikes)
+        .collect();
+    anomalies.sort_by(|a, b| a.start.total_cmp(&b.start));
+    anomalies
+}
+
+/// Settings to find drops in the rate of single channels (see
+/// [`find_rate_drops`]).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RateDropSettings {
+    /// Number of previous (normal) bins used as the baseline of a channel.
+    pub window: usize,
+    /// A bin is a drop if its rate is more than this factor below the baseline
+    /// of its channel.
+    pub factor: f64,
+    /// Largest relative change of the other channels (median of their rates
+    /// relative to their own baselines) at which a drop is still reported.
+    pub max_common_change: f64,
+}
+
+impl Default for RateDropSettings {
+    fn default() -> Self {
+        Self {
+            window: 10,
+            factor: 2.0,
+            max_common_change: 0.2,
+        }
+    }
+}
+
+/// Period in which the rate of a single channel dropped while the rest of the
+/// channels stayed constant.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RateDrop {
+    /// Index of the channel.
+    pub channel: usize,
+    /// Bins of the period.
+    pub bins: Range<usize>,
+    /// Mean rate of the channel during the period.
+    pub rate: f64,
+    /// Baseline of the channel before the period.
+    pub baseline: f64,
+}
+
+/// Find the periods in which the rate of a single channel (e.g. the charge
+/// rate of a pad column) collapses relative to its own baseline, while the
+/// other channels don't change (e.g. a single HV sector tripping). Drops are
+/// sorted by their first bin, and then by channel.
+///
+/// `rates[channel][bin]` is the rate of each channel in bins of equal width;
+/// `None` bins (e.g. no data) are skipped. The baseline of a channel is the
+/// mean of its previous `window` bins that were not a drop, so a channel that
+/// never recovers is reported until the end of the run. A bin is a drop if it
+/// is more than `factor` times below the baseline of its channel, and the
+/// median of the rates of all the other channels relative to their baselines
+/// is within `max_common_change` of `1` (i.e. the whole detector changing
+/// together, e.g. the beam turning off, is not a drop). Bins are not tested
+/// until the channel has a full window, and channels with a zero baseline
+/// (e.g. dead) are never tested. Consecutive drop bins of the same channel
+/// are merged into a single drop.
+pub fn find_rate_drops(rates: &[Vec<Option<f64>>], settings: &RateDropSettings) -> Vec<RateDrop> {
+    let bins = rates.iter().map(Vec::len).max().unwrap_or(0);
+    let mut windows = vec![VecDeque::with_capacity(settings.window); rates.len()];
+    let mut drops: Vec<RateDrop> = Vec::new();
+    // Index in `drops` of the current (i.e. possibly still growing) drop of
+    // each channel.
+    let mut current = vec![None; rates.len()];
+    for bin in 0..bins {
+        let values: Vec<_> = rates
+            .iter()
+            .map(|channel| channel.get(bin).copied().flatten())
+            .collect();
+        let baselines: Vec<_> = windows
+            .iter()
+            .map(|window: &VecDeque<f64>| {
+                (settings.window > 0 && window.len() == settings.window)
+                    .then(|| mean(window.iter().copied()))
+            })
+            .collect();
+        // Rate of each channel relative to its baseline.
+        let ratios: Vec<_> = values
+            .iter()
+            .zip(&baselines)
+            .map(|(value, baseline)| match (value, baseline) {
+                (Some(value), Some(baseline)) if *baseline > 0.0 => Some(value / baseline),
+                _ => None,
+            })
+            .collect();
+
+        for (channel, ratio) in ratios.iter().enumerate() {
+            let Some(value) = values[channel] else {
+                continue;
+            };
+            let is_drop = ratio.is_some_and(|ratio| {
+                let others: Vec<_> = ratios
+                    .iter()
+                    .enumerate()
+                    .filter(|&(other, _)| other != channel)
+                    .filter_map(|(_, ratio)| *ratio)
+                    .collect();
+                !others.is_empty()
+                    && (median(others) - 1.0).abs() <= settings.max_common_change
+                    && ratio * settings.factor < 1.0
+            });
+            if !is_drop {
+                current[channel] = None;
+                let window = &mut windows[channel];
+                if settings.window > 0 && window.len() == settings.window {
+                    window.pop_front();
+                }
+                window.push_back(value);
+                continue;
+            }
+
+            match current[channel] {
+                Some(index) if drops[index].bins.end == bin => {
+                    let last = &mut drops[index];
+                    let n = last.bins.len() as f64;
+                    last.rate = (last.rate * n + value) / (n + 1.0);
+                    last.bins.end = bin + 1;
+                }
+                _ => {
+                    current[channel] = Some(drops.len());
+                    drops.push(RateDrop {
+                        channel,
+                        bins: bin..bin + 1,
+                        rate: value,
+                        baseline: baselines[channel].unwrap(),
+                    });
+                }
+            }
+        }
+    }
+
+    drops
+}
+
+// The fraction of lost triggers is (almost) constant during normal running.
+// Don't report tiny fluctuations as spikes.
+const MIN_DEAD_TIME_SIGMA: f64 = 0.01;
+
+fn mean(values: impl IntoIterator<Item = f64>) -> f64 {
+    let (sum, n) = values
+        .into_iter()
+        .fold((0.0, 0), |(sum, n), value| (sum + value, n + 1));
+    sum / n as f64
+}
+
+// Periods longer than `max_silence` between consecutive events. Events are
+// `(serial_number, time)` sorted by time.
+fn silent_periods(events: &[(u32, f64)], max_silence: f64) -> Vec<Anomaly> {
+    events
+        .windows(2)
+        .filter(|pair| pair[1].1 - pair[0].1 > max_silence)
+        .map(|pair| Anomaly {
+            kind: AnomalyKind::Silence,
+            start: pair[0].1,
+            duration: pair[1].1 - pair[0].1,
+            before: Some(pair[0].0),
+            after: Some(pair[1].0),
+        })
+        .collect()
+}
+
+// Number of events in each complete bin of `bin_width` seconds starting at
+// the first event.
+fn event_counts(events: &[(u32, f64)], bin_width: f64) -> Vec<usize> {
+    let Some((&(_, t0), &(_, t_last))) = events.first().zip(events.last()) else {
+        return Vec::new();
+    };
+    let mut counts = vec![0; ((t_last - t0) / bin_width) as usize];
+    for &(_, t) in events {
+        if let Some(count) = counts.get_mut(((t - t0) / bin_width) as usize) {
+            *count += 1;
+        }
+    }
+    counts
+}
+
+// Fraction of input triggers that are not in the output in each complete bin
+// of `bin_width` seconds starting at `t0`. Bins without input triggers (or
+// without counters) are `None`.
+fn dead_time_fractions(rows: &[ScalersRow], t0: f64, bin_width: f64) -> Vec<Option<f64>> {
+    let counters: Vec<_> = rows
+        .iter()
+        .filter_map(|row| Some((row.trg_time?, row.input?, row.output?)))
+        .collect();
+    let Some(&(t_last, _, _)) = counters.last() else {
+        return Vec::new();
+    };
+    let mut totals = vec![(0u64, 0u64); ((t_last - t0) / bin_width) as usize];
+    for pair in counters.windows(2) {
+        let ((_, input0, output0), (t, input1, output1)) = (pair[0], pair[1]);
+        if let Some((input, output)) = totals.get_mut(((t - t0) / bin_width) as usize) {
+            // All counters overflow together, so wrapping differences are fine.
+            *input += u64::from(input1.wrapping_sub(input0));
+            *output += u64::from(output1.wrapping_sub(output0));
+        }
+    }
+    totals
+        .into_iter()
+        .map(|(input, output)| (input > 0).then(|| 1.0 - output as f64 / input as f64))
+        .collect()
+}
+
+// Ranges of consecutive bins (and the mean of their baseline) that are more
+// than `threshold` standard deviations away from the mean of the previous
+// `window` normal bins. The standard deviation is never smaller than
+// `min_sigma(mean)`. `None` bins are skipped.
+fn excursions(
+    series: &[Option<f64>],
+    window: usize,
+    threshold: f64,
+    min_sigma: impl Fn(f64) -> f64,
+) -> Vec<(Range<usize>, f64)> {
+    let mut baseline = VecDeque::with_capacity(window);
+    // Also keep track of the direction of each excursion. A drop right after
+    // a burst (or vice versa) is a different anomaly.
+    let mut found: Vec<(Range<usize>, f64, bool)> = Vec::new();
+    for (index, value) in series.iter().enumerate() {
+        let Some(value) = *value else {
+            continue;
+        };
+        if window > 0 && baseline.len() == window {
+            let mean = mean(baseline.iter().copied());
+            let variance = baseline
+                .iter()
+                .map(|v| (v - mean) * (v - mean))
+                .sum::<f64>()
+                / (window as f64);
+            let sigma = variance.sqrt().max(min_sigma(mean));
+            if (value - mean).abs() > threshold * sigma {
+                let above = value > mean;
+                match found.last_mut() {
+                    Some((bins, _, last_above)) if bins.end == index && *last_above == above => {
+                        bins.end = index + 1
+                    }
+                    _ => found.push((index..index + 1, mean, above)),
+                }
+                continue;
+            }
+            baseline.pop_front();
+        }
+        baseline.push_back(value);
+    }
+
+    found
+        .into_iter()
+        .map(|(bins, mean, _)| (bins, mean))
+        .collect()
+}
+
+// Anomaly that spans the given bins.
+fn anomaly_in_bins(
+    kind: AnomalyKind,
+    events: &[(u32, f64)],
+    t0: f64,
+    bin_width: f64,
+    bins: Range<usize>,
+) -> Anomaly {
+    let start = t0 + bins.start as f64 * bin_width;
+    let end = t0 + bins.end as f64 * bin_width;
+    let first_inside = events.partition_point(|&(_, t)| t < start);
+    let first_after = events.partition_point(|&(_, t)| t < end);
+
+    Anomaly {
+        kind,
+        start,
+        duration: end - start,
+        before: first_inside.checked_sub(1).map(|index| events[index].0),
+        after: events
+            .get(first_after)
+            .map(|&(serial_number, _)| serial_number),
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,280 @@
+use super::*;
+
+#[test]
+fn silent_periods_gap() {
+    let events = [(0, 0.0), (1, 1.0), (2, 7.5), (3, 8.0), (4, 20.0)];
+
+    assert_eq!(
+        silent_periods(&events, 5.0),
+        [
+            Anomaly {
+                kind: AnomalyKind::Silence,
+                start: 1.0,
+                duration: 6.5,
+                before: Some(1),
+                after: Some(2),
+            },
+            Anomaly {
+                kind: AnomalyKind::Silence,
+                start: 8.0,
+                duration: 12.0,
+                before: Some(3),
+                after: Some(4),
+            },
+        ]
+    );
+    assert!(silent_periods(&events, 12.0).is_empty());
+    assert!(silent_periods(&[], 1.0).is_empty());
+}
+
+#[test]
+fn event_counts_complete_bins() {
+    let events = [(0, 10.0), (1, 10.5), (2, 11.0), (3, 12.9)];
+
+    // The last bin (starting at 12.0) is incomplete.
+    assert_eq!(event_counts(&events, 1.0), [2, 1]);
+    assert_eq!(event_counts(&events, 2.0), [3]);
+    assert!(event_counts(&events[..1], 1.0).is_empty());
+}
+
+#[test]
+fn excursions_burst_and_drop() {
+    let series: Vec<_> = [10.0, 10.0, 10.0, 10.0, 10.0, 50.0, 50.0, 10.0, 0.0]
+        .into_iter()
+        .map(Some)
+        .chain([None, Some(10.0)])
+        .collect();
+
+    assert_eq!(
+        excursions(&series, 3, 3.0, |_| 1.0),
+        [(5..7, 10.0), (8..9, 10.0)]
+    );
+    // Not enough bins for a full baseline.
+    assert!(excursions(&series[..5], 10, 3.0, |_| 1.0).is_empty());
+}
+
+#[test]
+fn excursions_direction_change() {
+    let series = [10.0, 10.0, 10.0, 50.0, 0.0].map(Some);
+
+    assert_eq!(
+        excursions(&series, 3, 3.0, |_| 1.0),
+        [(3..4, 10.0), (4..5, 10.0)]
+    );
+}
+
+#[test]
+fn excursions_baseline_excludes_anomalies() {
+    // A long burst never becomes the baseline.
+    let mut series = vec![Some(10.0); 5];
+    series.extend(vec![Some(50.0); 20]);
+
+    assert_eq!(excursions(&series, 3, 3.0, |_| 1.0), [(5..25, 10.0)]);
+}
+
+#[test]
+fn dead_time_fractions_bins() {
+    let row = |trg_time, input, output| ScalersRow {
+        trg_time: Some(trg_time),
+        input: Some(input),
+        output: Some(output),
+        ..Default::default()
+    };
+    let rows = [
+        row(0.0, u32::MAX, 0),
+        row(0.5, 1, 1),
+        row(1.5, 11, 2),
+        ScalersRow::default(),
+        row(3.0, 11, 2),
+    ];
+
+    assert_eq!(
+        dead_time_fractions(&rows, 0.0, 1.0),
+        [Some(0.5), Some(0.9), None]
+    );
+}
+
+// Synthetic run at 100 Hz for 200 seconds with a silent period, a burst, and
+// a dead time spike. Every event adds 2 input triggers and 1 output trigger,
+// except during the dead time spike (10 input triggers).
+fn synthetic_run() -> Vec<ScalersRow> {
+    let mut times: Vec<f64> = (0..20000)
+        .map(|i| f64::from(i) / 100.0)
+        .filter(|t| !(100.0..110.0).contains(t))
+        .collect();
+    times.extend((0..1800).map(|i| 150.0 + f64::from(i) / 900.0));
+    times.sort_by(f64::total_cmp);
+
+    let (mut input, mut output) = (0u32, 0u32);
+    times
+        .into_iter()
+        .zip(0..)
+        .map(|(t, serial_number)| {
+            input += if (170.0..173.0).contains(&t) { 10 } else { 2 };
+            output += 1;
+            ScalersRow {
+                serial_number,
+                trg_time: Some(t),
+                input: Some(input),
+                output: Some(output),
+                ..Default::default()
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn find_anomalies_synthetic_run() {
+    let rows = synthetic_run();
+    let serial_number_at = |t: f64| {
+        rows.iter()
+            .find(|row| row.trg_time == Some(t))
+            .unwrap()
+            .serial_number
+    };
+    let anomalies = find_anomalies(&rows, &AnomalySettings::default());
+
+    assert_eq!(anomalies.len(), 3);
+
+    assert_eq!(anomalies[0].kind, AnomalyKind::Silence);
+    assert_eq!(anomalies[0].start, 99.99);
+    assert!((anomalies[0].duration - 10.01).abs() < 1e-9);
+    assert_eq!(anomalies[0].before, Some(serial_number_at(99.99)));
+    assert_eq!(anomalies[0].after, Some(serial_number_at(110.0)));
+
+    let AnomalyKind::Rate { rate, baseline } = anomalies[1].kind else {
+        panic!("expected a rate excursion, found {:?}", anomalies[1]);
+    };
+    assert!((rate - 1000.0).abs() < 2.0);
+    assert!((baseline - 100.0).abs() < 1.0);
+    assert_eq!((anomalies[1].start, anomalies[1].duration), (150.0, 2.0));
+    assert_eq!(anomalies[1].before, Some(serial_number_at(149.99)));
+    assert_eq!(anomalies[1].after, Some(serial_number_at(152.0)));
+
+    let AnomalyKind::DeadTime { fraction, baseline } = anomalies[2].kind else {
+        panic!("expected a dead time spike, found {:?}", anomalies[2]);
+    };
+    assert!((fraction - 0.9).abs() < 0.01);
+    assert!((baseline - 0.5).abs() < 1e-9);
+    assert_eq!((anomalies[2].start, anomalies[2].duration), (170.0, 3.0));
+}
+
+#[test]
+fn find_anomalies_normal_run() {
+    let rows: Vec<_> = synthetic_run()
+        .into_iter()
+        .filter(|row| row.trg_time < Some(100.0))
+        .map(|row| ScalersRow { input: None, ..row })
+        .collect();
+
+    assert!(find_anomalies(&rows, &AnomalySettings::default()).is_empty());
+    assert!(find_anomalies(&[], &AnomalySettings::default()).is_empty());
+}
+
+// Charge rate of 32 columns in 100 bins, with a small deterministic
+// fluctuation. `step(channel, bin)` scales the rate of a single bin.
+fn column_rates(step: impl Fn(usize, usize) -> f64) -> Vec<Vec<Option<f64>>> {
+    (0..32)
+        .map(|channel| {
+            (0..100)
+                .map(|bin| {
+                    let noise = ((channel * 7 + bin * 13) % 11) as f64 - 5.0;
+                    Some((1000.0 + noise) * step(channel, bin))
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[test]
+fn find_rate_drops_injected_step() {
+    let rates = column_rates(|channel, bin| {
+        if channel == 5 && (40..60).contains(&bin) {
+            0.1
+        } else {
+            1.0
+        }
+    });
+    let drops = find_rate_drops(&rates, &RateDropSettings::default());
+
+    assert_eq!(drops.len(), 1);
+    assert_eq!(drops[0].channel, 5);
+    assert_eq!(drops[0].bins, 40..60);
+    assert!((drops[0].rate - 100.0).abs() < 1.0);
+    assert!((drops[0].baseline - 1000.0).abs() < 5.0);
+}
+
+#[test]
+fn find_rate_drops_never_recovers() {
+    let rates = column_rates(|channel, bin| {
+        if (channel == 3 || channel == 20) && bin >= 70 {
+            0.0
+        } else {
+            1.0
+        }
+    });
+    let drops = find_rate_drops(&rates, &RateDropSettings::default());
+
+    assert_eq!(drops.len(), 2);
+    assert_eq!((drops[0].channel, drops[0].bins.clone()), (3, 70..100));
+    assert_eq!((drops[1].channel, drops[1].bins.clone()), (20, 70..100));
+    assert_eq!(drops[0].rate, 0.0);
+}
+
+#[test]
+fn find_rate_drops_small_drop() {
+    let rates = column_rates(
+        |channel, bin| {
+            if channel == 5 && bin >= 40 {
+                0.6
+            } else {
+                1.0
+            }
+        },
+    );
+
+    assert!(find_rate_drops(&rates, &RateDropSettings::default()).is_empty());
+}
+
+#[test]
+fn find_rate_drops_common_change() {
+    // All the columns drop together (e.g. no beam); not a sector trip.
+    let rates = column_rates(|_, bin| if (40..60).contains(&bin) { 0.1 } else { 1.0 });
+
+    assert!(find_rate_drops(&rates, &RateDropSettings::default()).is_empty());
+}
+
+#[test]
+fn find_rate_drops_needs_full_window() {
+    // The drop starts before there is a baseline.
+    let rates = column_rates(
+        |channel, bin| {
+            if channel == 5 && bin < 20 {
+                0.1
+            } else {
+                1.0
+            }
+        },
+    );
+
+    assert!(find_rate_drops(&rates, &RateDropSettings::default()).is_empty());
+    assert!(find_rate_drops(&[], &RateDropSettings::default()).is_empty());
+}
+
+#[test]
+fn find_rate_drops_missing_bins() {
+    let mut rates = column_rates(|channel, bin| {
+        if channel == 5 && (40..60).contains(&bin) {
+            0.1
+        } else {
+            1.0
+        }
+    });
+    // A gap in the middle of the drop splits it in two.
+    rates[5][50] = None;
+    let drops = find_rate_drops(&rates, &RateDropSettings::default());
+
+    assert_eq!(drops.len(), 2);
+    assert_eq!(drops[0].bins, 40..50);
+    assert_eq!(drops[1].bins, 51..60);
+}
@@ -0,0 +1,159 @@
+use anyhow::{bail, ensure, Context, Result};
+use clap::Parser;
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+#[derive(Parser)]
+#[command(version)]
+/// Replace the rows of a CSV output with the rows of a patch (e.g. from `--only-errors`)
+struct Args {
+    /// CSV patch with the reprocessed rows
+    patch: PathBuf,
+    /// Original CSV output (same program and columns as the patch)
+    original: PathBuf,
+    /// Write the merged output to `MERGED.csv` (or to stdout if `-`)
+    merged: PathBuf,
+}
+
+// CSV output with all its comment lines. Rows are kept as is, only the serial
+// number is parsed.
+#[derive(Clone, Debug, PartialEq)]
+struct Table {
+    comments: Vec<String>,
+    header: csv::StringRecord,
+    records: Vec<csv::StringRecord>,
+    serial_number: usize,
+}
+
+impl Table {
+    fn new<R: Read>(reader: R) -> Result<Self> {
+        let (comments, mut reader) = alpha_g_analysis::cli::read_commented_csv(reader)?;
+        let header = reader
+            .headers()
+            .context("failed to read csv header")?
+            .clone();
+        let serial_number = header
+            .iter()
+            .position(|field| field == "serial_number")
+            .context("missing `serial_number` column")?;
+        let records = reader
+            .into_records()
+            .collect::<Result<_, _>>()
+            .context("failed to read csv row")?;
+
+        Ok(Self {
+            comments,
+            header,
+            records,
+            serial_number,
+        })
+    }
+
+    // Return the rows indexed by their serial number.
+    fn rows(&self) -> Result<BTreeMap<u32, &csv::StringRecord>> {
+        let mut rows = BTreeMap::new();
+        for record in &self.records {
+            let serial_number: u32 = record
+                .get(self.serial_number)
+                .unwrap_or_default()
+                .parse()
+                .context("bad serial_number")?;
+            if rows.insert(serial_number, record).is_some() {
+                bail!("duplicate serial_number `{serial_number}`");
+            }
+        }
+
+        Ok(rows)
+    }
+}
+
+// Number of rows of the original output affected by a patch.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct PatchSummary {
+    replaced: usize,
+    added: usize,
+}
+
+// Return the rows of `original` with the rows of `patch` replacing the ones
+// with the same serial number (or added if they were missing), sorted by
+// serial number.
+fn apply_patch(patch: &Table, original: &Table) -> Result<(Vec<csv::StringRecord>, PatchSummary)> {
+    ensure!(
+        patch.header == original.header,
+        "column mismatch between patch and original"
+    );
+    let mut rows = original.rows().context("bad original")?;
+    let mut summary = PatchSummary::default();
+    for (serial_number, record) in patch.rows().context("bad patch")? {
+        match rows.insert(serial_number, record) {
+            Some(_) => summary.replaced += 1,
+            None => summary.added += 1,
+        }
+    }
+
+    Ok((rows.into_values().cloned().collect(), summary))
+}
+
+// Header lines of the inputs that still apply to the merged rows: the run
+// timing of the original, and the warnings of both (each only once).
+fn merged_comments<'a>(patch: &'a Table, original: &'a Table) -> Vec<&'a str> {
+    let (timing, warnings) = alpha_g_analysis::cli::carried_over_comments(&original.comments);
+    let mut comments: Vec<_> = timing.into_iter().chain(warnings).collect();
+    for warning in alpha_g_analysis::cli::carried_over_comments(&patch.comments).1 {
+        if !comments.contains(&warning) {
+            comments.push(warning);
+        }
+    }
+
+    comments
+}
+
+fn open(path: &Path) -> Result<Table> {
+    let file = alpha_g_analysis::cli::open_input(path)?;
+    Table::new(file).with_context(|| format!("failed to read `{}`", path.display()))
+}
+
+fn main() {
+    alpha_g_analysis::cli::exit_on_error(run());
+}
+
+fn run() -> Result<()> {
+    let args: Args = alpha_g_analysis::cli::parse(env!("CARGO_BIN_NAME"));
+
+    let patch = open(&args.patch)?;
+    let original = open(&args.original)?;
+    let (records, summary) = apply_patch(&patch, &original)?;
+    eprintln!(
+        "Replaced `{}` rows and added `{}` rows",
+        summary.replaced, summary.added
+    );
+
+    let mut wtr = alpha_g_analysis::cli::create_output(&args.merged, "csv")?;
+    wtr.write_all(
+        format!(
+            "# {} {}\n# {}\n",
+            env!("CARGO_PKG_NAME"),
+            env!("CARGO_PKG_VERSION"),
+            std::env::args().collect::<Vec<_>>().join(" ")
+        )
+        .as_bytes(),
+    )
+    .context("failed to write csv header")?;
+    for comment in merged_comments(&patch, &original) {
+        writeln!(wtr, "{comment}").context("failed to write csv header")?;
+    }
+    let mut wtr = csv::Writer::from_writer(wtr);
+    wtr.write_record(&original.header)
+        .context("failed to write csv header")?;
+    for record in &records {
+        wtr.write_record(record)
+            .context("failed to write csv row")?;
+    }
+    wtr.flush().context("failed to flush csv data")?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,116 @@
+use super::*;
+
+const ORIGINAL: &str = "# alpha-g-analysis 0.5.7
+# alpha-g-vertices run.mid
+serial_number,trg_time,reconstructed_x,reconstructed_y,reconstructed_z
+1,0.1,0.01,0.02,0.5
+2,0.2,,,
+4,0.4,0.01,0.02,-0.5
+";
+
+const PATCH: &str = "# alpha-g-analysis 0.5.8
+# alpha-g-vertices run.mid --only-errors errors.json
+# WARNING: only the events in errors.json
+serial_number,trg_time,reconstructed_x,reconstructed_y,reconstructed_z
+2,0.2,0.03,0.04,0.1
+3,0.3,,,
+";
+
+fn table(contents: &str) -> Table {
+    Table::new(contents.as_bytes()).unwrap()
+}
+
+fn serial_numbers(records: &[csv::StringRecord]) -> Vec<&str> {
+    records.iter().map(|record| &record[0]).collect()
+}
+
+#[test]
+fn table_comments_and_rows() {
+    let patch = table(PATCH);
+    assert_eq!(patch.comments.len(), 3);
+    assert_eq!(patch.serial_number, 0);
+    assert_eq!(
+        patch.rows().unwrap().keys().copied().collect::<Vec<_>>(),
+        [2, 3]
+    );
+
+    assert!(Table::new("trg_time\n0.1\n".as_bytes()).is_err());
+    assert!(table("serial_number\n1\n1\n").rows().is_err());
+}
+
+#[test]
+fn apply_patch_replaced_row() {
+    let (records, summary) = apply_patch(&table(PATCH), &table(ORIGINAL)).unwrap();
+    assert_eq!(summary.replaced, 1);
+    assert_eq!(serial_numbers(&records), ["1", "2", "3", "4"]);
+    assert_eq!(
+        records[1],
+        csv::StringRecord::from(vec!["2", "0.2", "0.03", "0.04", "0.1"])
+    );
+    // Rows not in the patch are kept as is.
+    assert_eq!(
+        records[0],
+        csv::StringRecord::from(vec!["1", "0.1", "0.01", "0.02", "0.5"])
+    );
+}
+
+#[test]
+fn apply_patch_added_row() {
+    let (records, summary) = apply_patch(&table(PATCH), &table(ORIGINAL)).unwrap();
+    assert_eq!(summary.added, 1);
+    assert_eq!(
+        records[2],
+        csv::StringRecord::from(vec!["3", "0.3", "", "", ""])
+    );
+}
+
+#[test]
+fn apply_patch_schema_mismatch() {
+    let extra_columns = "# alpha-g-analysis 0.5.8
+serial_number,trg_time,reconstructed_x,reconstructed_y,reconstructed_z,late_fraction
+2,0.2,0.03,0.04,0.1,0.5
+";
+    assert!(apply_patch(&table(extra_columns), &table(ORIGINAL)).is_err());
+
+    let reordered = "serial_number,reconstructed_x,reconstructed_y,reconstructed_z,trg_time
+2,0.03,0.04,0.1,0.2
+";
+    assert!(apply_patch(&table(reordered), &table(ORIGINAL)).is_err());
+}
+
+#[test]
+fn merged_comments_timing_and_warnings() {
+    let original = "# alpha-g-analysis 0.5.8
+# alpha-g-vertices run.mid
+# run start 100, end 110, duration 10 s, average main event rate 0.300 Hz
+# input run.mid xxh64:0123456789abcdef
+# WARNING: calibration from run 4000
+serial_number
+1
+";
+    let patch = "# alpha-g-analysis 0.5.8
+# alpha-g-vertices run.mid --only-errors errors.json
+# run start 100, end 110, duration 10 s, average main event rate 0.100 Hz
+# input run.mid xxh64:0123456789abcdef
+# WARNING: calibration from run 4000
+# WARNING: only the events in errors.json
+serial_number
+2
+";
+    assert_eq!(
+        merged_comments(&table(patch), &table(original)),
+        [
+            "# run start 100, end 110, duration 10 s, average main event rate 0.300 Hz",
+            "# WARNING: calibration from run 4000",
+            "# WARNING: only the events in errors.json",
+        ]
+    );
+    // Without timing in the original, none is taken from the patch.
+    assert_eq!(
+        merged_comments(&table(patch), &table(ORIGINAL)),
+        [
+            "# WARNING: calibration from run 4000",
+            "# WARNING: only the events in errors.json",
+        ]
+    );
+}
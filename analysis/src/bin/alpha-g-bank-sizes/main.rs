@@ -0,0 +1,236 @@
+use alpha_g_analysis::cli::{DeferredCsvWriter, RunTiming};
+use alpha_g_analysis::EventCensus;
+use alpha_g_detector::midas::{BankKind, EventId};
+use anyhow::{bail, ensure, Context, Result};
+use clap::Parser;
+use indicatif::{ProgressBar, ProgressStyle};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+#[derive(Parser)]
+#[command(version)]
+/// Extract the size of the data banks in all main events for a single run
+struct Args {
+    /// MIDAS files (or directories with them) from the run you want to inspect
+    #[arg(required = true)]
+    files: Vec<PathBuf>,
+    /// Also search for MIDAS files in subdirectories of the input directories
+    #[arg(long)]
+    recursive: bool,
+    /// Write the output to `OUTPUT.csv` (or to stdout if `-`) [default:
+    /// `R<run_number>_bank_sizes.csv`]
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    /// Aggregate events into time bins of this width (in seconds) instead of
+    /// writing one row per event
+    #[arg(long, value_name = "SECONDS", value_parser = clap::value_parser!(u32).range(1..))]
+    bin: Option<u32>,
+}
+
+// Number of banks and bytes per subsystem. Only the bank names and the length
+// of the data slices are used; no packet is ever parsed.
+#[derive(Clone, Copy, Debug, Default)]
+struct Sizes {
+    n_adc_banks: usize,
+    adc_bytes: usize,
+    n_pwb_banks: usize,
+    pwb_bytes: usize,
+    trg_bytes: usize,
+    other_bytes: usize,
+}
+
+impl Sizes {
+    fn add_bank(&mut self, name: &str, bytes: usize) {
+        match BankKind::from(name) {
+            BankKind::Alpha16 => {
+                self.n_adc_banks += 1;
+                self.adc_bytes += bytes;
+            }
+            BankKind::Padwing => {
+                self.n_pwb_banks += 1;
+                self.pwb_bytes += bytes;
+            }
+            BankKind::Trg => self.trg_bytes += bytes,
+            BankKind::Other => self.other_bytes += bytes,
+        }
+    }
+
+    fn merge(&mut self, other: &Self) {
+        self.n_adc_banks += other.n_adc_banks;
+        self.adc_bytes += other.adc_bytes;
+        self.n_pwb_banks += other.n_pwb_banks;
+        self.pwb_bytes += other.pwb_bytes;
+        self.trg_bytes += other.trg_bytes;
+        self.other_bytes += other.other_bytes;
+    }
+}
+
+#[derive(Debug, serde::Serialize)]
+struct EventRow {
+    serial_number: u32,
+    midas_timestamp: u32,
+    n_adc_banks: usize,
+    adc_bytes: usize,
+    n_pwb_banks: usize,
+    pwb_bytes: usize,
+    trg_bytes: usize,
+    other_bytes: usize,
+}
+
+impl EventRow {
+    fn new(serial_number: u32, midas_timestamp: u32, sizes: Sizes) -> Self {
+        Self {
+            serial_number,
+            midas_timestamp,
+            n_adc_banks: sizes.n_adc_banks,
+            adc_bytes: sizes.adc_bytes,
+            n_pwb_banks: sizes.n_pwb_banks,
+            pwb_bytes: sizes.pwb_bytes,
+            trg_bytes: sizes.trg_bytes,
+            other_bytes: sizes.other_bytes,
+        }
+    }
+}
+
+#[derive(Debug, serde::Serialize)]
+struct BinRow {
+    bin_start: u32,
+    n_events: usize,
+    n_adc_banks: usize,
+    adc_bytes: usize,
+    n_pwb_banks: usize,
+    pwb_bytes: usize,
+    trg_bytes: usize,
+    other_bytes: usize,
+}
+
+impl BinRow {
+    fn new(bin_start: u32, n_events: usize, sizes: Sizes) -> Self {
+        Self {
+            bin_start,
+            n_events,
+            n_adc_banks: sizes.n_adc_banks,
+            adc_bytes: sizes.adc_bytes,
+            n_pwb_banks: sizes.n_pwb_banks,
+            pwb_bytes: sizes.pwb_bytes,
+            trg_bytes: sizes.trg_bytes,
+            other_bytes: sizes.other_bytes,
+        }
+    }
+}
+
+fn main() {
+    alpha_g_analysis::cli::exit_on_error(run());
+}
+
+fn run() -> Result<()> {
+    let args: Args = alpha_g_analysis::cli::parse(env!("CARGO_BIN_NAME"));
+    let files = alpha_g_analysis::expand_run_files(args.files, args.recursive)?;
+    let (run_number, files) = alpha_g_analysis::sort_run_files(files, None)?;
+
+    let bar = ProgressBar::new(files.len().try_into().unwrap()).with_style(
+        ProgressStyle::with_template("  Processing [{bar:25}] {percent}%,  ETA: {eta}")
+            .unwrap()
+            .progress_chars("=> "),
+    );
+    bar.tick();
+
+    let output = args
+        .output
+        .unwrap_or_else(|| PathBuf::from(format!("R{run_number}_bank_sizes")));
+    // Rows are written as the events are read, but the header (i.e. the run
+    // timing) is only known after reading all the files.
+    let mut wtr = DeferredCsvWriter::new(
+        &output,
+        &format!(
+            "# {} {}\n# {}\n",
+            env!("CARGO_PKG_NAME"),
+            env!("CARGO_PKG_VERSION"),
+            std::env::args().collect::<Vec<_>>().join(" ")
+        ),
+    );
+    // Keyed by the (absolute) MIDAS timestamp at the start of the bin. Bins
+    // are only written at the end, but there are far fewer bins than events.
+    let mut bins: BTreeMap<u32, (usize, Sizes)> = BTreeMap::new();
+    let mut total_events = 0;
+    let mut totals = Sizes::default();
+    let mut census = EventCensus::default();
+    let mut previous_final_timestamp = None;
+    let mut run_start = None;
+    for file in files {
+        let contents = alpha_g_analysis::read(&file)?;
+        let file_view = midasio::FileView::try_from(&contents[..])
+            .with_context(|| format!("failed to parse `{}`", file.display()))?;
+        run_start.get_or_insert(file_view.initial_timestamp());
+        if let Some(previous_final_timestamp) = previous_final_timestamp {
+            match file_view
+                .initial_timestamp()
+                .checked_sub(previous_final_timestamp)
+            {
+                Some(gap) => ensure!(gap <= 1, "missing file before `{}`", file.display()),
+                None => bail!(
+                    "`{}` starts before the end of the previous file",
+                    file.display()
+                ),
+            }
+        }
+        previous_final_timestamp = Some(file_view.final_timestamp());
+        census.extend(file_view.iter().map(|event| event.id()));
+
+        for event in file_view
+            .into_iter()
+            .filter(|event| matches!(EventId::try_from(event.id()), Ok(EventId::Main)))
+        {
+            let serial_number = event.serial_number();
+            let timestamp = event.timestamp();
+
+            let mut sizes = Sizes::default();
+            for bank in event {
+                sizes.add_bank(bank.name(), bank.data_slice().len());
+            }
+            total_events += 1;
+            totals.merge(&sizes);
+
+            match args.bin {
+                Some(width) => {
+                    let (n_events, bin_sizes) =
+                        bins.entry(timestamp - timestamp % width).or_default();
+                    *n_events += 1;
+                    bin_sizes.merge(&sizes);
+                }
+                None => wtr.serialize(EventRow::new(serial_number, timestamp, sizes))?,
+            }
+        }
+
+        wtr.flush()?;
+        bar.inc(1);
+    }
+    bar.finish_and_clear();
+    alpha_g_analysis::cli::exit_if_no_main_events(&census);
+    // There is always at least one file, and the final timestamp is known
+    // after reading all its events.
+    let timing = RunTiming {
+        start: run_start.unwrap(),
+        end: previous_final_timestamp.unwrap(),
+        main_events: census.main,
+    };
+
+    for (bin_start, (n_events, sizes)) in bins {
+        wtr.serialize(BinRow::new(bin_start, n_events, sizes))?;
+    }
+    wtr.finish(&format!("# {timing}\n"))?;
+
+    eprintln!("Totals over `{total_events}` main events:");
+    eprintln!(
+        "  ADC: {} banks, {} bytes",
+        totals.n_adc_banks, totals.adc_bytes
+    );
+    eprintln!(
+        "  PWB: {} banks, {} bytes",
+        totals.n_pwb_banks, totals.pwb_bytes
+    );
+    eprintln!("  TRG: {} bytes", totals.trg_bytes);
+    eprintln!("  Other: {} bytes", totals.other_bytes);
+
+    Ok(())
+}
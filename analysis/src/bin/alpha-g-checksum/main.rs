@@ -0,0 +1,27 @@
+use anyhow::Result;
+use clap::Parser;
+use std::path::PathBuf;
+
+#[derive(Parser)]
+#[command(version)]
+/// Print the size and checksum of files (e.g. the inputs of an output)
+struct Args {
+    /// Files to checksum (e.g. the MIDAS files of a run)
+    #[arg(required = true)]
+    files: Vec<PathBuf>,
+}
+
+fn main() {
+    alpha_g_analysis::cli::exit_on_error(run());
+}
+
+fn run() -> Result<()> {
+    let args: Args = alpha_g_analysis::cli::parse(env!("CARGO_BIN_NAME"));
+
+    for file in args.files {
+        let checksum = alpha_g_analysis::file_checksum(&file)?;
+        println!("{checksum}");
+    }
+
+    Ok(())
+}
@@ -1,11 +1,12 @@
 // READ CAREFULLY ALL THE COMMENTS.
 // If this program reports an error, the solution is most likely to analyze the
 // Chronobox data manually instead of patching this program.
+use alpha_g_analysis::cli::ChannelSpec;
 use alpha_g_detector::chronobox::{
     chronobox_fifo, EdgeType, FifoEntry, TimestampCounter, WrapAroundMarker, TIMESTAMP_BITS,
 };
 use alpha_g_detector::midas::{ChronoboxBankName, EventId};
-use alpha_g_physics::chronobox::TIMESTAMP_CLOCK_FREQ;
+use alpha_g_physics::chronobox::{counts_to_time, fit_clock_relation, match_edges};
 use anyhow::{ensure, Context, Result};
 use clap::Parser;
 use indicatif::{ProgressBar, ProgressStyle};
@@ -19,13 +20,39 @@ use uom::si::time::second;
 #[command(version)]
 /// Extract the Chronobox timestamps for a single run
 struct Args {
-    /// MIDAS files from the run you want to inspect
+    /// MIDAS files (or directories with them) from the run you want to inspect
     #[arg(required = true)]
     files: Vec<PathBuf>,
-    /// Write the output to `OUTPUT.csv` [default:
-    /// `R<run_number>_chronobox_timestamps.csv`]
+    /// Also search for MIDAS files in subdirectories of the input directories
+    #[arg(long)]
+    recursive: bool,
+    /// Write the output to `OUTPUT.csv`, or to stdout if `-` [default:
+    /// `R<run_number>_chronobox_timestamps.csv`, or
+    /// `R<run_number>_chronobox_drift.csv` with `--drift-check`]
     #[arg(short, long)]
     output: Option<PathBuf>,
+    /// Add a `unix_time` column anchored to the MIDAS timestamp of the event
+    /// with the epoch 0 marker of each board (±1 s)
+    #[arg(long)]
+    absolute_time: bool,
+    /// Instead of the timestamps, measure the clock drift between boards from
+    /// the leading edges of a signal common to all of them. Give one channel
+    /// per board e.g. `cb01:20,cb02:20,cb03:20,cb04:20`
+    #[arg(
+        long,
+        value_name = "BOARD:CHANNEL",
+        value_delimiter = ',',
+        conflicts_with = "absolute_time"
+    )]
+    drift_check: Vec<ChannelSpec>,
+    /// Maximum difference (in seconds) between the time since the previous
+    /// matched edge in two boards for an edge to be matched
+    #[arg(long, value_name = "SECONDS", default_value_t = 1e-4)]
+    match_tolerance: f64,
+    /// Minimum number of matched edges to fit the clock relation of a pair
+    /// of boards
+    #[arg(long, value_name = "N", default_value_t = 10)]
+    min_matches: usize,
 }
 
 #[derive(Debug, Default, serde::Serialize)]
@@ -34,6 +61,56 @@ struct Row {
     channel: u8,
     leading_edge: bool,
     chronobox_time: Option<f64>,
+    // The outer `None` means that the column is not written at all (i.e.
+    // `--absolute-time` was not set).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    unix_time: Option<Option<f64>>,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct DriftRow {
+    board_a: String,
+    board_b: String,
+    matched_edges: usize,
+    offset: Option<f64>,
+    drift_ppm: Option<f64>,
+    residual_rms: Option<f64>,
+}
+
+// Data banks of a single Chronobox in the order they appear in the MIDAS files.
+// Each bank is paired with the MIDAS timestamp of the event that contains it.
+type BankData = Vec<(u32, Vec<u8>)>;
+
+// Return the MIDAS timestamp of the first event that contains (at least the
+// first byte of) the FIFO entry at `index`. The `buffer` is all the bank data
+// concatenated, and `index` has to be a valid index of the complete FIFO.
+fn entry_event_timestamp(banks: &[(u32, Vec<u8>)], buffer: &[u8], index: usize) -> u32 {
+    let entries = |len: usize| {
+        let mut input = &buffer[..len];
+        chronobox_fifo(&mut input).len()
+    };
+    // All FIFO entries and scalers blocks are a whole number of 32 bit words.
+    // Find the shortest prefix (in words) that includes the entry; the entry is
+    // then the last word of this prefix.
+    let (mut low, mut high) = (index + 1, buffer.len() / 4);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if entries(4 * mid) > index {
+            high = mid;
+        } else {
+            low = mid + 1;
+        }
+    }
+    let start = 4 * (low - 1);
+
+    let mut end = 0;
+    for (timestamp, data) in banks {
+        end += data.len();
+        if start < end {
+            return *timestamp;
+        }
+    }
+    unreachable!()
 }
 
 fn chronobox_time(
@@ -52,9 +129,9 @@ fn chronobox_time(
             let timestamp_counter = tsc.timestamp();
             let top_bit = (timestamp_counter >> (TIMESTAMP_BITS - 1)) == 1;
             if top_bit != previous.timestamp_top_bit {
-                let time = u64::from(timestamp_counter)
+                let counts = u64::from(timestamp_counter)
                     + u64::from(epoch_counter) * (1u64 << TIMESTAMP_BITS);
-                Some(time as f64 / TIMESTAMP_CLOCK_FREQ)
+                Some(counts_to_time(counts))
             } else {
                 // In theory, this case can be handled by knowing an appropriate
                 // threshold for how close a timestamp can be to a marker to
@@ -73,10 +150,112 @@ fn chronobox_time(
     }
 }
 
-fn main() -> Result<()> {
-    let args = Args::parse();
-    let (run_number, files) =
-        alpha_g_analysis::sort_run_files(args.files).context("failed to sort input files")?;
+// Chronobox time of each timestamp in a FIFO that starts at the epoch 0
+// marker.
+fn fifo_times(fifo: &[FifoEntry]) -> Vec<(TimestampCounter, Option<Time>)> {
+    let mut times = Vec::new();
+    let mut previous_marker: Option<WrapAroundMarker> = None;
+    for chunk in fifo.split_inclusive(|n| matches!(n, FifoEntry::WrapAroundMarker(_))) {
+        let (next_marker, timestamps) = match chunk.split_last() {
+            Some((&FifoEntry::WrapAroundMarker(marker), timestamps)) => (Some(marker), timestamps),
+            Some((_, timestamps)) => (None, timestamps),
+            _ => unreachable!(),
+        };
+        for &tsc in timestamps {
+            let FifoEntry::TimestampCounter(tsc) = tsc else {
+                unreachable!();
+            };
+            times.push((tsc, chronobox_time(tsc, previous_marker, next_marker)));
+        }
+        previous_marker = next_marker;
+    }
+
+    times
+}
+
+// Clock relation between each pair of boards in `specs` (in the given order).
+// Pairs with too few matched edges are reported, but they are not an error.
+fn drift_rows(
+    fifos: &BTreeMap<String, (Option<u32>, Vec<FifoEntry>)>,
+    specs: &[ChannelSpec],
+    tolerance: Time,
+    min_matches: usize,
+) -> Result<Vec<DriftRow>> {
+    let edges = specs
+        .iter()
+        .map(|spec| {
+            let (_, fifo) = fifos
+                .get(&spec.board)
+                .with_context(|| format!("no data for chronobox `{}`", spec.board))?;
+            let mut edges: Vec<_> = fifo_times(fifo)
+                .into_iter()
+                .filter(|(tsc, _)| {
+                    u8::from(tsc.channel) == spec.channel && matches!(tsc.edge, EdgeType::Leading)
+                })
+                .filter_map(|(_, time)| time)
+                .collect();
+            edges.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            Ok(edges)
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut rows = Vec::new();
+    for (i, spec_a) in specs.iter().enumerate() {
+        for (j, spec_b) in specs.iter().enumerate().skip(i + 1) {
+            let (matches, relation) = match match_edges(&edges[i], &edges[j], tolerance) {
+                Err(error) => {
+                    eprintln!(
+                        "Warning: failed to match edges between `{}` and `{}` ({error}), skipping fit",
+                        spec_a.board, spec_b.board
+                    );
+                    (Vec::new(), None)
+                }
+                Ok(matches) if matches.len() < min_matches => {
+                    eprintln!(
+                        "Warning: only `{}` matched edges between `{}` and `{}`, skipping fit",
+                        matches.len(),
+                        spec_a.board,
+                        spec_b.board
+                    );
+                    (matches, None)
+                }
+                Ok(matches) => match fit_clock_relation(&matches) {
+                    Ok(relation) => (matches, Some(relation)),
+                    Err(error) => {
+                        eprintln!(
+                            "Warning: failed to fit clock relation between `{}` and `{}` ({error})",
+                            spec_a.board, spec_b.board
+                        );
+                        (matches, None)
+                    }
+                },
+            };
+            rows.push(DriftRow {
+                board_a: spec_a.board.clone(),
+                board_b: spec_b.board.clone(),
+                matched_edges: matches.len(),
+                offset: relation.map(|r| r.offset.get::<second>()),
+                drift_ppm: relation.map(|r| r.drift_ppm()),
+                residual_rms: relation.map(|r| r.residual_rms.get::<second>()),
+            });
+        }
+    }
+
+    Ok(rows)
+}
+
+fn main() {
+    alpha_g_analysis::cli::exit_on_error(run());
+}
+
+fn run() -> Result<()> {
+    let args: Args = alpha_g_analysis::cli::parse(env!("CARGO_BIN_NAME"));
+    let files = alpha_g_analysis::expand_run_files(args.files, args.recursive)?;
+    let (run_number, files) = alpha_g_analysis::sort_run_files(files, None)?;
+    ensure!(
+        args.drift_check.is_empty() || args.drift_check.len() >= 2,
+        "`--drift-check` needs a channel in at least two boards"
+    );
 
     let bar = ProgressBar::new(files.len().try_into().unwrap()).with_style(
         ProgressStyle::with_template("  Processing [{bar:25}] {percent}%,  ETA: {eta}")
@@ -85,38 +264,40 @@ fn main() -> Result<()> {
     );
     bar.tick();
 
-    let mut cb_buffers: BTreeMap<_, Vec<_>> = BTreeMap::new();
+    let mut cb_banks: BTreeMap<_, BankData> = BTreeMap::new();
     let mut previous_final_timestamp = None;
+    let mut inputs = Vec::new();
     for file in files {
-        let contents = alpha_g_analysis::read(&file)
-            .with_context(|| format!("failed to read `{}`", file.display()))?;
-        let file_view = midasio::FileView::try_from(&contents[..])
-            .with_context(|| format!("failed to parse `{}`", file.display()))?;
+        let mut events = alpha_g_analysis::event_iter(&file)?;
         if let Some(previous_final_timestamp) = previous_final_timestamp {
             ensure!(
-                file_view.initial_timestamp() - previous_final_timestamp <= 1,
+                events.initial_timestamp() - previous_final_timestamp <= 1,
                 "missing file before `{}`",
                 file.display()
             );
         }
-        previous_final_timestamp = Some(file_view.final_timestamp());
 
-        for event_view in file_view
-            .into_iter()
-            .filter(|event| matches!(EventId::try_from(event.id()), Ok(EventId::Chronobox)))
-        {
-            for bank_view in event_view {
-                let Ok(name) = ChronoboxBankName::try_from(bank_view.name()) else {
+        for event in events.by_ref() {
+            let event = event?;
+            if !matches!(EventId::try_from(event.id), Ok(EventId::Chronobox)) {
+                continue;
+            }
+            for bank in event.banks {
+                let Some(name) = std::str::from_utf8(&bank.name)
+                    .ok()
+                    .and_then(|name| ChronoboxBankName::try_from(name).ok())
+                else {
                     continue;
                 };
-                let data = bank_view.data_slice();
 
-                cb_buffers
+                cb_banks
                     .entry(name.board_id.name().to_string())
                     .or_default()
-                    .extend(data.iter());
+                    .push((event.timestamp, bank.data));
             }
         }
+        previous_final_timestamp = events.final_timestamp();
+        inputs.push(events.checksum()?);
         bar.inc(1);
     }
     bar.finish_and_clear();
@@ -126,9 +307,10 @@ fn main() -> Result<()> {
     // and analyze the data manually. There is no guaranteed way to recover with
     // complete certainty (it is not correct to e.g. skip until the next marker;
     // we could mistakenly find a word in the middle of a scalers block, etc.).
-    let cb_fifos = cb_buffers
+    let cb_fifos = cb_banks
         .into_iter()
-        .map(|(name, buffer)| {
+        .map(|(name, banks)| {
+            let buffer: Vec<u8> = banks.iter().flat_map(|(_, data)| data).copied().collect();
             let mut input = &buffer[..];
             let mut fifo = chronobox_fifo(&mut input);
             ensure!(input.is_empty(), "bad FIFO data for chronobox `{name}`");
@@ -141,6 +323,13 @@ fn main() -> Result<()> {
                     _ => false,
                 })
                 .context("missing epoch 0 marker in chronobox `{name}`")?;
+            // The MIDAS timestamp only has a 1 second resolution, and the
+            // marker could have been written to the FIFO up to a readout
+            // period before the event. Good enough to correlate with slow
+            // control data, but nothing else.
+            let anchor = args
+                .absolute_time
+                .then(|| entry_event_timestamp(&banks, &buffer, epoch_0_index));
             let fifo = fifo.split_off(epoch_0_index);
             // This is important, otherwise the `epoch_counter` in
             // `chronobox_time` will be wrong every other marker.
@@ -154,59 +343,65 @@ fn main() -> Result<()> {
                 "bad first marker in chronobox `{name}`"
             );
 
-            Ok((name, fifo))
+            Ok((name, (anchor, fifo)))
         })
         .collect::<Result<BTreeMap<_, _>>>()
         .context("failed to parse FIFO data")?;
 
+    let default_name = if args.drift_check.is_empty() {
+        "chronobox_timestamps"
+    } else {
+        "chronobox_drift"
+    };
     let output = args
         .output
-        .unwrap_or_else(|| PathBuf::from(format!("R{run_number}_chronobox_timestamps")))
-        .with_extension("csv");
-    let mut wtr = std::fs::File::create(&output)
-        .with_context(|| format!("failed to create `{}`", output.display()))?;
-    eprintln!("Created `{}`", output.display());
-    wtr.write_all(
-        format!(
-            "# {} {}\n# {}\n",
-            env!("CARGO_PKG_NAME"),
-            env!("CARGO_PKG_VERSION"),
-            std::env::args().collect::<Vec<_>>().join(" ")
-        )
-        .as_bytes(),
-    )
-    .context("failed to write csv header")?;
+        .unwrap_or_else(|| PathBuf::from(format!("R{run_number}_{default_name}")));
+    let mut wtr = alpha_g_analysis::cli::create_output(&output, "csv")?;
+    let mut header = format!(
+        "# {} {}\n# {}\n",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION"),
+        std::env::args().collect::<Vec<_>>().join(" ")
+    );
+    for input in inputs {
+        header.push_str(&format!("# input {input}\n"));
+    }
+    wtr.write_all(header.as_bytes())
+        .context("failed to write csv header")?;
 
     let mut wtr = csv::Writer::from_writer(wtr);
-    for (name, fifo) in cb_fifos {
-        let mut previous_marker: Option<WrapAroundMarker> = None;
-        for chunk in fifo.split_inclusive(|n| matches!(n, FifoEntry::WrapAroundMarker(_))) {
-            let (next_marker, timestamps) = match chunk.split_last() {
-                Some((&FifoEntry::WrapAroundMarker(marker), timestamps)) => {
-                    (Some(marker), timestamps)
-                }
-                Some((_, timestamps)) => (None, timestamps),
-                _ => unreachable!(),
+    if !args.drift_check.is_empty() {
+        let tolerance = Time::new::<second>(args.match_tolerance);
+        for row in drift_rows(&cb_fifos, &args.drift_check, tolerance, args.min_matches)? {
+            wtr.serialize(row)
+                .context("failed to write row to csv data")?;
+        }
+        wtr.flush().context("failed to flush csv data")?;
+
+        return Ok(());
+    }
+    // The epoch 0 marker is the first half wrap-around marker.
+    let epoch_0_time = counts_to_time(1 << (TIMESTAMP_BITS - 1));
+    for (name, (anchor, fifo)) in cb_fifos {
+        for (tsc, time) in fifo_times(&fifo) {
+            let row = Row {
+                board: name.clone(),
+                channel: u8::from(tsc.channel),
+                leading_edge: matches!(tsc.edge, EdgeType::Leading),
+                chronobox_time: time.map(|t| t.get::<second>()),
+                unix_time: anchor.map(|anchor| {
+                    time.map(|t| f64::from(anchor) + (t - epoch_0_time).get::<second>())
+                }),
             };
-            for &tsc in timestamps {
-                let FifoEntry::TimestampCounter(tsc) = tsc else {
-                    unreachable!();
-                };
-                let row = Row {
-                    board: name.clone(),
-                    channel: u8::from(tsc.channel),
-                    leading_edge: matches!(tsc.edge, EdgeType::Leading),
-                    chronobox_time: chronobox_time(tsc, previous_marker, next_marker)
-                        .map(|t| t.get::<second>()),
-                };
 
-                wtr.serialize(row)
-                    .context("failed to write row to csv data")?;
-            }
-            previous_marker = next_marker;
+            wtr.serialize(row)
+                .context("failed to write row to csv data")?;
         }
     }
     wtr.flush().context("failed to flush csv data")?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,83 @@
+use super::*;
+
+const TIMESTAMP: [u8; 4] = [0x10, 0x00, 0x00, 0x81];
+const EPOCH_0_MARKER: [u8; 4] = [0x00, 0x00, 0x00, 0xFF];
+const NUM_SCALERS: usize = 59;
+
+// Scalers block in which all scalers look like an epoch 0 marker.
+fn scalers_block() -> Vec<u8> {
+    let mut block = vec![0x3C, 0x00, 0x00, 0xFE];
+    for _ in 0..NUM_SCALERS {
+        block.extend(EPOCH_0_MARKER);
+    }
+    block.extend([0x00; 4]);
+    block
+}
+
+fn epoch_0_index(buffer: &[u8]) -> usize {
+    let mut input = buffer;
+    let fifo = chronobox_fifo(&mut input);
+    assert!(input.is_empty());
+    fifo.iter()
+        .position(|entry| match entry {
+            FifoEntry::WrapAroundMarker(marker) => marker.wrap_around_counter() == 0,
+            _ => false,
+        })
+        .unwrap()
+}
+
+fn concatenate(banks: &[(u32, Vec<u8>)]) -> Vec<u8> {
+    banks.iter().flat_map(|(_, data)| data).copied().collect()
+}
+
+#[test]
+fn entry_event_timestamp_single_event() {
+    let mut first = TIMESTAMP.to_vec();
+    first.extend(scalers_block());
+    let mut second = TIMESTAMP.to_vec();
+    second.extend(EPOCH_0_MARKER);
+    second.extend(TIMESTAMP);
+    let banks = vec![(100, first), (101, second)];
+
+    let buffer = concatenate(&banks);
+    let index = epoch_0_index(&buffer);
+    assert_eq!(index, 2);
+    assert_eq!(entry_event_timestamp(&banks, &buffer, index), 101);
+}
+
+#[test]
+fn entry_event_timestamp_marker_across_events() {
+    let mut first = TIMESTAMP.to_vec();
+    first.extend(scalers_block());
+    first.extend(&EPOCH_0_MARKER[..2]);
+    let mut second = EPOCH_0_MARKER[2..].to_vec();
+    second.extend(TIMESTAMP);
+    let banks = vec![(100, first), (101, second), (102, TIMESTAMP.to_vec())];
+
+    let buffer = concatenate(&banks);
+    let index = epoch_0_index(&buffer);
+    assert_eq!(index, 1);
+    assert_eq!(entry_event_timestamp(&banks, &buffer, index), 100);
+}
+
+#[test]
+fn entry_event_timestamp_empty_banks() {
+    let banks = vec![
+        (100, TIMESTAMP.to_vec()),
+        (101, Vec::new()),
+        (102, EPOCH_0_MARKER[..1].to_vec()),
+        (103, EPOCH_0_MARKER[1..].to_vec()),
+    ];
+
+    let buffer = concatenate(&banks);
+    let index = epoch_0_index(&buffer);
+    assert_eq!(index, 1);
+    assert_eq!(entry_event_timestamp(&banks, &buffer, index), 102);
+}
+
+#[test]
+fn drift_rows_missing_board() {
+    let specs = ["cb01:20".parse().unwrap(), "cb02:20".parse().unwrap()];
+    let error = drift_rows(&BTreeMap::new(), &specs, Time::new::<second>(1e-4), 10).unwrap_err();
+    assert_eq!(error.to_string(), "no data for chronobox `cb01`");
+}
@@ -0,0 +1,310 @@
+use alpha_g_analysis::EventCensus;
+use alpha_g_detector::midas::EventId;
+use alpha_g_physics::geometry::FIDUCIAL_CYLINDER;
+use alpha_g_physics::reconstruction::{fit_single_track, StraightLineFit};
+use alpha_g_physics::{DecodeOptions, MainEvent, RunCalibration, SpacePoint};
+use anyhow::{ensure, Context, Result};
+use clap::Parser;
+use indicatif::{
+    MultiProgress, ParallelProgressIterator, ProgressBar, ProgressDrawTarget, ProgressStyle,
+};
+use rayon::prelude::*;
+use std::io::Write;
+use std::path::PathBuf;
+use uom::si::angle::radian;
+use uom::si::length::meter;
+
+#[derive(Parser)]
+#[command(version)]
+/// Fit a single through-going track (e.g. a cosmic ray) to every event of a
+/// single run
+struct Args {
+    /// MIDAS files (or directories with them) from the run you want to reconstruct
+    #[arg(required = true)]
+    files: Vec<PathBuf>,
+    /// Also search for MIDAS files in subdirectories of the input directories
+    #[arg(long)]
+    recursive: bool,
+    /// Write the output to `OUTPUT.csv`, or to stdout if `-` [default:
+    /// `R<run_number>_cosmics.csv`]
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    /// Also write the residual of every spacepoint to
+    /// `R<run_number>_cosmics_residuals.csv`
+    #[arg(long)]
+    residuals: bool,
+    /// Also write the zenith angle, azimuth, and fiducial length of every
+    /// fitted track to `COSMIC_ANGLES.csv` (or to stdout if `-`)
+    #[arg(long)]
+    cosmic_angles: Option<PathBuf>,
+    /// Print detailed information about errors (if any)
+    #[arg(short, long)]
+    verbose: bool,
+}
+
+#[derive(Debug, Default, serde::Serialize)]
+struct Row {
+    serial_number: u32,
+    num_points: Option<usize>,
+    num_inliers: Option<usize>,
+    point_x: Option<f64>,
+    point_y: Option<f64>,
+    point_z: Option<f64>,
+    direction_x: Option<f64>,
+    direction_y: Option<f64>,
+    direction_z: Option<f64>,
+    mean_residual: Option<f64>,
+}
+
+impl Row {
+    fn new(serial_number: u32, num_points: usize, fit: Option<&StraightLineFit>) -> Self {
+        let Some(fit) = fit else {
+            return Self {
+                serial_number,
+                num_points: Some(num_points),
+                ..Default::default()
+            };
+        };
+        Self {
+            serial_number,
+            num_points: Some(num_points),
+            num_inliers: Some(fit.num_inliers()),
+            point_x: Some(fit.point.x.get::<meter>()),
+            point_y: Some(fit.point.y.get::<meter>()),
+            point_z: Some(fit.point.z.get::<meter>()),
+            direction_x: Some(fit.direction[0]),
+            direction_y: Some(fit.direction[1]),
+            direction_z: Some(fit.direction[2]),
+            mean_residual: Some(fit.mean_residual().get::<meter>()),
+        }
+    }
+}
+
+#[derive(Debug, serde::Serialize)]
+struct AngleRow {
+    serial_number: u32,
+    zenith: f64,
+    azimuth: f64,
+    track_length: Option<f64>,
+    num_points: usize,
+}
+
+impl AngleRow {
+    fn new(serial_number: u32, fit: &StraightLineFit) -> Self {
+        let [x, y, z] = fit.direction;
+        Self {
+            serial_number,
+            // The direction always points up (non-negative `z`), i.e. towards
+            // where the cosmic ray came from.
+            zenith: z.acos(),
+            azimuth: y.atan2(x),
+            track_length: fit
+                .length_inside(&FIDUCIAL_CYLINDER)
+                .map(|length| length.get::<meter>()),
+            num_points: fit.num_inliers(),
+        }
+    }
+}
+
+#[derive(Debug, serde::Serialize)]
+struct ResidualRow {
+    serial_number: u32,
+    r: f64,
+    phi: f64,
+    z: f64,
+    residual: f64,
+    inlier: bool,
+}
+
+// Write the provenance header shared by all the CSV outputs.
+fn write_provenance(wtr: &mut dyn Write) -> Result<()> {
+    wtr.write_all(
+        format!(
+            "# {} {}\n# {}\n",
+            env!("CARGO_PKG_NAME"),
+            env!("CARGO_PKG_VERSION"),
+            std::env::args().collect::<Vec<_>>().join(" ")
+        )
+        .as_bytes(),
+    )
+    .context("failed to write csv header")
+}
+
+fn main() {
+    alpha_g_analysis::cli::exit_on_error(run());
+}
+
+fn run() -> Result<()> {
+    // Same as `alpha-g-vertices`; the default 2 MiB stack size for threads is
+    // not enough to reconstruct the avalanches.
+    rayon::ThreadPoolBuilder::new()
+        .stack_size(4 * 1024 * 1024)
+        .build_global()
+        .context("failed to initialize global thread pool")?;
+
+    let args: Args = alpha_g_analysis::cli::parse(env!("CARGO_BIN_NAME"));
+    let files = alpha_g_analysis::expand_run_files(args.files, args.recursive)?;
+    let (run_number, files) = alpha_g_analysis::sort_run_files(files, None)?;
+    let calibration = RunCalibration::new(run_number);
+
+    let multi_progress = MultiProgress::with_draw_target(ProgressDrawTarget::stderr_with_hz(1));
+    let tp_bar = multi_progress.add(
+        ProgressBar::new(files.len().try_into().unwrap())
+            .with_style(ProgressStyle::with_template("[{pos}/{len}] Processing").unwrap()),
+    );
+    tp_bar.tick();
+
+    let mut residuals_wtr = if args.residuals {
+        let path = PathBuf::from(format!("R{run_number}_cosmics_residuals"));
+        let mut wtr = alpha_g_analysis::cli::create_output(&path, "csv")?;
+        write_provenance(&mut wtr)?;
+        Some(csv::Writer::from_writer(wtr))
+    } else {
+        None
+    };
+    let mut angles_wtr = match &args.cosmic_angles {
+        Some(path) => {
+            let mut wtr = alpha_g_analysis::cli::create_output(path, "csv")?;
+            write_provenance(&mut wtr)?;
+            Some(csv::Writer::from_writer(wtr))
+        }
+        None => None,
+    };
+    let mut rows = Vec::new();
+    let mut census = EventCensus::default();
+    let mut num_errors = 0;
+    let mut previous_final_timestamp = None;
+    for file in files {
+        let contents = alpha_g_analysis::open(&file)?;
+        let file_view = midasio::FileView::try_from(&contents[..])
+            .with_context(|| format!("failed to parse `{}`", file.display()))?;
+        if let Some(previous_final_timestamp) = previous_final_timestamp {
+            ensure!(
+                file_view.initial_timestamp() - previous_final_timestamp <= 1,
+                "missing file before `{}`",
+                file.display()
+            );
+        }
+        previous_final_timestamp = Some(file_view.final_timestamp());
+        census.extend(file_view.iter().map(|event| event.id()));
+
+        let pb = multi_progress.add(
+            ProgressBar::new(file_view.iter().len().try_into().unwrap())
+                .with_style(
+                    ProgressStyle::with_template("[{bar:25}] {percent}%, ETA: {eta}    ({msg})")
+                        .unwrap()
+                        .progress_chars("=> "),
+                )
+                .with_message(format!("{}", file.display())),
+        );
+        let file_rows: Vec<_> = file_view
+            .into_par_iter()
+            .progress_with(pb.clone())
+            .filter(|event| matches!(EventId::try_from(event.id()), Ok(EventId::Main)))
+            .map(|event| {
+                let serial_number = event.serial_number();
+
+                let banks = event
+                    .into_iter()
+                    .map(|bank| (bank.name(), bank.data_slice()));
+                let event = match MainEvent::try_from_banks_with(
+                    &calibration,
+                    banks,
+                    &DecodeOptions::default(),
+                ) {
+                    Ok((event, _)) => event,
+                    Err(error) => {
+                        if args.verbose {
+                            pb.println(format!("Error in event `{serial_number}`: {error}"));
+                        }
+                        return (
+                            Row {
+                                serial_number,
+                                ..Default::default()
+                            },
+                            None,
+                            Vec::new(),
+                            true,
+                        );
+                    }
+                };
+                let points: Vec<_> = event
+                    .avalanches()
+                    .into_iter()
+                    .filter_map(|avalanche| SpacePoint::try_from(avalanche).ok())
+                    .collect();
+                let fit = fit_single_track(points.clone());
+
+                let residuals = match (&fit, args.residuals) {
+                    (Some(fit), true) => points
+                        .iter()
+                        .zip(&fit.residuals)
+                        .zip(&fit.inliers)
+                        .map(|((point, residual), &inlier)| ResidualRow {
+                            serial_number,
+                            r: point.r.get::<meter>(),
+                            phi: point.phi.get::<radian>(),
+                            z: point.z.get::<meter>(),
+                            residual: residual.get::<meter>(),
+                            inlier,
+                        })
+                        .collect(),
+                    _ => Vec::new(),
+                };
+                (
+                    Row::new(serial_number, points.len(), fit.as_ref()),
+                    fit.as_ref().map(|fit| AngleRow::new(serial_number, fit)),
+                    residuals,
+                    false,
+                )
+            })
+            .collect();
+        for (row, angles, residuals, failed) in file_rows {
+            if failed {
+                num_errors += 1;
+            }
+            if let (Some(wtr), Some(angles)) = (angles_wtr.as_mut(), angles) {
+                wtr.serialize(angles)
+                    .context("failed to write cosmic angles csv row")?;
+            }
+            if let Some(wtr) = residuals_wtr.as_mut() {
+                for residual in residuals {
+                    wtr.serialize(residual)
+                        .context("failed to write residuals csv row")?;
+                }
+            }
+            rows.push(row);
+        }
+        if let Some(wtr) = residuals_wtr.as_mut() {
+            wtr.flush().context("failed to flush residuals csv data")?;
+        }
+        if let Some(wtr) = angles_wtr.as_mut() {
+            wtr.flush()
+                .context("failed to flush cosmic angles csv data")?;
+        }
+        tp_bar.set_style(
+            ProgressStyle::with_template("[{pos}/{len}] Processing, ETA: {eta}").unwrap(),
+        );
+        tp_bar.inc(1);
+    }
+    tp_bar.finish_and_clear();
+    drop(residuals_wtr);
+    drop(angles_wtr);
+    alpha_g_analysis::cli::exit_if_no_main_events(&census);
+    if num_errors > 0 {
+        eprintln!("Warning: failed to reconstruct `{num_errors}` events");
+    }
+
+    let output = args
+        .output
+        .unwrap_or_else(|| PathBuf::from(format!("R{run_number}_cosmics")));
+    let mut wtr = alpha_g_analysis::cli::create_output(&output, "csv")?;
+    write_provenance(&mut wtr)?;
+    let mut wtr = csv::Writer::from_writer(wtr);
+    for row in rows {
+        wtr.serialize(row).context("failed to write csv row")?;
+    }
+    wtr.flush().context("failed to flush csv data")?;
+
+    Ok(())
+}
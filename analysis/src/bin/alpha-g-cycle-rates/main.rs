@@ -0,0 +1,332 @@
+use alpha_g_analysis::cli::ChannelSpec;
+use anyhow::{ensure, Context, Result};
+use clap::Parser;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+#[derive(Parser)]
+#[command(version)]
+/// Align the vertices of a run to the start of each cycle (e.g. mixing)
+struct Args {
+    /// CSV output of `alpha-g-vertices`
+    vertices: PathBuf,
+    /// CSV output of `alpha-g-chronobox-timestamps` (same run)
+    chronobox: PathBuf,
+    /// Chronobox channel with the cycle start signal (leading edges) e.g.
+    /// `cb02:17`
+    #[arg(long, value_name = "BOARD:CHANNEL")]
+    cycle_align: ChannelSpec,
+    /// Flag cycles shorter than this (in seconds) as anomalous
+    #[arg(long, value_name = "SECONDS", default_value_t = 0.0)]
+    min_cycle: f64,
+    /// Width (in seconds) of the time in cycle bins of the rate histogram
+    #[arg(long, value_name = "SECONDS", default_value_t = 1.0)]
+    bin_width: f64,
+    /// Chronobox time (in seconds) at a TRG time of 0. The TRG time starts at
+    /// the first triggered event, and the Chronobox time at the board reset;
+    /// there is no default
+    #[arg(long, value_name = "SECONDS", allow_hyphen_values = true)]
+    trg_offset: f64,
+    /// Chronobox seconds per TRG second
+    #[arg(long, default_value_t = 1.0)]
+    trg_scale: f64,
+    /// Write the output to `OUTPUT.csv` and `OUTPUT_rates.csv` [default:
+    /// `<VERTICES>_cycles.csv` and `<VERTICES>_cycles_rates.csv`]
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+// Linear mapping from TRG time to Chronobox time.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct ClockAlignment {
+    offset: f64,
+    scale: f64,
+}
+
+impl ClockAlignment {
+    fn chronobox_time(&self, trg_time: f64) -> f64 {
+        self.offset + self.scale * trg_time
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct ChronoboxRow {
+    board: String,
+    channel: u8,
+    leading_edge: bool,
+    chronobox_time: Option<f64>,
+}
+
+// Return the (sorted) times of all leading edges in a channel.
+fn leading_edges<R: Read>(reader: R, spec: &ChannelSpec) -> Result<Vec<f64>> {
+    let (_, mut reader) = alpha_g_analysis::cli::read_commented_csv(reader)?;
+    let mut edges = Vec::new();
+    for row in reader.deserialize() {
+        let row: ChronoboxRow = row.context("failed to read csv row")?;
+        if row.board == spec.board && row.channel == spec.channel && row.leading_edge {
+            if let Some(time) = row.chronobox_time {
+                edges.push(time);
+            }
+        }
+    }
+    edges.sort_by(f64::total_cmp);
+
+    Ok(edges)
+}
+
+// Cycles are delimited by consecutive cycle start edges. The last cycle has no
+// known end, so it never counts as anomalous.
+#[derive(Clone, Debug, PartialEq)]
+struct Cycles {
+    starts: Vec<f64>,
+    min_length: f64,
+}
+
+impl Cycles {
+    // Return the index of the cycle that contains `time`, and the time since
+    // its start. Return `None` before the first cycle start.
+    fn locate(&self, time: f64) -> Option<(usize, f64)> {
+        let index = self.starts.partition_point(|&start| start <= time);
+        let index = index.checked_sub(1)?;
+
+        Some((index, time - self.starts[index]))
+    }
+
+    fn length(&self, index: usize) -> Option<f64> {
+        Some(self.starts.get(index + 1)? - self.starts[index])
+    }
+
+    fn is_anomalous(&self, index: usize) -> bool {
+        self.length(index)
+            .is_some_and(|length| length < self.min_length)
+    }
+}
+
+// Number of vertices in each time in cycle bin, together with the number of
+// cycles that cover the bin (i.e. the exposure).
+#[derive(Clone, Debug, PartialEq)]
+struct RateHistogram {
+    bin_width: f64,
+    counts: Vec<u64>,
+    exposure: Vec<u64>,
+}
+
+impl RateHistogram {
+    // Anomalous cycles are excluded. The last cycle covers up to `end` (e.g.
+    // the last known time in the run).
+    fn new(cycles: &Cycles, bin_width: f64, end: f64) -> Self {
+        let mut exposure = Vec::new();
+        for index in 0..cycles.starts.len() {
+            if cycles.is_anomalous(index) {
+                continue;
+            }
+            let length = cycles
+                .length(index)
+                .unwrap_or(end - cycles.starts[index])
+                .max(0.0);
+            let bins = (length / bin_width).ceil() as usize;
+            if exposure.len() < bins {
+                exposure.resize(bins, 0);
+            }
+            exposure[..bins].iter_mut().for_each(|n| *n += 1);
+        }
+
+        Self {
+            bin_width,
+            counts: vec![0; exposure.len()],
+            exposure,
+        }
+    }
+
+    fn add(&mut self, t_in_cycle: f64) {
+        if let Some(count) = self.counts.get_mut((t_in_cycle / self.bin_width) as usize) {
+            *count += 1;
+        }
+    }
+}
+
+#[derive(Debug, serde::Serialize)]
+struct RateRow {
+    bin_start: f64,
+    n_vertices: u64,
+    n_cycles: u64,
+    rate: f64,
+}
+
+// Vertices CSV with all its comment lines. Rows are kept as is, only the
+// relevant columns are parsed.
+struct Vertices {
+    comments: Vec<String>,
+    header: csv::StringRecord,
+    records: Vec<csv::StringRecord>,
+    trg_time: usize,
+    reconstructed_x: usize,
+}
+
+impl Vertices {
+    fn new<R: Read>(reader: R) -> Result<Self> {
+        let (comments, mut reader) = alpha_g_analysis::cli::read_commented_csv(reader)?;
+        let header = reader
+            .headers()
+            .context("failed to read csv header")?
+            .clone();
+        let find = |name: &str| {
+            header
+                .iter()
+                .position(|field| field == name)
+                .with_context(|| format!("missing `{name}` column"))
+        };
+        let trg_time = find("trg_time")?;
+        let reconstructed_x = find("reconstructed_x")?;
+        let records = reader
+            .into_records()
+            .collect::<Result<_, _>>()
+            .context("failed to read csv row")?;
+
+        Ok(Self {
+            comments,
+            header,
+            records,
+            trg_time,
+            reconstructed_x,
+        })
+    }
+
+    // Return the TRG time of a row, and whether it has a reconstructed vertex.
+    fn parse(&self, record: &csv::StringRecord) -> Result<(Option<f64>, bool)> {
+        let field = |index: usize| record.get(index).filter(|field| !field.is_empty());
+        let trg_time = field(self.trg_time)
+            .map(str::parse)
+            .transpose()
+            .context("bad trg_time")?;
+
+        Ok((trg_time, field(self.reconstructed_x).is_some()))
+    }
+}
+
+fn open(path: &Path) -> Result<std::fs::File> {
+    std::fs::File::open(path).with_context(|| format!("failed to open `{}`", path.display()))
+}
+
+fn create(path: &Path) -> Result<std::fs::File> {
+    let mut file = std::fs::File::create(path)
+        .with_context(|| format!("failed to create `{}`", path.display()))?;
+    eprintln!("Created `{}`", path.display());
+    file.write_all(
+        format!(
+            "# {} {}\n# {}\n",
+            env!("CARGO_PKG_NAME"),
+            env!("CARGO_PKG_VERSION"),
+            std::env::args().collect::<Vec<_>>().join(" ")
+        )
+        .as_bytes(),
+    )
+    .context("failed to write csv header")?;
+
+    Ok(file)
+}
+
+fn main() {
+    alpha_g_analysis::cli::exit_on_error(run());
+}
+
+fn run() -> Result<()> {
+    let args: Args = alpha_g_analysis::cli::parse(env!("CARGO_BIN_NAME"));
+    ensure!(args.bin_width > 0.0, "`--bin-width` must be positive");
+    let alignment = ClockAlignment {
+        offset: args.trg_offset,
+        scale: args.trg_scale,
+    };
+
+    let vertices = Vertices::new(open(&args.vertices)?)
+        .with_context(|| format!("failed to read `{}`", args.vertices.display()))?;
+    let starts = leading_edges(open(&args.chronobox)?, &args.cycle_align)
+        .with_context(|| format!("failed to read `{}`", args.chronobox.display()))?;
+    ensure!(
+        !starts.is_empty(),
+        "no leading edges in channel `{}:{}`",
+        args.cycle_align.board,
+        args.cycle_align.channel
+    );
+    let cycles = Cycles {
+        starts,
+        min_length: args.min_cycle,
+    };
+    for index in (0..cycles.starts.len()).filter(|&i| cycles.is_anomalous(i)) {
+        eprintln!(
+            "Warning: cycle `{index}` is shorter than `--min-cycle` ({} s)",
+            cycles.length(index).unwrap()
+        );
+    }
+
+    let mut located = Vec::with_capacity(vertices.records.len());
+    let mut end = *cycles.starts.last().unwrap();
+    for record in &vertices.records {
+        let (trg_time, has_vertex) = vertices.parse(record)?;
+        let time = trg_time.map(|t| alignment.chronobox_time(t));
+        if let Some(time) = time {
+            end = end.max(time);
+        }
+        located.push((time.and_then(|t| cycles.locate(t)), has_vertex));
+    }
+    let mut histogram = RateHistogram::new(&cycles, args.bin_width, end);
+
+    let output = args.output.unwrap_or_else(|| {
+        let mut name = args.vertices.file_stem().unwrap_or_default().to_owned();
+        name.push("_cycles");
+        args.vertices.with_file_name(name)
+    });
+    let events_output = output.with_extension("csv");
+    let mut wtr = create(&events_output)?;
+    let (timing, warnings) = alpha_g_analysis::cli::carried_over_comments(&vertices.comments);
+    for comment in timing.into_iter().chain(warnings) {
+        writeln!(wtr, "{comment}").context("failed to write csv header")?;
+    }
+    let mut wtr = csv::Writer::from_writer(wtr);
+    let mut header = vertices.header.clone();
+    header.extend(["cycle", "t_in_cycle", "anomalous_cycle"]);
+    wtr.write_record(&header)
+        .context("failed to write csv header")?;
+    for (record, &(location, has_vertex)) in vertices.records.iter().zip(&located) {
+        let mut record = record.clone();
+        match location {
+            Some((index, t_in_cycle)) => {
+                let anomalous = cycles.is_anomalous(index);
+                if has_vertex && !anomalous {
+                    histogram.add(t_in_cycle);
+                }
+                record.extend([
+                    index.to_string(),
+                    t_in_cycle.to_string(),
+                    anomalous.to_string(),
+                ]);
+            }
+            None => record.extend(["", "", ""]),
+        }
+        wtr.write_record(&record)
+            .context("failed to write csv row")?;
+    }
+    wtr.flush().context("failed to flush csv data")?;
+
+    let mut rates_name = events_output.file_stem().unwrap_or_default().to_owned();
+    rates_name.push("_rates.csv");
+    let rates_output = events_output.with_file_name(rates_name);
+    let mut wtr = csv::Writer::from_writer(create(&rates_output)?);
+    for (bin, (&n_vertices, &n_cycles)) in
+        histogram.counts.iter().zip(&histogram.exposure).enumerate()
+    {
+        wtr.serialize(RateRow {
+            bin_start: bin as f64 * histogram.bin_width,
+            n_vertices,
+            n_cycles,
+            rate: n_vertices as f64 / (n_cycles as f64 * histogram.bin_width),
+        })
+        .context("failed to write csv row")?;
+    }
+    wtr.flush().context("failed to flush csv data")?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,107 @@
+use super::*;
+
+const CHRONOBOX: &str = "# alpha-g-analysis 0.5.8
+# alpha-g-chronobox-timestamps run.mid
+board,channel,leading_edge,chronobox_time
+cb02,17,true,20.0
+cb02,17,false,10.5
+cb02,17,true,10.0
+cb02,16,true,12.0
+cb01,17,true,13.0
+cb02,17,true,
+cb02,17,true,21.0
+";
+
+const VERTICES: &str = "# alpha-g-analysis 0.5.8
+# alpha-g-vertices run.mid
+# WARNING: run number overridden to 1
+serial_number,trg_time,reconstructed_x,reconstructed_y,reconstructed_z
+1,0.1,0.01,0.02,0.5
+2,,,,
+3,0.3,,,
+";
+
+fn cycles(starts: &[f64], min_length: f64) -> Cycles {
+    Cycles {
+        starts: starts.to_vec(),
+        min_length,
+    }
+}
+
+#[test]
+fn clock_alignment_chronobox_time() {
+    let alignment = ClockAlignment {
+        offset: 2.0,
+        scale: 0.5,
+    };
+    assert_eq!(alignment.chronobox_time(0.0), 2.0);
+    assert_eq!(alignment.chronobox_time(4.0), 4.0);
+}
+
+#[test]
+fn leading_edges_filter_and_sort() {
+    let spec = ChannelSpec {
+        board: String::from("cb02"),
+        channel: 17,
+    };
+    let edges = leading_edges(CHRONOBOX.as_bytes(), &spec).unwrap();
+    assert_eq!(edges, [10.0, 20.0, 21.0]);
+}
+
+#[test]
+fn cycles_locate() {
+    let cycles = cycles(&[10.0, 20.0, 21.0], 0.0);
+    assert_eq!(cycles.locate(5.0), None);
+    assert_eq!(cycles.locate(10.0), Some((0, 0.0)));
+    assert_eq!(cycles.locate(15.0), Some((0, 5.0)));
+    assert_eq!(cycles.locate(20.5), Some((1, 0.5)));
+    assert_eq!(cycles.locate(100.0), Some((2, 79.0)));
+}
+
+#[test]
+fn cycles_anomalous() {
+    let cycles = cycles(&[10.0, 20.0, 21.0], 5.0);
+    assert!(!cycles.is_anomalous(0));
+    assert!(cycles.is_anomalous(1));
+    // The last cycle has no known length.
+    assert!(!cycles.is_anomalous(2));
+    assert_eq!(cycles.length(2), None);
+}
+
+#[test]
+fn rate_histogram_exposure() {
+    // Cycle lengths: 3.0 (anomalous), 5.5, and 2.0 (last, until the end).
+    let cycles = cycles(&[0.0, 3.0, 8.5], 3.5);
+    let mut histogram = RateHistogram::new(&cycles, 1.0, 10.5);
+    assert_eq!(histogram.exposure, [2, 2, 1, 1, 1, 1]);
+
+    histogram.add(0.5);
+    histogram.add(1.0);
+    histogram.add(5.9);
+    // Outside of all bins.
+    histogram.add(6.0);
+    assert_eq!(histogram.counts, [1, 1, 0, 0, 0, 1]);
+}
+
+#[test]
+fn vertices_parse_rows() {
+    let vertices = Vertices::new(VERTICES.as_bytes()).unwrap();
+    assert_eq!(vertices.comments.len(), 3);
+    assert_eq!(vertices.records.len(), 3);
+
+    let parsed: Vec<_> = vertices
+        .records
+        .iter()
+        .map(|record| vertices.parse(record).unwrap())
+        .collect();
+    assert_eq!(
+        parsed,
+        [(Some(0.1), true), (None, false), (Some(0.3), false)]
+    );
+}
+
+#[test]
+fn vertices_missing_column() {
+    let contents = "serial_number,reconstructed_x\n1,0.1\n";
+    assert!(Vertices::new(contents.as_bytes()).is_err());
+}
@@ -0,0 +1,596 @@
+use alpha_g_detector::alpha16::aw_map::TpcWirePosition;
+use alpha_g_detector::midas::{BankKind, EventId};
+use alpha_g_detector::padwing::map::TpcPadPosition;
+use alpha_g_detector::run::RunNumber;
+use alpha_g_physics::reconstruction::{cluster_spacepoints, Coordinate, Track, TrackModel};
+use alpha_g_physics::{DeconvolvedSignal, MainEvent, ReconstructionReport, SpacePoint};
+use anyhow::{bail, ensure, Context, Result};
+use clap::Parser;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt::Write as _;
+use std::path::PathBuf;
+use uom::si::angle::radian;
+use uom::si::f64::Length;
+use uom::si::length::{meter, millimeter};
+use uom::si::reciprocal_length::reciprocal_meter;
+use uom::si::time::second;
+
+#[derive(Parser)]
+#[command(version)]
+/// Reconstruct a single event and write everything about it to a JSON file
+struct Args {
+    /// MIDAS files (or directories with them) from the run of the event
+    #[arg(
+        long,
+        num_args = 1..,
+        value_name = "FILES",
+        required_unless_present = "from_dump"
+    )]
+    run_files: Vec<PathBuf>,
+    /// Also search for MIDAS files in subdirectories of the input directories
+    #[arg(long)]
+    recursive: bool,
+    /// Serial number of the event
+    #[arg(long, value_name = "N", required_unless_present = "from_dump")]
+    serial: Option<u32>,
+    /// Write the dump to `OUTPUT.json`, or to stdout if `-` [default:
+    /// `R<run_number>_E<serial>_debug.json`]
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    /// Ignore the run number in the MIDAS files and use this one instead
+    #[arg(long, value_name = "RUN_NUMBER")]
+    run_number_override: Option<RunNumber>,
+    /// Don't reconstruct anything; print the report of a previous dump
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = ["run_files", "serial", "output", "run_number_override"]
+    )]
+    from_dump: Option<PathBuf>,
+}
+
+// Version of the dump format. Increase it with every change that makes a dump
+// unreadable by a previous version of this program (i.e. anything other than
+// adding an optional field).
+const DUMP_VERSION: u32 = 1;
+
+// All lengths are in meters, times in seconds, and angles in radians.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Dump {
+    version: u32,
+    package: String,
+    arguments: Vec<String>,
+    run_number: RunNumber,
+    serial_number: u32,
+    source_file: PathBuf,
+    banks: Vec<Bank>,
+    // Everything below is empty if the event can't be decoded (see `error`).
+    trg_timestamp: Option<u32>,
+    wires: Vec<WireSignal>,
+    pads: Vec<PadSignal>,
+    matching: Matching,
+    spacepoints: Vec<[f64; 3]>,
+    // Indices (in `spacepoints`) of the members of each cluster.
+    clusters: Vec<Vec<usize>>,
+    unclustered: Vec<usize>,
+    tracks: Vec<TrackSummary>,
+    vertex: Option<Vertex>,
+    // Not in dumps written by older versions.
+    #[serde(default)]
+    reconstruction: Option<ReconstructionReport>,
+    flags: Flags,
+    warnings: Vec<String>,
+    error: Option<ErrorSummary>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Bank {
+    name: String,
+    size: usize,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct SignalSummary {
+    samples: usize,
+    min: f64,
+    max: f64,
+    // Time, amplitude, and whether it was matched into an avalanche.
+    spikes: Vec<(f64, f64, bool)>,
+}
+
+impl From<DeconvolvedSignal> for SignalSummary {
+    fn from(deconvolved: DeconvolvedSignal) -> Self {
+        Self {
+            samples: deconvolved.signal.len(),
+            min: deconvolved
+                .signal
+                .iter()
+                .copied()
+                .fold(f64::INFINITY, f64::min),
+            max: deconvolved
+                .signal
+                .iter()
+                .copied()
+                .fold(f64::NEG_INFINITY, f64::max),
+            spikes: deconvolved
+                .spikes
+                .into_iter()
+                .map(|spike| (spike.t.get::<second>(), spike.amplitude, spike.matched))
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct WireSignal {
+    wire: TpcWirePosition,
+    #[serde(flatten)]
+    summary: SignalSummary,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct PadSignal {
+    pad: TpcPadPosition,
+    #[serde(flatten)]
+    summary: SignalSummary,
+}
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+struct Matching {
+    wire_spikes: usize,
+    matched_wire_spikes: usize,
+    pad_spikes: usize,
+    matched_pad_spikes: usize,
+    avalanches: usize,
+    saturated_avalanches: usize,
+    // Avalanches that couldn't be converted to a spacepoint.
+    outside_drift_tables: usize,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Model {
+    Helix {
+        curvature: f64,
+        pitch: f64,
+    },
+    Line {
+        direction: [f64; 3],
+        point: [f64; 3],
+    },
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct TrackSummary {
+    // Index in `clusters`.
+    cluster: usize,
+    num_points: usize,
+    rms_residual: f64,
+    model: Model,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Vertex {
+    position: [f64; 3],
+    sigma: Option<[f64; 3]>,
+    num_tracks: usize,
+}
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+struct Flags {
+    misbuilt: bool,
+    saturated: bool,
+    reconstruction_error: bool,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct ErrorSummary {
+    // Same as in the `--error-summary` of `alpha-g-vertices`.
+    kind: String,
+    // Error message followed by the messages of all its sources.
+    message: String,
+}
+
+impl ErrorSummary {
+    fn new(error: &(dyn Error + 'static)) -> Self {
+        let mut message = error.to_string();
+        let mut current = error.source();
+        while let Some(error) = current {
+            write!(message, ": {error}").unwrap();
+            current = error.source();
+        }
+
+        Self {
+            kind: alpha_g_analysis::error_kind(error),
+            message,
+        }
+    }
+}
+
+fn coordinate(c: Coordinate) -> [f64; 3] {
+    [c.x, c.y, c.z].map(|l| l.get::<meter>())
+}
+
+impl Dump {
+    // Dump without anything past the raw banks.
+    fn new(
+        run_number: RunNumber,
+        serial_number: u32,
+        source_file: PathBuf,
+        banks: Vec<Bank>,
+    ) -> Self {
+        Self {
+            version: DUMP_VERSION,
+            package: format!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")),
+            arguments: std::env::args().collect(),
+            run_number,
+            serial_number,
+            source_file,
+            banks,
+            trg_timestamp: None,
+            wires: Vec::new(),
+            pads: Vec::new(),
+            matching: Matching::default(),
+            spacepoints: Vec::new(),
+            clusters: Vec::new(),
+            unclustered: Vec::new(),
+            tracks: Vec::new(),
+            vertex: None,
+            reconstruction: None,
+            flags: Flags::default(),
+            warnings: Vec::new(),
+            error: None,
+        }
+    }
+    // Fill in all the reconstruction steps of the event.
+    fn reconstruct(&mut self, event: MainEvent) {
+        self.trg_timestamp = Some(event.timestamp());
+        if let Some(misbuilt) = event.misbuilt() {
+            self.flags.misbuilt = true;
+            let boards: Vec<_> = misbuilt
+                .alpha16_boards
+                .iter()
+                .map(|board_id| board_id.name())
+                .chain(
+                    misbuilt
+                        .padwing_boards
+                        .iter()
+                        .map(|board_id| board_id.name()),
+                )
+                .collect();
+            self.warnings.push(format!(
+                "misbuilt event (disagreeing boards: {})",
+                boards.join(", ")
+            ));
+        }
+
+        let deconvolution = event.deconvolution();
+        self.wires = deconvolution
+            .wires
+            .into_iter()
+            .map(|(wire, deconvolved)| WireSignal {
+                wire,
+                summary: deconvolved.into(),
+            })
+            .collect();
+        self.pads = deconvolution
+            .pads
+            .into_iter()
+            .map(|(pad, deconvolved)| PadSignal {
+                pad,
+                summary: deconvolved.into(),
+            })
+            .collect();
+        let spikes = |summaries: Vec<&SignalSummary>| {
+            let spikes = summaries.iter().flat_map(|summary| &summary.spikes);
+            (
+                spikes.clone().count(),
+                spikes.filter(|(_, _, matched)| *matched).count(),
+            )
+        };
+        (self.matching.wire_spikes, self.matching.matched_wire_spikes) =
+            spikes(self.wires.iter().map(|wire| &wire.summary).collect());
+        (self.matching.pad_spikes, self.matching.matched_pad_spikes) =
+            spikes(self.pads.iter().map(|pad| &pad.summary).collect());
+
+        let avalanches = event.avalanches();
+        self.matching.avalanches = avalanches.len();
+        self.matching.saturated_avalanches = avalanches.iter().filter(|a| a.saturated).count();
+        if self.matching.saturated_avalanches > 0 {
+            self.flags.saturated = true;
+            self.warnings.push(format!(
+                "`{}` saturated avalanches",
+                self.matching.saturated_avalanches
+            ));
+        }
+        // Same spacepoints as in `MainEvent::vertex`.
+        let spacepoints: Vec<SpacePoint> = avalanches
+            .into_iter()
+            .filter_map(|avalanche| avalanche.try_into().ok())
+            .collect();
+        self.matching.outside_drift_tables = self.matching.avalanches - spacepoints.len();
+        if self.matching.outside_drift_tables > 0 {
+            self.warnings.push(format!(
+                "`{}` avalanches outside the drift tables",
+                self.matching.outside_drift_tables
+            ));
+        }
+        self.spacepoints = spacepoints
+            .iter()
+            .map(|p| {
+                [
+                    p.r.get::<meter>(),
+                    p.phi.get::<radian>(),
+                    p.z.get::<meter>(),
+                ]
+            })
+            .collect();
+
+        let clustering = cluster_spacepoints(spacepoints.clone());
+        self.unclustered = clustering.remainder_indices;
+        for (index, cluster) in clustering.clusters.into_iter().enumerate() {
+            self.clusters.push(cluster.indices().to_vec());
+            match Track::try_from(cluster) {
+                Ok(track) => self.tracks.push(TrackSummary {
+                    cluster: index,
+                    num_points: track.num_points(),
+                    rms_residual: track.rms_residual().get::<meter>(),
+                    model: match track.model() {
+                        TrackModel::Helix { curvature, pitch } => Model::Helix {
+                            curvature: curvature.get::<reciprocal_meter>(),
+                            pitch: pitch.get::<meter>(),
+                        },
+                        TrackModel::Line { direction, point } => Model::Line {
+                            direction,
+                            point: coordinate(point),
+                        },
+                    },
+                }),
+                Err(error) => self
+                    .warnings
+                    .push(format!("failed to fit cluster `{index}` ({error})")),
+            }
+        }
+
+        // This is the exact same vertex as in the output of `alpha-g-vertices`.
+        let (result, reconstruction) =
+            alpha_g_physics::try_vertex_info_from_spacepoints_with_report(spacepoints);
+        self.reconstruction = Some(reconstruction);
+        match result {
+            Ok(vertex) => {
+                self.vertex = vertex.map(|info| Vertex {
+                    position: coordinate(info.position),
+                    sigma: info.sigma().map(|sigma| sigma.map(|s| s.get::<meter>())),
+                    num_tracks: info.tracks.len(),
+                })
+            }
+            Err(error) => {
+                self.flags.reconstruction_error = true;
+                self.error = Some(ErrorSummary::new(&error));
+            }
+        }
+    }
+}
+
+// Human readable summary of a dump.
+fn report(dump: &Dump) -> String {
+    let mm = |v: f64| Length::new::<meter>(v).get::<millimeter>();
+    let mut out = String::new();
+    let mut line = |s: String| writeln!(out, "{s}").unwrap();
+
+    line(format!(
+        "Run {}, event {} (dump version {}, {})",
+        dump.run_number, dump.serial_number, dump.version, dump.package
+    ));
+    line(format!("Source file: {}", dump.source_file.display()));
+    let kinds = [
+        ("ADC", BankKind::Alpha16),
+        ("PWB", BankKind::Padwing),
+        ("TRG", BankKind::Trg),
+        ("other", BankKind::Other),
+    ];
+    let banks: Vec<_> = kinds
+        .into_iter()
+        .filter_map(|(label, kind)| {
+            let banks: Vec<_> = dump
+                .banks
+                .iter()
+                .filter(|bank| BankKind::from(bank.name.as_str()) == kind)
+                .collect();
+            let bytes: usize = banks.iter().map(|bank| bank.size).sum();
+            (!banks.is_empty()).then(|| format!("{} {label} ({bytes} bytes)", banks.len()))
+        })
+        .collect();
+    line(format!("Banks: {}", banks.join(", ")));
+    if let Some(timestamp) = dump.trg_timestamp {
+        line(format!("TRG timestamp: {timestamp}"));
+    }
+
+    let m = &dump.matching;
+    line(format!(
+        "Signals: {} wires ({} spikes, {} matched), {} pads ({} spikes, {} matched)",
+        dump.wires.len(),
+        m.wire_spikes,
+        m.matched_wire_spikes,
+        dump.pads.len(),
+        m.pad_spikes,
+        m.matched_pad_spikes
+    ));
+    line(format!(
+        "Avalanches: {} ({} saturated, {} outside the drift tables)",
+        m.avalanches, m.saturated_avalanches, m.outside_drift_tables
+    ));
+    line(format!(
+        "Spacepoints: {} ({} clusters, {} unclustered)",
+        dump.spacepoints.len(),
+        dump.clusters.len(),
+        dump.unclustered.len()
+    ));
+    line(format!("Tracks: {}", dump.tracks.len()));
+    for track in &dump.tracks {
+        let model = match track.model {
+            Model::Helix { curvature, pitch } => format!(
+                "helix, radius {:.1} mm, pitch {:.1} mm",
+                mm(1.0 / curvature),
+                mm(pitch)
+            ),
+            Model::Line { direction, .. } => format!(
+                "line, direction ({:.3}, {:.3}, {:.3})",
+                direction[0], direction[1], direction[2]
+            ),
+        };
+        line(format!(
+            "  cluster {}: {model}, {} points, rms residual {:.2} mm",
+            track.cluster,
+            track.num_points,
+            mm(track.rms_residual)
+        ));
+    }
+    match &dump.vertex {
+        Some(vertex) => {
+            let [x, y, z] = vertex.position.map(mm);
+            let sigma = match vertex.sigma {
+                Some(sigma) => {
+                    let [sx, sy, sz] = sigma.map(mm);
+                    format!(" ± ({sx:.1}, {sy:.1}, {sz:.1})")
+                }
+                None => String::new(),
+            };
+            line(format!(
+                "Vertex: ({x:.1}, {y:.1}, {z:.1}){sigma} mm from {} tracks",
+                vertex.num_tracks
+            ));
+        }
+        None => line(String::from("Vertex: none")),
+    }
+    if let Some(reconstruction) = &dump.reconstruction {
+        line(format!("Reconstruction: {reconstruction}"));
+        if let Some(reason) = reconstruction.no_vertex_reason() {
+            line(format!("No vertex reason: {reason}"));
+        }
+    }
+
+    let flags = [
+        ("misbuilt", dump.flags.misbuilt),
+        ("saturated", dump.flags.saturated),
+        ("reconstruction_error", dump.flags.reconstruction_error),
+    ]
+    .into_iter()
+    .filter_map(|(name, set)| set.then_some(name))
+    .collect::<Vec<_>>();
+    if !flags.is_empty() {
+        line(format!("Flags: {}", flags.join(", ")));
+    }
+    for warning in &dump.warnings {
+        line(format!("Warning: {warning}"));
+    }
+    if let Some(error) = &dump.error {
+        line(format!("Error ({}): {}", error.kind, error.message));
+    }
+
+    out
+}
+
+// Parse a dump, failing with a clear message if it was written by an
+// incompatible version of this program.
+fn parse_dump(contents: &[u8]) -> Result<Dump> {
+    let value: serde_json::Value =
+        serde_json::from_slice(contents).context("failed to parse json")?;
+    let version = value
+        .get("version")
+        .and_then(serde_json::Value::as_u64)
+        .context("missing dump version")?;
+    ensure!(
+        version == u64::from(DUMP_VERSION),
+        "unsupported dump version `{version}` (expected `{DUMP_VERSION}`)"
+    );
+
+    serde_json::from_value(value).context("failed to parse dump")
+}
+
+fn main() {
+    alpha_g_analysis::cli::exit_on_error(run());
+}
+
+fn run() -> Result<()> {
+    let args: Args = alpha_g_analysis::cli::parse(env!("CARGO_BIN_NAME"));
+    if let Some(path) = args.from_dump {
+        let contents = alpha_g_analysis::read(&path)?;
+        let dump = parse_dump(&contents)
+            .with_context(|| format!("failed to read `{}`", path.display()))?;
+        print!("{}", report(&dump));
+        return Ok(());
+    }
+    // Both are required unless `--from-dump`.
+    let serial_number = args.serial.unwrap();
+    let files = alpha_g_analysis::expand_run_files(args.run_files, args.recursive)?;
+    let (run_number, files) = alpha_g_analysis::sort_run_files(files, args.run_number_override)?;
+
+    let mut dump = None;
+    for file in files {
+        let contents = alpha_g_analysis::open(&file)?;
+        let file_view = midasio::FileView::try_from(&contents[..])
+            .with_context(|| format!("failed to parse `{}`", file.display()))?;
+        let Some(event) = file_view.iter().find(|event| {
+            event.serial_number() == serial_number
+                && matches!(EventId::try_from(event.id()), Ok(EventId::Main))
+        }) else {
+            continue;
+        };
+
+        let banks: Vec<_> = event
+            .into_iter()
+            .map(|bank| (bank.name(), bank.data_slice()))
+            .collect();
+        let mut current = Dump::new(
+            run_number,
+            serial_number,
+            file.clone(),
+            banks
+                .iter()
+                .map(|&(name, data)| Bank {
+                    name: name.to_string(),
+                    size: data.len(),
+                })
+                .collect(),
+        );
+        if let Some(run_number) = args.run_number_override {
+            current
+                .warnings
+                .push(format!("run number overridden to {run_number}"));
+        }
+        current.warnings.extend(
+            alpha_g_physics::calibration::staleness(
+                run_number.into(),
+                alpha_g_physics::calibration::DEFAULT_STALENESS_MARGIN,
+            )
+            .iter()
+            .map(ToString::to_string),
+        );
+        match MainEvent::try_from_banks(run_number, banks) {
+            Ok(event) => current.reconstruct(event),
+            Err(error) => {
+                current.flags.reconstruction_error = true;
+                current.error = Some(ErrorSummary::new(&error));
+            }
+        }
+        dump = Some(current);
+        break;
+    }
+    let Some(dump) = dump else {
+        bail!("no main event with serial number `{serial_number}` in run `{run_number}`");
+    };
+
+    let output = args
+        .output
+        .unwrap_or_else(|| PathBuf::from(format!("R{run_number}_E{serial_number}_debug")));
+    let wtr = alpha_g_analysis::cli::create_output(&output, "json")?;
+    serde_json::to_writer_pretty(wtr, &dump).context("failed to write json dump")?;
+    eprint!("{}", report(&dump));
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests;
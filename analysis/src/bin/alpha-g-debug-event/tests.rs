@@ -0,0 +1,159 @@
+use super::*;
+use alpha_g_physics::{TrackFit, VertexStatus};
+
+fn dump() -> Dump {
+    let mut dump = Dump::new(
+        RunNumber::new(9570),
+        1234567,
+        PathBuf::from("run09570sub000.mid.lz4"),
+        vec![
+            Bank {
+                name: String::from("ATAT"),
+                size: 84,
+            },
+            Bank {
+                name: String::from("B09A"),
+                size: 1000,
+            },
+            Bank {
+                name: String::from("PC00"),
+                size: 500,
+            },
+        ],
+    );
+    dump.trg_timestamp = Some(42);
+    dump.wires.push(WireSignal {
+        wire: TpcWirePosition::try_from(17).unwrap(),
+        summary: SignalSummary {
+            samples: 400,
+            min: -3.0,
+            max: 1200.5,
+            spikes: vec![(1.5e-6, 800.0, true), (2.0e-6, 10.0, false)],
+        },
+    });
+    dump.pads.push(PadSignal {
+        pad: TpcPadPosition::try_from(100).unwrap(),
+        summary: SignalSummary {
+            samples: 500,
+            min: -900.0,
+            max: 5.0,
+            spikes: vec![(1.5e-6, 300.0, true)],
+        },
+    });
+    dump.matching = Matching {
+        wire_spikes: 2,
+        matched_wire_spikes: 1,
+        pad_spikes: 1,
+        matched_pad_spikes: 1,
+        avalanches: 1,
+        saturated_avalanches: 0,
+        outside_drift_tables: 0,
+    };
+    dump.spacepoints = vec![[0.1, 1.0, 0.05]];
+    dump.unclustered = vec![0];
+    dump.tracks.push(TrackSummary {
+        cluster: 0,
+        num_points: 12,
+        rms_residual: 0.0015,
+        model: Model::Helix {
+            curvature: 2.0,
+            pitch: -0.3,
+        },
+    });
+    dump.vertex = Some(Vertex {
+        position: [0.001, -0.002, 0.1],
+        sigma: Some([0.003, 0.003, 0.01]),
+        num_tracks: 2,
+    });
+    dump.reconstruction = Some(ReconstructionReport {
+        num_spacepoints: 1,
+        num_unclustered: 1,
+        vertex: VertexStatus::Converged { num_tracks: 2 },
+        ..Default::default()
+    });
+    dump.flags.misbuilt = true;
+    dump.warnings
+        .push(String::from("misbuilt event (disagreeing boards: 09)"));
+
+    dump
+}
+
+#[test]
+fn dump_json_round_trip() {
+    let dump = dump();
+    let json = serde_json::to_vec_pretty(&dump).unwrap();
+
+    assert_eq!(parse_dump(&json).unwrap(), dump);
+}
+
+#[test]
+fn parse_dump_version() {
+    let mut value = serde_json::to_value(dump()).unwrap();
+    value["version"] = serde_json::json!(DUMP_VERSION + 1);
+    let err = parse_dump(&serde_json::to_vec(&value).unwrap()).unwrap_err();
+    assert!(err.to_string().contains("unsupported dump version"));
+
+    value.as_object_mut().unwrap().remove("version");
+    let err = parse_dump(&serde_json::to_vec(&value).unwrap()).unwrap_err();
+    assert!(err.to_string().contains("missing dump version"));
+
+    assert!(parse_dump(b"not json").is_err());
+}
+
+#[test]
+fn parse_dump_without_reconstruction_report() {
+    let mut value = serde_json::to_value(dump()).unwrap();
+    value.as_object_mut().unwrap().remove("reconstruction");
+
+    assert!(parse_dump(&serde_json::to_vec(&value).unwrap())
+        .unwrap()
+        .reconstruction
+        .is_none());
+}
+
+#[test]
+fn report_from_dump() {
+    let report = report(&dump());
+    let lines: Vec<_> = report.lines().collect();
+
+    assert!(lines[0].starts_with("Run 9570, event 1234567 (dump version 1,"));
+    assert!(lines.contains(&"Banks: 1 ADC (1000 bytes), 1 PWB (500 bytes), 1 TRG (84 bytes)"));
+    assert!(lines.contains(&"Signals: 1 wires (2 spikes, 1 matched), 1 pads (1 spikes, 1 matched)"));
+    assert!(lines.contains(
+        &"  cluster 0: helix, radius 500.0 mm, pitch -300.0 mm, 12 points, rms residual 1.50 mm"
+    ));
+    assert!(lines.contains(&"Vertex: (1.0, -2.0, 100.0) ± (3.0, 3.0, 10.0) mm from 2 tracks"));
+    assert!(lines.contains(
+        &"Reconstruction: 1 spacepoints, 0 clusters [], 1 unclustered, 0 tracks, vertex converged (2 tracks)"
+    ));
+    assert!(!report.contains("No vertex reason"));
+    assert!(lines.contains(&"Flags: misbuilt"));
+    assert!(lines.contains(&"Warning: misbuilt event (disagreeing boards: 09)"));
+    assert!(!report.contains("Error"));
+}
+
+#[test]
+fn report_with_error() {
+    let mut dump = dump();
+    dump.vertex = None;
+    dump.reconstruction = Some(ReconstructionReport {
+        num_spacepoints: 30,
+        cluster_sizes: vec![16, 14],
+        track_fits: vec![TrackFit::FailedFit],
+        ..Default::default()
+    });
+    dump.flags = Flags {
+        reconstruction_error: true,
+        ..Default::default()
+    };
+    dump.error = Some(ErrorSummary {
+        kind: String::from("BadTrack/FailedFit"),
+        message: String::from("bad track fit: failed fit"),
+    });
+    let report = report(&dump);
+
+    assert!(report.contains("Vertex: none\n"));
+    assert!(report.contains("No vertex reason: track_fit_failed\n"));
+    assert!(report.contains("Flags: reconstruction_error\n"));
+    assert!(report.contains("Error (BadTrack/FailedFit): bad track fit: failed fit\n"));
+}
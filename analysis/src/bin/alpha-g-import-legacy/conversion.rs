@@ -0,0 +1,114 @@
+// All the conventions in which the legacy alphasoft vertex output differs from
+// the output of `alpha-g-vertices`. Nothing outside of this module should know
+// about legacy units or origins.
+use anyhow::{ensure, Result};
+use serde::Serialize;
+
+// Branches of the legacy TTree. They map to the `serial_number`, `trg_time`,
+// and `reconstructed_*` columns respectively. The run number is only used to
+// check that all entries are from the same run.
+pub const RUN_BRANCH: &str = "run";
+pub const EVENT_BRANCH: &str = "event";
+pub const TIME_BRANCH: &str = "time";
+pub const X_BRANCH: &str = "x";
+pub const Y_BRANCH: &str = "y";
+pub const Z_BRANCH: &str = "z";
+pub const BRANCHES: [&str; 6] = [
+    RUN_BRANCH,
+    EVENT_BRANCH,
+    TIME_BRANCH,
+    X_BRANCH,
+    Y_BRANCH,
+    Z_BRANCH,
+];
+
+// Legacy lengths are in millimeters; ours are in meters.
+const METERS_PER_LEGACY_LENGTH: f64 = 1e-3;
+
+// Conventions that are not fixed for all the legacy files, and have to be
+// given explicitly.
+#[derive(Clone, Copy, Debug)]
+pub struct Conventions {
+    // Position (in meters) of the legacy `z = 0` in our coordinates. The
+    // legacy origin is not at the center of the TPC.
+    pub z_offset: f64,
+}
+
+impl Conventions {
+    // Transverse coordinates only change units.
+    pub fn transverse(&self, legacy: f64) -> f64 {
+        legacy * METERS_PER_LEGACY_LENGTH
+    }
+
+    pub fn z(&self, legacy: f64) -> f64 {
+        legacy * METERS_PER_LEGACY_LENGTH + self.z_offset
+    }
+
+    // Inverse of `transverse` and `z`.
+    pub fn legacy_transverse(&self, meters: f64) -> f64 {
+        meters / METERS_PER_LEGACY_LENGTH
+    }
+
+    pub fn legacy_z(&self, meters: f64) -> f64 {
+        (meters - self.z_offset) / METERS_PER_LEGACY_LENGTH
+    }
+}
+
+// Single entry of the legacy TTree.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LegacyEntry {
+    pub run: i32,
+    pub event: i32,
+    // Seconds since the legacy clock started. This is not the TRG clock, so
+    // only differences between entries are meaningful.
+    pub time: f64,
+    // Non-finite coordinates mean that there is no vertex.
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+// Same columns as the (default) output of `alpha-g-vertices`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct Row {
+    pub serial_number: u32,
+    pub trg_time: Option<f64>,
+    pub reconstructed_x: Option<f64>,
+    pub reconstructed_y: Option<f64>,
+    pub reconstructed_z: Option<f64>,
+    // Not available in the legacy output.
+    pub reconstructed_sigma_x: Option<f64>,
+    pub reconstructed_sigma_y: Option<f64>,
+    pub reconstructed_sigma_z: Option<f64>,
+}
+
+// Convert all the entries of a single run. Same as in `alpha-g-vertices`, the
+// `trg_time` is relative to the first event.
+pub fn convert(entries: &[LegacyEntry], conventions: Conventions) -> Result<Vec<Row>> {
+    let Some(first) = entries.first() else {
+        return Ok(Vec::new());
+    };
+    let mut rows = Vec::with_capacity(entries.len());
+    for entry in entries {
+        ensure!(
+            entry.run == first.run,
+            "multiple runs in the input (`{}` and `{}`)",
+            first.run,
+            entry.run
+        );
+        let serial_number = u32::try_from(entry.event)
+            .map_err(|_| anyhow::anyhow!("negative event number `{}`", entry.event))?;
+        let has_vertex = entry.x.is_finite() && entry.y.is_finite() && entry.z.is_finite();
+
+        rows.push(Row {
+            serial_number,
+            trg_time: Some(entry.time - first.time),
+            reconstructed_x: has_vertex.then(|| conventions.transverse(entry.x)),
+            reconstructed_y: has_vertex.then(|| conventions.transverse(entry.y)),
+            reconstructed_z: has_vertex.then(|| conventions.z(entry.z)),
+            ..Default::default()
+        });
+    }
+
+    Ok(rows)
+}
@@ -0,0 +1,116 @@
+use crate::conversion::{Conventions, LegacyEntry, BRANCHES};
+use anyhow::{anyhow, ensure, Context, Result};
+use clap::Parser;
+use oxyroot::RootFile;
+use std::io::Write;
+use std::path::PathBuf;
+
+// Units, origins, and branch names of the legacy output.
+mod conversion;
+
+#[derive(Parser)]
+#[command(version)]
+/// Convert the vertices of a legacy alphasoft ROOT file to the CSV output of
+/// `alpha-g-vertices`
+struct Args {
+    /// ROOT file with the legacy vertices of a single run
+    file: PathBuf,
+    /// Name of the TTree with the vertices
+    #[arg(long, default_value = "vertices")]
+    tree: String,
+    /// Position (in meters) of the legacy `z = 0` in the `alpha-g-vertices`
+    /// coordinates
+    #[arg(long, value_name = "METERS", allow_hyphen_values = true)]
+    z_offset: f64,
+    /// Write the output to `OUTPUT.csv`, or to stdout if `-` [default:
+    /// `R<run_number>_legacy_vertices.csv`]
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+fn main() {
+    alpha_g_analysis::cli::exit_on_error(run());
+}
+
+fn run() -> Result<()> {
+    let args: Args = alpha_g_analysis::cli::parse(env!("CARGO_BIN_NAME"));
+    let conventions = Conventions {
+        z_offset: args.z_offset,
+    };
+
+    let mut file = RootFile::open(&args.file)
+        .map_err(|error| anyhow!("{error}"))
+        .with_context(|| format!("failed to open `{}`", args.file.display()))?;
+    let tree = file
+        .get_tree(&args.tree)
+        .map_err(|error| anyhow!("{error}"))
+        .with_context(|| format!("failed to read tree `{}`", args.tree))?;
+    for branch in tree.branches() {
+        if !BRANCHES.contains(&branch.name()) {
+            eprintln!("Warning: ignoring unmapped branch `{}`", branch.name());
+        }
+    }
+    macro_rules! read_branch {
+        ($name:expr, $ty:ty) => {
+            tree.branch($name)
+                .with_context(|| format!("missing branch `{}`", $name))?
+                .as_iter::<$ty>()
+                .map_err(|error| anyhow!("{error}"))
+                .with_context(|| format!("failed to read branch `{}`", $name))?
+                .collect::<Vec<_>>()
+        };
+    }
+    let run = read_branch!(conversion::RUN_BRANCH, i32);
+    let event = read_branch!(conversion::EVENT_BRANCH, i32);
+    let time = read_branch!(conversion::TIME_BRANCH, f64);
+    let x = read_branch!(conversion::X_BRANCH, f64);
+    let y = read_branch!(conversion::Y_BRANCH, f64);
+    let z = read_branch!(conversion::Z_BRANCH, f64);
+    ensure!(
+        [event.len(), time.len(), x.len(), y.len(), z.len()]
+            .iter()
+            .all(|&len| len == run.len()),
+        "branches have different number of entries"
+    );
+    let entries: Vec<_> = (0..run.len())
+        .map(|i| LegacyEntry {
+            run: run[i],
+            event: event[i],
+            time: time[i],
+            x: x[i],
+            y: y[i],
+            z: z[i],
+        })
+        .collect();
+    let run_number = entries
+        .first()
+        .context("no entries in the legacy tree")?
+        .run;
+    let rows = conversion::convert(&entries, conventions)?;
+
+    let output = args
+        .output
+        .unwrap_or_else(|| PathBuf::from(format!("R{run_number}_legacy_vertices")));
+    let mut wtr = alpha_g_analysis::cli::create_output(&output, "csv")?;
+    wtr.write_all(
+        format!(
+            "# {} {}\n# {}\n# WARNING: imported from legacy alphasoft output {}\n",
+            env!("CARGO_PKG_NAME"),
+            env!("CARGO_PKG_VERSION"),
+            std::env::args().collect::<Vec<_>>().join(" "),
+            args.file.display()
+        )
+        .as_bytes(),
+    )
+    .context("failed to write csv header")?;
+    let mut wtr = csv::Writer::from_writer(wtr);
+    for row in rows {
+        wtr.serialize(row).context("failed to write csv row")?;
+    }
+    wtr.flush().context("failed to flush csv data")?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,92 @@
+use super::*;
+use crate::conversion::{convert, Row};
+
+const CONVENTIONS: Conventions = Conventions { z_offset: -0.5 };
+
+// Legacy entries (run, event, time, x, y, z) as written by alphasoft. The
+// second event has no vertex.
+const LEGACY_FIXTURE: [(i32, i32, f64, f64, f64, f64); 3] = [
+    (9570, 1, 12.5, 10.0, -20.0, 500.0),
+    (9570, 2, 12.75, f64::NAN, f64::NAN, f64::NAN),
+    (9570, 4, 14.5, -3.5, 0.0, 1000.0),
+];
+
+fn entries() -> Vec<LegacyEntry> {
+    LEGACY_FIXTURE
+        .iter()
+        .map(|&(run, event, time, x, y, z)| LegacyEntry {
+            run,
+            event,
+            time,
+            x,
+            y,
+            z,
+        })
+        .collect()
+}
+
+#[test]
+fn convert_fixture() {
+    let rows = convert(&entries(), CONVENTIONS).unwrap();
+
+    assert_eq!(
+        rows,
+        [
+            Row {
+                serial_number: 1,
+                trg_time: Some(0.0),
+                reconstructed_x: Some(0.01),
+                reconstructed_y: Some(-0.02),
+                reconstructed_z: Some(0.0),
+                ..Default::default()
+            },
+            Row {
+                serial_number: 2,
+                trg_time: Some(0.25),
+                ..Default::default()
+            },
+            Row {
+                serial_number: 4,
+                trg_time: Some(2.0),
+                reconstructed_x: Some(-0.0035),
+                reconstructed_y: Some(0.0),
+                reconstructed_z: Some(0.5),
+                ..Default::default()
+            },
+        ]
+    );
+}
+
+#[test]
+fn conversion_round_trip() {
+    for entry in entries().into_iter().filter(|entry| entry.x.is_finite()) {
+        let x = CONVENTIONS.legacy_transverse(CONVENTIONS.transverse(entry.x));
+        let y = CONVENTIONS.legacy_transverse(CONVENTIONS.transverse(entry.y));
+        let z = CONVENTIONS.legacy_z(CONVENTIONS.z(entry.z));
+
+        assert!((x - entry.x).abs() < 1e-9);
+        assert!((y - entry.y).abs() < 1e-9);
+        assert!((z - entry.z).abs() < 1e-9);
+    }
+}
+
+#[test]
+fn convert_mixed_runs() {
+    let mut entries = entries();
+    entries[2].run = 9571;
+
+    assert!(convert(&entries, CONVENTIONS).is_err());
+}
+
+#[test]
+fn convert_negative_event() {
+    let mut entries = entries();
+    entries[0].event = -1;
+
+    assert!(convert(&entries, CONVENTIONS).is_err());
+}
+
+#[test]
+fn convert_empty() {
+    assert!(convert(&[], CONVENTIONS).unwrap().is_empty());
+}
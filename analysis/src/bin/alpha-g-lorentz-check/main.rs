@@ -0,0 +1,303 @@
+use alpha_g_analysis::statistics::{BinMean, BinnedMean2d, Binning};
+use alpha_g_analysis::EventCensus;
+use alpha_g_detector::midas::EventId;
+use alpha_g_detector::padwing::map::DETECTOR_LENGTH;
+use alpha_g_physics::reconstruction::{cluster_spacepoints, FieldConfiguration, Track};
+use alpha_g_physics::{DecodeOptions, MainEvent, RunCalibration, SpacePoint};
+use anyhow::{ensure, Context, Result};
+use clap::Parser;
+use indicatif::{
+    MultiProgress, ParallelProgressIterator, ProgressBar, ProgressDrawTarget, ProgressStyle,
+};
+use rayon::prelude::*;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use uom::si::angle::radian;
+use uom::si::length::meter;
+
+#[derive(Parser)]
+#[command(version)]
+/// Measure the azimuthal residuals of the spacepoints of clean single-track
+/// events (binned in `z` and `r`) to validate the Lorentz angle correction of a
+/// single run
+struct Args {
+    /// MIDAS files (or directories with them) from the run you want to reconstruct
+    #[arg(required = true)]
+    files: Vec<PathBuf>,
+    /// Also search for MIDAS files in subdirectories of the input directories
+    #[arg(long)]
+    recursive: bool,
+    /// Write the output to `OUTPUT.json` and `OUTPUT.csv` [default:
+    /// `R<run_number>_lorentz_check`]
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    /// Number of bins along `z` (over the full length of the detector)
+    #[arg(long, default_value_t = 20)]
+    z_bins: usize,
+    /// Number of bins along `r` (over the drift region)
+    #[arg(long, default_value_t = 8)]
+    r_bins: usize,
+    /// Minimum number of spacepoints in the track of a clean event
+    #[arg(long, default_value_t = 20)]
+    min_points: usize,
+    /// Print detailed information about errors (if any)
+    #[arg(short, long)]
+    verbose: bool,
+}
+
+// Mean residual (in radians) of a single (z, r) bin. Same fields in the JSON
+// table and in the CSV rows.
+#[derive(Debug, serde::Serialize)]
+struct Bin {
+    z: f64,
+    r: f64,
+    count: u64,
+    mean: f64,
+    error: Option<f64>,
+}
+
+impl From<BinMean> for Bin {
+    fn from(bin: BinMean) -> Self {
+        Self {
+            z: bin.x,
+            r: bin.y,
+            count: bin.count,
+            mean: bin.mean,
+            error: bin.error,
+        }
+    }
+}
+
+#[derive(Debug, serde::Serialize)]
+struct Table {
+    run_number: u32,
+    // Number of clean single-track events, and the total number of residuals
+    // (inside and outside of the binned range).
+    events: u64,
+    residuals: u64,
+    z: Binning,
+    r: Binning,
+    bins: Vec<Bin>,
+}
+
+// Azimuthal residual of a single spacepoint. Lengths are in meters and `phi`
+// is in radians.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Residual {
+    z: f64,
+    r: f64,
+    phi: f64,
+}
+
+// Return the residuals of all the spacepoints of the track in a clean event.
+// An event is clean if its spacepoints form exactly one cluster with
+// at least `min_points` points, and the cluster can be fitted to a track.
+// Spacepoints that are not part of the cluster (i.e. noise) are ignored.
+fn event_residuals(
+    points: Vec<SpacePoint>,
+    min_points: usize,
+    field: FieldConfiguration,
+) -> Option<Vec<Residual>> {
+    let mut clusters = cluster_spacepoints(points).clusters;
+    if clusters.len() != 1 {
+        return None;
+    }
+    let cluster = clusters.pop().unwrap();
+    if cluster.points().len() < min_points {
+        return None;
+    }
+    let points = cluster.points().to_vec();
+    let track = Track::try_from_cluster(cluster, field).ok()?;
+
+    Some(
+        points
+            .iter()
+            .filter_map(|point| {
+                let phi = track.phi_residual(point)?;
+                Some(Residual {
+                    z: point.z.get::<meter>(),
+                    r: point.r.get::<meter>(),
+                    phi: phi.get::<radian>(),
+                })
+            })
+            .collect(),
+    )
+}
+
+// Write the provenance header of the CSV output.
+fn write_provenance(wtr: &mut dyn Write) -> Result<()> {
+    wtr.write_all(
+        format!(
+            "# {} {}\n# {}\n",
+            env!("CARGO_PKG_NAME"),
+            env!("CARGO_PKG_VERSION"),
+            std::env::args().collect::<Vec<_>>().join(" ")
+        )
+        .as_bytes(),
+    )
+    .context("failed to write csv header")
+}
+
+fn main() {
+    alpha_g_analysis::cli::exit_on_error(run());
+}
+
+fn run() -> Result<()> {
+    // Same as `alpha-g-vertices`; the default 2 MiB stack size for threads is
+    // not enough to reconstruct the avalanches.
+    rayon::ThreadPoolBuilder::new()
+        .stack_size(4 * 1024 * 1024)
+        .build_global()
+        .context("failed to initialize global thread pool")?;
+
+    let args: Args = alpha_g_analysis::cli::parse(env!("CARGO_BIN_NAME"));
+    ensure!(
+        args.z_bins > 0 && args.r_bins > 0,
+        "`--z-bins` and `--r-bins` must be positive"
+    );
+    ensure!(
+        args.output.as_deref() != Some(Path::new(alpha_g_analysis::cli::STDOUT_PATH)),
+        "cannot write both the json and the csv output to stdout"
+    );
+    let files = alpha_g_analysis::expand_run_files(args.files, args.recursive)?;
+    let (run_number, files) = alpha_g_analysis::sort_run_files(files, None)?;
+    let calibration = RunCalibration::new(run_number);
+
+    let radius_range = alpha_g_physics::drift_radius_range();
+    let mut binned = BinnedMean2d::new(
+        Binning {
+            min: -DETECTOR_LENGTH / 2.0,
+            max: DETECTOR_LENGTH / 2.0,
+            bins: args.z_bins,
+        },
+        Binning {
+            min: radius_range.start().get::<meter>(),
+            max: radius_range.end().get::<meter>(),
+            bins: args.r_bins,
+        },
+    );
+
+    let multi_progress = MultiProgress::with_draw_target(ProgressDrawTarget::stderr_with_hz(1));
+    let tp_bar = multi_progress.add(
+        ProgressBar::new(files.len().try_into().unwrap())
+            .with_style(ProgressStyle::with_template("[{pos}/{len}] Processing").unwrap()),
+    );
+    tp_bar.tick();
+
+    let mut census = EventCensus::default();
+    let mut num_errors = 0;
+    let mut num_events = 0;
+    let mut num_residuals = 0;
+    let mut previous_final_timestamp = None;
+    for file in files {
+        let contents = alpha_g_analysis::open(&file)?;
+        let file_view = midasio::FileView::try_from(&contents[..])
+            .with_context(|| format!("failed to parse `{}`", file.display()))?;
+        if let Some(previous_final_timestamp) = previous_final_timestamp {
+            ensure!(
+                file_view.initial_timestamp() - previous_final_timestamp <= 1,
+                "missing file before `{}`",
+                file.display()
+            );
+        }
+        previous_final_timestamp = Some(file_view.final_timestamp());
+        census.extend(file_view.iter().map(|event| event.id()));
+
+        let pb = multi_progress.add(
+            ProgressBar::new(file_view.iter().len().try_into().unwrap())
+                .with_style(
+                    ProgressStyle::with_template("[{bar:25}] {percent}%, ETA: {eta}    ({msg})")
+                        .unwrap()
+                        .progress_chars("=> "),
+                )
+                .with_message(format!("{}", file.display())),
+        );
+        let file_residuals: Vec<_> = file_view
+            .into_par_iter()
+            .progress_with(pb.clone())
+            .filter(|event| matches!(EventId::try_from(event.id()), Ok(EventId::Main)))
+            .map(|event| {
+                let serial_number = event.serial_number();
+
+                let banks = event
+                    .into_iter()
+                    .map(|bank| (bank.name(), bank.data_slice()));
+                let event = match MainEvent::try_from_banks_with(
+                    &calibration,
+                    banks,
+                    &DecodeOptions::default(),
+                ) {
+                    Ok((event, _)) => event,
+                    Err(error) => {
+                        if args.verbose {
+                            pb.println(format!("Error in event `{serial_number}`: {error}"));
+                        }
+                        return Err(());
+                    }
+                };
+                let points = event
+                    .avalanches()
+                    .into_iter()
+                    .filter_map(|avalanche| SpacePoint::try_from(avalanche).ok())
+                    .collect();
+
+                Ok(event_residuals(
+                    points,
+                    args.min_points,
+                    calibration.field(),
+                ))
+            })
+            .collect();
+        for residuals in file_residuals {
+            match residuals {
+                Ok(Some(residuals)) => {
+                    num_events += 1;
+                    for residual in residuals {
+                        num_residuals += 1;
+                        binned.add(residual.z, residual.r, residual.phi);
+                    }
+                }
+                Ok(None) => {}
+                Err(()) => num_errors += 1,
+            }
+        }
+        tp_bar.set_style(
+            ProgressStyle::with_template("[{pos}/{len}] Processing, ETA: {eta}").unwrap(),
+        );
+        tp_bar.inc(1);
+    }
+    tp_bar.finish_and_clear();
+    alpha_g_analysis::cli::exit_if_no_main_events(&census);
+    if num_errors > 0 {
+        eprintln!("Warning: failed to reconstruct `{num_errors}` events");
+    }
+    eprintln!("Clean single-track events: `{num_events}` (`{num_residuals}` residuals)");
+
+    let table = Table {
+        run_number,
+        events: num_events,
+        residuals: num_residuals,
+        z: binned.x_binning(),
+        r: binned.y_binning(),
+        bins: binned.bins().into_iter().map(Bin::from).collect(),
+    };
+    let output = args
+        .output
+        .unwrap_or_else(|| PathBuf::from(format!("R{run_number}_lorentz_check")));
+
+    let wtr = alpha_g_analysis::cli::create_output(&output, "json")?;
+    serde_json::to_writer_pretty(wtr, &table).context("failed to write json table")?;
+
+    let mut wtr = alpha_g_analysis::cli::create_output(&output, "csv")?;
+    write_provenance(&mut wtr)?;
+    let mut wtr = csv::Writer::from_writer(wtr);
+    for bin in table.bins {
+        wtr.serialize(bin).context("failed to write csv row")?;
+    }
+    wtr.flush().context("failed to flush csv data")?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests;
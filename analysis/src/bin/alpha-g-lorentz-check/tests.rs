@@ -0,0 +1,107 @@
+use super::*;
+use std::f64::consts::PI;
+use uom::si::f64::{Angle, Length};
+
+// Radial straight track in the drift region (`r` in [0.11, 0.17) m), with an
+// extra rotation `delta` (in radians) injected into the spacepoints with `r` in
+// [0.14, 0.15) m.
+fn rotated_track(delta: f64) -> Vec<SpacePoint> {
+    // Align the track with the center of a bin of the default Hough transform
+    // used to find clusters, such that the injected rotation doesn't split the
+    // track into multiple clusters.
+    let phi = 68.5 * 2.0 * PI / 230.0 - PI / 2.0;
+
+    (0..120)
+        .map(|i| {
+            let r = 0.11 + 0.0005 * (f64::from(i) + 0.5);
+            let extra = if (0.14..0.15).contains(&r) {
+                delta
+            } else {
+                0.0
+            };
+            SpacePoint {
+                r: Length::new::<meter>(r),
+                phi: Angle::new::<radian>(phi + extra),
+                z: Length::new::<meter>(0.05 + 0.3 * (r - 0.11)),
+            }
+        })
+        .collect()
+}
+
+fn r_binned(residuals: Vec<Residual>) -> Vec<BinMean> {
+    let mut binned = BinnedMean2d::new(
+        Binning {
+            min: -1.0,
+            max: 1.0,
+            bins: 1,
+        },
+        Binning {
+            min: 0.11,
+            max: 0.17,
+            bins: 6,
+        },
+    );
+    for residual in residuals {
+        assert!(binned.add(residual.z, residual.r, residual.phi));
+    }
+
+    binned.bins()
+}
+
+#[test]
+fn event_residuals_no_rotation() {
+    let residuals = event_residuals(rotated_track(0.0), 20, FieldConfiguration::Off).unwrap();
+    assert_eq!(residuals.len(), 120);
+
+    for bin in r_binned(residuals) {
+        assert_eq!(bin.count, 20);
+        assert!(bin.mean.abs() < 1e-6);
+    }
+}
+
+#[test]
+fn event_residuals_recover_rotation() {
+    for delta in [0.005, -0.005] {
+        let residuals = event_residuals(rotated_track(delta), 20, FieldConfiguration::Off).unwrap();
+        let bins = r_binned(residuals);
+        // The track fit absorbs a small part of the rotation, so compare the
+        // rotated bin against the mean of all the others.
+        let others = bins
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| i != 3)
+            .map(|(_, bin)| bin.mean)
+            .sum::<f64>()
+            / 5.0;
+        let recovered = bins[3].mean - others;
+
+        assert!((recovered - delta).abs() < 0.2 * delta.abs());
+    }
+}
+
+#[test]
+fn event_residuals_too_few_points() {
+    assert!(event_residuals(rotated_track(0.0), 121, FieldConfiguration::Off).is_none());
+}
+
+#[test]
+fn event_residuals_no_cluster() {
+    assert!(event_residuals(Vec::new(), 20, FieldConfiguration::Off).is_none());
+}
+
+#[test]
+fn bin_from_bin_mean() {
+    let bin = Bin::from(BinMean {
+        x: 0.5,
+        y: 0.15,
+        count: 3,
+        mean: 0.01,
+        error: None,
+    });
+
+    assert_eq!(bin.z, 0.5);
+    assert_eq!(bin.r, 0.15);
+    assert_eq!(bin.count, 3);
+    assert_eq!(bin.mean, 0.01);
+    assert_eq!(bin.error, None);
+}
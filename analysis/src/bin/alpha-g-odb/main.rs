@@ -17,11 +17,14 @@ struct Args {
     r#final: bool,
 }
 
-fn main() -> Result<()> {
-    let args = Args::parse();
+fn main() {
+    alpha_g_analysis::cli::exit_on_error(run());
+}
+
+fn run() -> Result<()> {
+    let args: Args = alpha_g_analysis::cli::parse(env!("CARGO_BIN_NAME"));
 
-    let contents = alpha_g_analysis::read(&args.file)
-        .with_context(|| format!("failed to read `{}`", args.file.display()))?;
+    let contents = alpha_g_analysis::read(&args.file)?;
     let file_view = midasio::FileView::try_from(&contents[..])
         .with_context(|| format!("failed to parse `{}`", args.file.display()))?;
     let odb = if args.r#final {
@@ -0,0 +1,305 @@
+use alpha_g_analysis::{find_rate_drops, EventCensus, RateDrop, RateDropSettings, TrgScalers};
+use alpha_g_detector::midas::EventId;
+use alpha_g_detector::padwing::map::TPC_PAD_COLUMNS;
+use alpha_g_detector::run::RunNumber;
+use alpha_g_physics::{DecodeOptions, MainEvent, RunCalibration};
+use anyhow::{ensure, Context, Result};
+use clap::Parser;
+use indicatif::{
+    MultiProgress, ParallelProgressIterator, ProgressBar, ProgressDrawTarget, ProgressStyle,
+};
+use rayon::prelude::*;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+#[derive(Parser)]
+#[command(version)]
+/// Find pad columns whose charge collapses mid-run (e.g. an HV sector trip)
+struct Args {
+    /// MIDAS files (or directories with them) from the run you want to inspect
+    #[arg(required = true)]
+    files: Vec<PathBuf>,
+    /// Also search for MIDAS files in subdirectories of the input directories
+    #[arg(long)]
+    recursive: bool,
+    /// Write the output to `OUTPUT.csv`, or to stdout if `-` [default:
+    /// `R<run_number>_pad_charge.csv`]
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    /// Also write the time ranges of all the drops to `DROPS_OUTPUT.csv`
+    /// (e.g. to veto them in an analysis)
+    #[arg(long)]
+    drops_output: Option<PathBuf>,
+    /// Width (in seconds of TRG time) of the time bins
+    #[arg(long, value_name = "SECONDS", default_value_t = 60.0)]
+    bin_width: f64,
+    /// Number of previous bins used as the baseline of each column
+    #[arg(long, value_name = "BINS", default_value_t = RateDropSettings::default().window)]
+    window: usize,
+    /// Report a drop if the charge rate of a column is more than this factor
+    /// below its baseline
+    #[arg(long, default_value_t = RateDropSettings::default().factor)]
+    factor: f64,
+    /// Largest relative change of the charge rate of all the other columns at
+    /// which a drop is still reported
+    #[arg(
+        long,
+        value_name = "FRACTION",
+        default_value_t = RateDropSettings::default().max_common_change
+    )]
+    max_common_change: f64,
+    /// Print detailed information about errors (if any)
+    #[arg(short, long)]
+    verbose: bool,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct Row {
+    time: f64,
+    column: usize,
+    events: u64,
+    charge_rate: Option<f64>,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct DropRow {
+    column: usize,
+    start: f64,
+    end: f64,
+    charge_rate: f64,
+    baseline: f64,
+}
+
+// Total pad charge of each column (and number of events) in bins of TRG time.
+#[derive(Clone, Debug, PartialEq)]
+struct ChargeBins {
+    width: f64,
+    // Latest TRG time of all the events.
+    end: f64,
+    events: Vec<u64>,
+    charges: Vec<[f64; TPC_PAD_COLUMNS]>,
+}
+
+impl ChargeBins {
+    fn new(width: f64) -> Self {
+        Self {
+            width,
+            end: 0.0,
+            events: Vec::new(),
+            charges: Vec::new(),
+        }
+    }
+
+    fn add(&mut self, trg_time: f64, charge: &[f64; TPC_PAD_COLUMNS]) {
+        let bin = (trg_time / self.width) as usize;
+        if bin >= self.events.len() {
+            self.events.resize(bin + 1, 0);
+            self.charges.resize(bin + 1, [0.0; TPC_PAD_COLUMNS]);
+        }
+        self.events[bin] += 1;
+        for (total, charge) in self.charges[bin].iter_mut().zip(charge) {
+            *total += charge;
+        }
+        self.end = self.end.max(trg_time);
+    }
+
+    // Charge per second of each column (`rates[column][bin]`). Bins without
+    // events are `None`. The last bin is only as long as the run.
+    fn rates(&self) -> Vec<Vec<Option<f64>>> {
+        let exposure: Vec<_> = (0..self.events.len())
+            .map(|bin| {
+                let start = bin as f64 * self.width;
+                (self.events[bin] > 0 && self.end > start).then(|| self.width.min(self.end - start))
+            })
+            .collect();
+
+        (0..TPC_PAD_COLUMNS)
+            .map(|column| {
+                self.charges
+                    .iter()
+                    .zip(&exposure)
+                    .map(|(charges, exposure)| exposure.map(|exposure| charges[column] / exposure))
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+fn create(path: &Path) -> Result<Box<dyn Write + Send>> {
+    let mut wtr = alpha_g_analysis::cli::create_output(path, "csv")?;
+    wtr.write_all(
+        format!(
+            "# {} {}\n# {}\n",
+            env!("CARGO_PKG_NAME"),
+            env!("CARGO_PKG_VERSION"),
+            std::env::args().collect::<Vec<_>>().join(" ")
+        )
+        .as_bytes(),
+    )
+    .context("failed to write csv header")?;
+
+    Ok(wtr)
+}
+
+fn main() {
+    alpha_g_analysis::cli::exit_on_error(run());
+}
+
+fn run() -> Result<()> {
+    let args: Args = alpha_g_analysis::cli::parse(env!("CARGO_BIN_NAME"));
+    ensure!(
+        args.bin_width.is_finite() && args.bin_width > 0.0,
+        "`--bin-width` must be a positive number of seconds"
+    );
+    let files = alpha_g_analysis::expand_run_files(args.files, args.recursive)?;
+    let (run_number, files) = alpha_g_analysis::sort_run_files(files, None)?;
+    let calibration = RunCalibration::new(run_number);
+    // Progress bars were flickering with the default draw target rate.
+    let multi_progress = MultiProgress::with_draw_target(ProgressDrawTarget::stderr_with_hz(1));
+    let tp_bar = multi_progress.add(
+        ProgressBar::new(files.len().try_into().unwrap())
+            .with_style(ProgressStyle::with_template("[{pos}/{len}] Processing").unwrap()),
+    );
+    tp_bar.tick();
+
+    let mut bins = ChargeBins::new(args.bin_width);
+    let mut scalers = TrgScalers::default();
+    let mut census = EventCensus::default();
+    let mut failed = 0;
+    let mut previous_final_timestamp = None;
+    for file in files {
+        let contents = alpha_g_analysis::read(&file)?;
+        let file_view = midasio::FileView::try_from(&contents[..])
+            .with_context(|| format!("failed to parse `{}`", file.display()))?;
+        if let Some(previous_final_timestamp) = previous_final_timestamp {
+            ensure!(
+                file_view.initial_timestamp() - previous_final_timestamp <= 1,
+                "missing file before `{}`",
+                file.display()
+            );
+        }
+        previous_final_timestamp = Some(file_view.final_timestamp());
+        census.extend(file_view.iter().map(|event| event.id()));
+
+        let pb = multi_progress.add(
+            ProgressBar::new(file_view.iter().len().try_into().unwrap())
+                .with_style(
+                    ProgressStyle::with_template("[{bar:25}] {percent}%, ETA: {eta}    ({msg})")
+                        .unwrap()
+                        .progress_chars("=> "),
+                )
+                .with_message(format!("{}", file.display())),
+        );
+        let mut events: Vec<_> = file_view
+            .into_par_iter()
+            .progress_with(pb.clone())
+            .filter(|event| matches!(EventId::try_from(event.id()), Ok(EventId::Main)))
+            .map(|event| {
+                let serial_number = event.serial_number();
+                let banks: Vec<_> = event
+                    .into_iter()
+                    .map(|bank| (bank.name(), bank.data_slice()))
+                    .collect();
+                let trg_packet = alpha_g_analysis::trg_packet(banks.iter().copied()).ok();
+                let charge = match MainEvent::try_from_banks_with(
+                    &calibration,
+                    banks,
+                    &DecodeOptions::default(),
+                ) {
+                    Ok((event, _)) => Some(event.pad_column_charge()),
+                    Err(error) => {
+                        if args.verbose {
+                            pb.println(format!("Error in event `{serial_number}`: {error}"));
+                        }
+                        None
+                    }
+                };
+
+                (serial_number, trg_packet, charge)
+            })
+            .collect();
+        // The TRG time has to be accumulated in trigger order.
+        events.sort_unstable_by_key(|&(serial_number, _, _)| serial_number);
+        for (serial_number, trg_packet, charge) in events {
+            let row = scalers.next_row(serial_number, trg_packet.as_ref());
+            match (row.trg_time, charge) {
+                (Some(trg_time), Some(charge)) => bins.add(trg_time, &charge),
+                _ => failed += 1,
+            }
+        }
+
+        tp_bar.set_style(
+            ProgressStyle::with_template("[{pos}/{len}] Processing, ETA: {eta}").unwrap(),
+        );
+        tp_bar.inc(1);
+    }
+    tp_bar.finish_and_clear();
+    alpha_g_analysis::cli::exit_if_no_main_events(&census);
+    if failed > 0 {
+        eprintln!("Warning: skipped `{failed}` main events without a TRG time or pad charge");
+    }
+
+    let settings = RateDropSettings {
+        window: args.window,
+        factor: args.factor,
+        max_common_change: args.max_common_change,
+    };
+    let rates = bins.rates();
+    let drops = find_rate_drops(&rates, &settings);
+    report(run_number, &drops, args.bin_width);
+
+    let output = args
+        .output
+        .unwrap_or_else(|| PathBuf::from(format!("R{run_number}_pad_charge")));
+    let mut wtr = csv::Writer::from_writer(create(&output)?);
+    for (bin, &events) in bins.events.iter().enumerate() {
+        for (column, rates) in rates.iter().enumerate() {
+            wtr.serialize(Row {
+                time: bin as f64 * args.bin_width,
+                column,
+                events,
+                charge_rate: rates[bin],
+            })
+            .context("failed to write csv row")?;
+        }
+    }
+    wtr.flush().context("failed to flush csv data")?;
+
+    if let Some(drops_output) = args.drops_output {
+        let mut wtr = csv::Writer::from_writer(create(&drops_output)?);
+        for drop in drops {
+            wtr.serialize(drop_row(&drop, args.bin_width))
+                .context("failed to write csv row")?;
+        }
+        wtr.flush().context("failed to flush csv data")?;
+    }
+
+    Ok(())
+}
+
+fn drop_row(drop: &RateDrop, bin_width: f64) -> DropRow {
+    DropRow {
+        column: drop.channel,
+        start: drop.bins.start as f64 * bin_width,
+        end: drop.bins.end as f64 * bin_width,
+        charge_rate: drop.rate,
+        baseline: drop.baseline,
+    }
+}
+
+fn report(run_number: RunNumber, drops: &[RateDrop], bin_width: f64) {
+    eprintln!(
+        "Found `{}` pad column charge drops in run `{run_number}`",
+        drops.len()
+    );
+    for drop in drops {
+        let row = drop_row(drop, bin_width);
+        eprintln!(
+            "  column `{}` from {:.0} s to {:.0} s: charge rate {:.3e} (baseline {:.3e})",
+            row.column, row.start, row.end, row.charge_rate, row.baseline
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,58 @@
+use super::*;
+
+fn charge(column: usize, value: f64) -> [f64; TPC_PAD_COLUMNS] {
+    let mut charge = [1.0; TPC_PAD_COLUMNS];
+    charge[column] = value;
+    charge
+}
+
+#[test]
+fn charge_bins_add() {
+    let mut bins = ChargeBins::new(60.0);
+    bins.add(10.0, &charge(3, 5.0));
+    bins.add(59.9, &charge(3, 1.0));
+    bins.add(150.0, &charge(0, 2.0));
+
+    assert_eq!(bins.events, [2, 0, 1]);
+    assert_eq!(bins.charges[0][3], 6.0);
+    assert_eq!(bins.charges[0][0], 2.0);
+    assert_eq!(bins.charges[2][0], 2.0);
+    assert_eq!(bins.end, 150.0);
+}
+
+#[test]
+fn charge_bins_rates() {
+    let mut bins = ChargeBins::new(60.0);
+    bins.add(0.0, &charge(3, 120.0));
+    bins.add(150.0, &charge(3, 30.0));
+
+    let rates = bins.rates();
+    assert_eq!(rates.len(), TPC_PAD_COLUMNS);
+    assert_eq!(rates[3][0], Some(2.0));
+    // No events in the second bin.
+    assert_eq!(rates[3][1], None);
+    // The last bin is only 30 seconds long.
+    assert_eq!(rates[3][2], Some(1.0));
+    assert!(ChargeBins::new(60.0).rates().iter().all(Vec::is_empty));
+}
+
+#[test]
+fn charge_bins_column_trip() {
+    let mut bins = ChargeBins::new(60.0);
+    for second in 0..1800 {
+        let value = if (1200..1500).contains(&second) {
+            0.1
+        } else {
+            1.0
+        };
+        bins.add(f64::from(second), &charge(7, value));
+    }
+    // Anything after the last event doesn't count.
+    bins.add(1800.0, &charge(7, 1.0));
+
+    let drops = find_rate_drops(&bins.rates(), &RateDropSettings::default());
+    assert_eq!(drops.len(), 1);
+    let row = drop_row(&drops[0], bins.width);
+    assert_eq!(row.column, 7);
+    assert_eq!((row.start, row.end), (1200.0, 1500.0));
+}
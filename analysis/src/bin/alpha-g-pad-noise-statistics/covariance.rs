@@ -0,0 +1,173 @@
+use alpha_g_detector::padwing::map::{
+    TpcPadPosition, PWB_PAD_COLUMNS, PWB_PAD_ROWS, TPC_PADS, TPC_PAD_ROWS, TPC_PWB_COLUMNS,
+    TPC_PWB_ROWS,
+};
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::sync::Mutex;
+
+// Number of pads in a single PadWing board.
+const PWB_PADS: usize = PWB_PAD_COLUMNS * PWB_PAD_ROWS;
+
+// Sums of the products of the time-aligned samples of all pairs of `size` pads
+// (including each pad with itself), and the number of products in each sum.
+// Pairs are stored as a packed upper triangular matrix.
+#[derive(Clone, Debug)]
+struct Block {
+    size: usize,
+    sums: Vec<f64>,
+    counts: Vec<u64>,
+}
+
+impl Block {
+    fn new(size: usize) -> Self {
+        let len = size * (size + 1) / 2;
+        Self {
+            size,
+            sums: vec![0.0; len],
+            counts: vec![0; len],
+        }
+    }
+    // Index of the pair `(i, j)`. Requires `i <= j`.
+    fn index(&self, i: usize, j: usize) -> usize {
+        i * (2 * self.size - i + 1) / 2 + (j - i)
+    }
+}
+
+/// Covariance of the noise of a single pair of pads (in ADC counts squared).
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize)]
+pub struct CovarianceRow {
+    pub column_a: usize,
+    pub row_a: usize,
+    pub column_b: usize,
+    pub row_b: usize,
+    pub samples: u64,
+    pub covariance: f64,
+}
+
+// Block of a pad, and its index within the block.
+fn locate(full: bool, position: TpcPadPosition) -> (usize, usize) {
+    if full {
+        return (0, position.index());
+    }
+    let column = usize::from(position.column);
+    let row = usize::from(position.row);
+
+    (
+        (column / PWB_PAD_COLUMNS) * TPC_PWB_ROWS + row / PWB_PAD_ROWS,
+        (column % PWB_PAD_COLUMNS) * PWB_PAD_ROWS + row % PWB_PAD_ROWS,
+    )
+}
+
+// Inverse of `locate`.
+fn position(full: bool, block: usize, index: usize) -> TpcPadPosition {
+    if full {
+        return TpcPadPosition::try_from(index).unwrap();
+    }
+    let column = (block / TPC_PWB_ROWS) * PWB_PAD_COLUMNS + index / PWB_PAD_ROWS;
+    let row = (block % TPC_PWB_ROWS) * PWB_PAD_ROWS + index % PWB_PAD_ROWS;
+
+    TpcPadPosition::try_from(column * TPC_PAD_ROWS + row).unwrap()
+}
+
+/// Covariance between the time-aligned samples of pairs of pads.
+///
+/// Each waveform is shifted by its own mean before being multiplied with the
+/// other waveforms of the same event, i.e. this is the covariance of the
+/// fluctuations around the baseline of each event. By default only pairs of
+/// pads in the same PadWing board are kept; otherwise all the pairs of the
+/// rTPC are kept (this needs ~2.7 GB of memory).
+///
+/// Pads are grouped in blocks (one per board, or a single block with all the
+/// pads). Each block is behind its own lock, so memory usage doesn't grow with
+/// the number of threads.
+#[derive(Debug)]
+pub struct PadCovariance {
+    full: bool,
+    blocks: Vec<Mutex<Block>>,
+}
+
+impl PadCovariance {
+    /// Create an empty accumulator for all pairs of pads (if `full`), or only
+    /// for pairs in the same PadWing board.
+    pub fn new(full: bool) -> Self {
+        let blocks = if full {
+            vec![Mutex::new(Block::new(TPC_PADS))]
+        } else {
+            (0..TPC_PWB_COLUMNS * TPC_PWB_ROWS)
+                .map(|_| Mutex::new(Block::new(PWB_PADS)))
+                .collect()
+        };
+
+        Self { full, blocks }
+    }
+    /// Add the (noise only) waveforms of all the pads in a single event. If two
+    /// waveforms have a different length, only their common leading samples
+    /// are multiplied.
+    pub fn add_event(&self, waveforms: &[(TpcPadPosition, Vec<i16>)]) {
+        let mut by_block: BTreeMap<usize, Vec<(usize, Vec<f64>)>> = BTreeMap::new();
+        for (position, waveform) in waveforms {
+            if waveform.is_empty() {
+                continue;
+            }
+            let mean = waveform.iter().map(|&v| f64::from(v)).sum::<f64>() / waveform.len() as f64;
+            let (block, index) = locate(self.full, *position);
+            by_block.entry(block).or_default().push((
+                index,
+                waveform.iter().map(|&v| f64::from(v) - mean).collect(),
+            ));
+        }
+
+        for (block, mut pads) in by_block {
+            pads.sort_unstable_by_key(|&(index, _)| index);
+            let mut block = self.blocks[block].lock().unwrap();
+            for (k, (i, a)) in pads.iter().enumerate() {
+                for (j, b) in &pads[k..] {
+                    let len = a.len().min(b.len());
+                    let index = block.index(*i, *j);
+                    block.sums[index] += a[..len]
+                        .iter()
+                        .zip(&b[..len])
+                        .map(|(x, y)| x * y)
+                        .sum::<f64>();
+                    block.counts[index] += len as u64;
+                }
+            }
+        }
+    }
+    /// Write a row for every pair of pads that appeared together in at least
+    /// one event. Pairs are written once, with `a` before (or equal to) `b`
+    /// within each block.
+    pub fn write<W: Write>(self, wtr: &mut csv::Writer<W>) -> Result<()> {
+        for (b, block) in self.blocks.into_iter().enumerate() {
+            let block = block.into_inner().unwrap();
+            let mut index = 0;
+            for i in 0..block.size {
+                let a = position(self.full, b, i);
+                for j in i..block.size {
+                    let (sum, count) = (block.sums[index], block.counts[index]);
+                    index += 1;
+                    if count == 0 {
+                        continue;
+                    }
+                    let other = position(self.full, b, j);
+                    wtr.serialize(CovarianceRow {
+                        column_a: usize::from(a.column),
+                        row_a: usize::from(a.row),
+                        column_b: usize::from(other.column),
+                        row_b: usize::from(other.row),
+                        samples: count,
+                        covariance: sum / count as f64,
+                    })
+                    .context("failed to write csv row")?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,106 @@
+use super::*;
+
+fn rows(covariance: PadCovariance) -> Vec<CovarianceRow> {
+    let mut wtr = csv::Writer::from_writer(Vec::new());
+    covariance.write(&mut wtr).unwrap();
+    let data = String::from_utf8(wtr.into_inner().unwrap()).unwrap();
+
+    csv::Reader::from_reader(data.as_bytes())
+        .records()
+        .map(|record| {
+            let record = record.unwrap();
+            CovarianceRow {
+                column_a: record[0].parse().unwrap(),
+                row_a: record[1].parse().unwrap(),
+                column_b: record[2].parse().unwrap(),
+                row_b: record[3].parse().unwrap(),
+                samples: record[4].parse().unwrap(),
+                covariance: record[5].parse().unwrap(),
+            }
+        })
+        .collect()
+}
+
+fn pad(column: usize, row: usize) -> TpcPadPosition {
+    TpcPadPosition::try_from(column * TPC_PAD_ROWS + row).unwrap()
+}
+
+#[test]
+fn locate_position_round_trip() {
+    for full in [false, true] {
+        for index in [0, 1, 71, 72, 287, 288, 5000, TPC_PADS - 1] {
+            let expected = TpcPadPosition::try_from(index).unwrap();
+            let (block, index) = locate(full, expected);
+            assert_eq!(position(full, block, index), expected);
+        }
+    }
+}
+
+#[test]
+fn locate_same_board() {
+    // Pads in the same 4x72 PadWing board are in the same block.
+    let (first, _) = locate(false, pad(0, 0));
+    assert_eq!(locate(false, pad(3, 71)).0, first);
+    assert_ne!(locate(false, pad(4, 0)).0, first);
+    assert_ne!(locate(false, pad(0, 72)).0, first);
+}
+
+#[test]
+fn pad_covariance_correlated_pads() {
+    let covariance = PadCovariance::new(false);
+    let noise: Vec<i16> = vec![1, -1, 2, -2];
+    let opposite: Vec<i16> = noise.iter().map(|v| -v).collect();
+    covariance.add_event(&[
+        (pad(0, 0), noise.iter().map(|v| v + 100).collect()),
+        (pad(0, 1), noise.iter().map(|v| v + 50).collect()),
+        (pad(1, 0), opposite),
+    ]);
+
+    let rows = rows(covariance);
+    let get = |a: TpcPadPosition, b: TpcPadPosition| {
+        rows.iter()
+            .find(|row| {
+                (row.column_a, row.row_a, row.column_b, row.row_b)
+                    == (
+                        usize::from(a.column),
+                        usize::from(a.row),
+                        usize::from(b.column),
+                        usize::from(b.row),
+                    )
+            })
+            .unwrap()
+    };
+    // All pairs (and each pad with itself).
+    assert_eq!(rows.len(), 6);
+    assert!(rows.iter().all(|row| row.samples == 4));
+    // The baseline of each waveform is removed.
+    assert_eq!(get(pad(0, 0), pad(0, 0)).covariance, 2.5);
+    assert_eq!(get(pad(0, 0), pad(0, 1)).covariance, 2.5);
+    assert_eq!(get(pad(0, 0), pad(1, 0)).covariance, -2.5);
+}
+
+#[test]
+fn pad_covariance_different_lengths() {
+    let covariance = PadCovariance::new(false);
+    covariance.add_event(&[(pad(0, 0), vec![1, -1]), (pad(0, 1), vec![1, -1, 1])]);
+    covariance.add_event(&[(pad(0, 0), vec![1, -1])]);
+
+    let rows = rows(covariance);
+    assert_eq!(
+        rows.iter().map(|row| row.samples).collect::<Vec<_>>(),
+        [4, 2, 3]
+    );
+}
+
+#[test]
+fn pad_covariance_other_boards() {
+    let waveforms = [(pad(0, 0), vec![1, -1]), (pad(4, 0), vec![1, -1])];
+
+    let covariance = PadCovariance::new(false);
+    covariance.add_event(&waveforms);
+    assert_eq!(rows(covariance).len(), 2);
+
+    let covariance = PadCovariance::new(true);
+    covariance.add_event(&waveforms);
+    assert_eq!(rows(covariance).len(), 3);
+}
@@ -0,0 +1,470 @@
+use alpha_g_analysis::statistics::{NoiseClassifier, RunningStatistics};
+use alpha_g_analysis::{Emission, ErrorAggregator};
+use alpha_g_detector::midas::EventId;
+use alpha_g_detector::padwing::map::{TpcPadPosition, TPC_PADS};
+use alpha_g_detector::padwing::{
+    group_chunks, AfterId, BoardId, ChannelId, PadChannelId, PWB_RATE,
+};
+use alpha_g_detector::run::RunNumber;
+use anyhow::{ensure, Context, Result};
+use clap::Parser;
+use indicatif::{
+    MultiProgress, ParallelProgressIterator, ProgressBar, ProgressDrawTarget, ProgressStyle,
+};
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+// Covariance between the noise of pairs of pads (see `--save-covariance`).
+mod covariance;
+// Power spectral density of the noise of each pad (see `--spectra`).
+mod spectra;
+
+// Number of pads listed in the summary of `--spectra`.
+const NOISIEST_PADS: usize = 10;
+
+#[derive(Parser)]
+#[command(version)]
+/// Calculate the noise statistics of all pads for a single run
+struct Args {
+    /// MIDAS files (or directories with them) from the run you want to inspect
+    #[arg(required = true)]
+    files: Vec<PathBuf>,
+    /// Also search for MIDAS files in subdirectories of the input directories
+    #[arg(long)]
+    recursive: bool,
+    /// Write the output to `OUTPUT.csv` and `OUTPUT_per_after.csv` [default:
+    /// `R<run_number>_pad_noise_statistics`]
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    /// Flag an AFTER chip as noisy if the average standard deviation of its
+    /// pads is above this threshold (in ADC counts)
+    #[arg(long, default_value_t = 20.0)]
+    noise_threshold: f64,
+    /// Ignore waveforms (e.g. pulser or saturated) with a peak-to-peak
+    /// excursion larger than this multiple of their robust spread
+    #[arg(long, default_value_t = NoiseClassifier::PADS.max_spread_multiple)]
+    max_spread_multiple: f64,
+    /// Also write the covariance between the noise of all pairs of pads in the
+    /// same PadWing board to `OUTPUT_covariance.csv`
+    #[arg(long)]
+    save_covariance: bool,
+    /// Write the covariance between all pairs of pads in the rTPC instead
+    /// (needs ~2.7 GB of memory, and it is very slow)
+    #[arg(long, requires = "save_covariance")]
+    full_covariance: bool,
+    /// Also write the average power spectral density of the noise of every pad
+    /// to `OUTPUT_spectra.json`
+    #[arg(long)]
+    spectra: bool,
+    /// Ignore the run number in the MIDAS files and use this one instead
+    #[arg(long, value_name = "RUN_NUMBER")]
+    run_number_override: Option<RunNumber>,
+    /// Print detailed information about errors (if any)
+    #[arg(short, long)]
+    verbose: bool,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct PadRow {
+    board: String,
+    after: char,
+    channel: u16,
+    tpc_column: Option<usize>,
+    tpc_row: Option<usize>,
+    samples: u64,
+    mean: f64,
+    std_dev: f64,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct AfterRow {
+    board: String,
+    after: char,
+    mean_baseline: Option<f64>,
+    mean_std_dev: Option<f64>,
+    max_std_dev: Option<f64>,
+    missing_pads: usize,
+    noisy: bool,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct SpectraOutput {
+    package: String,
+    arguments: Vec<String>,
+    run_number: RunNumber,
+    warnings: Vec<String>,
+    sampling_rate: f64,
+    frequencies: Vec<f64>,
+    pads: Vec<spectra::PadSpectrum>,
+}
+
+type Statistics = HashMap<(BoardId, AfterId, PadChannelId), RunningStatistics>;
+
+// Also keep track of the number of waveforms that were rejected (i.e. not
+// noise), and the total number of waveforms.
+#[derive(Default)]
+struct Accumulator {
+    statistics: Statistics,
+    rejected: u64,
+    waveforms: u64,
+}
+
+fn merge_accumulators(mut a: Accumulator, b: Accumulator) -> Accumulator {
+    for (key, accumulator) in b.statistics {
+        a.statistics.entry(key).or_default().merge(&accumulator);
+    }
+    a.rejected += b.rejected;
+    a.waveforms += b.waveforms;
+    a
+}
+
+fn after_char(after_id: AfterId) -> char {
+    match after_id {
+        AfterId::A => 'A',
+        AfterId::B => 'B',
+        AfterId::C => 'C',
+        AfterId::D => 'D',
+    }
+}
+
+fn csv_writer(
+    path: &Path,
+    run_number_override: Option<RunNumber>,
+) -> Result<csv::Writer<std::fs::File>> {
+    let mut wtr = std::fs::File::create(path)
+        .with_context(|| format!("failed to create `{}`", path.display()))?;
+    eprintln!("Created `{}`", path.display());
+    wtr.write_all(
+        format!(
+            "# {} {}\n# {}\n",
+            env!("CARGO_PKG_NAME"),
+            env!("CARGO_PKG_VERSION"),
+            std::env::args().collect::<Vec<_>>().join(" ")
+        )
+        .as_bytes(),
+    )
+    .context("failed to write csv header")?;
+    if let Some(run_number) = run_number_override {
+        wtr.write_all(format!("# WARNING: run number overridden to {run_number}\n").as_bytes())
+            .context("failed to write csv header")?;
+    }
+
+    Ok(csv::Writer::from_writer(wtr))
+}
+
+// All the AFTER chips of the PadWing boards installed in the rTPC for the run
+// (even if they didn't send any data), and of any other board that sent data.
+// Sorted by board name and AFTER chip.
+fn after_groups(run_number: RunNumber, statistics: &Statistics) -> Vec<(BoardId, AfterId)> {
+    let installed = (0..TPC_PADS).filter_map(|index| {
+        TpcPadPosition::try_from(index)
+            .unwrap()
+            .board_after_channel(run_number)
+            .ok()
+            .map(|(board_id, after_id, _)| (board_id, after_id))
+    });
+    let mut groups: Vec<_> = statistics
+        .keys()
+        .map(|&(board_id, after_id, _)| (board_id, after_id))
+        .chain(installed)
+        .collect();
+    groups.sort_unstable_by_key(|&(board_id, after_id)| {
+        (board_id.name().to_string(), after_char(after_id))
+    });
+    groups.dedup();
+
+    groups
+}
+
+// Rows of the per pad and per AFTER chip outputs.
+fn noise_rows(
+    run_number: RunNumber,
+    statistics: &Statistics,
+    noise_threshold: f64,
+) -> (Vec<PadRow>, Vec<AfterRow>) {
+    let mut pad_rows = Vec::new();
+    let mut after_rows = Vec::new();
+    // Aggregate by AFTER chip before mapping to a TPC position. This grouping
+    // is independent of the (run dependent) cabling.
+    for (board_id, after_id) in after_groups(run_number, statistics) {
+        let mut pad_means = Vec::new();
+        let mut pad_std_devs = Vec::new();
+        let mut missing_pads = 0;
+        for channel in 1..=72 {
+            let pad_channel_id = PadChannelId::try_from(channel).unwrap();
+            let Some(accumulator) = statistics.get(&(board_id, after_id, pad_channel_id)) else {
+                missing_pads += 1;
+                continue;
+            };
+            let position = TpcPadPosition::try_new(run_number, board_id, after_id, pad_channel_id);
+            pad_rows.push(PadRow {
+                board: board_id.name().to_string(),
+                after: after_char(after_id),
+                channel,
+                tpc_column: position.as_ref().ok().map(|p| usize::from(p.column)),
+                tpc_row: position.as_ref().ok().map(|p| usize::from(p.row)),
+                samples: accumulator.count(),
+                mean: accumulator.mean(),
+                std_dev: accumulator.std_dev(),
+            });
+            pad_means.push(accumulator.mean());
+            pad_std_devs.push(accumulator.std_dev());
+        }
+
+        let average = |v: &[f64]| (!v.is_empty()).then(|| v.iter().sum::<f64>() / v.len() as f64);
+        let mean_std_dev = average(&pad_std_devs);
+        after_rows.push(AfterRow {
+            board: board_id.name().to_string(),
+            after: after_char(after_id),
+            mean_baseline: average(&pad_means),
+            mean_std_dev,
+            max_std_dev: pad_std_devs.iter().copied().reduce(f64::max),
+            missing_pads,
+            noisy: mean_std_dev.is_some_and(|s| s > noise_threshold),
+        });
+    }
+
+    (pad_rows, after_rows)
+}
+
+fn main() {
+    alpha_g_analysis::cli::exit_on_error(run());
+}
+
+fn run() -> Result<()> {
+    let args: Args = alpha_g_analysis::cli::parse(env!("CARGO_BIN_NAME"));
+    ensure!(
+        args.max_spread_multiple > 0.0,
+        "`--max-spread-multiple` must be positive"
+    );
+    let classifier = NoiseClassifier {
+        max_spread_multiple: args.max_spread_multiple,
+        ..NoiseClassifier::PADS
+    };
+    let files = alpha_g_analysis::expand_run_files(args.files, args.recursive)?;
+    let (run_number, files) = alpha_g_analysis::sort_run_files(files, args.run_number_override)?;
+    if args.run_number_override.is_some() {
+        eprintln!("Warning: ignoring the run number in the input files, using `{run_number}`");
+    }
+    // Before reading (and decompressing) all the files of the run.
+    alpha_g_analysis::cli::exit_if_no_main_events(&alpha_g_analysis::census_until_main_event(
+        &files,
+    ));
+    // Progress bars were flickering with the default draw target rate.
+    let multi_progress = MultiProgress::with_draw_target(ProgressDrawTarget::stderr_with_hz(1));
+    let tp_bar = multi_progress.add(
+        ProgressBar::new(files.len().try_into().unwrap())
+            .with_style(ProgressStyle::with_template("[{pos}/{len}] Processing").unwrap()),
+    );
+    tp_bar.tick();
+
+    let mut accumulator = Accumulator::default();
+    let covariance = args
+        .save_covariance
+        .then(|| covariance::PadCovariance::new(args.full_covariance));
+    let pad_spectra = args.spectra.then(spectra::PadSpectra::default);
+    let aggregator = Mutex::new(ErrorAggregator::default());
+    let mut previous_final_timestamp = None;
+    for file in files {
+        let contents = alpha_g_analysis::read(&file)?;
+        let file_view = midasio::FileView::try_from(&contents[..])
+            .with_context(|| format!("failed to parse `{}`", file.display()))?;
+        if let Some(previous_final_timestamp) = previous_final_timestamp {
+            ensure!(
+                file_view.initial_timestamp() - previous_final_timestamp <= 1,
+                "missing file before `{}`",
+                file.display()
+            );
+        }
+        previous_final_timestamp = Some(file_view.final_timestamp());
+
+        let pb = multi_progress.add(
+            ProgressBar::new(file_view.iter().len().try_into().unwrap())
+                .with_style(
+                    ProgressStyle::with_template("[{bar:25}] {percent}%, ETA: {eta}    ({msg})")
+                        .unwrap()
+                        .progress_chars("=> "),
+                )
+                .with_message(format!("{}", file.display())),
+        );
+        let file_accumulator = file_view
+            .into_par_iter()
+            .progress_with(pb.clone())
+            .filter(|event| matches!(EventId::try_from(event.id()), Ok(EventId::Main)))
+            .fold(Accumulator::default, |mut accumulator, event| {
+                let serial_number = event.serial_number();
+
+                let banks = event
+                    .into_iter()
+                    .map(|bank| (bank.name(), bank.data_slice()));
+                let (packets, _) = group_chunks(banks);
+                // Noise waveforms of all the pads in the event (only needed
+                // for the covariance), and their power spectral densities
+                // (only needed for the spectra).
+                let mut event_waveforms = Vec::new();
+                let mut event_spectra = Vec::new();
+                for packet in packets {
+                    let packet = match packet {
+                        Ok(packet) => packet,
+                        Err(error) => {
+                            let emission = aggregator.lock().unwrap().add(&error);
+                            match emission {
+                                Emission::Warn(unknown) => pb.println(format!(
+                                    "Warning: {unknown} in event `{serial_number}` (`{}` so far)",
+                                    unknown.count
+                                )),
+                                Emission::Other if args.verbose => {
+                                    pb.println(format!("Error in event `{serial_number}`: {error}"))
+                                }
+                                _ => {}
+                            }
+                            continue;
+                        }
+                    };
+                    for &channel_id in packet.channels_sent() {
+                        if let ChannelId::Pad(pad_channel_id) = channel_id {
+                            // A waveform is guaranteed to exist if the channel
+                            // was sent.
+                            let waveform = packet.waveform_at(channel_id).unwrap();
+                            accumulator.waveforms += 1;
+                            if !classifier.is_noise(waveform) {
+                                accumulator.rejected += 1;
+                                continue;
+                            }
+                            let key = (packet.board_id(), packet.after_id(), pad_channel_id);
+                            let statistics = accumulator.statistics.entry(key).or_default();
+                            for &v in waveform {
+                                statistics.add(f64::from(v));
+                            }
+                            if covariance.is_some() {
+                                if let Ok(position) = TpcPadPosition::try_new(
+                                    run_number,
+                                    packet.board_id(),
+                                    packet.after_id(),
+                                    pad_channel_id,
+                                ) {
+                                    event_waveforms.push((position, waveform.to_vec()));
+                                }
+                            }
+                            if pad_spectra.is_some() {
+                                if let Some(psd) =
+                                    spectra::power_spectral_density(waveform, PWB_RATE)
+                                {
+                                    event_spectra.push((key, psd));
+                                }
+                            }
+                        }
+                    }
+                }
+                if let Some(covariance) = &covariance {
+                    covariance.add_event(&event_waveforms);
+                }
+                if let Some(pad_spectra) = &pad_spectra {
+                    pad_spectra.add_event(event_spectra);
+                }
+
+                accumulator
+            })
+            .reduce(Accumulator::default, merge_accumulators);
+        accumulator = merge_accumulators(accumulator, file_accumulator);
+
+        tp_bar.set_style(
+            ProgressStyle::with_template("[{pos}/{len}] Processing, ETA: {eta}").unwrap(),
+        );
+        tp_bar.inc(1);
+    }
+    tp_bar.finish_and_clear();
+    for unknown in aggregator.into_inner().unwrap().unknown_versions() {
+        eprintln!("Warning: found `{}` errors with {unknown}", unknown.count);
+    }
+    eprintln!(
+        "Rejected `{}` of `{}` pad waveforms with a signal",
+        accumulator.rejected, accumulator.waveforms
+    );
+    let statistics = accumulator.statistics;
+
+    let (pad_rows, after_rows) = noise_rows(run_number, &statistics, args.noise_threshold);
+
+    let output = args
+        .output
+        .unwrap_or_else(|| PathBuf::from(format!("R{run_number}_pad_noise_statistics")))
+        .with_extension("");
+    let mut wtr = csv_writer(&output.with_extension("csv"), args.run_number_override)?;
+    for row in pad_rows {
+        wtr.serialize(row).context("failed to write csv row")?;
+    }
+    wtr.flush().context("failed to flush csv data")?;
+
+    let mut after_output = output.clone().into_os_string();
+    after_output.push("_per_after.csv");
+    let mut wtr = csv_writer(Path::new(&after_output), args.run_number_override)?;
+    for row in after_rows {
+        wtr.serialize(row).context("failed to write csv row")?;
+    }
+    wtr.flush().context("failed to flush csv data")?;
+
+    if let Some(covariance) = covariance {
+        let mut covariance_output = output.clone().into_os_string();
+        covariance_output.push("_covariance.csv");
+        let mut wtr = csv_writer(Path::new(&covariance_output), args.run_number_override)?;
+        covariance.write(&mut wtr)?;
+        wtr.flush().context("failed to flush csv data")?;
+    }
+
+    if let Some(pad_spectra) = pad_spectra {
+        let pads = pad_spectra.into_pads(run_number);
+        let frequencies = spectra::frequencies(PWB_RATE);
+
+        let mut noisiest: Vec<_> = pads
+            .iter()
+            .filter_map(|pad| spectra::peak(&pad.psd).map(|index| (pad, index)))
+            .collect();
+        noisiest.sort_unstable_by(|(a, i), (b, j)| b.psd[*j].total_cmp(&a.psd[*i]));
+        if !noisiest.is_empty() {
+            eprintln!("Pads with the largest peak in their noise spectrum:");
+        }
+        for (pad, index) in noisiest.into_iter().take(NOISIEST_PADS) {
+            eprintln!(
+                "  board `{}`, AFTER `{}`, channel `{}`: {:.3} MHz ({:.3e} counts^2/Hz)",
+                pad.board,
+                pad.after,
+                pad.channel,
+                frequencies[index] / 1e6,
+                pad.psd[index]
+            );
+        }
+
+        let spectra_output = SpectraOutput {
+            package: format!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")),
+            arguments: std::env::args().collect(),
+            run_number,
+            warnings: args
+                .run_number_override
+                .map(|run_number| format!("run number overridden to {run_number}"))
+                .into_iter()
+                .collect(),
+            sampling_rate: PWB_RATE,
+            frequencies,
+            pads,
+        };
+        let mut spectra_path = output.into_os_string();
+        spectra_path.push("_spectra.json");
+        let spectra_path = PathBuf::from(spectra_path);
+        let mut wtr = std::fs::File::create(&spectra_path)
+            .map(std::io::BufWriter::new)
+            .with_context(|| format!("failed to create `{}`", spectra_path.display()))?;
+        serde_json::to_writer(&mut wtr, &spectra_output)
+            .with_context(|| format!("failed to write `{}`", spectra_path.display()))?;
+        wtr.flush()
+            .with_context(|| format!("failed to write `{}`", spectra_path.display()))?;
+        eprintln!("Created `{}`", spectra_path.display());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests;
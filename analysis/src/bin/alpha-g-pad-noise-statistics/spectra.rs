@@ -0,0 +1,194 @@
+use crate::after_char;
+use alpha_g_detector::padwing::map::TpcPadPosition;
+use alpha_g_detector::padwing::{AfterId, BoardId, PadChannelId};
+use alpha_g_detector::run::RunNumber;
+use std::collections::HashMap;
+use std::f64::consts::PI;
+use std::sync::Mutex;
+
+/// Number of samples of each spectrum. Longer waveforms are truncated, and
+/// shorter waveforms are zero padded. A full PWB waveform has 511 samples.
+pub const SPECTRUM_LEN: usize = 512;
+
+// In-place radix-2 FFT. `re` and `im` must have the same length, which must be
+// a power of two (at least 2).
+fn fft(re: &mut [f64], im: &mut [f64]) {
+    let n = re.len();
+    let bits = n.trailing_zeros();
+    for i in 0..n {
+        let j = i.reverse_bits() >> (usize::BITS - bits);
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+    let twiddles: Vec<_> = (0..n / 2)
+        .map(|k| (-2.0 * PI * k as f64 / n as f64).sin_cos())
+        .collect();
+
+    let mut len = 2;
+    while len <= n {
+        let stride = n / len;
+        for start in (0..n).step_by(len) {
+            for k in 0..len / 2 {
+                let (sin, cos) = twiddles[k * stride];
+                let (a, b) = (start + k, start + k + len / 2);
+                let t_re = re[b] * cos - im[b] * sin;
+                let t_im = re[b] * sin + im[b] * cos;
+                re[b] = re[a] - t_re;
+                im[b] = im[a] - t_im;
+                re[a] += t_re;
+                im[a] += t_im;
+            }
+        }
+        len *= 2;
+    }
+}
+
+/// Frequencies (in Hz) of the values returned by [`power_spectral_density`]
+/// for a waveform sampled at `rate` (in samples per second).
+pub fn frequencies(rate: f64) -> Vec<f64> {
+    (0..=SPECTRUM_LEN / 2)
+        .map(|k| k as f64 * rate / SPECTRUM_LEN as f64)
+        .collect()
+}
+
+/// One-sided power spectral density (in ADC counts squared per Hz) of a
+/// waveform sampled at `rate` (in samples per second).
+///
+/// The mean of the waveform is subtracted, and a Hann window is applied
+/// before the transform. The sum of all the values times the frequency
+/// resolution is the (windowed) variance of the waveform. Returns [`None`] if
+/// the waveform has fewer than 2 samples.
+pub fn power_spectral_density(waveform: &[i16], rate: f64) -> Option<Vec<f64>> {
+    let waveform = &waveform[..waveform.len().min(SPECTRUM_LEN)];
+    if waveform.len() < 2 {
+        return None;
+    }
+    let mean = waveform.iter().map(|&v| f64::from(v)).sum::<f64>() / waveform.len() as f64;
+    let last = (waveform.len() - 1) as f64;
+    let window: Vec<_> = (0..waveform.len())
+        .map(|i| 0.5 - 0.5 * (2.0 * PI * i as f64 / last).cos())
+        .collect();
+
+    let mut re = vec![0.0; SPECTRUM_LEN];
+    let mut im = vec![0.0; SPECTRUM_LEN];
+    for ((re, &v), w) in re.iter_mut().zip(waveform).zip(&window) {
+        *re = (f64::from(v) - mean) * w;
+    }
+    fft(&mut re, &mut im);
+
+    let norm = rate * window.iter().map(|w| w * w).sum::<f64>();
+    Some(
+        (0..=SPECTRUM_LEN / 2)
+            .map(|k| {
+                let power = (re[k] * re[k] + im[k] * im[k]) / norm;
+                // Fold the negative frequencies (all but DC and Nyquist).
+                if k == 0 || k == SPECTRUM_LEN / 2 {
+                    power
+                } else {
+                    2.0 * power
+                }
+            })
+            .collect(),
+    )
+}
+
+/// Index of the largest value of a power spectral density, ignoring the DC
+/// component. Returns [`None`] if there are no other frequencies.
+pub fn peak(psd: &[f64]) -> Option<usize> {
+    psd.iter()
+        .enumerate()
+        .skip(1)
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(index, _)| index)
+}
+
+type Key = (BoardId, AfterId, PadChannelId);
+
+// Sum of the power spectral densities of all the waveforms of a pad, and the
+// number of waveforms.
+#[derive(Clone, Debug)]
+struct Sum {
+    psd: Vec<f64>,
+    waveforms: u64,
+}
+
+/// Average power spectral density of a single pad.
+#[derive(Clone, Debug, PartialEq, serde::Serialize)]
+pub struct PadSpectrum {
+    pub board: String,
+    pub after: char,
+    pub channel: u16,
+    pub tpc_column: Option<usize>,
+    pub tpc_row: Option<usize>,
+    pub waveforms: u64,
+    pub psd: Vec<f64>,
+}
+
+/// Average power spectral density of the noise waveforms of each pad.
+#[derive(Debug, Default)]
+pub struct PadSpectra {
+    sums: Mutex<HashMap<Key, Sum>>,
+}
+
+impl PadSpectra {
+    /// Add the power spectral densities (see [`power_spectral_density`]) of
+    /// the waveforms of a single event.
+    pub fn add_event(&self, spectra: Vec<(Key, Vec<f64>)>) {
+        let mut sums = self.sums.lock().unwrap();
+        for (key, psd) in spectra {
+            let sum = sums.entry(key).or_insert_with(|| Sum {
+                psd: vec![0.0; psd.len()],
+                waveforms: 0,
+            });
+            for (total, value) in sum.psd.iter_mut().zip(psd) {
+                *total += value;
+            }
+            sum.waveforms += 1;
+        }
+    }
+    /// Return the average spectrum of every pad with at least one waveform,
+    /// sorted by board name, AFTER chip, and channel.
+    pub fn into_pads(self, run_number: RunNumber) -> Vec<PadSpectrum> {
+        let mut sums = self.sums.into_inner().unwrap();
+        let mut groups: Vec<_> = sums
+            .keys()
+            .map(|&(board_id, after_id, _)| (board_id, after_id))
+            .collect();
+        groups.sort_unstable_by_key(|&(board_id, after_id)| {
+            (board_id.name().to_string(), after_char(after_id))
+        });
+        groups.dedup();
+
+        let mut pads = Vec::new();
+        for (board_id, after_id) in groups {
+            for channel in 1..=72 {
+                let pad_channel_id = PadChannelId::try_from(channel).unwrap();
+                let Some(sum) = sums.remove(&(board_id, after_id, pad_channel_id)) else {
+                    continue;
+                };
+                let position =
+                    TpcPadPosition::try_new(run_number, board_id, after_id, pad_channel_id);
+                pads.push(PadSpectrum {
+                    board: board_id.name().to_string(),
+                    after: after_char(after_id),
+                    channel,
+                    tpc_column: position.as_ref().ok().map(|p| usize::from(p.column)),
+                    tpc_row: position.as_ref().ok().map(|p| usize::from(p.row)),
+                    waveforms: sum.waveforms,
+                    psd: sum
+                        .psd
+                        .into_iter()
+                        .map(|v| v / sum.waveforms as f64)
+                        .collect(),
+                });
+            }
+        }
+
+        pads
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,123 @@
+use super::*;
+use alpha_g_detector::padwing::PWB_RATE;
+
+fn sine(frequency: f64, amplitude: f64, len: usize) -> Vec<i16> {
+    (0..len)
+        .map(|i| {
+            let t = i as f64 / PWB_RATE;
+            (100.0 + amplitude * (2.0 * PI * frequency * t).sin()).round() as i16
+        })
+        .collect()
+}
+
+fn key(board: &str, after_id: AfterId, channel: u16) -> Key {
+    (
+        BoardId::try_from(board).unwrap(),
+        after_id,
+        PadChannelId::try_from(channel).unwrap(),
+    )
+}
+
+#[test]
+fn fft_same_as_dft() {
+    let input: Vec<f64> = (0..16).map(|i| ((i * 7) % 5) as f64 - 2.0).collect();
+    let mut re = input.clone();
+    let mut im = vec![0.0; 16];
+    fft(&mut re, &mut im);
+
+    for k in 0..16 {
+        let (mut expected_re, mut expected_im) = (0.0, 0.0);
+        for (n, x) in input.iter().enumerate() {
+            let angle = -2.0 * PI * (k * n) as f64 / 16.0;
+            expected_re += x * angle.cos();
+            expected_im += x * angle.sin();
+        }
+        assert!((re[k] - expected_re).abs() < 1e-9);
+        assert!((im[k] - expected_im).abs() < 1e-9);
+    }
+}
+
+#[test]
+fn frequencies_resolution() {
+    let frequencies = frequencies(PWB_RATE);
+    assert_eq!(frequencies.len(), SPECTRUM_LEN / 2 + 1);
+    assert_eq!(frequencies[0], 0.0);
+    assert_eq!(frequencies[1], PWB_RATE / SPECTRUM_LEN as f64);
+    assert_eq!(frequencies[SPECTRUM_LEN / 2], PWB_RATE / 2.0);
+}
+
+#[test]
+fn power_spectral_density_flat_waveform() {
+    let psd = power_spectral_density(&[100; 511], PWB_RATE).unwrap();
+    assert!(psd.iter().all(|&v| v.abs() < 1e-12));
+}
+
+#[test]
+fn power_spectral_density_too_short() {
+    assert!(power_spectral_density(&[], PWB_RATE).is_none());
+    assert!(power_spectral_density(&[100], PWB_RATE).is_none());
+}
+
+#[test]
+fn power_spectral_density_injected_sine() {
+    let frequencies = frequencies(PWB_RATE);
+    let resolution = frequencies[1];
+    // On a frequency bin, between bins, and close to the Nyquist frequency.
+    for frequency in [40.0 * resolution, 3.3e6, 12.1e6, 30.0e6] {
+        let psd = power_spectral_density(&sine(frequency, 50.0, 511), PWB_RATE).unwrap();
+        let peak = frequencies[peak(&psd).unwrap()];
+        assert!((peak - frequency).abs() <= resolution / 2.0);
+    }
+}
+
+#[test]
+fn power_spectral_density_total_power() {
+    // The integral of the PSD is the variance of the waveform i.e. A^2 / 2 for
+    // a sine wave of amplitude A.
+    let frequency = 5.0e6;
+    let psd = power_spectral_density(&sine(frequency, 50.0, 511), PWB_RATE).unwrap();
+    let total = psd.iter().sum::<f64>() * PWB_RATE / SPECTRUM_LEN as f64;
+
+    assert!((total - 1250.0).abs() < 0.02 * 1250.0);
+}
+
+#[test]
+fn power_spectral_density_longer_waveform() {
+    let short = power_spectral_density(&sine(5.0e6, 50.0, SPECTRUM_LEN), PWB_RATE).unwrap();
+    let long = power_spectral_density(&sine(5.0e6, 50.0, 1000), PWB_RATE).unwrap();
+    assert_eq!(short, long);
+}
+
+#[test]
+fn peak_ignores_dc() {
+    assert_eq!(peak(&[10.0, 1.0, 3.0, 2.0]), Some(2));
+    assert_eq!(peak(&[10.0]), None);
+}
+
+#[test]
+fn pad_spectra_average() {
+    let spectra = PadSpectra::default();
+    spectra.add_event(vec![
+        (key("26", AfterId::B, 3), vec![1.0, 2.0]),
+        (key("26", AfterId::A, 5), vec![4.0, 4.0]),
+    ]);
+    spectra.add_event(vec![(key("26", AfterId::B, 3), vec![3.0, 6.0])]);
+
+    let pads = spectra.into_pads(RunNumber::from(4418));
+    assert_eq!(pads.len(), 2);
+
+    assert_eq!((pads[0].after, pads[0].channel), ('A', 5));
+    assert_eq!(pads[0].waveforms, 1);
+    assert_eq!(pads[0].psd, [4.0, 4.0]);
+
+    assert_eq!((pads[1].after, pads[1].channel), ('B', 3));
+    assert_eq!(pads[1].waveforms, 2);
+    assert_eq!(pads[1].psd, [2.0, 4.0]);
+}
+
+#[test]
+fn pad_spectra_empty() {
+    assert!(PadSpectra::default()
+        .into_pads(RunNumber::from(4418))
+        .is_empty());
+}
@@ -0,0 +1,61 @@
+use super::*;
+
+fn key(board: &str, after_id: AfterId, channel: u16) -> (BoardId, AfterId, PadChannelId) {
+    (
+        BoardId::try_from(board).unwrap(),
+        after_id,
+        PadChannelId::try_from(channel).unwrap(),
+    )
+}
+
+#[test]
+fn noise_rows_missing_after_chip() {
+    let mut statistics = Statistics::new();
+    let accumulator = statistics.entry(key("26", AfterId::A, 1)).or_default();
+    for v in [100.0, 102.0] {
+        accumulator.add(v);
+    }
+    let (pad_rows, after_rows) = noise_rows(RunNumber::new(5000), &statistics, 20.0);
+
+    assert_eq!(pad_rows.len(), 1);
+    // Every AFTER chip of the 64 boards installed in the rTPC.
+    assert_eq!(after_rows.len(), 256);
+
+    let row = |after: char| {
+        after_rows
+            .iter()
+            .find(|row| row.board == "26" && row.after == after)
+            .unwrap()
+    };
+    assert_eq!(row('A').missing_pads, 71);
+    assert_eq!(row('A').mean_baseline, Some(101.0));
+    // A chip that never sent any data.
+    assert_eq!(row('B').missing_pads, 72);
+    assert_eq!(row('B').mean_baseline, None);
+    assert!(!row('B').noisy);
+}
+
+#[test]
+fn noise_rows_board_not_installed() {
+    let mut statistics = Statistics::new();
+    statistics
+        .entry(key("26", AfterId::C, 5))
+        .or_default()
+        .add(1.0);
+    // No mapping before run 4418.
+    let (pad_rows, after_rows) = noise_rows(RunNumber::new(4417), &statistics, 20.0);
+
+    assert_eq!(pad_rows.len(), 1);
+    assert_eq!(pad_rows[0].tpc_column, None);
+    assert_eq!(after_rows.len(), 1);
+    assert_eq!((after_rows[0].after, after_rows[0].missing_pads), ('C', 71));
+}
+
+#[test]
+fn after_groups_sorted() {
+    let groups = after_groups(RunNumber::new(5000), &Statistics::new());
+
+    assert_eq!(groups[0], (BoardId::try_from("00").unwrap(), AfterId::A));
+    assert_eq!(groups[3], (BoardId::try_from("00").unwrap(), AfterId::D));
+    assert_eq!(groups[4], (BoardId::try_from("01").unwrap(), AfterId::A));
+}
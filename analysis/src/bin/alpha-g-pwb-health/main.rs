@@ -0,0 +1,312 @@
+use alpha_g_analysis::{Emission, ErrorAggregator, EventCensus};
+use alpha_g_detector::midas::EventId;
+use alpha_g_detector::padwing::{group_chunks, BoardId, PwbPacket, PwbV2Packet};
+use anyhow::{ensure, Context, Result};
+use clap::Parser;
+use indicatif::{
+    MultiProgress, ParallelProgressIterator, ProgressBar, ProgressDrawTarget, ProgressStyle,
+};
+use rayon::prelude::*;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+#[derive(Parser)]
+#[command(version)]
+/// Report the FIFO health of all PadWing boards for a single run
+struct Args {
+    /// MIDAS files (or directories with them) from the run you want to inspect
+    #[arg(required = true)]
+    files: Vec<PathBuf>,
+    /// Also search for MIDAS files in subdirectories of the input directories
+    #[arg(long)]
+    recursive: bool,
+    /// Write the output to `OUTPUT.csv`, or to stdout if `-` [default:
+    /// `R<run_number>_pwb_health.csv`]
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    /// Width (in seconds) of the time bins
+    #[arg(long, value_name = "SECONDS", default_value_t = 60)]
+    bin_width: u32,
+    /// Depth of the SCA FIFO of the PWBs (from the firmware documentation).
+    /// Required to report its occupancy
+    #[arg(long, value_name = "DEPTH")]
+    sca_fifo_depth: Option<u16>,
+    /// Depth of the event descriptor FIFO of the PWBs (from the firmware
+    /// documentation). Required to report its occupancy
+    #[arg(long, value_name = "DEPTH")]
+    event_descriptor_fifo_depth: Option<u8>,
+    /// Count a packet as near overflow if the occupancy fraction of any of
+    /// its FIFOs (with a known depth) is at least this threshold
+    #[arg(long, value_name = "FRACTION", default_value_t = 0.9)]
+    near_overflow: f64,
+    /// Print detailed information about errors (if any)
+    #[arg(short, long)]
+    verbose: bool,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct Row {
+    board: String,
+    time: u32,
+    packets: u64,
+    max_fifo_depth: u16,
+    max_fifo_occupancy: Option<f64>,
+    max_event_descriptor_write_depth: u8,
+    max_event_descriptor_read_depth: u8,
+    max_event_descriptor_occupancy: Option<f64>,
+    max_channels_over_threshold: usize,
+    near_overflow_packets: Option<u64>,
+}
+
+// Depths of the PWB FIFOs. These are not in the data, and there are no
+// defaults; a wrong depth would silently hide (or fake) an overflow.
+#[derive(Clone, Copy, Debug, Default)]
+struct FifoDepths {
+    sca: Option<u16>,
+    event_descriptor: Option<u8>,
+}
+
+impl FifoDepths {
+    fn is_empty(&self) -> bool {
+        self.sca.is_none() && self.event_descriptor.is_none()
+    }
+
+    fn sca_occupancy(&self, depth: u16) -> Option<f64> {
+        self.sca.map(|full| f64::from(depth) / f64::from(full))
+    }
+
+    // The occupancy of the event descriptor FIFO is the largest of its write
+    // and read depths.
+    fn event_descriptor_occupancy(&self, write_depth: u8, read_depth: u8) -> Option<f64> {
+        self.event_descriptor
+            .map(|full| f64::from(write_depth.max(read_depth)) / f64::from(full))
+    }
+}
+
+// Maxima of the health fields over all the packets of a board in a time bin.
+// Two of these can be merged, which allows us to process events in parallel.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct Health {
+    packets: u64,
+    max_fifo_depth: u16,
+    max_event_descriptor_write_depth: u8,
+    max_event_descriptor_read_depth: u8,
+    max_channels_over_threshold: usize,
+    near_overflow_packets: u64,
+}
+
+impl Health {
+    fn add(&mut self, packet: &PwbV2Packet, depths: &FifoDepths, near_overflow: f64) {
+        self.packets += 1;
+        self.max_fifo_depth = self.max_fifo_depth.max(packet.fifo_max_depth());
+        self.max_event_descriptor_write_depth = self
+            .max_event_descriptor_write_depth
+            .max(packet.event_descriptor_write_depth());
+        self.max_event_descriptor_read_depth = self
+            .max_event_descriptor_read_depth
+            .max(packet.event_descriptor_read_depth());
+        self.max_channels_over_threshold = self
+            .max_channels_over_threshold
+            .max(packet.channels_over_threshold().len());
+        let occupancies = [
+            depths.sca_occupancy(packet.fifo_max_depth()),
+            depths.event_descriptor_occupancy(
+                packet.event_descriptor_write_depth(),
+                packet.event_descriptor_read_depth(),
+            ),
+        ];
+        if occupancies
+            .into_iter()
+            .flatten()
+            .any(|occupancy| occupancy >= near_overflow)
+        {
+            self.near_overflow_packets += 1;
+        }
+    }
+
+    fn merge(&mut self, other: &Self) {
+        self.packets += other.packets;
+        self.max_fifo_depth = self.max_fifo_depth.max(other.max_fifo_depth);
+        self.max_event_descriptor_write_depth = self
+            .max_event_descriptor_write_depth
+            .max(other.max_event_descriptor_write_depth);
+        self.max_event_descriptor_read_depth = self
+            .max_event_descriptor_read_depth
+            .max(other.max_event_descriptor_read_depth);
+        self.max_channels_over_threshold = self
+            .max_channels_over_threshold
+            .max(other.max_channels_over_threshold);
+        self.near_overflow_packets += other.near_overflow_packets;
+    }
+}
+
+// Indexed by board (see `BoardId::index`) and time bin.
+type Statistics = BTreeMap<(usize, u32), Health>;
+
+fn merge_statistics(mut a: Statistics, b: Statistics) -> Statistics {
+    for (key, health) in b {
+        a.entry(key).or_default().merge(&health);
+    }
+    a
+}
+
+fn main() {
+    alpha_g_analysis::cli::exit_on_error(run());
+}
+
+fn run() -> Result<()> {
+    let args: Args = alpha_g_analysis::cli::parse(env!("CARGO_BIN_NAME"));
+    ensure!(args.bin_width > 0, "`--bin-width` must be positive");
+    ensure!(
+        args.sca_fifo_depth != Some(0) && args.event_descriptor_fifo_depth != Some(0),
+        "FIFO depths must be positive"
+    );
+    let depths = FifoDepths {
+        sca: args.sca_fifo_depth,
+        event_descriptor: args.event_descriptor_fifo_depth,
+    };
+    let files = alpha_g_analysis::expand_run_files(args.files, args.recursive)?;
+    let (run_number, files) = alpha_g_analysis::sort_run_files(files, None)?;
+    // Progress bars were flickering with the default draw target rate.
+    let multi_progress = MultiProgress::with_draw_target(ProgressDrawTarget::stderr_with_hz(1));
+    let tp_bar = multi_progress.add(
+        ProgressBar::new(files.len().try_into().unwrap())
+            .with_style(ProgressStyle::with_template("[{pos}/{len}] Processing").unwrap()),
+    );
+    tp_bar.tick();
+
+    let mut statistics = Statistics::new();
+    let aggregator = Mutex::new(ErrorAggregator::default());
+    let mut census = EventCensus::default();
+    let mut run_start = None;
+    let mut previous_final_timestamp = None;
+    for file in files {
+        let contents = alpha_g_analysis::read(&file)?;
+        let file_view = midasio::FileView::try_from(&contents[..])
+            .with_context(|| format!("failed to parse `{}`", file.display()))?;
+        if let Some(previous_final_timestamp) = previous_final_timestamp {
+            ensure!(
+                file_view.initial_timestamp() - previous_final_timestamp <= 1,
+                "missing file before `{}`",
+                file.display()
+            );
+        }
+        previous_final_timestamp = Some(file_view.final_timestamp());
+        let run_start = *run_start.get_or_insert(file_view.initial_timestamp());
+        census.extend(file_view.iter().map(|event| event.id()));
+
+        let pb = multi_progress.add(
+            ProgressBar::new(file_view.iter().len().try_into().unwrap())
+                .with_style(
+                    ProgressStyle::with_template("[{bar:25}] {percent}%, ETA: {eta}    ({msg})")
+                        .unwrap()
+                        .progress_chars("=> "),
+                )
+                .with_message(format!("{}", file.display())),
+        );
+        let file_statistics = file_view
+            .into_par_iter()
+            .progress_with(pb.clone())
+            .filter(|event| matches!(EventId::try_from(event.id()), Ok(EventId::Main)))
+            .fold(Statistics::new, |mut statistics, event| {
+                let serial_number = event.serial_number();
+                let bin = event.timestamp().saturating_sub(run_start) / args.bin_width;
+
+                let banks = event
+                    .into_iter()
+                    .map(|bank| (bank.name(), bank.data_slice()));
+                let (packets, _) = group_chunks(banks);
+                for packet in packets {
+                    match packet {
+                        Ok(PwbPacket::V2(packet)) => statistics
+                            .entry((packet.board_id().index(), bin))
+                            .or_default()
+                            .add(&packet, &depths, args.near_overflow),
+                        Err(error) => {
+                            let emission = aggregator.lock().unwrap().add(&error);
+                            match emission {
+                                Emission::Warn(unknown) => pb.println(format!(
+                                    "Warning: {unknown} in event `{serial_number}` (`{}` so far)",
+                                    unknown.count
+                                )),
+                                Emission::Other if args.verbose => {
+                                    pb.println(format!("Error in event `{serial_number}`: {error}"))
+                                }
+                                _ => {}
+                            }
+                        }
+                    }
+                }
+
+                statistics
+            })
+            .reduce(Statistics::new, merge_statistics);
+        statistics = merge_statistics(statistics, file_statistics);
+
+        tp_bar.set_style(
+            ProgressStyle::with_template("[{pos}/{len}] Processing, ETA: {eta}").unwrap(),
+        );
+        tp_bar.inc(1);
+    }
+    tp_bar.finish_and_clear();
+    alpha_g_analysis::cli::exit_if_no_main_events(&census);
+    for unknown in aggregator.into_inner().unwrap().unknown_versions() {
+        eprintln!("Warning: found `{}` errors with {unknown}", unknown.count);
+    }
+
+    if depths.is_empty() {
+        eprintln!("Warning: no FIFO depths given, skipping FIFO occupancy check");
+    }
+    let mut near_overflow = BTreeMap::new();
+    for (&(index, _), health) in &statistics {
+        *near_overflow.entry(index).or_insert(0) += health.near_overflow_packets;
+    }
+    for (index, count) in near_overflow.into_iter().filter(|&(_, count)| count > 0) {
+        eprintln!(
+            "Warning: found `{count}` packets near FIFO overflow in board `{}`",
+            BoardId::from_index(index).unwrap().name()
+        );
+    }
+
+    let output = args
+        .output
+        .unwrap_or_else(|| PathBuf::from(format!("R{run_number}_pwb_health")));
+    let mut wtr = alpha_g_analysis::cli::create_output(&output, "csv")?;
+    wtr.write_all(
+        format!(
+            "# {} {}\n# {}\n",
+            env!("CARGO_PKG_NAME"),
+            env!("CARGO_PKG_VERSION"),
+            std::env::args().collect::<Vec<_>>().join(" ")
+        )
+        .as_bytes(),
+    )
+    .context("failed to write csv header")?;
+    let mut wtr = csv::Writer::from_writer(wtr);
+    for ((index, bin), health) in statistics {
+        wtr.serialize(Row {
+            board: BoardId::from_index(index).unwrap().name().to_string(),
+            time: bin * args.bin_width,
+            packets: health.packets,
+            max_fifo_depth: health.max_fifo_depth,
+            max_fifo_occupancy: depths.sca_occupancy(health.max_fifo_depth),
+            max_event_descriptor_write_depth: health.max_event_descriptor_write_depth,
+            max_event_descriptor_read_depth: health.max_event_descriptor_read_depth,
+            max_event_descriptor_occupancy: depths.event_descriptor_occupancy(
+                health.max_event_descriptor_write_depth,
+                health.max_event_descriptor_read_depth,
+            ),
+            max_channels_over_threshold: health.max_channels_over_threshold,
+            near_overflow_packets: (!depths.is_empty()).then_some(health.near_overflow_packets),
+        })
+        .context("failed to write csv row")?;
+    }
+    wtr.flush().context("failed to flush csv data")?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests;
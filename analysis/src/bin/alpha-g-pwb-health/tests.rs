@@ -0,0 +1,94 @@
+use super::*;
+
+// Valid PWB v2 packet with a FIFO max depth of `200`, event descriptor depths
+// of `6` (write) and `7` (read), and 1 channel over threshold.
+const PACKET: [u8; 56] = [
+    2, 65, 0, 0, 236, 40, 255, 135, 84, 2, 1, 0, 2, 0, 0, 0, 0, 0, 0, 0, 100, 0, 255, 1, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 5, 0, 0, 0, 200, 0, 6, 7, 204, 204, 204,
+    204,
+];
+
+fn packet(fifo_max_depth: u16, event_descriptor_write_depth: u8) -> PwbV2Packet {
+    let mut bytes = PACKET;
+    bytes[48..50].copy_from_slice(&fifo_max_depth.to_le_bytes());
+    bytes[50] = event_descriptor_write_depth;
+    PwbV2Packet::try_from(&bytes[..]).unwrap()
+}
+
+const DEPTHS: FifoDepths = FifoDepths {
+    sca: Some(1000),
+    event_descriptor: Some(10),
+};
+
+#[test]
+fn fifo_depths_occupancy() {
+    assert_eq!(DEPTHS.sca_occupancy(200), Some(0.2));
+    // Largest of the write and read depths.
+    assert_eq!(DEPTHS.event_descriptor_occupancy(6, 7), Some(0.7));
+    assert_eq!(DEPTHS.event_descriptor_occupancy(9, 7), Some(0.9));
+
+    let unknown = FifoDepths::default();
+    assert!(unknown.is_empty());
+    assert_eq!(unknown.sca_occupancy(200), None);
+    assert_eq!(unknown.event_descriptor_occupancy(6, 7), None);
+}
+
+#[test]
+fn health_add() {
+    let mut health = Health::default();
+    health.add(&packet(200, 6), &DEPTHS, 0.9);
+    health.add(&packet(100, 8), &DEPTHS, 0.9);
+
+    assert_eq!(health.packets, 2);
+    assert_eq!(health.max_fifo_depth, 200);
+    assert_eq!(health.max_event_descriptor_write_depth, 8);
+    assert_eq!(health.max_event_descriptor_read_depth, 7);
+    assert_eq!(health.max_channels_over_threshold, 1);
+    assert_eq!(health.near_overflow_packets, 0);
+
+    health.add(&packet(950, 0), &DEPTHS, 0.9);
+    assert_eq!(health.near_overflow_packets, 1);
+    health.add(&packet(100, 9), &DEPTHS, 0.9);
+    assert_eq!(health.near_overflow_packets, 2);
+}
+
+#[test]
+fn health_add_unknown_depths() {
+    let mut health = Health::default();
+    health.add(&packet(u16::MAX, u8::MAX), &FifoDepths::default(), 0.0);
+
+    assert_eq!(health.max_fifo_depth, u16::MAX);
+    assert_eq!(health.near_overflow_packets, 0);
+}
+
+#[test]
+fn health_merge() {
+    let (mut a, mut b) = (Health::default(), Health::default());
+    a.add(&packet(200, 6), &DEPTHS, 0.9);
+    b.add(&packet(950, 0), &DEPTHS, 0.9);
+    b.add(&packet(100, 9), &DEPTHS, 0.9);
+
+    let mut all = Health::default();
+    for (fifo_max_depth, event_descriptor_write_depth) in [(200, 6), (950, 0), (100, 9)] {
+        all.add(
+            &packet(fifo_max_depth, event_descriptor_write_depth),
+            &DEPTHS,
+            0.9,
+        );
+    }
+    a.merge(&b);
+    assert_eq!(a, all);
+}
+
+#[test]
+fn merge_statistics_by_board_and_bin() {
+    let mut health = Health::default();
+    health.add(&packet(200, 6), &DEPTHS, 0.9);
+
+    let a = Statistics::from([((0, 0), health), ((0, 1), health)]);
+    let b = Statistics::from([((0, 1), health), ((1, 0), health)]);
+    let merged = merge_statistics(a, b);
+    assert_eq!(merged.len(), 3);
+    assert_eq!(merged[&(0, 1)].packets, 2);
+    assert_eq!(merged[&(1, 0)].packets, 1);
+}
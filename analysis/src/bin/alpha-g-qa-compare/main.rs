@@ -0,0 +1,450 @@
+use anyhow::{bail, ensure, Context, Result};
+use clap::Parser;
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
+
+#[derive(Parser)]
+#[command(version)]
+/// Compare the vertices of a run reconstructed by two different versions
+struct Args {
+    /// CSV output of `alpha-g-vertices` from the old (reference) version
+    old: PathBuf,
+    /// CSV output of `alpha-g-vertices` from the new version (same run)
+    new: PathBuf,
+    /// Write the output to `OUTPUT.json` [default: `qa_compare.json`]
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    /// Maximum fraction of events in which the existence of a vertex changed
+    #[arg(long, default_value_t = 0.01)]
+    max_existence_changed: f64,
+    /// Maximum RMS (in meters) of the difference of each vertex coordinate
+    #[arg(long, default_value_t = 0.001)]
+    max_rms_delta: f64,
+    /// Maximum fraction of events in which the number of tracks changed (only
+    /// if both files have a `num_tracks` column)
+    #[arg(long, default_value_t = 0.01)]
+    max_tracks_changed: f64,
+}
+
+// Vertex information of a single event.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Record {
+    serial_number: u32,
+    vertex: Option<[f64; 3]>,
+    num_tracks: Option<u64>,
+}
+
+// Index of the relevant columns in a vertices CSV. Any other column is ignored
+// (e.g. `--extra-columns` or columns added in newer versions).
+#[derive(Clone, Copy, Debug)]
+struct Columns {
+    serial_number: usize,
+    vertex: [usize; 3],
+    num_tracks: Option<usize>,
+}
+
+impl TryFrom<&csv::StringRecord> for Columns {
+    type Error = anyhow::Error;
+
+    fn try_from(header: &csv::StringRecord) -> Result<Self> {
+        let find = |name: &str| {
+            header
+                .iter()
+                .position(|field| field == name)
+                .with_context(|| format!("missing `{name}` column"))
+        };
+
+        Ok(Self {
+            serial_number: find("serial_number")?,
+            vertex: [
+                find("reconstructed_x")?,
+                find("reconstructed_y")?,
+                find("reconstructed_z")?,
+            ],
+            num_tracks: find("num_tracks").ok(),
+        })
+    }
+}
+
+// Streaming reader of a vertices CSV. Records are checked to be sorted by
+// serial number (which is the order in which `alpha-g-vertices` writes them).
+struct VerticesReader<R> {
+    // First line of the provenance header i.e. `<package_name> <version>`.
+    provenance: Option<String>,
+    columns: Columns,
+    records: csv::StringRecordsIntoIter<BufReader<R>>,
+    previous_serial_number: Option<u32>,
+}
+
+impl<R: Read> VerticesReader<R> {
+    fn new(reader: R) -> Result<Self> {
+        let (comments, mut reader) = alpha_g_analysis::cli::read_commented_csv(reader)?;
+        let provenance = comments
+            .first()
+            .map(|line| line.trim_start_matches('#').trim().to_string());
+        let columns = Columns::try_from(reader.headers().context("failed to read csv header")?)?;
+
+        Ok(Self {
+            provenance,
+            columns,
+            records: reader.into_records(),
+            previous_serial_number: None,
+        })
+    }
+
+    fn has_num_tracks(&self) -> bool {
+        self.columns.num_tracks.is_some()
+    }
+
+    fn next_record(&mut self) -> Result<Option<Record>> {
+        let Some(record) = self.records.next() else {
+            return Ok(None);
+        };
+        let record = record.context("failed to read csv row")?;
+        let field = |index: usize| record.get(index).filter(|field| !field.is_empty());
+
+        let serial_number = field(self.columns.serial_number)
+            .context("missing serial number")?
+            .parse()
+            .context("bad serial number")?;
+        if let Some(previous) = self.previous_serial_number {
+            ensure!(
+                serial_number > previous,
+                "rows not sorted by serial number (`{serial_number}` after `{previous}`)"
+            );
+        }
+        self.previous_serial_number = Some(serial_number);
+
+        let coordinates = self.columns.vertex.map(field);
+        let vertex = match coordinates {
+            [Some(x), Some(y), Some(z)] => Some([
+                x.parse().context("bad reconstructed_x")?,
+                y.parse().context("bad reconstructed_y")?,
+                z.parse().context("bad reconstructed_z")?,
+            ]),
+            [None, None, None] => None,
+            _ => bail!("incomplete vertex in event `{serial_number}`"),
+        };
+        let num_tracks = self
+            .columns
+            .num_tracks
+            .and_then(field)
+            .map(|n| n.parse().context("bad num_tracks"))
+            .transpose()?;
+
+        Ok(Some(Record {
+            serial_number,
+            vertex,
+            num_tracks,
+        }))
+    }
+}
+
+// Running statistics of the difference of a vertex coordinate.
+#[derive(Clone, Copy, Debug, Default)]
+struct DeltaStats {
+    count: usize,
+    mean: f64,
+    // Sum of squared differences from the mean (Welford's algorithm).
+    m2: f64,
+    max_abs: f64,
+}
+
+impl DeltaStats {
+    fn add(&mut self, delta: f64) {
+        self.count += 1;
+        let diff = delta - self.mean;
+        self.mean += diff / self.count as f64;
+        self.m2 += diff * (delta - self.mean);
+        self.max_abs = self.max_abs.max(delta.abs());
+    }
+
+    fn std_dev(&self) -> f64 {
+        if self.count == 0 {
+            0.0
+        } else {
+            (self.m2 / self.count as f64).sqrt()
+        }
+    }
+
+    fn rms(&self) -> f64 {
+        (self.mean.powi(2) + self.std_dev().powi(2)).sqrt()
+    }
+}
+
+impl Serialize for DeltaStats {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        struct Summary {
+            count: usize,
+            mean: f64,
+            std_dev: f64,
+            rms: f64,
+            max_abs: f64,
+        }
+
+        Summary {
+            count: self.count,
+            mean: self.mean,
+            std_dev: self.std_dev(),
+            rms: self.rms(),
+            max_abs: self.max_abs,
+        }
+        .serialize(serializer)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize)]
+struct DeltaReport {
+    x: DeltaStats,
+    y: DeltaStats,
+    z: DeltaStats,
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize)]
+struct ExistenceReport {
+    // Events with a vertex only in the old version.
+    lost: usize,
+    // Events with a vertex only in the new version.
+    gained: usize,
+    changed_fraction: f64,
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize)]
+struct TracksReport {
+    // Events in which both versions report a number of tracks.
+    compared: usize,
+    changed: usize,
+    changed_fraction: f64,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+struct Comparison {
+    matched_events: usize,
+    only_old_events: usize,
+    only_new_events: usize,
+    vertex_existence: ExistenceReport,
+    vertex_deltas: DeltaReport,
+    // `None` if any of the files doesn't have a `num_tracks` column.
+    tracks: Option<TracksReport>,
+}
+
+impl Comparison {
+    fn add(&mut self, old: Record, new: Record) {
+        self.matched_events += 1;
+        match (old.vertex, new.vertex) {
+            (Some(old), Some(new)) => {
+                self.vertex_deltas.x.add(new[0] - old[0]);
+                self.vertex_deltas.y.add(new[1] - old[1]);
+                self.vertex_deltas.z.add(new[2] - old[2]);
+            }
+            (Some(_), None) => self.vertex_existence.lost += 1,
+            (None, Some(_)) => self.vertex_existence.gained += 1,
+            (None, None) => {}
+        }
+        if let (Some(tracks), Some(old), Some(new)) =
+            (&mut self.tracks, old.num_tracks, new.num_tracks)
+        {
+            tracks.compared += 1;
+            if old != new {
+                tracks.changed += 1;
+            }
+        }
+    }
+
+    fn finish(&mut self) {
+        let fraction = |count: usize, total: usize| {
+            if total == 0 {
+                0.0
+            } else {
+                count as f64 / total as f64
+            }
+        };
+        let existence = &mut self.vertex_existence;
+        existence.changed_fraction =
+            fraction(existence.lost + existence.gained, self.matched_events);
+        if let Some(tracks) = &mut self.tracks {
+            tracks.changed_fraction = fraction(tracks.changed, tracks.compared);
+        }
+    }
+}
+
+// Join two vertices CSVs on the serial number and compare all the events.
+fn compare<R1: Read, R2: Read>(
+    old: &mut VerticesReader<R1>,
+    new: &mut VerticesReader<R2>,
+) -> Result<Comparison> {
+    let mut comparison = Comparison {
+        tracks: (old.has_num_tracks() && new.has_num_tracks()).then(TracksReport::default),
+        ..Default::default()
+    };
+
+    let (mut old_record, mut new_record) = (old.next_record()?, new.next_record()?);
+    loop {
+        match (old_record, new_record) {
+            (Some(o), Some(n)) => match o.serial_number.cmp(&n.serial_number) {
+                Ordering::Equal => {
+                    comparison.add(o, n);
+                    old_record = old.next_record()?;
+                    new_record = new.next_record()?;
+                }
+                Ordering::Less => {
+                    comparison.only_old_events += 1;
+                    old_record = old.next_record()?;
+                }
+                Ordering::Greater => {
+                    comparison.only_new_events += 1;
+                    new_record = new.next_record()?;
+                }
+            },
+            (Some(_), None) => {
+                comparison.only_old_events += 1;
+                old_record = old.next_record()?;
+            }
+            (None, Some(_)) => {
+                comparison.only_new_events += 1;
+                new_record = new.next_record()?;
+            }
+            (None, None) => break,
+        }
+    }
+    comparison.finish();
+
+    Ok(comparison)
+}
+
+// Thresholds that a comparison has to satisfy to pass.
+#[derive(Clone, Copy, Debug)]
+struct Thresholds {
+    max_existence_changed: f64,
+    max_rms_delta: f64,
+    max_tracks_changed: f64,
+}
+
+#[derive(Clone, Debug, Serialize)]
+struct Verdict {
+    pass: bool,
+    // Human readable reason of each failed check.
+    failures: Vec<String>,
+}
+
+fn verdict(comparison: &Comparison, thresholds: Thresholds) -> Verdict {
+    let mut failures = Vec::new();
+    // Both files are supposed to come from the same run.
+    if comparison.only_old_events + comparison.only_new_events > 0 {
+        failures.push(format!(
+            "unmatched events (`{}` only in old, `{}` only in new)",
+            comparison.only_old_events, comparison.only_new_events
+        ));
+    }
+    let changed = comparison.vertex_existence.changed_fraction;
+    if changed > thresholds.max_existence_changed {
+        failures.push(format!(
+            "vertex existence changed in a fraction `{changed}` of events (maximum `{}`)",
+            thresholds.max_existence_changed
+        ));
+    }
+    let deltas = &comparison.vertex_deltas;
+    for (axis, stats) in [("x", deltas.x), ("y", deltas.y), ("z", deltas.z)] {
+        let rms = stats.rms();
+        if rms > thresholds.max_rms_delta {
+            failures.push(format!(
+                "RMS of delta {axis} is `{rms}` m (maximum `{}` m)",
+                thresholds.max_rms_delta
+            ));
+        }
+    }
+    if let Some(tracks) = comparison.tracks {
+        if tracks.changed_fraction > thresholds.max_tracks_changed {
+            failures.push(format!(
+                "number of tracks changed in a fraction `{}` of events (maximum `{}`)",
+                tracks.changed_fraction, thresholds.max_tracks_changed
+            ));
+        }
+    }
+
+    Verdict {
+        pass: failures.is_empty(),
+        failures,
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct InputInfo {
+    file: PathBuf,
+    provenance: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct Report {
+    old: InputInfo,
+    new: InputInfo,
+    #[serde(flatten)]
+    comparison: Comparison,
+    verdict: Verdict,
+}
+
+fn open_vertices(path: &Path) -> Result<VerticesReader<std::fs::File>> {
+    let file = alpha_g_analysis::cli::open_input(path)?;
+    VerticesReader::new(file).with_context(|| format!("failed to read `{}`", path.display()))
+}
+
+fn main() {
+    alpha_g_analysis::cli::exit_on_error(run());
+}
+
+fn run() -> Result<()> {
+    let args: Args = alpha_g_analysis::cli::parse(env!("CARGO_BIN_NAME"));
+
+    let mut old = open_vertices(&args.old)?;
+    let mut new = open_vertices(&args.new)?;
+    if old.provenance.is_some() && old.provenance == new.provenance {
+        eprintln!("Warning: both files were produced by the same version");
+    }
+
+    let comparison = compare(&mut old, &mut new).context("failed to compare vertices")?;
+    let verdict = verdict(
+        &comparison,
+        Thresholds {
+            max_existence_changed: args.max_existence_changed,
+            max_rms_delta: args.max_rms_delta,
+            max_tracks_changed: args.max_tracks_changed,
+        },
+    );
+    let pass = verdict.pass;
+    let report = Report {
+        old: InputInfo {
+            file: args.old,
+            provenance: old.provenance,
+        },
+        new: InputInfo {
+            file: args.new,
+            provenance: new.provenance,
+        },
+        comparison,
+        verdict,
+    };
+
+    let output = args
+        .output
+        .unwrap_or_else(|| PathBuf::from("qa_compare"))
+        .with_extension("json");
+    std::fs::write(
+        &output,
+        serde_json::to_string_pretty(&report).context("failed to serialize report")? + "\n",
+    )
+    .with_context(|| format!("failed to write `{}`", output.display()))?;
+    eprintln!("Created `{}`", output.display());
+
+    for failure in &report.verdict.failures {
+        eprintln!("Failed: {failure}");
+    }
+    ensure!(pass, "QA comparison failed");
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,155 @@
+use super::*;
+
+const OLD: &str = "# alpha-g-analysis 0.5.7
+# alpha-g-vertices run.mid
+serial_number,trg_time,reconstructed_x,reconstructed_y,reconstructed_z
+1,0.1,0.01,0.02,0.5
+2,0.2,,,
+3,0.3,0.01,0.02,-0.5
+5,0.5,0.00,0.00,0.1
+";
+
+// Newer version with extra columns (in between and at the end) and a warning.
+const NEW: &str = "# alpha-g-analysis 0.5.8
+# alpha-g-vertices run.mid --extra-columns
+# WARNING: run number overridden to 1
+serial_number,trg_time,num_tracks,reconstructed_x,reconstructed_y,reconstructed_z,late_fraction
+1,0.1,2,0.011,0.02,0.5,0.1
+2,0.2,1,0.0,0.0,0.0,
+3,0.3,2,,,,0.0
+4,0.4,0,,,,
+5,0.5,2,0.00,0.00,0.098,0.5
+";
+
+fn reader(contents: &str) -> VerticesReader<&[u8]> {
+    VerticesReader::new(contents.as_bytes()).unwrap()
+}
+
+#[test]
+fn vertices_reader_columns_and_provenance() {
+    let mut old = reader(OLD);
+    assert_eq!(old.provenance.as_deref(), Some("alpha-g-analysis 0.5.7"));
+    assert!(!old.has_num_tracks());
+    assert_eq!(
+        old.next_record().unwrap(),
+        Some(Record {
+            serial_number: 1,
+            vertex: Some([0.01, 0.02, 0.5]),
+            num_tracks: None,
+        })
+    );
+    assert_eq!(
+        old.next_record().unwrap(),
+        Some(Record {
+            serial_number: 2,
+            vertex: None,
+            num_tracks: None,
+        })
+    );
+
+    let mut new = reader(NEW);
+    assert_eq!(new.provenance.as_deref(), Some("alpha-g-analysis 0.5.8"));
+    assert!(new.has_num_tracks());
+    assert_eq!(
+        new.next_record().unwrap(),
+        Some(Record {
+            serial_number: 1,
+            vertex: Some([0.011, 0.02, 0.5]),
+            num_tracks: Some(2),
+        })
+    );
+}
+
+#[test]
+fn vertices_reader_bad_input() {
+    assert!(VerticesReader::new("serial_number,reconstructed_x\n1,0.0\n".as_bytes()).is_err());
+
+    let mut unsorted =
+        reader("serial_number,reconstructed_x,reconstructed_y,reconstructed_z\n2,,,\n1,,,\n");
+    assert!(unsorted.next_record().is_ok());
+    assert!(unsorted.next_record().is_err());
+
+    let mut incomplete =
+        reader("serial_number,reconstructed_x,reconstructed_y,reconstructed_z\n1,0.0,,0.0\n");
+    assert!(incomplete.next_record().is_err());
+}
+
+#[test]
+fn compare_old_new() {
+    let comparison = compare(&mut reader(OLD), &mut reader(NEW)).unwrap();
+
+    assert_eq!(comparison.matched_events, 4);
+    assert_eq!(comparison.only_old_events, 0);
+    assert_eq!(comparison.only_new_events, 1);
+    assert_eq!(comparison.vertex_existence.lost, 1);
+    assert_eq!(comparison.vertex_existence.gained, 1);
+    assert_eq!(comparison.vertex_existence.changed_fraction, 0.5);
+    // The old version has no track counts.
+    assert!(comparison.tracks.is_none());
+
+    let deltas = comparison.vertex_deltas;
+    assert_eq!(deltas.x.count, 2);
+    assert!((deltas.x.mean - 0.0005).abs() < 1e-12);
+    assert!((deltas.x.max_abs - 0.001).abs() < 1e-12);
+    assert_eq!(deltas.y.max_abs, 0.0);
+    assert!((deltas.z.mean + 0.001).abs() < 1e-12);
+    assert!((deltas.z.rms() - 2e-6f64.sqrt()).abs() < 1e-12);
+}
+
+#[test]
+fn compare_same_file() {
+    let comparison = compare(&mut reader(NEW), &mut reader(NEW)).unwrap();
+
+    assert_eq!(comparison.matched_events, 5);
+    assert_eq!(comparison.only_old_events + comparison.only_new_events, 0);
+    assert_eq!(comparison.vertex_existence.changed_fraction, 0.0);
+    assert_eq!(comparison.vertex_deltas.z.rms(), 0.0);
+    let tracks = comparison.tracks.unwrap();
+    assert_eq!(tracks.compared, 5);
+    assert_eq!(tracks.changed, 0);
+
+    let thresholds = Thresholds {
+        max_existence_changed: 0.0,
+        max_rms_delta: 0.0,
+        max_tracks_changed: 0.0,
+    };
+    let verdict = verdict(&comparison, thresholds);
+    assert!(verdict.pass);
+    assert!(verdict.failures.is_empty());
+}
+
+#[test]
+fn verdict_failures() {
+    let comparison = compare(&mut reader(OLD), &mut reader(NEW)).unwrap();
+    let thresholds = Thresholds {
+        max_existence_changed: 0.5,
+        max_rms_delta: 0.01,
+        max_tracks_changed: 0.0,
+    };
+    // Only the unmatched event fails.
+    let verdict_result = verdict(&comparison, thresholds);
+    assert!(!verdict_result.pass);
+    assert_eq!(verdict_result.failures.len(), 1);
+
+    let thresholds = Thresholds {
+        max_existence_changed: 0.1,
+        max_rms_delta: 0.0005,
+        ..thresholds
+    };
+    // Unmatched, existence, x, and z.
+    assert_eq!(verdict(&comparison, thresholds).failures.len(), 4);
+}
+
+#[test]
+fn delta_stats_welford() {
+    let mut stats = DeltaStats::default();
+    for delta in [1.0, -1.0, 3.0, -3.0] {
+        stats.add(delta);
+    }
+
+    assert_eq!(stats.count, 4);
+    assert_eq!(stats.mean, 0.0);
+    assert!((stats.std_dev() - 5.0f64.sqrt()).abs() < 1e-12);
+    assert!((stats.rms() - 5.0f64.sqrt()).abs() < 1e-12);
+    assert_eq!(stats.max_abs, 3.0);
+}
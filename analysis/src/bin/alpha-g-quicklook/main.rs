@@ -0,0 +1,340 @@
+use alpha_g_analysis::midas::OwnedEvent;
+use alpha_g_analysis::EventIter;
+use alpha_g_detector::alpha16::aw_map::{ANODE_WIRES_RADIUS, INNER_CATHODE_RADIUS};
+use alpha_g_detector::midas::EventId;
+use alpha_g_detector::padwing::map::{CATHODE_PADS_RADIUS, DETECTOR_LENGTH};
+use alpha_g_detector::run::RunNumber;
+use alpha_g_physics::reconstruction::{cluster_spacepoints, Track};
+use alpha_g_physics::{MainEvent, SpacePoint};
+use anyhow::{Context, Result};
+use clap::Parser;
+use std::fmt::Write as _;
+use std::io::BufRead;
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+use uom::si::f64::Length;
+use uom::si::length::{meter, millimeter};
+
+#[derive(Parser)]
+#[command(version)]
+/// Quick look at the reconstruction of a run in the terminal
+struct Args {
+    /// MIDAS files (or directories with them) from the run you want to inspect
+    #[arg(required = true)]
+    files: Vec<PathBuf>,
+    /// Also search for MIDAS files in subdirectories of the input directories
+    #[arg(long)]
+    recursive: bool,
+    /// Start at the main event with this serial number [default: first main
+    /// event of the run]
+    #[arg(long, value_name = "N")]
+    serial: Option<u32>,
+    /// Skip all events with fewer spacepoints than this
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    min_spacepoints: usize,
+    /// Ignore the run number in the MIDAS files and use this one instead
+    #[arg(long, value_name = "RUN_NUMBER")]
+    run_number_override: Option<RunNumber>,
+}
+
+// Size (in characters) of the x-y view. Terminal cells are roughly twice as
+// tall as they are wide, so this looks square.
+const WIDTH: usize = 80;
+const HEIGHT: usize = 40;
+
+// Grid of characters that maps a rectangle in the plane to terminal cells.
+#[derive(Debug)]
+struct Raster {
+    cells: Vec<Vec<char>>,
+    x_range: RangeInclusive<f64>,
+    y_range: RangeInclusive<f64>,
+}
+
+impl Raster {
+    fn new(width: usize, height: usize, x: RangeInclusive<f64>, y: RangeInclusive<f64>) -> Self {
+        Self {
+            cells: vec![vec![' '; width]; height],
+            x_range: x,
+            y_range: y,
+        }
+    }
+    // Row and column of the cell that contains a point. The `y` axis points
+    // up.
+    fn cell(&self, x: f64, y: f64) -> Option<(usize, usize)> {
+        let index = |value: f64, range: &RangeInclusive<f64>, len: usize| {
+            if !range.contains(&value) {
+                return None;
+            }
+            let fraction = (value - range.start()) / (range.end() - range.start());
+            Some(((fraction * len as f64) as usize).min(len - 1))
+        };
+        let height = self.cells.len();
+        let row = height - 1 - index(y, &self.y_range, height)?;
+        let column = index(x, &self.x_range, self.cells[0].len())?;
+
+        Some((row, column))
+    }
+    // Points outside the raster are ignored.
+    fn plot(&mut self, x: f64, y: f64, c: char) {
+        if let Some((row, column)) = self.cell(x, y) {
+            self.cells[row][column] = c;
+        }
+    }
+    // Same as `plot`, but it doesn't overwrite anything. Used to draw the
+    // detector outline behind the data.
+    fn plot_background(&mut self, x: f64, y: f64, c: char) {
+        if let Some((row, column)) = self.cell(x, y) {
+            if self.cells[row][column] == ' ' {
+                self.cells[row][column] = c;
+            }
+        }
+    }
+    // Raster with a frame around it.
+    fn render(&self) -> String {
+        let border = format!("+{}+\n", "-".repeat(self.cells[0].len()));
+        let mut out = border.clone();
+        for row in &self.cells {
+            writeln!(out, "|{}|", row.iter().collect::<String>()).unwrap();
+        }
+        out.push_str(&border);
+
+        out
+    }
+}
+
+// Everything shown about a single event. All lengths are in meters.
+#[derive(Debug)]
+struct QuickLook {
+    serial_number: u32,
+    // As `[x, y, z]`.
+    spacepoints: Vec<[f64; 3]>,
+    // Number of points and RMS residual of each track.
+    tracks: Vec<(usize, f64)>,
+    // Position and number of associated tracks.
+    vertex: Option<([f64; 3], usize)>,
+    error: Option<String>,
+}
+
+impl QuickLook {
+    fn new(run_number: RunNumber, event: &OwnedEvent) -> Self {
+        let mut look = Self {
+            serial_number: event.serial_number,
+            spacepoints: Vec::new(),
+            tracks: Vec::new(),
+            vertex: None,
+            error: None,
+        };
+        let banks = event.banks.iter().filter_map(|bank| {
+            std::str::from_utf8(&bank.name)
+                .ok()
+                .map(|name| (name, &bank.data[..]))
+        });
+        let event = match MainEvent::try_from_banks(run_number, banks) {
+            Ok(event) => event,
+            Err(error) => {
+                look.error = Some(error.to_string());
+                return look;
+            }
+        };
+        // Same spacepoints as in `MainEvent::vertex`.
+        let spacepoints: Vec<SpacePoint> = event
+            .avalanches()
+            .into_iter()
+            .filter_map(|avalanche| avalanche.try_into().ok())
+            .collect();
+        look.spacepoints = spacepoints
+            .iter()
+            .map(|p| [p.x(), p.y(), p.z].map(|l| l.get::<meter>()))
+            .collect();
+        look.tracks = cluster_spacepoints(spacepoints.clone())
+            .clusters
+            .into_iter()
+            .filter_map(|cluster| Track::try_from(cluster).ok())
+            .map(|track| (track.num_points(), track.rms_residual().get::<meter>()))
+            .collect();
+        match alpha_g_physics::try_vertex_info_from_spacepoints(spacepoints) {
+            Ok(vertex) => {
+                look.vertex = vertex.map(|info| {
+                    let p = info.position;
+                    ([p.x, p.y, p.z].map(|l| l.get::<meter>()), info.tracks.len())
+                })
+            }
+            Err(error) => look.error = Some(error.to_string()),
+        }
+
+        look
+    }
+}
+
+// Text with the x-y and r-z views of an event followed by a numeric summary.
+// Space points are drawn as `*` and the vertex as `X` (on top of everything
+// else). The inner cathode and the anode wires are drawn as `.`.
+fn render(look: &QuickLook) -> String {
+    let mut xy = Raster::new(
+        WIDTH,
+        HEIGHT,
+        -CATHODE_PADS_RADIUS..=CATHODE_PADS_RADIUS,
+        -CATHODE_PADS_RADIUS..=CATHODE_PADS_RADIUS,
+    );
+    let half_length = DETECTOR_LENGTH / 2.0;
+    let mut rz = Raster::new(
+        WIDTH,
+        HEIGHT / 2,
+        -half_length..=half_length,
+        0.0..=CATHODE_PADS_RADIUS,
+    );
+    for radius in [INNER_CATHODE_RADIUS, ANODE_WIRES_RADIUS] {
+        for i in 0..360 {
+            let phi = f64::from(i).to_radians();
+            xy.plot_background(radius * phi.cos(), radius * phi.sin(), '.');
+        }
+        for i in 0..=WIDTH {
+            let z = -half_length + DETECTOR_LENGTH * i as f64 / WIDTH as f64;
+            rz.plot_background(z, radius, '.');
+        }
+    }
+    for &[x, y, z] in &look.spacepoints {
+        xy.plot(x, y, '*');
+        rz.plot(z, x.hypot(y), '*');
+    }
+    if let Some(([x, y, z], _)) = look.vertex {
+        xy.plot(x, y, 'X');
+        rz.plot(z, x.hypot(y), 'X');
+    }
+
+    let mm = |v: f64| Length::new::<meter>(v).get::<millimeter>();
+    let mut out = String::new();
+    writeln!(
+        out,
+        "x-y view ({:.0} mm across)",
+        mm(2.0 * CATHODE_PADS_RADIUS)
+    )
+    .unwrap();
+    out.push_str(&xy.render());
+    writeln!(
+        out,
+        "r-z view ({:.0} mm long, {:.0} mm high)",
+        mm(DETECTOR_LENGTH),
+        mm(CATHODE_PADS_RADIUS)
+    )
+    .unwrap();
+    out.push_str(&rz.render());
+
+    writeln!(
+        out,
+        "Event {}: {} spacepoints, {} tracks",
+        look.serial_number,
+        look.spacepoints.len(),
+        look.tracks.len()
+    )
+    .unwrap();
+    for (index, (num_points, rms_residual)) in look.tracks.iter().enumerate() {
+        writeln!(
+            out,
+            "  track {index}: {num_points} points, rms residual {:.2} mm",
+            mm(*rms_residual)
+        )
+        .unwrap();
+    }
+    match look.vertex {
+        Some(([x, y, z], num_tracks)) => writeln!(
+            out,
+            "Vertex: ({:.1}, {:.1}, {:.1}) mm from {num_tracks} tracks",
+            mm(x),
+            mm(y),
+            mm(z)
+        )
+        .unwrap(),
+        None => writeln!(out, "Vertex: none").unwrap(),
+    }
+    if let Some(error) = &look.error {
+        writeln!(out, "Error: {error}").unwrap();
+    }
+
+    out
+}
+
+// All the main events in the run files (in order).
+fn main_events(files: Vec<PathBuf>) -> impl Iterator<Item = Result<OwnedEvent>> {
+    let mut files = files.into_iter();
+    let mut current: Option<EventIter> = None;
+    std::iter::from_fn(move || loop {
+        if let Some(events) = current.as_mut() {
+            match events.next() {
+                Some(Ok(event)) if !matches!(EventId::try_from(event.id), Ok(EventId::Main)) => {
+                    continue
+                }
+                Some(result) => return Some(result.map_err(Into::into)),
+                None => current = None,
+            }
+        }
+        match alpha_g_analysis::event_iter(files.next()?) {
+            Ok(events) => current = Some(events),
+            Err(error) => return Some(Err(error.into())),
+        }
+    })
+}
+
+fn main() {
+    alpha_g_analysis::cli::exit_on_error(run());
+}
+
+fn run() -> Result<()> {
+    let args: Args = alpha_g_analysis::cli::parse(env!("CARGO_BIN_NAME"));
+    let files = alpha_g_analysis::expand_run_files(args.files, args.recursive)?;
+    let (run_number, files) = alpha_g_analysis::sort_run_files(files, args.run_number_override)?;
+
+    let mut events = main_events(files)
+        .skip_while(|event| match (event, args.serial) {
+            (Ok(event), Some(serial_number)) => event.serial_number != serial_number,
+            _ => false,
+        })
+        .map(|event| event.map(|event| QuickLook::new(run_number, &event)))
+        .filter(|look| match look {
+            Ok(look) => look.spacepoints.len() >= args.min_spacepoints,
+            Err(_) => true,
+        });
+    // Events are only reconstructed once; going back shows them from here.
+    let mut history = Vec::new();
+    let mut index = 0;
+    let mut commands = std::io::stdin().lock().lines();
+    loop {
+        if index == history.len() {
+            match events.next().transpose()? {
+                Some(look) => history.push(render(&look)),
+                None if history.is_empty() => {
+                    println!("No events to show in run `{run_number}`");
+                    return Ok(());
+                }
+                None => {
+                    eprintln!("No more events");
+                    index -= 1;
+                }
+            }
+        }
+        print!("{}", history[index]);
+
+        loop {
+            eprint!("[n]ext, [p]revious, or [q]uit: ");
+            let Some(command) = commands.next() else {
+                // EOF e.g. stdin is not interactive.
+                eprintln!();
+                return Ok(());
+            };
+            match command.context("failed to read command")?.trim() {
+                "n" => index += 1,
+                "p" if index == 0 => {
+                    eprintln!("No previous event");
+                    continue;
+                }
+                "p" => index -= 1,
+                "q" => return Ok(()),
+                _ => continue,
+            }
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,80 @@
+use super::*;
+
+#[test]
+fn raster_snapshot() {
+    let mut raster = Raster::new(8, 4, 0.0..=8.0, 0.0..=4.0);
+    raster.plot(0.0, 0.0, '*');
+    raster.plot(8.0, 4.0, '*');
+    raster.plot(3.5, 1.5, 'X');
+    raster.plot_background(3.5, 1.5, '.');
+    raster.plot_background(5.5, 2.5, '.');
+    // Outside the raster.
+    raster.plot(-1.0, 2.0, '*');
+    raster.plot(2.0, 4.5, '*');
+
+    assert_eq!(
+        raster.render(),
+        "\
++--------+
+|       *|
+|     .  |
+|   X    |
+|*       |
++--------+
+"
+    );
+}
+
+fn look() -> QuickLook {
+    QuickLook {
+        serial_number: 42,
+        spacepoints: vec![[0.15, 0.0, 0.5], [-0.15, 0.0, -0.5]],
+        tracks: vec![(12, 0.0015)],
+        vertex: Some(([0.0, 0.0, 0.0], 2)),
+        error: None,
+    }
+}
+
+#[test]
+fn render_snapshot() {
+    let rendered = render(&look());
+    let lines: Vec<_> = rendered.lines().collect();
+    // Title and frame of both views, and the summary.
+    assert_eq!(lines.len(), 1 + (HEIGHT + 2) + 1 + (HEIGHT / 2 + 2) + 3);
+    assert!(lines[1..HEIGHT + 3]
+        .iter()
+        .chain(&lines[HEIGHT + 4..HEIGHT + HEIGHT / 2 + 6])
+        .all(|line| line.chars().count() == WIDTH + 2));
+
+    assert_eq!(lines[0], "x-y view (380 mm across)");
+    // The vertex is at the center of the x-y view, and both spacepoints are
+    // on the same row.
+    let center = lines[1 + HEIGHT / 2];
+    assert_eq!(center.find('X'), Some(1 + WIDTH / 2));
+    assert_eq!(center.matches('*').count(), 2);
+
+    assert_eq!(lines[HEIGHT + 3], "r-z view (2304 mm long, 190 mm high)");
+    // The vertex is at the bottom of the r-z view (i.e. `r = 0`).
+    let bottom = lines[HEIGHT + 4 + HEIGHT / 2];
+    assert_eq!(bottom.find('X'), Some(1 + WIDTH / 2));
+
+    assert_eq!(
+        lines[lines.len() - 3..],
+        [
+            "Event 42: 2 spacepoints, 1 tracks",
+            "  track 0: 12 points, rms residual 1.50 mm",
+            "Vertex: (0.0, 0.0, 0.0) mm from 2 tracks",
+        ]
+    );
+}
+
+#[test]
+fn render_error() {
+    let mut look = look();
+    look.vertex = None;
+    look.error = Some(String::from("bad vertex"));
+    let rendered = render(&look);
+
+    assert!(!rendered.contains('X'));
+    assert!(rendered.ends_with("Vertex: none\nError: bad vertex\n"));
+}
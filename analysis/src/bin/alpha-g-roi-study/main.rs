@@ -0,0 +1,341 @@
+use alpha_g_analysis::EventCensus;
+use alpha_g_detector::midas::EventId;
+use alpha_g_physics::{DecodeOptions, MainEvent, RoiReduction, RoiSettings, RunCalibration};
+use anyhow::{ensure, Context, Result};
+use clap::Parser;
+use indicatif::{
+    MultiProgress, ParallelProgressIterator, ProgressBar, ProgressDrawTarget, ProgressStyle,
+};
+use rayon::prelude::*;
+use std::fmt;
+use std::io::Write;
+use std::path::PathBuf;
+use uom::si::f64::Length;
+use uom::si::length::{meter, millimeter};
+
+#[derive(Parser)]
+#[command(version)]
+/// Reconstruct the vertices of a single run with and without an emulated region
+/// of interest readout of the TPC
+struct Args {
+    /// MIDAS files (or directories with them) from the run you want to reconstruct
+    #[arg(required = true)]
+    files: Vec<PathBuf>,
+    /// Also search for MIDAS files in subdirectories of the input directories
+    #[arg(long)]
+    recursive: bool,
+    /// Write the output to `OUTPUT.csv`, or to stdout if `-` [default:
+    /// `R<run_number>_roi_study.csv`]
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    /// Number of samples kept before the first threshold crossing of a signal
+    #[arg(long)]
+    pre_samples: usize,
+    /// Number of samples kept from the first threshold crossing of a signal
+    /// onwards
+    #[arg(long)]
+    post_samples: usize,
+    /// Threshold on the absolute value of the calibrated signals
+    #[arg(long)]
+    threshold: f64,
+    /// Print detailed information about errors (if any)
+    #[arg(short, long)]
+    verbose: bool,
+}
+
+#[derive(Debug, Default, serde::Serialize)]
+struct Row {
+    serial_number: u32,
+    full_samples: Option<usize>,
+    roi_samples: Option<usize>,
+    size_reduction: Option<f64>,
+    full_x: Option<f64>,
+    full_y: Option<f64>,
+    full_z: Option<f64>,
+    roi_x: Option<f64>,
+    roi_y: Option<f64>,
+    roi_z: Option<f64>,
+}
+
+impl Row {
+    // Vertices are in meters.
+    fn new(
+        serial_number: u32,
+        reduction: RoiReduction,
+        full: Option<[f64; 3]>,
+        roi: Option<[f64; 3]>,
+    ) -> Self {
+        Self {
+            serial_number,
+            full_samples: Some(reduction.full_samples),
+            roi_samples: Some(reduction.roi_samples),
+            size_reduction: reduction.fraction(),
+            full_x: full.map(|v| v[0]),
+            full_y: full.map(|v| v[1]),
+            full_z: full.map(|v| v[2]),
+            roi_x: roi.map(|v| v[0]),
+            roi_y: roi.map(|v| v[1]),
+            roi_z: roi.map(|v| v[2]),
+        }
+    }
+
+    fn full_vertex(&self) -> Option<[f64; 3]> {
+        Some([self.full_x?, self.full_y?, self.full_z?])
+    }
+
+    fn roi_vertex(&self) -> Option<[f64; 3]> {
+        Some([self.roi_x?, self.roi_y?, self.roi_z?])
+    }
+}
+
+// Comparison between the full and the ROI reconstruction of all the events in
+// a run. Rows of events that failed to decode are ignored.
+#[derive(Debug, Default)]
+struct Summary {
+    events: usize,
+    full_vertices: usize,
+    roi_vertices: usize,
+    // Events with a vertex in both reconstructions, and the sum (and sum of
+    // squares) of their residuals (ROI - full) in meters.
+    paired_vertices: usize,
+    residual_sum: [f64; 3],
+    residual_sum_sq: [f64; 3],
+    full_samples: usize,
+    roi_samples: usize,
+}
+
+impl Summary {
+    fn add(&mut self, row: &Row) {
+        let (Some(full_samples), Some(roi_samples)) = (row.full_samples, row.roi_samples) else {
+            return;
+        };
+        self.events += 1;
+        self.full_samples += full_samples;
+        self.roi_samples += roi_samples;
+
+        let (full, roi) = (row.full_vertex(), row.roi_vertex());
+        self.full_vertices += usize::from(full.is_some());
+        self.roi_vertices += usize::from(roi.is_some());
+        if let (Some(full), Some(roi)) = (full, roi) {
+            self.paired_vertices += 1;
+            for i in 0..3 {
+                let residual = roi[i] - full[i];
+                self.residual_sum[i] += residual;
+                self.residual_sum_sq[i] += residual * residual;
+            }
+        }
+    }
+    // Number of ROI vertices per full vertex.
+    fn efficiency_ratio(&self) -> Option<f64> {
+        (self.full_vertices != 0).then(|| self.roi_vertices as f64 / self.full_vertices as f64)
+    }
+    // Mean and standard deviation of the residuals in each coordinate.
+    fn residuals(&self) -> Option<[(f64, f64); 3]> {
+        if self.paired_vertices == 0 {
+            return None;
+        }
+        let n = self.paired_vertices as f64;
+        Some(std::array::from_fn(|i| {
+            let mean = self.residual_sum[i] / n;
+            let variance = (self.residual_sum_sq[i] / n - mean * mean).max(0.0);
+            (mean, variance.sqrt())
+        }))
+    }
+    // Fraction of all the samples in the run that are not read out.
+    fn size_reduction(&self) -> Option<f64> {
+        RoiReduction {
+            full_samples: self.full_samples,
+            roi_samples: self.roi_samples,
+        }
+        .fraction()
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Vertices: {} full, {} ROI out of {} events",
+            self.full_vertices, self.roi_vertices, self.events
+        )?;
+        if let Some(ratio) = self.efficiency_ratio() {
+            write!(f, " (efficiency ratio {ratio:.3})")?;
+        }
+        if let Some(residuals) = self.residuals() {
+            write!(
+                f,
+                "\nResiduals (ROI - full) of {} events:",
+                self.paired_vertices
+            )?;
+            let mm = |v: f64| Length::new::<meter>(v).get::<millimeter>();
+            for (name, (mean, std_dev)) in ["x", "y", "z"].into_iter().zip(residuals) {
+                write!(f, " {name} {:.2} ± {:.2} mm", mm(mean), mm(std_dev))?;
+            }
+        }
+        if let Some(reduction) = self.size_reduction() {
+            write!(
+                f,
+                "\nData size reduction: {:.1}% ({} of {} samples read out)",
+                100.0 * reduction,
+                self.roi_samples,
+                self.full_samples
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+// Write the provenance header of the CSV output.
+fn write_provenance(wtr: &mut dyn Write) -> Result<()> {
+    wtr.write_all(
+        format!(
+            "# {} {}\n# {}\n",
+            env!("CARGO_PKG_NAME"),
+            env!("CARGO_PKG_VERSION"),
+            std::env::args().collect::<Vec<_>>().join(" ")
+        )
+        .as_bytes(),
+    )
+    .context("failed to write csv header")
+}
+
+// Reconstruct the vertex of an event (in meters). A reconstruction error (e.g.
+// a failed track fit) counts the same as an event without a vertex.
+fn vertex(event: &MainEvent) -> Option<[f64; 3]> {
+    let position = event.try_vertex_info().ok().flatten()?.position;
+    Some([
+        position.x.get::<meter>(),
+        position.y.get::<meter>(),
+        position.z.get::<meter>(),
+    ])
+}
+
+fn main() {
+    alpha_g_analysis::cli::exit_on_error(run());
+}
+
+fn run() -> Result<()> {
+    // Same as `alpha-g-vertices`; the default 2 MiB stack size for threads is
+    // not enough to reconstruct the avalanches.
+    rayon::ThreadPoolBuilder::new()
+        .stack_size(4 * 1024 * 1024)
+        .build_global()
+        .context("failed to initialize global thread pool")?;
+
+    let args: Args = alpha_g_analysis::cli::parse(env!("CARGO_BIN_NAME"));
+    ensure!(
+        args.threshold.is_finite() && args.threshold > 0.0,
+        "`--threshold` must be a positive number"
+    );
+    let settings = RoiSettings {
+        pre_samples: args.pre_samples,
+        post_samples: args.post_samples,
+        threshold: args.threshold,
+    };
+    let files = alpha_g_analysis::expand_run_files(args.files, args.recursive)?;
+    let (run_number, files) = alpha_g_analysis::sort_run_files(files, None)?;
+    let calibration = RunCalibration::new(run_number);
+
+    let multi_progress = MultiProgress::with_draw_target(ProgressDrawTarget::stderr_with_hz(1));
+    let tp_bar = multi_progress.add(
+        ProgressBar::new(files.len().try_into().unwrap())
+            .with_style(ProgressStyle::with_template("[{pos}/{len}] Processing").unwrap()),
+    );
+    tp_bar.tick();
+
+    let mut rows = Vec::new();
+    let mut summary = Summary::default();
+    let mut census = EventCensus::default();
+    let mut num_errors = 0;
+    let mut previous_final_timestamp = None;
+    for file in files {
+        let contents = alpha_g_analysis::open(&file)?;
+        let file_view = midasio::FileView::try_from(&contents[..])
+            .with_context(|| format!("failed to parse `{}`", file.display()))?;
+        if let Some(previous_final_timestamp) = previous_final_timestamp {
+            ensure!(
+                file_view.initial_timestamp() - previous_final_timestamp <= 1,
+                "missing file before `{}`",
+                file.display()
+            );
+        }
+        previous_final_timestamp = Some(file_view.final_timestamp());
+        census.extend(file_view.iter().map(|event| event.id()));
+
+        let pb = multi_progress.add(
+            ProgressBar::new(file_view.iter().len().try_into().unwrap())
+                .with_style(
+                    ProgressStyle::with_template("[{bar:25}] {percent}%, ETA: {eta}    ({msg})")
+                        .unwrap()
+                        .progress_chars("=> "),
+                )
+                .with_message(format!("{}", file.display())),
+        );
+        let file_rows: Vec<_> = file_view
+            .into_par_iter()
+            .progress_with(pb.clone())
+            .filter(|event| matches!(EventId::try_from(event.id()), Ok(EventId::Main)))
+            .map(|event| {
+                let serial_number = event.serial_number();
+
+                let banks = event
+                    .into_iter()
+                    .map(|bank| (bank.name(), bank.data_slice()));
+                // Both reconstructions share the same decoded event.
+                let event = match MainEvent::try_from_banks_with(
+                    &calibration,
+                    banks,
+                    &DecodeOptions::default(),
+                ) {
+                    Ok((event, _)) => event,
+                    Err(error) => {
+                        if args.verbose {
+                            pb.println(format!("Error in event `{serial_number}`: {error}"));
+                        }
+                        return Row {
+                            serial_number,
+                            ..Default::default()
+                        };
+                    }
+                };
+                let (roi_event, reduction) = event.roi_readout(&settings);
+
+                Row::new(serial_number, reduction, vertex(&event), vertex(&roi_event))
+            })
+            .collect();
+        for row in file_rows {
+            if row.full_samples.is_none() {
+                num_errors += 1;
+            }
+            summary.add(&row);
+            rows.push(row);
+        }
+        tp_bar.set_style(
+            ProgressStyle::with_template("[{pos}/{len}] Processing, ETA: {eta}").unwrap(),
+        );
+        tp_bar.inc(1);
+    }
+    tp_bar.finish_and_clear();
+    alpha_g_analysis::cli::exit_if_no_main_events(&census);
+    if num_errors > 0 {
+        eprintln!("Warning: failed to decode `{num_errors}` events");
+    }
+    eprintln!("{summary}");
+
+    let output = args
+        .output
+        .unwrap_or_else(|| PathBuf::from(format!("R{run_number}_roi_study")));
+    let mut wtr = alpha_g_analysis::cli::create_output(&output, "csv")?;
+    write_provenance(&mut wtr)?;
+    let mut wtr = csv::Writer::from_writer(wtr);
+    for row in rows {
+        wtr.serialize(row).context("failed to write csv row")?;
+    }
+    wtr.flush().context("failed to flush csv data")?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,111 @@
+use super::*;
+
+fn reduction(full_samples: usize, roi_samples: usize) -> RoiReduction {
+    RoiReduction {
+        full_samples,
+        roi_samples,
+    }
+}
+
+#[test]
+fn row_new() {
+    let row = Row::new(7, reduction(1000, 250), Some([0.01, -0.02, 0.5]), None);
+
+    assert_eq!(row.serial_number, 7);
+    assert_eq!(row.full_samples, Some(1000));
+    assert_eq!(row.roi_samples, Some(250));
+    assert_eq!(row.size_reduction, Some(0.75));
+    assert_eq!(row.full_vertex(), Some([0.01, -0.02, 0.5]));
+    assert_eq!(row.roi_vertex(), None);
+    assert_eq!(row.roi_x, None);
+}
+
+#[test]
+fn summary_paired_vertices() {
+    let rows = [
+        Row::new(
+            0,
+            reduction(1000, 100),
+            Some([0.0, 0.0, 0.1]),
+            Some([0.001, 0.0, 0.1]),
+        ),
+        Row::new(
+            1,
+            reduction(1000, 300),
+            Some([0.0, 0.0, 0.2]),
+            Some([0.003, 0.0, 0.2]),
+        ),
+        Row::new(2, reduction(1000, 200), Some([0.0, 0.0, 0.3]), None),
+        Row::new(3, reduction(1000, 200), None, Some([0.0, 0.0, 0.4])),
+        Row::new(4, reduction(1000, 200), None, None),
+        // Failed to decode.
+        Row {
+            serial_number: 5,
+            ..Default::default()
+        },
+    ];
+    let mut summary = Summary::default();
+    for row in &rows {
+        summary.add(row);
+    }
+
+    assert_eq!(summary.events, 5);
+    assert_eq!(summary.full_vertices, 3);
+    assert_eq!(summary.roi_vertices, 3);
+    assert_eq!(summary.paired_vertices, 2);
+    assert_eq!(summary.efficiency_ratio(), Some(1.0));
+    assert!((summary.size_reduction().unwrap() - 0.8).abs() < 1e-12);
+
+    let residuals = summary.residuals().unwrap();
+    assert!((residuals[0].0 - 0.002).abs() < 1e-12);
+    assert!((residuals[0].1 - 0.001).abs() < 1e-12);
+    for (mean, std_dev) in &residuals[1..] {
+        assert!(mean.abs() < 1e-12);
+        assert!(std_dev.abs() < 1e-12);
+    }
+}
+
+#[test]
+fn summary_empty() {
+    let mut summary = Summary::default();
+    summary.add(&Row {
+        serial_number: 0,
+        ..Default::default()
+    });
+
+    assert_eq!(summary.events, 0);
+    assert_eq!(summary.efficiency_ratio(), None);
+    assert_eq!(summary.residuals(), None);
+    assert_eq!(summary.size_reduction(), None);
+    assert_eq!(
+        summary.to_string(),
+        "Vertices: 0 full, 0 ROI out of 0 events"
+    );
+}
+
+#[test]
+fn summary_display() {
+    let mut summary = Summary::default();
+    summary.add(&Row::new(
+        0,
+        reduction(1000, 100),
+        Some([0.0, 0.0, 0.1]),
+        Some([0.001, 0.0, 0.1]),
+    ));
+    summary.add(&Row::new(
+        1,
+        reduction(1000, 100),
+        Some([0.0, 0.0, 0.2]),
+        None,
+    ));
+    let lines: Vec<_> = summary.to_string().lines().map(String::from).collect();
+
+    assert_eq!(
+        lines,
+        [
+            "Vertices: 2 full, 1 ROI out of 2 events (efficiency ratio 0.500)",
+            "Residuals (ROI - full) of 1 events: x 1.00 ± 0.00 mm y 0.00 ± 0.00 mm z 0.00 ± 0.00 mm",
+            "Data size reduction: 90.0% (200 of 2000 samples read out)",
+        ]
+    );
+}
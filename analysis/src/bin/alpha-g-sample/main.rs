@@ -0,0 +1,287 @@
+use alpha_g_analysis::midas::{Event, MidasWriter};
+use alpha_g_analysis::EventCensus;
+use alpha_g_detector::midas::{BankKind, EventId};
+use alpha_g_detector::run::RunNumber;
+use anyhow::{bail, ensure, Context, Result};
+use clap::{Parser, ValueEnum};
+use indicatif::{ProgressBar, ProgressStyle};
+use std::io::BufWriter;
+use std::path::PathBuf;
+
+#[derive(Parser)]
+#[command(version)]
+/// Write a deterministic subsample of the main events of a single run
+struct Args {
+    /// MIDAS files (or directories with them) from the run you want to sample
+    #[arg(required = true)]
+    files: Vec<PathBuf>,
+    /// Also search for MIDAS files in subdirectories of the input directories
+    #[arg(long)]
+    recursive: bool,
+    /// Write the output to `OUTPUT.mid` and `OUTPUT.json` [default:
+    /// `R<run_number>_sample.mid` and `R<run_number>_sample.json`]
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    /// Select every K-th main event (starting with the first one)
+    #[arg(
+        long,
+        value_name = "K",
+        required_unless_present = "fraction",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    every: Option<u64>,
+    /// Select a random fraction (between 0 and 1) of the main events
+    #[arg(long, conflicts_with = "every", value_parser = parse_fraction)]
+    fraction: Option<f64>,
+    /// Seed of the random selection with `--fraction`
+    #[arg(long, default_value_t = 0, requires = "fraction")]
+    seed: u64,
+    /// Stop after selecting this number of main events
+    #[arg(long)]
+    max_events: Option<usize>,
+    /// Data banks to keep in the selected events. All other banks are removed
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        default_values_t = [KeepBank::Adc, KeepBank::Pwb, KeepBank::Trg]
+    )]
+    keep_banks: Vec<KeepBank>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+enum KeepBank {
+    /// Alpha16 banks
+    Adc,
+    /// Padwing banks
+    Pwb,
+    /// TRG bank
+    Trg,
+}
+
+impl From<KeepBank> for BankKind {
+    fn from(keep: KeepBank) -> Self {
+        match keep {
+            KeepBank::Adc => Self::Alpha16,
+            KeepBank::Pwb => Self::Padwing,
+            KeepBank::Trg => Self::Trg,
+        }
+    }
+}
+
+fn parse_fraction(s: &str) -> Result<f64, String> {
+    let fraction: f64 = s.parse().map_err(|_| format!("`{s}` isn't a number"))?;
+    if (0.0..=1.0).contains(&fraction) {
+        Ok(fraction)
+    } else {
+        Err(String::from("must be between 0 and 1"))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize)]
+#[serde(untagged)]
+enum Selection {
+    Every { every: u64 },
+    Fraction { fraction: f64, seed: u64 },
+}
+
+// SplitMix64 finalizer. Good enough to decorrelate consecutive serial numbers,
+// and (unlike the `std` hashers) guaranteed to never change between versions.
+fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^ (z >> 31)
+}
+
+impl Selection {
+    // `index` is the position of the event among all the main events of the
+    // run. The random selection depends only on the serial number (and seed)
+    // such that it is the same regardless of how the run is split into files.
+    fn is_selected(&self, index: u64, serial_number: u32) -> bool {
+        match *self {
+            Self::Every { every } => index % every == 0,
+            Self::Fraction { fraction, seed } => {
+                let hash =
+                    mix(mix(seed.wrapping_add(0x9E3779B97F4A7C15)) ^ u64::from(serial_number));
+                // Uniform in [0, 1) with the 53 bits of precision of an `f64`.
+                let uniform = (hash >> 11) as f64 / (1u64 << 53) as f64;
+                uniform < fraction
+            }
+        }
+    }
+}
+
+// Record of how a sample was produced. This is written next to the MIDAS
+// output such that the sample can be reproduced.
+#[derive(Debug, serde::Serialize)]
+struct Manifest {
+    package: String,
+    arguments: Vec<String>,
+    source_run: RunNumber,
+    source_files: Vec<PathBuf>,
+    selection: Selection,
+    max_events: Option<usize>,
+    keep_banks: Vec<KeepBank>,
+    serial_numbers: Vec<u32>,
+}
+
+// Remove all the banks that are not in the allowlist.
+fn strip_banks(event: &mut Event, keep: &[BankKind]) {
+    event.banks.retain(|bank| {
+        let kind = std::str::from_utf8(&bank.name)
+            .map(BankKind::from)
+            .unwrap_or(BankKind::Other);
+        keep.contains(&kind)
+    });
+}
+
+fn main() {
+    alpha_g_analysis::cli::exit_on_error(run());
+}
+
+fn run() -> Result<()> {
+    let args: Args = alpha_g_analysis::cli::parse(env!("CARGO_BIN_NAME"));
+    let selection = match args.fraction {
+        Some(fraction) => Selection::Fraction {
+            fraction,
+            seed: args.seed,
+        },
+        None => Selection::Every {
+            every: args.every.unwrap(),
+        },
+    };
+    let keep: Vec<BankKind> = args.keep_banks.iter().copied().map(Into::into).collect();
+    let files = alpha_g_analysis::expand_run_files(args.files, args.recursive)?;
+    let (run_number, files) = alpha_g_analysis::sort_run_files(files, None)?;
+
+    let output = args
+        .output
+        .unwrap_or_else(|| PathBuf::from(format!("R{run_number}_sample")))
+        .with_extension("mid");
+    // Write to a temporary file first. A truncated sample would otherwise look
+    // like a valid (but incomplete) MIDAS file.
+    let partial = output.with_extension("mid.part");
+
+    let bar = ProgressBar::new(files.len().try_into().unwrap()).with_style(
+        ProgressStyle::with_template("  Processing [{bar:25}] {percent}%,  ETA: {eta}")
+            .unwrap()
+            .progress_chars("=> "),
+    );
+    bar.tick();
+
+    let mut writer = None;
+    let mut serial_numbers = Vec::new();
+    let mut main_index = 0;
+    let mut census = EventCensus::default();
+    let mut previous_final_timestamp = None;
+    let mut final_odb = (0, Vec::new());
+    let mut processed_files = 0;
+    let limit_reached = |serial_numbers: &[u32]| {
+        args.max_events
+            .is_some_and(|max| serial_numbers.len() >= max)
+    };
+    for file in &files {
+        let contents = alpha_g_analysis::open(file)?;
+        let file_view = midasio::FileView::try_from(&contents[..])
+            .with_context(|| format!("failed to parse `{}`", file.display()))?;
+        if let Some(previous_final_timestamp) = previous_final_timestamp {
+            match file_view
+                .initial_timestamp()
+                .checked_sub(previous_final_timestamp)
+            {
+                Some(gap) => ensure!(gap <= 1, "missing file before `{}`", file.display()),
+                None => bail!(
+                    "`{}` starts before the end of the previous file",
+                    file.display()
+                ),
+            }
+        }
+        previous_final_timestamp = Some(file_view.final_timestamp());
+        census.extend(file_view.iter().map(|event| event.id()));
+        final_odb = (file_view.final_timestamp(), file_view.final_odb().to_vec());
+
+        // The begin of run of the output is the same as the first file.
+        if writer.is_none() {
+            let file = std::fs::File::create(&partial)
+                .with_context(|| format!("failed to create `{}`", partial.display()))?;
+            writer = Some(
+                MidasWriter::new(
+                    BufWriter::new(file),
+                    run_number.into(),
+                    file_view.initial_timestamp(),
+                    file_view.initial_odb(),
+                )
+                .with_context(|| format!("failed to write to `{}`", partial.display()))?,
+            );
+        }
+        let writer = writer.as_mut().unwrap();
+
+        let events = alpha_g_analysis::midas::events(&contents)
+            .with_context(|| format!("failed to parse `{}`", file.display()))?;
+        for mut event in events
+            .into_iter()
+            .filter(|event| matches!(EventId::try_from(event.id), Ok(EventId::Main)))
+        {
+            if limit_reached(&serial_numbers) {
+                break;
+            }
+            let index = main_index;
+            main_index += 1;
+            if !selection.is_selected(index, event.serial_number) {
+                continue;
+            }
+
+            strip_banks(&mut event, &keep);
+            writer
+                .write_event(&event)
+                .with_context(|| format!("failed to write to `{}`", partial.display()))?;
+            serial_numbers.push(event.serial_number);
+        }
+
+        processed_files += 1;
+        bar.inc(1);
+        // Don't even open the rest of the files; the output ends with the end
+        // of run of this one.
+        if limit_reached(&serial_numbers) {
+            break;
+        }
+    }
+    bar.finish_and_clear();
+    if census.ensure_main_events().is_err() {
+        // Best effort; the error is more interesting than a leftover file.
+        let _ = std::fs::remove_file(&partial);
+    }
+    alpha_g_analysis::cli::exit_if_no_main_events(&census);
+
+    // There is at least one file, so the writer was created.
+    writer
+        .unwrap()
+        .finish(final_odb.0, &final_odb.1)
+        .with_context(|| format!("failed to write to `{}`", partial.display()))?;
+    std::fs::rename(&partial, &output)
+        .with_context(|| format!("failed to create `{}`", output.display()))?;
+    eprintln!("Created `{}`", output.display());
+
+    let manifest = Manifest {
+        package: format!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")),
+        arguments: std::env::args().collect(),
+        source_run: run_number,
+        source_files: files[..processed_files].to_vec(),
+        selection,
+        max_events: args.max_events,
+        keep_banks: args.keep_banks,
+        serial_numbers,
+    };
+    let manifest_path = output.with_extension("json");
+    let wtr = std::fs::File::create(&manifest_path)
+        .with_context(|| format!("failed to create `{}`", manifest_path.display()))?;
+    serde_json::to_writer_pretty(wtr, &manifest)
+        .with_context(|| format!("failed to write `{}`", manifest_path.display()))?;
+    eprintln!("Created `{}`", manifest_path.display());
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests;
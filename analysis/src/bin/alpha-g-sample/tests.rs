@@ -0,0 +1,96 @@
+use super::*;
+
+fn selected(selection: Selection, serial_numbers: std::ops::Range<u32>) -> Vec<u32> {
+    serial_numbers
+        .enumerate()
+        .filter(|&(index, serial_number)| selection.is_selected(index as u64, serial_number))
+        .map(|(_, serial_number)| serial_number)
+        .collect()
+}
+
+#[test]
+fn parse_fraction_range() {
+    assert_eq!(parse_fraction("0"), Ok(0.0));
+    assert_eq!(parse_fraction("0.25"), Ok(0.25));
+    assert_eq!(parse_fraction("1"), Ok(1.0));
+    assert!(parse_fraction("-0.1").is_err());
+    assert!(parse_fraction("1.1").is_err());
+    assert!(parse_fraction("NaN").is_err());
+    assert!(parse_fraction("half").is_err());
+}
+
+#[test]
+fn selection_every() {
+    let selection = Selection::Every { every: 3 };
+    assert_eq!(selected(selection, 10..20), [10, 13, 16, 19]);
+
+    let selection = Selection::Every { every: 1 };
+    assert_eq!(selected(selection, 0..5), [0, 1, 2, 3, 4]);
+}
+
+#[test]
+fn selection_fraction_deterministic() {
+    let selection = Selection::Fraction {
+        fraction: 0.1,
+        seed: 42,
+    };
+    assert_eq!(selected(selection, 0..10000), selected(selection, 0..10000));
+    // Independent of the position of the event.
+    assert!(selected(selection, 0..10000)
+        .into_iter()
+        .all(|serial_number| selection.is_selected(0, serial_number)));
+
+    let other_seed = Selection::Fraction {
+        fraction: 0.1,
+        seed: 43,
+    };
+    assert_ne!(
+        selected(selection, 0..10000),
+        selected(other_seed, 0..10000)
+    );
+}
+
+#[test]
+fn selection_fraction_size() {
+    for fraction in [0.01, 0.1, 0.5, 0.9] {
+        let selection = Selection::Fraction { fraction, seed: 0 };
+        let n = selected(selection, 0..100000).len() as f64;
+        // Only fails if more than 5 standard deviations away.
+        let sigma = (100000.0 * fraction * (1.0 - fraction)).sqrt();
+        assert!((n - 100000.0 * fraction).abs() < 5.0 * sigma);
+    }
+
+    let none = Selection::Fraction {
+        fraction: 0.0,
+        seed: 0,
+    };
+    assert!(selected(none, 0..10000).is_empty());
+    let all = Selection::Fraction {
+        fraction: 1.0,
+        seed: 0,
+    };
+    assert_eq!(selected(all, 0..10000).len(), 10000);
+}
+
+#[test]
+fn strip_banks_allowlist() {
+    let mut event = Event {
+        id: 1,
+        trigger_mask: 0,
+        serial_number: 0,
+        timestamp: 0,
+        bank_format: alpha_g_analysis::midas::BankFormat::Bank32,
+        banks: [b"B09A", b"PC00", b"ATAT", b"XXXX", b"\xFF\xFF\xFF\xFF"]
+            .into_iter()
+            .map(|name| alpha_g_analysis::midas::Bank {
+                name: *name,
+                data_type: 1,
+                data: &[],
+            })
+            .collect(),
+    };
+    strip_banks(&mut event, &[BankKind::Padwing, BankKind::Trg]);
+
+    let names: Vec<_> = event.banks.iter().map(|bank| bank.name).collect();
+    assert_eq!(names, [*b"PC00", *b"ATAT"]);
+}
@@ -9,9 +9,12 @@ use std::path::PathBuf;
 #[command(version)]
 /// Extract the sequencer data for a single run
 struct Args {
-    /// MIDAS files from the run you want to inspect
+    /// MIDAS files (or directories with them) from the run you want to inspect
     #[arg(required = true)]
     files: Vec<PathBuf>,
+    /// Also search for MIDAS files in subdirectories of the input directories
+    #[arg(long)]
+    recursive: bool,
     /// Write the output to `OUTPUT.csv` [default: `R<run_number>_sequencer.csv`]
     #[arg(short, long)]
     output: Option<PathBuf>,
@@ -25,10 +28,14 @@ struct Row {
     xml: String,
 }
 
-fn main() -> Result<()> {
-    let args = Args::parse();
-    let (run_number, files) =
-        alpha_g_analysis::sort_run_files(args.files).context("failed to sort input files")?;
+fn main() {
+    alpha_g_analysis::cli::exit_on_error(run());
+}
+
+fn run() -> Result<()> {
+    let args: Args = alpha_g_analysis::cli::parse(env!("CARGO_BIN_NAME"));
+    let files = alpha_g_analysis::expand_run_files(args.files, args.recursive)?;
+    let (run_number, files) = alpha_g_analysis::sort_run_files(files, None)?;
 
     let bar = ProgressBar::new(files.len().try_into().unwrap()).with_style(
         ProgressStyle::with_template("  Processing [{bar:25}] {percent}%,  ETA: {eta}")
@@ -40,37 +47,35 @@ fn main() -> Result<()> {
     let mut rows = Vec::new();
     let mut previous_final_timestamp = None;
     for file in files {
-        let contents = alpha_g_analysis::read(&file)
-            .with_context(|| format!("failed to read `{}`", file.display()))?;
-        let file_view = midasio::FileView::try_from(&contents[..])
-            .with_context(|| format!("failed to parse `{}`", file.display()))?;
+        let mut events = alpha_g_analysis::event_iter(&file)?;
         if let Some(previous_final_timestamp) = previous_final_timestamp {
             ensure!(
-                file_view.initial_timestamp() - previous_final_timestamp <= 1,
+                events.initial_timestamp() - previous_final_timestamp <= 1,
                 "missing file before `{}`",
                 file.display()
             );
         }
-        previous_final_timestamp = Some(file_view.final_timestamp());
 
-        for event in file_view
-            .into_iter()
-            .filter(|event| matches!(EventId::try_from(event.id()), Ok(EventId::Sequencer2)))
-        {
-            let serial_number = event.serial_number();
-            let midas_timestamp = event.timestamp();
+        for event in events.by_ref() {
+            let event = event?;
+            if !matches!(EventId::try_from(event.id), Ok(EventId::Sequencer2)) {
+                continue;
+            }
+            let serial_number = event.serial_number;
+            let midas_timestamp = event.timestamp;
 
-            let [seq_bank] = event.into_iter().collect::<Vec<_>>()[..] else {
+            let [seq_bank] = &event.banks[..] else {
                 bail!("unexpected number of sequencer data banks");
             };
             ensure!(
-                Seq2BankName::try_from(seq_bank.name()).is_ok(),
+                std::str::from_utf8(&seq_bank.name)
+                    .is_ok_and(|name| Seq2BankName::try_from(name).is_ok()),
                 "unexpected sequencer bank name"
             );
 
             let data = std::str::from_utf8(
                 seq_bank
-                    .data_slice()
+                    .data
                     .strip_suffix(b"\x00")
                     .context("failed to remove trailing 0 from sequencer data")?,
             )
@@ -86,6 +91,7 @@ fn main() -> Result<()> {
             };
             rows.push(row);
         }
+        previous_final_timestamp = events.final_timestamp();
         bar.inc(1);
     }
     bar.finish_and_clear();
@@ -0,0 +1,494 @@
+use alpha_g_analysis::statistics::{deviating_files, FileContribution, RunningStatistics};
+use alpha_g_analysis::{Emission, ErrorAggregator, EventCensus};
+use alpha_g_detector::alpha16::aw_map::TpcWirePosition;
+use alpha_g_detector::alpha16::{validate_bank_packet, AdcPacket};
+use alpha_g_detector::midas::{Alpha16BankName, EventId, MainEventBankName};
+use alpha_g_detector::padwing::map::TpcPadPosition;
+use alpha_g_detector::padwing::{group_chunks, ChannelId};
+use alpha_g_detector::run::RunNumber;
+use anyhow::{ensure, Context, Result};
+use clap::Parser;
+use indicatif::{
+    MultiProgress, ParallelProgressIterator, ProgressBar, ProgressDrawTarget, ProgressStyle,
+};
+use rayon::prelude::*;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+#[derive(Parser)]
+#[command(version)]
+/// Estimate the signal-to-noise ratio of all anode wires and pads for a single
+/// run
+struct Args {
+    /// MIDAS files (or directories with them) from the run you want to inspect
+    #[arg(required = true)]
+    files: Vec<PathBuf>,
+    /// Also search for MIDAS files in subdirectories of the input directories
+    #[arg(long)]
+    recursive: bool,
+    /// Write the output to `OUTPUT.csv`, or to stdout if `-` [default:
+    /// `R<run_number>_snr.csv`]
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    /// Flag a channel if its signal-to-noise ratio is below this threshold
+    #[arg(long, default_value_t = 5.0)]
+    snr_threshold: f64,
+    /// Warn about input files with statistics (e.g. number of events) more
+    /// than this factor away from the median of the other files
+    #[arg(long, value_name = "FACTOR", default_value_t = 2.0, value_parser = parse_factor)]
+    deviation_factor: f64,
+    /// Ignore the run number in the MIDAS files and use this one instead
+    #[arg(long, value_name = "RUN_NUMBER")]
+    run_number_override: Option<RunNumber>,
+    /// Print detailed information about errors (if any)
+    #[arg(short, long)]
+    verbose: bool,
+}
+
+// Channels are sorted with all the wires first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Channel {
+    // Index of the anode wire (see `TpcWirePosition`).
+    Wire(usize),
+    // Column and row of the pad.
+    Pad(usize, usize),
+}
+
+#[derive(Debug, serde::Serialize)]
+struct Row {
+    kind: &'static str,
+    wire: Option<usize>,
+    pad_column: Option<usize>,
+    pad_row: Option<usize>,
+    events: u64,
+    noise_rms: f64,
+    noise_rms_error: Option<f64>,
+    amplitude: f64,
+    amplitude_error: Option<f64>,
+    snr: Option<f64>,
+    snr_error: Option<f64>,
+    low_snr: bool,
+}
+
+// Noise and amplitude of a single waveform.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Features {
+    // Root mean square of the pre-signal samples around their mean.
+    noise_rms: f64,
+    // Maximum absolute deviation from the pre-signal mean after the delay.
+    amplitude: f64,
+}
+
+// The first `delay` samples of a waveform are recorded before the signal
+// window (see `alpha_g_physics::calibration::wire_delay`). These are used as
+// the noise (and baseline) estimate of the waveform in this event, and the
+// rest of the waveform is searched for the peak of the signal.
+//
+// Returns `None` if either region is empty.
+fn waveform_features(waveform: &[i16], delay: usize) -> Option<Features> {
+    if delay == 0 || waveform.len() <= delay {
+        return None;
+    }
+    let (pre_signal, signal) = waveform.split_at(delay);
+
+    let mut baseline = RunningStatistics::default();
+    for &v in pre_signal {
+        baseline.add(f64::from(v));
+    }
+    let amplitude = signal
+        .iter()
+        .map(|&v| (f64::from(v) - baseline.mean()).abs())
+        .fold(0.0, f64::max);
+
+    Some(Features {
+        noise_rms: baseline.std_dev(),
+        amplitude,
+    })
+}
+
+// Distribution of the waveform features of a channel over all the events in
+// which it fired.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct ChannelStatistics {
+    noise_rms: RunningStatistics,
+    amplitude: RunningStatistics,
+}
+
+impl ChannelStatistics {
+    fn add(&mut self, features: Features) {
+        self.noise_rms.add(features.noise_rms);
+        self.amplitude.add(features.amplitude);
+    }
+
+    fn merge(&mut self, other: &Self) {
+        self.noise_rms.merge(&other.noise_rms);
+        self.amplitude.merge(&other.amplitude);
+    }
+
+    // Ratio of the mean amplitude to the mean noise RMS, and its standard
+    // error (propagated from the standard errors of both means, assumed to be
+    // uncorrelated).
+    fn snr(&self) -> (Option<f64>, Option<f64>) {
+        let noise = self.noise_rms.mean();
+        if noise <= 0.0 {
+            return (None, None);
+        }
+        let snr = self.amplitude.mean() / noise;
+        let error = snr
+            * ((self.amplitude.std_error() / self.amplitude.mean()).powi(2)
+                + (self.noise_rms.std_error() / noise).powi(2))
+            .sqrt();
+
+        (Some(snr), error.is_finite().then_some(error))
+    }
+}
+
+type Statistics = BTreeMap<Channel, ChannelStatistics>;
+
+fn merge_statistics(mut a: Statistics, b: Statistics) -> Statistics {
+    for (key, channel_statistics) in b {
+        a.entry(key).or_default().merge(&channel_statistics);
+    }
+    a
+}
+
+fn parse_factor(s: &str) -> Result<f64, String> {
+    let factor: f64 = s.parse().map_err(|_| format!("`{s}` isn't a number"))?;
+    if factor >= 1.0 {
+        Ok(factor)
+    } else {
+        Err(String::from("must be at least 1"))
+    }
+}
+
+// Contribution of a single input file, as written to the sampling report.
+#[derive(Debug, serde::Serialize)]
+struct FileReport {
+    file: PathBuf,
+    events: u64,
+    wire_waveforms: u64,
+    pad_waveforms: u64,
+    mean_amplitude: Option<f64>,
+    // Statistics more than `--deviation-factor` away from the median of all
+    // the other files.
+    deviations: Vec<DeviationReport>,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct DeviationReport {
+    statistic: &'static str,
+    value: f64,
+    median: f64,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct SamplingReport {
+    package: String,
+    arguments: Vec<String>,
+    deviation_factor: f64,
+    files: Vec<FileReport>,
+}
+
+fn row(channel: Channel, statistics: &ChannelStatistics, snr_threshold: f64) -> Row {
+    let (kind, wire, pad_column, pad_row) = match channel {
+        Channel::Wire(index) => ("wire", Some(index), None, None),
+        Channel::Pad(column, row) => ("pad", None, Some(column), Some(row)),
+    };
+    let error = |stats: &RunningStatistics| Some(stats.std_error()).filter(|e| e.is_finite());
+    let (snr, snr_error) = statistics.snr();
+
+    Row {
+        kind,
+        wire,
+        pad_column,
+        pad_row,
+        events: statistics.noise_rms.count(),
+        noise_rms: statistics.noise_rms.mean(),
+        noise_rms_error: error(&statistics.noise_rms),
+        amplitude: statistics.amplitude.mean(),
+        amplitude_error: error(&statistics.amplitude),
+        snr,
+        snr_error,
+        low_snr: !snr.is_some_and(|snr| snr >= snr_threshold),
+    }
+}
+
+fn main() {
+    alpha_g_analysis::cli::exit_on_error(run());
+}
+
+fn run() -> Result<()> {
+    let args: Args = alpha_g_analysis::cli::parse(env!("CARGO_BIN_NAME"));
+    let files = alpha_g_analysis::expand_run_files(args.files, args.recursive)?;
+    let (run_number, files) = alpha_g_analysis::sort_run_files(files, args.run_number_override)?;
+    if args.run_number_override.is_some() {
+        eprintln!("Warning: ignoring the run number in the input files, using `{run_number}`");
+    }
+    let wire_delay = alpha_g_physics::calibration::wire_delay(run_number.into())?;
+    let pad_delay = alpha_g_physics::calibration::pad_delay(run_number.into())?;
+    // Progress bars were flickering with the default draw target rate.
+    let multi_progress = MultiProgress::with_draw_target(ProgressDrawTarget::stderr_with_hz(1));
+    let tp_bar = multi_progress.add(
+        ProgressBar::new(files.len().try_into().unwrap())
+            .with_style(ProgressStyle::with_template("[{pos}/{len}] Processing").unwrap()),
+    );
+    tp_bar.tick();
+
+    let mut statistics = Statistics::new();
+    let mut contributions = Vec::new();
+    let aggregator = Mutex::new(ErrorAggregator::default());
+    let mut census = EventCensus::default();
+    let mut previous_final_timestamp = None;
+    for file in &files {
+        let contents = alpha_g_analysis::read(file)?;
+        let file_view = midasio::FileView::try_from(&contents[..])
+            .with_context(|| format!("failed to parse `{}`", file.display()))?;
+        if let Some(previous_final_timestamp) = previous_final_timestamp {
+            ensure!(
+                file_view.initial_timestamp() - previous_final_timestamp <= 1,
+                "missing file before `{}`",
+                file.display()
+            );
+        }
+        previous_final_timestamp = Some(file_view.final_timestamp());
+        census.extend(file_view.iter().map(|event| event.id()));
+
+        let pb = multi_progress.add(
+            ProgressBar::new(file_view.iter().len().try_into().unwrap())
+                .with_style(
+                    ProgressStyle::with_template("[{bar:25}] {percent}%, ETA: {eta}    ({msg})")
+                        .unwrap()
+                        .progress_chars("=> "),
+                )
+                .with_message(format!("{}", file.display())),
+        );
+        let file_statistics = file_view
+            .into_par_iter()
+            .progress_with(pb.clone())
+            .filter(|event| matches!(EventId::try_from(event.id()), Ok(EventId::Main)))
+            .fold(
+                || (Statistics::new(), FileContribution::default()),
+                |(mut statistics, mut contribution), event| {
+                    contribution.events += 1;
+                    let serial_number = event.serial_number();
+                    let report = |error: &(dyn Error + 'static)| {
+                        let emission = aggregator.lock().unwrap().add(error);
+                        match emission {
+                            Emission::Warn(unknown) => pb.println(format!(
+                                "Warning: {unknown} in event `{serial_number}` (`{}` so far)",
+                                unknown.count
+                            )),
+                            Emission::Other if args.verbose => {
+                                pb.println(format!("Error in event `{serial_number}`: {error}"))
+                            }
+                            _ => {}
+                        }
+                    };
+
+                    let banks: Vec<_> = event
+                        .into_iter()
+                        .map(|bank| (bank.name(), bank.data_slice()))
+                        .collect();
+                    for &(name, data) in &banks {
+                        let Ok(MainEventBankName::Alpha16(Alpha16BankName::A32(bank_name))) =
+                            MainEventBankName::try_from(name)
+                        else {
+                            continue;
+                        };
+                        let packet = match AdcPacket::try_from(data) {
+                            Ok(packet) => packet,
+                            Err(error) => {
+                                report(&error);
+                                continue;
+                            }
+                        };
+                        // A mislabeled bank would contaminate the statistics
+                        // of a different wire.
+                        if let Err(error) = validate_bank_packet(bank_name, &packet) {
+                            report(&error);
+                            continue;
+                        }
+                        // Data suppression: a channel only has a waveform if it
+                        // fired.
+                        let Some(features) = waveform_features(packet.waveform(), wire_delay)
+                        else {
+                            continue;
+                        };
+                        match TpcWirePosition::try_new(
+                            run_number,
+                            bank_name.board_id(),
+                            bank_name.channel_id(),
+                        ) {
+                            Ok(position) => {
+                                statistics
+                                    .entry(Channel::Wire(usize::from(position)))
+                                    .or_default()
+                                    .add(features);
+                                contribution.wire_waveforms += 1;
+                                contribution.amplitude.add(features.amplitude);
+                            }
+                            Err(error) => report(&error),
+                        }
+                    }
+
+                    let (packets, _) = group_chunks(banks);
+                    for packet in packets {
+                        let packet = match packet {
+                            Ok(packet) => packet,
+                            Err(error) => {
+                                report(&error);
+                                continue;
+                            }
+                        };
+                        for &channel_id in packet.channels_sent() {
+                            let ChannelId::Pad(pad_channel_id) = channel_id else {
+                                continue;
+                            };
+                            // A waveform is guaranteed to exist if the channel was
+                            // sent.
+                            let waveform = packet.waveform_at(channel_id).unwrap();
+                            let Some(features) = waveform_features(waveform, pad_delay) else {
+                                continue;
+                            };
+                            match TpcPadPosition::try_new(
+                                run_number,
+                                packet.board_id(),
+                                packet.after_id(),
+                                pad_channel_id,
+                            ) {
+                                Ok(position) => {
+                                    statistics
+                                        .entry(Channel::Pad(
+                                            usize::from(position.column),
+                                            usize::from(position.row),
+                                        ))
+                                        .or_default()
+                                        .add(features);
+                                    contribution.pad_waveforms += 1;
+                                    contribution.amplitude.add(features.amplitude);
+                                }
+                                Err(error) => report(&error),
+                            }
+                        }
+                    }
+
+                    (statistics, contribution)
+                },
+            )
+            .reduce(
+                || (Statistics::new(), FileContribution::default()),
+                |(a, mut contribution), (b, other)| {
+                    contribution.merge(&other);
+                    (merge_statistics(a, b), contribution)
+                },
+            );
+        statistics = merge_statistics(statistics, file_statistics.0);
+        contributions.push(file_statistics.1);
+
+        tp_bar.set_style(
+            ProgressStyle::with_template("[{pos}/{len}] Processing, ETA: {eta}").unwrap(),
+        );
+        tp_bar.inc(1);
+    }
+    tp_bar.finish_and_clear();
+    alpha_g_analysis::cli::exit_if_no_main_events(&census);
+    let aggregator = aggregator.into_inner().unwrap();
+    for unknown in aggregator.unknown_versions() {
+        eprintln!("Warning: found `{}` errors with {unknown}", unknown.count);
+    }
+    for (kind, count) in aggregator.other_errors() {
+        eprintln!("Warning: found `{count}` `{kind}` errors");
+    }
+
+    let rows: Vec<_> = statistics
+        .iter()
+        .map(|(&channel, channel_statistics)| row(channel, channel_statistics, args.snr_threshold))
+        .collect();
+    let low_snr = rows.iter().filter(|row| row.low_snr).count();
+    if low_snr > 0 {
+        eprintln!(
+            "Warning: found `{low_snr}` channels with a signal-to-noise ratio below `{}`",
+            args.snr_threshold
+        );
+    }
+    let deviations = deviating_files(&contributions, args.deviation_factor);
+    for (file, deviations) in files.iter().zip(&deviations) {
+        for deviation in deviations {
+            eprintln!(
+                "Warning: `{}` in `{}` is `{}` (median of the other files is `{}`)",
+                deviation.statistic,
+                file.display(),
+                deviation.value,
+                deviation.median
+            );
+        }
+    }
+
+    let output = args
+        .output
+        .unwrap_or_else(|| PathBuf::from(format!("R{run_number}_snr")));
+    let mut wtr = alpha_g_analysis::cli::create_output(&output, "csv")?;
+    wtr.write_all(
+        format!(
+            "# {} {}\n# {}\n",
+            env!("CARGO_PKG_NAME"),
+            env!("CARGO_PKG_VERSION"),
+            std::env::args().collect::<Vec<_>>().join(" ")
+        )
+        .as_bytes(),
+    )
+    .context("failed to write csv header")?;
+    if let Some(run_number) = args.run_number_override {
+        wtr.write_all(format!("# WARNING: run number overridden to {run_number}\n").as_bytes())
+            .context("failed to write csv header")?;
+    }
+    let mut wtr = csv::Writer::from_writer(wtr);
+    for row in rows {
+        wtr.serialize(row).context("failed to write csv row")?;
+    }
+    wtr.flush().context("failed to flush csv data")?;
+
+    // Next to the CSV output (there is nowhere to put it if that is stdout).
+    if output != Path::new(alpha_g_analysis::cli::STDOUT_PATH) {
+        let report = SamplingReport {
+            package: format!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")),
+            arguments: std::env::args().collect(),
+            deviation_factor: args.deviation_factor,
+            files: files
+                .into_iter()
+                .zip(contributions)
+                .zip(deviations)
+                .map(|((file, contribution), deviations)| FileReport {
+                    file,
+                    events: contribution.events,
+                    wire_waveforms: contribution.wire_waveforms,
+                    pad_waveforms: contribution.pad_waveforms,
+                    mean_amplitude: (contribution.amplitude.count() > 0)
+                        .then(|| contribution.amplitude.mean()),
+                    deviations: deviations
+                        .into_iter()
+                        .map(|deviation| DeviationReport {
+                            statistic: deviation.statistic,
+                            value: deviation.value,
+                            median: deviation.median,
+                        })
+                        .collect(),
+                })
+                .collect(),
+        };
+        let report_path = output.with_extension("sampling_report.json");
+        let wtr = std::fs::File::create(&report_path)
+            .with_context(|| format!("failed to create `{}`", report_path.display()))?;
+        serde_json::to_writer_pretty(wtr, &report)
+            .with_context(|| format!("failed to write `{}`", report_path.display()))?;
+        eprintln!("Created `{}`", report_path.display());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests;
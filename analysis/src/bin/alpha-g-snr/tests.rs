@@ -0,0 +1,78 @@
+use super::*;
+
+#[test]
+fn waveform_features_empty_regions() {
+    assert_eq!(waveform_features(&[], 2), None);
+    assert_eq!(waveform_features(&[1, 2], 2), None);
+    assert_eq!(waveform_features(&[1, 2, 3], 0), None);
+}
+
+#[test]
+fn waveform_features_values() {
+    // Pre-signal mean is 10 with an RMS of 2.
+    let waveform = [8, 12, 8, 12, 10, -20, 15];
+    let features = waveform_features(&waveform, 4).unwrap();
+
+    assert!((features.noise_rms - 2.0).abs() < 1e-12);
+    assert!((features.amplitude - 30.0).abs() < 1e-12);
+}
+
+#[test]
+fn channel_statistics_snr() {
+    let mut statistics = ChannelStatistics::default();
+    assert_eq!(statistics.snr(), (None, None));
+
+    statistics.add(Features {
+        noise_rms: 2.0,
+        amplitude: 20.0,
+    });
+    let (snr, error) = statistics.snr();
+    assert!((snr.unwrap() - 10.0).abs() < 1e-12);
+    // Not enough events to estimate the uncertainty.
+    assert_eq!(error, None);
+
+    statistics.add(Features {
+        noise_rms: 2.0,
+        amplitude: 40.0,
+    });
+    let (snr, error) = statistics.snr();
+    assert!((snr.unwrap() - 15.0).abs() < 1e-12);
+    // Standard error of the amplitude is 10, and the noise is constant.
+    assert!((error.unwrap() - 5.0).abs() < 1e-12);
+}
+
+#[test]
+fn row_low_snr() {
+    let mut statistics = ChannelStatistics::default();
+    statistics.add(Features {
+        noise_rms: 2.0,
+        amplitude: 8.0,
+    });
+
+    let pad = row(Channel::Pad(3, 7), &statistics, 5.0);
+    assert_eq!(pad.kind, "pad");
+    assert_eq!(
+        (pad.wire, pad.pad_column, pad.pad_row),
+        (None, Some(3), Some(7))
+    );
+    assert_eq!(pad.events, 1);
+    assert!(pad.low_snr);
+    assert!(!row(Channel::Wire(0), &statistics, 3.0).low_snr);
+
+    // Without noise there is no SNR, which is always flagged.
+    let mut statistics = ChannelStatistics::default();
+    statistics.add(Features {
+        noise_rms: 0.0,
+        amplitude: 8.0,
+    });
+    assert!(row(Channel::Wire(0), &statistics, 5.0).low_snr);
+}
+
+#[test]
+fn parse_factor_range() {
+    assert_eq!(parse_factor("1"), Ok(1.0));
+    assert_eq!(parse_factor("2.5"), Ok(2.5));
+    assert!(parse_factor("0.5").is_err());
+    assert!(parse_factor("NaN").is_err());
+    assert!(parse_factor("twice").is_err());
+}
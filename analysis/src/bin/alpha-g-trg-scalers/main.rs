@@ -1,44 +1,73 @@
-use alpha_g_detector::midas::{EventId, TriggerBankName};
-use alpha_g_detector::trigger::TrgPacket;
-use alpha_g_physics::TRG_CLOCK_FREQ;
-use anyhow::{ensure, Context, Result};
+use alpha_g_analysis::cli::{iso8601, DeferredCsvWriter, RunTiming};
+use alpha_g_analysis::{find_anomalies, AnomalyKind, AnomalySettings, TrgScalers, TriggerRates};
+use alpha_g_detector::midas::EventId;
+use anyhow::{ensure, Result};
 use clap::Parser;
 use indicatif::{ProgressBar, ProgressStyle};
-use std::io::Write;
 use std::path::PathBuf;
-use uom::si::time::second;
 
 #[derive(Parser)]
 #[command(version)]
 /// Extract the TRG scalers for a single run
 struct Args {
-    /// MIDAS files from the run you want to inspect
+    /// MIDAS files (or directories with them) from the run you want to inspect
     #[arg(required = true)]
     files: Vec<PathBuf>,
-    /// Write the output to `OUTPUT.csv` [default:
+    /// Also search for MIDAS files in subdirectories of the input directories
+    #[arg(long)]
+    recursive: bool,
+    /// Write the output to `OUTPUT.csv`, or to stdout if `-` [default:
     /// `R<run_number>_trg_scalers.csv`]
     #[arg(short, long)]
     output: Option<PathBuf>,
     /// Print detailed information about errors (if any)
     #[arg(short, long)]
     verbose: bool,
+    /// Print all the silent periods, rate excursions, and dead time spikes in
+    /// the run
+    #[arg(long)]
+    anomalies: bool,
+    /// Longest time (in seconds) without events that is not an anomaly
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        requires = "anomalies",
+        default_value_t = AnomalySettings::default().max_silence
+    )]
+    max_silence: f64,
+    /// Number of standard deviations from the rolling baseline at which the
+    /// rate or dead time is an anomaly
+    #[arg(
+        long,
+        value_name = "K",
+        requires = "anomalies",
+        default_value_t = AnomalySettings::default().threshold
+    )]
+    threshold: f64,
+    /// Add the input and output trigger rates, and the live fraction, over the
+    /// last `SECONDS` to each row
+    #[arg(long, value_name = "SECONDS")]
+    rate_window: Option<f64>,
+    /// Add a `utc` column with the ISO 8601 (UTC) time of each event, i.e. the
+    /// run start plus the `trg_time`
+    #[arg(long)]
+    utc: bool,
 }
 
-#[derive(Debug, Default, serde::Serialize)]
-struct Row {
-    serial_number: u32,
-    trg_time: Option<f64>,
-    input: Option<u32>,
-    drift_veto: Option<u32>,
-    scaledown: Option<u32>,
-    pulser: Option<u32>,
-    output: Option<u32>,
+fn main() {
+    alpha_g_analysis::cli::exit_on_error(run());
 }
 
-fn main() -> Result<()> {
-    let args = Args::parse();
-    let (run_number, files) =
-        alpha_g_analysis::sort_run_files(args.files).context("failed to sort input files")?;
+fn run() -> Result<()> {
+    let args: Args = alpha_g_analysis::cli::parse(env!("CARGO_BIN_NAME"));
+    if let Some(window) = args.rate_window {
+        ensure!(
+            window.is_finite() && window > 0.0,
+            "`--rate-window` must be a positive number of seconds"
+        );
+    }
+    let files = alpha_g_analysis::expand_run_files(args.files, args.recursive)?;
+    let (run_number, files) = alpha_g_analysis::sort_run_files(files, None)?;
 
     let bar = ProgressBar::new(files.len().try_into().unwrap()).with_style(
         ProgressStyle::with_template("  Processing [{bar:25}] {percent}%,  ETA: {eta}")
@@ -47,113 +76,147 @@ fn main() -> Result<()> {
     );
     bar.tick();
 
+    let output = args
+        .output
+        .unwrap_or_else(|| PathBuf::from(format!("R{run_number}_trg_scalers")));
+    // Rows are written as the events are read, but the header (i.e. the run
+    // timing) is only known after reading all the files.
+    let mut wtr = DeferredCsvWriter::new(
+        &output,
+        &format!(
+            "# {} {}\n# {}\n",
+            env!("CARGO_PKG_NAME"),
+            env!("CARGO_PKG_VERSION"),
+            std::env::args().collect::<Vec<_>>().join(" ")
+        ),
+    );
+    let mut scalers = TrgScalers::default();
+    let mut trigger_rates = args.rate_window.map(TriggerRates::new);
+    let mut main_events = 0;
+    // The anomalies are found relative to a rolling baseline, so they need all
+    // the rows of the run.
     let mut rows = Vec::new();
     let mut previous_final_timestamp = None;
+    let mut run_start = None;
+    let mut inputs = Vec::new();
     for file in files {
-        let contents = alpha_g_analysis::read(&file)
-            .with_context(|| format!("failed to read `{}`", file.display()))?;
-        let file_view = midasio::FileView::try_from(&contents[..])
-            .with_context(|| format!("failed to parse `{}`", file.display()))?;
+        let mut events = alpha_g_analysis::event_iter(&file)?;
+        let start = f64::from(*run_start.get_or_insert(events.initial_timestamp()));
         if let Some(previous_final_timestamp) = previous_final_timestamp {
             ensure!(
-                file_view.initial_timestamp() - previous_final_timestamp <= 1,
+                events.initial_timestamp() - previous_final_timestamp <= 1,
                 "missing file before `{}`",
                 file.display()
             );
         }
-        previous_final_timestamp = Some(file_view.final_timestamp());
-
-        rows.extend(
-            file_view
-                .into_iter()
-                .filter(|event| matches!(EventId::try_from(event.id()), Ok(EventId::Main)))
-                .map(|event| {
-                    let serial_number = event.serial_number();
 
-                    let [trg_bank] = event
-                        .into_iter()
-                        .filter(|bank| TriggerBankName::try_from(bank.name()).is_ok())
-                        .collect::<Vec<_>>()[..]
-                    else {
-                        if args.verbose {
-                            bar.println(format!(
-                                "Error in event `{serial_number}`: bad number of trg data banks"
-                            ));
-                        }
-                        return (serial_number, None);
-                    };
+        for event in events.by_ref() {
+            let event = event?;
+            if !matches!(EventId::try_from(event.id), Ok(EventId::Main)) {
+                continue;
+            }
+            let serial_number = event.serial_number;
 
-                    match TrgPacket::try_from(trg_bank.data_slice()) {
-                        Ok(trg_packet) => (serial_number, Some(trg_packet)),
-                        Err(error) => {
-                            if args.verbose {
-                                bar.println(format!("Error in event `{serial_number}`: {error}"));
-                            }
-                            (serial_number, None)
-                        }
+            let banks = event.banks.iter().filter_map(|bank| {
+                std::str::from_utf8(&bank.name)
+                    .ok()
+                    .map(|name| (name, &bank.data[..]))
+            });
+            let trg_packet = match alpha_g_analysis::trg_packet(banks) {
+                Ok(trg_packet) => Some(trg_packet),
+                Err(error) => {
+                    if args.verbose {
+                        bar.println(format!("Error in event `{serial_number}`: {error}"));
                     }
-                }),
-        );
+                    None
+                }
+            };
+            let mut row = scalers.next_row(serial_number, trg_packet.as_ref());
+            if let Some(trigger_rates) = trigger_rates.as_mut() {
+                let rates = trigger_rates.next(&row);
+                row.input_rate = Some(rates.input_rate);
+                row.output_rate = Some(rates.output_rate);
+                row.live_fraction = Some(rates.live_fraction);
+            }
+            if args.utc {
+                row.utc = Some(row.trg_time.map(|t| iso8601(start + t)));
+            }
+            wtr.serialize(&row)?;
+            main_events += 1;
+            if args.anomalies {
+                rows.push(row);
+            }
+        }
+        previous_final_timestamp = events.final_timestamp();
+        inputs.push(events.checksum()?);
+        wtr.flush()?;
         bar.inc(1);
     }
     bar.finish_and_clear();
+    // There is always at least one file, and the final timestamp is known
+    // after reading all its events.
+    let timing = RunTiming {
+        start: run_start.unwrap(),
+        end: previous_final_timestamp.unwrap(),
+        main_events,
+    };
 
-    let rows = rows.into_iter().scan(
-        (None, 0),
-        |(previous, cumulative), (serial_number, trg_packet)| {
-            let timestamp = trg_packet.map(|p| p.timestamp());
-            // If we can't get a timestamp, it is OK to use the previous one
-            // because this counter overflows every 70ish seconds.
-            // This will only be problematic if we go a full 70 seconds
-            // without an event, which is already impossible because DAQ has
-            // a 10 seconds timeout before stopping the run.
-            let current = timestamp.unwrap_or(previous.unwrap_or(0));
-            let delta = current.wrapping_sub(previous.unwrap_or(current));
-            *previous = Some(current);
-            *cumulative += u64::from(delta);
-
-            if let Some(trg_packet) = trg_packet {
-                Some(Row {
-                    serial_number,
-                    trg_time: Some((*cumulative as f64 / TRG_CLOCK_FREQ).get::<second>()),
-                    input: Some(trg_packet.input_counter()),
-                    drift_veto: trg_packet.drift_veto_counter(),
-                    scaledown: trg_packet.scaledown_counter(),
-                    pulser: Some(trg_packet.pulser_counter()),
-                    output: Some(trg_packet.output_counter()),
-                })
+    if let Some(trigger_rates) = trigger_rates {
+        let duration = trigger_rates.duration();
+        let live_time = trigger_rates.live_time();
+        eprintln!(
+            "Total input `{}`, total output `{}`, live time `{live_time:.3}` s of `{duration:.3}` s{}",
+            trigger_rates.total_input(),
+            trigger_rates.total_output(),
+            if duration > 0.0 {
+                format!(" ({:.2}%)", 100.0 * live_time / duration)
             } else {
-                Some(Row {
-                    serial_number,
-                    ..Default::default()
-                })
+                String::new()
             }
-        },
-    );
+        );
+    }
 
-    let output = args
-        .output
-        .unwrap_or_else(|| PathBuf::from(format!("R{run_number}_trg_scalers")))
-        .with_extension("csv");
-    let mut wtr = std::fs::File::create(&output)
-        .with_context(|| format!("failed to create `{}`", output.display()))?;
-    eprintln!("Created `{}`", output.display());
-    wtr.write_all(
-        format!(
-            "# {} {}\n# {}\n",
-            env!("CARGO_PKG_NAME"),
-            env!("CARGO_PKG_VERSION"),
-            std::env::args().collect::<Vec<_>>().join(" ")
-        )
-        .as_bytes(),
-    )
-    .context("failed to write csv header")?;
-    let mut wtr = csv::Writer::from_writer(wtr);
-    for row in rows {
-        wtr.serialize(row)
-            .context("failed to write row to csv data")?;
+    if args.anomalies {
+        let settings = AnomalySettings {
+            max_silence: args.max_silence,
+            threshold: args.threshold,
+            ..Default::default()
+        };
+        let anomalies = find_anomalies(&rows, &settings);
+        eprintln!(
+            "Found `{}` anomalies in run `{run_number}`",
+            anomalies.len()
+        );
+        let serial_number = |serial_number: Option<u32>| {
+            serial_number.map_or(String::from("-"), |serial_number| serial_number.to_string())
+        };
+        for anomaly in anomalies {
+            let kind = match anomaly.kind {
+                AnomalyKind::Silence => String::from("no events"),
+                AnomalyKind::Rate { rate, baseline } => {
+                    format!("rate {rate:.1} Hz (baseline {baseline:.1} Hz)")
+                }
+                AnomalyKind::DeadTime { fraction, baseline } => format!(
+                    "lost triggers {:.1}% (baseline {:.1}%)",
+                    100.0 * fraction,
+                    100.0 * baseline
+                ),
+            };
+            eprintln!(
+                "  {:.3} s for {:.3} s, between events `{}` and `{}`: {kind}",
+                anomaly.start,
+                anomaly.duration,
+                serial_number(anomaly.before),
+                serial_number(anomaly.after)
+            );
+        }
+    }
+
+    let mut header = format!("# {timing}\n");
+    for input in inputs {
+        header.push_str(&format!("# input {input}\n"));
     }
-    wtr.flush().context("failed to flush csv data")?;
+    wtr.finish(&header)?;
 
     Ok(())
 }
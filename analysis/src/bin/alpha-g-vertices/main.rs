@@ -1,50 +1,894 @@
-use alpha_g_detector::midas::EventId;
-use alpha_g_physics::{MainEvent, TRG_CLOCK_FREQ};
-use anyhow::{ensure, Context, Result};
-use clap::Parser;
+use alpha_g_analysis::cli::{DeferredCsvWriter, RunTiming};
+use alpha_g_analysis::columnar::OutputFormat;
+#[cfg(feature = "parquet")]
+use alpha_g_analysis::columnar::{ParquetWriter, PROVENANCE_KEY};
+use alpha_g_analysis::{
+    ErrorSampler, EventCensus, FileChecksum, Sample, ScaledownCounters, ScaledownWeights,
+    TpcSignalCheck, TpcSignalThresholds, TrgScalers,
+};
+use alpha_g_detector::midas::{BankKind, EventId, RunConfig, TRIGGER_SCALEDOWN_JSON_PTR};
+use alpha_g_detector::run::RunNumber;
+use alpha_g_detector::trigger::TrgPacket;
+use alpha_g_physics::reconstruction::{Coordinate, FieldConfiguration, Track};
+use alpha_g_physics::{
+    DecodeOptions, MainEvent, Mirror, ReconstructionSummary, RunCalibration, SpacePoint,
+    TRG_CLOCK_FREQ,
+};
+use anyhow::{bail, ensure, Context, Result};
+#[cfg(feature = "parquet")]
+use arrow_schema::{DataType, Field, Schema};
+use clap::{Parser, ValueEnum};
 use indicatif::{
     MultiProgress, ParallelProgressIterator, ProgressBar, ProgressDrawTarget, ProgressStyle,
 };
 use rayon::prelude::*;
+use std::collections::{BTreeMap, HashSet};
+use std::error::Error;
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use uom::si::angle::radian;
+use uom::si::f64::{Length, MagneticFluxDensity, Time};
 use uom::si::length::meter;
+use uom::si::magnetic_flux_density::tesla;
+use uom::si::momentum::kilogram_meter_per_second;
+use uom::si::ratio::ratio;
 use uom::si::time::second;
 
+// Optional SQLite output (requires the `sqlite` feature).
+#[cfg(feature = "sqlite")]
+mod sqlite;
+
 #[derive(Parser)]
 #[command(version)]
 /// Reconstruct the annihilation vertices for a single run
 struct Args {
-    /// MIDAS files from the run you want to reconstruct
+    /// MIDAS files (or directories with them) from the run you want to reconstruct
     #[arg(required = true)]
     files: Vec<PathBuf>,
-    /// Write the output to `OUTPUT.csv` [default: `R<run_number>_vertices.csv`]
+    /// Also search for MIDAS files in subdirectories of the input directories
+    #[arg(long)]
+    recursive: bool,
+    /// Write the output to `OUTPUT.<csv|parquet>`, or to stdout if `-`
+    /// [default: `R<run_number>_vertices.<csv|parquet>`]
     #[arg(short, long)]
     output: Option<PathBuf>,
-    /// Print detailed information about errors (if any)
+    /// Format of the output file (`parquet` requires the `parquet` feature)
+    #[arg(long, value_enum, default_value_t = OutputFormat::Csv)]
+    format: OutputFormat,
+    /// Ignore the run number in the MIDAS files and use this one instead
+    #[arg(long, value_name = "RUN_NUMBER")]
+    run_number_override: Option<RunNumber>,
+    /// Format of the input files
+    #[arg(long, value_enum, default_value_t = InputFormat::Midas)]
+    input_format: InputFormat,
+    /// ODB setting (JSON pointer) that must not change during the run. Can be
+    /// used multiple times [default: all data suppression and trigger source
+    /// settings]
+    #[arg(
+        long = "odb-check",
+        value_name = "JSON_POINTER",
+        default_values = alpha_g_analysis::RUN_SETTINGS_JSON_PTRS.iter().copied(),
+        hide_default_value = true
+    )]
+    odb_checks: Vec<String>,
+    /// Warn if a calibration was derived from a run more than N runs away
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = alpha_g_physics::calibration::DEFAULT_STALENESS_MARGIN
+    )]
+    calibration_margin: u32,
+    /// Add extra columns with information about each event (slower)
+    #[arg(long)]
+    extra_columns: bool,
+    /// Add a `weight` column to correct for the trigger scaledown
+    #[arg(long)]
+    weights: bool,
+    /// Add a `utc` column with the ISO 8601 (UTC) time of each event, i.e. the
+    /// run start plus the `trg_time`
+    #[arg(long)]
+    utc: bool,
+    /// Print detailed information about at most N errors of each kind, and
+    /// the number of suppressed errors after each file [default: 0]
+    #[arg(long, value_name = "N", conflicts_with = "verbose")]
+    error_sample: Option<usize>,
+    /// Print detailed information about all errors (if any)
     #[arg(short, long)]
     verbose: bool,
+    /// Drop corrupted data banks (e.g. a PadWing chunk with a bad CRC) instead
+    /// of losing the whole event, and report how many were dropped per file
+    #[arg(long)]
+    lossy: bool,
+    /// Process runs with missing files (e.g. a corrupted subrun) instead of
+    /// failing. Adds an `exact_time` column to flag the rows after a gap
+    #[arg(long, conflicts_with = "scalers_output")]
+    allow_gaps: bool,
+    /// Skip input files that can't be read (e.g. a truncated subrun) instead
+    /// of failing, and process the rest of the run as with `--allow-gaps`
+    #[arg(long, conflicts_with = "scalers_output")]
+    skip_unreadable: bool,
+    /// Number of main events decoded at the start of the run to check that
+    /// there is TPC signal (0 to disable the check)
+    #[arg(long, value_name = "N", default_value_t = 100)]
+    signal_check_events: usize,
+    /// Reconstruct the run even if it appears to have no TPC signal (e.g. the
+    /// TPC HV was off)
+    #[arg(long)]
+    force: bool,
+    /// Maximum number of input files held in memory at the same time. Values
+    /// larger than 1 read (and decompress) the next files in the background
+    /// while reconstructing the current one
+    #[arg(long, value_name = "K", default_value_t = 1)]
+    prefetch_files: usize,
+    /// Add a `no_vertex_reason` column with the stage at which the
+    /// reconstruction of each event without a vertex stopped
+    #[arg(long)]
+    why_no_vertex: bool,
+    /// Add columns with the number of channels, avalanches, spacepoints,
+    /// clusters, and tracks in each event (implies `--why-no-vertex`)
+    #[arg(long)]
+    diagnostics: bool,
+    /// Developer option: mirror all the avalanches before reconstructing them
+    /// (cross-check of the reconstruction, not for physics). The `--output`
+    /// name has to contain `mirror`
+    #[arg(long, value_enum, conflicts_with_all = ["spacepoints", "tracks"])]
+    mirror: Option<MirrorCoordinate>,
+    /// Write the serial numbers of the events that failed to reconstruct
+    /// (grouped by kind of error) to a JSON file
+    #[arg(long, value_name = "FILE")]
+    error_summary: Option<PathBuf>,
+    /// Only reconstruct the events in the `--error-summary` of a previous
+    /// run, and write just their rows (e.g. to patch the previous output with
+    /// `alpha-g-apply-patch`)
+    #[arg(long, value_name = "FILE")]
+    only_errors: Option<PathBuf>,
+    /// Also write all the reconstructed spacepoints of each event to
+    /// `R<run_number>_spacepoints.csv`
+    #[arg(long)]
+    spacepoints: bool,
+    /// Also write all the fitted tracks of each event to
+    /// `R<run_number>_tracks.csv`
+    #[arg(long)]
+    tracks: bool,
+    /// Magnetic field configuration of the run. Tracks are fitted to helices
+    /// if the field is on, and to straight lines if it is off
+    #[arg(long, value_enum, default_value_t = Field::On)]
+    field: Field,
+    /// Also write the TRG scalers of the run (same as `alpha-g-trg-scalers`)
+    /// to `SCALERS_OUTPUT.csv`, or to stdout if `-`
+    #[arg(long, value_name = "SCALERS_OUTPUT")]
+    scalers_output: Option<PathBuf>,
+    /// Also write the rows (and the provenance of the run) to a SQLite
+    /// database, which can hold the vertices of many runs
+    #[cfg(feature = "sqlite")]
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["only_errors", "mirror"])]
+    output_sqlite: Option<PathBuf>,
+    /// What to do if the `--output-sqlite` database already has rows from the
+    /// same run
+    #[cfg(feature = "sqlite")]
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = sqlite::IfExists::Fail,
+        requires = "output_sqlite"
+    )]
+    if_exists: sqlite::IfExists,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum InputFormat {
+    /// All the (consecutive) MIDAS files of a run
+    Midas,
+    /// MIDAS files with only a subset of the events of a run (e.g. skimmed or
+    /// quarantined events)
+    Skim,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum MirrorCoordinate {
+    /// phi -> -phi
+    Phi,
+    /// z -> -z
+    Z,
+}
+
+impl MirrorCoordinate {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Phi => "phi",
+            Self::Z => "z",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum Field {
+    /// Solenoid on (helical tracks)
+    On,
+    /// Solenoid off (straight tracks)
+    Off,
+}
+
+impl From<Field> for FieldConfiguration {
+    fn from(field: Field) -> Self {
+        match field {
+            Field::On => Self::On,
+            Field::Off => Self::Off,
+        }
+    }
+}
+
+impl From<MirrorCoordinate> for Mirror {
+    fn from(coordinate: MirrorCoordinate) -> Self {
+        match coordinate {
+            MirrorCoordinate::Phi => Self::Phi,
+            MirrorCoordinate::Z => Self::Z,
+        }
+    }
+}
+
+// Machine-readable summary of the events that failed to reconstruct.
+#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+struct ErrorSummary {
+    run_number: RunNumber,
+    // Same as the `RunTiming` in the provenance header. Not available for
+    // skimmed input (or in summaries written by older versions).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    run_start: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    run_end: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    run_duration: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    main_event_rate: Option<f64>,
+    // Serial numbers (sorted) of the failed events for each kind of error.
+    errors: BTreeMap<String, Vec<u32>>,
+    // Input files skipped with `--skip-unreadable`, and all the gaps in the
+    // run (with either this or `--allow-gaps`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    skipped_files: Vec<PathBuf>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    gaps: Vec<TimeGap>,
+    // Size and checksum of all the input files that were processed (empty in
+    // summaries written by older versions).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    inputs: Vec<FileChecksum>,
 }
 
-#[derive(Debug, Default, serde::Serialize)]
+// Missing data between the final ODB dump of the file before a gap and the
+// initial ODB dump of the file after it (UNIX timestamps).
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+struct TimeGap {
+    start: u32,
+    end: u32,
+}
+
+impl ErrorSummary {
+    fn read(path: &Path) -> Result<Self> {
+        let contents =
+            std::fs::read(path).with_context(|| format!("failed to read `{}`", path.display()))?;
+        serde_json::from_slice(&contents)
+            .with_context(|| format!("failed to parse `{}`", path.display()))
+    }
+
+    fn serial_numbers(&self) -> HashSet<u32> {
+        self.errors.values().flatten().copied().collect()
+    }
+}
+
+#[derive(Debug, Default, PartialEq, serde::Serialize)]
 struct Row {
     serial_number: u32,
     trg_time: Option<f64>,
     reconstructed_x: Option<f64>,
     reconstructed_y: Option<f64>,
     reconstructed_z: Option<f64>,
+    reconstructed_sigma_x: Option<f64>,
+    reconstructed_sigma_y: Option<f64>,
+    reconstructed_sigma_z: Option<f64>,
+    // Extra columns. The outer `None` means that the column is not written at
+    // all (i.e. `--extra-columns` was not set).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    t_first_avalanche: Option<Option<f64>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    t_last_avalanche: Option<Option<f64>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    late_fraction: Option<Option<f64>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    truncated_fraction: Option<Option<f64>>,
+    // Same as above, but for `--weights`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    weight: Option<Option<f64>>,
+    // Same as above, but for `--allow-gaps`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    exact_time: Option<Option<bool>>,
+    // Same as above, but for `--utc`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    utc: Option<Option<String>>,
+    // Same as above, but for `--why-no-vertex`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    no_vertex_reason: Option<Option<&'static str>>,
+    // Same as above, but for `--diagnostics`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    num_wire_channels: Option<Option<usize>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    num_pad_channels: Option<Option<usize>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    num_avalanches: Option<Option<usize>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    num_spacepoints: Option<Option<usize>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    num_clusters: Option<Option<usize>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    num_tracks: Option<Option<usize>>,
+}
+
+// Result of reconstructing a single event (in parallel with all the other
+// events in the same file). The `trg_time` and `weight` of its row also depend
+// on all the previous events, so they are determined later by a `RowBuilder`.
+#[derive(Clone, Debug, Default)]
+struct EventResult {
+    serial_number: u32,
+    timestamp: Option<u32>,
+    vertex: Option<(Coordinate, Option<[Length; 3]>)>,
+    drift_window_stats: (Option<Time>, Option<Time>, Option<f64>),
+    misbuilt: bool,
+    truncated_fraction: Option<f64>,
+    counters: Option<ScaledownCounters>,
+    error_kind: Option<String>,
+    // Code of the `NoVertexReason` (or `decode_error` if the event failed to
+    // decode). Empty if the event has a vertex.
+    no_vertex_reason: Option<&'static str>,
+    // Empty if the event failed to decode.
+    summary: Option<ReconstructionSummary>,
+    // Whether the row is written to the output at all (see `--only-errors`).
+    selected: bool,
+}
+
+// Turn consecutive events into rows. The state is kept from one call to the
+// next, so the events of a run can be given one file at a time.
+struct RowBuilder {
+    previous: Option<u32>,
+    cumulative: u64,
+    exact: bool,
+    weights: Option<ScaledownWeights>,
+    extra_columns: bool,
+    allow_gaps: bool,
+    why_no_vertex: bool,
+    diagnostics: bool,
+    // Add the `utc` column, anchored to the UNIX timestamp of the initial ODB
+    // dump of the run (if known).
+    utc: bool,
+    run_start: Option<u32>,
+    // The TRG timestamp counter wraps around every ~68 seconds, so it can't be
+    // accumulated across isolated events (e.g. skimmed input). Their
+    // `trg_time` is left empty.
+    skim: bool,
+}
+
+impl RowBuilder {
+    fn new(extra_columns: bool, allow_gaps: bool, why_no_vertex: bool, diagnostics: bool) -> Self {
+        Self {
+            previous: None,
+            cumulative: 0,
+            exact: true,
+            weights: None,
+            extra_columns,
+            allow_gaps,
+            // The `no_vertex_reason` is the failure stage of the diagnostics.
+            why_no_vertex: why_no_vertex || diagnostics,
+            diagnostics,
+            utc: false,
+            run_start: None,
+            skim: false,
+        }
+    }
+
+    // The TRG timestamp could have overflowed any number of times during a
+    // gap, so the time elapsed is taken from the MIDAS timestamps around it
+    // instead (i.e. `duration` in seconds, with a resolution of 1 s).
+    fn skip_gap(&mut self, duration: u32) {
+        self.previous = None;
+        self.cumulative += (Time::new::<second>(f64::from(duration)) * TRG_CLOCK_FREQ)
+            .get::<ratio>()
+            .round() as u64;
+        self.exact = false;
+        if let Some(weights) = self.weights.as_mut() {
+            weights.skip_gap();
+        }
+    }
+
+    // Every event (even if it is not selected, or it failed to reconstruct)
+    // has to go through here to keep track of the timestamps and trigger
+    // counters.
+    fn next_row(&mut self, event: &EventResult) -> Row {
+        // If we don't have a timestamp, it is OK to use the previous one
+        // because this counter overflows every 68 seconds.
+        // This will only be problematic if we go over a full minute without
+        // an event, which is already impossible because DAQ has a 10 seconds
+        // timeout before stopping the run.
+        let current = event.timestamp.unwrap_or(self.previous.unwrap_or(0));
+        let delta = current.wrapping_sub(self.previous.unwrap_or(current));
+        self.previous = Some(current);
+        self.cumulative += u64::from(delta);
+
+        let weight = self
+            .weights
+            .as_mut()
+            .map(|weights| weights.next_weight(event.counters));
+
+        let (t_first, t_last, late_fraction) = event.drift_window_stats;
+        let vertex = event.vertex;
+        let sigma = |i: usize| {
+            vertex
+                .and_then(|(_, sigma)| sigma)
+                .map(|sigma: [Length; 3]| sigma[i].get::<meter>())
+        };
+        let extra = |value: Option<f64>| self.extra_columns.then_some(value);
+        let summary = event.summary.as_ref();
+        let count =
+            |f: fn(&ReconstructionSummary) -> usize| self.diagnostics.then_some(summary.map(f));
+        if event.timestamp.is_some() {
+            let trg_time = (self.cumulative as f64 / TRG_CLOCK_FREQ).get::<second>();
+            // The time elapsed during a gap is unknown, so is the UTC time of
+            // all the events after it.
+            let utc = self.utc.then(|| {
+                self.run_start
+                    .filter(|_| self.exact)
+                    .map(|start| alpha_g_analysis::cli::iso8601(f64::from(start) + trg_time))
+            });
+            Row {
+                serial_number: event.serial_number,
+                trg_time: (!self.skim).then_some(trg_time),
+                reconstructed_x: vertex.map(|(v, _)| v.x.get::<meter>()),
+                reconstructed_y: vertex.map(|(v, _)| v.y.get::<meter>()),
+                reconstructed_z: vertex.map(|(v, _)| v.z.get::<meter>()),
+                reconstructed_sigma_x: sigma(0),
+                reconstructed_sigma_y: sigma(1),
+                reconstructed_sigma_z: sigma(2),
+                t_first_avalanche: extra(t_first.map(|t| t.get::<second>())),
+                t_last_avalanche: extra(t_last.map(|t| t.get::<second>())),
+                late_fraction: extra(late_fraction),
+                truncated_fraction: extra(event.truncated_fraction),
+                weight,
+                exact_time: self.allow_gaps.then_some(Some(self.exact)),
+                utc,
+                no_vertex_reason: self.why_no_vertex.then_some(event.no_vertex_reason),
+                num_wire_channels: count(|s| s.wire_channels),
+                num_pad_channels: count(|s| s.pad_channels),
+                num_avalanches: count(|s| s.avalanches),
+                num_spacepoints: count(|s| s.report.num_spacepoints),
+                num_clusters: count(|s| s.report.cluster_sizes.len()),
+                num_tracks: count(|s| s.report.num_tracks()),
+            }
+        } else {
+            Row {
+                serial_number: event.serial_number,
+                t_first_avalanche: extra(None),
+                t_last_avalanche: extra(None),
+                late_fraction: extra(None),
+                truncated_fraction: extra(None),
+                weight,
+                exact_time: self.allow_gaps.then_some(None),
+                utc: self.utc.then_some(None),
+                no_vertex_reason: self.why_no_vertex.then_some(event.no_vertex_reason),
+                num_wire_channels: count(|s| s.wire_channels),
+                num_pad_channels: count(|s| s.pad_channels),
+                num_avalanches: count(|s| s.avalanches),
+                num_spacepoints: count(|s| s.report.num_spacepoints),
+                num_clusters: count(|s| s.report.cluster_sizes.len()),
+                num_tracks: count(|s| s.report.num_tracks()),
+                ..Default::default()
+            }
+        }
+    }
+}
+
+// Main output of the run. Rows are written as each file is processed, but the
+// header (e.g. the run timing and the warnings) is only known at the very end.
+enum RowWriter {
+    Csv(DeferredCsvWriter),
+    // One row group per input file. The metadata is written last.
+    #[cfg(feature = "parquet")]
+    Parquet(ParquetWriter<Box<dyn Write + Send>>),
+}
+
+impl RowWriter {
+    fn new(args: &Args, output: &Path) -> Result<Self> {
+        Ok(match args.format {
+            OutputFormat::Csv => Self::Csv(DeferredCsvWriter::new(output, &provenance())),
+            #[cfg(feature = "parquet")]
+            OutputFormat::Parquet => Self::Parquet(
+                ParquetWriter::new(
+                    alpha_g_analysis::cli::create_output(output, "parquet")?,
+                    parquet_schema(args),
+                )
+                .context("failed to create parquet writer")?
+                .with_statistics(["serial_number", "trg_time"]),
+            ),
+        })
+    }
+
+    fn serialize(&mut self, row: &Row) -> Result<()> {
+        match self {
+            Self::Csv(wtr) => wtr.serialize(row),
+            #[cfg(feature = "parquet")]
+            Self::Parquet(wtr) => wtr.serialize(row).context("failed to write parquet row"),
+        }
+    }
+
+    // Called after the rows of each input file.
+    fn end_of_file(&mut self) -> Result<()> {
+        match self {
+            Self::Csv(wtr) => wtr.flush(),
+            #[cfg(feature = "parquet")]
+            Self::Parquet(wtr) => wtr.flush().context("failed to write parquet row group"),
+        }
+    }
+
+    fn finish(
+        self,
+        timing: Option<RunTiming>,
+        inputs: &[FileChecksum],
+        warnings: &[String],
+    ) -> Result<()> {
+        // Everything after the provenance (which the CSV writer already has).
+        let mut header: Vec<_> = timing.iter().map(ToString::to_string).collect();
+        header.extend(inputs.iter().map(|input| format!("input {input}")));
+        header.extend(warnings.iter().map(|warning| format!("WARNING: {warning}")));
+        match self {
+            Self::Csv(wtr) => wtr.finish(
+                &header
+                    .iter()
+                    .map(|line| format!("# {line}\n"))
+                    .collect::<String>(),
+            ),
+            // Same lines as the CSV header, but as key-value metadata.
+            #[cfg(feature = "parquet")]
+            Self::Parquet(mut wtr) => {
+                let provenance = provenance();
+                let lines = provenance
+                    .lines()
+                    .map(|line| line.trim_start_matches("# "))
+                    .chain(header.iter().map(String::as_str));
+                wtr.add_metadata(PROVENANCE_KEY, lines.collect::<Vec<_>>().join("\n"));
+                wtr.finish().context("failed to write parquet file")?;
+                Ok(())
+            }
+        }
+    }
+}
+
+// Schema of the Parquet output, i.e. the columns of a `Row` that are enabled
+// by the command line options. It is declared instead of inferred from the
+// values, so that a column that only has nulls in a run (e.g. `num_tracks` if
+// no event has a TPC bank) has the same type in all runs.
+#[cfg(feature = "parquet")]
+fn parquet_schema(args: &Args) -> Schema {
+    let why_no_vertex = args.why_no_vertex || args.diagnostics;
+    let columns = [
+        ("serial_number", DataType::UInt32, true),
+        ("trg_time", DataType::Float64, true),
+        ("reconstructed_x", DataType::Float64, true),
+        ("reconstructed_y", DataType::Float64, true),
+        ("reconstructed_z", DataType::Float64, true),
+        ("reconstructed_sigma_x", DataType::Float64, true),
+        ("reconstructed_sigma_y", DataType::Float64, true),
+        ("reconstructed_sigma_z", DataType::Float64, true),
+        ("t_first_avalanche", DataType::Float64, args.extra_columns),
+        ("t_last_avalanche", DataType::Float64, args.extra_columns),
+        ("late_fraction", DataType::Float64, args.extra_columns),
+        ("truncated_fraction", DataType::Float64, args.extra_columns),
+        ("weight", DataType::Float64, args.weights),
+        (
+            "exact_time",
+            DataType::Boolean,
+            args.allow_gaps || args.skip_unreadable,
+        ),
+        ("utc", DataType::Utf8, args.utc),
+        ("no_vertex_reason", DataType::Utf8, why_no_vertex),
+        ("num_wire_channels", DataType::UInt64, args.diagnostics),
+        ("num_pad_channels", DataType::UInt64, args.diagnostics),
+        ("num_avalanches", DataType::UInt64, args.diagnostics),
+        ("num_spacepoints", DataType::UInt64, args.diagnostics),
+        ("num_clusters", DataType::UInt64, args.diagnostics),
+        ("num_tracks", DataType::UInt64, args.diagnostics),
+    ];
+
+    Schema::new(
+        columns
+            .into_iter()
+            .filter(|(_, _, enabled)| *enabled)
+            .map(|(name, data_type, _)| Field::new(name, data_type, true))
+            .collect::<Vec<_>>(),
+    )
 }
 
-fn main() -> Result<()> {
+#[derive(Debug, serde::Serialize)]
+struct SpacePointRow {
+    serial_number: u32,
+    r: f64,
+    phi: f64,
+    z: f64,
+    wire_amplitude: f64,
+    pad_amplitude: f64,
+}
+
+// Nominal field of the solenoid (same as the drift tables).
+const B_FIELD: f64 = 1.0;
+// Conversion from kg m/s to MeV/c.
+const MEV_PER_C: f64 = 1.602_176_634e-19 * 1e6 / 299_792_458.0;
+
+#[derive(Debug, serde::Serialize)]
+struct TrackRow {
+    serial_number: u32,
+    num_points: usize,
+    rms_residual: f64,
+    // Transverse momentum (in MeV/c) of a singly charged particle. Empty for
+    // straight tracks (field off).
+    pt: Option<f64>,
+}
+
+impl TrackRow {
+    fn new(serial_number: u32, track: &Track) -> Self {
+        Self {
+            serial_number,
+            num_points: track.num_points(),
+            rms_residual: track.rms_residual().get::<meter>(),
+            pt: track
+                .model()
+                .transverse_momentum(MagneticFluxDensity::new::<tesla>(B_FIELD))
+                .map(|pt| pt.get::<kilogram_meter_per_second>() / MEV_PER_C),
+        }
+    }
+}
+
+// Events in which more than this fraction of the (non-empty) wire and pad
+// channels have a truncated waveform (see `MainEvent::truncated_waveforms`)
+// are reported.
+const TRUNCATED_FRACTION_THRESHOLD: f64 = 0.01;
+
+// Provenance header shared by all the CSV outputs.
+fn provenance() -> String {
+    format!(
+        "# {} {}\n# {}\n",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION"),
+        std::env::args().collect::<Vec<_>>().join(" ")
+    )
+}
+
+// Warning about an input file skipped with `--skip-unreadable`. The file has to
+// stand out from the rest of the output, so it goes on its own line.
+fn unreadable_warning(path: &Path, error: &dyn std::fmt::Display) -> String {
+    format!(
+        "Warning: skipping unreadable file `{}`\n  {error}",
+        path.display()
+    )
+}
+
+fn write_provenance(wtr: &mut dyn Write) -> Result<()> {
+    wtr.write_all(provenance().as_bytes())
+        .context("failed to write csv header")
+}
+
+// Decode (at most) the first `max_events` main events of the run, and count
+// their channels with a signal. Also return how many main events were sampled
+// (including the ones that failed to decode). Unreadable files are left for the
+// main loop to report (or skip).
+fn sample_tpc_signal(
+    files: &[PathBuf],
+    max_events: usize,
+    calibration: &RunCalibration,
+    options: &DecodeOptions,
+) -> (usize, TpcSignalCheck) {
+    let mut sampled = 0;
+    let mut check = TpcSignalCheck::default();
+    for file in files {
+        if sampled == max_events {
+            break;
+        }
+        // Only the first few events are needed; don't decompress the whole
+        // file.
+        let Ok(events) = alpha_g_analysis::event_iter(file) else {
+            continue;
+        };
+        let events: Vec<_> = events
+            .map_while(Result::ok)
+            .filter(|event| matches!(EventId::try_from(event.id), Ok(EventId::Main)))
+            .take(max_events - sampled)
+            .collect();
+        sampled += events.len();
+        // Same decoding as the reconstruction of the full run.
+        let counts: Vec<_> = events
+            .into_par_iter()
+            .filter_map(|event| {
+                let banks = event.banks.iter().filter_map(|bank| {
+                    std::str::from_utf8(&bank.name)
+                        .ok()
+                        .map(|name| (name, &bank.data[..]))
+                });
+                let (event, _) =
+                    MainEvent::try_from_banks_with(calibration, banks, options).ok()?;
+                Some((event.num_wire_signals(), event.num_pad_signals()))
+            })
+            .collect();
+        for (wire_signals, pad_signals) in counts {
+            check.add(wire_signals, pad_signals);
+        }
+    }
+
+    (sampled, check)
+}
+
+// Get the trigger scaledown factor from an ODB dump.
+fn scaledown_factor(odb: &serde_json::Value) -> Result<u32> {
+    RunConfig::try_from(odb)?
+        .trigger_scaledown
+        .with_context(|| format!("missing `{TRIGGER_SCALEDOWN_JSON_PTR}` in the ODB"))
+}
+
+fn main() {
+    alpha_g_analysis::cli::exit_on_error(run());
+}
+
+fn run() -> Result<()> {
     // The default 2 MiB stack size for threads is not enough.
     rayon::ThreadPoolBuilder::new()
         .stack_size(4 * 1024 * 1024)
         .build_global()
         .context("failed to initialize global thread pool")?;
 
-    let args = Args::parse();
-    let (run_number, files) =
-        alpha_g_analysis::sort_run_files(args.files).context("failed to sort input files")?;
+    let args: Args = alpha_g_analysis::cli::parse(env!("CARGO_BIN_NAME"));
+    // Weights are determined from consecutive TRG packets and the ODB.
+    ensure!(
+        !(args.weights && args.input_format == InputFormat::Skim),
+        "`--weights` is not supported with `--input-format skim`"
+    );
+    // The timestamps of skimmed files are not the same as the run.
+    ensure!(
+        !(args.utc && args.input_format == InputFormat::Skim),
+        "`--utc` is not supported with `--input-format skim`"
+    );
+    // The `trg_time` of the patch rows has to be the same as in the original
+    // output, so all events have to be seen.
+    ensure!(
+        !(args.only_errors.is_some() && args.input_format == InputFormat::Skim),
+        "`--only-errors` is not supported with `--input-format skim`"
+    );
+    ensure!(
+        args.prefetch_files > 0,
+        "`--prefetch-files` must be at least 1"
+    );
+    // Both outputs can't be written to stdout at the same time.
+    let stdout = Some(Path::new(alpha_g_analysis::cli::STDOUT_PATH));
+    ensure!(
+        !(args.output.as_deref() == stdout && args.scalers_output.as_deref() == stdout),
+        "`--output` and `--scalers-output` can't both be `-`"
+    );
+    // A mirrored reconstruction must never be mistaken for (or overwrite) the
+    // real vertices of a run.
+    if let Some(mirror) = args.mirror {
+        let name = args
+            .output
+            .as_deref()
+            .and_then(Path::file_name)
+            .map(|name| name.to_string_lossy());
+        ensure!(
+            name.is_some_and(|name| name.contains("mirror")),
+            "`--mirror` requires an `--output` with `mirror` in its name (e.g. `R<run_number>_vertices_mirror_{}`)",
+            mirror.name()
+        );
+        eprintln!(
+            "Warning: mirroring {0} -> -{0}, the output is not for physics",
+            mirror.name()
+        );
+    }
+    let files = alpha_g_analysis::expand_run_files(args.files, args.recursive)?;
+    let (run_number, files, mut skipped_files) = if args.skip_unreadable {
+        let (run_number, files, skipped) =
+            alpha_g_analysis::sort_readable_run_files(files, args.run_number_override)?;
+        let mut skipped_files = Vec::new();
+        for (path, error) in skipped {
+            eprintln!("{}", unreadable_warning(&path, &error));
+            skipped_files.push(path);
+        }
+        (run_number, files, skipped_files)
+    } else {
+        let (run_number, files) =
+            alpha_g_analysis::sort_run_files(files, args.run_number_override)?;
+        (run_number, files, Vec::new())
+    };
+    if args.run_number_override.is_some() {
+        eprintln!("Warning: ignoring the run number in the input files, using `{run_number}`");
+    }
+    let selected = match &args.only_errors {
+        Some(path) => {
+            let summary = ErrorSummary::read(path)?;
+            ensure!(
+                summary.run_number == run_number,
+                "`{}` is from run `{}`, not `{run_number}`",
+                path.display(),
+                summary.run_number
+            );
+            Some(summary.serial_numbers())
+        }
+        None => None,
+    };
+    // Before creating any output. This only reads up to the first main event
+    // of the run.
+    alpha_g_analysis::cli::exit_if_no_main_events(&alpha_g_analysis::census_until_main_event(
+        &files,
+    ));
+    let calibration = RunCalibration::new(run_number).with_field(args.field.into());
+    let decode_options = DecodeOptions {
+        lossy: args.lossy,
+        ..Default::default()
+    };
+    // Decoding a few events is cheap compared to the full run, and it saves
+    // hours of CPU if the run was taken with the TPC HV off. Runs with fewer
+    // main events than the sample are not checked; there is not enough data to
+    // tell (nor much time to save).
+    if !args.force && args.signal_check_events > 0 {
+        let (sampled, check) = sample_tpc_signal(
+            &files,
+            args.signal_check_events,
+            &calibration,
+            &decode_options,
+        );
+        if sampled == args.signal_check_events {
+            if let Err(error) = check.ensure_signal(&TpcSignalThresholds::default()) {
+                eprintln!("Error: {error}");
+                eprintln!("Use `--force` to reconstruct the run anyway");
+                std::process::exit(alpha_g_analysis::cli::NO_TPC_SIGNAL_EXIT_CODE);
+            }
+        }
+    }
+    #[cfg(feature = "sqlite")]
+    let mut sqlite_output = match &args.output_sqlite {
+        Some(path) => Some(sqlite::SqliteOutput::open(
+            path,
+            run_number.into(),
+            args.if_exists,
+        )?),
+        None => None,
+    };
+    // Weights are determined as each file is processed, so the scaledown
+    // factor at the end of the run is needed before processing the first file.
+    // With `--skip-unreadable`, this is the last file that can be read (the
+    // main loop warns about the others).
+    let final_scaledown_factor = if args.weights {
+        let mut final_odb = None;
+        for path in files.iter().rev() {
+            let odb = alpha_g_analysis::open(path)
+                .map_err(anyhow::Error::from)
+                .and_then(|contents| {
+                    let file_view = midasio::FileView::try_from(&contents[..])
+                        .with_context(|| format!("failed to parse `{}`", path.display()))?;
+                    Ok(alpha_g_analysis::parse_odb(file_view.final_odb()))
+                });
+            match odb {
+                Ok(odb) => {
+                    final_odb = Some(odb);
+                    break;
+                }
+                Err(_) if args.skip_unreadable => continue,
+                Err(error) => return Err(error),
+            }
+        }
+        let Some(Ok(odb)) = final_odb else {
+            bail!("failed to parse ODB, required by `--weights`");
+        };
+        Some(scaledown_factor(&odb).context("bad final ODB")?)
+    } else {
+        None
+    };
+    // Reported once per run, before any of the (potentially long) processing.
+    let stale_calibrations =
+        alpha_g_physics::calibration::staleness(run_number.into(), args.calibration_margin);
+    for warning in &stale_calibrations {
+        eprintln!("Warning: {warning} from run `{run_number}`");
+    }
     // Progress bars were flickering with the default draw target rate.
     let multi_progress = MultiProgress::with_draw_target(ProgressDrawTarget::stderr_with_hz(1));
     // ETA is 0 until the first file is processed. So just don't show it until
@@ -55,21 +899,141 @@ fn main() -> Result<()> {
     );
     tp_bar.tick();
 
-    let mut rows = Vec::new();
+    // Spacepoints are written as each file is processed (instead of at the
+    // end with the vertices) to keep memory usage bounded.
+    let mut spacepoints_wtr = if args.spacepoints {
+        let path = PathBuf::from(format!("R{run_number}_spacepoints"));
+        let mut wtr = alpha_g_analysis::cli::create_output(&path, "csv")?;
+        write_provenance(&mut wtr)?;
+        Some(csv::Writer::from_writer(wtr))
+    } else {
+        None
+    };
+    // Same as the spacepoints.
+    let mut tracks_wtr = if args.tracks {
+        let path = PathBuf::from(format!("R{run_number}_tracks"));
+        let mut wtr = alpha_g_analysis::cli::create_output(&path, "csv")?;
+        write_provenance(&mut wtr)?;
+        Some(csv::Writer::from_writer(wtr))
+    } else {
+        None
+    };
+    // Same as the output of `alpha-g-trg-scalers`, but without reading all
+    // the files again.
+    let mut scalers_wtr = match &args.scalers_output {
+        Some(path) => {
+            let mut wtr = alpha_g_analysis::cli::create_output(path, "csv")?;
+            write_provenance(&mut wtr)?;
+            Some(csv::Writer::from_writer(wtr))
+        }
+        None => None,
+    };
+    let mut scalers = TrgScalers::default();
+    let output = args
+        .output
+        .clone()
+        .unwrap_or_else(|| PathBuf::from(format!("R{run_number}_vertices")));
+    let mut vertices_wtr = RowWriter::new(&args, &output)?;
+    let mut row_builder = RowBuilder::new(
+        args.extra_columns,
+        args.allow_gaps || args.skip_unreadable,
+        args.why_no_vertex,
+        args.diagnostics,
+    );
+    row_builder.utc = args.utc;
+    row_builder.skim = args.input_format == InputFormat::Skim;
+    // Serial numbers of the failed events for each kind of error.
+    let mut errors: BTreeMap<String, Vec<u32>> = BTreeMap::new();
+    let (mut misbuilt_events, mut truncated_events) = (0, 0);
+    // Built after the global thread pool, so there is one shard per thread.
+    let sampler = ErrorSampler::new(if args.verbose {
+        None
+    } else {
+        Some(args.error_sample.unwrap_or(0))
+    });
+    let mut census = EventCensus::default();
+    let mut total_dropped_banks = 0;
     let mut previous_final_timestamp = None;
-    for file in files {
-        let contents = alpha_g_analysis::read(&file)
-            .with_context(|| format!("failed to read `{}`", file.display()))?;
-        let file_view = midasio::FileView::try_from(&contents[..])
-            .with_context(|| format!("failed to parse `{}`", file.display()))?;
-        if let Some(previous_final_timestamp) = previous_final_timestamp {
-            ensure!(
-                file_view.initial_timestamp() - previous_final_timestamp <= 1,
-                "missing file before `{}`",
-                file.display()
-            );
+    let mut missing_files = 0;
+    let mut gaps = Vec::new();
+    // Initial ODB of the first file and final ODB (not parsed until the end)
+    // of the last file. Unreadable files are skipped for both.
+    let (mut initial_odb, mut final_odb) = (None, None);
+    // Initial timestamp of the first file. The timestamps of skimmed files are
+    // not the same as the run.
+    let mut run_start = None;
+    // Checksums of the files that were processed, in order.
+    let mut inputs = Vec::new();
+    // Files are read in order (while the rayon pool reconstructs the events of
+    // the current file), so the rows are still written in the order needed to
+    // accumulate the `trg_time`.
+    let prefetched = alpha_g_analysis::prefetch_files(files, args.prefetch_files);
+    for (file, contents) in prefetched {
+        // A file with a good header can still be truncated (or corrupted)
+        // further in.
+        let (contents, checksum) = match contents {
+            Ok(contents) => contents,
+            Err(error) if args.skip_unreadable => {
+                tp_bar.println(unreadable_warning(&file, &error));
+                skipped_files.push(file);
+                tp_bar.inc(1);
+                continue;
+            }
+            Err(error) => return Err(error.into()),
+        };
+        let file_view = match midasio::FileView::try_from(&contents[..]) {
+            Ok(file_view) => file_view,
+            Err(error) if args.skip_unreadable => {
+                tp_bar.println(unreadable_warning(&file, &error));
+                skipped_files.push(file);
+                tp_bar.inc(1);
+                continue;
+            }
+            Err(error) => {
+                return Err(error).with_context(|| format!("failed to parse `{}`", file.display()))
+            }
+        };
+        inputs.push(checksum);
+        // Skimmed files are not expected to be contiguous.
+        if let (InputFormat::Midas, Some(previous_final_timestamp)) =
+            (args.input_format, previous_final_timestamp)
+        {
+            let contiguous = file_view.initial_timestamp() - previous_final_timestamp <= 1;
+            // Each skipped file can explain (at most) one gap.
+            if !contiguous && (args.allow_gaps || missing_files < skipped_files.len()) {
+                tp_bar.println(format!("Warning: missing file before `{}`", file.display()));
+                missing_files += 1;
+                let gap = TimeGap {
+                    start: previous_final_timestamp,
+                    end: file_view.initial_timestamp(),
+                };
+                gaps.push(gap);
+                row_builder.skip_gap(gap.end.saturating_sub(gap.start));
+            } else {
+                ensure!(contiguous, "missing file before `{}`", file.display());
+            }
         }
         previous_final_timestamp = Some(file_view.final_timestamp());
+        census.extend(file_view.iter().map(|event| event.id()));
+        // The ODB of skimmed files is not necessarily from the same run.
+        if args.input_format == InputFormat::Midas {
+            // The first file might have been skipped.
+            if initial_odb.is_none() {
+                initial_odb = Some(alpha_g_analysis::parse_odb(file_view.initial_odb()));
+                run_start = Some(file_view.initial_timestamp());
+                row_builder.run_start = run_start;
+                if let Some(final_factor) = final_scaledown_factor {
+                    let Some(Ok(initial)) = &initial_odb else {
+                        bail!("failed to parse ODB, required by `--weights`");
+                    };
+                    row_builder.weights = Some(ScaledownWeights::new(
+                        scaledown_factor(initial).context("bad initial ODB")?,
+                        final_factor,
+                    ));
+                }
+            }
+            final_odb = Some(file_view.final_odb().to_vec());
+        }
 
         let pb = multi_progress.add(
             ProgressBar::new(file_view.iter().len().try_into().unwrap())
@@ -80,33 +1044,265 @@ fn main() -> Result<()> {
                 )
                 .with_message(format!("{}", file.display())),
         );
-        rows.par_extend(
-            file_view
-                .into_par_iter()
-                .progress_with(pb.clone())
-                .filter(|event| matches!(EventId::try_from(event.id()), Ok(EventId::Main)))
-                .map(|event| {
-                    let serial_number = event.serial_number();
-
-                    let banks = event
-                        .into_iter()
-                        .map(|bank| (bank.name(), bank.data_slice()));
-                    match MainEvent::try_from_banks(run_number, banks) {
-                        Ok(event) => (serial_number, Some(event.timestamp()), event.vertex()),
-                        Err(error) => {
+        let file_rows: Vec<_> = file_view
+            .into_par_iter()
+            .progress_with(pb.clone())
+            .filter(|event| matches!(EventId::try_from(event.id()), Ok(EventId::Main)))
+            .map(|event| {
+                let serial_number = event.serial_number();
+
+                let banks: Vec<_> = event
+                    .into_iter()
+                    .map(|bank| (bank.name(), bank.data_slice()))
+                    .collect();
+                let trg_packet = banks
+                    .iter()
+                    .find(|(name, _)| BankKind::from(*name) == BankKind::Trg)
+                    .and_then(|(_, data)| TrgPacket::try_from(*data).ok());
+                // Same TRG packet as `alpha-g-trg-scalers` (i.e. exactly one
+                // TRG bank) so both scalers outputs are identical.
+                let scalers_packet = if args.scalers_output.is_some() {
+                    alpha_g_analysis::trg_packet(banks.iter().copied()).ok()
+                } else {
+                    None
+                };
+                let counters = if args.weights {
+                    trg_packet
+                        .as_ref()
+                        .and_then(ScaledownCounters::from_trg_packet)
+                } else {
+                    None
+                };
+                if selected
+                    .as_ref()
+                    .is_some_and(|selected| !selected.contains(&serial_number))
+                {
+                    // Not reconstructed, but the TRG timestamp and counters
+                    // are still needed for the `trg_time` and `weight` of
+                    // the selected events.
+                    return (
+                        EventResult {
+                            serial_number,
+                            timestamp: trg_packet.map(|packet| packet.timestamp()),
+                            counters,
+                            ..Default::default()
+                        },
+                        Vec::new(),
+                        Vec::new(),
+                        scalers_packet,
+                        0,
+                    );
+                }
+                // Errors caused by an unknown packet version are reported
+                // only once.
+                // Use `pb` rather than `tp_bar`. Otherwise the observable
+                // ETA in `tp_bar` gets all messed up because this causes a
+                // `tick` and the current ETA implementation increases
+                // exponentially for slow-updating progress bars.
+                let report = |error: &(dyn Error + 'static)| match sampler.add(error) {
+                    Sample::Warn(unknown) => pb.println(format!(
+                        "Warning: {unknown} in event `{serial_number}` (`{}` so far)",
+                        unknown.count
+                    )),
+                    Sample::Report => {
+                        pb.println(format!("Error in event `{serial_number}`: {error}"))
+                    }
+                    Sample::Suppress => {}
+                };
+                // Banks are only skipped with `--lossy`.
+                let result = MainEvent::try_from_banks_with(&calibration, banks, &decode_options)
+                    .map(|(event, skipped)| {
+                        if args.verbose {
+                            for error in &skipped {
+                                pb.println(format!(
+                                    "Dropped bank in event `{serial_number}`: {error}"
+                                ));
+                            }
+                        }
+                        (event, skipped.len())
+                    });
+                match result {
+                    Ok((mut event, skipped)) => {
+                        if let Some(mirror) = args.mirror {
+                            event.mirror(mirror.into());
+                        }
+                        if let Some(misbuilt) = event.misbuilt() {
                             if args.verbose {
-                                // Use `pb` rather than `tp_bar`. Otherwise the
-                                // observable ETA in `tp_bar` gets all messed up
-                                // because this causes a `tick` and the current
-                                // ETA implementation increases exponentially
-                                // for slow-updating progress bars.
-                                pb.println(format!("Error in event `{serial_number}`: {error}"));
+                                pb.println(format!(
+                                    "Misbuilt event `{serial_number}`: {misbuilt:?}"
+                                ));
                             }
-                            (serial_number, None, None)
                         }
+                        let truncated = event.truncated_waveforms();
+                        if args.verbose && truncated.fraction() > TRUNCATED_FRACTION_THRESHOLD {
+                            pb.println(format!(
+                                "Truncated waveforms in event `{serial_number}`: {truncated:?}"
+                            ));
+                        }
+                        // Same vertex as `event.try_vertex_info()`, but
+                        // without reconstructing the avalanches twice.
+                        let avalanches = event.avalanches();
+                        let drift_window_stats = if args.extra_columns {
+                            event.drift_window_stats_from_avalanches(&avalanches)
+                        } else {
+                            (None, None, None)
+                        };
+                        let (result, summary) =
+                            event.reconstruction_summary_from_avalanches(&avalanches);
+                        let spacepoints = if args.spacepoints {
+                            avalanches
+                                .iter()
+                                .filter_map(|&avalanche| {
+                                    let point = SpacePoint::try_from(avalanche).ok()?;
+                                    Some(SpacePointRow {
+                                        serial_number,
+                                        r: point.r.get::<meter>(),
+                                        phi: point.phi.get::<radian>(),
+                                        z: point.z.get::<meter>(),
+                                        wire_amplitude: avalanche.wire_amplitude,
+                                        pad_amplitude: avalanche.pad_amplitude,
+                                    })
+                                })
+                                .collect()
+                        } else {
+                            Vec::new()
+                        };
+                        let tracks = if args.tracks {
+                            event
+                                .tracks_from_avalanches(&avalanches)
+                                .iter()
+                                .map(|(_, track)| TrackRow::new(serial_number, track))
+                                .collect()
+                        } else {
+                            Vec::new()
+                        };
+                        // A degenerate event still has a valid
+                        // timestamp; it just doesn't have a vertex.
+                        let (vertex, error_kind) = match result {
+                            Ok(vertex) => (vertex.map(|info| (info.position, info.sigma())), None),
+                            Err(error) => {
+                                report(&error);
+                                (None, Some(alpha_g_analysis::error_kind(&error)))
+                            }
+                        };
+                        (
+                            EventResult {
+                                serial_number,
+                                timestamp: Some(event.timestamp()),
+                                vertex,
+                                drift_window_stats,
+                                misbuilt: event.misbuilt().is_some(),
+                                truncated_fraction: Some(truncated.fraction()),
+                                counters,
+                                error_kind,
+                                no_vertex_reason: summary
+                                    .report
+                                    .no_vertex_reason()
+                                    .map(|reason| reason.code()),
+                                summary: Some(summary),
+                                selected: true,
+                            },
+                            spacepoints,
+                            tracks,
+                            scalers_packet,
+                            skipped,
+                        )
                     }
-                }),
-        );
+                    Err(error) => {
+                        report(&error);
+                        (
+                            EventResult {
+                                serial_number,
+                                counters,
+                                error_kind: Some(alpha_g_analysis::error_kind(&error)),
+                                no_vertex_reason: Some("decode_error"),
+                                selected: true,
+                                ..Default::default()
+                            },
+                            Vec::new(),
+                            Vec::new(),
+                            scalers_packet,
+                            0,
+                        )
+                    }
+                }
+            })
+            .collect();
+        let mut dropped_banks = 0;
+        #[cfg(feature = "sqlite")]
+        let mut selected_rows = Vec::new();
+        // The parallel iterator keeps the order of the events in the file,
+        // which is the order needed to accumulate the `trg_time`.
+        for (event, spacepoints, tracks, scalers_packet, skipped) in file_rows {
+            dropped_banks += skipped;
+            if let Some(wtr) = scalers_wtr.as_mut() {
+                let mut row = scalers.next_row(event.serial_number, scalers_packet.as_ref());
+                // Same as the `utc` of the vertices (there are no gaps with
+                // `--scalers-output`).
+                if args.utc {
+                    row.utc =
+                        Some(row.trg_time.zip(run_start).map(|(t, start)| {
+                            alpha_g_analysis::cli::iso8601(f64::from(start) + t)
+                        }));
+                }
+                wtr.serialize(row)
+                    .context("failed to write scalers csv row")?;
+            }
+            if let Some(wtr) = spacepoints_wtr.as_mut() {
+                for spacepoint in spacepoints {
+                    wtr.serialize(spacepoint)
+                        .context("failed to write spacepoints csv row")?;
+                }
+            }
+            if let Some(wtr) = tracks_wtr.as_mut() {
+                for track in tracks {
+                    wtr.serialize(track)
+                        .context("failed to write tracks csv row")?;
+                }
+            }
+            if let Some(kind) = &event.error_kind {
+                errors
+                    .entry(kind.clone())
+                    .or_default()
+                    .push(event.serial_number);
+            }
+            misbuilt_events += usize::from(event.misbuilt);
+            truncated_events +=
+                usize::from(event.truncated_fraction > Some(TRUNCATED_FRACTION_THRESHOLD));
+            let row = row_builder.next_row(&event);
+            if event.selected {
+                vertices_wtr.serialize(&row)?;
+                #[cfg(feature = "sqlite")]
+                if sqlite_output.is_some() {
+                    selected_rows.push(row);
+                }
+            }
+        }
+        #[cfg(feature = "sqlite")]
+        if let Some(sqlite_output) = sqlite_output.as_mut() {
+            sqlite_output.append(&selected_rows)?;
+        }
+        if let Some(wtr) = spacepoints_wtr.as_mut() {
+            wtr.flush()
+                .context("failed to flush spacepoints csv data")?;
+        }
+        if let Some(wtr) = tracks_wtr.as_mut() {
+            wtr.flush().context("failed to flush tracks csv data")?;
+        }
+        if let Some(wtr) = scalers_wtr.as_mut() {
+            wtr.flush().context("failed to flush scalers csv data")?;
+        }
+        vertices_wtr.end_of_file()?;
+        for (kind, count) in sampler.take_suppressed() {
+            pb.println(format!("Suppressed `{count}` more `{kind}` errors"));
+        }
+        if dropped_banks > 0 {
+            pb.println(format!(
+                "Dropped `{dropped_banks}` corrupted banks in `{}`",
+                file.display()
+            ));
+            total_dropped_banks += dropped_banks;
+        }
         // Set the style here rather than right after the first tick because a
         // println above would make this new style appear before this point.
         tp_bar.set_style(
@@ -115,59 +1311,121 @@ fn main() -> Result<()> {
         tp_bar.inc(1);
     }
     tp_bar.finish_and_clear();
+    // Nothing else is written to these; close the files now.
+    drop(spacepoints_wtr);
+    drop(tracks_wtr);
+    drop(scalers_wtr);
+    let timing = run_start
+        .zip(previous_final_timestamp)
+        .map(|(start, end)| RunTiming {
+            start,
+            end,
+            main_events: census.main,
+        });
+    // Exact totals, regardless of how many errors were printed.
+    let aggregator = sampler.into_aggregator();
+    for unknown in aggregator.unknown_versions() {
+        eprintln!("Warning: found `{}` errors with {unknown}", unknown.count);
+    }
+    for (kind, count) in aggregator.other_errors() {
+        eprintln!("Warning: found `{count}` `{kind}` errors");
+    }
 
-    let rows = rows.into_iter().scan(
-        (None, 0),
-        |(previous, cumulative), (serial_number, timestamp, vertex)| {
-            // If we don't have a timestamp, it is OK to use the previous one
-            // because this counter overflows every 68 seconds.
-            // This will only be problematic if we go over a full minute
-            // without an event, which is already impossible because DAQ has
-            // a 10 seconds timeout before stopping the run.
-            let current = timestamp.unwrap_or(previous.unwrap_or(0));
-            let delta = current.wrapping_sub(previous.unwrap_or(current));
-            *previous = Some(current);
-            *cumulative += u64::from(delta);
-
-            if timestamp.is_some() {
-                Some(Row {
-                    serial_number,
-                    trg_time: Some((*cumulative as f64 / TRG_CLOCK_FREQ).get::<second>()),
-                    reconstructed_x: vertex.map(|v| v.x.get::<meter>()),
-                    reconstructed_y: vertex.map(|v| v.y.get::<meter>()),
-                    reconstructed_z: vertex.map(|v| v.z.get::<meter>()),
-                })
-            } else {
-                Some(Row {
-                    serial_number,
-                    ..Default::default()
-                })
-            }
-        },
+    // Settings changed mid-run (e.g. suppression thresholds) invalidate some
+    // of the assumptions made by the reconstruction. It is still OK to
+    // produce the output, but it has to be obvious that something is off.
+    let final_odb = final_odb.map(|odb| alpha_g_analysis::parse_odb(&odb));
+    let odb_changes = match (initial_odb, final_odb) {
+        (Some(Ok(initial)), Some(Ok(last))) => {
+            alpha_g_analysis::compare_odbs(&initial, &last, &args.odb_checks)
+        }
+        (Some(Err(error)), _) | (_, Some(Err(error))) => {
+            eprintln!("Warning: failed to parse ODB ({error}), skipping ODB settings check");
+            Vec::new()
+        }
+        (None, None) if args.input_format == InputFormat::Midas => {
+            eprintln!("Warning: no readable ODB, skipping ODB settings check");
+            Vec::new()
+        }
+        _ => Vec::new(),
+    };
+    if !odb_changes.is_empty() {
+        eprintln!("Warning: ODB settings changed during the run:");
+        for change in &odb_changes {
+            eprintln!("  {change}");
+        }
+    }
+
+    if let Some(path) = &args.error_summary {
+        let summary = ErrorSummary {
+            run_number,
+            run_start: timing.map(|timing| timing.start),
+            run_end: timing.map(|timing| timing.end),
+            run_duration: timing.map(|timing| timing.duration()),
+            main_event_rate: timing.and_then(|timing| timing.main_event_rate()),
+            errors,
+            skipped_files: skipped_files.clone(),
+            gaps: gaps.clone(),
+            inputs: inputs.clone(),
+        };
+        let file = std::fs::File::create(path)
+            .with_context(|| format!("failed to create `{}`", path.display()))?;
+        serde_json::to_writer_pretty(file, &summary)
+            .with_context(|| format!("failed to write `{}`", path.display()))?;
+        eprintln!("Created `{}`", path.display());
+    }
+
+    if misbuilt_events > 0 {
+        eprintln!("Warning: found `{misbuilt_events}` misbuilt events");
+    }
+    if truncated_events > 0 {
+        eprintln!("Warning: found `{truncated_events}` events with truncated waveforms");
+    }
+
+    let mut warnings = Vec::new();
+    if let Some(run_number) = args.run_number_override {
+        warnings.push(format!("run number overridden to {run_number}"));
+    }
+    warnings.extend(odb_changes.iter().map(|change| format!("ODB {change}")));
+    warnings.extend(stale_calibrations.iter().map(ToString::to_string));
+    if args.input_format == InputFormat::Skim {
+        warnings.push(String::from("skim input, no trg_time"));
+    }
+    if let Some(path) = &args.only_errors {
+        warnings.push(format!("only the events in {}", path.display()));
+    }
+    if total_dropped_banks > 0 {
+        warnings.push(format!("dropped {total_dropped_banks} corrupted banks"));
+    }
+    warnings.extend(
+        skipped_files
+            .iter()
+            .map(|path| format!("skipped unreadable file {}", path.display())),
+    );
+    if missing_files > 0 {
+        warnings.push(format!(
+            "{missing_files} missing files, trg_time is not exact after the first one"
+        ));
+    }
+    warnings.extend(
+        gaps.iter()
+            .map(|gap| format!("missing data from {} to {}", gap.start, gap.end)),
     );
+    if let Some(mirror) = args.mirror {
+        warnings.push(format!(
+            "mirrored reconstruction ({0} -> -{0}), not for physics",
+            mirror.name()
+        ));
+    }
 
-    let output = args
-        .output
-        .unwrap_or_else(|| PathBuf::from(format!("R{run_number}_vertices")))
-        .with_extension("csv");
-    let mut wtr = std::fs::File::create(&output)
-        .with_context(|| format!("failed to create `{}`", output.display()))?;
-    eprintln!("Created `{}`", output.display());
-    wtr.write_all(
-        format!(
-            "# {} {}\n# {}\n",
-            env!("CARGO_PKG_NAME"),
-            env!("CARGO_PKG_VERSION"),
-            std::env::args().collect::<Vec<_>>().join(" ")
-        )
-        .as_bytes(),
-    )
-    .context("failed to write csv header")?;
-    let mut wtr = csv::Writer::from_writer(wtr);
-    for row in rows {
-        wtr.serialize(row).context("failed to write csv row")?;
+    vertices_wtr.finish(timing, &inputs, &warnings)?;
+    #[cfg(feature = "sqlite")]
+    if let Some(sqlite_output) = sqlite_output {
+        sqlite_output.finish(timing, &warnings)?;
     }
-    wtr.flush().context("failed to flush csv data")?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,218 @@
+// Write the rows of the output into a SQLite database. A single database can
+// hold the vertices of many runs; each run has exactly one row (with its
+// provenance) in the `runs` table.
+use crate::Row;
+use alpha_g_analysis::cli::RunTiming;
+use anyhow::{bail, Context, Result};
+use rusqlite::{params, Connection};
+use std::path::{Path, PathBuf};
+
+// Rows are inserted in transactions of this size. A transaction per row is
+// orders of magnitude slower, and a single transaction for the whole run keeps
+// an arbitrarily large journal.
+const BATCH_SIZE: usize = 10_000;
+
+// All the (optional) columns of `Row` are always in the table, even if they
+// are not in the CSV output of a particular run. Runs processed with different
+// flags can then share the same table.
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS runs (
+    run_number INTEGER PRIMARY KEY,
+    package TEXT NOT NULL,
+    arguments TEXT NOT NULL,
+    run_start INTEGER,
+    run_end INTEGER,
+    warnings TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS vertices (
+    run_number INTEGER NOT NULL,
+    serial_number INTEGER NOT NULL,
+    trg_time REAL,
+    reconstructed_x REAL,
+    reconstructed_y REAL,
+    reconstructed_z REAL,
+    reconstructed_sigma_x REAL,
+    reconstructed_sigma_y REAL,
+    reconstructed_sigma_z REAL,
+    t_first_avalanche REAL,
+    t_last_avalanche REAL,
+    late_fraction REAL,
+    truncated_fraction REAL,
+    weight REAL,
+    exact_time INTEGER,
+    utc TEXT,
+    no_vertex_reason TEXT,
+    num_wire_channels INTEGER,
+    num_pad_channels INTEGER,
+    num_avalanches INTEGER,
+    num_spacepoints INTEGER,
+    num_clusters INTEGER,
+    num_tracks INTEGER
+);
+CREATE UNIQUE INDEX IF NOT EXISTS vertices_run_serial
+    ON vertices (run_number, serial_number);
+";
+
+const INSERT_VERTEX: &str = "
+INSERT INTO vertices VALUES (
+    ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17,
+    ?18, ?19, ?20, ?21, ?22, ?23
+)
+";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum IfExists {
+    /// Fail without modifying the database
+    #[default]
+    Fail,
+    /// Delete all the previous rows of the run
+    Replace,
+}
+
+pub struct SqliteOutput {
+    path: PathBuf,
+    connection: Connection,
+    run_number: u32,
+    if_exists: IfExists,
+    // Whether the previous rows of the run were already checked (or deleted).
+    started: bool,
+}
+
+impl SqliteOutput {
+    // Open (or create) the database and check whether the run is already in
+    // it. This is done before reconstructing the run, so a conflict doesn't
+    // waste hours of processing.
+    pub fn open(path: &Path, run_number: u32, if_exists: IfExists) -> Result<Self> {
+        let connection = Connection::open(path)
+            .with_context(|| format!("failed to open `{}`", path.display()))?;
+        connection
+            .execute_batch(SCHEMA)
+            .with_context(|| format!("failed to create tables in `{}`", path.display()))?;
+        let output = Self {
+            path: path.to_path_buf(),
+            connection,
+            run_number,
+            if_exists,
+            started: false,
+        };
+        output.check_run()?;
+
+        Ok(output)
+    }
+
+    // Insert the rows of a single file. Called once per file, so all the rows
+    // of a run are never in memory at the same time.
+    pub fn append(&mut self, rows: &[Row]) -> Result<()> {
+        if !self.started {
+            self.start()?;
+        }
+
+        for batch in rows.chunks(BATCH_SIZE) {
+            let tx = self.connection.transaction()?;
+            {
+                let mut statement = tx.prepare_cached(INSERT_VERTEX)?;
+                for row in batch {
+                    statement.execute(params![
+                        self.run_number,
+                        row.serial_number,
+                        row.trg_time,
+                        row.reconstructed_x,
+                        row.reconstructed_y,
+                        row.reconstructed_z,
+                        row.reconstructed_sigma_x,
+                        row.reconstructed_sigma_y,
+                        row.reconstructed_sigma_z,
+                        row.t_first_avalanche.flatten(),
+                        row.t_last_avalanche.flatten(),
+                        row.late_fraction.flatten(),
+                        row.truncated_fraction.flatten(),
+                        row.weight.flatten(),
+                        row.exact_time.flatten(),
+                        row.utc.as_ref().and_then(Option::as_deref),
+                        row.no_vertex_reason.flatten(),
+                        row.num_wire_channels.flatten(),
+                        row.num_pad_channels.flatten(),
+                        row.num_avalanches.flatten(),
+                        row.num_spacepoints.flatten(),
+                        row.num_clusters.flatten(),
+                        row.num_tracks.flatten(),
+                    ])?;
+                }
+            }
+            tx.commit()
+                .with_context(|| format!("failed to write `{}`", self.path.display()))?;
+        }
+
+        Ok(())
+    }
+
+    pub fn finish(mut self, timing: Option<RunTiming>, warnings: &[String]) -> Result<()> {
+        // A run without any selected rows still gets its row in `runs`.
+        if !self.started {
+            self.start()?;
+        }
+        // Written last; a run without a row in `runs` was interrupted.
+        self.connection
+            .execute(
+                "INSERT INTO runs VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    self.run_number,
+                    format!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")),
+                    std::env::args().collect::<Vec<_>>().join(" "),
+                    timing.map(|timing| timing.start),
+                    timing.map(|timing| timing.end),
+                    warnings.join("\n"),
+                ],
+            )
+            .with_context(|| format!("failed to write `{}`", self.path.display()))?;
+        eprintln!("Updated `{}`", self.path.display());
+
+        Ok(())
+    }
+
+    // Check the run again (it could have been added by another process in the
+    // meantime), and delete its previous rows if they are going to be
+    // replaced.
+    fn start(&mut self) -> Result<()> {
+        self.check_run()?;
+        if self.if_exists == IfExists::Replace {
+            let tx = self.connection.transaction()?;
+            tx.execute(
+                "DELETE FROM vertices WHERE run_number = ?1",
+                [self.run_number],
+            )?;
+            tx.execute("DELETE FROM runs WHERE run_number = ?1", [self.run_number])?;
+            tx.commit()
+                .with_context(|| format!("failed to write `{}`", self.path.display()))?;
+        }
+        self.started = true;
+
+        Ok(())
+    }
+
+    // Fail if the run is already in the database (unless it is going to be
+    // replaced).
+    fn check_run(&self) -> Result<()> {
+        if self.if_exists == IfExists::Replace {
+            return Ok(());
+        }
+        let exists: bool = self
+            .connection
+            .query_row(
+                "SELECT EXISTS (SELECT 1 FROM runs WHERE run_number = ?1)
+                    OR EXISTS (SELECT 1 FROM vertices WHERE run_number = ?1)",
+                [self.run_number],
+                |row| row.get(0),
+            )
+            .with_context(|| format!("failed to read `{}`", self.path.display()))?;
+        if exists {
+            bail!(
+                "`{}` already has rows from run `{}` (use `--if-exists replace` to overwrite them)",
+                self.path.display(),
+                self.run_number
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,264 @@
+use super::*;
+
+// Events of a small run split across 3 files. The TRG timestamp overflows in
+// the second file, and some events don't have a timestamp (e.g. they failed to
+// reconstruct).
+fn files() -> Vec<Vec<EventResult>> {
+    let event = |serial_number, timestamp| EventResult {
+        serial_number,
+        timestamp,
+        selected: true,
+        ..Default::default()
+    };
+    vec![
+        vec![
+            event(0, Some(u32::MAX - 100)),
+            event(1, Some(u32::MAX - 50)),
+            event(2, None),
+        ],
+        vec![event(3, Some(49)), event(4, Some(100))],
+        vec![event(5, Some(200)), event(6, None)],
+    ]
+}
+
+fn trg_time(ticks: u32) -> Option<f64> {
+    Some((f64::from(ticks) / TRG_CLOCK_FREQ).get::<second>())
+}
+
+#[test]
+fn row_builder_contiguous_files() {
+    let mut builder = RowBuilder::new(false, false, false, false);
+    let rows: Vec<_> = files()
+        .iter()
+        .flat_map(|file| {
+            file.iter()
+                .map(|event| builder.next_row(event))
+                .collect::<Vec<_>>()
+        })
+        .collect();
+
+    assert_eq!(
+        rows.iter().map(|row| row.serial_number).collect::<Vec<_>>(),
+        [0, 1, 2, 3, 4, 5, 6]
+    );
+    assert_eq!(
+        rows.iter().map(|row| row.trg_time).collect::<Vec<_>>(),
+        [
+            trg_time(0),
+            trg_time(50),
+            None,
+            trg_time(150),
+            trg_time(201),
+            trg_time(301),
+            None
+        ]
+    );
+    assert!(rows.iter().all(|row| row.exact_time.is_none()));
+}
+
+#[test]
+fn row_builder_same_rows_file_by_file() {
+    let mut builder = RowBuilder::new(true, true, true, false);
+    let all_at_once: Vec<_> = files()
+        .concat()
+        .iter()
+        .map(|event| builder.next_row(event))
+        .collect();
+
+    let mut builder = RowBuilder::new(true, true, true, false);
+    let mut file_by_file = Vec::new();
+    for file in files() {
+        file_by_file.extend(file.iter().map(|event| builder.next_row(event)));
+    }
+
+    assert_eq!(file_by_file, all_at_once);
+}
+
+#[test]
+fn row_builder_gap() {
+    let mut builder = RowBuilder::new(false, true, false, false);
+    let mut rows = Vec::new();
+    for (index, file) in files().into_iter().enumerate() {
+        // Missing file (2 seconds) between the second and the third files.
+        if index == 2 {
+            builder.skip_gap(2);
+        }
+        rows.extend(file.iter().map(|event| builder.next_row(event)));
+    }
+
+    // The `trg_time` jumps by the duration of the gap after the last event
+    // before it.
+    assert_eq!(
+        rows[4..]
+            .iter()
+            .map(|row| (row.trg_time, row.exact_time))
+            .collect::<Vec<_>>(),
+        [
+            (trg_time(201), Some(Some(true))),
+            (trg_time(125_000_201), Some(Some(false))),
+            (None, Some(None))
+        ]
+    );
+}
+
+#[test]
+fn row_builder_skim() {
+    let mut builder = RowBuilder::new(false, false, true, false);
+    builder.skim = true;
+    let rows: Vec<_> = files()
+        .concat()
+        .iter()
+        .map(|event| builder.next_row(event))
+        .collect();
+
+    assert_eq!(
+        rows.iter().map(|row| row.serial_number).collect::<Vec<_>>(),
+        [0, 1, 2, 3, 4, 5, 6]
+    );
+    assert!(rows.iter().all(|row| row.trg_time.is_none()));
+}
+
+#[test]
+fn row_builder_utc() {
+    let mut builder = RowBuilder::new(false, true, false, false);
+    builder.utc = true;
+    builder.run_start = Some(1690000000);
+    let mut rows = Vec::new();
+    for (index, file) in files().into_iter().enumerate() {
+        if index == 2 {
+            builder.skip_gap(2);
+        }
+        rows.extend(file.iter().map(|event| builder.next_row(event)));
+    }
+
+    let start = || Some(Some(String::from("2023-07-22T04:26:40.000Z")));
+    // Empty without a `trg_time`, and after the gap.
+    assert_eq!(
+        rows.into_iter().map(|row| row.utc).collect::<Vec<_>>(),
+        [
+            start(),
+            start(),
+            Some(None),
+            start(),
+            start(),
+            Some(None),
+            Some(None)
+        ]
+    );
+}
+
+#[test]
+fn row_builder_no_utc() {
+    let mut builder = RowBuilder::new(false, false, false, false);
+    builder.run_start = Some(1690000000);
+    assert!(files()
+        .concat()
+        .iter()
+        .all(|event| builder.next_row(event).utc.is_none()));
+}
+
+#[test]
+fn row_builder_unselected_events_advance_trg_time() {
+    let mut events = files().concat();
+    for event in &mut events[..4] {
+        event.selected = false;
+    }
+
+    let mut builder = RowBuilder::new(false, false, false, false);
+    let rows: Vec<_> = events
+        .iter()
+        .map(|event| builder.next_row(event))
+        .zip(&events)
+        .filter_map(|(row, event)| event.selected.then_some(row))
+        .collect();
+
+    assert_eq!(
+        rows.iter()
+            .map(|row| (row.serial_number, row.trg_time))
+            .collect::<Vec<_>>(),
+        [(4, trg_time(201)), (5, trg_time(301)), (6, None)]
+    );
+}
+
+#[test]
+fn row_builder_no_vertex_reason() {
+    let event = EventResult {
+        serial_number: 7,
+        timestamp: Some(0),
+        no_vertex_reason: Some("no_clusters"),
+        selected: true,
+        ..Default::default()
+    };
+    let decode_error = EventResult {
+        serial_number: 8,
+        no_vertex_reason: Some("decode_error"),
+        selected: true,
+        ..Default::default()
+    };
+
+    let mut builder = RowBuilder::new(false, false, true, false);
+    assert_eq!(
+        builder.next_row(&event).no_vertex_reason,
+        Some(Some("no_clusters"))
+    );
+    assert_eq!(
+        builder.next_row(&decode_error).no_vertex_reason,
+        Some(Some("decode_error"))
+    );
+    // The column is not written at all without `--why-no-vertex`.
+    let mut builder = RowBuilder::new(false, false, false, false);
+    assert_eq!(builder.next_row(&event).no_vertex_reason, None);
+}
+
+#[test]
+fn row_builder_diagnostics() {
+    let event = EventResult {
+        serial_number: 7,
+        timestamp: Some(0),
+        no_vertex_reason: Some("insufficient_tracks"),
+        summary: Some(ReconstructionSummary {
+            wire_channels: 20,
+            pad_channels: 150,
+            avalanches: 45,
+            report: alpha_g_physics::ReconstructionReport {
+                num_spacepoints: 40,
+                cluster_sizes: vec![20, 15],
+                num_unclustered: 5,
+                track_fits: vec![
+                    alpha_g_physics::TrackFit::Fitted,
+                    alpha_g_physics::TrackFit::NoInitialParameters,
+                ],
+                ..Default::default()
+            },
+        }),
+        selected: true,
+        ..Default::default()
+    };
+    let decode_error = EventResult {
+        serial_number: 8,
+        no_vertex_reason: Some("decode_error"),
+        selected: true,
+        ..Default::default()
+    };
+
+    let mut builder = RowBuilder::new(false, false, false, true);
+    let row = builder.next_row(&event);
+    assert_eq!(row.no_vertex_reason, Some(Some("insufficient_tracks")));
+    assert_eq!(row.num_wire_channels, Some(Some(20)));
+    assert_eq!(row.num_pad_channels, Some(Some(150)));
+    assert_eq!(row.num_avalanches, Some(Some(45)));
+    assert_eq!(row.num_spacepoints, Some(Some(40)));
+    assert_eq!(row.num_clusters, Some(Some(2)));
+    assert_eq!(row.num_tracks, Some(Some(1)));
+
+    let row = builder.next_row(&decode_error);
+    assert_eq!(row.no_vertex_reason, Some(Some("decode_error")));
+    assert_eq!(row.num_wire_channels, Some(None));
+    assert_eq!(row.num_tracks, Some(None));
+    // Without `--diagnostics`, the default columns are unchanged.
+    let mut builder = RowBuilder::new(false, false, false, false);
+    let row = builder.next_row(&event);
+    assert_eq!(row.no_vertex_reason, None);
+    assert_eq!(row.num_wire_channels, None);
+    assert_eq!(row.num_tracks, None);
+}
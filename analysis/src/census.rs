@@ -0,0 +1,62 @@
+use alpha_g_detector::midas::EventId;
+use thiserror::Error;
+
+/// The error type returned when a run doesn't have any main events.
+#[derive(Debug, Error)]
+#[error("run contains no Main events (found {chronobox} Chronobox, {sequencer} Sequencer events)")]
+pub struct NoMainEventsError {
+    chronobox: usize,
+    sequencer: usize,
+}
+
+/// Number of events of each kind in a run.
+///
+/// Counting the events only requires the event headers, so this is cheap to
+/// do while scanning the files of a run. Events with an unknown ID are ignored.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EventCensus {
+    /// Number of [`EventId::Main`] events.
+    pub main: usize,
+    /// Number of [`EventId::Chronobox`] events.
+    pub chronobox: usize,
+    /// Number of [`EventId::Sequencer2`] events.
+    pub sequencer: usize,
+}
+
+impl EventCensus {
+    /// Count a single event given its MIDAS event ID.
+    pub fn add(&mut self, event_id: u16) {
+        match EventId::try_from(event_id) {
+            Ok(EventId::Main) => self.main += 1,
+            Ok(EventId::Chronobox) => self.chronobox += 1,
+            Ok(EventId::Sequencer2) => self.sequencer += 1,
+            Err(_) => {}
+        }
+    }
+
+    /// Return an error if no main events have been counted.
+    ///
+    /// Calibration and junk runs sometimes contain no main events at all.
+    /// Binaries should check this before writing any output.
+    pub fn ensure_main_events(&self) -> Result<(), NoMainEventsError> {
+        if self.main == 0 {
+            Err(NoMainEventsError {
+                chronobox: self.chronobox,
+                sequencer: self.sequencer,
+            })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl Extend<u16> for EventCensus {
+    fn extend<T: IntoIterator<Item = u16>>(&mut self, iter: T) {
+        for event_id in iter {
+            self.add(event_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
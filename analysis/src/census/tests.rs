@@ -0,0 +1,39 @@
+use super::*;
+
+#[test]
+fn event_census_add() {
+    let mut census = EventCensus::default();
+    census.extend([1, 4, 4, 8, 1, 1, 0x8000, 42]);
+
+    assert_eq!(
+        census,
+        EventCensus {
+            main: 3,
+            chronobox: 2,
+            sequencer: 1,
+        }
+    );
+    assert!(census.ensure_main_events().is_ok());
+}
+
+#[test]
+fn event_census_no_main_events() {
+    let mut census = EventCensus::default();
+    census.extend([4, 4, 8, 4]);
+
+    let error = census.ensure_main_events().unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "run contains no Main events (found 3 Chronobox, 1 Sequencer events)"
+    );
+}
+
+#[test]
+fn event_census_empty() {
+    let census = EventCensus::default();
+
+    assert_eq!(
+        census.ensure_main_events().unwrap_err().to_string(),
+        "run contains no Main events (found 0 Chronobox, 0 Sequencer events)"
+    );
+}
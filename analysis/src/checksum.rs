@@ -0,0 +1,126 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::cell::RefCell;
+use std::fmt;
+use std::hash::Hasher;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use twox_hash::XxHash64;
+
+/// Streaming XXH64 hasher (with a seed of `0`).
+///
+/// This is a fast non-cryptographic hash. It is only meant to detect that the
+/// input files of an output changed (e.g. they were re-copied or
+/// re-compressed), not to protect against tampering.
+#[derive(Clone, Debug, Default)]
+pub struct Xxh64 {
+    hasher: XxHash64,
+    total: u64,
+}
+
+impl Xxh64 {
+    /// Add more data to the hash.
+    pub fn update(&mut self, data: &[u8]) {
+        self.hasher.write(data);
+        self.total += data.len() as u64;
+    }
+    /// Return the number of bytes added so far.
+    pub fn total_len(&self) -> u64 {
+        self.total
+    }
+    /// Return the hash of all the data added so far.
+    pub fn digest(&self) -> u64 {
+        self.hasher.finish()
+    }
+}
+
+// Allows e.g. `std::io::copy` from a reader straight into the hasher.
+impl Write for Xxh64 {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Size and checksum of the (raw, i.e. compressed if applicable) bytes of an
+/// input file.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileChecksum {
+    /// Path of the file (as given to the binary).
+    pub path: PathBuf,
+    /// Size of the file in bytes.
+    pub size: u64,
+    /// XXH64 hash of the contents of the file (see [`Xxh64`]). Written as a
+    /// hexadecimal string; JSON numbers can't hold all 64 bit values exactly.
+    #[serde(serialize_with = "serialize_hex", deserialize_with = "deserialize_hex")]
+    pub xxh64: u64,
+}
+
+impl FileChecksum {
+    pub(crate) fn new(path: &Path, hasher: &Xxh64) -> Self {
+        Self {
+            path: path.to_owned(),
+            size: hasher.total_len(),
+            xxh64: hasher.digest(),
+        }
+    }
+}
+
+fn serialize_hex<S: Serializer>(value: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&format!("{value:016x}"))
+}
+
+fn deserialize_hex<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    let hex = String::deserialize(deserializer)?;
+    u64::from_str_radix(&hex, 16).map_err(serde::de::Error::custom)
+}
+
+impl fmt::Display for FileChecksum {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "xxh64 {:016x}, {} bytes, {}",
+            self.xxh64,
+            self.size,
+            self.path.display()
+        )
+    }
+}
+
+// Hasher of all the bytes read so far by a `ChecksumReader`.
+pub(crate) type ChecksumState = Rc<RefCell<Xxh64>>;
+
+// Reader that hashes all the bytes read through it. The state is shared such
+// that it is still accessible after the reader is moved into e.g. a decoder.
+pub(crate) struct ChecksumReader<R> {
+    inner: R,
+    state: ChecksumState,
+}
+
+impl<R> ChecksumReader<R> {
+    pub(crate) fn new(inner: R) -> Self {
+        Self {
+            inner,
+            state: ChecksumState::default(),
+        }
+    }
+
+    pub(crate) fn state(&self) -> ChecksumState {
+        Rc::clone(&self.state)
+    }
+}
+
+impl<R: Read> Read for ChecksumReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.state.borrow_mut().update(&buf[..n]);
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,79 @@
+use super::*;
+
+fn xxh64(data: &[u8]) -> u64 {
+    let mut hasher = Xxh64::default();
+    hasher.update(data);
+    hasher.digest()
+}
+
+#[test]
+fn xxh64_reference_values() {
+    assert_eq!(xxh64(b""), 0xef46db3751d8e999);
+    assert_eq!(xxh64(b"a"), 0xd24ec4f1a98c6e5b);
+    assert_eq!(xxh64(b"abc"), 0x44bc2cf5ad770999);
+    // Longer than a single 32 byte stripe.
+    assert_eq!(
+        xxh64(b"Nobody inspects the spammish repetition"),
+        0xfbcea83c8a378bf1
+    );
+}
+
+#[test]
+fn xxh64_streaming() {
+    let data: Vec<u8> = (0..1000u32).map(|i| (i * 7 + 3) as u8).collect();
+    let expected = xxh64(&data);
+
+    for chunk_size in [1, 3, 8, 31, 32, 33, 100, 999] {
+        let mut hasher = Xxh64::default();
+        for chunk in data.chunks(chunk_size) {
+            hasher.update(chunk);
+        }
+        assert_eq!(hasher.total_len(), 1000);
+        assert_eq!(hasher.digest(), expected);
+    }
+}
+
+#[test]
+fn checksum_reader() {
+    let data: Vec<u8> = (0..100u8).collect();
+    let mut reader = ChecksumReader::new(&data[..]);
+    let state = reader.state();
+
+    let mut first = [0; 40];
+    reader.read_exact(&mut first).unwrap();
+    assert_eq!(state.borrow().total_len(), 40);
+    std::io::copy(&mut reader, &mut std::io::sink()).unwrap();
+
+    assert_eq!(state.borrow().total_len(), 100);
+    assert_eq!(state.borrow().digest(), xxh64(&data));
+}
+
+#[test]
+fn file_checksum_display() {
+    let checksum = FileChecksum {
+        path: PathBuf::from("run09570sub000.mid.lz4"),
+        size: 3,
+        xxh64: 0x44bc2cf5ad770999,
+    };
+
+    assert_eq!(
+        checksum.to_string(),
+        "xxh64 44bc2cf5ad770999, 3 bytes, run09570sub000.mid.lz4"
+    );
+}
+
+#[test]
+fn file_checksum_json_round_trip() {
+    let checksum = FileChecksum {
+        path: PathBuf::from("run09570sub000.mid.lz4"),
+        size: 3,
+        xxh64: 0x04bc2cf5ad770999,
+    };
+    let json = serde_json::to_string(&checksum).unwrap();
+
+    assert!(json.contains(r#""xxh64":"04bc2cf5ad770999""#));
+    assert_eq!(
+        serde_json::from_str::<FileChecksum>(&json).unwrap(),
+        checksum
+    );
+}
@@ -0,0 +1,450 @@
+use crate::{AlphaIOError, ErrorCategory, EventCensus};
+use anyhow::Context;
+use clap::{Arg, ArgAction, CommandFactory, FromArgMatches, Parser};
+use serde::Serialize;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+const VERSION_JSON_ID: &str = "version_json";
+
+/// Exit code of a binary when the input run doesn't have any main events.
+///
+/// This is different from the exit code of any other error such that scripts
+/// processing many runs can easily skip calibration and junk runs.
+pub const NO_MAIN_EVENTS_EXIT_CODE: i32 = 3;
+/// Exit code of a binary when an input file or directory is not found (see
+/// [`ErrorCategory::NotFound`]).
+pub const NOT_FOUND_EXIT_CODE: i32 = 4;
+/// Exit code of a binary when it doesn't have permission to access an input
+/// file or directory (see [`ErrorCategory::PermissionDenied`]).
+pub const PERMISSION_DENIED_EXIT_CODE: i32 = 5;
+/// Exit code of a binary when an input file is not valid ALPHA-g data (see
+/// [`ErrorCategory::InvalidData`]).
+pub const INVALID_DATA_EXIT_CODE: i32 = 6;
+/// Exit code of a binary when a sample of the main events of the input run
+/// doesn't have any TPC signal (see
+/// [`TpcSignalCheck::ensure_signal`](crate::TpcSignalCheck::ensure_signal)).
+pub const NO_TPC_SIGNAL_EXIT_CODE: i32 = 7;
+
+/// Output path that means stdout (see [`create_output`]).
+pub const STDOUT_PATH: &str = "-";
+
+// Provenance information printed by `--version-json`.
+#[derive(Debug, Serialize)]
+struct VersionInfo {
+    name: String,
+    version: &'static str,
+    git_describe: &'static str,
+    features: Vec<&'static str>,
+    dependencies: Dependencies,
+}
+
+#[derive(Debug, Serialize)]
+struct Dependencies {
+    alpha_g_detector: &'static str,
+    alpha_g_physics: &'static str,
+}
+
+impl VersionInfo {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            version: env!("CARGO_PKG_VERSION"),
+            git_describe: env!("ALPHA_G_GIT_DESCRIBE"),
+            features: env!("ALPHA_G_FEATURES")
+                .split(',')
+                .filter(|feature| !feature.is_empty())
+                .collect(),
+            dependencies: Dependencies {
+                alpha_g_detector: alpha_g_detector::VERSION,
+                alpha_g_physics: alpha_g_physics::VERSION,
+            },
+        }
+    }
+}
+
+/// Parse the command line arguments of a binary (e.g. `env!("CARGO_BIN_NAME")`).
+///
+/// This is the same as [`Parser::parse`], but it adds a `--version-json` flag
+/// to the command. If this flag is set, the name, version, git description
+/// (captured at build time), enabled features, and versions of the
+/// `alpha_g_detector` and `alpha_g_physics` dependencies are printed to stdout
+/// as JSON, and the process exits.
+pub fn parse<T: Parser>(name: &str) -> T {
+    let mut cmd = T::command().arg(
+        Arg::new(VERSION_JSON_ID)
+            .long("version-json")
+            .action(ArgAction::SetTrue)
+            // Required arguments are not enforced when an exclusive argument is
+            // present.
+            .exclusive(true)
+            .help("Print version and build information as JSON and exit"),
+    );
+    let mut matches = cmd.get_matches_mut();
+    if matches.get_flag(VERSION_JSON_ID) {
+        // It is OK to unwrap; serializing this struct can't fail.
+        println!(
+            "{}",
+            serde_json::to_string(&VersionInfo::new(name)).unwrap()
+        );
+        std::process::exit(0);
+    }
+
+    T::from_arg_matches_mut(&mut matches).unwrap_or_else(|error| error.format(&mut cmd).exit())
+}
+
+/// Exit the process with [`NO_MAIN_EVENTS_EXIT_CODE`] if the run doesn't have
+/// any main events.
+///
+/// A message with the number of events of each kind found in the run is
+/// printed to stderr before exiting.
+pub fn exit_if_no_main_events(census: &EventCensus) {
+    if let Err(error) = census.ensure_main_events() {
+        eprintln!("Error: {error}");
+        std::process::exit(NO_MAIN_EVENTS_EXIT_CODE);
+    }
+}
+
+/// Exit the process if the main logic of a binary returned an error.
+///
+/// The error and all its causes are printed to stderr. If there is an
+/// [`AlphaIOError`] anywhere in the chain of causes, the exit code depends on
+/// its [`ErrorCategory`]. Otherwise (or for [`ErrorCategory::Other`]), the
+/// exit code is `1`.
+pub fn exit_on_error(result: anyhow::Result<()>) {
+    if let Err(error) = result {
+        eprintln!("Error: {error:?}");
+        let code = match error
+            .chain()
+            .find_map(|cause| cause.downcast_ref::<AlphaIOError>())
+            .map(AlphaIOError::category)
+        {
+            Some(ErrorCategory::NotFound) => NOT_FOUND_EXIT_CODE,
+            Some(ErrorCategory::PermissionDenied) => PERMISSION_DENIED_EXIT_CODE,
+            Some(ErrorCategory::InvalidData) => INVALID_DATA_EXIT_CODE,
+            Some(ErrorCategory::Other) | None => 1,
+        };
+        std::process::exit(code);
+    }
+}
+
+/// Create the output file of a binary with the given `extension` (e.g. `csv`).
+/// The path of the created file is printed to stderr.
+///
+/// If `output` is [`STDOUT_PATH`], nothing is created and the output is written
+/// to stdout instead (e.g. to pipe it into another process). All other
+/// messages (e.g. progress bars and warnings) always go to stderr.
+pub fn create_output(output: &Path, extension: &str) -> anyhow::Result<Box<dyn Write + Send>> {
+    if output == Path::new(STDOUT_PATH) {
+        return Ok(Box::new(BufWriter::new(std::io::stdout())));
+    }
+    let output = output.with_extension(extension);
+    let file = std::fs::File::create(&output)
+        .with_context(|| format!("failed to create `{}`", output.display()))?;
+    eprintln!("Created `{}`", output.display());
+
+    Ok(Box::new(file))
+}
+
+/// Open an input file of a binary that is not a MIDAS file (e.g. a CSV output
+/// of another binary).
+///
+/// The error is an [`AlphaIOError`], so [`exit_on_error`] exits with the code
+/// of its category (e.g. [`NOT_FOUND_EXIT_CODE`] if the file doesn't exist).
+pub fn open_input(path: &Path) -> Result<File, AlphaIOError> {
+    File::open(path).map_err(crate::io_error(path))
+}
+
+/// CSV output with a header (e.g. the [`RunTiming`] of a run) that is only
+/// known after all the rows are written.
+///
+/// Rows go to a temporary `OUTPUT.csv.partial` file as they come, so memory
+/// usage doesn't grow with the number of rows. [`DeferredCsvWriter::finish`]
+/// then writes the provenance and the header followed by all the rows to the
+/// actual output (see [`create_output`]), and removes the temporary file. The
+/// temporary file is only created with the first row, and it is left behind
+/// (with all the rows so far) if the program fails midway.
+///
+/// If the output is [`STDOUT_PATH`], there is no temporary file. The
+/// provenance and the rows are written straight to stdout, and the header is
+/// written as a trailing block of `#` lines after the rows.
+pub struct DeferredCsvWriter {
+    output: PathBuf,
+    provenance: String,
+    // `None` if the rows are written straight to stdout.
+    partial: Option<PathBuf>,
+    wtr: Option<csv::Writer<Box<dyn Write + Send>>>,
+}
+
+impl DeferredCsvWriter {
+    /// Create a writer for the given output (same as in [`create_output`]).
+    /// The `provenance` (verbatim, e.g. `#` lines ending in a newline) is
+    /// always the first thing in the output.
+    pub fn new(output: &Path, provenance: &str) -> Self {
+        let partial =
+            (output != Path::new(STDOUT_PATH)).then(|| output.with_extension("csv.partial"));
+
+        Self {
+            output: output.to_path_buf(),
+            provenance: provenance.to_string(),
+            partial,
+            wtr: None,
+        }
+    }
+
+    /// Return the path of the temporary file with the rows, or [`None`] if the
+    /// output is stdout.
+    pub fn partial_path(&self) -> Option<&Path> {
+        self.partial.as_deref()
+    }
+
+    // Output with only the provenance written to it.
+    fn create_stdout(&self) -> anyhow::Result<Box<dyn Write + Send>> {
+        let mut output = create_output(&self.output, "csv")?;
+        output
+            .write_all(self.provenance.as_bytes())
+            .context("failed to write csv header")?;
+
+        Ok(output)
+    }
+
+    /// Write a single row to the temporary file (or to stdout).
+    pub fn serialize<S: Serialize>(&mut self, row: S) -> anyhow::Result<()> {
+        if self.wtr.is_none() {
+            let writer: Box<dyn Write + Send> = match &self.partial {
+                Some(partial) => Box::new(
+                    File::create(partial)
+                        .with_context(|| format!("failed to create `{}`", partial.display()))?,
+                ),
+                None => self.create_stdout()?,
+            };
+            self.wtr = Some(csv::Writer::from_writer(writer));
+        }
+
+        self.wtr
+            .as_mut()
+            .unwrap()
+            .serialize(row)
+            .context("failed to write csv row")
+    }
+
+    /// Flush the rows written so far (e.g. at the end of each input file).
+    pub fn flush(&mut self) -> anyhow::Result<()> {
+        match self.wtr.as_mut() {
+            Some(wtr) => wtr.flush().context("failed to flush csv data"),
+            None => Ok(()),
+        }
+    }
+
+    /// Write the `header` (verbatim, e.g. `#` lines ending in a newline)
+    /// followed by all the rows to the output. If the output is stdout, the
+    /// `header` is written after the rows instead.
+    pub fn finish(self, header: &str) -> anyhow::Result<()> {
+        let Some(partial) = self.partial else {
+            let mut output = match self.wtr {
+                Some(wtr) => wtr
+                    .into_inner()
+                    .map_err(|error| error.into_error())
+                    .context("failed to flush csv data")?,
+                None => self.create_stdout()?,
+            };
+            output
+                .write_all(header.as_bytes())
+                .context("failed to write csv header")?;
+            return output.flush().context("failed to flush csv data");
+        };
+
+        let mut output = create_output(&self.output, "csv")?;
+        output
+            .write_all(self.provenance.as_bytes())
+            .context("failed to write csv header")?;
+        output
+            .write_all(header.as_bytes())
+            .context("failed to write csv header")?;
+        if let Some(mut wtr) = self.wtr {
+            wtr.flush().context("failed to flush csv data")?;
+            drop(wtr);
+            let mut body = File::open(&partial)
+                .with_context(|| format!("failed to open `{}`", partial.display()))?;
+            std::io::copy(&mut body, &mut output).context("failed to write csv rows")?;
+            std::fs::remove_file(&partial)
+                .with_context(|| format!("failed to remove `{}`", partial.display()))?;
+        }
+
+        output.flush().context("failed to flush csv data")
+    }
+}
+
+/// Read the comment lines at the beginning of a CSV output (i.e. the provenance
+/// header and any warnings), and return them (without the trailing newline)
+/// together with a reader positioned at the CSV header.
+///
+/// The returned reader skips any other `#` lines further in, e.g. a trailing
+/// block written after all the rows.
+pub fn read_commented_csv<R: Read>(
+    reader: R,
+) -> anyhow::Result<(Vec<String>, csv::Reader<BufReader<R>>)> {
+    let mut reader = BufReader::new(reader);
+    let mut comments = Vec::new();
+    while reader.fill_buf()?.first() == Some(&b'#') {
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        comments.push(line.trim_end().to_string());
+    }
+    let reader = csv::ReaderBuilder::new()
+        .comment(Some(b'#'))
+        .from_reader(reader);
+
+    Ok((comments, reader))
+}
+
+/// Split the comment lines of a CSV output (see [`read_commented_csv`]) into
+/// its run timing line (see [`RunTiming`]) and its `# WARNING:` lines. These are
+/// the only lines that still apply to an output derived from its rows; the
+/// provenance and `# input` lines describe how that CSV itself was produced.
+pub fn carried_over_comments(comments: &[String]) -> (Option<&str>, Vec<&str>) {
+    let timing = comments
+        .iter()
+        .find(|comment| comment.starts_with("# run start "))
+        .map(String::as_str);
+    let warnings = comments
+        .iter()
+        .filter(|comment| comment.starts_with("# WARNING:"))
+        .map(String::as_str)
+        .collect();
+
+    (timing, warnings)
+}
+
+/// Chronobox channel given as `BOARD:CHANNEL` (e.g. `cb02:17`) in the command
+/// line.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChannelSpec {
+    /// Name of the Chronobox board.
+    pub board: String,
+    /// Channel number.
+    pub channel: u8,
+}
+
+impl FromStr for ChannelSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (board, channel) = s
+            .split_once(':')
+            .ok_or_else(|| String::from("expected `BOARD:CHANNEL`"))?;
+        let channel = channel
+            .parse()
+            .map_err(|_| format!("bad channel `{channel}`"))?;
+
+        Ok(Self {
+            board: board.to_string(),
+            channel,
+        })
+    }
+}
+
+/// Start, end, and number of main events of a run.
+///
+/// This is written by the binaries in the provenance header of their output
+/// (see the [`Display`](fmt::Display) implementation) to answer simple
+/// questions about a run without opening its files again.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RunTiming {
+    /// UNIX timestamp of the initial ODB dump.
+    pub start: u32,
+    /// UNIX timestamp of the final ODB dump.
+    pub end: u32,
+    /// Number of main events in the run.
+    pub main_events: usize,
+}
+
+impl RunTiming {
+    /// Return the duration (in seconds) of the run.
+    pub fn duration(&self) -> u32 {
+        self.end.saturating_sub(self.start)
+    }
+
+    /// Return the average rate (in Hz) of main events. Returns [`None`] if the
+    /// duration of the run is `0`.
+    pub fn main_event_rate(&self) -> Option<f64> {
+        let duration = self.duration();
+        (duration > 0).then(|| self.main_events as f64 / f64::from(duration))
+    }
+}
+
+impl fmt::Display for RunTiming {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "run start {}, end {}, duration {} s, average main event rate ",
+            self.start,
+            self.end,
+            self.duration()
+        )?;
+        match self.main_event_rate() {
+            Some(rate) => write!(f, "{rate:.3} Hz")?,
+            None => write!(f, "-")?,
+        }
+        // Appended at the end so that the fields above can still be parsed the
+        // same way as before.
+        write!(
+            f,
+            ", UTC start {}, end {}",
+            iso8601_seconds(i64::from(self.start)),
+            iso8601_seconds(i64::from(self.end))
+        )
+    }
+}
+
+// Calendar date (year, month, day) of a number of days since the UNIX epoch
+// (proleptic Gregorian calendar).
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    (year, month.try_into().unwrap(), day.try_into().unwrap())
+}
+
+// ISO 8601 UTC representation (without fractional seconds) of a UNIX
+// timestamp.
+fn iso8601_seconds(timestamp: i64) -> String {
+    let (year, month, day) = civil_from_days(timestamp.div_euclid(86400));
+    let seconds = timestamp.rem_euclid(86400);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60
+    )
+}
+
+/// Return the ISO 8601 representation (in UTC, with millisecond precision) of
+/// a UNIX timestamp in seconds, e.g. `2023-07-22T04:26:40.000Z`.
+///
+/// UNIX timestamps (e.g. the ODB dumps of a MIDAS file) count the seconds
+/// since the epoch in UTC, so they don't depend on the time zone of the DAQ
+/// computer (nor on daylight saving time).
+pub fn iso8601(timestamp: f64) -> String {
+    let millis = (timestamp * 1000.0).round() as i64;
+    let seconds = iso8601_seconds(millis.div_euclid(1000));
+
+    format!(
+        "{}.{:03}Z",
+        seconds.trim_end_matches('Z'),
+        millis.rem_euclid(1000)
+    )
+}
+
+#[cfg(test)]
+mod tests;
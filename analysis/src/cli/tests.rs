@@ -0,0 +1,182 @@
+use super::*;
+
+#[test]
+fn channel_spec_from_str() {
+    assert_eq!(
+        "cb02:17".parse(),
+        Ok(ChannelSpec {
+            board: String::from("cb02"),
+            channel: 17,
+        })
+    );
+    assert!("cb02".parse::<ChannelSpec>().is_err());
+    assert!("cb02:x".parse::<ChannelSpec>().is_err());
+    assert!("cb02:256".parse::<ChannelSpec>().is_err());
+}
+
+#[test]
+fn run_timing_display() {
+    let timing = RunTiming {
+        start: 1690000000,
+        end: 1690000300,
+        main_events: 1000,
+    };
+    assert_eq!(timing.duration(), 300);
+    assert_eq!(
+        timing.to_string(),
+        "run start 1690000000, end 1690000300, duration 300 s, average main event rate 3.333 Hz, UTC start 2023-07-22T04:26:40Z, end 2023-07-22T04:31:40Z"
+    );
+
+    let timing = RunTiming {
+        end: 1690000000,
+        ..timing
+    };
+    assert_eq!(timing.main_event_rate(), None);
+    assert!(timing
+        .to_string()
+        .ends_with("duration 0 s, average main event rate -, UTC start 2023-07-22T04:26:40Z, end 2023-07-22T04:26:40Z"));
+}
+
+#[test]
+fn iso8601_whole_seconds() {
+    assert_eq!(iso8601(0.0), "1970-01-01T00:00:00.000Z");
+    assert_eq!(iso8601(1690000000.0), "2023-07-22T04:26:40.000Z");
+    // Leap days (including the century rules).
+    assert_eq!(iso8601(951782400.0), "2000-02-29T00:00:00.000Z");
+    assert_eq!(iso8601(4107542400.0), "2100-03-01T00:00:00.000Z");
+}
+
+#[test]
+fn iso8601_fractional_seconds() {
+    assert_eq!(iso8601(1690000000.1234), "2023-07-22T04:26:40.123Z");
+    assert_eq!(iso8601(-1.5), "1969-12-31T23:59:58.500Z");
+}
+
+#[test]
+fn iso8601_daylight_saving_time() {
+    // Clocks at CERN went from 02:00 CET to 03:00 CEST at 01:00 UTC on
+    // 2023-03-26. UNIX timestamps (and hence UTC) are continuous across it.
+    assert_eq!(iso8601(1679792399.9996), "2023-03-26T01:00:00.000Z");
+    assert_eq!(iso8601(1679792400.25), "2023-03-26T01:00:00.250Z");
+    assert_eq!(iso8601(1679792400.0 - 3600.0), "2023-03-26T00:00:00.000Z");
+    assert_eq!(iso8601(1679792400.0 + 3600.0), "2023-03-26T02:00:00.000Z");
+}
+
+#[test]
+fn deferred_csv_writer() {
+    #[derive(Serialize)]
+    struct Row {
+        a: u32,
+        b: Option<f64>,
+    }
+
+    let dir = std::env::temp_dir().join(format!(
+        "alpha_g_analysis_{}_deferred_csv_writer",
+        std::process::id()
+    ));
+    std::fs::create_dir_all(&dir).unwrap();
+    let output = dir.join("output");
+
+    let mut wtr = DeferredCsvWriter::new(&output, "# provenance\n");
+    let partial = dir.join("output.csv.partial");
+    assert_eq!(wtr.partial_path(), Some(partial.as_path()));
+    // Nothing is created until the first row.
+    assert!(!partial.exists());
+    wtr.serialize(Row { a: 1, b: Some(0.5) }).unwrap();
+    wtr.serialize(Row { a: 2, b: None }).unwrap();
+    wtr.flush().unwrap();
+    assert_eq!(
+        std::fs::read_to_string(&partial).unwrap(),
+        "a,b\n1,0.5\n2,\n"
+    );
+    wtr.finish("# header\n").unwrap();
+
+    assert_eq!(
+        std::fs::read_to_string(dir.join("output.csv")).unwrap(),
+        "# provenance\n# header\na,b\n1,0.5\n2,\n"
+    );
+    assert!(!dir.join("output.csv.partial").exists());
+}
+
+#[test]
+fn deferred_csv_writer_no_rows() {
+    let dir = std::env::temp_dir().join(format!(
+        "alpha_g_analysis_{}_deferred_csv_writer_no_rows",
+        std::process::id()
+    ));
+    std::fs::create_dir_all(&dir).unwrap();
+    let output = dir.join("output");
+
+    DeferredCsvWriter::new(&output, "# provenance\n")
+        .finish("# header\n")
+        .unwrap();
+    assert_eq!(
+        std::fs::read_to_string(dir.join("output.csv")).unwrap(),
+        "# provenance\n# header\n"
+    );
+}
+
+#[test]
+fn deferred_csv_writer_stdout_no_partial_path() {
+    let wtr = DeferredCsvWriter::new(Path::new(STDOUT_PATH), "# provenance\n");
+
+    assert!(wtr.partial_path().is_none());
+}
+
+#[test]
+fn read_commented_csv_leading_and_trailing_comments() {
+    let input = "# alpha_g_analysis 0.5.0\n# alpha-g-vertices run.mid\n# WARNING: gap\na,b\n1,2\n# trailing\n3,4\n";
+
+    let (comments, mut reader) = read_commented_csv(input.as_bytes()).unwrap();
+    assert_eq!(
+        comments,
+        [
+            "# alpha_g_analysis 0.5.0",
+            "# alpha-g-vertices run.mid",
+            "# WARNING: gap"
+        ]
+    );
+    assert_eq!(reader.headers().unwrap(), vec!["a", "b"]);
+    let records: Vec<_> = reader.records().map(Result::unwrap).collect();
+    assert_eq!(records, [vec!["1", "2"], vec!["3", "4"]]);
+}
+
+#[test]
+fn carried_over_comments_timing_and_warnings() {
+    let comments: Vec<_> = [
+        "# alpha_g_analysis 0.5.0",
+        "# alpha-g-vertices run.mid",
+        "# run start 100, end 110, duration 10 s, average main event rate 0.200 Hz",
+        "# input run.mid xxh64:0123456789abcdef",
+        "# WARNING: gap",
+        "# WARNING: run number overridden to 1",
+    ]
+    .map(String::from)
+    .into();
+
+    let (timing, warnings) = carried_over_comments(&comments);
+    assert_eq!(timing, Some(comments[2].as_str()));
+    assert_eq!(warnings, [comments[4].as_str(), comments[5].as_str()]);
+
+    let (timing, warnings) = carried_over_comments(&comments[..2]);
+    assert!(timing.is_none());
+    assert!(warnings.is_empty());
+}
+
+#[test]
+fn read_commented_csv_no_comments() {
+    let (comments, mut reader) = read_commented_csv(&b"a,b\n1,2\n"[..]).unwrap();
+    assert!(comments.is_empty());
+    assert_eq!(reader.headers().unwrap(), vec!["a", "b"]);
+}
+
+#[test]
+fn open_input_not_found() {
+    let path = std::env::temp_dir().join(format!(
+        "alpha_g_analysis_{}_open_input_not_found.csv",
+        std::process::id()
+    ));
+    let error = open_input(&path).unwrap_err();
+    assert_eq!(error.category(), ErrorCategory::NotFound);
+    assert!(error.to_string().contains(&path.display().to_string()));
+}
@@ -0,0 +1,39 @@
+#[cfg(feature = "parquet")]
+pub use writer::{ParquetWriter, ParquetWriterError};
+
+// Streaming Parquet writer (requires the `parquet` feature).
+#[cfg(feature = "parquet")]
+mod writer;
+
+/// Key of the key-value metadata with the provenance of a Parquet output. The
+/// value has the same lines as the `#` header of the equivalent CSV output
+/// (without the `# ` prefix), separated by newlines.
+pub const PROVENANCE_KEY: &str = "alpha_g_provenance";
+
+/// Format of the tabular output of a binary.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Comma-separated values, with the provenance (and any warnings) as
+    /// comments at the top of the file
+    #[default]
+    Csv,
+    /// Apache Parquet, with the provenance (and any warnings) as key-value
+    /// metadata of the file (see [`PROVENANCE_KEY`]). Requires the `parquet`
+    /// feature
+    #[cfg(feature = "parquet")]
+    Parquet,
+}
+
+impl OutputFormat {
+    /// Return the file extension of the format.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            #[cfg(feature = "parquet")]
+            Self::Parquet => "parquet",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
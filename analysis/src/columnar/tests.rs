@@ -0,0 +1,9 @@
+use super::*;
+
+#[test]
+fn output_format_extension() {
+    assert_eq!(OutputFormat::default(), OutputFormat::Csv);
+    assert_eq!(OutputFormat::Csv.extension(), "csv");
+    #[cfg(feature = "parquet")]
+    assert_eq!(OutputFormat::Parquet.extension(), "parquet");
+}
@@ -0,0 +1,500 @@
+use arrow_array::{
+    ArrayRef, BooleanArray, Float64Array, Int64Array, RecordBatch, StringArray, UInt32Array,
+    UInt64Array,
+};
+use arrow_schema::{ArrowError, DataType, Schema, SchemaRef};
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::errors::ParquetError;
+use parquet::file::properties::{EnabledStatistics, WriterProperties};
+use parquet::format::KeyValue;
+use parquet::schema::types::ColumnPath;
+use serde::ser::{self, Impossible, Serialize};
+use std::fmt;
+use std::io::Write;
+use std::sync::Arc;
+use thiserror::Error;
+
+/// The error type returned by a [`ParquetWriter`].
+#[derive(Debug, Error)]
+pub enum ParquetWriterError {
+    /// A row is not a struct of primitive values (or options of them), or it
+    /// doesn't match the schema of the writer.
+    #[error("unsupported row format ({0})")]
+    Unsupported(String),
+    /// A value doesn't have the type of its column in the schema.
+    #[error("mismatched value type in column `{column}`")]
+    TypeMismatch { column: String },
+    /// Error while converting the columns to Arrow arrays.
+    #[error("arrow error")]
+    Arrow(#[from] ArrowError),
+    /// Error while writing the Parquet file.
+    #[error("parquet error")]
+    Parquet(#[from] ParquetError),
+    /// Error flushing the underlying writer.
+    #[error("io error")]
+    Io(#[from] std::io::Error),
+}
+
+impl ser::Error for ParquetWriterError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Self::Unsupported(msg.to_string())
+    }
+}
+
+// A single (possibly null) cell of a row.
+#[derive(Clone, Debug, PartialEq)]
+enum Value {
+    Null,
+    U32(u32),
+    U64(u64),
+    I64(i64),
+    F64(f64),
+    Bool(bool),
+    Str(String),
+}
+
+// All the values of a column in the current row group.
+#[derive(Clone, Debug, PartialEq)]
+enum Values {
+    U32(Vec<Option<u32>>),
+    U64(Vec<Option<u64>>),
+    I64(Vec<Option<i64>>),
+    F64(Vec<Option<f64>>),
+    Bool(Vec<Option<bool>>),
+    Str(Vec<Option<String>>),
+}
+
+impl Values {
+    // Return `None` if the type can't be serialized from a primitive value.
+    fn new(data_type: &DataType) -> Option<Self> {
+        match data_type {
+            DataType::UInt32 => Some(Self::U32(Vec::new())),
+            DataType::UInt64 => Some(Self::U64(Vec::new())),
+            DataType::Int64 => Some(Self::I64(Vec::new())),
+            DataType::Float64 => Some(Self::F64(Vec::new())),
+            DataType::Boolean => Some(Self::Bool(Vec::new())),
+            DataType::Utf8 => Some(Self::Str(Vec::new())),
+            _ => None,
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            Self::U32(v) => v.len(),
+            Self::U64(v) => v.len(),
+            Self::I64(v) => v.len(),
+            Self::F64(v) => v.len(),
+            Self::Bool(v) => v.len(),
+            Self::Str(v) => v.len(),
+        }
+    }
+    // Return `false` if the value doesn't match the type of the column.
+    fn push(&mut self, value: Value) -> bool {
+        match (self, value) {
+            (Self::U32(v), Value::U32(x)) => v.push(Some(x)),
+            (Self::U64(v), Value::U64(x)) => v.push(Some(x)),
+            (Self::I64(v), Value::I64(x)) => v.push(Some(x)),
+            (Self::F64(v), Value::F64(x)) => v.push(Some(x)),
+            (Self::Bool(v), Value::Bool(x)) => v.push(Some(x)),
+            (Self::Str(v), Value::Str(x)) => v.push(Some(x)),
+            (Self::U32(v), Value::Null) => v.push(None),
+            (Self::U64(v), Value::Null) => v.push(None),
+            (Self::I64(v), Value::Null) => v.push(None),
+            (Self::F64(v), Value::Null) => v.push(None),
+            (Self::Bool(v), Value::Null) => v.push(None),
+            (Self::Str(v), Value::Null) => v.push(None),
+            _ => return false,
+        }
+
+        true
+    }
+    // Leave the column empty for the next row group.
+    fn take_array(&mut self) -> ArrayRef {
+        match self {
+            Self::U32(v) => Arc::new(UInt32Array::from(std::mem::take(v))),
+            Self::U64(v) => Arc::new(UInt64Array::from(std::mem::take(v))),
+            Self::I64(v) => Arc::new(Int64Array::from(std::mem::take(v))),
+            Self::F64(v) => Arc::new(Float64Array::from(std::mem::take(v))),
+            Self::Bool(v) => Arc::new(BooleanArray::from(std::mem::take(v))),
+            Self::Str(v) => Arc::new(StringArray::from(std::mem::take(v))),
+        }
+    }
+}
+
+/// Write rows to an Apache Parquet file.
+///
+/// This is meant to be a drop-in replacement for a [`csv::Writer`] when the
+/// rows are serialized from a struct: each field is written to the column of
+/// the schema with the same name, and [`None`] values are nulls (instead of
+/// empty strings). Fields that are skipped while serializing a row (e.g.
+/// `#[serde(skip_serializing_if = "...")]`) are also nulls. The schema is
+/// declared when the writer is created instead of inferred from the values,
+/// so the schema of the output doesn't depend on the particular values of a
+/// run (e.g. a column that only has nulls still has its own type).
+///
+/// The supported column types are `UInt32` (`u8`, `u16`, and `u32` fields),
+/// `UInt64` (`u64` and `usize`), `Int64` (all signed integers), `Float64`
+/// (`f32` and `f64`), `Boolean`, and `Utf8` (strings, characters, and unit
+/// variants).
+///
+/// Rows are kept in memory until [`ParquetWriter::flush`] ends the current row
+/// group (e.g. once per input file).
+#[derive(Debug)]
+pub struct ParquetWriter<W: Write + Send> {
+    // Only until the first row group is written.
+    writer: Option<W>,
+    arrow_writer: Option<ArrowWriter<W>>,
+    schema: SchemaRef,
+    statistics: Vec<String>,
+    metadata: Vec<KeyValue>,
+    // Rows of the current row group, in the same order as the schema.
+    columns: Vec<Values>,
+    num_rows: usize,
+}
+
+impl<W: Write + Send> ParquetWriter<W> {
+    /// Create a new writer with the given schema. Return an error if the type
+    /// of any column is not supported.
+    pub fn new(writer: W, schema: Schema) -> Result<Self, ParquetWriterError> {
+        let columns = schema
+            .fields()
+            .iter()
+            .map(|field| {
+                Values::new(field.data_type()).ok_or_else(|| {
+                    ParquetWriterError::Unsupported(format!(
+                        "column `{}` has type `{}`",
+                        field.name(),
+                        field.data_type()
+                    ))
+                })
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            writer: Some(writer),
+            arrow_writer: None,
+            schema: Arc::new(schema),
+            statistics: Vec::new(),
+            metadata: Vec::new(),
+            columns,
+            num_rows: 0,
+        })
+    }
+    /// Compute the min/max statistics (per row group and page) of the given
+    /// columns, e.g. to filter row groups by serial number when reading the
+    /// file. No statistics are computed for any other column.
+    pub fn with_statistics<I, S>(mut self, columns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.statistics = columns.into_iter().map(Into::into).collect();
+        self
+    }
+    /// Add a key-value pair to the metadata of the file (e.g. the provenance
+    /// of the data).
+    pub fn add_metadata(&mut self, key: impl Into<String>, value: impl Into<String>) {
+        self.metadata
+            .push(KeyValue::new(key.into(), Some(value.into())));
+    }
+    /// Serialize a single row.
+    ///
+    /// A row that fails to serialize might be partially written.
+    pub fn serialize<T: Serialize>(&mut self, row: T) -> Result<(), ParquetWriterError> {
+        row.serialize(RowSerializer { writer: self })?;
+        self.num_rows += 1;
+        // Fields skipped in this row.
+        for column in &mut self.columns {
+            if column.len() < self.num_rows {
+                column.push(Value::Null);
+            }
+        }
+
+        Ok(())
+    }
+    /// End the current row group (if it has any rows).
+    pub fn flush(&mut self) -> Result<(), ParquetWriterError> {
+        if self.num_rows == 0 {
+            return Ok(());
+        }
+        self.num_rows = 0;
+        let arrays = self.columns.iter_mut().map(Values::take_array).collect();
+        let batch = RecordBatch::try_new(self.schema.clone(), arrays)?;
+
+        let arrow_writer = self.arrow_writer()?;
+        arrow_writer.write(&batch)?;
+        arrow_writer.flush()?;
+
+        Ok(())
+    }
+    /// Write all the remaining rows, and the metadata, to the file. Return the
+    /// underlying writer.
+    pub fn finish(mut self) -> Result<W, ParquetWriterError> {
+        self.flush()?;
+        // A file without any rows still has the schema and metadata.
+        self.arrow_writer()?;
+
+        let mut arrow_writer = self.arrow_writer.unwrap();
+        for kv in self.metadata {
+            arrow_writer.append_key_value_metadata(kv);
+        }
+        let mut writer = arrow_writer.into_inner()?;
+        writer.flush()?;
+
+        Ok(writer)
+    }
+    // The `ArrowWriter` is only created when it is first needed, so that the
+    // statistics can be configured after `new`.
+    fn arrow_writer(&mut self) -> Result<&mut ArrowWriter<W>, ParquetWriterError> {
+        if self.arrow_writer.is_none() {
+            let mut props = WriterProperties::builder()
+                .set_compression(Compression::SNAPPY)
+                .set_statistics_enabled(EnabledStatistics::None);
+            for column in &self.statistics {
+                props = props.set_column_statistics_enabled(
+                    ColumnPath::from(column.as_str()),
+                    EnabledStatistics::Page,
+                );
+            }
+            let writer = self.writer.take().unwrap();
+            self.arrow_writer = Some(ArrowWriter::try_new(
+                writer,
+                self.schema.clone(),
+                Some(props.build()),
+            )?);
+        }
+
+        Ok(self.arrow_writer.as_mut().unwrap())
+    }
+
+    fn push(&mut self, name: &'static str, value: Value) -> Result<(), ParquetWriterError> {
+        let index = self.schema.index_of(name).map_err(|_| {
+            ParquetWriterError::Unsupported(format!("field `{name}` is not in the schema"))
+        })?;
+        let column = &mut self.columns[index];
+        if column.len() > self.num_rows {
+            return Err(ParquetWriterError::Unsupported(format!(
+                "duplicate field `{name}`"
+            )));
+        }
+        if !column.push(value) {
+            return Err(ParquetWriterError::TypeMismatch {
+                column: name.to_string(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+struct RowSerializer<'a, W: Write + Send> {
+    writer: &'a mut ParquetWriter<W>,
+}
+
+// Implement the methods of a `Serializer` that always fail with an
+// `Unsupported` error.
+macro_rules! unsupported {
+    ($reason:literal; $($method:ident($($arg:ty),*) -> $ret:ty;)*) => {
+        $(
+            fn $method(self, $(_: $arg),*) -> Result<$ret, Self::Error> {
+                Err(ParquetWriterError::Unsupported($reason.to_string()))
+            }
+        )*
+    };
+}
+
+impl<'a, W: Write + Send> ser::Serializer for RowSerializer<'a, W> {
+    type Ok = ();
+    type Error = ParquetWriterError;
+    type SerializeSeq = Impossible<(), ParquetWriterError>;
+    type SerializeTuple = Impossible<(), ParquetWriterError>;
+    type SerializeTupleStruct = Impossible<(), ParquetWriterError>;
+    type SerializeTupleVariant = Impossible<(), ParquetWriterError>;
+    type SerializeMap = Impossible<(), ParquetWriterError>;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Impossible<(), ParquetWriterError>;
+
+    unsupported! {
+        "row is not a struct";
+        serialize_bool(bool) -> ();
+        serialize_i8(i8) -> ();
+        serialize_i16(i16) -> ();
+        serialize_i32(i32) -> ();
+        serialize_i64(i64) -> ();
+        serialize_u8(u8) -> ();
+        serialize_u16(u16) -> ();
+        serialize_u32(u32) -> ();
+        serialize_u64(u64) -> ();
+        serialize_f32(f32) -> ();
+        serialize_f64(f64) -> ();
+        serialize_char(char) -> ();
+        serialize_str(&str) -> ();
+        serialize_bytes(&[u8]) -> ();
+        serialize_none() -> ();
+        serialize_unit() -> ();
+        serialize_unit_struct(&'static str) -> ();
+        serialize_unit_variant(&'static str, u32, &'static str) -> ();
+        serialize_seq(Option<usize>) -> Self::SerializeSeq;
+        serialize_tuple(usize) -> Self::SerializeTuple;
+        serialize_tuple_struct(&'static str, usize) -> Self::SerializeTupleStruct;
+        serialize_tuple_variant(&'static str, u32, &'static str, usize) -> Self::SerializeTupleVariant;
+        serialize_map(Option<usize>) -> Self::SerializeMap;
+        serialize_struct_variant(&'static str, u32, &'static str, usize) -> Self::SerializeStructVariant;
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<(), Self::Error> {
+        value.serialize(self)
+    }
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        value.serialize(self)
+    }
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: &T,
+    ) -> Result<(), Self::Error> {
+        Err(ParquetWriterError::Unsupported(
+            "row is not a struct".to_string(),
+        ))
+    }
+    fn serialize_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        Ok(self)
+    }
+}
+
+impl<'a, W: Write + Send> ser::SerializeStruct for RowSerializer<'a, W> {
+    type Ok = ();
+    type Error = ParquetWriterError;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), ParquetWriterError> {
+        let value = value.serialize(ValueSerializer)?;
+        self.writer.push(key, value)
+    }
+    fn end(self) -> Result<(), ParquetWriterError> {
+        Ok(())
+    }
+}
+
+// Serialize a single field of a row.
+struct ValueSerializer;
+
+impl ser::Serializer for ValueSerializer {
+    type Ok = Value;
+    type Error = ParquetWriterError;
+    type SerializeSeq = Impossible<Value, ParquetWriterError>;
+    type SerializeTuple = Impossible<Value, ParquetWriterError>;
+    type SerializeTupleStruct = Impossible<Value, ParquetWriterError>;
+    type SerializeTupleVariant = Impossible<Value, ParquetWriterError>;
+    type SerializeMap = Impossible<Value, ParquetWriterError>;
+    type SerializeStruct = Impossible<Value, ParquetWriterError>;
+    type SerializeStructVariant = Impossible<Value, ParquetWriterError>;
+
+    fn serialize_bool(self, v: bool) -> Result<Value, ParquetWriterError> {
+        Ok(Value::Bool(v))
+    }
+    fn serialize_i8(self, v: i8) -> Result<Value, ParquetWriterError> {
+        Ok(Value::I64(v.into()))
+    }
+    fn serialize_i16(self, v: i16) -> Result<Value, ParquetWriterError> {
+        Ok(Value::I64(v.into()))
+    }
+    fn serialize_i32(self, v: i32) -> Result<Value, ParquetWriterError> {
+        Ok(Value::I64(v.into()))
+    }
+    fn serialize_i64(self, v: i64) -> Result<Value, ParquetWriterError> {
+        Ok(Value::I64(v))
+    }
+    fn serialize_u8(self, v: u8) -> Result<Value, ParquetWriterError> {
+        Ok(Value::U32(v.into()))
+    }
+    fn serialize_u16(self, v: u16) -> Result<Value, ParquetWriterError> {
+        Ok(Value::U32(v.into()))
+    }
+    fn serialize_u32(self, v: u32) -> Result<Value, ParquetWriterError> {
+        Ok(Value::U32(v))
+    }
+    fn serialize_u64(self, v: u64) -> Result<Value, ParquetWriterError> {
+        Ok(Value::U64(v))
+    }
+    fn serialize_f32(self, v: f32) -> Result<Value, ParquetWriterError> {
+        Ok(Value::F64(v.into()))
+    }
+    fn serialize_f64(self, v: f64) -> Result<Value, ParquetWriterError> {
+        Ok(Value::F64(v))
+    }
+    fn serialize_char(self, v: char) -> Result<Value, ParquetWriterError> {
+        Ok(Value::Str(v.to_string()))
+    }
+    fn serialize_str(self, v: &str) -> Result<Value, ParquetWriterError> {
+        Ok(Value::Str(v.to_string()))
+    }
+    fn serialize_none(self) -> Result<Value, ParquetWriterError> {
+        Ok(Value::Null)
+    }
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Value, ParquetWriterError> {
+        value.serialize(self)
+    }
+    fn serialize_unit(self) -> Result<Value, ParquetWriterError> {
+        Ok(Value::Null)
+    }
+    fn serialize_unit_struct(self, _: &'static str) -> Result<Value, ParquetWriterError> {
+        Ok(Value::Null)
+    }
+    // Same as the CSV output, i.e. the name of the variant.
+    fn serialize_unit_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+    ) -> Result<Value, ParquetWriterError> {
+        Ok(Value::Str(variant.to_string()))
+    }
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<Value, ParquetWriterError> {
+        value.serialize(self)
+    }
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: &T,
+    ) -> Result<Value, ParquetWriterError> {
+        Err(ParquetWriterError::Unsupported(
+            "field is not a primitive value".to_string(),
+        ))
+    }
+
+    unsupported! {
+        "field is not a primitive value";
+        serialize_bytes(&[u8]) -> Value;
+        serialize_seq(Option<usize>) -> Self::SerializeSeq;
+        serialize_tuple(usize) -> Self::SerializeTuple;
+        serialize_tuple_struct(&'static str, usize) -> Self::SerializeTupleStruct;
+        serialize_tuple_variant(&'static str, u32, &'static str, usize) -> Self::SerializeTupleVariant;
+        serialize_map(Option<usize>) -> Self::SerializeMap;
+        serialize_struct(&'static str, usize) -> Self::SerializeStruct;
+        serialize_struct_variant(&'static str, u32, &'static str, usize) -> Self::SerializeStructVariant;
+    }
+}
+
+#[cfg(test)]
+mod tests;
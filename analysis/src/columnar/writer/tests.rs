@@ -0,0 +1,368 @@
+use super::*;
+use arrow_array::cast::AsArray;
+use arrow_array::types::{Float64Type, UInt32Type, UInt64Type};
+use arrow_array::Array;
+use arrow_schema::Field;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::file::reader::{FileReader, SerializedFileReader};
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+#[derive(Serialize)]
+struct Row {
+    serial_number: u32,
+    x: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    flag: Option<bool>,
+    never: Option<f64>,
+}
+
+fn row_schema() -> Schema {
+    Schema::new(vec![
+        Field::new("serial_number", DataType::UInt32, true),
+        Field::new("x", DataType::Float64, true),
+        Field::new("flag", DataType::Boolean, true),
+        Field::new("never", DataType::Float64, true),
+    ])
+}
+
+// Write the rows to a temporary Parquet file and return its path.
+fn write_rows<T: Serialize>(
+    name: &str,
+    schema: Schema,
+    rows: Vec<T>,
+    metadata: &[(&str, &str)],
+) -> Result<PathBuf, ParquetWriterError> {
+    let path = std::env::temp_dir().join(format!(
+        "alpha_g_analysis_{}_{name}.parquet",
+        std::process::id()
+    ));
+    let mut wtr = ParquetWriter::new(File::create(&path).unwrap(), schema)?;
+    for (key, value) in metadata {
+        wtr.add_metadata(*key, *value);
+    }
+    for row in rows {
+        wtr.serialize(row)?;
+    }
+    wtr.finish()?;
+
+    Ok(path)
+}
+
+fn read_batch(path: &Path) -> RecordBatch {
+    let reader = ParquetRecordBatchReaderBuilder::try_new(File::open(path).unwrap())
+        .unwrap()
+        .build()
+        .unwrap();
+    let batches: Vec<_> = reader.map(Result::unwrap).collect();
+    assert_eq!(batches.len(), 1);
+
+    batches.into_iter().next().unwrap()
+}
+
+#[test]
+fn parquet_writer_columns() {
+    let rows = vec![
+        Row {
+            serial_number: 1,
+            x: Some(1.5),
+            flag: None,
+            never: None,
+        },
+        Row {
+            serial_number: 2,
+            x: None,
+            flag: Some(true),
+            never: None,
+        },
+        Row {
+            serial_number: 3,
+            x: Some(-2.0),
+            flag: None,
+            never: None,
+        },
+    ];
+    let path = write_rows("columns", row_schema(), rows, &[]).unwrap();
+    let batch = read_batch(&path);
+    assert_eq!(batch.num_rows(), 3);
+
+    let schema = batch.schema();
+    let names: Vec<_> = schema.fields().iter().map(|f| f.name().as_str()).collect();
+    assert_eq!(names, ["serial_number", "x", "flag", "never"]);
+    assert!(schema.fields().iter().all(|f| f.is_nullable()));
+
+    let serial_number = batch.column(0).as_primitive::<UInt32Type>();
+    assert_eq!(serial_number.values(), &[1, 2, 3]);
+
+    let x = batch.column(1).as_primitive::<Float64Type>();
+    assert_eq!(x.value(0), 1.5);
+    assert!(x.is_null(1));
+    assert_eq!(x.value(2), -2.0);
+
+    let flag = batch.column(2).as_boolean();
+    assert!(flag.is_null(0));
+    assert!(flag.value(1));
+    assert!(flag.is_null(2));
+
+    let never = batch.column(3).as_primitive::<Float64Type>();
+    assert_eq!(never.null_count(), 3);
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn parquet_writer_metadata() {
+    let path = write_rows(
+        "metadata",
+        row_schema(),
+        vec![Row {
+            serial_number: 1,
+            x: None,
+            flag: None,
+            never: None,
+        }],
+        &[("package", "alpha-g-analysis 0.0.0"), ("warnings", "")],
+    )
+    .unwrap();
+    let reader = SerializedFileReader::new(File::open(&path).unwrap()).unwrap();
+    let metadata = reader
+        .metadata()
+        .file_metadata()
+        .key_value_metadata()
+        .unwrap();
+    let value = |key: &str| {
+        metadata
+            .iter()
+            .find(|kv| kv.key == key)
+            .and_then(|kv| kv.value.clone())
+    };
+    assert_eq!(value("package").unwrap(), "alpha-g-analysis 0.0.0");
+    assert_eq!(value("warnings").unwrap(), "");
+    assert_eq!(reader.metadata().file_metadata().num_rows(), 1);
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn parquet_writer_type_mismatch() {
+    #[derive(Serialize)]
+    #[serde(untagged)]
+    enum Either {
+        Int(u32),
+        Float(f64),
+    }
+    #[derive(Serialize)]
+    struct Row {
+        value: Either,
+    }
+
+    let rows = vec![
+        Row {
+            value: Either::Int(1),
+        },
+        Row {
+            value: Either::Float(1.0),
+        },
+    ];
+    let schema = Schema::new(vec![Field::new("value", DataType::UInt32, true)]);
+    let err = write_rows("type_mismatch", schema, rows, &[]).unwrap_err();
+    assert!(matches!(
+        err,
+        ParquetWriterError::TypeMismatch { column } if column == "value"
+    ));
+}
+
+#[test]
+fn parquet_writer_unsupported() {
+    assert!(matches!(
+        write_rows("unsupported_row", row_schema(), vec![1u32], &[]),
+        Err(ParquetWriterError::Unsupported(_))
+    ));
+
+    #[derive(Serialize)]
+    struct Values {
+        values: Vec<f64>,
+    }
+    let schema = Schema::new(vec![Field::new("values", DataType::Float64, true)]);
+    assert!(matches!(
+        write_rows(
+            "unsupported_field",
+            schema,
+            vec![Values { values: vec![] }],
+            &[]
+        ),
+        Err(ParquetWriterError::Unsupported(_))
+    ));
+    // Field that is not in the schema.
+    let schema = Schema::new(vec![Field::new("serial_number", DataType::UInt32, true)]);
+    let row = Row {
+        serial_number: 1,
+        x: None,
+        flag: None,
+        never: None,
+    };
+    assert!(matches!(
+        write_rows("unknown_field", schema, vec![row], &[]),
+        Err(ParquetWriterError::Unsupported(_))
+    ));
+    // Column type that can't be serialized from a primitive value.
+    let schema = Schema::new(vec![Field::new("date", DataType::Date32, true)]);
+    assert!(matches!(
+        ParquetWriter::new(Vec::new(), schema),
+        Err(ParquetWriterError::Unsupported(_))
+    ));
+}
+
+#[test]
+fn parquet_writer_row_groups() {
+    let path = std::env::temp_dir().join(format!(
+        "alpha_g_analysis_{}_row_groups.parquet",
+        std::process::id()
+    ));
+    let mut wtr = ParquetWriter::new(File::create(&path).unwrap(), row_schema())
+        .unwrap()
+        .with_statistics(["serial_number"]);
+    for (serial_number, x) in [(1, Some(1.0)), (2, None), (3, Some(3.0))] {
+        wtr.serialize(Row {
+            serial_number,
+            x,
+            flag: Some(true),
+            never: Some(0.0),
+        })
+        .unwrap();
+    }
+    wtr.flush().unwrap();
+    // Empty row groups are not written.
+    wtr.flush().unwrap();
+    for serial_number in [4, 5] {
+        wtr.serialize(Row {
+            serial_number,
+            x: None,
+            flag: None,
+            never: None,
+        })
+        .unwrap();
+    }
+    wtr.flush().unwrap();
+    wtr.serialize(Row {
+        serial_number: 6,
+        x: Some(6.0),
+        flag: Some(false),
+        never: None,
+    })
+    .unwrap();
+    wtr.finish().unwrap();
+
+    let reader = SerializedFileReader::new(File::open(&path).unwrap()).unwrap();
+    let metadata = reader.metadata();
+    let num_rows: Vec<_> = metadata
+        .row_groups()
+        .iter()
+        .map(|rg| rg.num_rows())
+        .collect();
+    assert_eq!(num_rows, [3, 2, 1]);
+    for row_group in metadata.row_groups() {
+        assert!(row_group.column(0).statistics().is_some());
+        assert!(row_group.column(1).statistics().is_none());
+    }
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn parquet_writer_declared_types() {
+    #[derive(Serialize)]
+    struct Row {
+        serial_number: u32,
+        count: Option<usize>,
+        reason: Option<&'static str>,
+    }
+
+    let schema = Schema::new(vec![
+        Field::new("serial_number", DataType::UInt32, true),
+        Field::new("count", DataType::UInt64, true),
+        Field::new("reason", DataType::Utf8, true),
+    ]);
+    let path = std::env::temp_dir().join(format!(
+        "alpha_g_analysis_{}_declared_types.parquet",
+        std::process::id()
+    ));
+    let mut wtr = ParquetWriter::new(File::create(&path).unwrap(), schema).unwrap();
+    wtr.serialize(Row {
+        serial_number: 1,
+        count: None,
+        reason: None,
+    })
+    .unwrap();
+    wtr.flush().unwrap();
+    wtr.serialize(Row {
+        serial_number: 2,
+        count: Some(5),
+        reason: None,
+    })
+    .unwrap();
+    wtr.finish().unwrap();
+
+    let reader = SerializedFileReader::new(File::open(&path).unwrap()).unwrap();
+    assert_eq!(reader.metadata().num_row_groups(), 2);
+    let batches: Vec<_> = ParquetRecordBatchReaderBuilder::try_new(File::open(&path).unwrap())
+        .unwrap()
+        .build()
+        .unwrap()
+        .map(Result::unwrap)
+        .collect();
+    let schema = batches[0].schema();
+    // Not `Float64` even if a column only has nulls.
+    assert_eq!(schema.field(1).data_type(), &DataType::UInt64);
+    assert_eq!(schema.field(2).data_type(), &DataType::Utf8);
+    let count: Vec<_> = batches
+        .iter()
+        .flat_map(|batch| batch.column(1).as_primitive::<UInt64Type>().iter())
+        .collect();
+    assert_eq!(count, [None, Some(5)]);
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn parquet_writer_field_after_first_row_group() {
+    #[derive(Serialize)]
+    struct Row {
+        serial_number: u32,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        flag: Option<bool>,
+    }
+
+    let schema = Schema::new(vec![
+        Field::new("serial_number", DataType::UInt32, true),
+        Field::new("flag", DataType::Boolean, true),
+    ]);
+    let path = std::env::temp_dir().join(format!(
+        "alpha_g_analysis_{}_field_after_first_row_group.parquet",
+        std::process::id()
+    ));
+    let mut wtr = ParquetWriter::new(File::create(&path).unwrap(), schema).unwrap();
+    wtr.serialize(Row {
+        serial_number: 1,
+        flag: None,
+    })
+    .unwrap();
+    wtr.flush().unwrap();
+    wtr.serialize(Row {
+        serial_number: 2,
+        flag: Some(true),
+    })
+    .unwrap();
+    wtr.finish().unwrap();
+
+    let flag: Vec<_> = ParquetRecordBatchReaderBuilder::try_new(File::open(&path).unwrap())
+        .unwrap()
+        .build()
+        .unwrap()
+        .map(Result::unwrap)
+        .flat_map(|batch| batch.column(1).as_boolean().iter().collect::<Vec<_>>())
+        .collect();
+    assert_eq!(flag, [None, Some(true)]);
+
+    std::fs::remove_file(path).unwrap();
+}
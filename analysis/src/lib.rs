@@ -1,9 +1,59 @@
+use crate::checksum::{ChecksumReader, ChecksumState};
+use alpha_g_detector::run::RunNumber;
 use midasio::file::{initial_timestamp_unchecked, run_number_unchecked, TryFileViewFromBytesError};
+use std::collections::HashSet;
 use std::ffi::{OsStr, OsString};
-use std::io::Read;
+use std::io::{ErrorKind, Read, Seek, SeekFrom};
+use std::ops::Deref;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 use thiserror::Error;
 
+pub use crate::aggregator::{
+    error_kind, Emission, ErrorAggregator, ErrorSampler, PacketKind, Sample, UnknownVersion,
+};
+pub use crate::anomalies::{
+    find_anomalies, find_rate_drops, Anomaly, AnomalyKind, AnomalySettings, RateDrop,
+    RateDropSettings,
+};
+pub use crate::census::{EventCensus, NoMainEventsError};
+pub use crate::checksum::{FileChecksum, Xxh64};
+pub use crate::odb::{compare_odbs, parse_odb, OdbChange, OdbFormatError, RUN_SETTINGS_JSON_PTRS};
+pub use crate::scaledown::{ScaledownCounters, ScaledownWeights};
+pub use crate::scalers::{
+    trg_packet, ScalersRow, TrgBankError, TrgScalers, TriggerRates, WindowRates,
+};
+pub use crate::tpc_signal::{NoTpcSignalError, TpcSignalCheck, TpcSignalThresholds};
+
+// Deduplicate the errors that flood the output of a run (e.g. after a firmware
+// update).
+mod aggregator;
+// Detect silent periods, rate excursions, and dead time spikes in a run.
+mod anomalies;
+// Count the events of each kind in a run.
+mod census;
+// Checksums of the input files, recorded in the provenance of the outputs.
+mod checksum;
+/// Command line helpers shared by all binaries.
+pub mod cli;
+/// Columnar output formats (e.g. Apache Parquet, with the `parquet` feature)
+/// shared by the binaries that produce tables.
+pub mod columnar;
+/// Raw MIDAS events, and the writer shared by all binaries that produce MIDAS
+/// files.
+pub mod midas;
+
+// Parse ODB dumps in any of the formats found in ALPHA-g data.
+mod odb;
+// Weight events accepted via the trigger scaledown.
+mod scaledown;
+// TRG scalers output shared by `alpha-g-trg-scalers` and `alpha-g-vertices`.
+mod scalers;
+/// Statistics helpers shared by the calibration and residual tools.
+pub mod statistics;
+// Detect runs without any TPC signal (e.g. HV off) before reconstructing them.
+mod tpc_signal;
+
 // Known ALPHA-g file extensions.
 #[derive(Clone, Copy, Debug)]
 enum Extension {
@@ -36,28 +86,114 @@ impl TryFrom<&OsStr> for Extension {
 }
 
 /// The error type for I/O operations on ALPHA-g files
+///
+/// Every variant carries the path(s) of the offending file or directory.
 #[derive(Debug, Error)]
 pub enum AlphaIOError {
     /// The error type for I/O operations of the Read, Write, Seek, and
     /// associated traits.
-    #[error("io error")]
-    IoError(#[from] std::io::Error),
+    #[error("io error in `{}`", .path.display())]
+    IoError {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
     /// Unknown file extension.
-    #[error("unknown file extension")]
-    UnknownExtension(#[from] TryExtensionFromOsStrError),
+    #[error("unknown file extension of `{}`", .path.display())]
+    UnknownExtension {
+        path: PathBuf,
+        #[source]
+        source: TryExtensionFromOsStrError,
+    },
     /// MIDAS file format error.
-    #[error("midas file format error")]
-    MidasFileFormatError(#[from] TryFileViewFromBytesError),
+    #[error("midas file format error in `{}`", .path.display())]
+    MidasFileFormatError {
+        path: PathBuf,
+        #[source]
+        source: TryFileViewFromBytesError,
+    },
     /// Bad run number.
     #[error("bad run number in `{}` (expected `{expected}`, found `{found}`)", .path.display())]
     BadRunNumber {
         path: PathBuf,
-        expected: u32,
-        found: u32,
+        expected: RunNumber,
+        found: RunNumber,
     },
     /// Duplicate files by their initial timestamp.
     #[error("duplicate initial timestamp in `{}` and `{}`", .path1.display(), .path2.display())]
     DuplicateInitialTimestamp { path1: PathBuf, path2: PathBuf },
+    /// Directory without any MIDAS files.
+    #[error("no midas files found in `{}`", .path.display())]
+    NoRunFiles { path: PathBuf },
+    /// Empty list of input files (e.g. a shell glob that didn't match
+    /// anything).
+    #[error("no input files provided")]
+    NoInputFiles,
+    /// Reading the header of a run file failed. The number of files that were
+    /// read successfully before the failure helps to tell apart e.g. a bad
+    /// path pattern (nothing read) from a disk problem (failure midway).
+    #[error("{error} (after reading `{parsed}` of `{total}` run files)")]
+    BadRunFile {
+        parsed: usize,
+        total: usize,
+        error: Box<AlphaIOError>,
+    },
+}
+
+/// Broad category of an [`AlphaIOError`].
+///
+/// Each category maps to a different exit code of the binaries (see
+/// [`cli::exit_on_error`]).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorCategory {
+    /// A file or directory doesn't exist, or a directory doesn't have any
+    /// MIDAS files.
+    NotFound,
+    /// Not enough permissions to access a file or directory.
+    PermissionDenied,
+    /// The contents (or name) of a file are not valid ALPHA-g data e.g. a
+    /// corrupted or truncated file, or files from different runs.
+    InvalidData,
+    /// Any other error.
+    Other,
+}
+
+impl AlphaIOError {
+    /// Return the [`ErrorCategory`] of the error.
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            Self::IoError { source, .. } => match source.kind() {
+                ErrorKind::NotFound => ErrorCategory::NotFound,
+                ErrorKind::PermissionDenied => ErrorCategory::PermissionDenied,
+                ErrorKind::InvalidData | ErrorKind::UnexpectedEof => ErrorCategory::InvalidData,
+                _ => ErrorCategory::Other,
+            },
+            Self::NoRunFiles { .. } | Self::NoInputFiles => ErrorCategory::NotFound,
+            Self::BadRunFile { error, .. } => error.category(),
+            Self::UnknownExtension { .. }
+            | Self::MidasFileFormatError { .. }
+            | Self::BadRunNumber { .. }
+            | Self::DuplicateInitialTimestamp { .. } => ErrorCategory::InvalidData,
+        }
+    }
+}
+
+// Attach the path to an I/O error e.g. `.map_err(io_error(path))`.
+fn io_error(path: &Path) -> impl FnOnce(std::io::Error) -> AlphaIOError + '_ {
+    move |source| AlphaIOError::IoError {
+        path: path.to_owned(),
+        source,
+    }
+}
+
+// Get the known ALPHA-g extension of a file.
+fn extension(path: &Path) -> Result<Extension, AlphaIOError> {
+    Extension::try_from(path.extension().unwrap_or_default()).map_err(|source| {
+        AlphaIOError::UnknownExtension {
+            path: path.to_owned(),
+            source,
+        }
+    })
 }
 
 /// Read the entire contents of a file (auto-detecting compression).
@@ -66,63 +202,485 @@ pub enum AlphaIOError {
 /// convenience function for using [`std::fs::read`] and handling the known
 /// compression algorithms used to store ALPHA-g data.
 pub fn read<P: AsRef<Path>>(path: P) -> Result<Vec<u8>, AlphaIOError> {
-    match Extension::try_from(path.as_ref().extension().unwrap_or_default())? {
-        Extension::Mid => Ok(std::fs::read(&path)?),
+    let path = path.as_ref();
+    match extension(path)? {
+        Extension::Mid => std::fs::read(path).map_err(io_error(path)),
         Extension::Lz4 => {
-            let file = std::fs::File::open(&path)?;
-            let mut decoder = lz4::Decoder::new(file)?;
+            let file = std::fs::File::open(path).map_err(io_error(path))?;
+            let mut decoder = lz4::Decoder::new(file).map_err(io_error(path))?;
             let mut contents = Vec::new();
-            std::io::copy(&mut decoder, &mut contents)?;
+            std::io::copy(&mut decoder, &mut contents).map_err(io_error(path))?;
             Ok(contents)
         }
     }
 }
 
-/// Sort all the files of an individual run by their initial ODB dump timestamp.
+/// Contents of a file returned by [`open`].
+#[derive(Debug)]
+pub enum FileContents {
+    /// Decompressed contents of a compressed file.
+    Owned(Vec<u8>),
+    /// Memory map of an uncompressed file.
+    Mapped(memmap2::Mmap),
+}
+
+impl Deref for FileContents {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            Self::Owned(contents) => contents,
+            Self::Mapped(mmap) => mmap,
+        }
+    }
+}
+
+/// Open a file for reading all its contents (auto-detecting compression).
 ///
-/// Returns an error if:
-/// - Not all files correspond to the same run number.
-/// - Two files have the same initial timestamp.
+/// This is the same as [`read`], except that uncompressed files are memory
+/// mapped instead of copied into memory. The peak memory usage is then roughly
+/// halved (e.g. when parsing a `.mid` file into a [`midasio::FileView`]).
 ///
-/// # Panics
+/// # Safety considerations
 ///
-/// Panics if the input iterator is empty.
-pub fn sort_run_files<P: AsRef<Path>>(
-    files: impl IntoIterator<Item = P>,
-) -> Result<(u32, Vec<P>), AlphaIOError> {
-    let mut files = files
-        .into_iter()
-        .map(|path| {
-            let mut file = std::fs::File::open(&path)?;
-            // The first 12 bytes contain both the run number and the initial
-            // timestamp.
-            let mut buffer = [0; 12];
-            match Extension::try_from(path.as_ref().extension().unwrap_or_default())? {
-                Extension::Mid => {
-                    file.read_exact(&mut buffer)?;
+/// Modifying or truncating a file while it is memory mapped is undefined
+/// behavior. This function is still safe to use with ALPHA-g data files
+/// because the analysis only ever processes files from closed runs, which are
+/// never written to again. Do not use it with files that are still being
+/// written by the DAQ; use [`read`] instead.
+pub fn open<P: AsRef<Path>>(path: P) -> Result<FileContents, AlphaIOError> {
+    let path = path.as_ref();
+    match extension(path)? {
+        Extension::Mid => {
+            let file = std::fs::File::open(path).map_err(io_error(path))?;
+            // SAFETY: See the "Safety considerations" section above.
+            let mmap = unsafe { memmap2::Mmap::map(&file) }.map_err(io_error(path))?;
+            Ok(FileContents::Mapped(mmap))
+        }
+        Extension::Lz4 => Ok(FileContents::Owned(read(path)?)),
+    }
+}
+
+/// Same as [`open`], but also return the [`FileChecksum`] of the file.
+///
+/// The checksum is computed from the bytes as they are read (or from the
+/// memory map), so the file is not read from disk a second time.
+pub fn open_with_checksum<P: AsRef<Path>>(
+    path: P,
+) -> Result<(FileContents, FileChecksum), AlphaIOError> {
+    let path = path.as_ref();
+    match extension(path)? {
+        Extension::Mid => {
+            let contents = open(path)?;
+            let mut hasher = Xxh64::default();
+            hasher.update(&contents);
+            let checksum = FileChecksum::new(path, &hasher);
+            Ok((contents, checksum))
+        }
+        Extension::Lz4 => {
+            let file = std::fs::File::open(path).map_err(io_error(path))?;
+            let reader = ChecksumReader::new(file);
+            let state = reader.state();
+            let mut decoder = lz4::Decoder::new(reader).map_err(io_error(path))?;
+            let mut contents = Vec::new();
+            std::io::copy(&mut decoder, &mut contents).map_err(io_error(path))?;
+            // Anything after the end of the compressed data is still part of
+            // the file.
+            let (mut reader, result) = decoder.finish();
+            result.map_err(io_error(path))?;
+            std::io::copy(&mut reader, &mut std::io::sink()).map_err(io_error(path))?;
+            let checksum = FileChecksum::new(path, &state.borrow());
+            Ok((FileContents::Owned(contents), checksum))
+        }
+    }
+}
+
+/// Compute the [`FileChecksum`] of any file (without decompressing it).
+pub fn file_checksum<P: AsRef<Path>>(path: P) -> Result<FileChecksum, AlphaIOError> {
+    let path = path.as_ref();
+    let mut file = std::fs::File::open(path).map_err(io_error(path))?;
+    let mut hasher = Xxh64::default();
+    std::io::copy(&mut file, &mut hasher).map_err(io_error(path))?;
+
+    Ok(FileChecksum::new(path, &hasher))
+}
+
+/// Open (see [`open_with_checksum`]) each file in a background thread while
+/// the previous files are being processed.
+///
+/// Files are returned in the same order as the input. At most `max_files`
+/// files are held in memory at the same time, including the one that was
+/// returned last. With a `max_files` of 1 (or 0) there is no background
+/// thread; each file is opened only when the next item is requested i.e. the
+/// same as calling [`open_with_checksum`] in a loop.
+///
+/// The background thread stops as soon as the returned iterator is dropped.
+pub fn prefetch_files<P>(
+    files: Vec<P>,
+    max_files: usize,
+) -> Box<dyn Iterator<Item = (P, Result<(FileContents, FileChecksum), AlphaIOError>)>>
+where
+    P: AsRef<Path> + Send + 'static,
+{
+    if max_files <= 1 {
+        return Box::new(files.into_iter().map(|file| {
+            let contents = open_with_checksum(&file);
+            (file, contents)
+        }));
+    }
+    // The background thread holds one more file while it is blocked sending
+    // it to the channel.
+    let (sender, receiver) = std::sync::mpsc::sync_channel(max_files - 2);
+    std::thread::spawn(move || {
+        for file in files {
+            let contents = open_with_checksum(&file);
+            if sender.send((file, contents)).is_err() {
+                break;
+            }
+        }
+    });
+
+    Box::new(receiver.into_iter())
+}
+
+/// Iterator over the events of a MIDAS file returned by [`event_iter`].
+pub struct EventIter {
+    path: PathBuf,
+    reader: midas::EventReader<Box<dyn Read>>,
+    checksum: ChecksumState,
+}
+
+impl EventIter {
+    /// Return the run number in the begin of run header.
+    pub fn run_number(&self) -> RunNumber {
+        RunNumber::from(self.reader.run_number())
+    }
+
+    /// Return the timestamp of the initial ODB dump.
+    pub fn initial_timestamp(&self) -> u32 {
+        self.reader.initial_timestamp()
+    }
+
+    /// Return the timestamp of the final ODB dump. This is [`None`] until all
+    /// the events have been read.
+    pub fn final_timestamp(&self) -> Option<u32> {
+        self.reader.final_timestamp()
+    }
+
+    /// Return the [`FileChecksum`] of the file. All the bytes read so far are
+    /// already hashed; only the rest of the file (if any) is read again.
+    pub fn checksum(&self) -> Result<FileChecksum, AlphaIOError> {
+        let mut hasher = self.checksum.borrow().clone();
+        let mut file = std::fs::File::open(&self.path).map_err(io_error(&self.path))?;
+        file.seek(SeekFrom::Start(hasher.total_len()))
+            .map_err(io_error(&self.path))?;
+        std::io::copy(&mut file, &mut hasher).map_err(io_error(&self.path))?;
+
+        Ok(FileChecksum::new(&self.path, &hasher))
+    }
+}
+
+impl Iterator for EventIter {
+    type Item = Result<midas::OwnedEvent, AlphaIOError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.reader
+            .next()
+            .map(|result| result.map_err(io_error(&self.path)))
+    }
+}
+
+/// Iterate over the events of a MIDAS file (auto-detecting compression).
+///
+/// Unlike [`read`] and [`open`], the file is decompressed and parsed
+/// incrementally; the peak memory usage depends on the size of the largest
+/// event instead of the size of the file. Prefer this for binaries that only
+/// need a single pass over the events of a run. The begin and end of run ODB
+/// dumps are skipped (see [`midas::EventReader`]).
+///
+/// Format errors are returned as an [`AlphaIOError::IoError`] with a
+/// [`midas::MidasFormatError`] as the inner error of its source.
+pub fn event_iter<P: AsRef<Path>>(path: P) -> Result<EventIter, AlphaIOError> {
+    let path = path.as_ref();
+    let extension = extension(path)?;
+    let file = ChecksumReader::new(std::fs::File::open(path).map_err(io_error(path))?);
+    let checksum = file.state();
+    let reader: Box<dyn Read> = match extension {
+        Extension::Mid => Box::new(std::io::BufReader::new(file)),
+        Extension::Lz4 => Box::new(lz4::Decoder::new(file).map_err(io_error(path))?),
+    };
+
+    Ok(EventIter {
+        path: path.to_owned(),
+        reader: midas::EventReader::new(reader).map_err(io_error(path))?,
+        checksum,
+    })
+}
+
+// ALPHA-g MIDAS files are named `run<RUN_NUMBER>sub<SUBRUN>` with any of the
+// known extensions (e.g. `run09570sub003.mid.lz4`).
+fn is_run_file_name(name: &str) -> bool {
+    let Some(stem) = name
+        .strip_suffix(".mid.lz4")
+        .or_else(|| name.strip_suffix(".mid"))
+    else {
+        return false;
+    };
+    let Some((run, sub)) = stem
+        .strip_prefix("run")
+        .and_then(|rest| rest.split_once("sub"))
+    else {
+        return false;
+    };
+    let is_number = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+
+    is_number(run) && is_number(sub)
+}
+
+// Add all the MIDAS files in a directory to `files`. Directories that have
+// already been `visited` are skipped (symlinks can make a directory reachable
+// more than once, or even form a loop).
+fn expand_directory(
+    dir: &Path,
+    recursive: bool,
+    visited: &mut HashSet<PathBuf>,
+    files: &mut Vec<PathBuf>,
+) -> Result<(), AlphaIOError> {
+    if !visited.insert(dir.canonicalize().map_err(io_error(dir))?) {
+        return Ok(());
+    }
+    let mut entries = std::fs::read_dir(dir)
+        .and_then(|entries| {
+            entries
+                .map(|entry| entry.map(|entry| entry.path()))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .map_err(io_error(dir))?;
+    entries.sort_unstable();
+
+    for entry in entries {
+        let Some(name) = entry.file_name().and_then(OsStr::to_str) else {
+            continue;
+        };
+        if name.starts_with('.') {
+            continue;
+        }
+        // Both `is_dir` and `is_file` follow symlinks.
+        if entry.is_dir() {
+            if recursive {
+                expand_directory(&entry, recursive, visited, files)?;
+            }
+        } else if entry.is_file() && is_run_file_name(name) {
+            files.push(entry);
+        }
+    }
+
+    Ok(())
+}
+
+/// Replace all directories in the input by the MIDAS files they contain.
+///
+/// A directory is replaced by all the files in it that follow the ALPHA-g
+/// naming pattern (e.g. `run09570sub003.mid.lz4`). Hidden files and
+/// directories (i.e. starting with `.`) are ignored, and symlinks are followed.
+/// Subdirectories are only searched if `recursive` is `true`. Any other input
+/// path is returned unchanged.
+///
+/// Returns an error if a directory doesn't contain any MIDAS files. Note that
+/// the output is not checked to belong to a single run; use
+/// [`sort_run_files`] for that.
+pub fn expand_run_files<P: AsRef<Path>>(
+    paths: impl IntoIterator<Item = P>,
+    recursive: bool,
+) -> Result<Vec<PathBuf>, AlphaIOError> {
+    let mut files = Vec::new();
+    let mut visited = HashSet::new();
+    for path in paths {
+        let path = path.as_ref();
+        if path.is_dir() {
+            // Same directory given more than once.
+            if visited.contains(&path.canonicalize().map_err(io_error(path))?) {
+                continue;
+            }
+            let previous_len = files.len();
+            expand_directory(path, recursive, &mut visited, &mut files)?;
+            if files.len() == previous_len {
+                return Err(AlphaIOError::NoRunFiles {
+                    path: path.to_owned(),
+                });
+            }
+        } else {
+            files.push(path.to_owned());
+        }
+    }
+
+    Ok(files)
+}
+
+// Read the run number and initial timestamp from the header of a MIDAS file.
+fn run_file_header(path: &Path) -> Result<(RunNumber, u32), AlphaIOError> {
+    let extension = extension(path)?;
+    let mut file = std::fs::File::open(path).map_err(io_error(path))?;
+    // The first 12 bytes contain both the run number and the initial
+    // timestamp.
+    let mut buffer = [0; 12];
+    match extension {
+        Extension::Mid => {
+            file.read_exact(&mut buffer).map_err(io_error(path))?;
+        }
+        Extension::Lz4 => {
+            let mut decoder = lz4::Decoder::new(&mut file).map_err(io_error(path))?;
+            decoder.read_exact(&mut buffer).map_err(io_error(path))?;
+        }
+    }
+
+    let midas_error = |source| AlphaIOError::MidasFileFormatError {
+        path: path.to_owned(),
+        source,
+    };
+    let run_number = RunNumber::from(run_number_unchecked(&buffer).map_err(midas_error)?);
+    let initial_timestamp = initial_timestamp_unchecked(&buffer).map_err(midas_error)?;
+
+    Ok((run_number, initial_timestamp))
+}
+
+// Number of (decompressed) bytes at the end of a file in which the end of run
+// header is searched for. Final ODB dumps of ALPHA-g runs are a few MB.
+const TAIL_WINDOW: usize = 32 << 20;
+
+// Read the final timestamp from the end of run header of a MIDAS file. Only
+// the last `TAIL_WINDOW` bytes are ever kept in memory.
+fn run_file_trailer(path: &Path) -> Result<u32, AlphaIOError> {
+    let mut file = std::fs::File::open(path).map_err(io_error(path))?;
+    let mut tail = Vec::new();
+    match extension(path)? {
+        Extension::Mid => {
+            let len = file.metadata().map_err(io_error(path))?.len();
+            let window = len.min(TAIL_WINDOW as u64);
+            file.seek(SeekFrom::End(-(window as i64)))
+                .map_err(io_error(path))?;
+            file.read_to_end(&mut tail).map_err(io_error(path))?;
+        }
+        // There is no way to jump to the end of an LZ4 frame without
+        // decompressing it. Decompress everything, but discard all the output
+        // except the last `TAIL_WINDOW` bytes.
+        Extension::Lz4 => {
+            let mut decoder = lz4::Decoder::new(file).map_err(io_error(path))?;
+            let mut chunk = vec![0; 1 << 20];
+            loop {
+                let n = decoder.read(&mut chunk).map_err(io_error(path))?;
+                if n == 0 {
+                    break;
                 }
-                Extension::Lz4 => {
-                    let mut decoder = lz4::Decoder::new(&mut file)?;
-                    decoder.read_exact(&mut buffer)?;
+                tail.extend_from_slice(&chunk[..n]);
+                // Draining only when twice the window keeps this linear.
+                if tail.len() > 2 * TAIL_WINDOW {
+                    tail.drain(..tail.len() - TAIL_WINDOW);
                 }
             }
+            if tail.len() > TAIL_WINDOW {
+                tail.drain(..tail.len() - TAIL_WINDOW);
+            }
+        }
+    }
+
+    midas::final_timestamp(&tail).ok_or_else(|| AlphaIOError::IoError {
+        path: path.to_owned(),
+        source: std::io::Error::new(
+            ErrorKind::InvalidData,
+            format!("no end of run header in the last `{TAIL_WINDOW}` bytes"),
+        ),
+    })
+}
+
+/// Return the duration of a run given all its files sorted by time (e.g. as
+/// returned by [`sort_run_files`]).
+///
+/// This is the time between the initial ODB dump of the first file and the
+/// final ODB dump of the last file. Only the header of the first file and the
+/// end of the last file are read, so this is much cheaper than iterating over
+/// all the events of a run. Note that a compressed last file still has to be
+/// decompressed (but not kept in memory).
+pub fn run_duration<P: AsRef<Path>>(files: &[P]) -> Result<Duration, AlphaIOError> {
+    let (Some(first), Some(last)) = (files.first(), files.last()) else {
+        return Err(AlphaIOError::NoInputFiles);
+    };
+    let (_, initial_timestamp) = run_file_header(first.as_ref())?;
+    let final_timestamp = run_file_trailer(last.as_ref())?;
+
+    Ok(Duration::from_secs(
+        final_timestamp.saturating_sub(initial_timestamp).into(),
+    ))
+}
+
+/// Count the events of a run (given all its files sorted by time) up to its
+/// first main event.
+///
+/// This is meant to call [`EventCensus::ensure_main_events`] before creating
+/// any output. Events are read incrementally (see [`event_iter`]), so only the
+/// first few events of a normal run are read; the files are read in full only
+/// if the run has no main events at all. Files that can't be read (or the rest
+/// of a file after a format error) are skipped; these errors are reported when
+/// the run is actually processed.
+pub fn census_until_main_event<P: AsRef<Path>>(files: &[P]) -> EventCensus {
+    let mut census = EventCensus::default();
+    for file in files {
+        let Ok(events) = event_iter(file) else {
+            continue;
+        };
+        for event in events.map_while(Result::ok) {
+            census.add(event.id);
+            if census.main > 0 {
+                return census;
+            }
+        }
+    }
 
-            let run_number = run_number_unchecked(&buffer)?;
-            let initial_timestamp = initial_timestamp_unchecked(&buffer)?;
+    census
+}
 
-            Ok((run_number, initial_timestamp, path))
-        })
-        .collect::<Result<Vec<_>, AlphaIOError>>()?;
+/// Sort all the files of an individual run by their initial ODB dump timestamp.
+///
+/// Returns an error if:
+/// - There are no input files ([`AlphaIOError::NoInputFiles`]).
+/// - The header of a file can't be read ([`AlphaIOError::BadRunFile`]).
+/// - Not all files correspond to the same run number.
+/// - Two files have the same initial timestamp.
+///
+/// If `run_number_override` is [`Some`], the run number embedded in the files
+/// is ignored (i.e. no consistency check is done) and the given run number is
+/// returned instead. This is meant to recover orphaned files with a corrupted
+/// run number; it should not be used otherwise.
+pub fn sort_run_files<P: AsRef<Path>>(
+    files: impl IntoIterator<Item = P>,
+    run_number_override: Option<RunNumber>,
+) -> Result<(RunNumber, Vec<P>), AlphaIOError> {
+    let paths: Vec<_> = files.into_iter().collect();
+    if paths.is_empty() {
+        return Err(AlphaIOError::NoInputFiles);
+    }
+    let total = paths.len();
+    let mut files = Vec::with_capacity(total);
+    for (parsed, path) in paths.into_iter().enumerate() {
+        let (run_number, initial_timestamp) =
+            run_file_header(path.as_ref()).map_err(|error| AlphaIOError::BadRunFile {
+                parsed,
+                total,
+                error: Box::new(error),
+            })?;
+        files.push((run_number, initial_timestamp, path));
+    }
 
-    assert!(!files.is_empty());
     let expected_run_number = files[0].0;
-    for (run_number, _, path) in &files {
-        if *run_number != expected_run_number {
-            return Err(AlphaIOError::BadRunNumber {
-                path: path.as_ref().to_owned(),
-                expected: expected_run_number,
-                found: *run_number,
-            });
+    if run_number_override.is_none() {
+        for (run_number, _, path) in &files {
+            if *run_number != expected_run_number {
+                return Err(AlphaIOError::BadRunNumber {
+                    path: path.as_ref().to_owned(),
+                    expected: expected_run_number,
+                    found: *run_number,
+                });
+            }
         }
     }
 
@@ -138,7 +696,53 @@ pub fn sort_run_files<P: AsRef<Path>>(
     }
 
     Ok((
-        expected_run_number,
+        run_number_override.unwrap_or(expected_run_number),
         files.into_iter().map(|(_, _, path)| path).collect(),
     ))
 }
+
+/// Same as [`sort_run_files`], but with the run numbers as bare `u32`s.
+#[deprecated(note = "use `sort_run_files` with a `RunNumber`")]
+pub fn sort_run_files_u32<P: AsRef<Path>>(
+    files: impl IntoIterator<Item = P>,
+    run_number_override: Option<u32>,
+) -> Result<(u32, Vec<P>), AlphaIOError> {
+    sort_run_files(files, run_number_override.map(RunNumber::from))
+        .map(|(run_number, files)| (run_number.into(), files))
+}
+
+/// Same as [`sort_run_files`], but files whose header can't be read (e.g. an
+/// empty or truncated file) are skipped instead of failing. The skipped files
+/// are returned (in input order) together with the error of each of them.
+///
+/// Still returns an error if none of the files can be read.
+pub fn sort_readable_run_files<P: AsRef<Path>>(
+    files: impl IntoIterator<Item = P>,
+    run_number_override: Option<RunNumber>,
+) -> Result<(RunNumber, Vec<P>, Vec<(P, AlphaIOError)>), AlphaIOError> {
+    let paths: Vec<_> = files.into_iter().collect();
+    let total = paths.len();
+    let (mut readable, mut skipped) = (Vec::new(), Vec::new());
+    for path in paths {
+        // Only the first few bytes are read, so it is fine to read the header
+        // of the readable files again when sorting them.
+        match run_file_header(path.as_ref()) {
+            Ok(_) => readable.push(path),
+            Err(error) => skipped.push((path, error)),
+        }
+    }
+    if readable.is_empty() && !skipped.is_empty() {
+        let (_, error) = skipped.swap_remove(0);
+        return Err(AlphaIOError::BadRunFile {
+            parsed: 0,
+            total,
+            error: Box::new(error),
+        });
+    }
+
+    let (run_number, files) = sort_run_files(readable, run_number_override)?;
+    Ok((run_number, files, skipped))
+}
+
+#[cfg(test)]
+mod tests;
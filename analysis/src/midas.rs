@@ -0,0 +1,555 @@
+use std::io::{Read, Write};
+use thiserror::Error;
+
+// Identifiers of the begin and end of run ODB dumps.
+const BOR_ID: u16 = 0x8000;
+const EOR_ID: u16 = 0x8001;
+// Written in the trigger mask field of the begin and end of run headers.
+const ODB_MAGIC: u16 = 0x494D;
+// Size of the header of every event (including the ODB dumps).
+const EVENT_HEADER_SIZE: usize = 16;
+
+/// The error type returned when parsing the events of a MIDAS file fails.
+#[derive(Debug, Error)]
+#[error("bad midas file format at byte `{offset}`")]
+pub struct MidasFormatError {
+    offset: usize,
+}
+
+/// Format of the data banks in an [`Event`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BankFormat {
+    /// 16-bit bank type and size.
+    Bank16,
+    /// 32-bit bank type and size.
+    Bank32,
+    /// 32-bit bank type and size, with a header padded to 64 bits.
+    Bank32A,
+}
+
+impl BankFormat {
+    fn from_flags(flags: u32) -> Option<Self> {
+        match flags {
+            1 => Some(Self::Bank16),
+            17 => Some(Self::Bank32),
+            49 => Some(Self::Bank32A),
+            _ => None,
+        }
+    }
+
+    fn flags(self) -> u32 {
+        match self {
+            Self::Bank16 => 1,
+            Self::Bank32 => 17,
+            Self::Bank32A => 49,
+        }
+    }
+
+    fn header_size(self) -> usize {
+        match self {
+            Self::Bank16 => 8,
+            Self::Bank32 => 12,
+            Self::Bank32A => 16,
+        }
+    }
+}
+
+/// MIDAS data bank.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Bank<'a> {
+    /// Name of the bank.
+    pub name: [u8; 4],
+    /// MIDAS type ID of the data (e.g. `6` for `u32`). This is kept as is; it
+    /// is not needed to copy a bank.
+    pub data_type: u32,
+    /// Raw data (without padding).
+    pub data: &'a [u8],
+}
+
+/// MIDAS event i.e. a header and its data banks.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Event<'a> {
+    /// Event ID.
+    pub id: u16,
+    /// Trigger mask.
+    pub trigger_mask: u16,
+    /// Serial number.
+    pub serial_number: u32,
+    /// Unix timestamp.
+    pub timestamp: u32,
+    /// Format of the data banks.
+    pub bank_format: BankFormat,
+    /// Data banks.
+    pub banks: Vec<Bank<'a>>,
+}
+
+// Read `N` bytes at `offset`.
+fn bytes<const N: usize>(contents: &[u8], offset: usize) -> Result<[u8; N], MidasFormatError> {
+    contents
+        .get(offset..)
+        .and_then(|rest| rest.get(..N))
+        .map(|slice| slice.try_into().unwrap())
+        .ok_or(MidasFormatError { offset })
+}
+
+fn u16_at(contents: &[u8], offset: usize) -> Result<u16, MidasFormatError> {
+    bytes(contents, offset).map(u16::from_le_bytes)
+}
+
+fn u32_at(contents: &[u8], offset: usize) -> Result<u32, MidasFormatError> {
+    bytes(contents, offset).map(u32::from_le_bytes)
+}
+
+// Banks are padded to a multiple of 8 bytes.
+fn padding(len: usize) -> usize {
+    (8 - len % 8) % 8
+}
+
+// Parse the data banks of an event with data in `contents[start..end]`.
+fn parse_banks(
+    contents: &[u8],
+    start: usize,
+    end: usize,
+) -> Result<(BankFormat, Vec<Bank>), MidasFormatError> {
+    let all_banks_size = u32_at(contents, start)? as usize;
+    let flags = u32_at(contents, start + 4)?;
+    let format = BankFormat::from_flags(flags).ok_or(MidasFormatError { offset: start + 4 })?;
+    let banks_end = start + 8 + all_banks_size;
+    if banks_end > end {
+        return Err(MidasFormatError { offset: start });
+    }
+
+    let mut banks = Vec::new();
+    let mut position = start + 8;
+    while position < banks_end {
+        let name = bytes(contents, position)?;
+        let (data_type, size) = match format {
+            BankFormat::Bank16 => (
+                u32::from(u16_at(contents, position + 4)?),
+                usize::from(u16_at(contents, position + 6)?),
+            ),
+            BankFormat::Bank32 | BankFormat::Bank32A => (
+                u32_at(contents, position + 4)?,
+                u32_at(contents, position + 8)? as usize,
+            ),
+        };
+        let data_start = position + format.header_size();
+        let data_end = data_start + size;
+        if data_end > banks_end {
+            return Err(MidasFormatError { offset: position });
+        }
+        banks.push(Bank {
+            name,
+            data_type,
+            data: &contents[data_start..data_end],
+        });
+        position = data_end + padding(size);
+    }
+
+    Ok((format, banks))
+}
+
+/// Parse all the events in the contents of a MIDAS file. The begin and end of
+/// run ODB dumps are skipped.
+///
+/// Unlike [`midasio::FileView`], this keeps all the information needed to
+/// write the events back into a new file with a [`MidasWriter`] (e.g. the
+/// data type and format of each bank).
+pub fn events(contents: &[u8]) -> Result<Vec<Event>, MidasFormatError> {
+    if u16_at(contents, 0)? != BOR_ID {
+        return Err(MidasFormatError { offset: 0 });
+    }
+    let odb_size = u32_at(contents, 12)? as usize;
+
+    let mut events = Vec::new();
+    let mut offset = EVENT_HEADER_SIZE + odb_size;
+    loop {
+        let id = u16_at(contents, offset)?;
+        if id == EOR_ID {
+            break;
+        }
+        let data_size = u32_at(contents, offset + 12)? as usize;
+        let data_offset = offset + EVENT_HEADER_SIZE;
+        if data_offset + data_size > contents.len() {
+            return Err(MidasFormatError { offset });
+        }
+        let (bank_format, banks) = parse_banks(contents, data_offset, data_offset + data_size)?;
+
+        events.push(Event {
+            id,
+            trigger_mask: u16_at(contents, offset + 2)?,
+            serial_number: u32_at(contents, offset + 4)?,
+            timestamp: u32_at(contents, offset + 8)?,
+            bank_format,
+            banks,
+        });
+        offset = data_offset + data_size;
+    }
+
+    Ok(events)
+}
+
+/// MIDAS data bank that owns its data. This is the owned version of a
+/// [`Bank`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OwnedBank {
+    /// Name of the bank.
+    pub name: [u8; 4],
+    /// MIDAS type ID of the data.
+    pub data_type: u32,
+    /// Raw data (without padding).
+    pub data: Vec<u8>,
+}
+
+/// MIDAS event that owns its data banks. This is the owned version of an
+/// [`Event`], returned by an [`EventReader`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OwnedEvent {
+    /// Event ID.
+    pub id: u16,
+    /// Trigger mask.
+    pub trigger_mask: u16,
+    /// Serial number.
+    pub serial_number: u32,
+    /// Unix timestamp.
+    pub timestamp: u32,
+    /// Format of the data banks.
+    pub bank_format: BankFormat,
+    /// Data banks.
+    pub banks: Vec<OwnedBank>,
+}
+
+impl OwnedEvent {
+    /// Borrow the event e.g. to write it with a [`MidasWriter`].
+    pub fn as_event(&self) -> Event<'_> {
+        Event {
+            id: self.id,
+            trigger_mask: self.trigger_mask,
+            serial_number: self.serial_number,
+            timestamp: self.timestamp,
+            bank_format: self.bank_format,
+            banks: self
+                .banks
+                .iter()
+                .map(|bank| Bank {
+                    name: bank.name,
+                    data_type: bank.data_type,
+                    data: &bank.data,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<&Event<'_>> for OwnedEvent {
+    fn from(event: &Event<'_>) -> Self {
+        Self {
+            id: event.id,
+            trigger_mask: event.trigger_mask,
+            serial_number: event.serial_number,
+            timestamp: event.timestamp,
+            bank_format: event.bank_format,
+            banks: event
+                .banks
+                .iter()
+                .map(|bank| OwnedBank {
+                    name: bank.name,
+                    data_type: bank.data_type,
+                    data: bank.data.to_vec(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Find the end of run header in the last bytes of a MIDAS file, and return
+/// the timestamp of the final ODB dump.
+///
+/// Returns [`None`] if `tail` doesn't contain the whole end of run header and
+/// final ODB dump (e.g. the dump is larger than `tail`, or the file is
+/// truncated).
+pub fn final_timestamp(tail: &[u8]) -> Option<u32> {
+    // The end of run header is the one followed by exactly the rest of the
+    // file. Searching backwards finds it as soon as possible, and a fake match
+    // inside the (text) ODB dump is not possible because `EOR_ID` is not ASCII.
+    (0..=tail.len().checked_sub(EVENT_HEADER_SIZE)?)
+        .rev()
+        .find(|&start| {
+            let size = tail.len() - start - EVENT_HEADER_SIZE;
+            u16_at(tail, start).ok() == Some(EOR_ID)
+                && u16_at(tail, start + 2).ok() == Some(ODB_MAGIC)
+                && u32_at(tail, start + 12).ok() == u32::try_from(size).ok()
+        })
+        .map(|start| u32_at(tail, start + 8).unwrap())
+}
+
+/// Read the events of a MIDAS file one at a time.
+///
+/// This is the streaming version of [`events`]: the contents of the file don't
+/// need to be in memory, and only a single event is read from the underlying
+/// reader at a time. The begin and end of run ODB dumps are skipped.
+///
+/// A format error is returned as an [`std::io::Error`] of kind
+/// [`InvalidData`](std::io::ErrorKind::InvalidData) with a
+/// [`MidasFormatError`] as its inner error. The iterator ends after the end of
+/// run ODB dump, or after the first error.
+#[derive(Debug)]
+pub struct EventReader<R: Read> {
+    reader: R,
+    run_number: u32,
+    initial_timestamp: u32,
+    final_timestamp: Option<u32>,
+    // Number of bytes read so far. Used to report the position of format
+    // errors the same way as `events`.
+    offset: usize,
+    done: bool,
+}
+
+impl<R: Read> EventReader<R> {
+    /// Create a new reader, and read the begin of run header. The initial ODB
+    /// dump is skipped.
+    pub fn new(reader: R) -> std::io::Result<Self> {
+        let mut event_reader = Self {
+            reader,
+            run_number: 0,
+            initial_timestamp: 0,
+            final_timestamp: None,
+            offset: 0,
+            done: false,
+        };
+        let header = event_reader.read_header()?;
+        if u16_at(&header, 0).unwrap() != BOR_ID {
+            return Err(format_error(0));
+        }
+        event_reader.run_number = u32_at(&header, 4).unwrap();
+        event_reader.initial_timestamp = u32_at(&header, 8).unwrap();
+        event_reader.skip(u32_at(&header, 12).unwrap() as usize)?;
+
+        Ok(event_reader)
+    }
+
+    /// Return the run number in the begin of run header.
+    pub fn run_number(&self) -> u32 {
+        self.run_number
+    }
+
+    /// Return the timestamp of the initial ODB dump.
+    pub fn initial_timestamp(&self) -> u32 {
+        self.initial_timestamp
+    }
+
+    /// Return the timestamp of the final ODB dump. This is [`None`] until all
+    /// the events have been read.
+    pub fn final_timestamp(&self) -> Option<u32> {
+        self.final_timestamp
+    }
+
+    // Read an event header. A file that ends in the middle of the header is a
+    // format error.
+    fn read_header(&mut self) -> std::io::Result<[u8; EVENT_HEADER_SIZE]> {
+        let mut header = [0; EVENT_HEADER_SIZE];
+        self.reader.read_exact(&mut header).map_err(|error| {
+            if error.kind() == std::io::ErrorKind::UnexpectedEof {
+                format_error(self.offset)
+            } else {
+                error
+            }
+        })?;
+        self.offset += EVENT_HEADER_SIZE;
+
+        Ok(header)
+    }
+
+    // Read up to `size` bytes (less only if the reader reaches EOF). This
+    // doesn't allocate the whole buffer upfront, so a corrupted size fails with
+    // a format error instead of running out of memory.
+    fn read_data(&mut self, size: usize) -> std::io::Result<Vec<u8>> {
+        let mut data = Vec::new();
+        (&mut self.reader)
+            .take(size as u64)
+            .read_to_end(&mut data)?;
+        self.offset += data.len();
+
+        Ok(data)
+    }
+
+    fn skip(&mut self, size: usize) -> std::io::Result<()> {
+        let skipped = std::io::copy(
+            &mut (&mut self.reader).take(size as u64),
+            &mut std::io::sink(),
+        )?;
+        self.offset += skipped as usize;
+        if skipped != size as u64 {
+            return Err(format_error(self.offset));
+        }
+
+        Ok(())
+    }
+
+    fn next_event(&mut self) -> std::io::Result<Option<OwnedEvent>> {
+        let offset = self.offset;
+        let header = self.read_header()?;
+        let id = u16_at(&header, 0).unwrap();
+        if id == EOR_ID {
+            self.final_timestamp = Some(u32_at(&header, 8).unwrap());
+            self.skip(u32_at(&header, 12).unwrap() as usize)?;
+            return Ok(None);
+        }
+        let data_size = u32_at(&header, 12).unwrap() as usize;
+        let data = self.read_data(data_size)?;
+        if data.len() != data_size {
+            return Err(format_error(offset));
+        }
+        let data_offset = offset + EVENT_HEADER_SIZE;
+        let (bank_format, banks) = parse_banks(&data, 0, data_size)
+            .map_err(|error| format_error(data_offset + error.offset))?;
+
+        Ok(Some(OwnedEvent::from(&Event {
+            id,
+            trigger_mask: u16_at(&header, 2).unwrap(),
+            serial_number: u32_at(&header, 4).unwrap(),
+            timestamp: u32_at(&header, 8).unwrap(),
+            bank_format,
+            banks,
+        })))
+    }
+}
+
+impl<R: Read> Iterator for EventReader<R> {
+    type Item = std::io::Result<OwnedEvent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let result = self.next_event();
+        if !matches!(result, Ok(Some(_))) {
+            self.done = true;
+        }
+
+        result.transpose()
+    }
+}
+
+fn format_error(offset: usize) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, MidasFormatError { offset })
+}
+
+/// Write a MIDAS file.
+///
+/// This is the write path shared by all the binaries that produce MIDAS files
+/// (e.g. a subset of the events of a run). Events are written in the order in
+/// which they are given, and the file is only complete after calling
+/// [`MidasWriter::finish`].
+#[derive(Debug)]
+pub struct MidasWriter<W: Write> {
+    writer: W,
+    run_number: u32,
+}
+
+impl<W: Write> MidasWriter<W> {
+    /// Create a new writer, and write the begin of run header with the initial
+    /// ODB dump.
+    pub fn new(
+        mut writer: W,
+        run_number: u32,
+        initial_timestamp: u32,
+        initial_odb: &[u8],
+    ) -> std::io::Result<Self> {
+        write_odb(
+            &mut writer,
+            BOR_ID,
+            run_number,
+            initial_timestamp,
+            initial_odb,
+        )?;
+
+        Ok(Self { writer, run_number })
+    }
+
+    /// Write an event.
+    pub fn write_event(&mut self, event: &Event) -> std::io::Result<()> {
+        let format = event.bank_format;
+        let all_banks_size: usize = event
+            .banks
+            .iter()
+            .map(|bank| format.header_size() + bank.data.len() + padding(bank.data.len()))
+            .sum();
+        let data_size = 8 + all_banks_size;
+
+        let w = &mut self.writer;
+        w.write_all(&event.id.to_le_bytes())?;
+        w.write_all(&event.trigger_mask.to_le_bytes())?;
+        w.write_all(&event.serial_number.to_le_bytes())?;
+        w.write_all(&event.timestamp.to_le_bytes())?;
+        w.write_all(&size_u32(data_size)?.to_le_bytes())?;
+        w.write_all(&size_u32(all_banks_size)?.to_le_bytes())?;
+        w.write_all(&format.flags().to_le_bytes())?;
+        for bank in &event.banks {
+            w.write_all(&bank.name)?;
+            match format {
+                BankFormat::Bank16 => {
+                    let too_large = || {
+                        std::io::Error::new(
+                            std::io::ErrorKind::InvalidInput,
+                            "bank too large for 16-bit format",
+                        )
+                    };
+                    let data_type = u16::try_from(bank.data_type).map_err(|_| too_large())?;
+                    let size = u16::try_from(bank.data.len()).map_err(|_| too_large())?;
+                    w.write_all(&data_type.to_le_bytes())?;
+                    w.write_all(&size.to_le_bytes())?;
+                }
+                BankFormat::Bank32 | BankFormat::Bank32A => {
+                    w.write_all(&bank.data_type.to_le_bytes())?;
+                    w.write_all(&size_u32(bank.data.len())?.to_le_bytes())?;
+                    if format == BankFormat::Bank32A {
+                        w.write_all(&[0; 4])?;
+                    }
+                }
+            }
+            w.write_all(bank.data)?;
+            w.write_all(&[0; 8][..padding(bank.data.len())])?;
+        }
+
+        Ok(())
+    }
+
+    /// Write the end of run header with the final ODB dump, and return the
+    /// underlying writer.
+    pub fn finish(mut self, final_timestamp: u32, final_odb: &[u8]) -> std::io::Result<W> {
+        write_odb(
+            &mut self.writer,
+            EOR_ID,
+            self.run_number,
+            final_timestamp,
+            final_odb,
+        )?;
+        self.writer.flush()?;
+
+        Ok(self.writer)
+    }
+}
+
+fn size_u32(size: usize) -> std::io::Result<u32> {
+    u32::try_from(size)
+        .map_err(|_| std::io::Error::new(std::io::ErrorKind::InvalidInput, "event too large"))
+}
+
+fn write_odb<W: Write>(
+    writer: &mut W,
+    id: u16,
+    run_number: u32,
+    timestamp: u32,
+    odb: &[u8],
+) -> std::io::Result<()> {
+    writer.write_all(&id.to_le_bytes())?;
+    writer.write_all(&ODB_MAGIC.to_le_bytes())?;
+    writer.write_all(&run_number.to_le_bytes())?;
+    writer.write_all(&timestamp.to_le_bytes())?;
+    writer.write_all(&size_u32(odb.len())?.to_le_bytes())?;
+    writer.write_all(odb)
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,269 @@
+use super::*;
+
+const INITIAL_ODB: &[u8] = b"{\"initial\": true}";
+const FINAL_ODB: &[u8] = b"{\"final\": true}";
+
+fn test_events(bank_format: BankFormat) -> Vec<Event<'static>> {
+    vec![
+        Event {
+            id: 1,
+            trigger_mask: 0,
+            serial_number: 0,
+            timestamp: 100,
+            bank_format,
+            banks: vec![
+                Bank {
+                    name: *b"ATAT",
+                    data_type: 6,
+                    data: &[1, 2, 3, 4, 5, 6, 7, 8],
+                },
+                // Needs padding.
+                Bank {
+                    name: *b"B09A",
+                    data_type: 1,
+                    data: &[9, 10, 11],
+                },
+                Bank {
+                    name: *b"PC00",
+                    data_type: 1,
+                    data: &[],
+                },
+            ],
+        },
+        Event {
+            id: 4,
+            trigger_mask: 7,
+            serial_number: 1,
+            timestamp: 101,
+            bank_format,
+            banks: Vec::new(),
+        },
+    ]
+}
+
+fn write_file(events: &[Event]) -> Vec<u8> {
+    let mut writer = MidasWriter::new(Vec::new(), 11186, 100, INITIAL_ODB).unwrap();
+    for event in events {
+        writer.write_event(event).unwrap();
+    }
+    writer.finish(102, FINAL_ODB).unwrap()
+}
+
+#[test]
+fn midas_writer_round_trip() {
+    for format in [BankFormat::Bank16, BankFormat::Bank32, BankFormat::Bank32A] {
+        let expected = test_events(format);
+        let contents = write_file(&expected);
+
+        assert_eq!(events(&contents).unwrap(), expected);
+    }
+}
+
+#[test]
+fn midas_writer_midasio_compatible() {
+    for format in [BankFormat::Bank16, BankFormat::Bank32, BankFormat::Bank32A] {
+        let expected = test_events(format);
+        let contents = write_file(&expected);
+        let file_view = midasio::FileView::try_from(&contents[..]).unwrap();
+
+        assert_eq!(file_view.initial_timestamp(), 100);
+        assert_eq!(file_view.final_timestamp(), 102);
+        assert_eq!(file_view.initial_odb(), INITIAL_ODB);
+        assert_eq!(file_view.final_odb(), FINAL_ODB);
+        let found = file_view
+            .iter()
+            .map(|event| {
+                (
+                    event.id(),
+                    event.serial_number(),
+                    event
+                        .into_iter()
+                        .map(|bank| (bank.name().to_string(), bank.data_slice().to_vec()))
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .collect::<Vec<_>>();
+        let expected = expected
+            .iter()
+            .map(|event| {
+                (
+                    event.id,
+                    event.serial_number,
+                    event
+                        .banks
+                        .iter()
+                        .map(|bank| {
+                            (
+                                String::from_utf8(bank.name.to_vec()).unwrap(),
+                                bank.data.to_vec(),
+                            )
+                        })
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(found, expected);
+    }
+}
+
+#[test]
+fn midas_writer_bank_too_large() {
+    let data = vec![0; usize::from(u16::MAX) + 1];
+    let event = Event {
+        id: 1,
+        trigger_mask: 0,
+        serial_number: 0,
+        timestamp: 100,
+        bank_format: BankFormat::Bank16,
+        banks: vec![Bank {
+            name: *b"PC00",
+            data_type: 1,
+            data: &data,
+        }],
+    };
+    let mut writer = MidasWriter::new(Vec::new(), 11186, 100, INITIAL_ODB).unwrap();
+
+    assert!(writer.write_event(&event).is_err());
+}
+
+#[test]
+fn events_truncated_file() {
+    let contents = write_file(&test_events(BankFormat::Bank32));
+
+    for len in [0, 10, contents.len() / 2, contents.len() - 1] {
+        assert!(events(&contents[..len]).is_err());
+    }
+}
+
+#[test]
+fn event_reader_same_as_events() {
+    for format in [BankFormat::Bank16, BankFormat::Bank32, BankFormat::Bank32A] {
+        let contents = write_file(&test_events(format));
+        let expected: Vec<_> = events(&contents)
+            .unwrap()
+            .iter()
+            .map(OwnedEvent::from)
+            .collect();
+
+        let mut reader = EventReader::new(&contents[..]).unwrap();
+        assert_eq!(reader.run_number(), 11186);
+        assert_eq!(reader.initial_timestamp(), 100);
+        assert_eq!(reader.final_timestamp(), None);
+
+        let found = reader.by_ref().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(found, expected);
+        assert_eq!(reader.final_timestamp(), Some(102));
+        assert!(reader.next().is_none());
+    }
+}
+
+#[test]
+fn owned_event_round_trip() {
+    let expected = test_events(BankFormat::Bank32);
+    let owned: Vec<_> = expected.iter().map(OwnedEvent::from).collect();
+    let borrowed: Vec<_> = owned.iter().map(OwnedEvent::as_event).collect();
+
+    assert_eq!(borrowed, expected);
+}
+
+#[test]
+fn event_reader_truncated_file() {
+    let contents = write_file(&test_events(BankFormat::Bank32));
+
+    for len in [0, 10, contents.len() / 2, contents.len() - 1] {
+        let result = EventReader::new(&contents[..len])
+            .and_then(|reader| reader.collect::<Result<Vec<_>, _>>());
+        let error = result.unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+        assert!(error.into_inner().unwrap().is::<MidasFormatError>());
+    }
+}
+
+// MIDAS file with `count` copies of the same event. The contents are generated
+// as they are read, so the file never has to be in memory. The number of bytes
+// read so far is shared with the test.
+struct SyntheticFile {
+    bor: Vec<u8>,
+    event: Vec<u8>,
+    eor: Vec<u8>,
+    count: usize,
+    position: std::rc::Rc<std::cell::Cell<usize>>,
+}
+
+impl Read for SyntheticFile {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let position = self.position.get();
+        let eor_start = self.bor.len() + self.count * self.event.len();
+        let (segment, offset) = if position < self.bor.len() {
+            (&self.bor, position)
+        } else if position < eor_start {
+            (&self.event, (position - self.bor.len()) % self.event.len())
+        } else if position < eor_start + self.eor.len() {
+            (&self.eor, position - eor_start)
+        } else {
+            return Ok(0);
+        };
+        let n = buf.len().min(segment.len() - offset);
+        buf[..n].copy_from_slice(&segment[offset..][..n]);
+        self.position.set(position + n);
+
+        Ok(n)
+    }
+}
+
+#[test]
+fn event_reader_large_file() {
+    // 2048 events of 1 MiB each i.e. a 2 GiB subrun.
+    let data = vec![0xAB; 1 << 20];
+    let event = Event {
+        id: 1,
+        trigger_mask: 0,
+        serial_number: 0,
+        timestamp: 100,
+        bank_format: BankFormat::Bank32,
+        banks: vec![Bank {
+            name: *b"PC00",
+            data_type: 1,
+            data: &data,
+        }],
+    };
+    let contents = write_file(std::slice::from_ref(&event));
+    let bor_len = EVENT_HEADER_SIZE + INITIAL_ODB.len();
+    let eor_len = EVENT_HEADER_SIZE + FINAL_ODB.len();
+    let position = std::rc::Rc::new(std::cell::Cell::new(0));
+    let file = SyntheticFile {
+        bor: contents[..bor_len].to_vec(),
+        event: contents[bor_len..contents.len() - eor_len].to_vec(),
+        eor: contents[contents.len() - eor_len..].to_vec(),
+        count: 2048,
+        position: position.clone(),
+    };
+    let event_len = file.event.len();
+
+    let mut reader = EventReader::new(file).unwrap();
+    let mut count = 0;
+    for found in reader.by_ref() {
+        let found = found.unwrap();
+        assert_eq!(found.banks[0].data.len(), data.len());
+        count += 1;
+        // Nothing is read past the current event, so memory usage is bounded
+        // by the size of a single event regardless of the size of the file.
+        assert_eq!(position.get(), bor_len + count * event_len);
+    }
+    assert_eq!(count, 2048);
+    assert_eq!(reader.final_timestamp(), Some(102));
+}
+
+#[test]
+fn final_timestamp_in_tail() {
+    let contents = write_file(&test_events(BankFormat::Bank32));
+    let odb_start = contents.len() - FINAL_ODB.len() - EVENT_HEADER_SIZE;
+
+    assert_eq!(final_timestamp(&contents), Some(102));
+    assert_eq!(final_timestamp(&contents[odb_start..]), Some(102));
+    // Not enough bytes for the whole header.
+    assert_eq!(final_timestamp(&contents[odb_start + 1..]), None);
+    // Truncated final ODB dump.
+    assert_eq!(final_timestamp(&contents[..contents.len() - 1]), None);
+    assert_eq!(final_timestamp(&[]), None);
+}
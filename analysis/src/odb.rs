@@ -0,0 +1,407 @@
+use alpha_g_detector::midas::{
+    ADC16_SUPPRESSION_THRESHOLD_JSON_PTR, ADC32_SUPPRESSION_ENABLE_JSON_PTR,
+    ADC32_SUPPRESSION_THRESHOLD_JSON_PTR, PWB_FORCE_CHANNELS_JSON_PTR,
+    PWB_SUPPRESSION_THRESHOLD_JSON_PTR, TRIGGER_SOURCES_JSON_PTR,
+};
+use serde_json::{Map, Number, Value};
+use std::fmt;
+use thiserror::Error;
+
+// Nesting depth of XML elements. Real ODB dumps are not even close to this,
+// it is just to avoid a stack overflow with corrupted files.
+const MAX_XML_DEPTH: usize = 64;
+
+/// The error type returned when parsing an ODB dump fails.
+#[derive(Debug, Error)]
+pub enum OdbFormatError {
+    /// The ODB dump looks like JSON, but it is not valid JSON.
+    #[error("bad json odb dump")]
+    BadJson(#[from] serde_json::Error),
+    /// The ODB dump looks like XML, but it is not valid XML.
+    #[error("bad xml odb dump at byte `{position}`")]
+    BadXml { position: usize },
+    /// Unknown element in an XML ODB dump.
+    #[error("unknown element `{name}` in xml odb dump")]
+    UnknownXmlElement { name: String },
+    /// An XML element is missing a required attribute.
+    #[error("missing attribute `{attribute}` in xml element `{element}`")]
+    MissingXmlAttribute { element: String, attribute: String },
+    /// The ODB dump is neither JSON nor XML.
+    #[error("unknown odb dump format")]
+    UnknownFormat,
+}
+
+/// Parse an ODB dump (e.g. [`midasio::FileView::initial_odb`]) into a JSON
+/// value.
+///
+/// Both the JSON and XML formats are supported. XML dumps (e.g. from some
+/// 2021 runs) are converted to the equivalent JSON value, such that the same
+/// JSON pointers (e.g.
+/// [`alpha_g_detector::midas::PULSER_ENABLE_JSON_PTR`]) can be used
+/// regardless of the original format. Any trailing data after the ODB dump is
+/// ignored.
+///
+/// Note that the XML support is limited to what MIDAS writes in ODB dumps
+/// (directories, keys, and key arrays).
+pub fn parse_odb(bytes: &[u8]) -> Result<Value, OdbFormatError> {
+    match bytes.iter().find(|b| !b.is_ascii_whitespace()) {
+        Some(b'{') => {
+            match serde_json::Deserializer::from_slice(bytes)
+                .into_iter::<Value>()
+                .next()
+            {
+                Some(value) => Ok(value?),
+                None => unreachable!(),
+            }
+        }
+        Some(b'<') => {
+            let input = String::from_utf8_lossy(bytes);
+            let mut parser = XmlParser {
+                input: &input,
+                position: 0,
+            };
+            parser.skip_misc()?;
+            let root = parser.element(0)?;
+            xml_to_json(&root)
+        }
+        _ => Err(OdbFormatError::UnknownFormat),
+    }
+}
+
+/// JSON pointers to the ODB settings that the reconstruction (e.g. the
+/// calibration) assumes to be constant during a run.
+pub const RUN_SETTINGS_JSON_PTRS: &[&str] = &[
+    ADC16_SUPPRESSION_THRESHOLD_JSON_PTR,
+    ADC32_SUPPRESSION_ENABLE_JSON_PTR,
+    ADC32_SUPPRESSION_THRESHOLD_JSON_PTR,
+    PWB_FORCE_CHANNELS_JSON_PTR,
+    PWB_SUPPRESSION_THRESHOLD_JSON_PTR,
+    TRIGGER_SOURCES_JSON_PTR,
+];
+
+/// ODB setting that has a different value in two ODB dumps.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OdbChange {
+    /// JSON pointer that identifies the setting.
+    pub pointer: String,
+    /// Value in the first ODB dump ([`None`] if missing).
+    pub before: Option<Value>,
+    /// Value in the second ODB dump ([`None`] if missing).
+    pub after: Option<Value>,
+}
+
+impl fmt::Display for OdbChange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let value = |value: &Option<Value>| match value {
+            Some(value) => format!("`{value}`"),
+            None => String::from("missing"),
+        };
+        write!(
+            f,
+            "`{}` changed from {} to {}",
+            self.pointer,
+            value(&self.before),
+            value(&self.after)
+        )
+    }
+}
+
+/// Compare the value of a set of ODB settings (identified by their JSON
+/// pointer) between two parsed ODB dumps (see [`parse_odb`]).
+///
+/// Returns all the settings that differ, in the same order as the input
+/// pointers. A setting that is missing in both dumps is not a change.
+///
+/// # Examples
+///
+/// ```
+/// use alpha_g_analysis::compare_odbs;
+/// use serde_json::json;
+///
+/// let initial = json!({"Settings": {"threshold": 1100, "enable": true}});
+/// let last = json!({"Settings": {"threshold": 1300, "enable": true}});
+///
+/// let changes = compare_odbs(&initial, &last, ["/Settings/threshold", "/Settings/enable"]);
+/// assert_eq!(changes.len(), 1);
+/// assert_eq!(changes[0].pointer, "/Settings/threshold");
+/// assert_eq!(changes[0].after, Some(json!(1300)));
+/// ```
+pub fn compare_odbs<S: AsRef<str>>(
+    before: &Value,
+    after: &Value,
+    pointers: impl IntoIterator<Item = S>,
+) -> Vec<OdbChange> {
+    pointers
+        .into_iter()
+        .filter_map(|pointer| {
+            let pointer = pointer.as_ref();
+            let (before, after) = (before.pointer(pointer), after.pointer(pointer));
+            (before != after).then(|| OdbChange {
+                pointer: pointer.to_string(),
+                before: before.cloned(),
+                after: after.cloned(),
+            })
+        })
+        .collect()
+}
+
+#[derive(Debug)]
+struct XmlElement {
+    name: String,
+    attributes: Vec<(String, String)>,
+    children: Vec<XmlElement>,
+    text: String,
+}
+
+impl XmlElement {
+    fn attribute(&self, attribute: &str) -> Result<&str, OdbFormatError> {
+        self.attributes
+            .iter()
+            .find(|(name, _)| name == attribute)
+            .map(|(_, value)| value.as_str())
+            .ok_or_else(|| OdbFormatError::MissingXmlAttribute {
+                element: self.name.clone(),
+                attribute: attribute.to_string(),
+            })
+    }
+}
+
+// Just enough of an XML parser to read MIDAS ODB dumps: elements, attributes,
+// text, comments, and processing instructions. No DTDs, CDATA, namespaces,
+// etc.
+struct XmlParser<'a> {
+    input: &'a str,
+    position: usize,
+}
+
+impl<'a> XmlParser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.input[self.position..]
+    }
+
+    fn error(&self) -> OdbFormatError {
+        OdbFormatError::BadXml {
+            position: self.position,
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.position += rest.len() - rest.trim_start().len();
+    }
+    // Advance until right after the next occurrence of `pattern`.
+    fn skip_past(&mut self, pattern: &str) -> Result<(), OdbFormatError> {
+        match self.rest().find(pattern) {
+            Some(index) => {
+                self.position += index + pattern.len();
+                Ok(())
+            }
+            None => Err(self.error()),
+        }
+    }
+    // Skip whitespace, comments, processing instructions, and declarations.
+    fn skip_misc(&mut self) -> Result<(), OdbFormatError> {
+        loop {
+            self.skip_whitespace();
+            if self.rest().starts_with("<?") {
+                self.skip_past("?>")?;
+            } else if self.rest().starts_with("<!--") {
+                self.skip_past("-->")?;
+            } else if self.rest().starts_with("<!") {
+                self.skip_past(">")?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn expect(&mut self, pattern: &str) -> Result<(), OdbFormatError> {
+        if self.rest().starts_with(pattern) {
+            self.position += pattern.len();
+            Ok(())
+        } else {
+            Err(self.error())
+        }
+    }
+
+    fn name(&mut self) -> Result<String, OdbFormatError> {
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '-' | ':' | '.')))
+            .unwrap_or(rest.len());
+        if len == 0 {
+            return Err(self.error());
+        }
+        self.position += len;
+        Ok(rest[..len].to_string())
+    }
+
+    fn attribute_value(&mut self) -> Result<String, OdbFormatError> {
+        let quote = match self.rest().chars().next() {
+            Some(c @ ('"' | '\'')) => c,
+            _ => return Err(self.error()),
+        };
+        self.position += 1;
+        let Some(len) = self.rest().find(quote) else {
+            return Err(self.error());
+        };
+        let value = unescape(&self.rest()[..len]);
+        self.position += len + 1;
+        Ok(value)
+    }
+
+    fn element(&mut self, depth: usize) -> Result<XmlElement, OdbFormatError> {
+        if depth > MAX_XML_DEPTH {
+            return Err(self.error());
+        }
+        self.expect("<")?;
+        let mut element = XmlElement {
+            name: self.name()?,
+            attributes: Vec::new(),
+            children: Vec::new(),
+            text: String::new(),
+        };
+        loop {
+            self.skip_whitespace();
+            if self.rest().starts_with("/>") {
+                self.position += 2;
+                return Ok(element);
+            } else if self.rest().starts_with('>') {
+                self.position += 1;
+                break;
+            }
+            let name = self.name()?;
+            self.skip_whitespace();
+            self.expect("=")?;
+            self.skip_whitespace();
+            let value = self.attribute_value()?;
+            element.attributes.push((name, value));
+        }
+
+        loop {
+            if self.rest().starts_with("</") {
+                self.position += 2;
+                if self.name()? != element.name {
+                    return Err(self.error());
+                }
+                self.skip_whitespace();
+                self.expect(">")?;
+                return Ok(element);
+            } else if self.rest().starts_with("<!--") {
+                self.skip_past("-->")?;
+            } else if self.rest().starts_with('<') {
+                element.children.push(self.element(depth + 1)?);
+            } else {
+                let Some(len) = self.rest().find('<') else {
+                    return Err(self.error());
+                };
+                element.text.push_str(&unescape(&self.rest()[..len]));
+                self.position += len;
+            }
+        }
+    }
+}
+
+// Replace the predefined XML entities and character references. Anything that
+// is not recognized is left as is.
+fn unescape(input: &str) -> String {
+    let mut output = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(index) = rest.find('&') {
+        output.push_str(&rest[..index]);
+        rest = &rest[index..];
+        let replacement = rest.find(';').and_then(|end| {
+            let c = match &rest[1..end] {
+                "amp" => '&',
+                "lt" => '<',
+                "gt" => '>',
+                "quot" => '"',
+                "apos" => '\'',
+                reference => {
+                    let code = if let Some(hex) = reference.strip_prefix("#x") {
+                        u32::from_str_radix(hex, 16).ok()?
+                    } else {
+                        reference.strip_prefix('#')?.parse().ok()?
+                    };
+                    char::from_u32(code)?
+                }
+            };
+            Some((c, end + 1))
+        });
+        match replacement {
+            Some((c, len)) => {
+                output.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                output.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    output.push_str(rest);
+
+    output
+}
+
+fn xml_to_json(element: &XmlElement) -> Result<Value, OdbFormatError> {
+    match element.name.as_str() {
+        "odb" | "dir" => {
+            let mut map = Map::new();
+            for child in &element.children {
+                map.insert(child.attribute("name")?.to_string(), xml_to_json(child)?);
+            }
+            Ok(Value::Object(map))
+        }
+        "key" => Ok(typed_value(element.attribute("type")?, &element.text)),
+        "keyarray" => {
+            let type_name = element.attribute("type")?;
+            element
+                .children
+                .iter()
+                .map(|child| match child.name.as_str() {
+                    "value" => Ok(typed_value(type_name, &child.text)),
+                    _ => Err(OdbFormatError::UnknownXmlElement {
+                        name: child.name.clone(),
+                    }),
+                })
+                .collect::<Result<Vec<_>, _>>()
+                .map(Value::Array)
+        }
+        _ => Err(OdbFormatError::UnknownXmlElement {
+            name: element.name.clone(),
+        }),
+    }
+}
+
+// Convert the text of an ODB key to a JSON value given its MIDAS type. If the
+// text doesn't match the expected type, it is kept as a string rather than
+// failing (these are not needed by most tools anyway).
+fn typed_value(type_name: &str, text: &str) -> Value {
+    let trimmed = text.trim();
+    let value = match type_name {
+        "BOOL" => match trimmed {
+            "y" | "1" | "true" => Some(Value::Bool(true)),
+            "n" | "0" | "false" => Some(Value::Bool(false)),
+            _ => None,
+        },
+        "BYTE" | "SBYTE" | "CHAR" | "WORD" | "SHORT" | "DWORD" | "INT" | "INT8" | "UINT8"
+        | "INT16" | "UINT16" | "INT32" | "UINT32" | "INT64" | "UINT64" | "BITFIELD" => trimmed
+            .parse::<i64>()
+            .map(Number::from)
+            .or_else(|_| trimmed.parse::<u64>().map(Number::from))
+            .ok()
+            .map(Value::Number),
+        "FLOAT" | "DOUBLE" => trimmed
+            .parse::<f64>()
+            .ok()
+            .and_then(Number::from_f64)
+            .map(Value::Number),
+        _ => None,
+    };
+
+    value.unwrap_or_else(|| Value::String(text.to_string()))
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,144 @@
+use super::*;
+use alpha_g_detector::midas::{
+    ADC16_SUPPRESSION_THRESHOLD_JSON_PTR, ADC32_SUPPRESSION_ENABLE_JSON_PTR,
+    PULSER_ENABLE_JSON_PTR, PWB_FORCE_CHANNELS_JSON_PTR, PWB_SUPPRESSION_THRESHOLD_JSON_PTR,
+    TRIGGER_SOURCES_JSON_PTR,
+};
+use serde_json::json;
+
+const JSON_DUMP: &[u8] = include_bytes!("../../data/odb/odb_dump.json");
+const XML_DUMP: &[u8] = include_bytes!("../../data/odb/odb_dump.xml");
+const CORRUPTED_DUMP: &[u8] = include_bytes!("../../data/odb/odb_dump_corrupted.xml");
+// Same as `JSON_DUMP`, but with a different PWB suppression threshold.
+const FINAL_JSON_DUMP: &[u8] = include_bytes!("../../data/odb/odb_dump_final.json");
+
+#[test]
+fn parse_odb_json() {
+    let odb = parse_odb(JSON_DUMP).unwrap();
+
+    assert_eq!(odb.pointer(PULSER_ENABLE_JSON_PTR), Some(&json!(true)));
+    assert_eq!(
+        odb.pointer(PWB_FORCE_CHANNELS_JSON_PTR),
+        Some(&json!(false))
+    );
+    assert_eq!(
+        odb.pointer(ADC16_SUPPRESSION_THRESHOLD_JSON_PTR),
+        Some(&json!(1500))
+    );
+}
+
+#[test]
+fn parse_odb_json_trailing_garbage() {
+    let mut bytes = JSON_DUMP.to_vec();
+    bytes.extend_from_slice(b"\0\0garbage");
+
+    assert_eq!(parse_odb(&bytes).unwrap(), parse_odb(JSON_DUMP).unwrap());
+}
+
+#[test]
+fn parse_odb_xml() {
+    let odb = parse_odb(XML_DUMP).unwrap();
+
+    assert_eq!(odb.pointer(PULSER_ENABLE_JSON_PTR), Some(&json!(true)));
+    assert_eq!(
+        odb.pointer(ADC32_SUPPRESSION_ENABLE_JSON_PTR),
+        Some(&json!([true, false, true]))
+    );
+    assert_eq!(
+        odb.pointer(TRIGGER_SOURCES_JSON_PTR),
+        Some(&json!({"TrigPulser": false, "TrigAdc16Grand": true}))
+    );
+    assert_eq!(odb.pointer("/Experiment/Name"), Some(&json!("agdaq")));
+}
+
+#[test]
+fn parse_odb_xml_equals_json() {
+    assert_eq!(parse_odb(XML_DUMP).unwrap(), parse_odb(JSON_DUMP).unwrap());
+}
+
+#[test]
+fn parse_odb_corrupted() {
+    assert!(matches!(
+        parse_odb(CORRUPTED_DUMP),
+        Err(OdbFormatError::BadXml { .. })
+    ));
+    assert!(matches!(
+        parse_odb(&JSON_DUMP[..100]),
+        Err(OdbFormatError::BadJson(_))
+    ));
+    assert!(matches!(
+        parse_odb(b"\0\0\0\0"),
+        Err(OdbFormatError::UnknownFormat)
+    ));
+    assert!(matches!(parse_odb(b""), Err(OdbFormatError::UnknownFormat)));
+}
+
+#[test]
+fn parse_odb_xml_unknown_element() {
+    let bytes = br#"<odb root="/"><dir name="a"><link name="b">c</link></dir></odb>"#;
+
+    match parse_odb(bytes) {
+        Err(OdbFormatError::UnknownXmlElement { name }) => assert_eq!(name, "link"),
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn parse_odb_xml_escaped_text() {
+    let bytes =
+        br#"<odb><key name="a &amp; b" type="STRING">&lt;x&gt; &#65;&#x42; &foo;</key></odb>"#;
+
+    assert_eq!(parse_odb(bytes).unwrap(), json!({"a & b": "<x> AB &foo;"}));
+}
+
+#[test]
+fn compare_odbs_same() {
+    let odb = parse_odb(JSON_DUMP).unwrap();
+
+    assert!(compare_odbs(&odb, &odb, RUN_SETTINGS_JSON_PTRS).is_empty());
+    // The XML dump has the same content as the JSON dump.
+    let xml_odb = parse_odb(XML_DUMP).unwrap();
+    assert!(compare_odbs(&xml_odb, &odb, RUN_SETTINGS_JSON_PTRS).is_empty());
+}
+
+#[test]
+fn compare_odbs_changed_threshold() {
+    let initial = parse_odb(JSON_DUMP).unwrap();
+    let last = parse_odb(FINAL_JSON_DUMP).unwrap();
+
+    let changes = compare_odbs(&initial, &last, RUN_SETTINGS_JSON_PTRS);
+    assert_eq!(
+        changes,
+        [OdbChange {
+            pointer: String::from(PWB_SUPPRESSION_THRESHOLD_JSON_PTR),
+            before: Some(json!(1100)),
+            after: Some(json!(1300)),
+        }]
+    );
+    assert_eq!(
+        changes[0].to_string(),
+        "`/Equipment/CTRL/Settings/PWB/ch_threshold` changed from `1100` to `1300`"
+    );
+    // Only the requested settings are compared.
+    assert!(compare_odbs(&initial, &last, [PULSER_ENABLE_JSON_PTR]).is_empty());
+}
+
+#[test]
+fn compare_odbs_missing_setting() {
+    let initial = parse_odb(JSON_DUMP).unwrap();
+    let last = json!({});
+
+    let changes = compare_odbs(&initial, &last, [PWB_FORCE_CHANNELS_JSON_PTR, "/Not/A/Key"]);
+    assert_eq!(
+        changes,
+        [OdbChange {
+            pointer: String::from(PWB_FORCE_CHANNELS_JSON_PTR),
+            before: Some(json!(false)),
+            after: None,
+        }]
+    );
+    assert_eq!(
+        changes[0].to_string(),
+        "`/Equipment/CTRL/Settings/PWB/ch_force` changed from `false` to missing"
+    );
+}
@@ -0,0 +1,101 @@
+use alpha_g_detector::trigger::TrgPacket;
+
+/// Trigger counters needed to determine the weight of an event.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ScaledownCounters {
+    /// Number of signals that passed the drift veto i.e. the signals that
+    /// reach the scaledown.
+    pub drift_veto: u32,
+    /// Number of signals that passed the scaledown.
+    pub scaledown: u32,
+}
+
+impl ScaledownCounters {
+    /// Return the counters from a TRG packet. Return [`None`] if the packet
+    /// version does not have these counters.
+    pub fn from_trg_packet(packet: &TrgPacket) -> Option<Self> {
+        Some(Self {
+            drift_veto: packet.drift_veto_counter()?,
+            scaledown: packet.scaledown_counter()?,
+        })
+    }
+}
+
+/// Weight of each event given the scaledown of the trigger.
+///
+/// When the scaledown factor `N` is larger than 1, the trigger only accepts 1
+/// out of every `N` signals that pass the drift veto. Each of these events
+/// represents `N` real triggers, and it has to be weighted accordingly.
+///
+/// Events have to be added in trigger order (i.e. consecutive TRG packets).
+/// The weight of an event is determined from the difference between its
+/// counters and the counters of the previous event as follows:
+///
+/// - An event was accepted via the scaledown if the active factor is larger
+///   than 1 and at least one signal was rejected by the scaledown since the
+///   previous event. Its weight is the active factor. Otherwise, its weight
+///   is `1.0`.
+/// - A factor of `0` is the same as `1` (i.e. the scaledown is disabled).
+/// - The active factor is the `initial_factor` until the first event whose
+///   counters are inconsistent with it. From then on, the active factor is the
+///   `final_factor`. The single event that straddles the change is weighted by
+///   the number of signals that reached the scaledown per accepted signal
+///   since the previous event (i.e. a mix of both factors). Note that the ODB
+///   only records the factor at the beginning and end of a run, so at most a
+///   single change can be handled.
+///
+/// The weight of the first event can't be determined (there are no previous
+/// counters). Events without counters (e.g. a bad TRG packet) have no weight,
+/// and the next event is compared against the last event with counters.
+#[derive(Clone, Copy, Debug)]
+pub struct ScaledownWeights {
+    active_factor: u32,
+    final_factor: u32,
+    previous: Option<ScaledownCounters>,
+}
+
+impl ScaledownWeights {
+    /// Create a new [`ScaledownWeights`] given the scaledown factor at the
+    /// beginning and end of a run.
+    pub fn new(initial_factor: u32, final_factor: u32) -> Self {
+        Self {
+            active_factor: initial_factor.max(1),
+            final_factor: final_factor.max(1),
+            previous: None,
+        }
+    }
+
+    /// Return the weight of the next event.
+    pub fn next_weight(&mut self, counters: Option<ScaledownCounters>) -> Option<f64> {
+        let current = counters?;
+        let previous = self.previous.replace(current)?;
+        // All counters overflow together, so wrapping differences are fine.
+        let reached = u64::from(current.drift_veto.wrapping_sub(previous.drift_veto));
+        let accepted = u64::from(current.scaledown.wrapping_sub(previous.scaledown));
+        // Every event has to pass the scaledown.
+        if accepted == 0 {
+            return None;
+        }
+
+        if self.active_factor != self.final_factor
+            && reached != u64::from(self.active_factor) * accepted
+        {
+            self.active_factor = self.final_factor;
+            Some(reached as f64 / accepted as f64)
+        } else if self.active_factor > 1 && reached > accepted {
+            Some(f64::from(self.active_factor))
+        } else {
+            Some(1.0)
+        }
+    }
+
+    /// Forget the counters of the previous event. Use this when events are
+    /// missing (e.g. a missing file) before the next event, whose weight then
+    /// can't be determined (same as the first event).
+    pub fn skip_gap(&mut self) {
+        self.previous = None;
+    }
+}
+
+#[cfg(test)]
+mod tests;
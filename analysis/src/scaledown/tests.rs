@@ -0,0 +1,162 @@
+use super::*;
+
+// Build the counters of consecutive events in a run.
+struct Run {
+    counters: ScaledownCounters,
+    events: Vec<Option<ScaledownCounters>>,
+}
+
+impl Run {
+    fn new() -> Self {
+        Self::starting_at(0, 0)
+    }
+
+    fn starting_at(drift_veto: u32, scaledown: u32) -> Self {
+        Self {
+            counters: ScaledownCounters {
+                drift_veto,
+                scaledown,
+            },
+            events: Vec::new(),
+        }
+    }
+
+    // Next event is accepted after `reached` signals reached the scaledown.
+    fn event(mut self, reached: u32) -> Self {
+        self.counters.drift_veto = self.counters.drift_veto.wrapping_add(reached);
+        self.counters.scaledown = self.counters.scaledown.wrapping_add(1);
+        self.events.push(Some(self.counters));
+        self
+    }
+
+    // Next event passed the scaledown, but has no counters (e.g. bad packet).
+    fn bad_event(mut self, reached: u32) -> Self {
+        self = self.event(reached);
+        *self.events.last_mut().unwrap() = None;
+        self
+    }
+
+    fn weights(self, initial_factor: u32, final_factor: u32) -> Vec<Option<f64>> {
+        let mut weights = ScaledownWeights::new(initial_factor, final_factor);
+        self.events
+            .into_iter()
+            .map(|counters| weights.next_weight(counters))
+            .collect()
+    }
+}
+
+#[test]
+fn scaledown_weights_disabled() {
+    for factor in [0, 1] {
+        let weights = Run::new()
+            .event(1)
+            .event(1)
+            .event(1)
+            .weights(factor, factor);
+        assert_eq!(weights, [None, Some(1.0), Some(1.0)]);
+    }
+}
+
+#[test]
+fn scaledown_weights_constant_factor() {
+    let weights = Run::new().event(10).event(10).event(10).weights(10, 10);
+    assert_eq!(weights, [None, Some(10.0), Some(10.0)]);
+}
+
+#[test]
+fn scaledown_weights_no_rejected_signals() {
+    let weights = Run::new().event(10).event(1).event(10).weights(10, 10);
+    assert_eq!(weights, [None, Some(1.0), Some(10.0)]);
+}
+
+#[test]
+fn scaledown_weights_factor_change() {
+    // The event right after the change is a mix of both factors.
+    let weights = Run::new()
+        .event(10)
+        .event(10)
+        .event(7)
+        .event(5)
+        .event(5)
+        .weights(10, 5);
+    assert_eq!(weights, [None, Some(10.0), Some(7.0), Some(5.0), Some(5.0)]);
+    // The change happened exactly at an accepted signal.
+    let weights = Run::new().event(10).event(5).event(5).weights(10, 5);
+    assert_eq!(weights, [None, Some(5.0), Some(5.0)]);
+}
+
+#[test]
+fn scaledown_weights_enabled_mid_run() {
+    let weights = Run::new()
+        .event(1)
+        .event(1)
+        .event(3)
+        .event(4)
+        .event(4)
+        .weights(0, 4);
+    assert_eq!(weights, [None, Some(1.0), Some(3.0), Some(4.0), Some(4.0)]);
+}
+
+#[test]
+fn scaledown_weights_disabled_mid_run() {
+    let weights = Run::new().event(4).event(4).event(1).event(1).weights(4, 1);
+    assert_eq!(weights, [None, Some(4.0), Some(1.0), Some(1.0)]);
+}
+
+#[test]
+fn scaledown_weights_missing_counters() {
+    let weights = Run::new()
+        .bad_event(10)
+        .event(10)
+        .bad_event(10)
+        .event(10)
+        .weights(10, 10);
+    assert_eq!(weights, [None, None, None, Some(10.0)]);
+}
+
+#[test]
+fn scaledown_weights_skip_gap() {
+    let events = Run::new()
+        .event(10)
+        .event(10)
+        .event(10)
+        .event(10)
+        .event(10)
+        .events;
+    let mut weights = ScaledownWeights::new(10, 10);
+
+    assert_eq!(weights.next_weight(events[0]), None);
+    assert_eq!(weights.next_weight(events[1]), Some(10.0));
+    // The third event is missing.
+    weights.skip_gap();
+    assert_eq!(weights.next_weight(events[3]), None);
+    assert_eq!(weights.next_weight(events[4]), Some(10.0));
+}
+
+#[test]
+fn scaledown_weights_counters_overflow() {
+    let weights = Run::starting_at(u32::MAX - 15, u32::MAX - 1)
+        .event(10)
+        .event(10)
+        .event(10)
+        .weights(10, 10);
+    assert_eq!(weights, [None, Some(10.0), Some(10.0)]);
+}
+
+#[test]
+fn scaledown_counters_from_trg_packet() {
+    let buffer = [
+        255, 0, 0, 0, 0, 0, 0, 128, 254, 0, 0, 0, 0, 0, 0, 0, 3, 0, 0, 0, 0, 0, 0, 0, 5, 0, 0, 0,
+        6, 0, 0, 0, 7, 0, 0, 0, 8, 0, 0, 128, 2, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 9, 0, 10, 0, 11,
+        0, 0, 0, 0, 0, 0, 0, 12, 0, 0, 0, 13, 0, 0, 0, 14, 0, 0, 0, 0, 0, 0, 224,
+    ];
+    let packet = TrgPacket::try_from(&buffer[..]).unwrap();
+
+    assert_eq!(
+        ScaledownCounters::from_trg_packet(&packet),
+        Some(ScaledownCounters {
+            drift_veto: 2,
+            scaledown: 1,
+        })
+    );
+}
@@ -0,0 +1,238 @@
+use alpha_g_detector::midas::TriggerBankName;
+use alpha_g_detector::trigger::{TrgPacket, TryTrgPacketFromSliceError};
+use alpha_g_physics::TRG_CLOCK_FREQ;
+use std::collections::VecDeque;
+use thiserror::Error;
+use uom::si::time::second;
+
+/// The error type returned when the TRG packet of an event can't be found.
+#[derive(Debug, Error)]
+pub enum TrgBankError {
+    /// The event doesn't have exactly one TRG data bank.
+    #[error("bad number of trg data banks")]
+    BadBankCount,
+    /// The TRG data bank is invalid.
+    #[error(transparent)]
+    BadPacket(#[from] TryTrgPacketFromSliceError),
+}
+
+/// Return the TRG packet of an event given an iterator over its data banks (as
+/// tuples of bank name and data slice). The event must have exactly one TRG
+/// data bank.
+pub fn trg_packet<'a, I>(banks: I) -> Result<TrgPacket, TrgBankError>
+where
+    I: IntoIterator<Item = (&'a str, &'a [u8])>,
+{
+    let [(_, data)] = banks
+        .into_iter()
+        .filter(|(name, _)| TriggerBankName::try_from(*name).is_ok())
+        .collect::<Vec<_>>()[..]
+    else {
+        return Err(TrgBankError::BadBankCount);
+    };
+
+    Ok(TrgPacket::try_from(data)?)
+}
+
+/// Row of the TRG scalers CSV output.
+///
+/// All binaries that write TRG scalers use this row so that their outputs
+/// are identical.
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize)]
+pub struct ScalersRow {
+    /// Serial number of the event.
+    pub serial_number: u32,
+    /// Time (in seconds) since the first event with a TRG packet.
+    pub trg_time: Option<f64>,
+    /// Input counter.
+    pub input: Option<u32>,
+    /// Drift veto counter.
+    pub drift_veto: Option<u32>,
+    /// Scaledown counter.
+    pub scaledown: Option<u32>,
+    /// Pulser counter.
+    pub pulser: Option<u32>,
+    /// Output counter.
+    pub output: Option<u32>,
+    /// Input trigger rate (in Hz) over the rate window (see [`TriggerRates`]).
+    /// The outer [`None`] means that the column is not written at all.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input_rate: Option<Option<f64>>,
+    /// Output trigger rate (in Hz) over the rate window. Same as
+    /// `input_rate` for the outer [`None`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_rate: Option<Option<f64>>,
+    /// Live fraction over the rate window. Same as `input_rate` for the outer
+    /// [`None`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub live_fraction: Option<Option<f64>>,
+    /// ISO 8601 (UTC) time of the event, i.e. the start of the run plus the
+    /// `trg_time` (see [`iso8601`](crate::cli::iso8601)). Same as
+    /// `input_rate` for the outer [`None`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub utc: Option<Option<String>>,
+}
+
+/// Convert the TRG packets of consecutive events into [`ScalersRow`]s.
+///
+/// Events have to be added in trigger order, including events without a
+/// (valid) TRG packet. These still get a row with just their serial number.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TrgScalers {
+    previous: Option<u32>,
+    cumulative: u64,
+}
+
+impl TrgScalers {
+    /// Return the row of the next event.
+    pub fn next_row(&mut self, serial_number: u32, trg_packet: Option<&TrgPacket>) -> ScalersRow {
+        let timestamp = trg_packet.map(|p| p.timestamp());
+        // If we can't get a timestamp, it is OK to use the previous one
+        // because this counter overflows every 70ish seconds.
+        // This will only be problematic if we go a full 70 seconds
+        // without an event, which is already impossible because DAQ has
+        // a 10 seconds timeout before stopping the run.
+        let current = timestamp.unwrap_or(self.previous.unwrap_or(0));
+        let delta = current.wrapping_sub(self.previous.unwrap_or(current));
+        self.previous = Some(current);
+        self.cumulative += u64::from(delta);
+
+        if let Some(trg_packet) = trg_packet {
+            ScalersRow {
+                serial_number,
+                trg_time: Some((self.cumulative as f64 / TRG_CLOCK_FREQ).get::<second>()),
+                input: Some(trg_packet.input_counter()),
+                drift_veto: trg_packet.drift_veto_counter(),
+                scaledown: trg_packet.scaledown_counter(),
+                pulser: Some(trg_packet.pulser_counter()),
+                output: Some(trg_packet.output_counter()),
+                ..Default::default()
+            }
+        } else {
+            ScalersRow {
+                serial_number,
+                ..Default::default()
+            }
+        }
+    }
+}
+
+/// Trigger rates and live fraction of an event over a sliding time window (see
+/// [`TriggerRates`]).
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct WindowRates {
+    /// Input trigger rate (in Hz).
+    pub input_rate: Option<f64>,
+    /// Output trigger rate (in Hz).
+    pub output_rate: Option<f64>,
+    /// Fraction of the triggers that reached the output out of those that
+    /// passed the drift veto and the scaledown.
+    pub live_fraction: Option<f64>,
+}
+
+// Cumulative input, reached (i.e. passed the drift veto and the scaledown),
+// and output counters.
+type Counters = [u64; 3];
+
+/// Dead time and live time accounting from the [`ScalersRow`]s of consecutive
+/// events.
+///
+/// The rates of an event are computed between the earliest event within the
+/// last `window` seconds and the event itself. The live fraction is
+/// `Δoutput / Δscaledown` i.e. it is corrected for the triggers rejected by
+/// the drift veto and the scaledown (`Δoutput / Δinput` for TRG packets
+/// without these counters). The 32-bit counters wrap around, so all
+/// differences between consecutive events use wrapping subtraction.
+///
+/// Rows have to be added in trigger order. Rows without a `trg_time` or
+/// without counters (e.g. no TRG packet) are skipped, and all their rates are
+/// [`None`].
+#[derive(Clone, Debug)]
+pub struct TriggerRates {
+    window: f64,
+    previous: Option<(f64, [u32; 3])>,
+    cumulative: Counters,
+    samples: VecDeque<(f64, Counters)>,
+    live_time: f64,
+    duration: f64,
+}
+
+impl TriggerRates {
+    /// Create a new [`TriggerRates`] with the given window (in seconds).
+    pub fn new(window: f64) -> Self {
+        Self {
+            window,
+            previous: None,
+            cumulative: [0; 3],
+            samples: VecDeque::new(),
+            live_time: 0.0,
+            duration: 0.0,
+        }
+    }
+    /// Return the rates of the next event.
+    pub fn next(&mut self, row: &ScalersRow) -> WindowRates {
+        let (Some(t), Some(input), Some(output)) = (row.trg_time, row.input, row.output) else {
+            return WindowRates::default();
+        };
+        let current = [input, row.scaledown.unwrap_or(input), output];
+
+        if let Some((previous_t, previous)) = self.previous {
+            let delta: [u64; 3] =
+                std::array::from_fn(|i| u64::from(current[i].wrapping_sub(previous[i])));
+            for (cumulative, delta) in self.cumulative.iter_mut().zip(delta) {
+                *cumulative += delta;
+            }
+
+            let dt = t - previous_t;
+            self.duration += dt;
+            // If no trigger reached the output gate, none of them was lost.
+            // Triggers that bypass the scaledown (if any) could otherwise make
+            // the fraction larger than 1.
+            self.live_time += if delta[1] == 0 {
+                dt
+            } else {
+                dt * (delta[2] as f64 / delta[1] as f64).min(1.0)
+            };
+        }
+        self.previous = Some((t, current));
+        self.samples.push_back((t, self.cumulative));
+        while self
+            .samples
+            .front()
+            .is_some_and(|&(front_t, _)| front_t < t - self.window)
+        {
+            self.samples.pop_front();
+        }
+
+        let (front_t, front) = self.samples.front().copied().unwrap();
+        let dt = t - front_t;
+        let delta: [u64; 3] = std::array::from_fn(|i| self.cumulative[i] - front[i]);
+        let rate = |count: u64| (dt > 0.0).then(|| count as f64 / dt);
+        WindowRates {
+            input_rate: rate(delta[0]),
+            output_rate: rate(delta[2]),
+            live_fraction: (delta[1] != 0).then(|| delta[2] as f64 / delta[1] as f64),
+        }
+    }
+    /// Return the total number of input triggers since the first event.
+    pub fn total_input(&self) -> u64 {
+        self.cumulative[0]
+    }
+    /// Return the total number of output triggers since the first event.
+    pub fn total_output(&self) -> u64 {
+        self.cumulative[2]
+    }
+    /// Return the integrated live time (in seconds) since the first event.
+    /// Between consecutive events, the live time is the elapsed time weighted
+    /// by the live fraction of the interval.
+    pub fn live_time(&self) -> f64 {
+        self.live_time
+    }
+    /// Return the time (in seconds) between the first and the last event.
+    pub fn duration(&self) -> f64 {
+        self.duration
+    }
+}
+
+#[cfg(test)]
+mod tests;
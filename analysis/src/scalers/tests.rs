@@ -0,0 +1,152 @@
+use super::*;
+
+// Valid TRG packet.
+const TRG_PACKET: [u8; 84] = [
+    255, 0, 0, 0, 0, 0, 0, 128, 254, 0, 0, 0, 0, 0, 0, 0, 3, 0, 0, 0, 0, 0, 0, 0, 5, 0, 0, 0, 6, 0,
+    0, 0, 7, 0, 0, 0, 8, 0, 0, 128, 2, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 9, 0, 10, 0, 11, 0, 0, 0,
+    0, 0, 0, 0, 12, 0, 0, 0, 13, 0, 0, 0, 14, 0, 0, 0, 0, 0, 0, 224,
+];
+
+#[test]
+fn trg_packet_single_bank() {
+    let banks = [("ATAT", &TRG_PACKET[..]), ("AAAA", &[][..])];
+
+    assert!(trg_packet(banks).is_ok());
+}
+
+#[test]
+fn trg_packet_bad_bank_count() {
+    assert!(matches!(trg_packet([]), Err(TrgBankError::BadBankCount)));
+
+    let banks = [("ATAT", &TRG_PACKET[..]), ("ATAT", &TRG_PACKET[..])];
+    assert!(matches!(trg_packet(banks), Err(TrgBankError::BadBankCount)));
+}
+
+#[test]
+fn trg_packet_bad_packet() {
+    let banks = [("ATAT", &TRG_PACKET[1..])];
+
+    assert!(matches!(trg_packet(banks), Err(TrgBankError::BadPacket(_))));
+}
+
+#[test]
+fn trg_scalers_next_row() {
+    let packet = TrgPacket::try_from(&TRG_PACKET[..]).unwrap();
+    let mut scalers = TrgScalers::default();
+
+    let first = scalers.next_row(0, Some(&packet));
+    assert_eq!(first.serial_number, 0);
+    assert_eq!(first.trg_time, Some(0.0));
+    assert_eq!(first.input, Some(packet.input_counter()));
+    assert_eq!(first.drift_veto, packet.drift_veto_counter());
+    assert_eq!(first.scaledown, packet.scaledown_counter());
+    assert_eq!(first.pulser, Some(packet.pulser_counter()));
+    assert_eq!(first.output, Some(packet.output_counter()));
+
+    assert_eq!(
+        scalers.next_row(1, None),
+        ScalersRow {
+            serial_number: 1,
+            ..Default::default()
+        }
+    );
+    // Same timestamp as the first event.
+    assert_eq!(scalers.next_row(2, Some(&packet)).trg_time, Some(0.0));
+}
+
+// Every second there are 10 input triggers, 5 of them pass the drift veto and
+// the scaledown, and 4 reach the output. The counters wrap around right after
+// the first event.
+fn counting_rows() -> Vec<ScalersRow> {
+    (0..4)
+        .map(|i| ScalersRow {
+            serial_number: i,
+            trg_time: Some(f64::from(i)),
+            input: Some((u32::MAX - 4).wrapping_add(10 * i)),
+            drift_veto: Some(7 * i),
+            scaledown: Some(u32::MAX.wrapping_add(5 * i)),
+            pulser: Some(0),
+            output: Some(u32::MAX.wrapping_add(4 * i)),
+            ..Default::default()
+        })
+        .collect()
+}
+
+#[test]
+fn trigger_rates_window() {
+    let mut trigger_rates = TriggerRates::new(2.0);
+    let rates: Vec<_> = counting_rows()
+        .iter()
+        .map(|row| trigger_rates.next(row))
+        .collect();
+
+    assert_eq!(rates[0], WindowRates::default());
+    for rates in &rates[1..] {
+        assert_eq!(rates.input_rate, Some(10.0));
+        assert_eq!(rates.output_rate, Some(4.0));
+        assert_eq!(rates.live_fraction, Some(0.8));
+    }
+    assert_eq!(trigger_rates.total_input(), 30);
+    assert_eq!(trigger_rates.total_output(), 12);
+    assert_eq!(trigger_rates.duration(), 3.0);
+    assert!((trigger_rates.live_time() - 2.4).abs() < 1e-12);
+}
+
+#[test]
+fn trigger_rates_skip_rows_without_counters() {
+    let mut rows = counting_rows();
+    rows.insert(
+        2,
+        ScalersRow {
+            serial_number: 100,
+            ..Default::default()
+        },
+    );
+    let mut trigger_rates = TriggerRates::new(10.0);
+    let rates: Vec<_> = rows.iter().map(|row| trigger_rates.next(row)).collect();
+
+    assert_eq!(rates[2], WindowRates::default());
+    assert_eq!(rates[4].input_rate, Some(10.0));
+    assert_eq!(rates[4].live_fraction, Some(0.8));
+    assert_eq!(trigger_rates.total_input(), 30);
+}
+
+#[test]
+fn trigger_rates_without_scaledown_counter() {
+    let mut trigger_rates = TriggerRates::new(10.0);
+    let rates: Vec<_> = counting_rows()
+        .into_iter()
+        .map(|row| {
+            trigger_rates.next(&ScalersRow {
+                drift_veto: None,
+                scaledown: None,
+                ..row
+            })
+        })
+        .collect();
+
+    assert_eq!(rates[3].live_fraction, Some(0.4));
+    assert!((trigger_rates.live_time() - 1.2).abs() < 1e-12);
+}
+
+#[test]
+fn scalers_row_rate_columns() {
+    let mut wtr = csv::Writer::from_writer(Vec::new());
+    wtr.serialize(ScalersRow::default()).unwrap();
+    let header = String::from_utf8(wtr.into_inner().unwrap()).unwrap();
+    assert!(header.starts_with("serial_number,trg_time,input,drift_veto,scaledown,pulser,output\n"));
+
+    let mut wtr = csv::Writer::from_writer(Vec::new());
+    wtr.serialize(ScalersRow {
+        input_rate: Some(Some(10.0)),
+        output_rate: Some(Some(4.0)),
+        live_fraction: Some(None),
+        ..Default::default()
+    })
+    .unwrap();
+    let output = String::from_utf8(wtr.into_inner().unwrap()).unwrap();
+    assert_eq!(
+        output,
+        "serial_number,trg_time,input,drift_veto,scaledown,pulser,output,input_rate,output_rate,live_fraction\n0,,,,,,,10.0,4.0,\n"
+    );
+}
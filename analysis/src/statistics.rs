@@ -0,0 +1,416 @@
+use std::collections::BTreeMap;
+
+/// Estimate of a quantity from a single calibration run.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Measurement {
+    /// Estimated value.
+    pub value: f64,
+    /// Standard error of the estimated value.
+    pub error: f64,
+    /// Number of samples used to estimate the value.
+    pub samples: usize,
+}
+
+/// Combination of multiple [`Measurement`]s of the same quantity.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MergedMeasurement {
+    /// Inverse-variance weighted mean of all inputs.
+    pub value: f64,
+    /// Standard error of the weighted mean.
+    pub error: f64,
+    /// Total number of samples from all inputs.
+    pub samples: usize,
+    /// Number of inputs that contributed to the result.
+    pub inputs: usize,
+}
+
+/// Combine multiple measurements of the same quantity with inverse-variance
+/// weighting.
+///
+/// Measurements with an error that is not strictly positive and finite can't
+/// be weighted, hence they are ignored. Returns [`None`] if there are no valid
+/// measurements.
+pub fn inverse_variance_merge<I>(measurements: I) -> Option<MergedMeasurement>
+where
+    I: IntoIterator<Item = Measurement>,
+{
+    let mut sum_weights = 0.0;
+    let mut sum_weighted_values = 0.0;
+    let mut samples = 0;
+    let mut inputs = 0;
+    for measurement in measurements {
+        if !(measurement.error.is_finite() && measurement.error > 0.0) {
+            continue;
+        }
+        let weight = measurement.error.powi(-2);
+        sum_weights += weight;
+        sum_weighted_values += weight * measurement.value;
+        samples += measurement.samples;
+        inputs += 1;
+    }
+
+    (inputs > 0).then(|| MergedMeasurement {
+        value: sum_weighted_values / sum_weights,
+        error: sum_weights.sqrt().recip(),
+        samples,
+        inputs,
+    })
+}
+
+/// Merge multiple calibration maps (e.g. one per run) key by key with
+/// [`inverse_variance_merge`].
+///
+/// Keys don't need to be present in all maps; the `inputs` field of each
+/// [`MergedMeasurement`] reports how many maps contributed to it. Maps are
+/// ordered by key so that serialized calibrations are deterministic (and diffs
+/// between them are easy to review).
+pub fn merge_maps<K: Clone + Ord>(
+    maps: &[BTreeMap<K, Measurement>],
+) -> BTreeMap<K, MergedMeasurement> {
+    let mut grouped: BTreeMap<K, Vec<Measurement>> = BTreeMap::new();
+    for map in maps {
+        for (key, &measurement) in map {
+            grouped.entry(key.clone()).or_default().push(measurement);
+        }
+    }
+
+    grouped
+        .into_iter()
+        .filter_map(|(key, measurements)| {
+            inverse_variance_merge(measurements).map(|merged| (key, merged))
+        })
+        .collect()
+}
+
+/// Running mean and variance of a set of samples.
+///
+/// Two accumulators can be merged, which allows samples to be processed in
+/// parallel (e.g. one accumulator per thread).
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct RunningStatistics {
+    count: u64,
+    mean: f64,
+    m2: f64,
+}
+
+impl RunningStatistics {
+    /// Add a sample.
+    pub fn add(&mut self, value: f64) {
+        self.count += 1;
+        let delta = value - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (value - self.mean);
+    }
+    /// Add all the samples seen by `other`.
+    pub fn merge(&mut self, other: &Self) {
+        if other.count == 0 {
+            return;
+        }
+        let count = self.count + other.count;
+        let delta = other.mean - self.mean;
+        self.mean += delta * other.count as f64 / count as f64;
+        self.m2 += other.m2 + delta * delta * (self.count * other.count) as f64 / count as f64;
+        self.count = count;
+    }
+    /// Return the number of samples.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+    /// Return the mean of all samples. This is `0` if there are no samples.
+    pub fn mean(&self) -> f64 {
+        self.mean
+    }
+    /// Return the (population) standard deviation of all samples. This is NaN
+    /// if there are no samples.
+    pub fn std_dev(&self) -> f64 {
+        (self.m2 / self.count as f64).sqrt()
+    }
+    /// Return the standard error of the mean. This is NaN with less than two
+    /// samples.
+    pub fn std_error(&self) -> f64 {
+        if self.count < 2 {
+            return f64::NAN;
+        }
+        (self.m2 / (self.count - 1) as f64 / self.count as f64).sqrt()
+    }
+}
+
+/// Contribution of a single input file to the samples of a calibration.
+///
+/// A single bad file (e.g. a corrupted subrun) can skew the distributions of a
+/// whole calibration dataset. Contributions of different threads processing
+/// the same file can be merged; see [`deviating_files`] to compare files
+/// against each other.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FileContribution {
+    /// Number of events used from the file.
+    pub events: u64,
+    /// Number of accepted anode wire waveforms.
+    pub wire_waveforms: u64,
+    /// Number of accepted cathode pad waveforms.
+    pub pad_waveforms: u64,
+    /// Amplitude of all the accepted waveforms (wires and pads).
+    pub amplitude: RunningStatistics,
+}
+
+impl FileContribution {
+    /// Add the contribution of `other` (e.g. a different part of the same
+    /// file).
+    pub fn merge(&mut self, other: &Self) {
+        self.events += other.events;
+        self.wire_waveforms += other.wire_waveforms;
+        self.pad_waveforms += other.pad_waveforms;
+        self.amplitude.merge(&other.amplitude);
+    }
+    // Named statistics compared by `deviating_files`.
+    fn statistics(&self) -> [(&'static str, f64); 4] {
+        [
+            ("events", self.events as f64),
+            ("wire_waveforms", self.wire_waveforms as f64),
+            ("pad_waveforms", self.pad_waveforms as f64),
+            ("mean_amplitude", self.amplitude.mean()),
+        ]
+    }
+}
+
+/// Statistic of a [`FileContribution`] that deviates from the rest of the
+/// files. See [`deviating_files`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Deviation {
+    /// Name of the statistic (`events`, `wire_waveforms`, `pad_waveforms`, or
+    /// `mean_amplitude`).
+    pub statistic: &'static str,
+    /// Value of the statistic in the file.
+    pub value: f64,
+    /// Median of the same statistic over all the other files.
+    pub median: f64,
+}
+
+// Median of a non-empty set of values.
+pub(crate) fn median(mut values: Vec<f64>) -> f64 {
+    values.sort_unstable_by(f64::total_cmp);
+    let mid = values.len() / 2;
+    if values.len() % 2 == 0 {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    }
+}
+
+/// Compare each file against the median of all the other files.
+///
+/// A statistic deviates if it is more than `factor` times larger, or smaller,
+/// than the median of the same statistic over the other files. Returns the
+/// deviations of each file in the same order as the input (empty for files
+/// that don't deviate). Nothing deviates with less than two files.
+pub fn deviating_files(contributions: &[FileContribution], factor: f64) -> Vec<Vec<Deviation>> {
+    let statistics: Vec<_> = contributions.iter().map(|c| c.statistics()).collect();
+
+    statistics
+        .iter()
+        .enumerate()
+        .map(|(i, file)| {
+            if statistics.len() < 2 {
+                return Vec::new();
+            }
+            file.iter()
+                .enumerate()
+                .filter_map(|(j, &(statistic, value))| {
+                    let median = median(
+                        statistics
+                            .iter()
+                            .enumerate()
+                            .filter(|&(k, _)| k != i)
+                            .map(|(_, other)| other[j].1)
+                            .collect(),
+                    );
+                    (value > median * factor || value < median / factor).then_some(Deviation {
+                        statistic,
+                        value,
+                        median,
+                    })
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Uniform binning of the range `[min, max)`.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Binning {
+    /// Lower edge of the first bin.
+    pub min: f64,
+    /// Upper edge of the last bin.
+    pub max: f64,
+    /// Number of bins.
+    pub bins: usize,
+}
+
+impl Binning {
+    /// Return the index of the bin that contains `value`. Returns [`None`] if
+    /// the value is outside of the binned range (or NaN).
+    pub fn index(&self, value: f64) -> Option<usize> {
+        if !(self.min..self.max).contains(&value) {
+            return None;
+        }
+        let index = ((value - self.min) / self.width()).floor() as usize;
+        // Guard against rounding right below `max`.
+        Some(index.min(self.bins.checked_sub(1)?))
+    }
+    /// Return the center of the bin with the given index.
+    pub fn center(&self, index: usize) -> f64 {
+        self.min + (index as f64 + 0.5) * self.width()
+    }
+    /// Return the width of each bin.
+    pub fn width(&self) -> f64 {
+        (self.max - self.min) / self.bins as f64
+    }
+}
+
+/// Mean of the samples that fall in a single bin of a [`BinnedMean2d`].
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize)]
+pub struct BinMean {
+    /// Center of the bin along the first axis.
+    pub x: f64,
+    /// Center of the bin along the second axis.
+    pub y: f64,
+    /// Number of samples in the bin.
+    pub count: u64,
+    /// Mean of the samples in the bin.
+    pub mean: f64,
+    /// Standard error of the mean. This is [`None`] with less than two samples.
+    pub error: Option<f64>,
+}
+
+/// Mean of a quantity binned in two dimensions (e.g. a residual as a function
+/// of `z` and `r`).
+///
+/// Same as [`RunningStatistics`], two accumulators with the same binning can be
+/// merged.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BinnedMean2d {
+    x: Binning,
+    y: Binning,
+    // Row-major i.e. `bins[ix * y.bins + iy]`.
+    bins: Vec<RunningStatistics>,
+}
+
+impl BinnedMean2d {
+    /// Create an empty accumulator with the given binning along each axis.
+    pub fn new(x: Binning, y: Binning) -> Self {
+        Self {
+            x,
+            y,
+            bins: vec![RunningStatistics::default(); x.bins * y.bins],
+        }
+    }
+    /// Return the binning along the first axis.
+    pub fn x_binning(&self) -> Binning {
+        self.x
+    }
+    /// Return the binning along the second axis.
+    pub fn y_binning(&self) -> Binning {
+        self.y
+    }
+    /// Add a sample at `(x, y)`. Returns `false` (and the sample is ignored) if
+    /// the position is outside of the binned range.
+    pub fn add(&mut self, x: f64, y: f64, value: f64) -> bool {
+        let (Some(ix), Some(iy)) = (self.x.index(x), self.y.index(y)) else {
+            return false;
+        };
+        self.bins[ix * self.y.bins + iy].add(value);
+        true
+    }
+    /// Add all the samples seen by `other`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the binning of `other` is different.
+    pub fn merge(&mut self, other: &Self) {
+        assert!(
+            self.x == other.x && self.y == other.y,
+            "merging accumulators with different binning"
+        );
+        for (bin, other) in self.bins.iter_mut().zip(&other.bins) {
+            bin.merge(other);
+        }
+    }
+    /// Return the mean of every bin, including empty bins (with a `count` of
+    /// `0`). Bins are ordered by `x` first, then by `y`.
+    pub fn bins(&self) -> Vec<BinMean> {
+        self.bins
+            .iter()
+            .enumerate()
+            .map(|(i, statistics)| {
+                let error = statistics.std_error();
+                BinMean {
+                    x: self.x.center(i / self.y.bins),
+                    y: self.y.center(i % self.y.bins),
+                    count: statistics.count(),
+                    mean: statistics.mean(),
+                    error: error.is_finite().then_some(error),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Classifier of waveforms that contain only noise (i.e. no signal).
+///
+/// Baseline and noise calibrations need to reject waveforms with a signal
+/// (e.g. pulser, cosmics, or saturated channels); otherwise these bias both the
+/// mean and the standard deviation of the samples. A waveform is not noise if
+/// either:
+/// - Its peak-to-peak excursion is larger than `max_spread_multiple` times its
+///   robust spread (`1.4826` times the median absolute deviation around the
+///   median).
+/// - Any sample deviates from the average of the first `baseline_samples`
+///   samples by more than `max_deviation` ADC counts.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NoiseClassifier {
+    pub max_spread_multiple: f64,
+    pub baseline_samples: usize,
+    pub max_deviation: f64,
+}
+
+impl NoiseClassifier {
+    /// Default classifier for anode wire waveforms.
+    pub const ANODE_WIRES: Self = Self {
+        max_spread_multiple: 10.0,
+        baseline_samples: 100,
+        max_deviation: 50.0,
+    };
+    /// Default classifier for cathode pad waveforms.
+    pub const PADS: Self = Self {
+        max_spread_multiple: 10.0,
+        baseline_samples: 50,
+        max_deviation: 100.0,
+    };
+    /// Return `true` if the waveform contains only noise. An empty waveform is
+    /// never noise.
+    pub fn is_noise(&self, waveform: &[i16]) -> bool {
+        let Some((&min, &max)) = waveform.iter().min().zip(waveform.iter().max()) else {
+            return false;
+        };
+
+        let samples: Vec<_> = waveform.iter().copied().map(f64::from).collect();
+        let center = median(samples.clone());
+        let mad = median(samples.iter().map(|v| (v - center).abs()).collect());
+        // The median absolute deviation of quantized samples can be zero (e.g.
+        // a quiet channel where most samples are the same ADC count). Never
+        // let the spread go below a single ADC count.
+        let spread = (1.4826 * mad).max(1.0);
+        if f64::from(max) - f64::from(min) > self.max_spread_multiple * spread {
+            return false;
+        }
+
+        let head = &samples[..self.baseline_samples.clamp(1, samples.len())];
+        let baseline = head.iter().sum::<f64>() / head.len() as f64;
+        samples
+            .iter()
+            .all(|v| (v - baseline).abs() <= self.max_deviation)
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,365 @@
+use super::*;
+
+fn measurement(value: f64, error: f64, samples: usize) -> Measurement {
+    Measurement {
+        value,
+        error,
+        samples,
+    }
+}
+
+#[test]
+fn inverse_variance_merge_empty() {
+    assert_eq!(inverse_variance_merge(Vec::new()), None);
+}
+
+#[test]
+fn inverse_variance_merge_single() {
+    let merged = inverse_variance_merge([measurement(3.0, 0.5, 10)]).unwrap();
+
+    assert_eq!(
+        merged,
+        MergedMeasurement {
+            value: 3.0,
+            error: 0.5,
+            samples: 10,
+            inputs: 1,
+        }
+    );
+}
+
+#[test]
+fn inverse_variance_merge_weights() {
+    // Weights are 4 and 1.
+    let merged =
+        inverse_variance_merge([measurement(1.0, 0.5, 10), measurement(6.0, 1.0, 20)]).unwrap();
+
+    assert!((merged.value - 2.0).abs() < 1e-12);
+    assert!((merged.error - 5.0f64.sqrt().recip()).abs() < 1e-12);
+    assert_eq!(merged.samples, 30);
+    assert_eq!(merged.inputs, 2);
+}
+
+#[test]
+fn inverse_variance_merge_invalid_errors() {
+    let merged = inverse_variance_merge([
+        measurement(1.0, 0.0, 10),
+        measurement(2.0, f64::NAN, 10),
+        measurement(3.0, -1.0, 10),
+        measurement(4.0, 1.0, 10),
+    ])
+    .unwrap();
+    assert_eq!(merged.value, 4.0);
+    assert_eq!(merged.inputs, 1);
+
+    assert_eq!(inverse_variance_merge([measurement(1.0, 0.0, 10)]), None);
+}
+
+#[test]
+fn merge_maps_partial_keys() {
+    let first = BTreeMap::from([
+        ("a", measurement(1.0, 1.0, 5)),
+        ("b", measurement(2.0, 1.0, 5)),
+    ]);
+    let second = BTreeMap::from([("a", measurement(3.0, 1.0, 5))]);
+    let third = BTreeMap::from([
+        ("a", measurement(5.0, 1.0, 5)),
+        ("c", measurement(7.0, 0.0, 5)),
+    ]);
+
+    let merged = merge_maps(&[first, second, third]);
+    assert_eq!(merged.keys().copied().collect::<Vec<_>>(), ["a", "b"]);
+
+    let a = merged["a"];
+    assert!((a.value - 3.0).abs() < 1e-12);
+    assert_eq!(a.samples, 15);
+    assert_eq!(a.inputs, 3);
+
+    let b = merged["b"];
+    assert_eq!(b.value, 2.0);
+    assert_eq!(b.inputs, 1);
+    // No valid measurement for `c`.
+    assert!(!merged.contains_key("c"));
+}
+
+#[test]
+fn running_statistics_empty() {
+    let stats = RunningStatistics::default();
+
+    assert_eq!(stats.count(), 0);
+    assert!(stats.std_dev().is_nan());
+    assert!(stats.std_error().is_nan());
+}
+
+#[test]
+fn running_statistics_values() {
+    let mut stats = RunningStatistics::default();
+    for value in [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0] {
+        stats.add(value);
+    }
+
+    assert_eq!(stats.count(), 8);
+    assert!((stats.mean() - 5.0).abs() < 1e-12);
+    assert!((stats.std_dev() - 2.0).abs() < 1e-12);
+    assert!((stats.std_error() - (32.0f64 / 7.0 / 8.0).sqrt()).abs() < 1e-12);
+}
+
+#[test]
+fn running_statistics_merge() {
+    let values = [1.0, 3.0, -2.0, 8.0, 0.5, 4.0, 4.0];
+    let mut all = RunningStatistics::default();
+    for &value in &values {
+        all.add(value);
+    }
+
+    let (left, right) = values.split_at(3);
+    let mut a = RunningStatistics::default();
+    for &value in left {
+        a.add(value);
+    }
+    let mut b = RunningStatistics::default();
+    for &value in right {
+        b.add(value);
+    }
+    a.merge(&b);
+    a.merge(&RunningStatistics::default());
+
+    assert_eq!(a.count(), all.count());
+    assert!((a.mean() - all.mean()).abs() < 1e-12);
+    assert!((a.std_dev() - all.std_dev()).abs() < 1e-12);
+}
+
+fn contribution(events: u64, amplitudes: &[f64]) -> FileContribution {
+    let mut contribution = FileContribution {
+        events,
+        wire_waveforms: 2 * events,
+        pad_waveforms: 10 * events,
+        ..Default::default()
+    };
+    for &amplitude in amplitudes {
+        contribution.amplitude.add(amplitude);
+    }
+    contribution
+}
+
+#[test]
+fn file_contribution_merge() {
+    let mut a = contribution(10, &[1.0, 2.0]);
+    a.merge(&contribution(5, &[6.0]));
+
+    assert_eq!(a.events, 15);
+    assert_eq!(a.wire_waveforms, 30);
+    assert_eq!(a.pad_waveforms, 150);
+    assert_eq!(a.amplitude.count(), 3);
+    assert!((a.amplitude.mean() - 3.0).abs() < 1e-12);
+}
+
+#[test]
+fn median_values() {
+    assert_eq!(median(vec![3.0]), 3.0);
+    assert_eq!(median(vec![3.0, 1.0, 2.0]), 2.0);
+    assert_eq!(median(vec![4.0, 1.0, 3.0, 2.0]), 2.5);
+}
+
+#[test]
+fn deviating_files_half_events() {
+    let amplitudes = [10.0, 20.0];
+    let mut contributions = vec![contribution(1000, &amplitudes); 5];
+    contributions[3] = contribution(500, &amplitudes);
+
+    let deviations = deviating_files(&contributions, 1.5);
+    for (i, deviations) in deviations.iter().enumerate() {
+        if i == 3 {
+            let statistics: Vec<_> = deviations.iter().map(|d| d.statistic).collect();
+            assert_eq!(statistics, ["events", "wire_waveforms", "pad_waveforms"]);
+            assert_eq!(deviations[0].value, 500.0);
+            assert_eq!(deviations[0].median, 1000.0);
+        } else {
+            assert!(deviations.is_empty());
+        }
+    }
+
+    // Exactly a factor of 2 is not a deviation.
+    let deviations = deviating_files(&contributions, 2.0);
+    assert!(deviations.iter().all(Vec::is_empty));
+}
+
+#[test]
+fn deviating_files_mean_amplitude() {
+    let mut contributions = vec![contribution(100, &[10.0]); 5];
+    contributions[0] = contribution(100, &[50.0]);
+
+    let deviations = deviating_files(&contributions, 2.0);
+    assert_eq!(
+        deviations[0],
+        [Deviation {
+            statistic: "mean_amplitude",
+            value: 50.0,
+            median: 10.0,
+        }]
+    );
+    assert!(deviations[1..].iter().all(Vec::is_empty));
+}
+
+#[test]
+fn deviating_files_single_file() {
+    let deviations = deviating_files(&[contribution(1, &[])], 2.0);
+    assert_eq!(deviations, [Vec::new()]);
+
+    assert!(deviating_files(&[], 2.0).is_empty());
+}
+
+#[test]
+fn binning_index() {
+    let binning = Binning {
+        min: -1.0,
+        max: 1.0,
+        bins: 4,
+    };
+
+    assert_eq!(binning.index(-1.0), Some(0));
+    assert_eq!(binning.index(-0.6), Some(0));
+    assert_eq!(binning.index(-0.5), Some(1));
+    assert_eq!(binning.index(0.99), Some(3));
+    assert_eq!(binning.index(1.0), None);
+    assert_eq!(binning.index(-1.1), None);
+    assert_eq!(binning.index(f64::NAN), None);
+    assert_eq!(binning.center(0), -0.75);
+    assert_eq!(binning.center(3), 0.75);
+}
+
+#[test]
+fn binning_without_bins() {
+    let binning = Binning {
+        min: 0.0,
+        max: 1.0,
+        bins: 0,
+    };
+
+    assert_eq!(binning.index(0.5), None);
+}
+
+fn unit_binning(bins: usize) -> Binning {
+    Binning {
+        min: 0.0,
+        max: 1.0,
+        bins,
+    }
+}
+
+#[test]
+fn binned_mean_2d() {
+    let mut binned = BinnedMean2d::new(unit_binning(2), unit_binning(3));
+    assert!(binned.add(0.1, 0.1, 1.0));
+    assert!(binned.add(0.2, 0.2, 3.0));
+    assert!(binned.add(0.9, 0.5, 5.0));
+    assert!(!binned.add(1.5, 0.5, 7.0));
+
+    let bins = binned.bins();
+    assert_eq!(bins.len(), 6);
+    assert_eq!(bins[0].x, 0.25);
+    assert!((bins[0].y - 1.0 / 6.0).abs() < 1e-12);
+    assert_eq!(bins[0].count, 2);
+    assert_eq!(bins[0].mean, 2.0);
+    assert!((bins[0].error.unwrap() - 1.0).abs() < 1e-12);
+    // Second x bin, second y bin.
+    assert_eq!(bins[4].x, 0.75);
+    assert_eq!(bins[4].count, 1);
+    assert_eq!(bins[4].mean, 5.0);
+    assert_eq!(bins[4].error, None);
+    for i in [1, 2, 3, 5] {
+        assert_eq!(bins[i].count, 0);
+        assert_eq!(bins[i].error, None);
+    }
+}
+
+#[test]
+fn binned_mean_2d_merge() {
+    let mut all = BinnedMean2d::new(unit_binning(2), unit_binning(2));
+    let mut first = all.clone();
+    let mut second = all.clone();
+    for (i, value) in [1.0, 4.0, 2.0, 8.0, 5.0].into_iter().enumerate() {
+        all.add(0.1, 0.6, value);
+        if i % 2 == 0 {
+            first.add(0.1, 0.6, value);
+        } else {
+            second.add(0.1, 0.6, value);
+        }
+    }
+    first.merge(&second);
+
+    let (all, merged) = (all.bins(), first.bins());
+    assert_eq!(merged[1].count, 5);
+    assert!((merged[1].mean - all[1].mean).abs() < 1e-12);
+    assert!((merged[1].error.unwrap() - all[1].error.unwrap()).abs() < 1e-12);
+}
+
+#[test]
+#[should_panic]
+fn binned_mean_2d_merge_different_binning() {
+    let mut first = BinnedMean2d::new(unit_binning(2), unit_binning(2));
+    let second = BinnedMean2d::new(unit_binning(2), unit_binning(3));
+    first.merge(&second);
+}
+
+// Noise-only waveform around a baseline of `200` ADC counts. Deterministic
+// pseudo-random samples in the range [-8, 8].
+fn clean_waveform(len: usize) -> Vec<i16> {
+    let mut state: u32 = 12345;
+    (0..len)
+        .map(|_| {
+            state = state.wrapping_mul(1103515245).wrapping_add(12345);
+            200 + i16::try_from((state >> 16) % 17).unwrap() - 8
+        })
+        .collect()
+}
+
+#[test]
+fn noise_classifier_clean() {
+    let waveform = clean_waveform(511);
+    assert!(NoiseClassifier::PADS.is_noise(&waveform));
+    assert!(NoiseClassifier::ANODE_WIRES.is_noise(&waveform));
+}
+
+#[test]
+fn noise_classifier_flat() {
+    assert!(NoiseClassifier::PADS.is_noise(&[200; 511]));
+}
+
+#[test]
+fn noise_classifier_empty() {
+    assert!(!NoiseClassifier::PADS.is_noise(&[]));
+}
+
+#[test]
+fn noise_classifier_pulser() {
+    let mut waveform = clean_waveform(511);
+    for (i, v) in waveform[300..340].iter_mut().enumerate() {
+        *v -= 500 - 12 * i16::try_from(i).unwrap();
+    }
+    assert!(!NoiseClassifier::PADS.is_noise(&waveform));
+    assert!(!NoiseClassifier::ANODE_WIRES.is_noise(&waveform));
+}
+
+#[test]
+fn noise_classifier_saturated() {
+    let mut waveform = clean_waveform(511);
+    for v in waveform[250..400].iter_mut() {
+        *v = -2048;
+    }
+    assert!(!NoiseClassifier::PADS.is_noise(&waveform));
+    assert!(!NoiseClassifier::ANODE_WIRES.is_noise(&waveform));
+}
+
+#[test]
+fn noise_classifier_baseline_shift() {
+    // Half of the samples are shifted; the robust spread is as large as the
+    // shift, so only the deviation from the baseline rejects it.
+    let waveform: Vec<i16> = (0..512).map(|i| if i < 256 { 200 } else { 400 }).collect();
+    let classifier = NoiseClassifier {
+        max_deviation: f64::INFINITY,
+        ..NoiseClassifier::PADS
+    };
+    assert!(classifier.is_noise(&waveform));
+    assert!(!NoiseClassifier::PADS.is_noise(&waveform));
+}
@@ -0,0 +1,671 @@
+use super::*;
+
+// Empty directory for the files of a single test. It is removed first if it
+// already exists (e.g. after an interrupted run), and again when dropped.
+struct TestDir(PathBuf);
+
+impl Deref for TestDir {
+    type Target = PathBuf;
+
+    fn deref(&self) -> &PathBuf {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for TestDir {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+fn test_dir(name: &str) -> TestDir {
+    let dir = std::env::temp_dir().join(format!("alpha_g_analysis_{}_{name}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+
+    TestDir(dir)
+}
+
+// Write a MIDAS file that only has the beginning of run header. The file is
+// compressed if `name` ends with `.lz4`.
+fn write_run_file(dir: &Path, name: &str, run_number: u32, initial_timestamp: u32) -> PathBuf {
+    let path = dir.join(name);
+    let mut contents = vec![0x00, 0x80, 0x4D, 0x49];
+    contents.extend_from_slice(&run_number.to_le_bytes());
+    contents.extend_from_slice(&initial_timestamp.to_le_bytes());
+    if name.ends_with(".lz4") {
+        let mut encoder = lz4::EncoderBuilder::new()
+            .build(std::fs::File::create(&path).unwrap())
+            .unwrap();
+        std::io::Write::write_all(&mut encoder, &contents).unwrap();
+        let (_, result) = encoder.finish();
+        result.unwrap();
+    } else {
+        std::fs::write(&path, contents).unwrap();
+    }
+
+    path
+}
+
+#[test]
+fn sort_run_files_good() {
+    let dir = test_dir("sort_good");
+    let first = write_run_file(&dir, "first.mid", 5, 100);
+    let second = write_run_file(&dir, "second.mid", 5, 200);
+
+    let (run_number, files) = sort_run_files([&second, &first], None).unwrap();
+    assert_eq!(run_number, RunNumber::new(5));
+    assert_eq!(files, [&first, &second]);
+}
+
+#[test]
+fn sort_run_files_bad_run_number() {
+    let dir = test_dir("sort_bad_run_number");
+    let first = write_run_file(&dir, "first.mid", 5, 100);
+    let second = write_run_file(&dir, "second.mid", 0, 200);
+
+    match sort_run_files([&first, &second], None) {
+        Err(AlphaIOError::BadRunNumber {
+            expected, found, ..
+        }) => {
+            assert_eq!(expected, RunNumber::new(5));
+            assert_eq!(found, RunNumber::new(0));
+        }
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn sort_run_files_empty() {
+    let error = sort_run_files(Vec::<PathBuf>::new(), None).unwrap_err();
+    assert!(matches!(error, AlphaIOError::NoInputFiles));
+    assert_eq!(error.to_string(), "no input files provided");
+    assert_eq!(error.category(), ErrorCategory::NotFound);
+
+    let error = sort_run_files(Vec::<PathBuf>::new(), Some(RunNumber::new(5))).unwrap_err();
+    assert!(matches!(error, AlphaIOError::NoInputFiles));
+}
+
+#[test]
+fn sort_run_files_single_file() {
+    let dir = test_dir("sort_single");
+    let file = write_run_file(&dir, "single.mid", 5, 100);
+
+    let (run_number, files) = sort_run_files([&file], None).unwrap();
+    assert_eq!(run_number, RunNumber::new(5));
+    assert_eq!(files, [&file]);
+}
+
+#[test]
+#[allow(deprecated)]
+fn sort_run_files_u32_deprecated() {
+    let dir = test_dir("sort_u32");
+    let first = write_run_file(&dir, "first.mid", 5, 100);
+    let second = write_run_file(&dir, "second.mid", 5, 200);
+
+    let (run_number, files) = sort_run_files_u32([&second, &first], None).unwrap();
+    assert_eq!(run_number, 5);
+    assert_eq!(files, [&first, &second]);
+
+    let (run_number, _) = sort_run_files_u32([&first], Some(7)).unwrap();
+    assert_eq!(run_number, 7);
+}
+
+#[test]
+fn sort_run_files_mixed_runs() {
+    let dir = test_dir("sort_mixed");
+    let first = write_run_file(&dir, "first.mid", 5, 100);
+    let second = write_run_file(&dir, "second.mid", 5, 200);
+    let third = write_run_file(&dir, "third.mid", 6, 300);
+
+    match sort_run_files([&third, &first, &second], None) {
+        Err(AlphaIOError::BadRunNumber {
+            path,
+            expected,
+            found,
+        }) => {
+            assert_eq!(path, first);
+            assert_eq!(expected, RunNumber::new(6));
+            assert_eq!(found, RunNumber::new(5));
+        }
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn sort_run_files_parsed_before_failure() {
+    let dir = test_dir("sort_parsed");
+    let first = write_run_file(&dir, "first.mid", 5, 100);
+    let second = write_run_file(&dir, "second.mid", 5, 200);
+    let missing = dir.join("run00005sub002.mid");
+
+    let error = sort_run_files([&first, &second, &missing], None).unwrap_err();
+    match &error {
+        AlphaIOError::BadRunFile {
+            parsed,
+            total,
+            error,
+        } => {
+            assert_eq!((*parsed, *total), (2, 3));
+            assert!(matches!(**error, AlphaIOError::IoError { .. }));
+        }
+        _ => unreachable!(),
+    }
+    let message = error.to_string();
+    assert!(message.contains(&missing.display().to_string()));
+    assert!(message.contains("`2` of `3`"));
+    assert_eq!(error.category(), ErrorCategory::NotFound);
+}
+
+#[test]
+fn sort_run_files_run_number_override() {
+    let dir = test_dir("sort_override");
+    let first = write_run_file(&dir, "first.mid", 5, 100);
+    let second = write_run_file(&dir, "second.mid", 0, 200);
+
+    let (run_number, files) = sort_run_files([&second, &first], Some(RunNumber::new(5))).unwrap();
+    assert_eq!(run_number, RunNumber::new(5));
+    assert_eq!(files, [&first, &second]);
+
+    let (run_number, _) = sort_run_files([&first], Some(RunNumber::new(7))).unwrap();
+    assert_eq!(run_number, RunNumber::new(7));
+}
+
+#[test]
+fn open_uncompressed_file() {
+    let dir = test_dir("open_uncompressed");
+    let path = write_run_file(&dir, "run00005sub000.mid", 5, 100);
+
+    let contents = open(&path).unwrap();
+    assert!(matches!(contents, FileContents::Mapped(_)));
+    assert_eq!(&contents[..], &std::fs::read(&path).unwrap()[..]);
+}
+
+#[test]
+fn open_compressed_file() {
+    let dir = test_dir("open_compressed");
+    let uncompressed = write_run_file(&dir, "run00005sub000.mid", 5, 100);
+    let expected = std::fs::read(uncompressed).unwrap();
+    let path = write_run_file(&dir, "run00005sub000.mid.lz4", 5, 100);
+
+    let contents = open(&path).unwrap();
+    assert!(matches!(contents, FileContents::Owned(_)));
+    assert_eq!(&contents[..], &expected[..]);
+}
+
+#[test]
+fn is_run_file_name_patterns() {
+    assert!(is_run_file_name("run09570sub000.mid"));
+    assert!(is_run_file_name("run09570sub012.mid.lz4"));
+    assert!(is_run_file_name("run11186sub1.mid"));
+
+    assert!(!is_run_file_name("run09570sub000.mid.gz"));
+    assert!(!is_run_file_name("run09570sub000.lz4"));
+    assert!(!is_run_file_name("run09570.mid"));
+    assert!(!is_run_file_name("runsub000.mid"));
+    assert!(!is_run_file_name("run09570sub.mid"));
+    assert!(!is_run_file_name("run0957xsub000.mid"));
+    assert!(!is_run_file_name("R09570_vertices.csv"));
+}
+
+#[test]
+fn expand_run_files_nested_directories() {
+    let dir = test_dir("expand_nested");
+    let sub0 = write_run_file(&dir, "run00005sub000.mid", 5, 100);
+    std::fs::create_dir_all(dir.join("a/b")).unwrap();
+    let sub1 = write_run_file(&dir.join("a"), "run00005sub001.mid.lz4", 5, 200);
+    let sub2 = write_run_file(&dir.join("a/b"), "run00005sub002.mid", 5, 300);
+    // Ignored files.
+    std::fs::write(dir.join("notes.txt"), "").unwrap();
+    write_run_file(&dir, ".run00005sub003.mid", 5, 400);
+    std::fs::create_dir(dir.join(".hidden")).unwrap();
+    write_run_file(&dir.join(".hidden"), "run00005sub004.mid", 5, 500);
+
+    let files = expand_run_files([&dir], false).unwrap();
+    assert_eq!(files, [sub0.clone()]);
+
+    let files = expand_run_files([&dir], true).unwrap();
+    let (run_number, files) = sort_run_files(files, None).unwrap();
+    assert_eq!(run_number, RunNumber::new(5));
+    assert_eq!(files, [sub0, sub1, sub2]);
+}
+
+#[test]
+fn expand_run_files_mixed_compression() {
+    let dir = test_dir("expand_mixed");
+    let sub0 = write_run_file(&dir, "run00005sub000.mid.lz4", 5, 100);
+    let sub1 = write_run_file(&dir, "run00005sub001.mid", 5, 200);
+    let sub2 = write_run_file(&dir, "run00005sub002.mid.lz4", 5, 300);
+    // Files and directories can be mixed in the input.
+    let other_dir = test_dir("expand_mixed_other");
+    let sub3 = write_run_file(&other_dir, "run00005sub003.mid", 5, 400);
+
+    let files = expand_run_files([&sub3, &*dir], false).unwrap();
+    let (_, files) = sort_run_files(files, None).unwrap();
+    assert_eq!(files, [sub0, sub1, sub2, sub3]);
+}
+
+#[test]
+fn expand_run_files_two_runs() {
+    let dir = test_dir("expand_two_runs");
+    write_run_file(&dir, "run00005sub000.mid", 5, 100);
+    write_run_file(&dir, "run00006sub000.mid", 6, 200);
+
+    let files = expand_run_files([&dir], false).unwrap();
+    assert_eq!(files.len(), 2);
+    match sort_run_files(files, None) {
+        Err(error @ AlphaIOError::BadRunNumber { .. }) => {
+            let message = error.to_string();
+            assert!(message.contains("expected `5`"));
+            assert!(message.contains("found `6`"));
+        }
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn expand_run_files_empty_directory() {
+    let dir = test_dir("expand_empty");
+    std::fs::write(dir.join("notes.txt"), "").unwrap();
+
+    match expand_run_files([&dir], true) {
+        Err(AlphaIOError::NoRunFiles { path }) => assert_eq!(path, *dir),
+        _ => unreachable!(),
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn expand_run_files_follow_symlinks() {
+    let dir = test_dir("expand_symlinks");
+    let target = test_dir("expand_symlinks_target");
+    let sub0 = write_run_file(&target, "run00005sub000.mid", 5, 100);
+    std::os::unix::fs::symlink(&target, dir.join("linked")).unwrap();
+    std::os::unix::fs::symlink(&sub0, dir.join("run00005sub001.mid")).unwrap();
+    // A loop must not recurse forever.
+    std::os::unix::fs::symlink(&dir, target.join("loop")).unwrap();
+
+    let files = expand_run_files([&dir], false).unwrap();
+    assert_eq!(files, [dir.join("run00005sub001.mid")]);
+
+    let files = expand_run_files([&dir], true).unwrap();
+    assert_eq!(
+        files,
+        [
+            dir.join("linked/run00005sub000.mid"),
+            dir.join("run00005sub001.mid")
+        ]
+    );
+}
+
+#[test]
+fn read_nonexistent_file() {
+    let dir = test_dir("read_nonexistent");
+    let path = dir.join("run00005sub000.mid");
+
+    for error in [
+        read(&path).unwrap_err(),
+        open(&path).unwrap_err(),
+        sort_run_files([&path], None).unwrap_err(),
+    ] {
+        assert!(error.to_string().contains(&path.display().to_string()));
+        assert_eq!(error.category(), ErrorCategory::NotFound);
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn read_permission_denied() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = test_dir("permission_denied");
+    let path = write_run_file(&dir, "run00005sub000.mid", 5, 100);
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o000)).unwrap();
+    // Permissions are not enforced for privileged users (e.g. root).
+    if std::fs::File::open(&path).is_ok() {
+        return;
+    }
+
+    for error in [
+        read(&path).unwrap_err(),
+        open(&path).unwrap_err(),
+        sort_run_files([&path], None).unwrap_err(),
+    ] {
+        assert!(error.to_string().contains(&path.display().to_string()));
+        assert_eq!(error.category(), ErrorCategory::PermissionDenied);
+    }
+}
+
+#[test]
+fn read_unknown_extension() {
+    let dir = test_dir("unknown_extension");
+    let path = write_run_file(&dir, "run00005sub000.mid", 5, 100).with_extension("gz");
+
+    let error = read(&path).unwrap_err();
+    assert!(error.to_string().contains(&path.display().to_string()));
+    assert_eq!(error.category(), ErrorCategory::InvalidData);
+}
+
+#[test]
+fn sort_run_files_truncated_file() {
+    let dir = test_dir("truncated");
+    let path = dir.join("run00005sub000.mid");
+    std::fs::write(&path, [0x00, 0x80, 0x4D, 0x49]).unwrap();
+
+    let error = sort_run_files([&path], None).unwrap_err();
+    assert!(error.to_string().contains(&path.display().to_string()));
+    assert_eq!(error.category(), ErrorCategory::InvalidData);
+}
+
+#[test]
+fn sort_readable_run_files_skip_truncated() {
+    let dir = test_dir("readable");
+    let first = write_run_file(&dir, "run00005sub000.mid", 5, 100);
+    let third = write_run_file(&dir, "run00005sub002.mid", 5, 300);
+    let second = dir.join("run00005sub001.mid");
+    std::fs::write(&second, [0x00, 0x80, 0x4D, 0x49]).unwrap();
+
+    let (run_number, files, skipped) =
+        sort_readable_run_files([&third, &second, &first], None).unwrap();
+    assert_eq!(run_number, RunNumber::new(5));
+    assert_eq!(files, [&first, &third]);
+    assert_eq!(skipped.len(), 1);
+    assert_eq!(skipped[0].0, &second);
+    assert!(skipped[0]
+        .1
+        .to_string()
+        .contains(&second.display().to_string()));
+
+    let (_, files, skipped) = sort_readable_run_files([&first, &third], None).unwrap();
+    assert_eq!(files, [&first, &third]);
+    assert!(skipped.is_empty());
+}
+
+#[test]
+fn sort_readable_run_files_nothing_readable() {
+    let dir = test_dir("nothing_readable");
+    let path = dir.join("run00005sub000.mid");
+    std::fs::write(&path, b"").unwrap();
+
+    let error = sort_readable_run_files([&path], None).unwrap_err();
+    assert!(matches!(
+        error,
+        AlphaIOError::BadRunFile {
+            parsed: 0,
+            total: 1,
+            ..
+        }
+    ));
+    assert_eq!(error.category(), ErrorCategory::InvalidData);
+
+    let error = sort_readable_run_files(Vec::<PathBuf>::new(), None).unwrap_err();
+    assert!(matches!(error, AlphaIOError::NoInputFiles));
+}
+
+// Write a complete MIDAS file with a few events. The file is compressed if
+// `name` ends with `.lz4`.
+fn write_events_file(dir: &Path, name: &str) -> PathBuf {
+    let banks = [midas::Bank {
+        name: *b"ATAT",
+        data_type: 6,
+        data: &[1, 2, 3, 4, 5, 6, 7, 8],
+    }];
+    let mut writer = midas::MidasWriter::new(Vec::new(), 11186, 100, b"{}").unwrap();
+    for serial_number in 0..10 {
+        let event = midas::Event {
+            id: 1,
+            trigger_mask: 0,
+            serial_number,
+            timestamp: 100,
+            bank_format: midas::BankFormat::Bank32,
+            banks: banks.to_vec(),
+        };
+        writer.write_event(&event).unwrap();
+    }
+    let contents = writer.finish(101, b"{}").unwrap();
+
+    let path = dir.join(name);
+    if name.ends_with(".lz4") {
+        let mut encoder = lz4::EncoderBuilder::new()
+            .build(std::fs::File::create(&path).unwrap())
+            .unwrap();
+        std::io::Write::write_all(&mut encoder, &contents).unwrap();
+        let (_, result) = encoder.finish();
+        result.unwrap();
+    } else {
+        std::fs::write(&path, contents).unwrap();
+    }
+
+    path
+}
+
+#[test]
+fn event_iter_same_as_events() {
+    let dir = test_dir("event_iter");
+    for name in ["run11186sub000.mid", "run11186sub000.mid.lz4"] {
+        let path = write_events_file(&dir, name);
+        let contents = read(&path).unwrap();
+        let expected: Vec<_> = midas::events(&contents)
+            .unwrap()
+            .iter()
+            .map(midas::OwnedEvent::from)
+            .collect();
+
+        let mut events = event_iter(&path).unwrap();
+        assert_eq!(events.run_number(), RunNumber::new(11186));
+        assert_eq!(events.initial_timestamp(), 100);
+        let found = events.by_ref().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(found, expected);
+        assert_eq!(events.final_timestamp(), Some(101));
+    }
+}
+
+#[test]
+fn event_iter_truncated_file() {
+    let dir = test_dir("event_iter_truncated");
+    let path = write_events_file(&dir, "run11186sub000.mid");
+    let contents = std::fs::read(&path).unwrap();
+    std::fs::write(&path, &contents[..contents.len() / 2]).unwrap();
+
+    let error = event_iter(&path).unwrap().find_map(Result::err).unwrap();
+    assert_eq!(error.category(), ErrorCategory::InvalidData);
+    assert!(error.to_string().contains("run11186sub000.mid"));
+}
+
+#[test]
+fn event_iter_nonexistent_file() {
+    let error = event_iter("nonexistent_file.mid").err().unwrap();
+    assert_eq!(error.category(), ErrorCategory::NotFound);
+}
+
+// Write a complete MIDAS file with `num_events` events of the given ID.
+fn write_census_file(dir: &Path, name: &str, id: u16, num_events: u32) -> PathBuf {
+    let mut writer = midas::MidasWriter::new(Vec::new(), 11186, 100, b"{}").unwrap();
+    for serial_number in 0..num_events {
+        let event = midas::Event {
+            id,
+            trigger_mask: 0,
+            serial_number,
+            timestamp: 100,
+            bank_format: midas::BankFormat::Bank32,
+            banks: Vec::new(),
+        };
+        writer.write_event(&event).unwrap();
+    }
+    let path = dir.join(name);
+    std::fs::write(&path, writer.finish(101, b"{}").unwrap()).unwrap();
+
+    path
+}
+
+#[test]
+fn census_until_main_event_stops_early() {
+    let dir = test_dir("census_stops_early");
+    let first = write_census_file(&dir, "run11186sub000.mid", 4, 3);
+    let second = write_census_file(&dir, "run11186sub001.mid", 1, 5);
+    let third = write_census_file(&dir, "run11186sub002.mid", 8, 2);
+
+    let census = census_until_main_event(&[first, second, third]);
+    assert_eq!(
+        census,
+        EventCensus {
+            main: 1,
+            chronobox: 3,
+            sequencer: 0,
+        }
+    );
+}
+
+#[test]
+fn census_until_main_event_no_main_events() {
+    let dir = test_dir("census_no_main_events");
+    let first = write_census_file(&dir, "run11186sub000.mid", 4, 3);
+    let second = dir.join("run11186sub001.mid");
+    std::fs::write(&second, [0x00, 0x80]).unwrap();
+    let third = write_census_file(&dir, "run11186sub002.mid", 8, 2);
+
+    let census = census_until_main_event(&[first, second, third]);
+    assert_eq!(census.main, 0);
+    assert_eq!(census.chronobox, 3);
+    assert_eq!(census.sequencer, 2);
+    assert!(census.ensure_main_events().is_err());
+}
+
+#[test]
+fn run_duration_first_and_last_files() {
+    let dir = test_dir("run_duration");
+    let first = write_run_file(&dir, "run11186sub000.mid", 11186, 90);
+    for name in ["run11186sub001.mid", "run11186sub001.mid.lz4"] {
+        let last = write_events_file(&dir, name);
+
+        assert_eq!(
+            run_duration(&[&first, &last]).unwrap(),
+            Duration::from_secs(11)
+        );
+        assert_eq!(run_duration(&[&last]).unwrap(), Duration::from_secs(1));
+    }
+}
+
+#[test]
+fn run_duration_truncated_last_file() {
+    let dir = test_dir("run_duration_truncated");
+    let path = write_events_file(&dir, "run11186sub000.mid");
+    let contents = std::fs::read(&path).unwrap();
+    std::fs::write(&path, &contents[..contents.len() - 1]).unwrap();
+
+    let error = run_duration(&[&path]).unwrap_err();
+    assert_eq!(error.category(), ErrorCategory::InvalidData);
+}
+
+#[test]
+fn run_duration_no_files() {
+    let files: [PathBuf; 0] = [];
+    assert!(matches!(
+        run_duration(&files),
+        Err(AlphaIOError::NoInputFiles)
+    ));
+}
+
+#[test]
+fn prefetch_files_order() {
+    let dir = test_dir("prefetch");
+    let files = vec![
+        write_run_file(&dir, "run00005sub000.mid.lz4", 5, 100),
+        dir.join("run00005sub001.mid"),
+        write_run_file(&dir, "run00005sub002.mid", 5, 300),
+        write_run_file(&dir, "run00005sub003.mid.lz4", 5, 400),
+    ];
+
+    for max_files in [0, 1, 2, 3, 10] {
+        let prefetched: Vec<_> = prefetch_files(files.clone(), max_files).collect();
+        assert_eq!(prefetched.len(), files.len());
+        for ((file, contents), expected) in prefetched.into_iter().zip(&files) {
+            assert_eq!(&file, expected);
+            match open(expected) {
+                Ok(contents_expected) => {
+                    let (contents, checksum) = contents.unwrap();
+                    assert_eq!(&contents[..], &contents_expected[..]);
+                    assert_eq!(checksum, file_checksum(expected).unwrap());
+                }
+                Err(error) => {
+                    assert_eq!(contents.unwrap_err().category(), error.category());
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn prefetch_files_early_drop() {
+    let dir = test_dir("prefetch_early_drop");
+    let files: Vec<_> = (0..5)
+        .map(|i| write_run_file(&dir, &format!("run00005sub00{i}.mid"), 5, 100 * i))
+        .collect();
+
+    let mut prefetched = prefetch_files(files.clone(), 2);
+    assert_eq!(prefetched.next().unwrap().0, files[0]);
+    // The background thread must not block forever (or panic) after this.
+    drop(prefetched);
+}
+
+#[test]
+fn open_with_checksum_same_as_file_checksum() {
+    let dir = test_dir("open_with_checksum");
+    for name in ["run00005sub000.mid", "run00005sub001.mid.lz4"] {
+        let file = write_run_file(&dir, name, 5, 100);
+        let (contents, checksum) = open_with_checksum(&file).unwrap();
+        assert_eq!(&contents[..], &open(&file).unwrap()[..]);
+        assert_eq!(checksum, file_checksum(&file).unwrap());
+        assert_eq!(checksum.size, std::fs::metadata(&file).unwrap().len());
+    }
+}
+
+#[test]
+fn event_iter_checksum() {
+    let dir = test_dir("event_iter_checksum");
+    // Complete file (i.e. with begin and end of run ODB dumps) without events.
+    let contents = midas::MidasWriter::new(Vec::new(), 5, 100, b"{}")
+        .unwrap()
+        .finish(102, b"{}")
+        .unwrap();
+    for name in ["run00005sub000.mid", "run00005sub001.mid.lz4"] {
+        let file = dir.join(name);
+        if name.ends_with(".lz4") {
+            let mut encoder = lz4::EncoderBuilder::new()
+                .build(std::fs::File::create(&file).unwrap())
+                .unwrap();
+            std::io::Write::write_all(&mut encoder, &contents).unwrap();
+            let (_, result) = encoder.finish();
+            result.unwrap();
+        } else {
+            std::fs::write(&file, &contents).unwrap();
+        }
+        let expected = file_checksum(&file).unwrap();
+
+        let mut events = event_iter(&file).unwrap();
+        // Before (and after) reading all the events.
+        assert_eq!(events.checksum().unwrap(), expected);
+        for event in events.by_ref() {
+            event.unwrap();
+        }
+        assert_eq!(events.checksum().unwrap(), expected);
+    }
+}
+
+#[test]
+fn file_checksum_any_file() {
+    let dir = test_dir("file_checksum");
+    let file = dir.join("notes.txt");
+    std::fs::write(&file, "abc").unwrap();
+
+    let checksum = file_checksum(&file).unwrap();
+    assert_eq!(checksum.path, file);
+    assert_eq!(checksum.size, 3);
+    assert_eq!(checksum.xxh64, 0x44bc2cf5ad770999);
+}
@@ -0,0 +1,99 @@
+use alpha_g_detector::alpha16::aw_map::TPC_ANODE_WIRES;
+use alpha_g_physics::MainEvent;
+use thiserror::Error;
+
+/// The error type returned when a sample of events doesn't have any TPC signal
+/// (see [`TpcSignalCheck::ensure_signal`]).
+#[derive(Debug, Error)]
+#[error("run appears to have no TPC signal (HV off?): mean wire occupancy {wire_occupancy:.4}, {pad_fraction:.4} of {events} events with a pad signal")]
+pub struct NoTpcSignalError {
+    events: usize,
+    wire_occupancy: f64,
+    pad_fraction: f64,
+}
+
+/// Thresholds below which a sample of events is consistent with the TPC high
+/// voltage being off (see [`TpcSignalCheck::ensure_signal`]).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TpcSignalThresholds {
+    /// Mean fraction of the anode wires with a signal in each event.
+    pub wire_occupancy: f64,
+    /// Fraction of events with at least one pad signal.
+    pub pad_fraction: f64,
+}
+
+impl Default for TpcSignalThresholds {
+    // Every track fires a few anode wires (i.e. about 1% of the 256 wires) and
+    // dozens of pads, and almost every triggered event has at least one track.
+    // With the HV off, only the occasional noisy channel makes it through the
+    // data suppression.
+    fn default() -> Self {
+        Self {
+            wire_occupancy: 0.01,
+            pad_fraction: 0.1,
+        }
+    }
+}
+
+/// Occupancy of the TPC in a sample of events.
+///
+/// Counting the channels with a signal only requires the decoded events, so
+/// this is cheap compared to the reconstruction of the same events.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TpcSignalCheck {
+    events: usize,
+    wire_signals: usize,
+    pad_events: usize,
+}
+
+impl TpcSignalCheck {
+    /// Add an event given its number of wire and pad channels with a signal.
+    pub fn add(&mut self, wire_signals: usize, pad_signals: usize) {
+        self.events += 1;
+        self.wire_signals += wire_signals;
+        self.pad_events += usize::from(pad_signals > 0);
+    }
+    /// Add a decoded event.
+    pub fn add_event(&mut self, event: &MainEvent) {
+        self.add(event.num_wire_signals(), event.num_pad_signals());
+    }
+    /// Return the number of events added.
+    pub fn events(&self) -> usize {
+        self.events
+    }
+    /// Return the mean fraction of the anode wires with a signal in each event.
+    /// Returns [`None`] if no events have been added.
+    pub fn wire_occupancy(&self) -> Option<f64> {
+        (self.events > 0).then(|| self.wire_signals as f64 / (self.events * TPC_ANODE_WIRES) as f64)
+    }
+    /// Return the fraction of events with at least one pad signal. Returns
+    /// [`None`] if no events have been added.
+    pub fn pad_fraction(&self) -> Option<f64> {
+        (self.events > 0).then(|| self.pad_events as f64 / self.events as f64)
+    }
+    /// Return an error if both the wire occupancy and the fraction of events
+    /// with a pad signal are below the given thresholds.
+    ///
+    /// Runs taken with the TPC high voltage off still decode fine, but there
+    /// is nothing to reconstruct. Nothing is checked if no events have been
+    /// added.
+    pub fn ensure_signal(&self, thresholds: &TpcSignalThresholds) -> Result<(), NoTpcSignalError> {
+        let (Some(wire_occupancy), Some(pad_fraction)) =
+            (self.wire_occupancy(), self.pad_fraction())
+        else {
+            return Ok(());
+        };
+        if wire_occupancy < thresholds.wire_occupancy && pad_fraction < thresholds.pad_fraction {
+            Err(NoTpcSignalError {
+                events: self.events,
+                wire_occupancy,
+                pad_fraction,
+            })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
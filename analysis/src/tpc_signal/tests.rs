@@ -0,0 +1,80 @@
+use super::*;
+
+// Event with a couple of tracks.
+fn add_normal_event(check: &mut TpcSignalCheck) {
+    check.add(12, 150);
+}
+
+// Event with the TPC HV off; just the occasional noisy channel.
+fn add_empty_event(check: &mut TpcSignalCheck, noisy_wire: bool) {
+    check.add(usize::from(noisy_wire), 0);
+}
+
+#[test]
+fn tpc_signal_check_no_events() {
+    let check = TpcSignalCheck::default();
+
+    assert_eq!(check.events(), 0);
+    assert_eq!(check.wire_occupancy(), None);
+    assert_eq!(check.pad_fraction(), None);
+    assert!(check.ensure_signal(&TpcSignalThresholds::default()).is_ok());
+}
+
+#[test]
+fn tpc_signal_check_normal_events() {
+    let mut check = TpcSignalCheck::default();
+    for i in 0..100 {
+        if i % 4 == 0 {
+            add_empty_event(&mut check, false);
+        } else {
+            add_normal_event(&mut check);
+        }
+    }
+
+    assert_eq!(check.events(), 100);
+    assert_eq!(check.wire_occupancy(), Some(75.0 * 12.0 / (100.0 * 256.0)));
+    assert_eq!(check.pad_fraction(), Some(0.75));
+    assert!(check.ensure_signal(&TpcSignalThresholds::default()).is_ok());
+}
+
+#[test]
+fn tpc_signal_check_empty_events() {
+    let mut check = TpcSignalCheck::default();
+    for i in 0..100 {
+        add_empty_event(&mut check, i % 10 == 0);
+    }
+
+    assert_eq!(check.wire_occupancy(), Some(10.0 / (100.0 * 256.0)));
+    assert_eq!(check.pad_fraction(), Some(0.0));
+    let error = check
+        .ensure_signal(&TpcSignalThresholds::default())
+        .unwrap_err();
+    assert!(error
+        .to_string()
+        .starts_with("run appears to have no TPC signal (HV off?)"));
+    assert!(error.to_string().contains("of 100 events"));
+}
+
+#[test]
+fn tpc_signal_check_both_thresholds() {
+    // Pads without wires (e.g. only the wire HV is off) still has signal.
+    let mut check = TpcSignalCheck::default();
+    for _ in 0..100 {
+        check.add(0, 20);
+    }
+    assert!(check.ensure_signal(&TpcSignalThresholds::default()).is_ok());
+    // Same for wires without pads.
+    let mut check = TpcSignalCheck::default();
+    for _ in 0..100 {
+        check.add(10, 0);
+    }
+    assert!(check.ensure_signal(&TpcSignalThresholds::default()).is_ok());
+}
+
+#[test]
+fn tpc_signal_thresholds_default() {
+    let thresholds = TpcSignalThresholds::default();
+
+    assert_eq!(thresholds.wire_occupancy, 0.01);
+    assert_eq!(thresholds.pad_fraction, 0.1);
+}
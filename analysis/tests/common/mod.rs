@@ -0,0 +1,104 @@
+// MIDAS fixtures shared by the integration tests. Every test file is compiled
+// as its own crate, and not all of them use every fixture.
+#![allow(dead_code)]
+
+use alpha_g_analysis::midas::{Bank, BankFormat, Event, MidasWriter};
+use std::path::{Path, PathBuf};
+
+pub const RUN_NUMBER: u32 = 11186;
+// Number of events in a `main_run`.
+pub const NUM_EVENTS: u32 = 10;
+// Valid TRG packet.
+pub const TRG_PACKET: [u8; 84] = [
+    255, 0, 0, 0, 0, 0, 0, 128, 254, 0, 0, 0, 0, 0, 0, 0, 3, 0, 0, 0, 0, 0, 0, 0, 5, 0, 0, 0, 6, 0,
+    0, 0, 7, 0, 0, 0, 8, 0, 0, 128, 2, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 9, 0, 10, 0, 11, 0, 0, 0,
+    0, 0, 0, 0, 12, 0, 0, 0, 13, 0, 0, 0, 14, 0, 0, 0, 0, 0, 0, 224,
+];
+
+// MIDAS main event with the given 16-bit banks.
+pub fn main_event(serial_number: u32, timestamp: u32, banks: Vec<Bank>) -> Event {
+    Event {
+        id: 1,
+        trigger_mask: 0,
+        serial_number,
+        timestamp,
+        bank_format: BankFormat::Bank16,
+        banks,
+    }
+}
+
+// MIDAS main event with either a single TRG bank or no banks at all.
+pub fn trg_event(serial_number: u32, with_trg: bool) -> Event<'static> {
+    let banks = if with_trg {
+        vec![Bank {
+            name: *b"ATAT",
+            data_type: 6,
+            data: &TRG_PACKET,
+        }]
+    } else {
+        Vec::new()
+    };
+
+    main_event(serial_number, 100, banks)
+}
+
+// Write a MIDAS file of `RUN_NUMBER` that starts and ends at the given
+// timestamps.
+pub fn midas_file<'a>(
+    path: &Path,
+    timestamps: (u32, u32),
+    events: impl IntoIterator<Item = Event<'a>>,
+) {
+    midas_file_with_odb(path, timestamps, b"{}", events);
+}
+
+// Same as `midas_file`, but with the given initial and final ODB dumps.
+pub fn midas_file_with_odb<'a>(
+    path: &Path,
+    timestamps: (u32, u32),
+    odb: &[u8],
+    events: impl IntoIterator<Item = Event<'a>>,
+) {
+    let mut writer = MidasWriter::new(Vec::new(), RUN_NUMBER, timestamps.0, odb).unwrap();
+    for event in events {
+        writer.write_event(&event).unwrap();
+    }
+    std::fs::write(path, writer.finish(timestamps.1, odb).unwrap()).unwrap();
+}
+
+// Write a subrun file with a TRG main event for each serial number.
+pub fn subrun(dir: &Path, name: &str, timestamps: (u32, u32), serial_numbers: &[u32]) -> PathBuf {
+    let path = dir.join(name);
+    midas_file(
+        &path,
+        timestamps,
+        serial_numbers.iter().map(|&n| trg_event(n, true)),
+    );
+
+    path
+}
+
+// Write a run (single MIDAS file) with `NUM_EVENTS` TRG main events.
+pub fn main_run(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!(
+        "alpha_g_analysis_{}_{name}.mid",
+        std::process::id()
+    ));
+    midas_file(
+        &path,
+        (100, 101),
+        (0..NUM_EVENTS).map(|n| trg_event(n, true)),
+    );
+
+    path
+}
+
+// Write a MIDAS file with a main event for each serial number, in which every
+// third event is missing its TRG bank.
+pub fn missing_trg_file(path: &Path, timestamps: (u32, u32), serial_numbers: std::ops::Range<u32>) {
+    midas_file(
+        path,
+        timestamps,
+        serial_numbers.map(|n| trg_event(n, n % 3 != 1)),
+    );
+}
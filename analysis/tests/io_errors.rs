@@ -0,0 +1,48 @@
+use alpha_g_analysis::cli::NOT_FOUND_EXIT_CODE;
+use assert_cmd::Command;
+
+#[test]
+fn nonexistent_input_file() {
+    let input = std::env::temp_dir().join(format!(
+        "alpha_g_analysis_{}_nonexistent/run00005sub000.mid",
+        std::process::id()
+    ));
+
+    for bin in ["alpha-g-odb", "alpha-g-trg-scalers", "alpha-g-vertices"] {
+        let result = Command::cargo_bin(bin)
+            .unwrap()
+            .arg(&input)
+            .output()
+            .unwrap();
+        assert_eq!(result.status.code(), Some(NOT_FOUND_EXIT_CODE));
+        assert!(String::from_utf8_lossy(&result.stderr).contains(&input.display().to_string()));
+    }
+}
+
+#[test]
+fn nonexistent_csv_input() {
+    let dir = std::env::temp_dir().join(format!(
+        "alpha_g_analysis_{}_nonexistent_csv",
+        std::process::id()
+    ));
+    let input = dir.join("vertices.csv");
+    let other = dir.join("other.csv");
+
+    for (bin, output) in [
+        ("alpha-g-apply-patch", vec![dir.join("merged")]),
+        (
+            "alpha-g-qa-compare",
+            vec!["--output".into(), dir.join("qa_compare")],
+        ),
+    ] {
+        let result = Command::cargo_bin(bin)
+            .unwrap()
+            .arg(&input)
+            .arg(&other)
+            .args(output)
+            .output()
+            .unwrap();
+        assert_eq!(result.status.code(), Some(NOT_FOUND_EXIT_CODE));
+        assert!(String::from_utf8_lossy(&result.stderr).contains(&input.display().to_string()));
+    }
+}
@@ -0,0 +1,59 @@
+use assert_cmd::Command;
+use common::subrun;
+use std::path::Path;
+
+mod common;
+
+#[test]
+fn alpha_g_vertices_mirror_requires_mirror_output() {
+    let dir = std::env::temp_dir().join(format!(
+        "alpha_g_analysis_{}_mirror_output",
+        std::process::id()
+    ));
+    std::fs::create_dir_all(&dir).unwrap();
+    let input = subrun(&dir, "sub000.mid", (100, 110), &[0, 1]);
+
+    for output in [None, Some("-"), Some("R11186_vertices")] {
+        let mut command = Command::cargo_bin("alpha-g-vertices").unwrap();
+        command.arg(&input).args(["--mirror", "phi"]);
+        if let Some(output) = output {
+            command.arg("--output").arg(output);
+        }
+        let result = command.output().unwrap();
+        assert!(!result.status.success());
+        assert!(String::from_utf8_lossy(&result.stderr)
+            .contains("`--mirror` requires an `--output` with `mirror` in its name"));
+    }
+    assert!(!Path::new("R11186_vertices.csv").exists());
+}
+
+#[test]
+fn alpha_g_vertices_mirror() {
+    let dir = std::env::temp_dir().join(format!("alpha_g_analysis_{}_mirror", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let result = Command::cargo_bin("alpha-g-vertices")
+        .unwrap()
+        .arg(subrun(&dir, "sub000.mid", (100, 110), &[0, 1]))
+        .args(["--mirror", "z", "--output"])
+        .arg(dir.join("vertices_mirror_z"))
+        .output()
+        .unwrap();
+    assert!(result.status.success());
+    assert!(String::from_utf8_lossy(&result.stderr).contains("Warning: mirroring z -> -z"));
+
+    let output = std::fs::read_to_string(dir.join("vertices_mirror_z.csv")).unwrap();
+    assert!(output.contains("# WARNING: mirrored reconstruction (z -> -z), not for physics"));
+}
+
+#[test]
+fn alpha_g_vertices_mirror_conflicts_with_spacepoints() {
+    let result = Command::cargo_bin("alpha-g-vertices")
+        .unwrap()
+        .args(["run.mid", "--mirror", "phi", "--spacepoints"])
+        .args(["--output", "vertices_mirror_phi"])
+        .output()
+        .unwrap();
+    assert!(!result.status.success());
+    assert!(String::from_utf8_lossy(&result.stderr).contains("cannot be used with"));
+}
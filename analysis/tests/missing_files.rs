@@ -0,0 +1,383 @@
+use assert_cmd::Command;
+use common::{midas_file_with_odb, subrun, trg_event};
+use std::path::{Path, PathBuf};
+
+mod common;
+
+// Run with the file between these two missing.
+fn run_with_gap(dir: &Path) -> [PathBuf; 2] {
+    [
+        subrun(dir, "sub000.mid", (100, 110), &[0, 1]),
+        subrun(dir, "sub002.mid", (150, 160), &[4, 5]),
+    ]
+}
+
+#[test]
+fn alpha_g_vertices_missing_file() {
+    let dir = std::env::temp_dir().join(format!(
+        "alpha_g_analysis_{}_missing_file",
+        std::process::id()
+    ));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let result = Command::cargo_bin("alpha-g-vertices")
+        .unwrap()
+        .args(run_with_gap(&dir))
+        .arg("--output")
+        .arg(dir.join("vertices"))
+        .output()
+        .unwrap();
+    assert!(!result.status.success());
+    assert!(String::from_utf8_lossy(&result.stderr).contains("missing file before"));
+}
+
+#[test]
+fn alpha_g_vertices_allow_gaps() {
+    let dir = std::env::temp_dir().join(format!(
+        "alpha_g_analysis_{}_allow_gaps",
+        std::process::id()
+    ));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let result = Command::cargo_bin("alpha-g-vertices")
+        .unwrap()
+        .args(run_with_gap(&dir))
+        .arg("--output")
+        .arg(dir.join("vertices"))
+        .arg("--allow-gaps")
+        .output()
+        .unwrap();
+    assert!(result.status.success());
+    assert!(String::from_utf8_lossy(&result.stderr).contains("Warning: missing file before"));
+
+    let output = std::fs::read_to_string(dir.join("vertices.csv")).unwrap();
+    assert!(output.contains("# WARNING: 1 missing files"));
+    let records: Vec<_> = output
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .collect();
+    assert!(records[0].ends_with(",exact_time"));
+    let exact_time: Vec<_> = records[1..]
+        .iter()
+        .map(|record| record.rsplit(',').next().unwrap())
+        .collect();
+    assert_eq!(exact_time, ["true", "true", "false", "false"]);
+    // The `trg_time` jumps by the 40 s between the two files.
+    let trg_time: Vec<_> = records[1..]
+        .iter()
+        .map(|record| record.split(',').nth(1).unwrap())
+        .collect();
+    assert_eq!(trg_time, ["0.0", "0.0", "40.0", "40.0"]);
+}
+
+#[test]
+fn alpha_g_vertices_contiguous_files() {
+    let dir = std::env::temp_dir().join(format!(
+        "alpha_g_analysis_{}_contiguous_files",
+        std::process::id()
+    ));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let result = Command::cargo_bin("alpha-g-vertices")
+        .unwrap()
+        .arg(subrun(&dir, "sub000.mid", (100, 110), &[0, 1]))
+        .arg(subrun(&dir, "sub001.mid", (110, 120), &[2, 3, 4]))
+        .arg(subrun(&dir, "sub002.mid", (120, 130), &[5]))
+        .arg("--output")
+        .arg(dir.join("vertices"))
+        .output()
+        .unwrap();
+    assert!(result.status.success());
+    // Rows are written to a temporary file while processing the run.
+    assert!(!dir.join("vertices.csv.partial").exists());
+
+    let output = std::fs::read_to_string(dir.join("vertices.csv")).unwrap();
+    let (header, records): (Vec<_>, Vec<_>) =
+        output.lines().partition(|line| line.starts_with('#'));
+    // The header is written after processing all the files, but it is still
+    // at the top of the output.
+    assert!(output
+        .lines()
+        .take(header.len())
+        .all(|line| line.starts_with('#')));
+    assert!(records[0].starts_with("serial_number,trg_time,"));
+    let serial_numbers: Vec<_> = records[1..]
+        .iter()
+        .map(|record| record.split(',').next().unwrap())
+        .collect();
+    assert_eq!(serial_numbers, ["0", "1", "2", "3", "4", "5"]);
+    // All the events have the same TRG packet.
+    assert!(records[1..]
+        .iter()
+        .all(|record| record.split(',').nth(1) == Some("0.0")));
+}
+
+// Output without the command line in the provenance header (i.e. the second
+// line), which is different for each invocation.
+fn without_arguments(path: &Path) -> String {
+    let output = std::fs::read_to_string(path).unwrap();
+    let mut lines: Vec<_> = output.split_inclusive('\n').collect();
+    assert!(lines[1].starts_with("# "));
+    lines.remove(1);
+
+    lines.concat()
+}
+
+#[test]
+fn alpha_g_vertices_prefetch_files() {
+    let dir = std::env::temp_dir().join(format!(
+        "alpha_g_analysis_{}_prefetch_files",
+        std::process::id()
+    ));
+    std::fs::create_dir_all(&dir).unwrap();
+    let files = [
+        subrun(&dir, "sub000.mid", (100, 110), &[0, 1]),
+        subrun(&dir, "sub001.mid", (110, 120), &[2, 3, 4]),
+        subrun(&dir, "sub002.mid", (120, 130), &[5]),
+        subrun(&dir, "sub003.mid", (130, 140), &[6, 7]),
+    ];
+
+    let mut outputs = Vec::new();
+    for k in ["1", "2", "3", "10"] {
+        let output = dir.join(format!("vertices_{k}"));
+        Command::cargo_bin("alpha-g-vertices")
+            .unwrap()
+            .args(&files)
+            .args(["--prefetch-files", k, "--output"])
+            .arg(&output)
+            .assert()
+            .success();
+        outputs.push(without_arguments(&output.with_extension("csv")));
+    }
+    // Same as without the option.
+    let output = dir.join("vertices");
+    Command::cargo_bin("alpha-g-vertices")
+        .unwrap()
+        .args(&files)
+        .arg("--output")
+        .arg(&output)
+        .assert()
+        .success();
+    let expected = without_arguments(&output.with_extension("csv"));
+
+    assert!(expected.contains("\n7,"));
+    for output in outputs {
+        assert_eq!(output, expected);
+    }
+}
+
+#[test]
+fn alpha_g_vertices_prefetch_files_zero() {
+    let result = Command::cargo_bin("alpha-g-vertices")
+        .unwrap()
+        .arg("run.mid")
+        .args(["--prefetch-files", "0"])
+        .output()
+        .unwrap();
+    assert!(!result.status.success());
+    assert!(
+        String::from_utf8_lossy(&result.stderr).contains("`--prefetch-files` must be at least 1")
+    );
+}
+
+#[test]
+fn alpha_g_vertices_allow_gaps_conflicts_with_scalers_output() {
+    let result = Command::cargo_bin("alpha-g-vertices")
+        .unwrap()
+        .args(["run.mid", "--allow-gaps", "--scalers-output", "scalers"])
+        .output()
+        .unwrap();
+    assert!(!result.status.success());
+    assert!(String::from_utf8_lossy(&result.stderr).contains("cannot be used with"));
+}
+
+// Run in which the middle file is truncated to its first `length` bytes.
+fn run_with_truncated_file(dir: &Path, length: usize) -> [PathBuf; 3] {
+    let truncated = subrun(dir, "sub001.mid", (110, 150), &[2, 3]);
+    let contents = std::fs::read(&truncated).unwrap();
+    std::fs::write(&truncated, &contents[..length]).unwrap();
+
+    [
+        subrun(dir, "sub000.mid", (100, 110), &[0, 1]),
+        truncated,
+        subrun(dir, "sub002.mid", (150, 160), &[4, 5]),
+    ]
+}
+
+#[test]
+fn alpha_g_vertices_unreadable_file() {
+    let dir = std::env::temp_dir().join(format!(
+        "alpha_g_analysis_{}_unreadable_file",
+        std::process::id()
+    ));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let result = Command::cargo_bin("alpha-g-vertices")
+        .unwrap()
+        .args(run_with_truncated_file(&dir, 4))
+        .arg("--output")
+        .arg(dir.join("vertices"))
+        .output()
+        .unwrap();
+    assert!(!result.status.success());
+    assert!(String::from_utf8_lossy(&result.stderr).contains("sub001.mid"));
+    assert!(!dir.join("vertices.csv").exists());
+}
+
+#[test]
+fn alpha_g_vertices_skip_unreadable() {
+    // Truncated within the header (skipped before processing the run), and
+    // within the events (skipped while processing the run).
+    for length in [4, 60] {
+        let dir = std::env::temp_dir().join(format!(
+            "alpha_g_analysis_{}_skip_unreadable_{length}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        let files = run_with_truncated_file(&dir, length);
+
+        let result = Command::cargo_bin("alpha-g-vertices")
+            .unwrap()
+            .args(&files)
+            .arg("--output")
+            .arg(dir.join("vertices"))
+            .arg("--error-summary")
+            .arg(dir.join("errors.json"))
+            .arg("--skip-unreadable")
+            .output()
+            .unwrap();
+        assert!(result.status.success());
+        let stderr = String::from_utf8_lossy(&result.stderr);
+        assert!(stderr.contains(&format!(
+            "Warning: skipping unreadable file `{}`",
+            files[1].display()
+        )));
+
+        let output = std::fs::read_to_string(dir.join("vertices.csv")).unwrap();
+        assert!(output.contains(&format!(
+            "# WARNING: skipped unreadable file {}\n",
+            files[1].display()
+        )));
+        assert!(output.contains("# WARNING: 1 missing files"));
+        assert!(output.contains("# WARNING: missing data from 110 to 150\n"));
+        let records: Vec<_> = output
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .collect();
+        assert!(records[0].ends_with(",exact_time"));
+        let exact_time: Vec<_> = records[1..]
+            .iter()
+            .map(|record| record.rsplit(',').next().unwrap())
+            .collect();
+        assert_eq!(exact_time, ["true", "true", "false", "false"]);
+
+        let summary: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(dir.join("errors.json")).unwrap())
+                .unwrap();
+        assert_eq!(
+            summary["skipped_files"],
+            serde_json::json!([files[1].display().to_string()])
+        );
+        assert_eq!(
+            summary["gaps"],
+            serde_json::json!([{"start": 110, "end": 150}])
+        );
+        // Skipped files are not inputs of the output.
+        let inputs: Vec<_> = summary["inputs"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|input| input["path"].as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(
+            inputs,
+            [
+                files[0].display().to_string(),
+                files[2].display().to_string()
+            ]
+        );
+        for file in [&files[0], &files[2]] {
+            let size = std::fs::metadata(file).unwrap().len();
+            assert!(output.contains(&format!(" bytes, {}\n", file.display())));
+            assert!(output.contains(&format!(", {size} bytes, ")));
+        }
+        assert_eq!(output.matches("# input xxh64 ").count(), 2);
+    }
+}
+
+#[test]
+fn alpha_g_vertices_skip_unreadable_last_file() {
+    let dir = std::env::temp_dir().join(format!(
+        "alpha_g_analysis_{}_skip_unreadable_last_file",
+        std::process::id()
+    ));
+    std::fs::create_dir_all(&dir).unwrap();
+    let odb = br#"{"Equipment": {"CTRL": {"Settings": {"TRG": {"Scaledown": 1}}}}}"#;
+    let files = [
+        dir.join("sub000.mid"),
+        dir.join("sub001.mid"),
+        dir.join("sub002.mid"),
+    ];
+    for (index, file) in files.iter().enumerate() {
+        let start = 100 + 10 * index as u32;
+        let serial_numbers = 2 * index as u32..2 * index as u32 + 2;
+        midas_file_with_odb(
+            file,
+            (start, start + 10),
+            odb,
+            serial_numbers.map(|n| trg_event(n, true)),
+        );
+    }
+    // Truncated within the events of the last file.
+    let contents = std::fs::read(&files[2]).unwrap();
+    std::fs::write(&files[2], &contents[..contents.len() - 60]).unwrap();
+
+    let result = Command::cargo_bin("alpha-g-vertices")
+        .unwrap()
+        .args(&files)
+        .arg("--output")
+        .arg(dir.join("vertices"))
+        .args(["--skip-unreadable", "--weights"])
+        .output()
+        .unwrap();
+    assert!(result.status.success());
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert!(stderr.contains(&format!(
+        "Warning: skipping unreadable file `{}`",
+        files[2].display()
+    )));
+    // The final ODB of the second file is used instead.
+    assert!(!stderr.contains("skipping ODB settings check"));
+
+    let output = std::fs::read_to_string(dir.join("vertices.csv")).unwrap();
+    let serial_numbers: Vec<_> = output
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .skip(1)
+        .map(|record| record.split(',').next().unwrap())
+        .collect();
+    assert_eq!(serial_numbers, ["0", "1", "2", "3"]);
+}
+
+#[test]
+fn alpha_g_vertices_skip_unreadable_other_gap() {
+    let dir = std::env::temp_dir().join(format!(
+        "alpha_g_analysis_{}_skip_unreadable_other_gap",
+        std::process::id()
+    ));
+    std::fs::create_dir_all(&dir).unwrap();
+    let mut files = run_with_truncated_file(&dir, 4).to_vec();
+    // Nothing was skipped to explain this second gap.
+    files.push(subrun(&dir, "sub004.mid", (200, 210), &[8, 9]));
+
+    let result = Command::cargo_bin("alpha-g-vertices")
+        .unwrap()
+        .args(&files)
+        .arg("--output")
+        .arg(dir.join("vertices"))
+        .arg("--skip-unreadable")
+        .output()
+        .unwrap();
+    assert!(!result.status.success());
+    assert!(String::from_utf8_lossy(&result.stderr).contains("missing file before"));
+}
@@ -0,0 +1,109 @@
+use alpha_g_analysis::cli::NO_MAIN_EVENTS_EXIT_CODE;
+use assert_cmd::Command;
+use std::path::PathBuf;
+
+const RUN_NUMBER: u32 = 11186;
+
+// MIDAS event with a single 16-bit bank of `u32` data.
+fn midas_event(id: u16, serial_number: u32, timestamp: u32, bank_name: &[u8; 4]) -> Vec<u8> {
+    let mut bank = bank_name.to_vec();
+    bank.extend_from_slice(&6u16.to_le_bytes());
+    bank.extend_from_slice(&4u16.to_le_bytes());
+    bank.extend_from_slice(&serial_number.to_le_bytes());
+    // Banks are padded to 8 bytes.
+    bank.extend_from_slice(&[0; 4]);
+
+    let mut event = id.to_le_bytes().to_vec();
+    event.extend_from_slice(&0u16.to_le_bytes());
+    event.extend_from_slice(&serial_number.to_le_bytes());
+    event.extend_from_slice(&timestamp.to_le_bytes());
+    event.extend_from_slice(&(8 + bank.len() as u32).to_le_bytes());
+    event.extend_from_slice(&(bank.len() as u32).to_le_bytes());
+    event.extend_from_slice(&1u32.to_le_bytes());
+    event.extend_from_slice(&bank);
+
+    event
+}
+
+// Write a run (single MIDAS file) with only Chronobox and Sequencer events.
+fn chronobox_only_run(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!(
+        "alpha_g_analysis_{}_{name}.mid",
+        std::process::id()
+    ));
+    let mut contents = vec![0x00, 0x80, 0x4D, 0x49];
+    contents.extend_from_slice(&RUN_NUMBER.to_le_bytes());
+    contents.extend_from_slice(&100u32.to_le_bytes());
+    contents.extend_from_slice(&0u32.to_le_bytes());
+    for serial_number in 0..3 {
+        contents.extend(midas_event(4, serial_number, 100, b"cbf0"));
+    }
+    contents.extend(midas_event(8, 0, 101, b"SEQ2"));
+    contents.extend_from_slice(&[0x01, 0x80, 0x4D, 0x49]);
+    contents.extend_from_slice(&RUN_NUMBER.to_le_bytes());
+    contents.extend_from_slice(&101u32.to_le_bytes());
+    contents.extend_from_slice(&0u32.to_le_bytes());
+    std::fs::write(&path, contents).unwrap();
+
+    path
+}
+
+fn assert_no_main_events(bin: &str) {
+    let input = chronobox_only_run(bin);
+    let output = input.with_extension("csv");
+
+    let result = Command::cargo_bin(bin)
+        .unwrap()
+        .arg(&input)
+        .arg("--output")
+        .arg(&output)
+        .output()
+        .unwrap();
+    assert_eq!(result.status.code(), Some(NO_MAIN_EVENTS_EXIT_CODE));
+    assert!(String::from_utf8_lossy(&result.stderr)
+        .contains("run contains no Main events (found 3 Chronobox, 1 Sequencer events)"));
+    // No (empty) output should be created.
+    assert!(!output.exists());
+}
+
+#[test]
+fn alpha_g_vertices_no_main_events() {
+    assert_no_main_events("alpha-g-vertices");
+}
+
+#[test]
+fn alpha_g_pad_noise_statistics_no_main_events() {
+    assert_no_main_events("alpha-g-pad-noise-statistics");
+}
+
+#[test]
+fn alpha_g_bank_sizes_no_main_events() {
+    assert_no_main_events("alpha-g-bank-sizes");
+}
+
+#[test]
+fn alpha_g_cosmics_no_main_events() {
+    assert_no_main_events("alpha-g-cosmics");
+}
+
+#[test]
+fn alpha_g_vertices_no_main_events_no_side_outputs() {
+    let input = chronobox_only_run("vertices_side_outputs");
+    let dir = input.with_extension("d");
+    std::fs::create_dir_all(&dir).unwrap();
+    let scalers = dir.join("scalers.csv");
+
+    let result = Command::cargo_bin("alpha-g-vertices")
+        .unwrap()
+        .current_dir(&dir)
+        .arg(&input)
+        .arg("--spacepoints")
+        .arg("--scalers-output")
+        .arg(&scalers)
+        .output()
+        .unwrap();
+    assert_eq!(result.status.code(), Some(NO_MAIN_EVENTS_EXIT_CODE));
+    assert!(!scalers.exists());
+    assert!(!dir.join(format!("R{RUN_NUMBER}_spacepoints.csv")).exists());
+    assert!(!dir.join(format!("R{RUN_NUMBER}_vertices.csv")).exists());
+}
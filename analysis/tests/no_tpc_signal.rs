@@ -0,0 +1,59 @@
+use alpha_g_analysis::cli::NO_TPC_SIGNAL_EXIT_CODE;
+use assert_cmd::Command;
+use common::{main_run, NUM_EVENTS};
+
+mod common;
+
+#[test]
+fn alpha_g_vertices_no_tpc_signal() {
+    let input = main_run("no_tpc_signal");
+    let output = input.with_extension("csv");
+
+    let result = Command::cargo_bin("alpha-g-vertices")
+        .unwrap()
+        .arg(&input)
+        .args(["--signal-check-events", "5", "--output"])
+        .arg(&output)
+        .output()
+        .unwrap();
+    assert_eq!(result.status.code(), Some(NO_TPC_SIGNAL_EXIT_CODE));
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert!(stderr.contains("run appears to have no TPC signal (HV off?)"));
+    assert!(stderr.contains("of 5 events"));
+    assert!(stderr.contains("--force"));
+    // No (empty) output should be created.
+    assert!(!output.exists());
+}
+
+#[test]
+fn alpha_g_vertices_no_tpc_signal_force() {
+    let input = main_run("no_tpc_signal_force");
+    let output = input.with_extension("csv");
+
+    Command::cargo_bin("alpha-g-vertices")
+        .unwrap()
+        .arg(&input)
+        .args(["--signal-check-events", "5", "--force", "--output"])
+        .arg(&output)
+        .assert()
+        .success();
+    assert!(output.exists());
+}
+
+#[test]
+fn alpha_g_vertices_no_tpc_signal_too_few_events() {
+    let input = main_run("no_tpc_signal_too_few_events");
+    let output = input.with_extension("csv");
+
+    // Not enough main events to tell.
+    Command::cargo_bin("alpha-g-vertices")
+        .unwrap()
+        .arg(&input)
+        .arg("--signal-check-events")
+        .arg((NUM_EVENTS + 1).to_string())
+        .arg("--output")
+        .arg(&output)
+        .assert()
+        .success();
+    assert!(output.exists());
+}
@@ -0,0 +1,154 @@
+use arrow_array::cast::AsArray;
+use arrow_array::types::{Float64Type, UInt32Type};
+use arrow_array::Array;
+use arrow_schema::DataType;
+use assert_cmd::Command;
+use common::{main_run, subrun, NUM_EVENTS};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use std::fs::File;
+
+mod common;
+
+#[test]
+fn alpha_g_vertices_parquet_output() {
+    let input = main_run("vertices_parquet");
+    let dir = std::env::temp_dir().join(format!(
+        "alpha_g_analysis_{}_vertices_parquet",
+        std::process::id()
+    ));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let result = Command::cargo_bin("alpha-g-vertices")
+        .unwrap()
+        .arg(&input)
+        .args(["--output", "-"])
+        .output()
+        .unwrap();
+    assert!(result.status.success());
+    let result_csv_stdout = result.stdout;
+    let mut rdr = csv::ReaderBuilder::new()
+        .comment(Some(b'#'))
+        .from_reader(&result_csv_stdout[..]);
+    let csv_rows: Vec<csv::StringRecord> = rdr.records().map(Result::unwrap).collect();
+    assert_eq!(csv_rows.len(), NUM_EVENTS as usize);
+
+    let output = dir.join("vertices");
+    let result = Command::cargo_bin("alpha-g-vertices")
+        .unwrap()
+        .arg(&input)
+        .args(["--format", "parquet", "--output"])
+        .arg(&output)
+        .output()
+        .unwrap();
+    assert!(result.status.success());
+
+    let output = output.with_extension("parquet");
+    let builder = ParquetRecordBatchReaderBuilder::try_new(File::open(&output).unwrap()).unwrap();
+    let metadata = builder
+        .metadata()
+        .file_metadata()
+        .key_value_metadata()
+        .unwrap()
+        .clone();
+    assert_eq!(metadata.len(), 1);
+    assert_eq!(metadata[0].key, "alpha_g_provenance");
+    // Same lines as the CSV header.
+    let csv_header: Vec<_> = String::from_utf8_lossy(&result_csv_stdout)
+        .lines()
+        .filter_map(|line| line.strip_prefix("# "))
+        .skip(2)
+        .map(String::from)
+        .collect();
+    let provenance: Vec<_> = metadata[0].value.as_deref().unwrap().lines().collect();
+    assert_eq!(
+        provenance[0],
+        format!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"))
+    );
+    assert_eq!(provenance[2..], csv_header);
+    assert_eq!(builder.metadata().num_row_groups(), 1);
+
+    let batches: Vec<_> = builder.build().unwrap().map(Result::unwrap).collect();
+    assert_eq!(batches.len(), 1);
+    let batch = &batches[0];
+    assert_eq!(batch.num_rows(), NUM_EVENTS as usize);
+
+    let column = |name: &str| batch.column(batch.schema().index_of(name).unwrap()).clone();
+    let serial_number = column("serial_number");
+    let serial_number = serial_number.as_primitive::<UInt32Type>();
+    let trg_time = column("trg_time");
+    let trg_time = trg_time.as_primitive::<Float64Type>();
+    let reconstructed_x = column("reconstructed_x");
+    let reconstructed_x = reconstructed_x.as_primitive::<Float64Type>();
+    for (i, record) in csv_rows.iter().enumerate() {
+        assert_eq!(serial_number.value(i).to_string(), &record[0]);
+        if record[1].is_empty() {
+            assert!(trg_time.is_null(i));
+        } else {
+            assert_eq!(trg_time.value(i), record[1].parse::<f64>().unwrap());
+        }
+        // Events with only a TRG bank have no vertex.
+        assert!(record[2].is_empty());
+        assert!(reconstructed_x.is_null(i));
+    }
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn alpha_g_vertices_parquet_row_group_per_file() {
+    let dir = std::env::temp_dir().join(format!(
+        "alpha_g_analysis_{}_vertices_parquet_row_groups",
+        std::process::id()
+    ));
+    std::fs::create_dir_all(&dir).unwrap();
+    let inputs = [
+        subrun(&dir, "sub000.mid", (100, 110), &[0, 1]),
+        subrun(&dir, "sub001.mid", (110, 120), &[2, 3, 4]),
+        subrun(&dir, "sub002.mid", (120, 130), &[5]),
+    ];
+
+    let output = dir.join("vertices");
+    let result = Command::cargo_bin("alpha-g-vertices")
+        .unwrap()
+        .args(&inputs)
+        .arg("--diagnostics")
+        .args(["--format", "parquet", "--output"])
+        .arg(&output)
+        .output()
+        .unwrap();
+    assert!(result.status.success());
+
+    let file = File::open(output.with_extension("parquet")).unwrap();
+    let builder = ParquetRecordBatchReaderBuilder::try_new(file).unwrap();
+    // Declared types, even if these columns only have nulls (no event has a
+    // TPC bank).
+    let schema = builder.schema();
+    let data_type = |name: &str| schema.field_with_name(name).unwrap().data_type().clone();
+    assert_eq!(data_type("reconstructed_x"), DataType::Float64);
+    assert_eq!(data_type("no_vertex_reason"), DataType::Utf8);
+    assert_eq!(data_type("num_tracks"), DataType::UInt64);
+    let metadata = builder.metadata();
+    assert_eq!(metadata.num_row_groups(), inputs.len());
+    let num_rows: Vec<_> = metadata
+        .row_groups()
+        .iter()
+        .map(|rg| rg.num_rows())
+        .collect();
+    assert_eq!(num_rows, [2, 3, 1]);
+    // Statistics only for the columns used to select row groups.
+    let row_group = metadata.row_group(0);
+    let has_statistics = |name: &str| {
+        row_group
+            .columns()
+            .iter()
+            .find(|column| column.column_path().string() == name)
+            .unwrap()
+            .statistics()
+            .is_some()
+    };
+    assert!(has_statistics("serial_number"));
+    assert!(has_statistics("trg_time"));
+    assert!(!has_statistics("reconstructed_x"));
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
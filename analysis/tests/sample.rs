@@ -0,0 +1,142 @@
+use alpha_g_analysis::midas::Bank;
+use assert_cmd::Command;
+use common::{main_event, midas_file, RUN_NUMBER};
+use std::path::PathBuf;
+
+mod common;
+
+const BANK_NAMES: [&[u8; 4]; 4] = [b"B09A", b"PC00", b"ATAT", b"XXXX"];
+
+// Write a run (single MIDAS file) with 1000 main events. Each event has a
+// bank of `u32` data (its serial number) for each of `BANK_NAMES`.
+fn main_run(dir: &PathBuf) -> PathBuf {
+    let path = dir.join("run11186sub000.mid");
+    let data: Vec<_> = (0..1000u32).map(u32::to_le_bytes).collect();
+    let events = (0..1000).zip(&data).map(|(serial_number, data)| {
+        let banks = BANK_NAMES
+            .iter()
+            .map(|&&name| Bank {
+                name,
+                data_type: 6,
+                data,
+            })
+            .collect();
+        main_event(serial_number, 100 + serial_number / 100, banks)
+    });
+    midas_file(&path, (100, 110), events);
+
+    path
+}
+
+// Run `alpha-g-sample` and return the serial numbers in the manifest.
+fn sample(input: &PathBuf, output: &PathBuf, args: &[&str]) -> Vec<u32> {
+    Command::cargo_bin("alpha-g-sample")
+        .unwrap()
+        .arg(input)
+        .arg("--output")
+        .arg(output)
+        .args(args)
+        .assert()
+        .success();
+
+    let manifest: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(output.with_extension("json")).unwrap()).unwrap();
+    assert_eq!(manifest["source_run"], RUN_NUMBER);
+    serde_json::from_value(manifest["serial_numbers"].clone()).unwrap()
+}
+
+#[test]
+fn alpha_g_sample_fraction() {
+    let dir = std::env::temp_dir().join(format!("alpha_g_sample_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let input = main_run(&dir);
+    let first = dir.join("first.mid");
+    let second = dir.join("second.mid");
+
+    let args = [
+        "--fraction",
+        "0.2",
+        "--seed",
+        "7",
+        "--keep-banks",
+        "pwb,trg",
+    ];
+    let serial_numbers = sample(&input, &first, &args);
+    assert!(!serial_numbers.is_empty() && serial_numbers.len() < 1000);
+    assert_eq!(sample(&input, &second, &args), serial_numbers);
+    assert_eq!(
+        std::fs::read(&first).unwrap(),
+        std::fs::read(&second).unwrap()
+    );
+
+    let contents = std::fs::read(&first).unwrap();
+    let file_view = midasio::FileView::try_from(&contents[..]).unwrap();
+    assert_eq!(file_view.run_number(), RUN_NUMBER);
+    assert_eq!(file_view.initial_timestamp(), 100);
+    assert_eq!(file_view.final_timestamp(), 110);
+    let mut found = Vec::new();
+    for event in file_view {
+        found.push(event.serial_number());
+        let names: Vec<_> = event.into_iter().map(|bank| bank.name()).collect();
+        assert_eq!(names, ["PC00", "ATAT"]);
+    }
+    assert_eq!(found, serial_numbers);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn alpha_g_sample_every_max_events() {
+    let dir = std::env::temp_dir().join(format!("alpha_g_sample_every_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let input = main_run(&dir);
+    let output = dir.join("every.mid");
+
+    let serial_numbers = sample(&input, &output, &["--every", "10", "--max-events", "5"]);
+    assert_eq!(serial_numbers, [0, 10, 20, 30, 40]);
+    assert!(!output.with_extension("mid.part").exists());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn alpha_g_sample_max_events_stops_reading_files() {
+    let dir =
+        std::env::temp_dir().join(format!("alpha_g_sample_max_events_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let first = main_run(&dir);
+    // Valid header (same run, later start), but the events are truncated.
+    // Reading this file would fail.
+    let second = dir.join("run11186sub001.mid");
+    midas_file(
+        &second,
+        (110, 120),
+        (1000..1010).map(|n| main_event(n, 115, Vec::new())),
+    );
+    let contents = std::fs::read(&second).unwrap();
+    std::fs::write(&second, &contents[..contents.len() - 20]).unwrap();
+    let output = dir.join("max_events.mid");
+
+    Command::cargo_bin("alpha-g-sample")
+        .unwrap()
+        .arg(&first)
+        .arg(&second)
+        .arg("--output")
+        .arg(&output)
+        .args(["--every", "10", "--max-events", "5"])
+        .assert()
+        .success();
+
+    let manifest: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(output.with_extension("json")).unwrap()).unwrap();
+    assert_eq!(
+        manifest["serial_numbers"],
+        serde_json::json!([0, 10, 20, 30, 40])
+    );
+    assert_eq!(manifest["source_files"], serde_json::json!([first]));
+    let contents = std::fs::read(&output).unwrap();
+    let file_view = midasio::FileView::try_from(&contents[..]).unwrap();
+    assert_eq!(file_view.final_timestamp(), 110);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,49 @@
+use assert_cmd::Command;
+use common::missing_trg_file;
+use std::path::Path;
+
+mod common;
+
+// CSV rows (i.e. without the provenance header) of an output file.
+fn records(path: &Path) -> Vec<String> {
+    std::fs::read_to_string(path)
+        .unwrap()
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .map(String::from)
+        .collect()
+}
+
+#[test]
+fn alpha_g_vertices_scalers_output_matches_trg_scalers() {
+    let dir = std::env::temp_dir().join(format!(
+        "alpha_g_analysis_{}_scalers_output",
+        std::process::id()
+    ));
+    std::fs::create_dir_all(&dir).unwrap();
+    let input = dir.join("run.mid");
+    missing_trg_file(&input, (100, 101), 0..10);
+
+    Command::cargo_bin("alpha-g-trg-scalers")
+        .unwrap()
+        .arg(&input)
+        .arg("--output")
+        .arg(dir.join("standalone"))
+        .assert()
+        .success();
+    Command::cargo_bin("alpha-g-vertices")
+        .unwrap()
+        .arg(&input)
+        .arg("--output")
+        .arg(dir.join("vertices"))
+        .arg("--scalers-output")
+        .arg(dir.join("combined"))
+        .assert()
+        .success();
+
+    let standalone = records(&dir.join("standalone.csv"));
+    let combined = records(&dir.join("combined.csv"));
+    // Header and a row per main event.
+    assert_eq!(standalone.len(), 11);
+    assert_eq!(standalone, combined);
+}
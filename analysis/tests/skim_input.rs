@@ -0,0 +1,79 @@
+use assert_cmd::Command;
+use common::subrun;
+use std::collections::HashMap;
+use std::path::Path;
+
+mod common;
+
+// Run `alpha-g-vertices` and return the CSV header and the rows keyed by
+// serial number.
+fn vertices(
+    input: &Path,
+    output: &Path,
+    args: &[&str],
+) -> (Vec<String>, HashMap<u32, Vec<String>>) {
+    Command::cargo_bin("alpha-g-vertices")
+        .unwrap()
+        .arg(input)
+        .args(["--why-no-vertex", "--diagnostics", "--output"])
+        .arg(output)
+        .args(args)
+        .assert()
+        .success();
+
+    let contents = std::fs::read_to_string(output.with_extension("csv")).unwrap();
+    let mut records = contents
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .map(|line| line.split(',').map(String::from).collect::<Vec<_>>());
+    let header = records.next().unwrap();
+    let rows = records
+        .map(|record| (record[0].parse().unwrap(), record))
+        .collect();
+
+    (header, rows)
+}
+
+#[test]
+fn alpha_g_vertices_skim_same_as_full_file() {
+    let dir = std::env::temp_dir().join(format!(
+        "alpha_g_analysis_{}_skim_input",
+        std::process::id()
+    ));
+    std::fs::create_dir_all(&dir).unwrap();
+    let serial_numbers: Vec<_> = (0..10).collect();
+    let full = subrun(&dir, "run11186sub000.mid", (100, 110), &serial_numbers);
+    let skim = dir.join("skim.mid");
+    Command::cargo_bin("alpha-g-sample")
+        .unwrap()
+        .arg(&full)
+        .arg("--output")
+        .arg(&skim)
+        .args(["--every", "3"])
+        .assert()
+        .success();
+
+    let (header, full_rows) = vertices(&full, &dir.join("full"), &[]);
+    let (skim_header, skim_rows) = vertices(&skim, &dir.join("skim"), &["--input-format", "skim"]);
+    assert_eq!(skim_header, header);
+    let trg_time = header.iter().position(|name| name == "trg_time").unwrap();
+
+    let mut skimmed: Vec<_> = skim_rows.keys().copied().collect();
+    skimmed.sort_unstable();
+    assert_eq!(skimmed, [0, 3, 6, 9]);
+    for (serial_number, skim_row) in skim_rows {
+        let full_row = &full_rows[&serial_number];
+        assert!(!full_row[trg_time].is_empty());
+        assert!(skim_row[trg_time].is_empty());
+        // Same reconstruction (vertex, failure reason, and diagnostics).
+        for (index, (skim, full)) in skim_row.iter().zip(full_row).enumerate() {
+            if index != trg_time {
+                assert_eq!(skim, full, "`{}` of event `{serial_number}`", header[index]);
+            }
+        }
+    }
+    let contents = std::fs::read_to_string(dir.join("skim.csv")).unwrap();
+    assert!(contents.contains("# WARNING: skim input, no trg_time"));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
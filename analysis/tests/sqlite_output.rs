@@ -0,0 +1,90 @@
+use assert_cmd::Command;
+use common::{main_run, NUM_EVENTS};
+use rusqlite::Connection;
+use std::path::Path;
+
+mod common;
+
+fn count(db: &Path, query: &str) -> u32 {
+    Connection::open(db)
+        .unwrap()
+        .query_row(query, [], |row| row.get(0))
+        .unwrap()
+}
+
+#[test]
+fn alpha_g_vertices_sqlite_output() {
+    let input = main_run("vertices_sqlite");
+    let db = input.with_extension("db");
+    let vertices = |args: &[&str]| {
+        Command::cargo_bin("alpha-g-vertices")
+            .unwrap()
+            .arg(&input)
+            .args(["--output", "-", "--output-sqlite"])
+            .arg(&db)
+            .args(args)
+            .output()
+            .unwrap()
+    };
+
+    assert!(vertices(&[]).status.success());
+    assert_eq!(count(&db, "SELECT COUNT(*) FROM vertices"), NUM_EVENTS);
+    assert_eq!(
+        count(&db, "SELECT COUNT(*) FROM runs WHERE run_number = 11186"),
+        1
+    );
+    // Events with only a TRG bank have a time but no vertex.
+    assert_eq!(
+        count(
+            &db,
+            "SELECT COUNT(*) FROM vertices WHERE trg_time IS NOT NULL AND reconstructed_x IS NULL"
+        ),
+        NUM_EVENTS
+    );
+    assert_eq!(
+        count(
+            &db,
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'index' AND name = 'vertices_run_serial'"
+        ),
+        1
+    );
+
+    // Same run again.
+    let result = vertices(&[]);
+    assert!(!result.status.success());
+    assert!(String::from_utf8_lossy(&result.stderr).contains("already has rows from run `11186`"));
+    assert_eq!(count(&db, "SELECT COUNT(*) FROM vertices"), NUM_EVENTS);
+
+    assert!(vertices(&["--if-exists", "replace"]).status.success());
+    assert_eq!(count(&db, "SELECT COUNT(*) FROM vertices"), NUM_EVENTS);
+    assert_eq!(count(&db, "SELECT COUNT(*) FROM runs"), 1);
+
+    // A different run is appended.
+    assert!(vertices(&["--run-number-override", "11187"])
+        .status
+        .success());
+    assert_eq!(count(&db, "SELECT COUNT(*) FROM vertices"), 2 * NUM_EVENTS);
+    assert_eq!(
+        count(
+            &db,
+            "SELECT COUNT(*) FROM vertices WHERE run_number = 11187"
+        ),
+        NUM_EVENTS
+    );
+    assert_eq!(count(&db, "SELECT COUNT(*) FROM runs"), 2);
+
+    // Optional columns (e.g. `--utc`) are also written.
+    assert!(vertices(&["--run-number-override", "11188", "--utc"])
+        .status
+        .success());
+    assert_eq!(
+        count(
+            &db,
+            "SELECT COUNT(*) FROM vertices WHERE run_number = 11188 AND utc IS NOT NULL"
+        ),
+        NUM_EVENTS
+    );
+
+    std::fs::remove_file(input).unwrap();
+    std::fs::remove_file(db).unwrap();
+}
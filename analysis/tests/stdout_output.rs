@@ -0,0 +1,100 @@
+use assert_cmd::Command;
+use common::{main_run, subrun, NUM_EVENTS};
+use std::io::{BufRead, BufReader};
+use std::process::Stdio;
+
+mod common;
+
+fn assert_stdout_output(bin: &str) {
+    let input = main_run(bin);
+    let dir = std::env::temp_dir().join(format!(
+        "alpha_g_analysis_{}_{bin}_stdout",
+        std::process::id()
+    ));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let result = Command::cargo_bin(bin)
+        .unwrap()
+        .current_dir(&dir)
+        .arg(&input)
+        .args(["--output", "-"])
+        .output()
+        .unwrap();
+    assert!(result.status.success());
+    assert!(!String::from_utf8_lossy(&result.stderr).contains("Created"));
+    // Nothing should be written to disk.
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+
+    let stdout = String::from_utf8(result.stdout).unwrap();
+    let mut lines = stdout.lines();
+    assert!(lines.next().unwrap().starts_with(&format!(
+        "# {} {}",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION")
+    )));
+    assert!(lines.next().unwrap().starts_with("# "));
+    // CSV header followed by a row per main event, and then the lines that are
+    // only known at the end of the run.
+    let records: Vec<_> = lines.by_ref().take(NUM_EVENTS as usize + 1).collect();
+    assert!(records[0].starts_with("serial_number"));
+    assert!(records.iter().all(|record| !record.starts_with('#')));
+    assert!(lines.next().unwrap().starts_with("# run start "));
+    assert!(lines.all(|line| line.starts_with("# ")));
+}
+
+#[test]
+fn alpha_g_vertices_stdout_output() {
+    assert_stdout_output("alpha-g-vertices");
+}
+
+#[test]
+fn alpha_g_trg_scalers_stdout_output() {
+    assert_stdout_output("alpha-g-trg-scalers");
+}
+
+#[test]
+fn alpha_g_bank_sizes_stdout_output() {
+    assert_stdout_output("alpha-g-bank-sizes");
+}
+
+#[test]
+fn alpha_g_vertices_stdout_streaming() {
+    let dir = std::env::temp_dir().join(format!(
+        "alpha_g_analysis_{}_vertices_stdout_streaming",
+        std::process::id()
+    ));
+    let tmp = dir.join("tmp");
+    std::fs::create_dir_all(&tmp).unwrap();
+    // The first file has many more rows than fit in a pipe buffer, so the
+    // program can't get to the second file before they are read.
+    let first: Vec<_> = (0..50_000).collect();
+    let inputs = [
+        subrun(&dir, "sub000.mid", (100, 200), &first),
+        subrun(&dir, "sub001.mid", (200, 210), &[50_000, 50_001]),
+    ];
+
+    let mut child = std::process::Command::new(assert_cmd::cargo::cargo_bin("alpha-g-vertices"))
+        .args(&inputs)
+        .args(["--force", "--output", "-"])
+        .env("TMPDIR", &tmp)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    let mut lines = BufReader::new(child.stdout.take().unwrap()).lines();
+    // Provenance, CSV header, and the first row.
+    let head: Vec<_> = lines.by_ref().take(4).map(Result::unwrap).collect();
+    assert!(head[2].starts_with("serial_number,"));
+    assert!(head[3].starts_with("0,"));
+    assert!(child.try_wait().unwrap().is_none());
+    // The rows are not kept in a temporary file either.
+    assert_eq!(std::fs::read_dir(&tmp).unwrap().count(), 0);
+
+    let rest: Vec<_> = lines.map(Result::unwrap).collect();
+    assert!(child.wait().unwrap().success());
+    let (comments, rows): (Vec<_>, Vec<_>) = rest.iter().partition(|line| line.starts_with('#'));
+    assert_eq!(rows.len(), first.len() + 1);
+    assert!(comments[0].starts_with("# run start "));
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
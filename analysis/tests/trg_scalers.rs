@@ -0,0 +1,184 @@
+use assert_cmd::Command;
+use common::missing_trg_file;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::process::Stdio;
+
+mod common;
+
+// Output of `alpha-g-trg-scalers` without the `#` lines (e.g. the command line
+// and the paths of the inputs), which change from one test run to the next.
+fn without_comments(path: &Path) -> String {
+    let output = std::fs::read_to_string(path).unwrap();
+    assert!(output.starts_with("# alpha_g_analysis "));
+
+    output
+        .split_inclusive('\n')
+        .filter(|line| !line.starts_with('#'))
+        .collect()
+}
+
+// Output for the same run as in `scalers_output.rs`. This must not change when
+// refactoring how the rows are written.
+const GOLDEN: &str = include_str!("golden/R11186_trg_scalers.csv");
+
+#[test]
+fn alpha_g_trg_scalers_golden() {
+    let dir = std::env::temp_dir().join(format!(
+        "alpha_g_analysis_{}_trg_scalers_golden",
+        std::process::id()
+    ));
+    std::fs::create_dir_all(&dir).unwrap();
+    let input = dir.join("run.mid");
+    missing_trg_file(&input, (100, 101), 0..10);
+
+    Command::cargo_bin("alpha-g-trg-scalers")
+        .unwrap()
+        .arg(&input)
+        .arg("--output")
+        .arg(dir.join("scalers"))
+        .assert()
+        .success();
+
+    let output = std::fs::read_to_string(dir.join("scalers.csv")).unwrap();
+    assert!(output
+        .lines()
+        .nth(2)
+        .unwrap()
+        .starts_with("# run start 100, end 101, duration 1 s, average main event rate 10.000 Hz"));
+    assert_eq!(without_comments(&dir.join("scalers.csv")), GOLDEN);
+    assert!(!dir.join("scalers.csv.partial").exists());
+}
+
+#[test]
+fn alpha_g_trg_scalers_golden_multiple_files() {
+    let dir = std::env::temp_dir().join(format!(
+        "alpha_g_analysis_{}_trg_scalers_golden_multiple_files",
+        std::process::id()
+    ));
+    std::fs::create_dir_all(&dir).unwrap();
+    let inputs = [dir.join("sub000.mid"), dir.join("sub001.mid")];
+    missing_trg_file(&inputs[0], (100, 101), 0..4);
+    missing_trg_file(&inputs[1], (101, 101), 4..10);
+
+    Command::cargo_bin("alpha-g-trg-scalers")
+        .unwrap()
+        .args(&inputs)
+        .arg("--output")
+        .arg(dir.join("scalers"))
+        .assert()
+        .success();
+
+    // Same rows regardless of how the run is split into files.
+    assert_eq!(without_comments(&dir.join("scalers.csv")), GOLDEN);
+}
+
+#[test]
+fn alpha_g_trg_scalers_golden_stdout() {
+    let dir = std::env::temp_dir().join(format!(
+        "alpha_g_analysis_{}_trg_scalers_golden_stdout",
+        std::process::id()
+    ));
+    std::fs::create_dir_all(&dir).unwrap();
+    let input = dir.join("run.mid");
+    missing_trg_file(&input, (100, 101), 0..10);
+
+    let result = Command::cargo_bin("alpha-g-trg-scalers")
+        .unwrap()
+        .arg(&input)
+        .args(["--output", "-"])
+        .output()
+        .unwrap();
+    assert!(result.status.success());
+
+    let path = dir.join("stdout.csv");
+    std::fs::write(&path, result.stdout).unwrap();
+    assert_eq!(without_comments(&path), GOLDEN);
+}
+
+#[test]
+fn alpha_g_trg_scalers_stdout_streaming() {
+    let dir = std::env::temp_dir().join(format!(
+        "alpha_g_analysis_{}_trg_scalers_stdout_streaming",
+        std::process::id()
+    ));
+    let tmp = dir.join("tmp");
+    std::fs::create_dir_all(&tmp).unwrap();
+    let input = dir.join("run.mid");
+    // Many more rows than fit in a pipe buffer, so the program can't exit
+    // before they are read.
+    let num_events = 50_000;
+    missing_trg_file(&input, (100, 200), 0..num_events);
+
+    let mut child = std::process::Command::new(assert_cmd::cargo::cargo_bin("alpha-g-trg-scalers"))
+        .arg(&input)
+        .args(["--output", "-"])
+        .env("TMPDIR", &tmp)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    let mut lines = BufReader::new(child.stdout.take().unwrap()).lines();
+    // Provenance, CSV header, and the first row.
+    let first: Vec<_> = lines.by_ref().take(4).map(Result::unwrap).collect();
+    assert_eq!(first[3], "0,0.0,3,2,1,0,0");
+    assert!(child.try_wait().unwrap().is_none());
+    // The rows are not kept in a temporary file either.
+    assert_eq!(std::fs::read_dir(&tmp).unwrap().count(), 0);
+
+    let rest: Vec<_> = lines.map(Result::unwrap).collect();
+    assert!(child.wait().unwrap().success());
+    let (comments, rows): (Vec<_>, Vec<_>) = rest.iter().partition(|line| line.starts_with('#'));
+    assert_eq!(rows.len(), num_events as usize - 1);
+    assert!(comments[0].starts_with("# run start "));
+    assert!(comments[1].starts_with("# input "));
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn alpha_g_trg_scalers_rate_window() {
+    let dir = std::env::temp_dir().join(format!(
+        "alpha_g_analysis_{}_trg_scalers_rate_window",
+        std::process::id()
+    ));
+    std::fs::create_dir_all(&dir).unwrap();
+    let input = dir.join("run.mid");
+    missing_trg_file(&input, (100, 101), 0..10);
+
+    let result = Command::cargo_bin("alpha-g-trg-scalers")
+        .unwrap()
+        .arg(&input)
+        .args(["--rate-window", "1.5", "--output"])
+        .arg(dir.join("scalers"))
+        .output()
+        .unwrap();
+    assert!(result.status.success());
+    // All the TRG packets are identical.
+    assert!(String::from_utf8_lossy(&result.stderr)
+        .contains("Total input `0`, total output `0`, live time `0.000` s of `0.000` s"));
+
+    let output = without_comments(&dir.join("scalers.csv"));
+    let mut lines = output.lines();
+    assert_eq!(
+        lines.next(),
+        Some("serial_number,trg_time,input,drift_veto,scaledown,pulser,output,input_rate,output_rate,live_fraction")
+    );
+    assert_eq!(lines.next(), Some("0,0.0,3,2,1,0,0,,,"));
+    assert_eq!(lines.next(), Some("1,,,,,,,,,"));
+}
+
+#[test]
+fn alpha_g_trg_scalers_bad_rate_window() {
+    for window in ["0", "-1", "inf"] {
+        let result = Command::cargo_bin("alpha-g-trg-scalers")
+            .unwrap()
+            .arg("run.mid")
+            .arg(format!("--rate-window={window}"))
+            .output()
+            .unwrap();
+        assert!(!result.status.success());
+        assert!(String::from_utf8_lossy(&result.stderr)
+            .contains("`--rate-window` must be a positive number of seconds"));
+    }
+}
@@ -0,0 +1,37 @@
+use assert_cmd::Command;
+use serde_json::Value;
+
+#[test]
+fn alpha_g_odb_version_json() {
+    // The `alpha-g-odb` program has a required positional argument. This also
+    // checks that `--version-json` works without it.
+    let output = Command::cargo_bin("alpha-g-odb")
+        .unwrap()
+        .arg("--version-json")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let info: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(info["name"], "alpha-g-odb");
+    assert_eq!(info["version"], env!("CARGO_PKG_VERSION"));
+    assert!(info["git_describe"].as_str().is_some_and(|s| !s.is_empty()));
+    assert!(info["features"].is_array());
+    assert_eq!(
+        info["dependencies"]["alpha_g_detector"],
+        alpha_g_detector::VERSION
+    );
+    assert_eq!(
+        info["dependencies"]["alpha_g_physics"],
+        alpha_g_physics::VERSION
+    );
+}
+
+#[test]
+fn alpha_g_vertices_version_json_is_exclusive() {
+    Command::cargo_bin("alpha-g-vertices")
+        .unwrap()
+        .args(["--version-json", "file.mid"])
+        .assert()
+        .failure();
+}
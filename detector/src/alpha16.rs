@@ -1,3 +1,4 @@
+use crate::midas::Adc32BankName;
 use std::fmt;
 use thiserror::Error;
 
@@ -29,7 +30,7 @@ pub struct TryChannelIdFromUnsignedError {
 }
 
 /// Channel ID that corresponds to SiPMs of the Barrel Veto.
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct Adc16ChannelId(u8);
 impl TryFrom<u8> for Adc16ChannelId {
     type Error = TryChannelIdFromUnsignedError;
@@ -64,7 +65,7 @@ impl TryFrom<u8> for Adc32ChannelId {
 }
 
 /// ADC channel ID in an Alpha16 board.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ChannelId {
     /// Barrel Veto SiPM channel.
     A16(Adc16ChannelId),
@@ -304,7 +305,7 @@ pub enum TryAdcPacketFromSliceError {
 ///
 /// Bytes `[12..size - 4]` are only included in the packet if the `keep_bit` is
 /// set after data suppression.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct AdcV3Packet {
     accepted_trigger: u16,
     module_id: ModuleId,
@@ -676,6 +677,54 @@ impl AdcV3Packet {
     pub fn is_suppression_enabled(&self) -> bool {
         self.suppression_enabled
     }
+    /// Return the binary representation of the [`AdcV3Packet`]. This is the
+    /// inverse of [`AdcV3Packet::try_from`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use alpha_g_detector::alpha16::TryAdcPacketFromSliceError;
+    /// # fn main() -> Result<(), TryAdcPacketFromSliceError> {
+    /// use alpha_g_detector::alpha16::AdcV3Packet;
+    ///
+    /// let buffer = [1, 3, 0, 4, 5, 6, 2, 187, 0, 0, 0, 7, 224, 0, 0, 0];
+    /// let packet = AdcV3Packet::try_from(&buffer[..])?;
+    ///
+    /// assert_eq!(packet.to_bytes(), buffer);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let channel_id = match self.channel_id {
+            ChannelId::A16(channel) => channel.0,
+            ChannelId::A32(channel) => channel.0 + 128,
+        };
+        let mut bytes = Vec::with_capacity(36 + 2 * self.waveform.len());
+        bytes.extend_from_slice(&[self.packet_type(), self.packet_version()]);
+        bytes.extend_from_slice(&self.accepted_trigger.to_be_bytes());
+        bytes.extend_from_slice(&[self.module_id.0, channel_id]);
+        bytes.extend_from_slice(&u16::try_from(self.requested_samples).unwrap().to_be_bytes());
+        let event_timestamp = self.event_timestamp.to_be_bytes();
+        bytes.extend_from_slice(&event_timestamp[4..]);
+        // All of these are either Some or None at the same time.
+        if let (Some(board_id), Some(trigger_offset), Some(build_timestamp)) =
+            (self.board_id, self.trigger_offset, self.build_timestamp)
+        {
+            bytes.extend_from_slice(&[0, 0]);
+            bytes.extend_from_slice(&board_id.mac_address());
+            bytes.extend_from_slice(&event_timestamp[..4]);
+            bytes.extend_from_slice(&trigger_offset.to_be_bytes());
+            bytes.extend_from_slice(&build_timestamp.to_be_bytes());
+            bytes.extend(self.waveform.iter().flat_map(|n| n.to_be_bytes()));
+        }
+        let footer = u16::try_from(self.keep_last).unwrap()
+            | u16::from(self.keep_bit) << 12
+            | u16::from(self.suppression_enabled) << 13;
+        bytes.extend_from_slice(&footer.to_be_bytes());
+        bytes.extend_from_slice(&self.suppression_baseline.to_be_bytes());
+
+        bytes
+    }
 }
 
 // The minimum number of samples required to reconstruct the data suppression
@@ -899,7 +948,7 @@ impl TryFrom<&[u8]> for AdcV3Packet {
 ///
 /// This enum can currently contain only an [`AdcV3Packet`]. See its
 /// documentation for more details.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum AdcPacket {
     /// Version 3 of an ADC packet.
     V3(AdcV3Packet),
@@ -1291,6 +1340,28 @@ impl AdcPacket {
     pub fn is_v3(&self) -> bool {
         matches!(self, Self::V3(_))
     }
+    /// Return the binary representation of the [`AdcPacket`]. This is the
+    /// inverse of [`AdcPacket::try_from`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use alpha_g_detector::alpha16::TryAdcPacketFromSliceError;
+    /// # fn main() -> Result<(), TryAdcPacketFromSliceError> {
+    /// use alpha_g_detector::alpha16::AdcPacket;
+    ///
+    /// let buffer = [1, 3, 0, 4, 5, 6, 2, 187, 0, 0, 0, 7, 224, 0, 0, 0];
+    /// let packet = AdcPacket::try_from(&buffer[..])?;
+    ///
+    /// assert_eq!(packet.to_bytes(), buffer);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            Self::V3(packet) => packet.to_bytes(),
+        }
+    }
 }
 
 impl TryFrom<&[u8]> for AdcPacket {
@@ -1301,5 +1372,58 @@ impl TryFrom<&[u8]> for AdcPacket {
     }
 }
 
+/// The error type returned by [`validate_bank_packet`].
+#[derive(Error, Debug)]
+pub enum ValidateBankPacketError {
+    /// An anode wire data bank has a BV channel_id.
+    #[error("anode wire data bank `{bank_name:?}` has a BV channel_id")]
+    BvChannel { bank_name: Adc32BankName },
+    /// The `(board_id, channel_id)` pair in the packet does not match the
+    /// expected value from the bank name.
+    #[error("alpha16 board_id/channel_id mismatch (expected {expected:?}, found {found:?})")]
+    IdMismatch {
+        expected: (BoardId, Adc32ChannelId),
+        found: (BoardId, Adc32ChannelId),
+    },
+}
+
+/// Check that an [`AdcPacket`] is consistent with the name of the anode wire
+/// data bank it was read from.
+///
+/// Packets with an empty waveform (data suppression) don't include a
+/// [`BoardId`]. Only their channel ID is checked.
+///
+/// # Examples
+///
+/// ```
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// use alpha_g_detector::alpha16::{validate_bank_packet, AdcPacket};
+/// use alpha_g_detector::midas::Adc32BankName;
+///
+/// let buffer = [1, 3, 0, 4, 5, 131, 2, 187, 0, 0, 0, 7, 224, 0, 0, 0];
+/// let packet = AdcPacket::try_from(&buffer[..])?;
+///
+/// assert!(validate_bank_packet(Adc32BankName::try_from("C093")?, &packet).is_ok());
+/// assert!(validate_bank_packet(Adc32BankName::try_from("C094")?, &packet).is_err());
+/// # Ok(())
+/// # }
+/// ```
+pub fn validate_bank_packet(
+    bank_name: Adc32BankName,
+    packet: &AdcPacket,
+) -> Result<(), ValidateBankPacketError> {
+    let ChannelId::A32(channel_id) = packet.channel_id() else {
+        return Err(ValidateBankPacketError::BvChannel { bank_name });
+    };
+    let expected = (bank_name.board_id(), bank_name.channel_id());
+    let found = (packet.board_id().unwrap_or(expected.0), channel_id);
+    if found != expected {
+        return Err(ValidateBankPacketError::IdMismatch { expected, found });
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests;
@@ -1,4 +1,5 @@
 use crate::alpha16::{Adc32ChannelId, BoardId};
+use crate::run::{RunNumber, RunRange};
 use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -24,7 +25,7 @@ pub const ANODE_WIRE_PITCH_PHI: f64 = 2.0 * PI / (TPC_ANODE_WIRES as f64);
 // When you add a new map, remember to:
 //     - Add all 3 unit tests.
 //     - Add the new lazy_static! for the map.
-//     - Add the new map for the corresponding run number.
+//     - Add the new map for the corresponding run range.
 //
 // Run 2941+ (including 2941):
 const PREAMPS_2941: [(&str, (usize, usize)); 8] = [
@@ -59,7 +60,7 @@ lazy_static! {
 //
 // When you add a new map, remember to:
 //    - Add unit test.
-//    - Add the new map for the corresponding run number.
+//    - Add the new map for the corresponding run range.
 //
 // Revision 1.1 was implemented in run 2724
 const INV_CHANNELS_2724: [usize; 32] = [
@@ -72,11 +73,61 @@ const INV_CHANNELS_2724: [usize; 32] = [
 #[derive(Debug, Error)]
 pub enum MapTpcWirePositionError {
     #[error("no rTPC preamp mapping available for run number {run_number}")]
-    MissingPreampMap { run_number: u32 },
+    MissingPreampMap { run_number: RunNumber },
     #[error("alpha16 `{}` not found in map for run number {run_number}", board_id.name())]
-    BoardIdNotFound { board_id: BoardId, run_number: u32 },
+    BoardIdNotFound {
+        board_id: BoardId,
+        run_number: RunNumber,
+    },
+    #[error("no rTPC wire mapping available for run number {run_number}")]
+    MissingWireMap { run_number: RunNumber },
+}
+
+/// The error type returned when mapping a [`TpcWirePosition`] back to a
+/// [`BoardId`] and [`Adc32ChannelId`] fails.
+#[derive(Debug, Error)]
+pub enum MapAdc32ChannelError {
+    #[error("no rTPC preamp mapping available for run number {run_number}")]
+    MissingPreampMap { run_number: RunNumber },
+    #[error("no alpha16 connected to wire {} for run number {run_number}", usize::from(*wire))]
+    WireNotConnected {
+        wire: TpcWirePosition,
+        run_number: RunNumber,
+    },
     #[error("no rTPC wire mapping available for run number {run_number}")]
-    MissingWireMap { run_number: u32 },
+    MissingWireMap { run_number: RunNumber },
+}
+
+// The simulation mapping was done to match the mapping of run number 5000.
+//
+// This map changes whenever a board is replaced/moved.
+fn preamp_map(run_number: RunNumber) -> Option<&'static HashMap<BoardId, (usize, usize)>> {
+    if run_number.is_simulation() {
+        Some(&PREAMPS_MAP_2941)
+    } else {
+        [(
+            RunRange::starting_at(RunNumber::new(2941)),
+            &*PREAMPS_MAP_2941,
+        )]
+        .into_iter()
+        .find(|(runs, _)| runs.contains(run_number))
+        .map(|(_, map)| map)
+    }
+}
+
+// This map will rarely change. Needs new revision of Alpha16 boards.
+fn channel_map(run_number: RunNumber) -> Option<&'static [usize; 32]> {
+    if run_number.is_simulation() {
+        Some(&INV_CHANNELS_2724)
+    } else {
+        [(
+            RunRange::starting_at(RunNumber::new(2724)),
+            &INV_CHANNELS_2724,
+        )]
+        .into_iter()
+        .find(|(runs, _)| runs.contains(run_number))
+        .map(|(_, map)| map)
+    }
 }
 
 /// The error type returned when conversion from [`usize`] to a
@@ -88,6 +139,9 @@ pub struct TryTpcWirePositionFromIndexError {
 }
 
 /// Position of an anode wire in the TPC.
+///
+/// Wires are ordered by their index (see [`TpcWirePosition::try_from`]), which
+/// is not the same as ordering them by `phi`.
 // IMPORTANT: The internal index represents the numbering starting from the
 // first wire in the first anode wire board. This is not the same as the first
 // wire at phi = 0. There is an offset of 8 wires between the first wire in the
@@ -145,8 +199,9 @@ impl TpcWirePosition {
     /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
     /// use alpha_g_detector::alpha16::{Adc32ChannelId, BoardId};
     /// use alpha_g_detector::alpha16::aw_map::TpcWirePosition;
+    /// use alpha_g_detector::run::RunNumber;
     ///
-    /// let run_number = 5000;
+    /// let run_number = RunNumber::new(5000);
     /// let board_id = BoardId::try_from("09")?;
     /// let channel_id = Adc32ChannelId::try_from(0)?;
     ///
@@ -155,26 +210,14 @@ impl TpcWirePosition {
     /// # }
     /// ```
     pub fn try_new(
-        run_number: u32,
+        run_number: RunNumber,
         board_id: BoardId,
         channel_id: Adc32ChannelId,
     ) -> Result<Self, MapTpcWirePositionError> {
-        // This map changes whenever a board is replaced/moved.
-        let preamp_map = match run_number {
-            // u32::MAX corresponds to a simulation run. The simulation mapping
-            // was done to match the mapping of run number 5000.
-            u32::MAX => &PREAMPS_MAP_2941,
-            2941.. => &PREAMPS_MAP_2941,
-            _ => return Err(MapTpcWirePositionError::MissingPreampMap { run_number }),
-        };
-        // This map will rarely change. Needs new revision of Alpha16 boards.
-        let channel_map = match run_number {
-            // u32::MAX corresponds to a simulation run. The simulation mapping
-            // was done to match the mapping of run number 5000.
-            u32::MAX => &INV_CHANNELS_2724,
-            2724.. => &INV_CHANNELS_2724,
-            _ => return Err(MapTpcWirePositionError::MissingWireMap { run_number }),
-        };
+        let preamp_map = preamp_map(run_number)
+            .ok_or(MapTpcWirePositionError::MissingPreampMap { run_number })?;
+        let channel_map = channel_map(run_number)
+            .ok_or(MapTpcWirePositionError::MissingWireMap { run_number })?;
         // The logic below doesn't change even if a map above does.
         let (preamp_1, preamp_2) =
             preamp_map
@@ -191,6 +234,75 @@ impl TpcWirePosition {
         };
         Ok(Self(wire_position))
     }
+    /// Same as [`TpcWirePosition::try_new`], but with the run number as a
+    /// bare `u32`.
+    #[deprecated(note = "use `TpcWirePosition::try_new` with a `RunNumber`")]
+    pub fn try_new_u32(
+        run_number: u32,
+        board_id: BoardId,
+        channel_id: Adc32ChannelId,
+    ) -> Result<Self, MapTpcWirePositionError> {
+        Self::try_new(RunNumber::from(run_number), board_id, channel_id)
+    }
+    /// Map a [`TpcWirePosition`] back to the [`BoardId`] and [`Adc32ChannelId`]
+    /// that read it out for a given run number. This is the inverse of
+    /// [`TpcWirePosition::try_new`]. Returns an error if the mapping is not
+    /// available for the given `run_number` or if no board is connected to the
+    /// wire for that `run_number`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use alpha_g_detector::alpha16::{Adc32ChannelId, BoardId};
+    /// use alpha_g_detector::alpha16::aw_map::TpcWirePosition;
+    /// use alpha_g_detector::run::RunNumber;
+    ///
+    /// let run_number = RunNumber::new(5000);
+    /// let wire_position = TpcWirePosition::try_from(0)?;
+    ///
+    /// let (board_id, channel_id) = wire_position.board_and_channel(run_number)?;
+    /// assert_eq!(board_id, BoardId::try_from("09")?);
+    /// assert_eq!(channel_id, Adc32ChannelId::try_from(2)?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn board_and_channel(
+        self,
+        run_number: RunNumber,
+    ) -> Result<(BoardId, Adc32ChannelId), MapAdc32ChannelError> {
+        let preamp_map =
+            preamp_map(run_number).ok_or(MapAdc32ChannelError::MissingPreampMap { run_number })?;
+        let channel_map =
+            channel_map(run_number).ok_or(MapAdc32ChannelError::MissingWireMap { run_number })?;
+
+        let (preamp, wire) = (self.0 / 16, self.0 % 16);
+        let (board_id, mapped_channel) = preamp_map
+            .iter()
+            .find_map(|(board_id, &(preamp_1, preamp_2))| {
+                if preamp == preamp_1 {
+                    Some((*board_id, wire))
+                } else if preamp == preamp_2 {
+                    Some((*board_id, wire + 16))
+                } else {
+                    None
+                }
+            })
+            .ok_or(MapAdc32ChannelError::WireNotConnected {
+                wire: self,
+                run_number,
+            })?;
+        // Every channel map is a permutation of `0..32`.
+        let channel = channel_map
+            .iter()
+            .position(|&channel| channel == mapped_channel)
+            .unwrap();
+
+        Ok((
+            board_id,
+            Adc32ChannelId::try_from(u8::try_from(channel).unwrap()).unwrap(),
+        ))
+    }
     /// Return the `phi` coordinate (in radians) of the wire within the rTPC.
     ///
     /// # Examples
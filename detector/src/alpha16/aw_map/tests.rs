@@ -48,6 +48,15 @@ fn try_from_tpc_wire_position_usize() {
     }
 }
 
+#[test]
+fn tpc_wire_position_ordering() {
+    for i in 1..=255 {
+        let previous = TpcWirePosition::try_from(i - 1).unwrap();
+        let current = TpcWirePosition::try_from(i).unwrap();
+        assert!(previous < current);
+    }
+}
+
 #[test]
 fn tpc_wire_position_ron_roundtrip() {
     for i in 0..=255 {
@@ -129,9 +138,9 @@ fn tpc_wire_position_missing_preamp_map() {
     let board_id = BoardId::try_from("09").unwrap();
     let adc32_channel_id = Adc32ChannelId::try_from(0).unwrap();
     for i in 0..=2940 {
-        match TpcWirePosition::try_new(i, board_id, adc32_channel_id) {
+        match TpcWirePosition::try_new(RunNumber::new(i), board_id, adc32_channel_id) {
             Err(MapTpcWirePositionError::MissingPreampMap { run_number }) => {
-                assert_eq!(run_number, i);
+                assert_eq!(run_number, RunNumber::new(i));
             }
             _ => unreachable!(),
         }
@@ -140,7 +149,7 @@ fn tpc_wire_position_missing_preamp_map() {
 
 #[test]
 fn tpc_wire_position_correctness_2941() {
-    let run_number = 2941;
+    let run_number = RunNumber::new(2941);
     let chan_map: [u8; 32] = [
         2, 8, 1, 9, 0, 10, 3, 11, 4, 12, 5, 13, 6, 14, 7, 15, 16, 24, 17, 25, 18, 26, 19, 27, 20,
         28, 21, 29, 22, 30, 23, 31,
@@ -212,8 +221,10 @@ fn tpc_wire_position_correctness_sim() {
         for channel_id in 0..=31 {
             let channel_id = Adc32ChannelId::try_from(channel_id).unwrap();
 
-            let wire_5000 = TpcWirePosition::try_new(5000, board_id, channel_id).unwrap();
-            let wire_sim = TpcWirePosition::try_new(u32::MAX, board_id, channel_id).unwrap();
+            let wire_5000 =
+                TpcWirePosition::try_new(RunNumber::new(5000), board_id, channel_id).unwrap();
+            let wire_sim =
+                TpcWirePosition::try_new(RunNumber::SIMULATION, board_id, channel_id).unwrap();
 
             assert_eq!(wire_5000, wire_sim);
         }
@@ -230,3 +241,50 @@ fn tpc_wire_position_phi() {
         assert!(abs_diff < 1e-10);
     }
 }
+
+// One run number per mapping epoch (and the boundaries between them).
+const MAP_EPOCHS: [RunNumber; 4] = [
+    RunNumber::new(2941),
+    RunNumber::new(5000),
+    RunNumber::new(u32::MAX - 1),
+    RunNumber::SIMULATION,
+];
+
+#[test]
+fn tpc_wire_position_board_and_channel_roundtrip() {
+    for run_number in MAP_EPOCHS {
+        for i in 0..TPC_ANODE_WIRES {
+            let wire_position = TpcWirePosition::try_from(i).unwrap();
+            let (board_id, channel_id) = wire_position.board_and_channel(run_number).unwrap();
+
+            assert_eq!(
+                TpcWirePosition::try_new(run_number, board_id, channel_id).unwrap(),
+                wire_position
+            );
+        }
+    }
+}
+
+#[test]
+fn tpc_wire_position_board_and_channel_missing_map() {
+    let wire_position = TpcWirePosition::try_from(0).unwrap();
+    assert!(matches!(
+        wire_position.board_and_channel(RunNumber::new(2940)),
+        Err(MapAdc32ChannelError::MissingPreampMap { .. })
+    ));
+}
+
+#[test]
+#[allow(deprecated)]
+fn tpc_wire_position_try_new_u32() {
+    let board_id = BoardId::try_from("09").unwrap();
+    for run_number in [0, 2941, 5000] {
+        for channel in 0..32 {
+            let channel_id = Adc32ChannelId::try_from(channel).unwrap();
+            assert_eq!(
+                TpcWirePosition::try_new_u32(run_number, board_id, channel_id).ok(),
+                TpcWirePosition::try_new(RunNumber::from(run_number), board_id, channel_id).ok()
+            );
+        }
+    }
+}
@@ -868,6 +868,78 @@ fn adc_v3_packet_is_suppression_enabled() {
         .is_suppression_enabled());
 }
 
+#[test]
+fn adc_v3_packet_to_bytes() {
+    for buffer in [&SHORT_ADC_V3_PACKET[..], &LONG_ADC_V3_PACKET[..]] {
+        let packet = AdcV3Packet::try_from(buffer).unwrap();
+        assert_eq!(packet.to_bytes(), buffer);
+        assert_eq!(
+            AdcV3Packet::try_from(&packet.to_bytes()[..]).unwrap(),
+            packet
+        );
+    }
+}
+
+// Xorshift pseudo-random number generator. Good enough to generate arbitrary
+// packets without any extra dependency.
+fn xorshift(state: &mut u64) -> u64 {
+    *state ^= *state << 13;
+    *state ^= *state >> 7;
+    *state ^= *state << 17;
+    *state
+}
+
+#[test]
+fn adc_v3_packet_to_bytes_random_waveforms() {
+    let mut state = 0x2545_f491_4f6c_dd1d;
+    for _ in 0..1000 {
+        let suppression_enabled = xorshift(&mut state) % 2 == 0;
+        let requested_samples = 67 + (xorshift(&mut state) % 700) as usize;
+        let (samples, keep_last) = if suppression_enabled {
+            let samples = 65 + (xorshift(&mut state) % (requested_samples as u64 - 66)) as usize;
+            let max_keep_last = (samples + 1) / 2 + 1;
+            let keep_last = MIN_KEEP_LAST
+                + (xorshift(&mut state) % (max_keep_last - MIN_KEEP_LAST + 1) as u64) as usize;
+            (samples, keep_last)
+        } else {
+            (requested_samples - 2, 0)
+        };
+        let waveform: Vec<i16> = (0..samples).map(|_| xorshift(&mut state) as i16).collect();
+        let baseline = waveform[..BASELINE_SAMPLES]
+            .iter()
+            .map(|&n| i32::from(n))
+            .sum::<i32>()
+            .div_euclid(64);
+        let channel = xorshift(&mut state) as u8;
+        let channel_id = if channel % 2 == 0 {
+            ChannelId::A16(Adc16ChannelId(channel % 16))
+        } else {
+            ChannelId::A32(Adc32ChannelId(channel % 32))
+        };
+        let (name, _) = ALPHA16BOARDS[(xorshift(&mut state) % 8) as usize];
+
+        let packet = AdcV3Packet {
+            accepted_trigger: xorshift(&mut state) as u16,
+            module_id: ModuleId((xorshift(&mut state) % 8) as u8),
+            channel_id,
+            requested_samples,
+            event_timestamp: xorshift(&mut state),
+            board_id: Some(BoardId::try_from(name).unwrap()),
+            trigger_offset: Some(xorshift(&mut state) as i32),
+            build_timestamp: Some(xorshift(&mut state) as u32),
+            waveform,
+            suppression_baseline: baseline.try_into().unwrap(),
+            keep_last,
+            keep_bit: suppression_enabled,
+            suppression_enabled,
+        };
+        assert_eq!(
+            AdcV3Packet::try_from(&packet.to_bytes()[..]).unwrap(),
+            packet
+        );
+    }
+}
+
 #[test]
 fn adc_to_string() {
     let packet = AdcPacket::try_from(&SHORT_ADC_V3_PACKET[..]).unwrap();
@@ -1171,3 +1243,52 @@ fn adc_packet_is_suppression_enabled() {
         .is_suppression_enabled()
         .unwrap());
 }
+
+#[test]
+fn adc_packet_to_bytes() {
+    for buffer in [&SHORT_ADC_V3_PACKET[..], &LONG_ADC_V3_PACKET[..]] {
+        let packet = AdcPacket::try_from(buffer).unwrap();
+        assert_eq!(packet.to_bytes(), buffer);
+        assert_eq!(AdcPacket::try_from(&packet.to_bytes()[..]).unwrap(), packet);
+    }
+}
+
+#[test]
+fn validate_bank_packet_wire() {
+    let mut buffer = LONG_ADC_V3_PACKET;
+    buffer[5] = 128 + 3;
+    let packet = AdcPacket::try_from(&buffer[..]).unwrap();
+
+    assert!(validate_bank_packet(Adc32BankName::try_from("C183").unwrap(), &packet).is_ok());
+    for name in ["C184", "C093"] {
+        assert!(matches!(
+            validate_bank_packet(Adc32BankName::try_from(name).unwrap(), &packet),
+            Err(ValidateBankPacketError::IdMismatch { .. })
+        ));
+    }
+}
+
+#[test]
+fn validate_bank_packet_empty_waveform() {
+    let mut buffer = SHORT_ADC_V3_PACKET;
+    buffer[5] = 128 + 3;
+    let packet = AdcPacket::try_from(&buffer[..]).unwrap();
+
+    // There is no board ID to compare against.
+    assert!(validate_bank_packet(Adc32BankName::try_from("C093").unwrap(), &packet).is_ok());
+    assert!(validate_bank_packet(Adc32BankName::try_from("C183").unwrap(), &packet).is_ok());
+    assert!(matches!(
+        validate_bank_packet(Adc32BankName::try_from("C184").unwrap(), &packet),
+        Err(ValidateBankPacketError::IdMismatch { .. })
+    ));
+}
+
+#[test]
+fn validate_bank_packet_bv_channel() {
+    let packet = AdcPacket::try_from(&LONG_ADC_V3_PACKET[..]).unwrap();
+
+    assert!(matches!(
+        validate_bank_packet(Adc32BankName::try_from("C183").unwrap(), &packet),
+        Err(ValidateBankPacketError::BvChannel { .. })
+    ));
+}
@@ -32,5 +32,15 @@ pub mod trigger;
 /// clock channel.
 pub mod chronobox;
 
+/// Run numbers.
+///
+/// The mapping of the detector channels (and the calibration of the detector
+/// response) changes with the run number. Ranges of run numbers identify the
+/// runs for which a given mapping is valid.
+pub mod run;
+
+/// Version of this crate (e.g. to record the provenance of analysis results).
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
 #[cfg(test)]
 mod tests;
@@ -1,4 +1,8 @@
 use crate::alpha16::{Adc16ChannelId, Adc32ChannelId, ChannelId};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
 use std::num::ParseIntError;
 use thiserror::Error;
 
@@ -30,6 +34,114 @@ pub const PWB_SUPPRESSION_THRESHOLD_JSON_PTR: &str = "/Equipment/CTRL/Settings/P
 pub const TRIGGER_PULSER_JSON_PTR: &str = "/Equipment/CTRL/Settings/TrigSrc/TrigPulser";
 /// JSON pointer that identifies the trigger sources in the ODB.
 pub const TRIGGER_SOURCES_JSON_PTR: &str = "/Equipment/CTRL/Settings/TrigSrc";
+/// JSON pointer that identifies the trigger scaledown factor in the ODB.
+pub const TRIGGER_SCALEDOWN_JSON_PTR: &str = "/Equipment/CTRL/Settings/TRG/Scaledown";
+
+/// The error type returned when parsing a [`RunConfig`] from an ODB dump
+/// fails.
+#[derive(Debug, Error)]
+pub enum RunConfigError {
+    /// The ODB dump is not valid JSON.
+    #[error("bad json odb dump")]
+    BadJson(#[from] serde_json::Error),
+    /// A setting is present in the ODB, but its value has an unexpected type.
+    #[error("bad value at `{pointer}`")]
+    BadValue { pointer: &'static str },
+}
+
+/// Run configuration settings from an ODB dump.
+///
+/// Each field is [`None`] if the setting is missing from the ODB (e.g. the
+/// key didn't exist yet in older runs).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RunConfig {
+    /// ADC16 data suppression threshold.
+    pub adc16_suppression_threshold: Option<i32>,
+    /// ADC32 data suppression enable flag of each channel.
+    pub adc32_suppression_enable: Option<Vec<bool>>,
+    /// ADC32 data suppression threshold.
+    pub adc32_suppression_threshold: Option<i32>,
+    /// Barrel Veto pulser enable flag.
+    pub bsc_pulser_enable: Option<bool>,
+    /// Field wire pulser enable flag.
+    pub field_wire_pulser_enable: Option<bool>,
+    /// Pulser enable flag.
+    pub pulser_enable: Option<bool>,
+    /// PWB force channels flag (i.e. data suppression disabled).
+    pub pwb_force_channels: Option<bool>,
+    /// PWB data suppression threshold.
+    pub pwb_suppression_threshold: Option<i32>,
+    /// Enable flag of each trigger source, by name. Entries that are not flags
+    /// are ignored.
+    pub trigger_sources: Option<BTreeMap<String, bool>>,
+    /// Trigger scaledown factor.
+    pub trigger_scaledown: Option<u32>,
+}
+
+// Get the value at `pointer`. A missing key is not an error.
+fn odb_value<T: DeserializeOwned>(
+    odb: &Value,
+    pointer: &'static str,
+) -> Result<Option<T>, RunConfigError> {
+    odb.pointer(pointer)
+        .map(|value| T::deserialize(value).map_err(|_| RunConfigError::BadValue { pointer }))
+        .transpose()
+}
+
+impl RunConfig {
+    /// Parse the run configuration from a JSON ODB dump (e.g.
+    /// `midasio::FileView::initial_odb`). Any trailing data after the ODB dump
+    /// is ignored.
+    ///
+    /// XML ODB dumps are not supported. These can be converted to JSON first,
+    /// and then parsed with the [`TryFrom<&Value>`] implementation.
+    pub fn try_from_odb(bytes: &[u8]) -> Result<Self, RunConfigError> {
+        let odb = Value::deserialize(&mut serde_json::Deserializer::from_slice(bytes))?;
+        Self::try_from(&odb)
+    }
+
+    /// Return the pulser trigger flag.
+    pub fn trigger_pulser(&self) -> Option<bool> {
+        self.trigger_sources
+            .as_ref()
+            .and_then(|sources| sources.get("TrigPulser").copied())
+    }
+
+    /// Return an iterator over the names of the enabled trigger sources.
+    pub fn active_trigger_sources(&self) -> impl Iterator<Item = &str> + '_ {
+        self.trigger_sources
+            .iter()
+            .flatten()
+            .filter_map(|(name, enabled)| enabled.then_some(name.as_str()))
+    }
+}
+
+impl TryFrom<&Value> for RunConfig {
+    type Error = RunConfigError;
+
+    fn try_from(odb: &Value) -> Result<Self, Self::Error> {
+        let trigger_sources = odb_value::<BTreeMap<String, Value>>(odb, TRIGGER_SOURCES_JSON_PTR)?
+            .map(|sources| {
+                sources
+                    .into_iter()
+                    .filter_map(|(name, value)| Some((name, value.as_bool()?)))
+                    .collect()
+            });
+
+        Ok(Self {
+            adc16_suppression_threshold: odb_value(odb, ADC16_SUPPRESSION_THRESHOLD_JSON_PTR)?,
+            adc32_suppression_enable: odb_value(odb, ADC32_SUPPRESSION_ENABLE_JSON_PTR)?,
+            adc32_suppression_threshold: odb_value(odb, ADC32_SUPPRESSION_THRESHOLD_JSON_PTR)?,
+            bsc_pulser_enable: odb_value(odb, BSC_PULSER_ENABLE_JSON_PTR)?,
+            field_wire_pulser_enable: odb_value(odb, FIELD_WIRE_PULSER_ENABLE_JSON_PTR)?,
+            pulser_enable: odb_value(odb, PULSER_ENABLE_JSON_PTR)?,
+            pwb_force_channels: odb_value(odb, PWB_FORCE_CHANNELS_JSON_PTR)?,
+            pwb_suppression_threshold: odb_value(odb, PWB_SUPPRESSION_THRESHOLD_JSON_PTR)?,
+            trigger_sources,
+            trigger_scaledown: odb_value(odb, TRIGGER_SCALEDOWN_JSON_PTR)?,
+        })
+    }
+}
 
 /// The error type returned when conversion from unsigned integer to [`EventId`]
 /// fails.
@@ -518,6 +630,44 @@ impl TryFrom<&str> for MainEventBankName {
     }
 }
 
+/// Subsystem that produced a MIDAS bank in a main event.
+///
+/// Classification is based exclusively on the bank name; the data in the bank
+/// is never parsed. Any name that is not a valid Alpha16, Padwing, or Trigger
+/// bank name is classified as [`BankKind::Other`].
+///
+/// # Examples
+///
+/// ```
+/// use alpha_g_detector::midas::BankKind;
+///
+/// assert_eq!(BankKind::from("B09A"), BankKind::Alpha16);
+/// assert_eq!(BankKind::from("PC00"), BankKind::Padwing);
+/// assert_eq!(BankKind::from("ATAT"), BankKind::Trg);
+/// assert_eq!(BankKind::from("XXXX"), BankKind::Other);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BankKind {
+    /// Bank with data from an Alpha16 board (ADC).
+    Alpha16,
+    /// Bank with data from a Padwing board (PWB).
+    Padwing,
+    /// Bank with data from the Trigger board.
+    Trg,
+    /// Any other bank.
+    Other,
+}
+impl From<&str> for BankKind {
+    fn from(name: &str) -> Self {
+        match MainEventBankName::try_from(name) {
+            Ok(MainEventBankName::Alpha16(_)) => Self::Alpha16,
+            Ok(MainEventBankName::Padwing(_)) => Self::Padwing,
+            Ok(MainEventBankName::Trg(_)) => Self::Trg,
+            _ => Self::Other,
+        }
+    }
+}
+
 /// The error type returned when parsing a Chronobox bank name fails.
 #[derive(Error, Debug)]
 pub enum ParseChronoboxBankNameError {
@@ -77,6 +77,129 @@ fn trigger_sources_json_ptr() {
     assert_eq!(TRIGGER_SOURCES_JSON_PTR, "/Equipment/CTRL/Settings/TrigSrc");
 }
 
+#[test]
+fn trigger_scaledown_json_ptr() {
+    assert_eq!(
+        TRIGGER_SCALEDOWN_JSON_PTR,
+        "/Equipment/CTRL/Settings/TRG/Scaledown"
+    );
+}
+
+// Trimmed ODB dumps from two different eras of the experiment. The older one
+// doesn't have e.g. the trigger scaledown or the BV pulser settings.
+const OLD_ODB: &[u8] = include_bytes!("../../data/odb/R4418_initial_odb.json");
+const NEW_ODB: &[u8] = include_bytes!("../../data/odb/R11186_initial_odb.json");
+
+#[test]
+fn run_config_try_from_old_odb() {
+    let config = RunConfig::try_from_odb(OLD_ODB).unwrap();
+
+    assert_eq!(
+        config,
+        RunConfig {
+            adc16_suppression_threshold: Some(1500),
+            adc32_suppression_enable: Some(vec![true, true, false]),
+            adc32_suppression_threshold: Some(-1500),
+            bsc_pulser_enable: None,
+            field_wire_pulser_enable: None,
+            pulser_enable: Some(true),
+            pwb_force_channels: Some(true),
+            pwb_suppression_threshold: Some(1100),
+            trigger_sources: Some(BTreeMap::from([
+                (String::from("TrigAdc16Grand"), false),
+                (String::from("TrigAdc32Grand"), false),
+                (String::from("TrigPulser"), true),
+            ])),
+            trigger_scaledown: None,
+        }
+    );
+    assert_eq!(config.trigger_pulser(), Some(true));
+    assert_eq!(
+        config.active_trigger_sources().collect::<Vec<_>>(),
+        ["TrigPulser"]
+    );
+}
+
+#[test]
+fn run_config_try_from_new_odb() {
+    let config = RunConfig::try_from_odb(NEW_ODB).unwrap();
+
+    assert_eq!(
+        config,
+        RunConfig {
+            adc16_suppression_threshold: Some(1000),
+            adc32_suppression_enable: Some(vec![true, true, true]),
+            adc32_suppression_threshold: Some(-2000),
+            bsc_pulser_enable: Some(true),
+            field_wire_pulser_enable: Some(false),
+            pulser_enable: Some(false),
+            pwb_force_channels: Some(false),
+            pwb_suppression_threshold: Some(500),
+            // `TrigSelect` is not a flag.
+            trigger_sources: Some(BTreeMap::from([
+                (String::from("TrigAdc16Grand"), true),
+                (String::from("TrigAdc32Grand"), false),
+                (String::from("TrigCoincA"), true),
+                (String::from("TrigPulser"), false),
+            ])),
+            trigger_scaledown: Some(10),
+        }
+    );
+    assert_eq!(config.trigger_pulser(), Some(false));
+    assert_eq!(
+        config.active_trigger_sources().collect::<Vec<_>>(),
+        ["TrigAdc16Grand", "TrigCoincA"]
+    );
+}
+
+#[test]
+fn run_config_try_from_odb_trailing_garbage() {
+    let mut bytes = NEW_ODB.to_vec();
+    bytes.extend_from_slice(b"\0\0garbage");
+
+    assert_eq!(
+        RunConfig::try_from_odb(&bytes).unwrap(),
+        RunConfig::try_from_odb(NEW_ODB).unwrap()
+    );
+}
+
+#[test]
+fn run_config_try_from_empty_odb() {
+    let config = RunConfig::try_from_odb(b"{}").unwrap();
+
+    assert_eq!(config, RunConfig::default());
+    assert_eq!(config.trigger_pulser(), None);
+    assert_eq!(config.active_trigger_sources().count(), 0);
+}
+
+#[test]
+fn run_config_try_from_bad_odb() {
+    assert!(matches!(
+        RunConfig::try_from_odb(b"<odb></odb>"),
+        Err(RunConfigError::BadJson(_))
+    ));
+
+    let odb = serde_json::json!({
+        "Equipment": { "CTRL": { "Settings": { "TRG": { "Scaledown": -1 } } } }
+    });
+    assert!(matches!(
+        RunConfig::try_from(&odb),
+        Err(RunConfigError::BadValue {
+            pointer: TRIGGER_SCALEDOWN_JSON_PTR
+        })
+    ));
+
+    let odb = serde_json::json!({
+        "Equipment": { "CTRL": { "Settings": { "PWB": { "ch_force": 1 } } } }
+    });
+    assert!(matches!(
+        RunConfig::try_from(&odb),
+        Err(RunConfigError::BadValue {
+            pointer: PWB_FORCE_CHANNELS_JSON_PTR
+        })
+    ));
+}
+
 #[test]
 fn event_id_try_from_u16() {
     for num in 0..=u16::MAX {
@@ -763,6 +886,19 @@ fn main_event_bank_name_valid() {
     ));
 }
 
+#[test]
+fn bank_kind_from_name() {
+    assert_eq!(BankKind::from("B09A"), BankKind::Alpha16);
+    assert_eq!(BankKind::from("C09A"), BankKind::Alpha16);
+    assert_eq!(BankKind::from("PC00"), BankKind::Padwing);
+    assert_eq!(BankKind::from("ATAT"), BankKind::Trg);
+    assert_eq!(BankKind::from("TRBA"), BankKind::Other);
+    assert_eq!(BankKind::from("MCVX"), BankKind::Other);
+    assert_eq!(BankKind::from("CBF1"), BankKind::Other);
+    assert_eq!(BankKind::from("PCXX"), BankKind::Other);
+    assert_eq!(BankKind::from(""), BankKind::Other);
+}
+
 #[test]
 fn chronobox_bank_name_valid() {
     assert_eq!(
@@ -1,3 +1,6 @@
+use crate::midas::PadwingBankName;
+use crate::run::RunNumber;
+use lazy_static::lazy_static;
 use std::fmt;
 use thiserror::Error;
 
@@ -50,6 +53,8 @@ pub struct TryBoardIdFromUnsignedError {
 // Note: The device ID is just the first 4 bytes of the MAC address as
 // little endian u32. Maybe remove the last u32 in the future, and just get it
 // from the MAC address.
+// Names, MAC addresses, and device IDs have to be unique. All of this is
+// checked by the `padwing_boards` unit test.
 const PADWING_BOARDS: [(&str, [u8; 6], u32); 71] = [
     ("00", [236, 40, 255, 135, 84, 2], 2281646316),
     ("01", [236, 40, 250, 162, 84, 2], 2734303468),
@@ -136,55 +141,55 @@ pub struct BoardId {
     name: &'static str,
     mac_address: [u8; 6],
     device_id: u32,
+    index: usize,
 }
+lazy_static! {
+    // Indices of `PADWING_BOARDS` sorted by each key. Converting to a `BoardId`
+    // is done for every chunk, so avoid a linear search through the table.
+    static ref INDEX_BY_NAME: Vec<(&'static str, usize)> = sorted_index(|board| board.0);
+    static ref INDEX_BY_MAC: Vec<([u8; 6], usize)> = sorted_index(|board| board.1);
+    static ref INDEX_BY_DEVICE_ID: Vec<(u32, usize)> = sorted_index(|board| board.2);
+}
+
+fn sorted_index<K: Ord>(key: impl Fn(&(&'static str, [u8; 6], u32)) -> K) -> Vec<(K, usize)> {
+    let mut index: Vec<_> = PADWING_BOARDS.iter().map(key).zip(0..).collect();
+    index.sort_unstable();
+    index
+}
+
 impl TryFrom<&str> for BoardId {
     type Error = ParseBoardIdError;
 
     fn try_from(name: &str) -> Result<Self, Self::Error> {
-        for triplet in PADWING_BOARDS {
-            if name == triplet.0 {
-                return Ok(BoardId {
-                    name: triplet.0,
-                    mac_address: triplet.1,
-                    device_id: triplet.2,
-                });
-            }
-        }
-        Err(ParseBoardIdError {
-            input: name.to_string(),
-        })
+        INDEX_BY_NAME
+            .binary_search_by_key(&name, |&(key, _)| key)
+            .ok()
+            .and_then(|i| BoardId::from_index(INDEX_BY_NAME[i].1))
+            .ok_or_else(|| ParseBoardIdError {
+                input: name.to_string(),
+            })
     }
 }
 impl TryFrom<[u8; 6]> for BoardId {
     type Error = TryBoardIdFromMacAddressError;
 
     fn try_from(mac: [u8; 6]) -> Result<Self, Self::Error> {
-        for triplet in PADWING_BOARDS {
-            if mac == triplet.1 {
-                return Ok(BoardId {
-                    name: triplet.0,
-                    mac_address: triplet.1,
-                    device_id: triplet.2,
-                });
-            }
-        }
-        Err(TryBoardIdFromMacAddressError { input: mac })
+        INDEX_BY_MAC
+            .binary_search_by_key(&mac, |&(key, _)| key)
+            .ok()
+            .and_then(|i| BoardId::from_index(INDEX_BY_MAC[i].1))
+            .ok_or(TryBoardIdFromMacAddressError { input: mac })
     }
 }
 impl TryFrom<u32> for BoardId {
     type Error = TryBoardIdFromUnsignedError;
 
     fn try_from(device_id: u32) -> Result<Self, Self::Error> {
-        for triplet in PADWING_BOARDS {
-            if device_id == triplet.2 {
-                return Ok(BoardId {
-                    name: triplet.0,
-                    mac_address: triplet.1,
-                    device_id: triplet.2,
-                });
-            }
-        }
-        Err(TryBoardIdFromUnsignedError { input: device_id })
+        INDEX_BY_DEVICE_ID
+            .binary_search_by_key(&device_id, |&(key, _)| key)
+            .ok()
+            .and_then(|i| BoardId::from_index(INDEX_BY_DEVICE_ID[i].1))
+            .ok_or(TryBoardIdFromUnsignedError { input: device_id })
     }
 }
 impl BoardId {
@@ -240,6 +245,47 @@ impl BoardId {
     pub fn device_id(&self) -> u32 {
         self.device_id
     }
+    /// Return the index of a physical PadWing board. Each known board has a
+    /// unique index in the range `0..N`, where `N` is the number of known
+    /// boards. This is useful to store information about each board in a
+    /// fixed size array.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use alpha_g_detector::padwing::ParseBoardIdError;
+    /// # fn main() -> Result<(), ParseBoardIdError> {
+    /// use alpha_g_detector::padwing::BoardId;
+    ///
+    /// let board_id = BoardId::try_from("00")?;
+    /// assert_eq!(board_id.index(), 0);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn index(&self) -> usize {
+        self.index
+    }
+    /// Return the physical PadWing board with a given index (see
+    /// [`BoardId::index`]), or [`None`] if there is no board with that index.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use alpha_g_detector::padwing::BoardId;
+    ///
+    /// let board_id = BoardId::from_index(0).unwrap();
+    /// assert_eq!(board_id.name(), "00");
+    /// assert!(BoardId::from_index(1000).is_none());
+    /// ```
+    pub fn from_index(index: usize) -> Option<Self> {
+        let &(name, mac_address, device_id) = PADWING_BOARDS.get(index)?;
+        Some(BoardId {
+            name,
+            mac_address,
+            device_id,
+            index,
+        })
+    }
 }
 
 /// The error type returned when conversion from unsigned integer to [`AfterId`]
@@ -353,7 +399,7 @@ pub enum TryChunkFromSliceError {
 /// |Last 4 bytes| Payload CRC-32C|
 ///
 /// </center>
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Chunk {
     // Even though device_id and channel_id represent the BoardId and AfterId,
     // keep them as integers internally to simplify the CRC-32C and printing
@@ -584,6 +630,43 @@ impl Chunk {
             .collect();
         !crc32c::crc32c(&slice[..])
     }
+    /// Return the binary representation of the [`Chunk`], including the
+    /// CRC-32C values and the padding bytes. This is the inverse of
+    /// [`Chunk::try_from`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use alpha_g_detector::padwing::TryChunkFromSliceError;
+    /// # fn main() -> Result<(), TryChunkFromSliceError> {
+    /// use alpha_g_detector::padwing::Chunk;
+    ///
+    /// let buffer = [236, 40, 255, 135, 2, 0, 0, 0, 3, 0, 0, 1, 5, 0, 1, 0, 143, 203, 131, 81, 255, 0, 0, 0, 122, 92, 155, 159];
+    /// let chunk = Chunk::try_from(&buffer[..])?;
+    ///
+    /// assert_eq!(chunk.to_bytes(), buffer);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let padding = match self.payload.len() % 4 {
+            0 => 0,
+            r => 4 - r,
+        };
+        let mut bytes = Vec::with_capacity(24 + self.payload.len() + padding);
+        bytes.extend_from_slice(&self.device_id.to_le_bytes());
+        bytes.extend_from_slice(&self.packet_sequence.to_le_bytes());
+        bytes.extend_from_slice(&self.channel_sequence.to_le_bytes());
+        bytes.extend_from_slice(&[self.channel_id, self.flags]);
+        bytes.extend_from_slice(&self.chunk_id.to_le_bytes());
+        bytes.extend_from_slice(&u16::try_from(self.payload.len()).unwrap().to_le_bytes());
+        bytes.extend_from_slice(&self.header_crc32c().to_le_bytes());
+        bytes.extend_from_slice(&self.payload);
+        bytes.extend(std::iter::repeat(0).take(padding));
+        bytes.extend_from_slice(&self.payload_crc32c().to_le_bytes());
+
+        bytes
+    }
 }
 
 impl TryFrom<&[u8]> for Chunk {
@@ -679,7 +762,7 @@ pub struct TryCompressionFromUnsignedError {
 }
 
 /// Compression types available for the PadWing boards event data.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Compression {
     /// Uncompressed raw data. Any SCA channel data is sent without compression,
     /// in 16-bit signed format.
@@ -705,7 +788,7 @@ pub struct TryTriggerFromUnsignedError {
 }
 
 /// Trigger sources available that cause an event to be captured.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Trigger {
     /// Trigger came from the external pin on the PadWing board.
     External,
@@ -762,7 +845,7 @@ impl TryFrom<u16> for ResetChannelId {
 ///
 /// Every AFTER chip has 4 FPN channels, with readout indices 16, 29, 54, and
 /// 67.
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 // The internal u16 does NOT correspond to the readout index.
 // It corresponds to the channel index 1, 2, 3, or 4.
 pub struct FpnChannelId(u16);
@@ -837,6 +920,18 @@ impl TryFrom<u16> for ChannelId {
         }
     }
 }
+impl ChannelId {
+    // Inverse of the TryFrom<u16> implementation above.
+    fn readout_index(self) -> u16 {
+        match self {
+            ChannelId::Reset(ResetChannelId(index)) => index,
+            ChannelId::Fpn(FpnChannelId(index)) => [16, 29, 54, 67][usize::from(index) - 1],
+            ChannelId::Pad(PadChannelId(index)) => [16, 29, 54, 67]
+                .into_iter()
+                .fold(index + 3, |i, fpn| i + u16::from(i >= fpn)),
+        }
+    }
+}
 
 /// The error type returned when conversion from
 /// [`&[u8]`](https://doc.rust-lang.org/std/primitive.slice.html) to
@@ -934,7 +1029,7 @@ pub enum TryPwbPacketFromSliceError {
 /// |...|Waveforms|
 ///
 /// </center>
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct PwbV2Packet {
     after_id: AfterId,
     compression: Compression,
@@ -1332,6 +1427,115 @@ impl PwbV2Packet {
             None
         }
     }
+    /// Return the binary representation of the [`PwbV2Packet`]. This is the
+    /// inverse of [`PwbV2Packet::try_from`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use alpha_g_detector::padwing::TryPwbPacketFromSliceError;
+    /// # fn main() -> Result<(), TryPwbPacketFromSliceError> {
+    /// use alpha_g_detector::padwing::PwbV2Packet;
+    ///
+    /// let payload = [2, 65, 0, 0, 236, 40, 255, 135, 84, 2, 1, 0, 2, 0, 0, 0, 0, 0, 0, 0, 100, 0, 255, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 5, 0, 0, 0, 200, 0, 6, 7, 204, 204, 204, 204];
+    /// let packet = PwbV2Packet::try_from(&payload[..])?;
+    ///
+    /// assert_eq!(packet.to_bytes(), payload);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let bitmask = |channels: &[ChannelId]| {
+            channels
+                .iter()
+                .fold(0u128, |acc, c| acc | 1 << (c.readout_index() - 1))
+                .to_le_bytes()
+        };
+        let compression = match self.compression {
+            Compression::Raw => 0,
+        };
+        let trigger_source = match self.trigger_source {
+            Trigger::External => 0,
+            Trigger::Manual => 1,
+            Trigger::InternalPulse => 3,
+        };
+        let mut bytes = Vec::with_capacity(52 + 2 * self.data.len());
+        bytes.extend_from_slice(&[
+            self.packet_version(),
+            b'A' + self.after_id as u8,
+            compression,
+            trigger_source,
+        ]);
+        bytes.extend_from_slice(&self.board_id.mac_address());
+        bytes.extend_from_slice(&self.trigger_delay.to_le_bytes());
+        bytes.extend_from_slice(&self.trigger_timestamp.to_le_bytes());
+        bytes.extend_from_slice(&self.last_sca_cell.to_le_bytes());
+        bytes.extend_from_slice(&u16::try_from(self.requested_samples).unwrap().to_le_bytes());
+        bytes.extend_from_slice(&bitmask(&self.channels_sent)[..10]);
+        bytes.extend_from_slice(&bitmask(&self.channels_over_threshold)[..10]);
+        bytes.extend_from_slice(&self.event_counter.to_le_bytes());
+        bytes.extend_from_slice(&self.fifo_max_depth.to_le_bytes());
+        bytes.extend_from_slice(&[
+            self.event_descriptor_write_depth,
+            self.event_descriptor_read_depth,
+        ]);
+        bytes.extend(self.data.iter().flat_map(|n| n.to_le_bytes()));
+
+        bytes
+    }
+    /// Split the binary representation of the [`PwbV2Packet`] into the
+    /// [`Chunk`]s that would be sent by the PWB. All chunks (except the last
+    /// one) have a payload of `max_chunk_payload` bytes. This is the inverse of
+    /// [`PwbV2Packet::try_from`] from a [`Vec<Chunk>`].
+    ///
+    /// The packet and channel sequences depend on all the other chunks sent by
+    /// the board, which are unknown here. Both are set to the chunk ID.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_chunk_payload` is `0` or larger than [`u16::MAX`], or if
+    /// the packet needs more than `65536` chunks.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use alpha_g_detector::padwing::TryPwbPacketFromSliceError;
+    /// # fn main() -> Result<(), TryPwbPacketFromSliceError> {
+    /// use alpha_g_detector::padwing::PwbV2Packet;
+    ///
+    /// let payload = [2, 65, 0, 0, 236, 40, 255, 135, 84, 2, 1, 0, 2, 0, 0, 0, 0, 0, 0, 0, 100, 0, 255, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 5, 0, 0, 0, 200, 0, 6, 7, 204, 204, 204, 204];
+    /// let packet = PwbV2Packet::try_from(&payload[..])?;
+    /// let chunks = packet.to_chunks(40);
+    ///
+    /// assert_eq!(chunks.len(), 2);
+    /// assert!(chunks[1].is_end_of_message());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn to_chunks(&self, max_chunk_payload: usize) -> Vec<Chunk> {
+        assert!(
+            max_chunk_payload > 0 && max_chunk_payload <= u16::MAX.into(),
+            "bad `max_chunk_payload` `{max_chunk_payload}`"
+        );
+        let bytes = self.to_bytes();
+        let num_chunks = bytes.len().div_ceil(max_chunk_payload);
+        bytes
+            .chunks(max_chunk_payload)
+            .enumerate()
+            .map(|(index, payload)| {
+                let chunk_id = u16::try_from(index).unwrap();
+                Chunk {
+                    device_id: self.board_id.device_id(),
+                    packet_sequence: chunk_id.into(),
+                    channel_sequence: chunk_id,
+                    channel_id: self.after_id as u8,
+                    flags: u8::from(index + 1 == num_chunks),
+                    chunk_id,
+                    payload: payload.to_vec(),
+                }
+            })
+            .collect()
+    }
 }
 
 impl TryFrom<&[u8]> for PwbV2Packet {
@@ -1593,7 +1797,7 @@ impl TryFrom<Vec<Chunk>> for PwbV2Packet {
 ///
 /// This enum can currently contain only a [`PwbV2Packet`]. See its
 /// documentation for more details.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum PwbPacket {
     /// Version 2 of a PWB packet.
     V2(PwbV2Packet),
@@ -1986,6 +2190,40 @@ impl PwbPacket {
     pub fn is_v2(&self) -> bool {
         matches!(self, Self::V2(_))
     }
+    /// Return the binary representation of the [`PwbPacket`]. This is the
+    /// inverse of [`PwbPacket::try_from`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use alpha_g_detector::padwing::TryPwbPacketFromSliceError;
+    /// # fn main() -> Result<(), TryPwbPacketFromSliceError> {
+    /// use alpha_g_detector::padwing::PwbPacket;
+    ///
+    /// let payload = [2, 65, 0, 0, 236, 40, 255, 135, 84, 2, 1, 0, 2, 0, 0, 0, 0, 0, 0, 0, 100, 0, 255, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 5, 0, 0, 0, 200, 0, 6, 7, 204, 204, 204, 204];
+    /// let packet = PwbPacket::try_from(&payload[..])?;
+    ///
+    /// assert_eq!(packet.to_bytes(), payload);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            Self::V2(packet) => packet.to_bytes(),
+        }
+    }
+    /// Split the binary representation of the [`PwbPacket`] into [`Chunk`]s.
+    /// See [`PwbV2Packet::to_chunks`] for more details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_chunk_payload` is `0` or larger than [`u16::MAX`], or if
+    /// the packet needs more than `65536` chunks.
+    pub fn to_chunks(&self, max_chunk_payload: usize) -> Vec<Chunk> {
+        match self {
+            Self::V2(packet) => packet.to_chunks(max_chunk_payload),
+        }
+    }
 }
 
 impl TryFrom<&[u8]> for PwbPacket {
@@ -2004,6 +2242,122 @@ impl TryFrom<Vec<Chunk>> for PwbPacket {
     }
 }
 
+/// The error type returned by [`group_chunks`] for an individual bank or group
+/// of chunks.
+#[derive(Error, Debug)]
+pub enum GroupChunksError {
+    /// A data bank is not a valid [`Chunk`].
+    #[error("bad chunk")]
+    BadChunk(#[from] TryChunkFromSliceError),
+    /// The [`BoardId`] in a chunk does not match the one in its bank name.
+    #[error("board id mismatch (expected `{expected:?}`, found `{found:?}`)")]
+    BoardIdMismatch { found: BoardId, expected: BoardId },
+    /// The chunks from an individual AFTER chip can not be assembled into a
+    /// [`PwbPacket`].
+    #[error("bad packet from board `{}` and AFTER `{after_id:?}`", board_id.name())]
+    BadPacket {
+        board_id: BoardId,
+        after_id: AfterId,
+        #[source]
+        source: TryPwbPacketFromChunksError,
+    },
+}
+
+/// Summary of the work done by [`group_chunks`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ChunkGroupingStats {
+    /// Number of PadWing banks seen.
+    pub banks_seen: usize,
+    /// Number of banks successfully parsed into a [`Chunk`].
+    pub chunks_parsed: usize,
+    /// Number of [`PwbPacket`]s successfully assembled.
+    pub packets_assembled: usize,
+    /// Number of [`GroupChunksError::BadChunk`] errors.
+    pub bad_chunks: usize,
+    /// Number of [`GroupChunksError::BoardIdMismatch`] errors.
+    pub board_id_mismatches: usize,
+    /// Number of [`GroupChunksError::BadPacket`] errors.
+    pub bad_packets: usize,
+}
+
+/// Parse all PadWing banks in an event, group their chunks by [`BoardId`] and
+/// [`AfterId`], and assemble them into [`PwbPacket`]s.
+///
+/// The data banks are provided as an iterator over tuples of bank name and data
+/// slice; banks that are not PadWing banks are ignored. An error in a bank or
+/// group does not affect the other groups, i.e. a bad chunk only causes the
+/// packet it belongs to (if known) to be missing from the output.
+///
+/// Bank errors are returned first (in the same order as the input banks),
+/// followed by the assembled packets and group errors sorted by
+/// [`BoardId::index`] and [`AfterId`].
+pub fn group_chunks<'a, I>(
+    banks: I,
+) -> (Vec<Result<PwbPacket, GroupChunksError>>, ChunkGroupingStats)
+where
+    I: IntoIterator<Item = (&'a str, &'a [u8])>,
+{
+    let mut results = Vec::new();
+    let mut stats = ChunkGroupingStats::default();
+    // The set of boards and AFTER chips is small and fixed, so chunks are
+    // grouped in an array indexed by board and chip. This avoids hashing and
+    // allocating a map for every event.
+    let mut groups = [(); PADWING_BOARDS.len()].map(|_| [(); 4].map(|_| Vec::new()));
+
+    for (bank_name, data_slice) in banks {
+        let Ok(bank_name) = PadwingBankName::try_from(bank_name) else {
+            continue;
+        };
+        stats.banks_seen += 1;
+
+        let chunk = match Chunk::try_from(data_slice) {
+            Ok(chunk) => chunk,
+            Err(error) => {
+                stats.bad_chunks += 1;
+                results.push(Err(error.into()));
+                continue;
+            }
+        };
+        stats.chunks_parsed += 1;
+        let key = (chunk.board_id(), chunk.after_id());
+        if key.0 != bank_name.board_id() {
+            stats.board_id_mismatches += 1;
+            results.push(Err(GroupChunksError::BoardIdMismatch {
+                found: key.0,
+                expected: bank_name.board_id(),
+            }));
+            continue;
+        }
+        groups[key.0.index()][key.1 as usize].push(chunk);
+    }
+
+    for (index, chips) in groups.into_iter().enumerate() {
+        let board_id = BoardId::from_index(index).unwrap();
+        let after_ids = [AfterId::A, AfterId::B, AfterId::C, AfterId::D];
+        for (after_id, chunks) in after_ids.into_iter().zip(chips) {
+            if chunks.is_empty() {
+                continue;
+            }
+            match PwbPacket::try_from(chunks) {
+                Ok(packet) => {
+                    stats.packets_assembled += 1;
+                    results.push(Ok(packet));
+                }
+                Err(source) => {
+                    stats.bad_packets += 1;
+                    results.push(Err(GroupChunksError::BadPacket {
+                        board_id,
+                        after_id,
+                        source,
+                    }));
+                }
+            }
+        }
+    }
+
+    (results, stats)
+}
+
 /// The error type returned when calculating the Padwing data suppression
 /// baseline fails.
 #[derive(Error, Debug)]
@@ -2022,7 +2376,7 @@ pub struct CalculateSuppressionBaselineError {
 // the waveform. But I am not sure if this is a good place for this; maybe at
 // a higher level in the analysis chain.
 pub fn suppression_baseline(
-    _run_number: u32,
+    _run_number: RunNumber,
     waveform: &[i16],
 ) -> Result<Option<i16>, CalculateSuppressionBaselineError> {
     if waveform.len() < 68 {
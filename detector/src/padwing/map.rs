@@ -1,4 +1,5 @@
 use crate::padwing::{AfterId, BoardId, PadChannelId};
+use crate::run::{RunNumber, RunRange};
 use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -80,7 +81,7 @@ impl TryFrom<usize> for TpcPwbRow {
 //     - Test inverse map.
 //
 // Also remember to add the inverse (actually needed) map to the lazy_static
-// below and update TpcPwbPosition::try_new.
+// below and update `pwb_maps`.
 const PADWING_BOARDS_4418: [[&str; TPC_PWB_ROWS]; TPC_PWB_COLUMNS] = [
     ["12", "13", "14", "02", "11", "17", "18", "19"],
     ["20", "21", "22", "23", "24", "25", "26", "27"],
@@ -130,16 +131,49 @@ lazy_static! {
         inverse_pwb_map(PADWING_BOARDS_10418);
 }
 
+// Return the map and inverse map of all PWB boards installed in the rTPC for a
+// given run number.
+//
+// The simulation mapping was done to match the mapping of run number 5000.
+#[allow(clippy::type_complexity)]
+fn pwb_maps(
+    run_number: RunNumber,
+) -> Option<(
+    &'static [[&'static str; TPC_PWB_ROWS]; TPC_PWB_COLUMNS],
+    &'static HashMap<BoardId, TpcPwbPosition>,
+)> {
+    if run_number.is_simulation() {
+        Some((&PADWING_BOARDS_4418, &*INV_PADWING_BOARDS_4418))
+    } else {
+        [
+            (
+                RunRange::new(RunNumber::new(4418), RunNumber::new(10418)),
+                (&PADWING_BOARDS_4418, &*INV_PADWING_BOARDS_4418),
+            ),
+            (
+                RunRange::starting_at(RunNumber::new(10418)),
+                (&PADWING_BOARDS_10418, &*INV_PADWING_BOARDS_10418),
+            ),
+        ]
+        .into_iter()
+        .find(|(runs, _)| runs.contains(run_number))
+        .map(|(_, maps)| maps)
+    }
+}
+
 /// The error type returned when mapping a [`BoardId`] to a [`TpcPwbPosition`]
 /// fails.
 #[derive(Debug, Error)]
 pub enum MapTpcPwbPositionError {
     /// There is no mapping available for the given `run_number`.
     #[error("no rTPC PWB mapping available for run number {run_number}")]
-    MissingMap { run_number: u32 },
+    MissingMap { run_number: RunNumber },
     /// The given [`BoardId`] is not in the map for the given `run_number`.
     #[error("pwb `{}` not found in map for run number {run_number}", board_id.name())]
-    BoardIdNotFound { run_number: u32, board_id: BoardId },
+    BoardIdNotFound {
+        run_number: RunNumber,
+        board_id: BoardId,
+    },
 }
 
 /// Position of a Padwing board in the rTPC.
@@ -176,22 +210,20 @@ impl TpcPwbPosition {
     /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
     /// use alpha_g_detector::padwing::map::TpcPwbPosition;
     /// use alpha_g_detector::padwing::BoardId;
+    /// use alpha_g_detector::run::RunNumber;
     ///
-    /// let run_number = 5000;
+    /// let run_number = RunNumber::new(5000);
     /// let board_id = BoardId::try_from("26")?;
     ///
     /// let position = TpcPwbPosition::try_new(run_number, board_id)?;
     /// # Ok(())
     /// # }
-    pub fn try_new(run_number: u32, board_id: BoardId) -> Result<Self, MapTpcPwbPositionError> {
-        let position_map = match run_number {
-            // u32::MAX corresponds to a simulation run. The simulation mapping
-            // was done to match the mapping of run number 5000.
-            u32::MAX => &*INV_PADWING_BOARDS_4418,
-            10418.. => &*INV_PADWING_BOARDS_10418,
-            4418.. => &*INV_PADWING_BOARDS_4418,
-            _ => return Err(MapTpcPwbPositionError::MissingMap { run_number }),
-        };
+    pub fn try_new(
+        run_number: RunNumber,
+        board_id: BoardId,
+    ) -> Result<Self, MapTpcPwbPositionError> {
+        let (_, position_map) =
+            pwb_maps(run_number).ok_or(MapTpcPwbPositionError::MissingMap { run_number })?;
 
         position_map
             .get(&board_id)
@@ -201,6 +233,12 @@ impl TpcPwbPosition {
                 board_id,
             })
     }
+    /// Same as [`TpcPwbPosition::try_new`], but with the run number as a bare
+    /// `u32`.
+    #[deprecated(note = "use `TpcPwbPosition::try_new` with a `RunNumber`")]
+    pub fn try_new_u32(run_number: u32, board_id: BoardId) -> Result<Self, MapTpcPwbPositionError> {
+        Self::try_new(RunNumber::from(run_number), board_id)
+    }
     /// Return the column of the Padwing board within the rTPC.
     ///
     /// # Examples
@@ -209,8 +247,9 @@ impl TpcPwbPosition {
     /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
     /// use alpha_g_detector::padwing::map::{TpcPwbPosition, TpcPwbColumn};
     /// use alpha_g_detector::padwing::BoardId;
+    /// use alpha_g_detector::run::RunNumber;
     ///
-    /// let run_number = 5000;
+    /// let run_number = RunNumber::new(5000);
     /// let board_id = BoardId::try_from("26")?;
     /// let position = TpcPwbPosition::try_new(run_number, board_id)?;
     ///
@@ -228,8 +267,9 @@ impl TpcPwbPosition {
     /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
     /// use alpha_g_detector::padwing::map::{TpcPwbPosition, TpcPwbRow};
     /// use alpha_g_detector::padwing::BoardId;
+    /// use alpha_g_detector::run::RunNumber;
     ///
-    /// let run_number = 5000;
+    /// let run_number = RunNumber::new(5000);
     /// let board_id = BoardId::try_from("26")?;
     /// let position = TpcPwbPosition::try_new(run_number, board_id)?;
     ///
@@ -326,6 +366,10 @@ lazy_static! {
         }
         inverse
     };
+    static ref PADS_0: HashMap<PwbPadPosition, (AfterId, PadChannelId)> = INV_PADS_0
+        .iter()
+        .map(|(&channel, &position)| (position, channel))
+        .collect();
 }
 
 /// The error type returned when mapping an [`AfterId`] and [`PadChannelId`] to a
@@ -333,7 +377,7 @@ lazy_static! {
 #[derive(Debug, Error)]
 #[error("no PWB pad mapping available for run number {run_number}")]
 pub struct MapPwbPadPositionError {
-    run_number: u32,
+    run_number: RunNumber,
 }
 
 /// Position of a pad in a Padwing Board.
@@ -369,8 +413,9 @@ impl PwbPadPosition {
     /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
     /// use alpha_g_detector::padwing::map::PwbPadPosition;
     /// use alpha_g_detector::padwing::{AfterId, PadChannelId};
+    /// use alpha_g_detector::run::RunNumber;
     ///
-    /// let run_number = 5000;
+    /// let run_number = RunNumber::new(5000);
     /// let after_id = AfterId::try_from('A')?;
     /// let pad_channel_id = PadChannelId::try_from(1)?;
     ///
@@ -379,16 +424,26 @@ impl PwbPadPosition {
     /// # }
     /// ```
     pub fn try_new(
-        _run_number: u32,
+        _run_number: RunNumber,
         after_id: AfterId,
         pad_channel_id: PadChannelId,
     ) -> Result<PwbPadPosition, MapPwbPadPositionError> {
         // If this ever changes (and becomes a function of run number), recall
-        // that simulation (run number u32::MAX) was written to match the map
+        // that simulation (`RunNumber::SIMULATION`) was written to match the map
         // from run number 5000.
         let position_map = &INV_PADS_0;
         Ok(*position_map.get(&(after_id, pad_channel_id)).unwrap())
     }
+    /// Same as [`PwbPadPosition::try_new`], but with the run number as a bare
+    /// `u32`.
+    #[deprecated(note = "use `PwbPadPosition::try_new` with a `RunNumber`")]
+    pub fn try_new_u32(
+        run_number: u32,
+        after_id: AfterId,
+        pad_channel_id: PadChannelId,
+    ) -> Result<PwbPadPosition, MapPwbPadPositionError> {
+        Self::try_new(RunNumber::from(run_number), after_id, pad_channel_id)
+    }
     /// Return the column of the pad within the Padwing Board.
     ///
     /// # Examples
@@ -397,8 +452,9 @@ impl PwbPadPosition {
     /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
     /// use alpha_g_detector::padwing::map::{PwbPadPosition, PwbPadColumn};
     /// use alpha_g_detector::padwing::{AfterId, PadChannelId};
+    /// use alpha_g_detector::run::RunNumber;
     ///
-    /// let run_number = 5000;
+    /// let run_number = RunNumber::new(5000);
     /// let after_id = AfterId::try_from('A')?;
     /// let pad_channel_id = PadChannelId::try_from(1)?;
     ///
@@ -419,8 +475,9 @@ impl PwbPadPosition {
     /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
     /// use alpha_g_detector::padwing::map::{PwbPadPosition, PwbPadRow};
     /// use alpha_g_detector::padwing::{AfterId, PadChannelId};
+    /// use alpha_g_detector::run::RunNumber;
     ///
-    /// let run_number = 5000;
+    /// let run_number = RunNumber::new(5000);
     /// let after_id = AfterId::try_from('A')?;
     /// let pad_channel_id = PadChannelId::try_from(1)?;
     ///
@@ -538,12 +595,41 @@ pub enum MapTpcPadPositionError {
     BadPwbPadPosition(#[from] MapPwbPadPositionError),
 }
 
+/// The error type returned when mapping a [`TpcPadPosition`] back to a
+/// [`BoardId`], [`AfterId`], and [`PadChannelId`] fails.
+#[derive(Debug, Error)]
+#[error("no rTPC PWB mapping available for run number {run_number}")]
+pub struct MapPadChannelError {
+    run_number: RunNumber,
+}
+
 /// Position of a pad in the rTPC.
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+///
+/// Pads are ordered in column-major order i.e. first by column and then by
+/// row. This is the same order as [`TpcPadPosition::index`].
+// The derived `Ord` relies on the declaration order of the fields. Do not
+// reorder them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct TpcPadPosition {
     pub column: TpcPadColumn,
     pub row: TpcPadRow,
 }
+impl TryFrom<usize> for TpcPadPosition {
+    type Error = TryPositionFromIndexError;
+
+    /// Convert from a `usize` (`0..TPC_PADS`) to a [`TpcPadPosition`]. This is
+    /// the inverse of [`TpcPadPosition::index`].
+    fn try_from(value: usize) -> Result<Self, Self::Error> {
+        if value < TPC_PADS {
+            Ok(TpcPadPosition {
+                column: TpcPadColumn(value / TPC_PAD_ROWS),
+                row: TpcPadRow(value % TPC_PAD_ROWS),
+            })
+        } else {
+            Err(TryPositionFromIndexError { input: value })
+        }
+    }
+}
 impl TpcPadPosition {
     /// Map a [`TpcPwbPosition`] and [`PwbPadPosition`] to a [`TpcPadPosition`].
     ///
@@ -553,8 +639,9 @@ impl TpcPadPosition {
     /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
     /// use alpha_g_detector::padwing::map::{TpcPadPosition, TpcPwbPosition, PwbPadPosition};
     /// use alpha_g_detector::padwing::{AfterId, PadChannelId, BoardId};
+    /// use alpha_g_detector::run::RunNumber;
     ///
-    /// let run_number = 5000;
+    /// let run_number = RunNumber::new(5000);
     /// let board = BoardId::try_from("26")?;
     /// let board_pos = TpcPwbPosition::try_new(run_number, board)?;
     ///
@@ -585,8 +672,9 @@ impl TpcPadPosition {
     /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
     /// use alpha_g_detector::padwing::map::TpcPadPosition;
     /// use alpha_g_detector::padwing::{AfterId, PadChannelId, BoardId};
+    /// use alpha_g_detector::run::RunNumber;
     ///
-    /// let run_number = 5000;
+    /// let run_number = RunNumber::new(5000);
     /// let board = BoardId::try_from("26")?;
     /// let after = AfterId::try_from('A')?;
     /// let pad_channel = PadChannelId::try_from(1)?;
@@ -596,7 +684,7 @@ impl TpcPadPosition {
     /// # }
     /// ```
     pub fn try_new(
-        run_number: u32,
+        run_number: RunNumber,
         board_id: BoardId,
         after_id: AfterId,
         pad_channel_id: PadChannelId,
@@ -605,6 +693,72 @@ impl TpcPadPosition {
         let pad_position = PwbPadPosition::try_new(run_number, after_id, pad_channel_id)?;
         Ok(TpcPadPosition::new(board_position, pad_position))
     }
+    /// Same as [`TpcPadPosition::try_new`], but with the run number as a bare
+    /// `u32`.
+    #[deprecated(note = "use `TpcPadPosition::try_new` with a `RunNumber`")]
+    pub fn try_new_u32(
+        run_number: u32,
+        board_id: BoardId,
+        after_id: AfterId,
+        pad_channel_id: PadChannelId,
+    ) -> Result<Self, MapTpcPadPositionError> {
+        Self::try_new(
+            RunNumber::from(run_number),
+            board_id,
+            after_id,
+            pad_channel_id,
+        )
+    }
+    /// Map a [`TpcPadPosition`] back to the [`BoardId`], [`AfterId`], and
+    /// [`PadChannelId`] that read it out for a given `run_number`. This is the
+    /// inverse of [`TpcPadPosition::try_new`]. Returns an error if there is no
+    /// map available for the given `run_number`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use alpha_g_detector::padwing::map::TpcPadPosition;
+    /// use alpha_g_detector::padwing::{AfterId, PadChannelId, BoardId};
+    /// use alpha_g_detector::run::RunNumber;
+    ///
+    /// let run_number = RunNumber::new(5000);
+    /// let board = BoardId::try_from("26")?;
+    /// let after = AfterId::try_from('A')?;
+    /// let pad_channel = PadChannelId::try_from(1)?;
+    /// let tpc_pad_position = TpcPadPosition::try_new(run_number, board, after, pad_channel)?;
+    ///
+    /// assert_eq!(
+    ///     tpc_pad_position.board_after_channel(run_number)?,
+    ///     (board, after, pad_channel)
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn board_after_channel(
+        self,
+        run_number: RunNumber,
+    ) -> Result<(BoardId, AfterId, PadChannelId), MapPadChannelError> {
+        let (board_map, _) = pwb_maps(run_number).ok_or(MapPadChannelError { run_number })?;
+        // If the (AFTER, channel) -> position mapping ever becomes a function of
+        // run number, do the same as in `PwbPadPosition::try_new`.
+        let pad_map = &PADS_0;
+
+        let (column, row) = (self.column.0, self.row.0);
+        let board_name = board_map[column / PWB_PAD_COLUMNS][row / PWB_PAD_ROWS];
+        let pad_position = PwbPadPosition {
+            column: PwbPadColumn(column % PWB_PAD_COLUMNS),
+            row: PwbPadRow(row % PWB_PAD_ROWS),
+        };
+        let (after_id, pad_channel_id) = pad_map[&pad_position];
+
+        Ok((
+            // Safe to unwrap. Unit tests validate all the board names.
+            BoardId::try_from(board_name).unwrap(),
+            after_id,
+            pad_channel_id,
+        ))
+    }
     /// Return the `z` coordinate (in meters) of the pad center within the rTPC.
     /// The `z` coordinate is measured from the center of the rTPC (positive
     /// upward).
@@ -615,8 +769,9 @@ impl TpcPadPosition {
     /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
     /// use alpha_g_detector::padwing::map::TpcPadPosition;
     /// use alpha_g_detector::padwing::{AfterId, PadChannelId, BoardId};
+    /// use alpha_g_detector::run::RunNumber;
     ///
-    /// let run_number = 5000;
+    /// let run_number = RunNumber::new(5000);
     /// let board = BoardId::try_from("26")?;
     /// let after = AfterId::try_from('A')?;
     /// let pad_channel = PadChannelId::try_from(1)?;
@@ -639,8 +794,9 @@ impl TpcPadPosition {
     /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
     /// use alpha_g_detector::padwing::map::TpcPadPosition;
     /// use alpha_g_detector::padwing::{AfterId, PadChannelId, BoardId};
+    /// use alpha_g_detector::run::RunNumber;
     ///
-    /// let run_number = 5000;
+    /// let run_number = RunNumber::new(5000);
     /// let board = BoardId::try_from("26")?;
     /// let after = AfterId::try_from('A')?;
     /// let pad_channel = PadChannelId::try_from(1)?;
@@ -654,6 +810,30 @@ impl TpcPadPosition {
     pub fn phi(&self) -> f64 {
         self.column.phi()
     }
+    /// Return a unique index (`0..TPC_PADS`) of the pad in column-major order.
+    /// This is useful e.g. to store pad data in a dense array. Do not assume
+    /// any spatial information based on this index; the
+    /// [`TpcPadPosition::phi()`] and [`TpcPadPosition::z()`] methods should be
+    /// used instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use alpha_g_detector::padwing::map::{TpcPadPosition, TPC_PADS};
+    ///
+    /// let first = TpcPadPosition::try_from(0)?;
+    /// let last = TpcPadPosition::try_from(TPC_PADS - 1)?;
+    ///
+    /// assert_eq!(first.index(), 0);
+    /// assert_eq!(last.index(), TPC_PADS - 1);
+    /// assert!(first < last);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn index(&self) -> usize {
+        self.column.0 * TPC_PAD_ROWS + self.row.0
+    }
 }
 
 #[cfg(test)]
@@ -181,8 +181,10 @@ fn tpc_pwb_position_missing_map() {
     for i in 0..4418 {
         for (name, _mac, _id) in PADWING_BOARDS {
             let board_id = BoardId::try_from(name).unwrap();
-            match TpcPwbPosition::try_new(i, board_id) {
-                Err(MapTpcPwbPositionError::MissingMap { run_number }) => assert_eq!(run_number, i),
+            match TpcPwbPosition::try_new(RunNumber::new(i), board_id) {
+                Err(MapTpcPwbPositionError::MissingMap { run_number }) => {
+                    assert_eq!(run_number, RunNumber::new(i))
+                }
                 _ => unreachable!(),
             }
         }
@@ -194,8 +196,8 @@ fn tpc_pwb_position_sim_correctness() {
     for name in PADWING_BOARDS_4418.iter().flatten() {
         let board_id = BoardId::try_from(*name).unwrap();
 
-        let pos_5000 = TpcPwbPosition::try_new(5000, board_id).unwrap();
-        let pos_sim = TpcPwbPosition::try_new(u32::MAX, board_id).unwrap();
+        let pos_5000 = TpcPwbPosition::try_new(RunNumber::new(5000), board_id).unwrap();
+        let pos_sim = TpcPwbPosition::try_new(RunNumber::SIMULATION, board_id).unwrap();
 
         assert_eq!(pos_5000, pos_sim);
     }
@@ -211,8 +213,11 @@ fn inverse_map_tpc_pwb_position_4418() {
                     row: TpcPwbRow(j),
                 };
                 assert_eq!(
-                    TpcPwbPosition::try_new(run_number, BoardId::try_from(*board).unwrap())
-                        .unwrap(),
+                    TpcPwbPosition::try_new(
+                        RunNumber::new(run_number),
+                        BoardId::try_from(*board).unwrap()
+                    )
+                    .unwrap(),
                     position
                 );
             }
@@ -242,16 +247,18 @@ fn inverse_map_tpc_pwb_position_10418() {
                 row: TpcPwbRow(*row),
             };
             assert_eq!(
-                TpcPwbPosition::try_new(10418, BoardId::try_from(name).unwrap()).unwrap(),
+                TpcPwbPosition::try_new(RunNumber::new(10418), BoardId::try_from(name).unwrap())
+                    .unwrap(),
                 position
             );
             count += 1;
         } else if let Ok(position) =
-            TpcPwbPosition::try_new(10418, BoardId::try_from(name).unwrap())
+            TpcPwbPosition::try_new(RunNumber::new(10418), BoardId::try_from(name).unwrap())
         {
             assert_eq!(
                 position,
-                TpcPwbPosition::try_new(4418, BoardId::try_from(name).unwrap()).unwrap()
+                TpcPwbPosition::try_new(RunNumber::new(4418), BoardId::try_from(name).unwrap())
+                    .unwrap()
             );
             count += 1;
         }
@@ -322,7 +329,7 @@ fn pwb_pad_position_all_exist() {
     for after in 'A'..='D' {
         for channel in 1..=72 {
             assert!(PwbPadPosition::try_new(
-                0,
+                RunNumber::new(0),
                 AfterId::try_from(after).unwrap(),
                 PadChannelId::try_from(channel).unwrap()
             )
@@ -336,7 +343,7 @@ fn pwb_pad_position_correctness() {
     for (row, channel) in (19..=36).rev().enumerate() {
         assert_eq!(
             PwbPadPosition::try_new(
-                0,
+                RunNumber::new(0),
                 AfterId::try_from('A').unwrap(),
                 PadChannelId::try_from(channel).unwrap()
             )
@@ -350,7 +357,7 @@ fn pwb_pad_position_correctness() {
     for (row, channel) in (37..=54).rev().enumerate() {
         assert_eq!(
             PwbPadPosition::try_new(
-                0,
+                RunNumber::new(0),
                 AfterId::try_from('A').unwrap(),
                 PadChannelId::try_from(channel).unwrap()
             )
@@ -364,7 +371,7 @@ fn pwb_pad_position_correctness() {
     for (row, channel) in (19..=36).rev().enumerate() {
         assert_eq!(
             PwbPadPosition::try_new(
-                0,
+                RunNumber::new(0),
                 AfterId::try_from('B').unwrap(),
                 PadChannelId::try_from(channel).unwrap()
             )
@@ -378,7 +385,7 @@ fn pwb_pad_position_correctness() {
     for (row, channel) in (37..=54).rev().enumerate() {
         assert_eq!(
             PwbPadPosition::try_new(
-                0,
+                RunNumber::new(0),
                 AfterId::try_from('B').unwrap(),
                 PadChannelId::try_from(channel).unwrap()
             )
@@ -392,7 +399,7 @@ fn pwb_pad_position_correctness() {
     for (row, channel) in (1..=18).enumerate() {
         assert_eq!(
             PwbPadPosition::try_new(
-                0,
+                RunNumber::new(0),
                 AfterId::try_from('A').unwrap(),
                 PadChannelId::try_from(channel).unwrap()
             )
@@ -406,7 +413,7 @@ fn pwb_pad_position_correctness() {
     for (row, channel) in (55..=72).enumerate() {
         assert_eq!(
             PwbPadPosition::try_new(
-                0,
+                RunNumber::new(0),
                 AfterId::try_from('A').unwrap(),
                 PadChannelId::try_from(channel).unwrap()
             )
@@ -420,7 +427,7 @@ fn pwb_pad_position_correctness() {
     for (row, channel) in (1..=18).enumerate() {
         assert_eq!(
             PwbPadPosition::try_new(
-                0,
+                RunNumber::new(0),
                 AfterId::try_from('B').unwrap(),
                 PadChannelId::try_from(channel).unwrap()
             )
@@ -434,7 +441,7 @@ fn pwb_pad_position_correctness() {
     for (row, channel) in (55..=72).enumerate() {
         assert_eq!(
             PwbPadPosition::try_new(
-                0,
+                RunNumber::new(0),
                 AfterId::try_from('B').unwrap(),
                 PadChannelId::try_from(channel).unwrap()
             )
@@ -448,7 +455,7 @@ fn pwb_pad_position_correctness() {
     for (row, channel) in (55..=72).rev().enumerate() {
         assert_eq!(
             PwbPadPosition::try_new(
-                0,
+                RunNumber::new(0),
                 AfterId::try_from('D').unwrap(),
                 PadChannelId::try_from(channel).unwrap()
             )
@@ -462,7 +469,7 @@ fn pwb_pad_position_correctness() {
     for (row, channel) in (1..=18).rev().enumerate() {
         assert_eq!(
             PwbPadPosition::try_new(
-                0,
+                RunNumber::new(0),
                 AfterId::try_from('D').unwrap(),
                 PadChannelId::try_from(channel).unwrap()
             )
@@ -476,7 +483,7 @@ fn pwb_pad_position_correctness() {
     for (row, channel) in (55..=72).rev().enumerate() {
         assert_eq!(
             PwbPadPosition::try_new(
-                0,
+                RunNumber::new(0),
                 AfterId::try_from('C').unwrap(),
                 PadChannelId::try_from(channel).unwrap()
             )
@@ -490,7 +497,7 @@ fn pwb_pad_position_correctness() {
     for (row, channel) in (1..=18).rev().enumerate() {
         assert_eq!(
             PwbPadPosition::try_new(
-                0,
+                RunNumber::new(0),
                 AfterId::try_from('C').unwrap(),
                 PadChannelId::try_from(channel).unwrap()
             )
@@ -504,7 +511,7 @@ fn pwb_pad_position_correctness() {
     for (row, channel) in (37..=54).enumerate() {
         assert_eq!(
             PwbPadPosition::try_new(
-                0,
+                RunNumber::new(0),
                 AfterId::try_from('D').unwrap(),
                 PadChannelId::try_from(channel).unwrap()
             )
@@ -518,7 +525,7 @@ fn pwb_pad_position_correctness() {
     for (row, channel) in (19..=36).enumerate() {
         assert_eq!(
             PwbPadPosition::try_new(
-                0,
+                RunNumber::new(0),
                 AfterId::try_from('D').unwrap(),
                 PadChannelId::try_from(channel).unwrap()
             )
@@ -532,7 +539,7 @@ fn pwb_pad_position_correctness() {
     for (row, channel) in (37..=54).enumerate() {
         assert_eq!(
             PwbPadPosition::try_new(
-                0,
+                RunNumber::new(0),
                 AfterId::try_from('C').unwrap(),
                 PadChannelId::try_from(channel).unwrap()
             )
@@ -546,7 +553,7 @@ fn pwb_pad_position_correctness() {
     for (row, channel) in (19..=36).enumerate() {
         assert_eq!(
             PwbPadPosition::try_new(
-                0,
+                RunNumber::new(0),
                 AfterId::try_from('C').unwrap(),
                 PadChannelId::try_from(channel).unwrap()
             )
@@ -708,7 +715,7 @@ fn tpc_pad_position_bad_tpc_pwb_position() {
         let channel_id = PadChannelId::try_from(1).unwrap();
 
         assert!(matches!(
-            TpcPadPosition::try_new(run_number, board_id, after_id, channel_id),
+            TpcPadPosition::try_new(RunNumber::new(run_number), board_id, after_id, channel_id),
             Err(MapTpcPadPositionError::BadTpcPwbPosition(_))
         ));
     }
@@ -716,7 +723,7 @@ fn tpc_pad_position_bad_tpc_pwb_position() {
 
 #[test]
 fn tpc_pad_position_try_new() {
-    let run_number = 4418;
+    let run_number = RunNumber::new(4418);
     for (column, row) in REGRESSION_GATE_KEEPER_4418.into_iter().enumerate() {
         for (row, name) in row.into_iter().enumerate() {
             let board_id = BoardId::try_from(name).unwrap();
@@ -846,3 +853,140 @@ fn tpc_pad_position_phi() {
         }
     }
 }
+
+#[test]
+fn tpc_pad_position_after_groups() {
+    for (run_number, map) in [
+        (RunNumber::new(4418), PADWING_BOARDS_4418),
+        (RunNumber::new(10418), PADWING_BOARDS_10418),
+    ] {
+        for (column, row) in map.into_iter().enumerate() {
+            for (row, name) in row.into_iter().enumerate() {
+                let board_id = BoardId::try_from(name).unwrap();
+
+                let mut board_pads = std::collections::HashSet::new();
+                for after in ['A', 'B', 'C', 'D'] {
+                    let after_id = AfterId::try_from(after).unwrap();
+                    // FPN and reset channels are not pad channels, so every
+                    // AFTER chip should have exactly 72 pads.
+                    let after_pads: std::collections::HashSet<_> = (1..=72)
+                        .map(|channel| {
+                            let pad_channel_id = PadChannelId::try_from(channel).unwrap();
+                            TpcPadPosition::try_new(run_number, board_id, after_id, pad_channel_id)
+                                .unwrap()
+                        })
+                        .collect();
+                    assert_eq!(after_pads.len(), 72);
+
+                    for position in after_pads {
+                        assert_eq!(usize::from(position.column) / 4, column);
+                        assert_eq!(usize::from(position.row) / 72, row);
+                        assert!(board_pads.insert(position));
+                    }
+                }
+                assert_eq!(board_pads.len(), 288);
+            }
+        }
+    }
+}
+
+#[test]
+fn tpc_pad_position_index_roundtrip() {
+    for i in 0..TPC_PADS {
+        let position = TpcPadPosition::try_from(i).unwrap();
+        assert_eq!(position.index(), i);
+    }
+    for i in TPC_PADS..=2 * TPC_PADS {
+        assert!(TpcPadPosition::try_from(i).is_err());
+    }
+
+    for column in 0..TPC_PAD_COLUMNS {
+        for row in 0..TPC_PAD_ROWS {
+            let position = TpcPadPosition {
+                column: TpcPadColumn::try_from(column).unwrap(),
+                row: TpcPadRow::try_from(row).unwrap(),
+            };
+            assert_eq!(
+                TpcPadPosition::try_from(position.index()).unwrap(),
+                position
+            );
+        }
+    }
+}
+
+#[test]
+fn tpc_pad_position_ordering() {
+    for i in 1..TPC_PADS {
+        let previous = TpcPadPosition::try_from(i - 1).unwrap();
+        let current = TpcPadPosition::try_from(i).unwrap();
+        assert!(previous < current);
+        // Column-major order.
+        if previous.column == current.column {
+            assert_eq!(usize::from(previous.row) + 1, usize::from(current.row));
+        } else {
+            assert_eq!(
+                usize::from(previous.column) + 1,
+                usize::from(current.column)
+            );
+            assert_eq!(usize::from(previous.row), TPC_PAD_ROWS - 1);
+            assert_eq!(usize::from(current.row), 0);
+        }
+    }
+}
+
+// One run number per mapping epoch (and the boundaries between them).
+const MAP_EPOCHS: [RunNumber; 5] = [
+    RunNumber::new(4418),
+    RunNumber::new(10417),
+    RunNumber::new(10418),
+    RunNumber::new(u32::MAX - 1),
+    RunNumber::SIMULATION,
+];
+
+#[test]
+fn tpc_pad_position_board_after_channel_roundtrip() {
+    for run_number in MAP_EPOCHS {
+        for i in 0..TPC_PADS {
+            let pad_position = TpcPadPosition::try_from(i).unwrap();
+            let (board_id, after_id, pad_channel_id) =
+                pad_position.board_after_channel(run_number).unwrap();
+
+            assert_eq!(
+                TpcPadPosition::try_new(run_number, board_id, after_id, pad_channel_id).unwrap(),
+                pad_position
+            );
+        }
+    }
+}
+
+#[test]
+fn tpc_pad_position_board_after_channel_missing_map() {
+    let pad_position = TpcPadPosition::try_from(0).unwrap();
+    assert!(pad_position
+        .board_after_channel(RunNumber::new(4417))
+        .is_err());
+}
+
+#[test]
+#[allow(deprecated)]
+fn try_new_u32_same_as_run_number() {
+    let after_id = AfterId::try_from('B').unwrap();
+    let pad_channel_id = PadChannelId::try_from(37).unwrap();
+    for run_number in [0, 4418, 5000] {
+        for board_id in (0..).map_while(BoardId::from_index) {
+            let expected = RunNumber::from(run_number);
+            assert_eq!(
+                TpcPwbPosition::try_new_u32(run_number, board_id).ok(),
+                TpcPwbPosition::try_new(expected, board_id).ok()
+            );
+            assert_eq!(
+                TpcPadPosition::try_new_u32(run_number, board_id, after_id, pad_channel_id).ok(),
+                TpcPadPosition::try_new(expected, board_id, after_id, pad_channel_id).ok()
+            );
+        }
+        assert_eq!(
+            PwbPadPosition::try_new_u32(run_number, after_id, pad_channel_id).unwrap(),
+            PwbPadPosition::try_new(RunNumber::from(run_number), after_id, pad_channel_id).unwrap()
+        );
+    }
+}
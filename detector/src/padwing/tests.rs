@@ -16,15 +16,63 @@ fn pwb_min() {
     assert_eq!(PWB_MIN, -2048);
 }
 
-#[test]
-fn padwing_boards() {
-    for (i, board) in PADWING_BOARDS.iter().enumerate() {
-        for other_board in PADWING_BOARDS.iter().skip(i + 1) {
-            assert_ne!(board.0, other_board.0);
-            assert_ne!(board.1, other_board.1);
-            assert_ne!(board.2, other_board.2);
+// Every inconsistency in a table of boards with the same format as
+// `PADWING_BOARDS`.
+fn board_table_errors(boards: &[(&str, [u8; 6], u32)]) -> Vec<String> {
+    let mut errors = Vec::new();
+    for (i, board) in boards.iter().enumerate() {
+        let (name, mac, device_id) = *board;
+        if name.len() != 2 || !name.bytes().all(|b| b.is_ascii_alphanumeric()) {
+            errors.push(format!("board `{name}`: name is not 2 ASCII characters"));
+        }
+        let expected = u32::from_le_bytes([mac[0], mac[1], mac[2], mac[3]]);
+        if device_id != expected {
+            errors.push(format!(
+                "board `{name}`: device ID `{device_id}` is not the first 4 MAC bytes `{expected}`"
+            ));
+        }
+        for other in &boards[i + 1..] {
+            if name == other.0 {
+                errors.push(format!("duplicate name `{name}`"));
+            }
+            if mac == other.1 {
+                errors.push(format!(
+                    "boards `{name}` and `{}` have the same MAC",
+                    other.0
+                ));
+            }
+            if device_id == other.2 {
+                errors.push(format!(
+                    "boards `{name}` and `{}` have the same device ID",
+                    other.0
+                ));
+            }
         }
     }
+    errors
+}
+
+#[test]
+fn padwing_boards() {
+    let errors = board_table_errors(&PADWING_BOARDS);
+    assert!(
+        errors.is_empty(),
+        "inconsistent `PADWING_BOARDS`:\n{}",
+        errors.join("\n")
+    );
+}
+
+#[test]
+fn board_table_errors_inconsistent() {
+    let mut boards = PADWING_BOARDS.to_vec();
+    boards.push(("00", [236, 40, 255, 135, 0, 0], 2281646316));
+    boards.push(("AB", [1, 2, 3, 4, 5, 6], 7));
+    let errors = board_table_errors(&boards);
+
+    assert_eq!(errors.len(), 3);
+    assert!(errors[0].contains("duplicate name `00`"));
+    assert!(errors[1].contains("boards `00` and `00` have the same device ID"));
+    assert!(errors[2].starts_with("board `AB`: device ID `7`"));
 }
 
 #[test]
@@ -52,6 +100,28 @@ fn board_id() {
     }
 }
 
+#[test]
+fn board_id_unknown() {
+    for name in ["", "0", "09", "99", "000"] {
+        assert!(BoardId::try_from(name).is_err());
+    }
+    assert!(BoardId::try_from([0; 6]).is_err());
+    assert!(BoardId::try_from([236, 40, 255, 135, 84, 3]).is_err());
+    for device_id in [0, 1, u32::MAX] {
+        assert!(BoardId::try_from(device_id).is_err());
+    }
+}
+
+#[test]
+fn board_id_index() {
+    for (index, triplet) in PADWING_BOARDS.iter().enumerate() {
+        let board_id = BoardId::from_index(index).unwrap();
+        assert_eq!(board_id, BoardId::try_from(triplet.0).unwrap());
+        assert_eq!(board_id.index(), index);
+    }
+    assert!(BoardId::from_index(PADWING_BOARDS.len()).is_none());
+}
+
 #[test]
 fn try_from_unsigned_after() {
     assert!(matches!(AfterId::try_from(0).unwrap(), AfterId::A));
@@ -427,6 +497,21 @@ fn chunk_payload_crc() {
     }
 }
 
+#[test]
+fn chunk_to_bytes() {
+    for buffer in [
+        &CHUNK[..],
+        &CHUNK_ZERO,
+        &CHUNK_ONE,
+        &CHUNK_TWO,
+        &CHUNK_ALONE,
+    ] {
+        let chunk = Chunk::try_from(buffer).unwrap();
+        assert_eq!(chunk.to_bytes(), buffer);
+        assert_eq!(Chunk::try_from(&chunk.to_bytes()[..]).unwrap(), chunk);
+    }
+}
+
 #[test]
 fn try_from_unsigned_compression() {
     assert!(matches!(
@@ -1135,6 +1220,23 @@ fn pwb_v2_packet_waveform_at() {
     );
 }
 
+#[test]
+fn pwb_v2_packet_to_bytes() {
+    let packet = PwbV2Packet::try_from(&ODD_PWB_V2_PACKET[..]).unwrap();
+    assert_eq!(packet.to_bytes(), ODD_PWB_V2_PACKET);
+    assert_eq!(
+        PwbV2Packet::try_from(&packet.to_bytes()[..]).unwrap(),
+        packet
+    );
+}
+
+#[test]
+fn channel_id_readout_index() {
+    for i in 1..=79 {
+        assert_eq!(ChannelId::try_from(i).unwrap().readout_index(), i);
+    }
+}
+
 const CHUNK_ZERO: [u8; 64] = [
     236, 40, 255, 135, 0, 0, 0, 0, 0, 0, 3, 0, 0, 0, 40, 0, 118, 99, 211, 179, 2, 68, 0, 0, 236,
     40, 255, 135, 84, 2, 1, 0, 2, 0, 0, 0, 0, 0, 0, 0, 3, 0, 5, 0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1,
@@ -1457,6 +1559,98 @@ fn pwb_v2_packet_from_chunks_bad_payload() {
     ));
 }
 
+#[test]
+fn pwb_v2_packet_to_chunks() {
+    let chunks: Vec<_> = [&CHUNK_ZERO[..], &CHUNK_ONE, &CHUNK_TWO]
+        .into_iter()
+        .map(|buffer| Chunk::try_from(buffer).unwrap())
+        .collect();
+    let packet = PwbV2Packet::try_from(chunks.clone()).unwrap();
+
+    let found = packet.to_chunks(40);
+    assert_eq!(found.len(), chunks.len());
+    for (found, expected) in found.iter().zip(&chunks) {
+        assert_eq!(found.board_id(), expected.board_id());
+        assert_eq!(found.after_id(), expected.after_id());
+        assert_eq!(found.chunk_id(), expected.chunk_id());
+        assert_eq!(found.is_end_of_message(), expected.is_end_of_message());
+        assert_eq!(found.payload(), expected.payload());
+    }
+    assert_eq!(PwbV2Packet::try_from(found).unwrap(), packet);
+
+    for max_chunk_payload in 1..=200 {
+        let chunks = packet.to_chunks(max_chunk_payload);
+        assert_eq!(PwbV2Packet::try_from(chunks).unwrap(), packet);
+    }
+}
+
+// Xorshift pseudo-random number generator. Good enough to generate arbitrary
+// packets without any extra dependency.
+fn xorshift(state: &mut u64) -> u64 {
+    *state ^= *state << 13;
+    *state ^= *state >> 7;
+    *state ^= *state << 17;
+    *state
+}
+
+#[test]
+fn pwb_v2_packet_to_bytes_random_waveforms() {
+    let after_ids = [AfterId::A, AfterId::B, AfterId::C, AfterId::D];
+    let triggers = [Trigger::External, Trigger::Manual, Trigger::InternalPulse];
+    let mut state = 0x9e37_79b9_7f4a_7c15;
+    for _ in 0..200 {
+        let requested_samples = (xorshift(&mut state) % 512) as usize;
+        let random_channels = |state: &mut u64| -> Vec<ChannelId> {
+            let mask = u128::from(xorshift(state)) << 64 | u128::from(xorshift(state));
+            (1..=79u16)
+                .filter(|i| mask >> (i - 1) & 1 == 1)
+                .map(|i| ChannelId::try_from(i).unwrap())
+                .collect()
+        };
+        let channels_sent = random_channels(&mut state);
+        let channels_over_threshold = random_channels(&mut state);
+        let mut data = Vec::new();
+        for channel in &channels_sent {
+            data.push(channel.readout_index() as i16);
+            data.push(requested_samples as i16);
+            data.extend((0..requested_samples).map(|_| xorshift(&mut state) as i16));
+            if requested_samples % 2 != 0 {
+                data.push(0);
+            }
+        }
+        data.extend([0xCCCCu16 as i16; 2]);
+
+        let packet = PwbV2Packet {
+            after_id: after_ids[(xorshift(&mut state) % 4) as usize],
+            compression: Compression::Raw,
+            trigger_source: triggers[(xorshift(&mut state) % 3) as usize],
+            board_id: BoardId::from_index((xorshift(&mut state) % 71) as usize).unwrap(),
+            trigger_delay: xorshift(&mut state) as u16,
+            trigger_timestamp: xorshift(&mut state) & 0xFFFF_FFFF_FFFF,
+            last_sca_cell: (xorshift(&mut state) % 512) as u16,
+            requested_samples,
+            channels_sent,
+            channels_over_threshold,
+            event_counter: xorshift(&mut state) as u32,
+            fifo_max_depth: xorshift(&mut state) as u16,
+            event_descriptor_write_depth: xorshift(&mut state) as u8,
+            event_descriptor_read_depth: xorshift(&mut state) as u8,
+            data,
+        };
+        assert_eq!(
+            PwbV2Packet::try_from(&packet.to_bytes()[..]).unwrap(),
+            packet
+        );
+
+        let max_chunk_payload = 1 + (xorshift(&mut state) % 4096) as usize;
+        let chunks = packet.to_chunks(max_chunk_payload);
+        for chunk in &chunks {
+            assert_eq!(Chunk::try_from(&chunk.to_bytes()[..]).unwrap(), *chunk);
+        }
+        assert_eq!(PwbV2Packet::try_from(chunks).unwrap(), packet);
+    }
+}
+
 #[test]
 fn pwb_packet_good() {
     let chunk_zero = Chunk::try_from(&CHUNK_ZERO[..]).unwrap();
@@ -1691,7 +1885,7 @@ fn pwb_packet_waveform_at() {
 #[test]
 fn suppression_baseline_short_slice() {
     let slice = [0; 67];
-    match suppression_baseline(0, &slice) {
+    match suppression_baseline(RunNumber::new(0), &slice) {
         Err(CalculateSuppressionBaselineError { found }) => {
             assert_eq!(found, 67);
         }
@@ -1702,7 +1896,7 @@ fn suppression_baseline_short_slice() {
 #[test]
 fn suppression_baseline_ok() {
     let mut slice = vec![0; 68];
-    match suppression_baseline(0, &slice) {
+    match suppression_baseline(RunNumber::new(0), &slice) {
         Ok(Some(value)) => {
             assert_eq!(value, 0);
         }
@@ -1713,7 +1907,7 @@ fn suppression_baseline_ok() {
     slice[1] = i16::MAX;
     slice[2] = i16::MAX;
     slice[3] = i16::MAX;
-    match suppression_baseline(0, &slice) {
+    match suppression_baseline(RunNumber::new(0), &slice) {
         Ok(Some(value)) => {
             assert_eq!(value, 0);
         }
@@ -1721,10 +1915,133 @@ fn suppression_baseline_ok() {
     }
 
     slice[67] = 64;
-    match suppression_baseline(0, &slice) {
+    match suppression_baseline(RunNumber::new(0), &slice) {
         Ok(Some(value)) => {
             assert_eq!(value, 1);
         }
         _ => unreachable!(),
     }
 }
+
+#[test]
+fn group_chunks_good() {
+    let banks = [
+        ("PC00", &CHUNK_TWO[..]),
+        ("PC00", &CHUNK_ZERO[..]),
+        ("ATAT", &[][..]),
+        ("PC00", &CHUNK_ONE[..]),
+    ];
+    let (results, stats) = group_chunks(banks);
+
+    assert_eq!(results.len(), 1);
+    let packet = results.into_iter().next().unwrap().unwrap();
+    assert_eq!(packet.board_id(), BoardId::try_from("00").unwrap());
+    assert_eq!(packet.after_id(), AfterId::D);
+    assert_eq!(
+        stats,
+        ChunkGroupingStats {
+            banks_seen: 3,
+            chunks_parsed: 3,
+            packets_assembled: 1,
+            ..Default::default()
+        }
+    );
+}
+
+#[test]
+fn group_chunks_errors_do_not_affect_other_groups() {
+    // Same chunk, but from AFTER A. It is missing the first chunk.
+    let mut lonely_chunk = CHUNK_ONE;
+    lonely_chunk[10] = 0;
+    let crc = !crc32c::crc32c(&lonely_chunk[0..16]);
+    lonely_chunk[16..20].copy_from_slice(&crc.to_le_bytes()[..]);
+
+    let banks = [
+        ("PC00", &CHUNK_ALONE[..]),
+        ("PC00", &lonely_chunk[..]),
+        ("PC00", &CHUNK[..3]),
+        ("PC01", &CHUNK_ALONE[..]),
+    ];
+    let (results, stats) = group_chunks(banks);
+
+    assert_eq!(results.len(), 4);
+    assert!(matches!(results[0], Err(GroupChunksError::BadChunk(_))));
+    match &results[1] {
+        Err(GroupChunksError::BoardIdMismatch { found, expected }) => {
+            assert_eq!(*found, BoardId::try_from("00").unwrap());
+            assert_eq!(*expected, BoardId::try_from("01").unwrap());
+        }
+        _ => unreachable!(),
+    }
+    let packets: Vec<_> = results.iter().filter_map(|r| r.as_ref().ok()).collect();
+    assert_eq!(packets.len(), 1);
+    assert_eq!(packets[0].after_id(), AfterId::D);
+    assert!(results.iter().any(|r| matches!(
+        r,
+        Err(GroupChunksError::BadPacket {
+            after_id: AfterId::A,
+            source: TryPwbPacketFromChunksError::MissingChunk { position: 0 },
+            ..
+        })
+    )));
+    assert_eq!(
+        stats,
+        ChunkGroupingStats {
+            banks_seen: 4,
+            chunks_parsed: 3,
+            packets_assembled: 1,
+            bad_chunks: 1,
+            board_id_mismatches: 1,
+            bad_packets: 1,
+        }
+    );
+}
+
+// Same as `CHUNK_ALONE`, but from any board and AFTER chip.
+fn chunk_alone(board_id: BoardId, after_id: AfterId) -> [u8; 128] {
+    let mut chunk = CHUNK_ALONE;
+    chunk[..4].copy_from_slice(&board_id.device_id().to_le_bytes());
+    chunk[10] = after_id as u8;
+    chunk[21] = b'A' + after_id as u8;
+    chunk[24..30].copy_from_slice(&board_id.mac_address());
+    let crc = !crc32c::crc32c(&chunk[0..16]);
+    chunk[16..20].copy_from_slice(&crc.to_le_bytes()[..]);
+    let crc = !crc32c::crc32c(&chunk[20..124]);
+    chunk[124..].copy_from_slice(&crc.to_le_bytes()[..]);
+    chunk
+}
+
+#[test]
+fn group_chunks_all_boards() {
+    let after_ids = [AfterId::A, AfterId::B, AfterId::C, AfterId::D];
+    let keys: Vec<_> = (0..)
+        .map_while(BoardId::from_index)
+        .flat_map(|board_id| after_ids.map(|after_id| (board_id, after_id)))
+        .collect();
+    // Reverse order to check that the output doesn't depend on the input.
+    let banks: Vec<_> = keys
+        .iter()
+        .rev()
+        .map(|&(board_id, after_id)| {
+            (
+                format!("PC{}", board_id.name()),
+                chunk_alone(board_id, after_id),
+            )
+        })
+        .collect();
+    let (results, stats) = group_chunks(
+        banks
+            .iter()
+            .map(|(name, chunk)| (name.as_str(), &chunk[..])),
+    );
+
+    assert_eq!(stats.packets_assembled, PADWING_BOARDS.len() * 4);
+    let found: Vec<_> = results
+        .into_iter()
+        .map(|result| {
+            let packet = result.unwrap();
+            (packet.board_id(), packet.after_id())
+        })
+        .collect();
+    assert_eq!(found, keys);
+}
@@ -0,0 +1,129 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::num::ParseIntError;
+use std::str::FromStr;
+
+/// Run number.
+///
+/// This is a thin wrapper around a [`u32`] that prevents mixing up a run
+/// number with any other integer (e.g. the serial number of an event).
+/// Conversions to and from [`u32`] are always available.
+///
+/// # Examples
+///
+/// ```
+/// use alpha_g_detector::run::RunNumber;
+///
+/// let run_number = RunNumber::from(5000);
+/// assert_eq!(u32::from(run_number), 5000);
+/// assert_eq!(run_number.to_string(), "5000");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct RunNumber(u32);
+
+impl RunNumber {
+    /// Run number of simulated data. The detector mappings of simulation match
+    /// those of run number 5000, and no calibration epoch applies to it.
+    pub const SIMULATION: Self = Self(u32::MAX);
+
+    /// Create a new [`RunNumber`]. Same as [`RunNumber::from`], but usable in
+    /// constant expressions.
+    pub const fn new(run_number: u32) -> Self {
+        Self(run_number)
+    }
+    /// Return `true` if this is [`RunNumber::SIMULATION`].
+    pub fn is_simulation(&self) -> bool {
+        *self == Self::SIMULATION
+    }
+}
+
+impl From<u32> for RunNumber {
+    fn from(run_number: u32) -> Self {
+        Self(run_number)
+    }
+}
+
+impl From<RunNumber> for u32 {
+    fn from(run_number: RunNumber) -> Self {
+        run_number.0
+    }
+}
+
+impl fmt::Display for RunNumber {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromStr for RunNumber {
+    type Err = ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(Self)
+    }
+}
+
+/// Range of consecutive run numbers.
+///
+/// A range includes its `start` and excludes its `end` (if any). Ranges without
+/// an end include all the run numbers from `start` onwards, including
+/// [`RunNumber::SIMULATION`].
+///
+/// # Examples
+///
+/// ```
+/// use alpha_g_detector::run::{RunNumber, RunRange};
+///
+/// let range = RunRange::new(RunNumber::new(4418), RunNumber::new(10418));
+/// assert!(range.contains(RunNumber::new(4418)));
+/// assert!(!range.contains(RunNumber::new(10418)));
+///
+/// let range = RunRange::starting_at(RunNumber::new(10418));
+/// assert!(range.contains(RunNumber::new(20000)));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct RunRange {
+    start: RunNumber,
+    end: Option<RunNumber>,
+}
+
+impl RunRange {
+    /// Create a range of run numbers from `start` (inclusive) to `end`
+    /// (exclusive). The range is empty if `end <= start`.
+    pub const fn new(start: RunNumber, end: RunNumber) -> Self {
+        Self {
+            start,
+            end: Some(end),
+        }
+    }
+    /// Create a range with all the run numbers from `start` onwards.
+    pub const fn starting_at(start: RunNumber) -> Self {
+        Self { start, end: None }
+    }
+    /// Return the first run number of the range.
+    pub fn start(&self) -> RunNumber {
+        self.start
+    }
+    /// Return the first run number after the range, or [`None`] if the range
+    /// has no end.
+    pub fn end(&self) -> Option<RunNumber> {
+        self.end
+    }
+    /// Return `true` if the range contains the given run number.
+    pub fn contains(&self, run_number: RunNumber) -> bool {
+        self.start <= run_number && self.end.map_or(true, |end| run_number < end)
+    }
+}
+
+impl fmt::Display for RunRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.end {
+            Some(end) => write!(f, "{}..{end}", self.start),
+            None => write!(f, "{}..", self.start),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,98 @@
+use super::*;
+
+#[test]
+fn run_number_u32_conversions() {
+    for n in [0, 1, 5000, u32::MAX] {
+        let run_number = RunNumber::from(n);
+        assert_eq!(run_number, RunNumber::new(n));
+        assert_eq!(u32::from(run_number), n);
+    }
+}
+
+#[test]
+fn run_number_simulation() {
+    assert!(RunNumber::SIMULATION.is_simulation());
+    assert!(RunNumber::from(u32::MAX).is_simulation());
+    assert!(!RunNumber::from(5000).is_simulation());
+}
+
+#[test]
+fn run_number_ordering() {
+    assert!(RunNumber::new(4418) < RunNumber::new(10418));
+    assert!(RunNumber::new(10418) < RunNumber::SIMULATION);
+}
+
+#[test]
+fn run_number_display_from_str() {
+    let run_number = RunNumber::new(11186);
+    assert_eq!(run_number.to_string(), "11186");
+    assert_eq!("11186".parse::<RunNumber>().unwrap(), run_number);
+
+    assert!("".parse::<RunNumber>().is_err());
+    assert!("-1".parse::<RunNumber>().is_err());
+    assert!("R11186".parse::<RunNumber>().is_err());
+    assert!("4294967296".parse::<RunNumber>().is_err());
+}
+
+#[test]
+fn run_number_serde() {
+    let run_number = RunNumber::new(11186);
+    let json = serde_json::to_string(&run_number).unwrap();
+    assert_eq!(json, "11186");
+    assert_eq!(
+        serde_json::from_str::<RunNumber>(&json).unwrap(),
+        run_number
+    );
+
+    let ron = ron::to_string(&run_number).unwrap();
+    assert_eq!(ron::from_str::<RunNumber>(&ron).unwrap(), run_number);
+}
+
+#[test]
+fn run_range_contains() {
+    let range = RunRange::new(RunNumber::new(4418), RunNumber::new(10418));
+    assert!(!range.contains(RunNumber::new(0)));
+    assert!(!range.contains(RunNumber::new(4417)));
+    assert!(range.contains(RunNumber::new(4418)));
+    assert!(range.contains(RunNumber::new(10417)));
+    assert!(!range.contains(RunNumber::new(10418)));
+    assert!(!range.contains(RunNumber::SIMULATION));
+}
+
+#[test]
+fn run_range_starting_at_contains() {
+    let range = RunRange::starting_at(RunNumber::new(10418));
+    assert!(!range.contains(RunNumber::new(10417)));
+    assert!(range.contains(RunNumber::new(10418)));
+    assert!(range.contains(RunNumber::new(u32::MAX - 1)));
+    assert!(range.contains(RunNumber::SIMULATION));
+}
+
+#[test]
+fn run_range_empty() {
+    let start = RunNumber::new(10);
+    for end in [0, 5, 10] {
+        let range = RunRange::new(start, RunNumber::new(end));
+        assert!((0..=20).all(|n| !range.contains(RunNumber::new(n))));
+    }
+}
+
+#[test]
+fn run_range_accessors() {
+    let range = RunRange::new(RunNumber::new(1), RunNumber::new(2));
+    assert_eq!(range.start(), RunNumber::new(1));
+    assert_eq!(range.end(), Some(RunNumber::new(2)));
+
+    let range = RunRange::starting_at(RunNumber::new(1));
+    assert_eq!(range.start(), RunNumber::new(1));
+    assert_eq!(range.end(), None);
+}
+
+#[test]
+fn run_range_display() {
+    let range = RunRange::new(RunNumber::new(4418), RunNumber::new(10418));
+    assert_eq!(range.to_string(), "4418..10418");
+
+    let range = RunRange::starting_at(RunNumber::new(10418));
+    assert_eq!(range.to_string(), "10418..");
+}
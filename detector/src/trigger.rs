@@ -614,6 +614,11 @@ impl TryFrom<&[u8]> for TrgV3Packet {
 ///
 /// This enum can currently contain only a [`TrgV3Packet`]. See its
 /// documentation for more details.
+///
+/// Note that a [`TrgV3Packet`] has a fixed length of 80 bytes, and it doesn't
+/// carry the multiplicity of each individual Alpha16 board; only the global
+/// AW16 bus and multiplicity are available. Decoding per-board multiplicities
+/// (e.g. to emulate the trigger decision offline) needs a new packet version.
 #[derive(Clone, Copy, Debug)]
 pub enum TrgPacket {
     /// Version 3 of a TRG packet.
@@ -0,0 +1,132 @@
+use alpha_g_detector::alpha16::aw_map::{TpcWirePosition, TPC_ANODE_WIRES};
+use alpha_g_detector::alpha16::{self, Adc32ChannelId};
+use alpha_g_detector::padwing::map::TpcPwbPosition;
+use alpha_g_detector::padwing::{AfterId, BoardId};
+use alpha_g_detector::run::RunNumber;
+use alpha_g_physics::MainEvent;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+// Board 00, AFTER D. Channels sent (readout index): 57 (pad), 67 (FPN 4), and
+// 73 (pad).
+const PWB_CHUNK: [u8; 128] = [
+    236, 40, 255, 135, 2, 0, 0, 0, 2, 0, 3, 1, 0, 0, 104, 0, 240, 152, 78, 132, 2, 68, 0, 0, 236,
+    40, 255, 135, 84, 2, 1, 0, 2, 0, 0, 0, 0, 0, 0, 0, 3, 0, 5, 0, 0, 0, 0, 0, 0, 0, 0, 1, 4, 1, 1,
+    1, 1, 0, 0, 0, 0, 0, 0, 0, 4, 0, 0, 0, 5, 0, 6, 7, 57, 0, 5, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10,
+    0, 0, 67, 0, 5, 0, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 0, 0, 73, 0, 5, 0, 21, 22, 23, 24,
+    25, 26, 27, 28, 29, 30, 0, 0, 204, 204, 204, 204, 183, 97, 50, 1,
+];
+
+const TRG_PACKET: [u8; 80] = [
+    255, 0, 0, 0, 0, 0, 0, 128, 254, 0, 0, 0, 0, 0, 0, 0, 3, 0, 0, 0, 4, 0, 0, 0, 5, 0, 0, 0, 6, 0,
+    0, 0, 7, 0, 0, 0, 8, 0, 0, 128, 2, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 9, 0, 10, 0, 11, 0, 0, 0,
+    0, 0, 0, 0, 12, 0, 0, 0, 13, 0, 0, 0, 14, 0, 0, 0, 0, 0, 0, 224,
+];
+
+const ALPHA16_BOARDS: [&str; 8] = ["09", "10", "11", "12", "13", "14", "16", "18"];
+
+// Alpha16 packet of an anode wire with a few pulses on top of the simulation
+// baseline.
+fn wire_packet(board_id: alpha16::BoardId, channel: u8) -> Vec<u8> {
+    const BASELINE: i16 = 3000;
+    let waveform: Vec<i16> = (0..700)
+        .map(|i| {
+            let pulse = |t0: f64| {
+                let x = (f64::from(i) - t0) / 8.0;
+                1000.0 * (-x * x).exp()
+            };
+            let t0 = 200.0 + f64::from(channel % 5) * 30.0;
+            BASELINE + (pulse(t0) + pulse(t0 + 150.0)) as i16
+        })
+        .collect();
+
+    let mut packet = vec![1, 3, 0, 1, 2, 128 + channel];
+    packet.extend_from_slice(&u16::try_from(waveform.len() + 2).unwrap().to_be_bytes());
+    packet.extend_from_slice(&[0, 0, 0, 4, 0, 0]);
+    packet.extend_from_slice(&board_id.mac_address());
+    packet.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, 5, 0, 0, 0, 6]);
+    packet.extend(waveform.iter().flat_map(|v| v.to_be_bytes()));
+    packet.extend_from_slice(&[0, 0]);
+    packet.extend_from_slice(&BASELINE.to_be_bytes());
+    packet
+}
+
+// Synthetic event with a signal in every anode wire (i.e. every pad column
+// has to be matched), and the same chunk from every AFTER chip of every PadWing
+// board.
+fn synthetic_banks() -> Vec<(String, Vec<u8>)> {
+    let mut banks: Vec<_> = ALPHA16_BOARDS
+        .into_iter()
+        .map(|name| alpha16::BoardId::try_from(name).unwrap())
+        .flat_map(|board_id| (0..32).map(move |channel| (board_id, channel)))
+        .filter(|&(board_id, channel)| {
+            let channel_id = Adc32ChannelId::try_from(channel).unwrap();
+            TpcWirePosition::try_new(RunNumber::SIMULATION, board_id, channel_id).is_ok()
+        })
+        .map(|(board_id, channel)| {
+            let channel_char = char::from_digit(channel.into(), 32)
+                .unwrap()
+                .to_ascii_uppercase();
+            (
+                format!("C{}{channel_char}", board_id.name()),
+                wire_packet(board_id, channel),
+            )
+        })
+        .collect();
+
+    let after_ids = [AfterId::A, AfterId::B, AfterId::C, AfterId::D];
+    banks.extend(
+        (0..)
+            .map_while(BoardId::from_index)
+            .filter(|&board_id| TpcPwbPosition::try_new(RunNumber::SIMULATION, board_id).is_ok())
+            .flat_map(|board_id| after_ids.map(|after_id| (board_id, after_id)))
+            .map(|(board_id, after_id)| {
+                let mut chunk = PWB_CHUNK;
+                chunk[..4].copy_from_slice(&board_id.device_id().to_le_bytes());
+                chunk[10] = after_id as u8;
+                chunk[21] = b'A' + after_id as u8;
+                chunk[24..30].copy_from_slice(&board_id.mac_address());
+                let crc = !crc32c::crc32c(&chunk[0..16]);
+                chunk[16..20].copy_from_slice(&crc.to_le_bytes());
+                let crc = !crc32c::crc32c(&chunk[20..124]);
+                chunk[124..].copy_from_slice(&crc.to_le_bytes());
+
+                (format!("PC{}", board_id.name()), chunk.to_vec())
+            }),
+    );
+    banks.push((String::from("ATAT"), TRG_PACKET.to_vec()));
+
+    banks
+}
+
+// Without the `rayon` feature, `avalanches` processes the pad columns one
+// after the other. To compare against the parallel path, save a baseline
+// without the feature and then run again with it e.g.:
+//
+//     cargo bench -p alpha_g_physics --bench avalanches -- --save-baseline serial
+//     cargo bench -p alpha_g_physics --bench avalanches --features rayon -- --baseline serial
+//
+// With the feature, `avalanches_single_thread` measures the overhead of the
+// parallel path on its own.
+fn wire_heavy_event(c: &mut Criterion) {
+    let banks = synthetic_banks();
+    let banks = banks.iter().map(|(name, data)| (name.as_str(), &data[..]));
+    let event = MainEvent::try_from_banks(RunNumber::SIMULATION, banks).unwrap();
+    assert_eq!(event.num_wire_signals(), TPC_ANODE_WIRES);
+
+    let mut group = c.benchmark_group("wire_heavy_event");
+    group.bench_function("avalanches", |b| b.iter(|| black_box(&event).avalanches()));
+    #[cfg(feature = "rayon")]
+    {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(1)
+            .build()
+            .unwrap();
+        group.bench_function("avalanches_single_thread", |b| {
+            b.iter(|| pool.install(|| black_box(&event).avalanches()))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, wire_heavy_event);
+criterion_main!(benches);
@@ -0,0 +1,65 @@
+use alpha_g_detector::padwing::map::TpcPwbPosition;
+use alpha_g_detector::padwing::{self, AfterId, BoardId};
+use alpha_g_detector::run::RunNumber;
+use alpha_g_physics::MainEvent;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+// Board 00, AFTER D. Channels sent (readout index): 57 (pad), 67 (FPN 4), and
+// 73 (pad).
+const PWB_CHUNK: [u8; 128] = [
+    236, 40, 255, 135, 2, 0, 0, 0, 2, 0, 3, 1, 0, 0, 104, 0, 240, 152, 78, 132, 2, 68, 0, 0, 236,
+    40, 255, 135, 84, 2, 1, 0, 2, 0, 0, 0, 0, 0, 0, 0, 3, 0, 5, 0, 0, 0, 0, 0, 0, 0, 0, 1, 4, 1, 1,
+    1, 1, 0, 0, 0, 0, 0, 0, 0, 4, 0, 0, 0, 5, 0, 6, 7, 57, 0, 5, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10,
+    0, 0, 67, 0, 5, 0, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 0, 0, 73, 0, 5, 0, 21, 22, 23, 24,
+    25, 26, 27, 28, 29, 30, 0, 0, 204, 204, 204, 204, 183, 97, 50, 1,
+];
+
+const TRG_PACKET: [u8; 80] = [
+    255, 0, 0, 0, 0, 0, 0, 128, 254, 0, 0, 0, 0, 0, 0, 0, 3, 0, 0, 0, 4, 0, 0, 0, 5, 0, 0, 0, 6, 0,
+    0, 0, 7, 0, 0, 0, 8, 0, 0, 128, 2, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 9, 0, 10, 0, 11, 0, 0, 0,
+    0, 0, 0, 0, 12, 0, 0, 0, 13, 0, 0, 0, 14, 0, 0, 0, 0, 0, 0, 224,
+];
+
+// Synthetic event with the same chunk from every AFTER chip of every board.
+// This is the worst case for grouping the PadWing chunks of an event.
+fn synthetic_banks() -> Vec<(String, Vec<u8>)> {
+    let after_ids = [AfterId::A, AfterId::B, AfterId::C, AfterId::D];
+    let mut banks: Vec<_> = (0..)
+        .map_while(BoardId::from_index)
+        .filter(|&board_id| TpcPwbPosition::try_new(RunNumber::SIMULATION, board_id).is_ok())
+        .flat_map(|board_id| after_ids.map(|after_id| (board_id, after_id)))
+        .map(|(board_id, after_id)| {
+            let mut chunk = PWB_CHUNK;
+            chunk[..4].copy_from_slice(&board_id.device_id().to_le_bytes());
+            chunk[10] = after_id as u8;
+            chunk[21] = b'A' + after_id as u8;
+            chunk[24..30].copy_from_slice(&board_id.mac_address());
+            let crc = !crc32c::crc32c(&chunk[0..16]);
+            chunk[16..20].copy_from_slice(&crc.to_le_bytes());
+            let crc = !crc32c::crc32c(&chunk[20..124]);
+            chunk[124..].copy_from_slice(&crc.to_le_bytes());
+
+            (format!("PC{}", board_id.name()), chunk.to_vec())
+        })
+        .collect();
+    banks.push((String::from("ATAT"), TRG_PACKET.to_vec()));
+
+    banks
+}
+
+fn pad_heavy_event(c: &mut Criterion) {
+    let banks = synthetic_banks();
+    let banks = || banks.iter().map(|(name, data)| (name.as_str(), &data[..]));
+
+    let mut group = c.benchmark_group("pad_heavy_event");
+    group.bench_function("group_chunks", |b| {
+        b.iter(|| padwing::group_chunks(black_box(banks())))
+    });
+    group.bench_function("try_from_banks", |b| {
+        b.iter(|| MainEvent::try_from_banks(black_box(RunNumber::SIMULATION), banks()).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, pad_heavy_event);
+criterion_main!(benches);
@@ -1,10 +1,11 @@
 use alpha_g_detector::midas::EventId;
+use alpha_g_detector::run::RunNumber;
 use alpha_g_physics::MainEvent;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let contents = std::fs::read("example.mid")?;
     let file_view = midasio::FileView::try_from(&contents[..])?;
-    let run_number = file_view.run_number();
+    let run_number = RunNumber::from(file_view.run_number());
 
     let main_events = file_view
         .into_iter()
@@ -0,0 +1,83 @@
+use alpha_g_detector::alpha16;
+use uom::si::f64::*;
+
+/// Sampling period of the Barrel Veto waveforms (see
+/// [`MainEvent::bv_signals`](crate::MainEvent::bv_signals)).
+pub const SAMPLING_PERIOD: Time = Time {
+    dimension: uom::lib::marker::PhantomData,
+    units: uom::lib::marker::PhantomData,
+    value: 1.0 / alpha16::ADC16_RATE,
+};
+
+/// Pulse in the waveform of a single Barrel Veto SiPM.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BvPulse {
+    /// Leading edge time with respect to the first sample of the waveform.
+    pub t: Time,
+    /// Absolute value of the peak (baseline-subtracted) sample.
+    pub amplitude: f64,
+}
+
+/// Find the pulse in a baseline-subtracted Barrel Veto waveform.
+///
+/// The amplitude of the pulse is given by the sample furthest away from the
+/// baseline, which can be of either polarity. The leading edge time is given
+/// by the last time (before the peak) at which the waveform crosses `fraction`
+/// of the amplitude. Crossing times are linearly interpolated between samples.
+///
+/// Returns [`None`] if the waveform is empty or flat.
+pub fn find_pulse(signal: &[f64], fraction: f64) -> Option<BvPulse> {
+    let (peak, &peak_value) = signal
+        .iter()
+        .enumerate()
+        .max_by(|(_, a), (_, b)| a.abs().total_cmp(&b.abs()))?;
+    if peak_value == 0.0 {
+        return None;
+    }
+    // Flip negative pulses such that everything below is the same for both
+    // polarities.
+    let amplitude = peak_value.abs();
+    let threshold = fraction * amplitude;
+    let value = |i: usize| signal[i] * peak_value.signum();
+
+    let index = match (0..peak).rev().find(|&i| value(i) < threshold) {
+        // Interpolate between the samples right before and after crossing.
+        Some(i) => i as f64 + (threshold - value(i)) / (value(i + 1) - value(i)),
+        // The pulse starts before the first sample.
+        None => 0.0,
+    };
+
+    Some(BvPulse {
+        t: index * SAMPLING_PERIOD,
+        amplitude,
+    })
+}
+
+/// Hit in a single Barrel Veto bar i.e. the combination of the pulses at both
+/// ends of the bar.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BvHit {
+    /// Average of the leading edge times at both ends. This is offset (by the
+    /// same amount in all bars) from the actual time of the hit.
+    pub t: Time,
+    /// Axial position of the hit with respect to the center of the bar.
+    /// Positive values are towards the top end.
+    pub z: Length,
+    /// Geometric mean of the amplitudes at both ends. This is independent of
+    /// the position along the bar for an exponential light attenuation.
+    pub amplitude: f64,
+}
+
+/// Combine the pulses at the top and bottom ends of a Barrel Veto bar into a
+/// single hit. The `speed` is the effective propagation speed of the light
+/// along the bar.
+pub fn combine_ends(top: BvPulse, bottom: BvPulse, speed: Velocity) -> BvHit {
+    BvHit {
+        t: (top.t + bottom.t) / 2.0,
+        z: (bottom.t - top.t) * speed / 2.0,
+        amplitude: (top.amplitude * bottom.amplitude).sqrt(),
+    }
+}
+
+#[cfg(test)]
+mod tests;
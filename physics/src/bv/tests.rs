@@ -0,0 +1,68 @@
+use super::*;
+use uom::si::length::meter;
+use uom::si::time::nanosecond;
+use uom::si::velocity::meter_per_second;
+
+// Pulse that rises linearly for `rise` samples starting at `t0` (in samples),
+// and then decays exponentially.
+fn injected_pulse(t0: f64, rise: f64, amplitude: f64) -> Vec<f64> {
+    (0..200)
+        .map(|i| {
+            let x = f64::from(i) - t0;
+            if x <= 0.0 {
+                0.0
+            } else if x <= rise {
+                amplitude * x / rise
+            } else {
+                amplitude * (-(x - rise) / 20.0).exp()
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn find_pulse_empty_and_flat() {
+    assert!(find_pulse(&[], 0.5).is_none());
+    assert!(find_pulse(&[0.0; 100], 0.5).is_none());
+}
+
+#[test]
+fn find_pulse_injected_times() {
+    for t0 in [10.0, 25.3, 40.5, 73.9] {
+        for amplitude in [100.0, -2000.0] {
+            let signal = injected_pulse(t0, 8.0, amplitude);
+            let pulse = find_pulse(&signal, 0.25).unwrap();
+
+            let expected = (t0 + 2.0) * SAMPLING_PERIOD;
+            assert!((pulse.t - expected).abs() < SAMPLING_PERIOD);
+            assert!((pulse.amplitude - amplitude.abs()).abs() < 1e-9);
+        }
+    }
+}
+
+#[test]
+fn find_pulse_before_first_sample() {
+    let signal = injected_pulse(-5.0, 8.0, 100.0);
+    let pulse = find_pulse(&signal, 0.5).unwrap();
+
+    assert_eq!(pulse.t, Time::new::<nanosecond>(0.0));
+}
+
+#[test]
+fn combine_ends_center_and_top() {
+    let speed = Velocity::new::<meter_per_second>(2e8);
+    let pulse = |t: f64, amplitude: f64| BvPulse {
+        t: Time::new::<nanosecond>(t),
+        amplitude,
+    };
+
+    let hit = combine_ends(pulse(10.0, 100.0), pulse(10.0, 100.0), speed);
+    assert_eq!(hit.t, Time::new::<nanosecond>(10.0));
+    assert_eq!(hit.z, Length::new::<meter>(0.0));
+    assert_eq!(hit.amplitude, 100.0);
+    // Closer to the top end.
+    let hit = combine_ends(pulse(8.0, 400.0), pulse(12.0, 100.0), speed);
+    assert!((hit.t - Time::new::<nanosecond>(10.0)).abs() < Time::new::<nanosecond>(1e-9));
+    assert!((hit.z - Length::new::<meter>(0.4)).abs() < Length::new::<meter>(1e-9));
+    assert!((hit.amplitude - 200.0).abs() < 1e-9);
+}
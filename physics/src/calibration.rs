@@ -1,3 +1,7 @@
+use crate::{MapPadDelayError, MapWireDelayError};
+use alpha_g_detector::run::{RunNumber, RunRange};
+use std::fmt;
+
 // This is a macro definition for a convenient way to include multiple files
 // as byte arrays. It takes a directory and multiple file names.
 // The contents of all files are embedded at compile time.
@@ -13,3 +17,217 @@ macro_rules! includes {
 pub(crate) mod wires;
 // Pad calibration
 pub(crate) mod pads;
+
+/// Default maximum distance (in number of runs) between a run and the run
+/// from which its calibration was derived (see [`staleness`]).
+pub const DEFAULT_STALENESS_MARGIN: u32 = 100;
+
+/// Calibration that is split in epochs, i.e. it changes with the run number.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CalibrationKind {
+    /// Gain of the anode wires.
+    WireGain,
+    /// Azimuthal alignment of the anode wire plane.
+    WireAlignment,
+    /// Timing offsets of the pad columns.
+    PadColumnOffsets,
+}
+
+impl fmt::Display for CalibrationKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::WireGain => write!(f, "wire gain"),
+            Self::WireAlignment => write!(f, "wire alignment"),
+            Self::PadColumnOffsets => write!(f, "pad column offsets"),
+        }
+    }
+}
+
+/// Calibration epoch.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Epoch {
+    /// First run number for which the calibration is valid.
+    pub valid_from_run: u32,
+    /// Run number from which the calibration was derived. This is not
+    /// necessarily within the epoch (e.g. a calibration run taken right after
+    /// a physics run).
+    pub source_run: u32,
+}
+
+impl Epoch {
+    // Find the epoch (if any) used for a given run number. Epochs are sorted,
+    // and each one is valid until the next one starts.
+    pub(crate) fn find(epochs: &[(u32, u32, &[u8])], run_number: u32) -> Option<Self> {
+        Self::find_index(epochs, run_number).map(|index| {
+            let (valid_from_run, source_run, _) = epochs[index];
+            Self {
+                valid_from_run,
+                source_run,
+            }
+        })
+    }
+    // Same as `find`, but return the index of the epoch in `epochs`.
+    fn find_index(epochs: &[(u32, u32, &[u8])], run_number: u32) -> Option<usize> {
+        let run_number = RunNumber::from(run_number);
+        // No calibration epoch is ever used in simulation.
+        if run_number.is_simulation() {
+            return None;
+        }
+        let ends = epochs
+            .iter()
+            .skip(1)
+            .map(|&(next_first_run, _, _)| Some(RunNumber::from(next_first_run)))
+            .chain([None]);
+        epochs
+            .iter()
+            .zip(ends)
+            .position(|(&(first_run, _, _), end)| {
+                let start = RunNumber::from(first_run);
+                let runs = match end {
+                    Some(end) => RunRange::new(start, end),
+                    None => RunRange::starting_at(start),
+                };
+                runs.contains(run_number)
+            })
+    }
+
+    /// Return the number of runs between a run number and the source run of
+    /// the epoch.
+    pub fn distance(&self, run_number: u32) -> u32 {
+        run_number.abs_diff(self.source_run)
+    }
+}
+
+// Values of a calibration that is split in epochs (parsed once from the
+// contents of each calibration file). The value applied to a run and the
+// `Epoch` reported for it come from the same lookup, so they always agree.
+pub(crate) struct EpochValues<T> {
+    epochs: &'static [(u32, u32, &'static [u8])],
+    values: Vec<T>,
+}
+
+impl<T> EpochValues<T> {
+    pub(crate) fn new(
+        epochs: &'static [(u32, u32, &'static [u8])],
+        parse: impl Fn(&[u8]) -> T,
+    ) -> Self {
+        Self {
+            epochs,
+            values: epochs.iter().map(|&(_, _, bytes)| parse(bytes)).collect(),
+        }
+    }
+    // Value used for a given run number. This is `None` in simulation and
+    // before the first epoch.
+    pub(crate) fn get(&self, run_number: u32) -> Option<&T> {
+        Epoch::find_index(self.epochs, run_number).map(|index| &self.values[index])
+    }
+    pub(crate) fn epoch(&self, run_number: u32) -> Option<Epoch> {
+        Epoch::find(self.epochs, run_number)
+    }
+}
+
+/// Warning about a calibration derived from a run too far away from the run
+/// that is being reconstructed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StalenessWarning {
+    /// Type of calibration.
+    pub kind: CalibrationKind,
+    /// Epoch of the calibration.
+    pub epoch: Epoch,
+    /// Number of runs between the reconstructed run and the source run of the
+    /// calibration.
+    pub distance: u32,
+}
+
+impl fmt::Display for StalenessWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} calibration derived from run `{}` is `{}` runs away",
+            self.kind, self.epoch.source_run, self.distance
+        )
+    }
+}
+
+/// Calibration epochs used to reconstruct a run.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CalibrationSummary {
+    /// Run number.
+    pub run_number: u32,
+    /// Epoch of each calibration. Calibrations without an epoch for this run
+    /// (e.g. before the first epoch or simulation) use their default values
+    /// and are omitted.
+    pub epochs: Vec<(CalibrationKind, Epoch)>,
+    /// Calibrations derived from a run more than `margin` runs away from
+    /// `run_number`.
+    pub warnings: Vec<StalenessWarning>,
+}
+
+impl CalibrationSummary {
+    /// Create the summary of the calibration epochs used for a run. See
+    /// [`staleness`] for the meaning of `margin`.
+    pub fn new(run_number: u32, margin: u32) -> Self {
+        let epochs: Vec<_> = [
+            (
+                CalibrationKind::WireGain,
+                wires::gain::GAINS.epoch(run_number),
+            ),
+            (
+                CalibrationKind::WireAlignment,
+                wires::alignment::OFFSETS.epoch(run_number),
+            ),
+            (
+                CalibrationKind::PadColumnOffsets,
+                pads::column_offsets::OFFSETS.epoch(run_number),
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(kind, epoch)| Some((kind, epoch?)))
+        .collect();
+        let warnings = epochs
+            .iter()
+            .map(|&(kind, epoch)| StalenessWarning {
+                kind,
+                epoch,
+                distance: epoch.distance(run_number),
+            })
+            .filter(|warning| warning.distance > margin)
+            .collect();
+
+        Self {
+            run_number,
+            epochs,
+            warnings,
+        }
+    }
+}
+
+/// Return a warning for each calibration used for a run that was derived from
+/// a run more than `margin` runs away (in either direction).
+///
+/// The epoch used for a run is always the nearest one that starts at or before
+/// it. When a run is close to an epoch boundary (e.g. the calibration was only
+/// taken days after the run), this might be the wrong calibration.
+pub fn staleness(run_number: u32, margin: u32) -> Vec<StalenessWarning> {
+    CalibrationSummary::new(run_number, margin).warnings
+}
+
+/// Return the number of samples at the start of every anode wire waveform
+/// that are recorded before the signal window (see
+/// [`wire_signal_window`](crate::detector_response::wire_signal_window)).
+///
+/// These samples are dropped when creating a [`MainEvent`](crate::MainEvent).
+/// No avalanche signal is expected in them, so they are a good estimate of the
+/// noise of the channel in each event.
+pub fn wire_delay(run_number: u32) -> Result<usize, MapWireDelayError> {
+    wires::delay::try_wire_delay(run_number)
+}
+
+/// Same as [`wire_delay`], but for the cathode pad waveforms (see
+/// [`pad_signal_window`](crate::detector_response::pad_signal_window)).
+pub fn pad_delay(run_number: u32) -> Result<usize, MapPadDelayError> {
+    pads::delay::try_pad_delay(run_number)
+}
+
+#[cfg(test)]
+mod tests;
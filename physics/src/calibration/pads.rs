@@ -4,3 +4,5 @@ pub(crate) mod baseline;
 pub(crate) mod gain;
 // ADC delay calibration
 pub(crate) mod delay;
+// Timing offsets of each pad column
+pub(crate) mod column_offsets;
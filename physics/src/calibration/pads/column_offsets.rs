@@ -0,0 +1,41 @@
+use crate::calibration::EpochValues;
+use alpha_g_detector::padwing::map::TPC_PAD_COLUMNS;
+use lazy_static::lazy_static;
+
+// Calibration epochs. This module is generated; new calibrations are added
+// with `cargo xtask ingest-pad-column-offsets` (see `xtask/README.md`).
+mod epochs;
+
+#[derive(serde::Deserialize)]
+struct ColumnOffsets {
+    // Samples. One per pad column.
+    sample_offsets: Vec<i32>,
+}
+
+lazy_static! {
+    pub(crate) static ref OFFSETS: EpochValues<[i32; TPC_PAD_COLUMNS]> =
+        EpochValues::new(epochs::EPOCHS, offsets_from_bytes);
+}
+/// Get the timing offset (in samples) of each pad column relative to the
+/// wires. A pad signal at sample `t` in a given column corresponds to the wire
+/// signals at sample `t + offset`.
+///
+/// Same as the wire alignment, a missing calibration is not an error: it just
+/// means that all pad columns are in time with the wires (i.e. offsets of 0).
+pub(crate) fn pad_column_offsets(run_number: u32) -> [i32; TPC_PAD_COLUMNS] {
+    // There is no epoch for a simulation run. There is no clock distribution
+    // skew in simulation.
+    OFFSETS
+        .get(run_number)
+        .copied()
+        .unwrap_or([0; TPC_PAD_COLUMNS])
+}
+
+fn offsets_from_bytes(bytes: &[u8]) -> [i32; TPC_PAD_COLUMNS] {
+    // Correctness of the format is checked by unit tests.
+    let offsets: ColumnOffsets = serde_json::from_slice(bytes).unwrap();
+    offsets.sample_offsets.try_into().unwrap()
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,7 @@
+// @generated by `cargo xtask ingest-pad-column-offsets` from
+// `data/calibration/pads/column_offsets/epochs.json`. Do not edit by hand.
+
+// First run number for which each calibration is valid (sorted in increasing
+// order), the run number from which it was derived, and the contents of the
+// calibration file.
+pub(super) const EPOCHS: &[(u32, u32, &[u8])] = &[];
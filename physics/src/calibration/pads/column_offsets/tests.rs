@@ -0,0 +1,21 @@
+use super::*;
+
+#[test]
+fn pad_column_offsets_simulation() {
+    assert_eq!(pad_column_offsets(u32::MAX), [0; TPC_PAD_COLUMNS]);
+}
+
+#[test]
+fn pad_column_offsets_before_first_epoch() {
+    let first_run = epochs::EPOCHS.first().map_or(u32::MAX, |&(run, _, _)| run);
+    for run_number in 0..first_run.min(20000) {
+        assert_eq!(pad_column_offsets(run_number), [0; TPC_PAD_COLUMNS]);
+    }
+}
+
+#[test]
+fn epochs_correctness() {
+    for (&(first_run, _, _), &offsets) in OFFSETS.epochs.iter().zip(&OFFSETS.values) {
+        assert_eq!(pad_column_offsets(first_run), offsets);
+    }
+}
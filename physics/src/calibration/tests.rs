@@ -0,0 +1,161 @@
+use super::*;
+
+const EPOCHS: &[(u32, u32, &[u8])] = &[(100, 100, b""), (200, 250, b""), (300, 290, b"")];
+
+#[test]
+fn epoch_find() {
+    assert_eq!(Epoch::find(EPOCHS, 99), None);
+    assert_eq!(
+        Epoch::find(EPOCHS, 100),
+        Some(Epoch {
+            valid_from_run: 100,
+            source_run: 100,
+        })
+    );
+    assert_eq!(
+        Epoch::find(EPOCHS, 299),
+        Some(Epoch {
+            valid_from_run: 200,
+            source_run: 250,
+        })
+    );
+    assert_eq!(
+        Epoch::find(EPOCHS, 1000),
+        Some(Epoch {
+            valid_from_run: 300,
+            source_run: 290,
+        })
+    );
+    assert_eq!(Epoch::find(EPOCHS, u32::MAX), None);
+    assert_eq!(Epoch::find(&[], 100), None);
+}
+
+#[test]
+fn epoch_values_same_as_epoch() {
+    let values = EpochValues::new(EPOCHS, |_| ());
+    for run_number in [0, 99, 100, 199, 200, 299, 300, 1000, u32::MAX] {
+        assert_eq!(
+            values.get(run_number).is_some(),
+            values.epoch(run_number).is_some()
+        );
+    }
+
+    let values = EpochValues {
+        epochs: EPOCHS,
+        values: vec!["a", "b", "c"],
+    };
+    assert_eq!(values.get(99), None);
+    assert_eq!(values.get(199), Some(&"a"));
+    assert_eq!(values.get(200), Some(&"b"));
+    assert_eq!(values.get(1000), Some(&"c"));
+    assert_eq!(values.get(u32::MAX), None);
+}
+
+// Generated epochs of every calibration, with their data directory.
+fn all_epochs() -> [(&'static [(u32, u32, &'static [u8])], &'static str); 3] {
+    [
+        (wires::gain::GAINS.epochs, "wires/gain"),
+        (wires::alignment::OFFSETS.epochs, "wires/alignment"),
+        (pads::column_offsets::OFFSETS.epochs, "pads/column_offsets"),
+    ]
+}
+
+#[test]
+fn epochs_sorted() {
+    for (epochs, dir) in all_epochs() {
+        assert!(epochs.windows(2).all(|w| w[0].0 < w[1].0), "{dir}");
+    }
+}
+
+#[test]
+fn epochs_match_index() {
+    #[derive(serde::Deserialize)]
+    struct Entry {
+        valid_from_run: u32,
+        source_run: u32,
+        file: String,
+    }
+    for (epochs, dir) in all_epochs() {
+        let data_dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("data/calibration")
+            .join(dir);
+        let index: Vec<Entry> =
+            serde_json::from_slice(&std::fs::read(data_dir.join("epochs.json")).unwrap()).unwrap();
+
+        assert_eq!(index.len(), epochs.len(), "{dir}");
+        for (entry, &(first_run, source_run, bytes)) in index.iter().zip(epochs) {
+            assert_eq!(entry.valid_from_run, first_run);
+            assert_eq!(entry.source_run, source_run);
+            assert_eq!(std::fs::read(data_dir.join(&entry.file)).unwrap(), bytes);
+        }
+    }
+}
+
+#[test]
+fn epoch_distance() {
+    let epoch = Epoch {
+        valid_from_run: 200,
+        source_run: 250,
+    };
+    assert_eq!(epoch.distance(250), 0);
+    assert_eq!(epoch.distance(200), 50);
+    assert_eq!(epoch.distance(260), 10);
+}
+
+#[test]
+fn staleness_adjacent_to_source_run() {
+    // The wire gain calibration for 11084.. was derived from 11186.
+    for run_number in [11185, 11186, 11187] {
+        assert!(staleness(run_number, DEFAULT_STALENESS_MARGIN).is_empty());
+    }
+}
+
+#[test]
+fn staleness_far_from_source_run() {
+    let warnings = staleness(11084, 50);
+    assert_eq!(
+        warnings,
+        [StalenessWarning {
+            kind: CalibrationKind::WireGain,
+            epoch: Epoch {
+                valid_from_run: 11084,
+                source_run: 11186,
+            },
+            distance: 102,
+        }]
+    );
+    assert_eq!(
+        warnings[0].to_string(),
+        "wire gain calibration derived from run `11186` is `102` runs away"
+    );
+    // Just within the margin.
+    assert!(staleness(11084, 102).is_empty());
+}
+
+#[test]
+fn calibration_summary_simulation() {
+    let summary = CalibrationSummary::new(u32::MAX, 0);
+    assert!(summary.epochs.is_empty());
+    assert!(summary.warnings.is_empty());
+}
+
+#[test]
+fn calibration_summary_epochs() {
+    let summary = CalibrationSummary::new(11186, 0);
+    assert!(summary.epochs.contains(&(
+        CalibrationKind::WireGain,
+        Epoch {
+            valid_from_run: 11084,
+            source_run: 11186,
+        }
+    )));
+    assert!(summary.warnings.is_empty());
+}
+
+#[test]
+fn public_delays() {
+    assert_eq!(wire_delay(u32::MAX).unwrap(), 100);
+    assert_eq!(pad_delay(u32::MAX).unwrap(), 100);
+    assert!(wire_delay(0).is_err());
+    assert!(pad_delay(0).is_err());
+}
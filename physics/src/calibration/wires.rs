@@ -4,3 +4,5 @@ pub(crate) mod baseline;
 pub(crate) mod gain;
 // ADC delay calibration
 pub(crate) mod delay;
+// Azimuthal alignment of the anode wire plane
+pub(crate) mod alignment;
@@ -0,0 +1,37 @@
+use crate::calibration::EpochValues;
+use lazy_static::lazy_static;
+
+// Calibration epochs. This module is generated; new calibrations are added
+// with `cargo xtask ingest-wire-alignment` (see `xtask/README.md`).
+mod epochs;
+
+#[derive(serde::Deserialize)]
+struct Alignment {
+    // Radians.
+    phi_offset: f64,
+}
+
+lazy_static! {
+    pub(crate) static ref OFFSETS: EpochValues<f64> =
+        EpochValues::new(epochs::EPOCHS, offset_from_bytes);
+}
+/// Get the azimuthal rotation (in radians) of the anode wire plane relative to
+/// the pad plane. This offset has to be added to the nominal `phi` of every
+/// wire.
+///
+/// Unlike other calibrations, a missing alignment is not an error: it just
+/// means that the nominal geometry is used (i.e. an offset of 0).
+pub(crate) fn wire_phi_offset(run_number: u32) -> f64 {
+    // There is no epoch for a simulation run. Simulated wires are always at
+    // their nominal position.
+    OFFSETS.get(run_number).copied().unwrap_or(0.0)
+}
+
+fn offset_from_bytes(bytes: &[u8]) -> f64 {
+    // Correctness of the format is checked by unit tests.
+    let alignment: Alignment = serde_json::from_slice(bytes).unwrap();
+    alignment.phi_offset
+}
+
+#[cfg(test)]
+mod tests;
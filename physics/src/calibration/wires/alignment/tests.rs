@@ -0,0 +1,22 @@
+use super::*;
+
+#[test]
+fn wire_phi_offset_simulation() {
+    assert_eq!(wire_phi_offset(u32::MAX), 0.0);
+}
+
+#[test]
+fn wire_phi_offset_before_first_epoch() {
+    let first_run = epochs::EPOCHS.first().map_or(u32::MAX, |&(run, _, _)| run);
+    for run_number in 0..first_run.min(20000) {
+        assert_eq!(wire_phi_offset(run_number), 0.0);
+    }
+}
+
+#[test]
+fn epochs_correctness() {
+    for (&(first_run, _, _), &offset) in OFFSETS.epochs.iter().zip(&OFFSETS.values) {
+        assert!(offset.is_finite());
+        assert_eq!(wire_phi_offset(first_run), offset);
+    }
+}
@@ -1,26 +1,23 @@
+use crate::calibration::EpochValues;
 use alpha_g_detector::alpha16::aw_map::TpcWirePosition;
 use lazy_static::lazy_static;
 use std::collections::HashMap;
 use thiserror::Error;
 
+// Calibration epochs. This module is generated; new calibrations are added
+// with `cargo xtask ingest-wire-gain` (see `xtask/README.md`).
+mod epochs;
+
 includes! {
     DATA_PATH = "../../../data/calibration/wires/gain/";
-    // All the following files are embedded at compile time.
-    // Add new files to the list below to include them.
     BYTES_SIMULATION = "simulation_complete.json",
-    BYTES_9277 = "9277_complete.json",
-    BYTES_11186 = "11186_complete.json",
 }
 
 lazy_static! {
-    // Whenever a new file is added, generate the appropriate new HashMap.
-    // Do not delete any of the existing maps.
-    //
-    // Adding a new map is as simple as:
-    // complete_from_bytes(BYTES_NUMBER)
-    static ref MAP_SIMULATION: HashMap<TpcWirePosition, f64> = complete_from_bytes(BYTES_SIMULATION);
-    static ref MAP_9277: HashMap<TpcWirePosition, f64> = complete_from_bytes(BYTES_9277);
-    static ref MAP_11186: HashMap<TpcWirePosition, f64> = complete_from_bytes(BYTES_11186);
+    static ref MAP_SIMULATION: HashMap<TpcWirePosition, f64> =
+        complete_from_bytes(BYTES_SIMULATION);
+    pub(crate) static ref GAINS: EpochValues<HashMap<TpcWirePosition, f64>> =
+        EpochValues::new(epochs::EPOCHS, complete_from_bytes);
 }
 /// Try to get the gain for a given wire. Return an error if there is no map
 /// available for the given run number or if there is no gain for a given
@@ -29,15 +26,13 @@ pub(crate) fn try_wire_gain(
     run_number: u32,
     wire: TpcWirePosition,
 ) -> Result<f64, MapWireGainError> {
-    // This map should be updated whenever a new file is added.
-    let map = match run_number {
+    let map = if run_number == u32::MAX {
         // u32::MAX corresponds to a simulation run.
-        u32::MAX => &*MAP_SIMULATION,
-        // This calibration was done at 11186. But the detector was in this
-        // condition since 11084 when it was turned on.
-        11084.. => &*MAP_11186,
-        9277.. => &*MAP_9277,
-        _ => return Err(MapWireGainError::MissingMap { run_number }),
+        &*MAP_SIMULATION
+    } else {
+        GAINS
+            .get(run_number)
+            .ok_or(MapWireGainError::MissingMap { run_number })?
     };
 
     map.get(&wire)
@@ -45,8 +40,6 @@ pub(crate) fn try_wire_gain(
         .ok_or(MapWireGainError::MissingWire { run_number, wire })
 }
 
-// Nothing below this line needs to be changed when new files are added.
-
 /// The error type returned when the gain calibration map is not available.
 #[derive(Debug, Error)]
 pub enum MapWireGainError {
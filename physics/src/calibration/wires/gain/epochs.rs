@@ -0,0 +1,20 @@
+// @generated by `cargo xtask ingest-wire-gain` from
+// `data/calibration/wires/gain/epochs.json`. Do not edit by hand.
+
+// First run number for which each calibration is valid (sorted in increasing
+// order), the run number from which it was derived, and the contents of the
+// calibration file.
+pub(super) const EPOCHS: &[(u32, u32, &[u8])] = &[
+    (
+        9277,
+        9277,
+        include_bytes!("../../../../data/calibration/wires/gain/9277_complete.json"),
+    ),
+    // This calibration was done at 11186. But the detector was in this
+    // condition since 11084 when it was turned on.
+    (
+        11084,
+        11186,
+        include_bytes!("../../../../data/calibration/wires/gain/11186_complete.json"),
+    ),
+];
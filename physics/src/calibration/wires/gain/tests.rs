@@ -69,3 +69,12 @@ fn try_wire_gain_correctness_sim() {
         assert_eq!(gain, 1.0);
     }
 }
+
+#[test]
+fn epochs_correctness() {
+    for map in &GAINS.values {
+        for gain in map.values() {
+            assert!(gain.is_finite() && *gain > 0.0);
+        }
+    }
+}
@@ -1,5 +1,8 @@
 use alpha_g_detector::chronobox;
+use thiserror::Error;
 use uom::si::f64::*;
+use uom::si::ratio::ratio;
+use uom::si::time::second;
 
 /// Frequency of the timestamp counter clock.
 pub const TIMESTAMP_CLOCK_FREQ: Frequency = Frequency {
@@ -8,6 +11,191 @@ pub const TIMESTAMP_CLOCK_FREQ: Frequency = Frequency {
     value: chronobox::TIMESTAMP_CLOCK_FREQ,
 };
 
+/// Time it takes the timestamp counter to wrap around (i.e. the duration of a
+/// single epoch).
+pub const WRAP_PERIOD: Time = Time {
+    dimension: uom::lib::marker::PhantomData,
+    units: uom::lib::marker::PhantomData,
+    value: (1u64 << chronobox::TIMESTAMP_BITS) as f64 / chronobox::TIMESTAMP_CLOCK_FREQ,
+};
+
+/// Convert a number of timestamp clock counts (already unwrapped, i.e.
+/// including all previous epochs) to time.
+pub fn counts_to_time(counts: u64) -> Time {
+    counts as f64 / TIMESTAMP_CLOCK_FREQ
+}
+
+/// Split a time into the epoch (number of timestamp counter wrap arounds) and
+/// the timestamp counter offset within that epoch. This is the inverse of
+/// [`counts_to_time`] with `counts = epoch * 2^TIMESTAMP_BITS + offset`.
+///
+/// The time is rounded to the nearest clock count. Negative times saturate to
+/// the start of epoch 0, and times after the end of epoch [`u32::MAX`]
+/// saturate to its last count.
+pub fn epoch_and_offset(time: Time) -> (u32, u32) {
+    let max_counts = ((u64::from(u32::MAX) + 1) << chronobox::TIMESTAMP_BITS) - 1;
+    let counts = (time * TIMESTAMP_CLOCK_FREQ).get::<ratio>().round() as u64;
+    let counts = counts.min(max_counts);
+    let epoch = counts >> chronobox::TIMESTAMP_BITS;
+    let offset = counts & ((1u64 << chronobox::TIMESTAMP_BITS) - 1);
+
+    (epoch.try_into().unwrap(), offset.try_into().unwrap())
+}
+
+// Number of leading edges of each board that are tried as the anchor of
+// `match_edges`.
+const MAX_ANCHOR_CANDIDATES: usize = 4;
+
+/// The error type returned when matching edges between Chronoboxes fails.
+#[derive(Debug, Error)]
+pub enum MatchEdgesError {
+    /// More than one anchor gives the largest number of matched edges.
+    #[error("ambiguous anchor (`{matches}` matched edges for more than one anchor)")]
+    AmbiguousAnchor { matches: usize },
+}
+
+/// Match the edges of the same physical signal recorded by two Chronoboxes
+/// (e.g. a signal fanned out to multiple boards). Edges are matched in sequence
+/// order; both inputs have to be sorted.
+///
+/// Each of the first few edges of one board is tried as the same edge as the
+/// first edge of the other board (the anchor). Starting from the anchor,
+/// consecutive edges are matched if the time since the previous match is the
+/// same on both boards within `tolerance`. An edge that is missing in one of
+/// the boards (e.g. an edge without a valid timestamp) is skipped. The anchor
+/// with the most matches is kept.
+///
+/// Returns an error if more than one anchor gives the largest number of
+/// matches (e.g. a perfectly periodic signal with a missing first edge).
+pub fn match_edges(
+    a: &[Time],
+    b: &[Time],
+    tolerance: Time,
+) -> Result<Vec<(Time, Time)>, MatchEdgesError> {
+    if a.is_empty() || b.is_empty() {
+        return Ok(Vec::new());
+    }
+    let anchors = (0..a.len().min(MAX_ANCHOR_CANDIDATES))
+        .map(|i| (i, 0))
+        .chain((1..b.len().min(MAX_ANCHOR_CANDIDATES)).map(|j| (0, j)));
+
+    let mut best: Vec<(Time, Time)> = Vec::new();
+    let mut ambiguous = false;
+    for (i, j) in anchors {
+        let matches = match_edges_from(&a[i..], &b[j..], tolerance);
+        if matches.len() > best.len() {
+            best = matches;
+            ambiguous = false;
+        } else if matches.len() == best.len() {
+            ambiguous = true;
+        }
+    }
+
+    if ambiguous {
+        Err(MatchEdgesError::AmbiguousAnchor {
+            matches: best.len(),
+        })
+    } else {
+        Ok(best)
+    }
+}
+
+// Match edges assuming that the first edges of both (non-empty) boards are the
+// same edge.
+fn match_edges_from(a: &[Time], b: &[Time], tolerance: Time) -> Vec<(Time, Time)> {
+    let mut matches = vec![(a[0], b[0])];
+    let (mut i, mut j) = (1, 1);
+    while i < a.len() && j < b.len() {
+        let (previous_a, previous_b) = *matches.last().unwrap();
+        let (interval_a, interval_b) = (a[i] - previous_a, b[j] - previous_b);
+        if (interval_a - interval_b).abs() <= tolerance {
+            matches.push((a[i], b[j]));
+            i += 1;
+            j += 1;
+        } else if interval_a < interval_b {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+
+    matches
+}
+
+/// The error type returned when fitting a [`ClockRelation`] fails.
+#[derive(Debug, Error)]
+pub enum FitClockRelationError {
+    /// Not enough matched edges to fit a line.
+    #[error("not enough matched edges (expected at least 2, found `{found}`)")]
+    TooFewMatches { found: usize },
+    /// All matched edges happened at the same time.
+    #[error("all matched edges at the same time")]
+    DegenerateMatches,
+}
+
+/// Linear relation between the clocks of two Chronoboxes. The time `t_b` in
+/// the second board of an edge recorded at time `t_a` in the first board is
+/// `t_b = offset + (1 + drift) * t_a`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ClockRelation {
+    /// Time offset between the boards.
+    pub offset: Time,
+    /// Relative clock drift of the second board with respect to the first.
+    pub drift: f64,
+    /// Root mean square of the residuals of the fit.
+    pub residual_rms: Time,
+}
+
+impl ClockRelation {
+    /// Return the relative clock drift in parts per million.
+    pub fn drift_ppm(&self) -> f64 {
+        self.drift * 1e6
+    }
+}
+
+/// Fit (least squares) the [`ClockRelation`] between two Chronoboxes given
+/// their matched edges (see [`match_edges`]).
+pub fn fit_clock_relation(
+    matches: &[(Time, Time)],
+) -> Result<ClockRelation, FitClockRelationError> {
+    if matches.len() < 2 {
+        return Err(FitClockRelationError::TooFewMatches {
+            found: matches.len(),
+        });
+    }
+    let (x, y): (Vec<f64>, Vec<f64>) = matches
+        .iter()
+        .map(|(a, b)| (a.get::<second>(), b.get::<second>()))
+        .unzip();
+    let n = matches.len() as f64;
+    let mean_x = x.iter().sum::<f64>() / n;
+    let mean_y = y.iter().sum::<f64>() / n;
+    let sxx: f64 = x.iter().map(|x| (x - mean_x).powi(2)).sum();
+    if sxx == 0.0 {
+        return Err(FitClockRelationError::DegenerateMatches);
+    }
+    let sxy: f64 = x
+        .iter()
+        .zip(&y)
+        .map(|(x, y)| (x - mean_x) * (y - mean_y))
+        .sum();
+    let slope = sxy / sxx;
+    let intercept = mean_y - slope * mean_x;
+    let residual_rms = (x
+        .iter()
+        .zip(&y)
+        .map(|(x, y)| (y - intercept - slope * x).powi(2))
+        .sum::<f64>()
+        / n)
+        .sqrt();
+
+    Ok(ClockRelation {
+        offset: Time::new::<second>(intercept),
+        drift: slope - 1.0,
+        residual_rms: Time::new::<second>(residual_rms),
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -18,4 +206,159 @@ mod tests {
         let f = Frequency::new::<megahertz>(10.0);
         assert_eq!(TIMESTAMP_CLOCK_FREQ, f);
     }
+
+    #[test]
+    fn chronobox_wrap_period() {
+        assert_eq!(WRAP_PERIOD.get::<second>(), 1.6777216);
+        assert_eq!(WRAP_PERIOD, counts_to_time(1 << chronobox::TIMESTAMP_BITS));
+    }
+
+    #[test]
+    fn chronobox_counts_to_time() {
+        assert_eq!(counts_to_time(0).get::<second>(), 0.0);
+        assert_eq!(counts_to_time(10_000_000).get::<second>(), 1.0);
+    }
+
+    #[test]
+    fn chronobox_epoch_and_offset() {
+        assert_eq!(epoch_and_offset(Time::new::<second>(0.0)), (0, 0));
+        assert_eq!(epoch_and_offset(Time::new::<second>(-1.0)), (0, 0));
+        assert_eq!(epoch_and_offset(WRAP_PERIOD), (1, 0));
+        assert_eq!(epoch_and_offset(WRAP_PERIOD * 3.0), (3, 0));
+        assert_eq!(epoch_and_offset(Time::new::<second>(1.0)), (0, 10_000_000));
+    }
+
+    #[test]
+    fn chronobox_epoch_and_offset_saturates() {
+        let last = (u32::MAX, 0xFFFFFF);
+        assert_eq!(epoch_and_offset(WRAP_PERIOD * 2f64.powi(33)), last);
+        assert_eq!(epoch_and_offset(WRAP_PERIOD * 1e12), last);
+        assert_eq!(epoch_and_offset(Time::new::<second>(f64::INFINITY)), last);
+    }
+
+    #[test]
+    fn chronobox_round_trip_at_wrap_boundary() {
+        let wrap = 1u64 << chronobox::TIMESTAMP_BITS;
+        for epoch in [0, 1, 2, 1000] {
+            for counts in [
+                epoch * wrap,
+                epoch * wrap + 1,
+                (epoch + 1) * wrap - 1,
+                (epoch + 1) * wrap,
+            ] {
+                let (e, o) = epoch_and_offset(counts_to_time(counts));
+                assert_eq!(u64::from(e) * wrap + u64::from(o), counts);
+            }
+        }
+        assert_eq!(epoch_and_offset(counts_to_time(wrap - 1)), (0, 0xFFFFFF));
+        assert_eq!(epoch_and_offset(counts_to_time(wrap)), (1, 0));
+    }
+
+    // Edges every 0.1 seconds as seen by a board with a given offset and
+    // drift.
+    fn edge_train(n: usize, offset: f64, drift: f64) -> Vec<Time> {
+        (0..n)
+            .map(|i| Time::new::<second>(offset + (1.0 + drift) * (0.5 + i as f64 * 0.1)))
+            .collect()
+    }
+
+    #[test]
+    fn chronobox_match_edges_identical() {
+        let a = edge_train(100, 0.0, 0.0);
+        let matches = match_edges(&a, &a, Time::new::<second>(1e-6)).unwrap();
+        assert_eq!(matches.len(), 100);
+        assert!(matches.iter().all(|(a, b)| a == b));
+    }
+
+    #[test]
+    fn chronobox_match_edges_missing() {
+        let a = edge_train(100, 0.0, 0.0);
+        let mut b = edge_train(100, 2.0, 50e-6);
+        b.remove(10);
+        b.remove(50);
+        let mut a_short = a.clone();
+        a_short.remove(70);
+
+        let matches = match_edges(&a_short, &b, Time::new::<second>(1e-4)).unwrap();
+        assert_eq!(matches.len(), 97);
+        for (t_a, t_b) in matches {
+            let expected = Time::new::<second>(2.0) + t_a * (1.0 + 50e-6);
+            assert!((t_b - expected).abs() < Time::new::<second>(1e-9));
+        }
+    }
+
+    // Edges at irregular intervals (between 0.1 and 0.2 seconds) as seen by a
+    // board with a given offset.
+    fn irregular_edges(n: usize, offset: f64) -> Vec<Time> {
+        (0..n)
+            .scan(0.5, |t, i| {
+                *t += 0.1 + 0.1 * ((i * 7919) % 97) as f64 / 97.0;
+                Some(Time::new::<second>(offset + *t))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn chronobox_match_edges_missing_first() {
+        let a = irregular_edges(100, 0.0);
+        let b = irregular_edges(100, 2.0);
+        let tolerance = Time::new::<second>(1e-6);
+
+        for (a, b) in [(&a[1..], &b[..]), (&a[..], &b[1..]), (&a[..], &b[2..])] {
+            let matches = match_edges(a, b, tolerance).unwrap();
+            assert_eq!(matches.len(), a.len().min(b.len()));
+            for (t_a, t_b) in matches {
+                assert!((t_b - t_a - Time::new::<second>(2.0)).abs() < tolerance);
+            }
+        }
+    }
+
+    #[test]
+    fn chronobox_match_edges_ambiguous_anchor() {
+        // A periodic signal with a missing first edge matches equally well with
+        // either anchor.
+        let a = edge_train(100, 0.0, 0.0);
+        let b = edge_train(100, 2.0, 0.0);
+        assert!(matches!(
+            match_edges(&a, &b[1..], Time::new::<second>(1e-6)),
+            Err(MatchEdgesError::AmbiguousAnchor { matches: 99 })
+        ));
+    }
+
+    #[test]
+    fn chronobox_match_edges_empty() {
+        let a = edge_train(10, 0.0, 0.0);
+        assert!(match_edges(&a, &[], Time::new::<second>(1e-6))
+            .unwrap()
+            .is_empty());
+        assert!(match_edges(&[], &a, Time::new::<second>(1e-6))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn chronobox_fit_clock_relation_injected_drift() {
+        let a = edge_train(1000, 0.0, 0.0);
+        let b = edge_train(1000, -0.25, 20e-6);
+        let matches = match_edges(&a, &b, Time::new::<second>(1e-4)).unwrap();
+        let relation = fit_clock_relation(&matches).unwrap();
+
+        assert!((relation.offset.get::<second>() + 0.25).abs() < 1e-9);
+        assert!((relation.drift_ppm() - 20.0).abs() < 1e-6);
+        assert!(relation.residual_rms.get::<second>() < 1e-9);
+    }
+
+    #[test]
+    fn chronobox_fit_clock_relation_too_few_matches() {
+        let a = edge_train(1, 0.0, 0.0);
+        assert!(matches!(
+            fit_clock_relation(&match_edges(&a, &a, Time::new::<second>(1e-6)).unwrap()),
+            Err(FitClockRelationError::TooFewMatches { found: 1 })
+        ));
+        let t = Time::new::<second>(1.0);
+        assert!(matches!(
+            fit_clock_relation(&[(t, t), (t, t)]),
+            Err(FitClockRelationError::DegenerateMatches)
+        ));
+    }
 }
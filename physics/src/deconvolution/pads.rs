@@ -1,6 +1,8 @@
 use crate::deconvolution::ls_deconvolution;
 use alpha_g_detector::padwing::PWB_RATE;
 use lazy_static::lazy_static;
+use uom::si::f64::Time;
+use uom::si::time::nanosecond;
 
 // Width in nanoseconds of each pad signal bin.
 const BIN_WIDTH: usize = (1.0e9 / PWB_RATE) as usize;
@@ -22,6 +24,19 @@ lazy_static! {
     };
 }
 
+// Time from an input to the peak of its response.
+pub(crate) fn pad_peaking_time() -> Time {
+    let peak = PAD_RESPONSE
+        .iter()
+        .map(|r| r.abs())
+        .enumerate()
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap()
+        .0;
+
+    Time::new::<nanosecond>((peak * BIN_WIDTH) as f64)
+}
+
 pub(crate) fn pad_deconvolution(signal: &[f64]) -> Vec<f64> {
     // A 2D histogram of `best_offset` and `best_look_ahead` is not as
     // concentrated for the pad signals as it is for the wires. But it is not
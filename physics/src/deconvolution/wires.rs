@@ -2,9 +2,11 @@
 // [Option<Vec<f64>>; TPC_ANODE_WIRES] where an empty channel is `None`.
 
 use crate::deconvolution::ls_deconvolution;
-use alpha_g_detector::alpha16::{aw_map::TPC_ANODE_WIRES, ADC32_RATE};
+use alpha_g_detector::alpha16::{aw_map::TPC_ANODE_WIRES, ADC32_RATE, ADC_MAX, ADC_MIN};
 use dyn_stack::ReborrowMut;
 use lazy_static::lazy_static;
+use uom::si::f64::Time;
+use uom::si::time::nanosecond;
 
 // Width in nanoseconds of each wire signal bin.
 const BIN_WIDTH: usize = (1.0e9 / ADC32_RATE) as usize;
@@ -22,6 +24,30 @@ lazy_static! {
             .map(|chunk| chunk.iter().sum())
             .collect()
     };
+    // Number of samples (starting at the input time bin) in which the response
+    // to an input is above half of its maximum. A clipped sample within this
+    // window of an input means that the input was most likely underestimated.
+    static ref SATURATION_LOOK_AHEAD: usize = {
+        let max = WIRE_RESPONSE.iter().map(|r| r.abs()).reduce(f64::max).unwrap();
+
+        WIRE_RESPONSE
+            .iter()
+            .rposition(|r| r.abs() >= 0.5 * max)
+            .unwrap()
+            + 1
+    };
+}
+// Time from an input to the peak of its response.
+pub(crate) fn wire_peaking_time() -> Time {
+    let peak = WIRE_RESPONSE
+        .iter()
+        .map(|r| r.abs())
+        .enumerate()
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap()
+        .0;
+
+    Time::new::<nanosecond>((peak * BIN_WIDTH) as f64)
 }
 // "Strength" of the signal induced on a neighboring wire.
 const NEIGHBOR_FACTORS: [f64; 5] = [1.0, -0.1275, -0.0365, -0.012, -0.0042];
@@ -133,6 +159,39 @@ pub(crate) fn wire_range_deconvolution(
     range_to_indices(range).zip(sol).collect()
 }
 
+// Return the indices of all samples in a raw wire waveform that are clipped at
+// the ADC rails.
+//
+// Comparing the raw samples against the rails is equivalent to comparing the
+// baseline subtracted signal against the rails shifted by the baseline. The
+// indices are then valid for the calibrated signal (as long as the same delay
+// is skipped in both).
+pub(crate) fn clipped_samples(waveform: &[i16]) -> Vec<usize> {
+    waveform
+        .iter()
+        .enumerate()
+        .filter(|(_, &v)| v >= ADC_MAX || v <= ADC_MIN)
+        .map(|(i, _)| i)
+        .collect()
+}
+
+// Given the clipped samples of a wire signal, return which of the `len`
+// deconvolved inputs are affected by saturation i.e. there is a clipped sample
+// in the main lobe of their response.
+//
+// The amplitude of these inputs is underestimated by an unknown amount.
+pub(crate) fn saturated_inputs(clipped: &[usize], len: usize) -> Vec<bool> {
+    let mut saturated = vec![false; len];
+    for &sample in clipped {
+        let first = (sample + 1).saturating_sub(*SATURATION_LOOK_AHEAD);
+        for flag in saturated.iter_mut().take(sample + 1).skip(first) {
+            *flag = true;
+        }
+    }
+
+    saturated
+}
+
 // Given a range [first, last), return an iterator over the indices.
 fn range_to_indices(range: (usize, usize)) -> Box<dyn Iterator<Item = usize>> {
     let (first, last) = range;
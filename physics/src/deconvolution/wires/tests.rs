@@ -83,3 +83,70 @@ fn trivial_multiple_wires_deconvolution() {
         }
     }
 }
+
+#[test]
+fn clipped_samples_at_rails() {
+    let waveform = [0, ADC_MAX, 100, ADC_MIN, ADC_MAX - 1, ADC_MIN + 1, ADC_MAX];
+
+    assert_eq!(clipped_samples(&waveform), [1, 3, 6]);
+    assert!(clipped_samples(&[0; 10]).is_empty());
+}
+
+#[test]
+fn saturated_inputs_look_ahead() {
+    let look_ahead = *SATURATION_LOOK_AHEAD;
+    assert!(look_ahead > 0);
+
+    let saturated = saturated_inputs(&[50], 100);
+    for (t, flag) in saturated.into_iter().enumerate() {
+        assert_eq!(flag, t <= 50 && t + look_ahead > 50, "t = {t}");
+    }
+    // Clipped samples close to the edges.
+    let saturated = saturated_inputs(&[0, 99], 100);
+    assert!(saturated[0]);
+    assert!(saturated[99]);
+    assert!(!saturated[50]);
+
+    assert!(saturated_inputs(&[], 10).iter().all(|&flag| !flag));
+    assert!(saturated_inputs(&[5], 0).is_empty());
+}
+
+// Synthetic pulses (raw ADC samples with a baseline of 0) clipped at different
+// fractions of their peak. The largest deconvolved input (i.e. the avalanche)
+// has to be flagged whenever there is clipping, and no input should be flagged
+// otherwise.
+#[test]
+fn saturated_single_wire_pulse() {
+    let peak = WIRE_RESPONSE
+        .iter()
+        .map(|r| r.abs())
+        .reduce(f64::max)
+        .unwrap();
+    // Fraction of the peak at which the pulse is clipped (larger than 1 means
+    // no clipping at all).
+    for fraction in [2.0, 1.1, 0.95, 0.8, 0.5, 0.2] {
+        let scale = -f64::from(ADC_MIN) / (fraction * peak);
+        let raw: Vec<i16> = vec![0.0; 10]
+            .into_iter()
+            .chain(WIRE_RESPONSE.iter().map(|r| r * scale))
+            .map(|v| v.round().clamp(f64::from(ADC_MIN), f64::from(ADC_MAX)) as i16)
+            .collect();
+        let clipped = clipped_samples(&raw);
+        assert_eq!(!clipped.is_empty(), fraction < 1.0);
+
+        let mut signals = [(); TPC_ANODE_WIRES].map(|_| None);
+        signals[0] = Some(raw.iter().map(|&v| f64::from(v)).collect::<Vec<_>>());
+        let (_, input) = wire_range_deconvolution(&signals, (0, 1)).pop().unwrap();
+        let saturated = saturated_inputs(&clipped, input.len());
+
+        let (t_max, _) = input
+            .iter()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .unwrap();
+        assert_eq!(saturated[t_max], fraction < 1.0, "fraction = {fraction}");
+        if fraction >= 1.0 {
+            assert!(saturated.iter().all(|&flag| !flag));
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use crate::deconvolution::pads::pad_peaking_time;
+use crate::deconvolution::wires::wire_peaking_time;
+use crate::drift::DRIFT_TABLES;
+use crate::reconstruction::FieldConfiguration;
+use uom::si::f64::Time;
+use uom::si::time::second;
+
+/// Return the maximum drift time (i.e. the drift time of an ionization at the
+/// inner cathode) for a given field configuration.
+///
+/// There are only drift tables for the nominal 1 T field, and the
+/// reconstruction uses them regardless of the field configuration. Hence, this
+/// is currently the same for both configurations.
+pub fn max_drift_time(field: FieldConfiguration) -> Time {
+    match field {
+        FieldConfiguration::On | FieldConfiguration::Off => DRIFT_TABLES.max_drift_time(),
+    }
+}
+
+/// Return the time window in which anode wire signals are expected.
+///
+/// Times are relative to the start of the (delay calibrated) wire signals,
+/// same as [`Avalanche::t`](crate::Avalanche::t). An avalanche can happen at
+/// any time from `0` up to the [`max_drift_time`], and its signal peaks one
+/// peaking time of the wire response later.
+pub fn wire_signal_window() -> (Time, Time) {
+    (
+        Time::new::<second>(0.0),
+        max_drift_time(FieldConfiguration::On) + wire_peaking_time(),
+    )
+}
+
+/// Return the time window in which cathode pad signals are expected.
+///
+/// Same as [`wire_signal_window`], but with the peaking time of the pad
+/// response.
+pub fn pad_signal_window() -> (Time, Time) {
+    (
+        Time::new::<second>(0.0),
+        max_drift_time(FieldConfiguration::On) + pad_peaking_time(),
+    )
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,33 @@
+use super::*;
+use crate::drift_time_range;
+use uom::si::f64::Length;
+use uom::si::length::meter;
+use uom::si::time::microsecond;
+
+#[test]
+fn max_drift_time_same_for_all_field_configurations() {
+    assert_eq!(
+        max_drift_time(FieldConfiguration::On),
+        max_drift_time(FieldConfiguration::Off)
+    );
+}
+
+#[test]
+fn max_drift_time_covers_all_tables() {
+    let max = max_drift_time(FieldConfiguration::On);
+    let range = drift_time_range(Length::new::<meter>(0.0)).unwrap();
+    assert!(*range.end() <= max);
+    assert!(max > Time::new::<microsecond>(1.0));
+    assert!(max < Time::new::<microsecond>(10.0));
+}
+
+#[test]
+fn signal_windows_after_max_drift_time() {
+    let max = max_drift_time(FieldConfiguration::On);
+    for (start, end) in [wire_signal_window(), pad_signal_window()] {
+        assert_eq!(start, Time::new::<second>(0.0));
+        assert!(end > max);
+        // Shaping is much faster than the drift.
+        assert!(end - max < max);
+    }
+}
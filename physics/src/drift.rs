@@ -1,17 +1,40 @@
 use lazy_static::lazy_static;
 use serde::Deserialize;
+use std::ops::RangeInclusive;
 use thiserror::Error;
 use uom::si::f64::{Angle, Length, Time};
 
 /// The error type returned when a drift time lookup fails.
 #[derive(Debug, Error)]
 pub enum TryDriftLookupError {
+    /// The drift time is before the start of the drift tables (i.e. negative).
     #[error("drift time `{0:?}` is out of range")]
     DriftTimeOutOfRange(Time),
+    /// The drift time is beyond the physical maximum drift time (plus a small
+    /// extrapolation margin) at the given axial position.
+    #[error("drift time `{t:?}` is beyond the maximum `{max:?}`")]
+    DriftTimeBeyondMaximum { t: Time, max: Time },
+    /// The axial position is outside the detector.
     #[error("axial position `{0:?}` is out of range")]
     AxialPositionOutOfRange(Length),
 }
 
+/// Default margin beyond the end of the drift tables within which drift times
+/// are linearly extrapolated when converting an
+/// [`Avalanche`](crate::Avalanche) to a [`SpacePoint`](crate::SpacePoint).
+///
+/// Drift times slightly beyond the last point of a drift table (i.e.
+/// ionization right at the inner cathode, plus the time resolution of the
+/// avalanches) are extrapolated from the last two points. Otherwise there is a
+/// sharp step in the radial distribution at the edge of the table. The tables
+/// have a time step of 8 ns, so this is a handful of points (the extrapolated
+/// radius is at most ~1 mm inside the inner cathode).
+pub const DEFAULT_EXTRAPOLATION_MARGIN: Time = Time {
+    dimension: uom::lib::marker::PhantomData,
+    units: uom::lib::marker::PhantomData,
+    value: 50e-9,
+};
+
 // Represents the radius and Lorentz correction as a function of drift time.
 // The drift time is in ascending order. Given that this structure only exists
 // in a static lookup table loaded at compile time, this is guaranteed by unit
@@ -20,18 +43,28 @@ pub enum TryDriftLookupError {
 pub(crate) struct DriftTable(Vec<(Time, Length, Angle)>);
 
 impl DriftTable {
-    fn at(&self, t: Time) -> Result<(Length, Angle), TryDriftLookupError> {
+    fn time_range(&self) -> RangeInclusive<Time> {
         // Unit tests guarantee that the inner vector has at least 2 elements.
-        if t < self.0[0].0 || t > self.0[self.0.len() - 1].0 {
+        self.0[0].0..=self.0[self.0.len() - 1].0
+    }
+
+    fn at(&self, t: Time, margin: Time) -> Result<(Length, Angle), TryDriftLookupError> {
+        let time_range = self.time_range();
+        if t < *time_range.start() {
             return Err(TryDriftLookupError::DriftTimeOutOfRange(t));
         }
+        let max = *time_range.end() + margin;
+        if t > max {
+            return Err(TryDriftLookupError::DriftTimeBeyondMaximum { t, max });
+        }
         // Identify the two points that bracket the given drift time. A linear
         // interpolation is good enough.
         let rhs_index = self
             .0
             .iter()
             .position(|&(time, _, _)| time > t)
-            // If t is the last element, then the last two elements bracket t.
+            // If t is the last element (or within the extrapolation margin),
+            // then use the last two elements.
             .unwrap_or(self.0.len() - 1);
         let lhs_index = rhs_index - 1;
         let (lhs_time, lhs_radius, lhs_correction) = self.0[lhs_index];
@@ -56,7 +89,7 @@ impl DriftTable {
 pub(crate) struct DriftTables(Vec<(DriftTable, Length)>);
 
 impl DriftTables {
-    pub(crate) fn at(&self, z: Length, t: Time) -> Result<(Length, Angle), TryDriftLookupError> {
+    fn table(&self, z: Length) -> Result<&DriftTable, TryDriftLookupError> {
         let z_abs = z.abs();
         if z_abs > self.0[self.0.len() - 1].1 {
             return Err(TryDriftLookupError::AxialPositionOutOfRange(z));
@@ -68,7 +101,50 @@ impl DriftTables {
             .find(|(_, z_upper_bound)| z_upper_bound >= &z_abs)
             .unwrap();
 
-        table.at(t)
+        Ok(table)
+    }
+
+    // Drift times up to `margin` beyond the last point of the table are
+    // linearly extrapolated.
+    pub(crate) fn at(
+        &self,
+        z: Length,
+        t: Time,
+        margin: Time,
+    ) -> Result<(Length, Angle), TryDriftLookupError> {
+        self.table(z)?.at(t, margin)
+    }
+    // Range of drift times covered by the table at a given axial position
+    // (without extrapolation).
+    pub(crate) fn time_range(
+        &self,
+        z: Length,
+    ) -> Result<RangeInclusive<Time>, TryDriftLookupError> {
+        Ok(self.table(z)?.time_range())
+    }
+    // Range of radii covered by all the tables (without extrapolation).
+    pub(crate) fn radius_range(&self) -> RangeInclusive<Length> {
+        let radii = self
+            .0
+            .iter()
+            .flat_map(|(table, _)| table.0.iter().map(|&(_, radius, _)| radius));
+        let min = radii
+            .clone()
+            .reduce(|a, b| if b < a { b } else { a })
+            .unwrap();
+        let max = radii.reduce(|a, b| if b > a { b } else { a }).unwrap();
+
+        min..=max
+    }
+    // Largest drift time in any of the drift tables. No lookup is possible
+    // past this time.
+    pub(crate) fn max_drift_time(&self) -> Time {
+        self.0
+            .iter()
+            // Unit tests guarantee that all tables are not empty and sorted.
+            .map(|(table, _)| table.0[table.0.len() - 1].0)
+            .reduce(|a, b| if b > a { b } else { a })
+            .unwrap()
     }
 }
 
@@ -106,8 +106,11 @@ fn valid_drift_time_lookup() {
 
         let mut t = Time::new::<microsecond>(0.0);
         while t <= Time::new::<microsecond>(3.5) {
-            let (radius_pos, correction_pos) = DRIFT_TABLES.at(z, t).unwrap();
-            let (radius_neg, correction_neg) = DRIFT_TABLES.at(z_neg, t).unwrap();
+            let (radius_pos, correction_pos) =
+                DRIFT_TABLES.at(z, t, DEFAULT_EXTRAPOLATION_MARGIN).unwrap();
+            let (radius_neg, correction_neg) = DRIFT_TABLES
+                .at(z_neg, t, DEFAULT_EXTRAPOLATION_MARGIN)
+                .unwrap();
 
             assert_eq!(radius_pos, radius_neg);
             assert_eq!(correction_pos, correction_neg);
@@ -117,3 +120,141 @@ fn valid_drift_time_lookup() {
         z += Length::new::<meter>(0.1);
     }
 }
+
+#[test]
+fn max_drift_time_drift_tables() {
+    let max_drift_time = DRIFT_TABLES.max_drift_time();
+    for (table, _) in DRIFT_TABLES.0.iter() {
+        for (time, _, _) in table.0.iter() {
+            assert!(*time <= max_drift_time);
+        }
+    }
+    assert!(DRIFT_TABLES
+        .0
+        .iter()
+        .any(|(table, _)| table.0[table.0.len() - 1].0 == max_drift_time));
+}
+
+#[test]
+fn drift_time_lookup_at_table_edge() {
+    let z = Length::new::<meter>(0.0);
+    let (table, _) = &DRIFT_TABLES.0[0];
+    let (last_time, last_radius, last_correction) = table.0[table.0.len() - 1];
+
+    let (radius, correction) = DRIFT_TABLES
+        .at(z, last_time, DEFAULT_EXTRAPOLATION_MARGIN)
+        .unwrap();
+    assert!((radius - last_radius).abs() < Length::new::<meter>(1e-12));
+    assert!((correction - last_correction).abs() < Angle::new::<radian>(1e-12));
+}
+
+#[test]
+fn drift_time_lookup_within_extrapolation_margin() {
+    let z = Length::new::<meter>(0.0);
+    let (table, _) = &DRIFT_TABLES.0[0];
+    let (lhs_time, lhs_radius, _) = table.0[table.0.len() - 2];
+    let (rhs_time, rhs_radius, rhs_correction) = table.0[table.0.len() - 1];
+
+    let t = rhs_time + DEFAULT_EXTRAPOLATION_MARGIN / 2.0;
+    let (radius, correction) = DRIFT_TABLES.at(z, t, DEFAULT_EXTRAPOLATION_MARGIN).unwrap();
+    let expected = rhs_radius + (t - rhs_time) / (rhs_time - lhs_time) * (rhs_radius - lhs_radius);
+    assert!((radius - expected).abs() < Length::new::<meter>(1e-9));
+    assert!(radius < rhs_radius);
+    assert!(correction > rhs_correction);
+
+    assert!(DRIFT_TABLES
+        .at(
+            z,
+            rhs_time + DEFAULT_EXTRAPOLATION_MARGIN,
+            DEFAULT_EXTRAPOLATION_MARGIN
+        )
+        .is_ok());
+}
+
+#[test]
+fn drift_time_lookup_beyond_extrapolation_margin() {
+    let z = Length::new::<meter>(0.0);
+    let (table, _) = &DRIFT_TABLES.0[0];
+    let last_time = table.0[table.0.len() - 1].0;
+
+    let t = last_time + DEFAULT_EXTRAPOLATION_MARGIN + Time::new::<microsecond>(0.001);
+    match DRIFT_TABLES.at(z, t, DEFAULT_EXTRAPOLATION_MARGIN) {
+        Err(TryDriftLookupError::DriftTimeBeyondMaximum { t: found, max }) => {
+            assert_eq!(found, t);
+            assert_eq!(max, last_time + DEFAULT_EXTRAPOLATION_MARGIN);
+        }
+        _ => unreachable!(),
+    }
+    assert!(matches!(
+        DRIFT_TABLES.at(
+            z,
+            Time::new::<microsecond>(1000.0),
+            DEFAULT_EXTRAPOLATION_MARGIN
+        ),
+        Err(TryDriftLookupError::DriftTimeBeyondMaximum { .. })
+    ));
+    // The margin is configurable.
+    assert!(matches!(
+        DRIFT_TABLES.at(
+            z,
+            last_time + DEFAULT_EXTRAPOLATION_MARGIN / 2.0,
+            Time::new::<microsecond>(0.0)
+        ),
+        Err(TryDriftLookupError::DriftTimeBeyondMaximum { .. })
+    ));
+    assert!(DRIFT_TABLES
+        .at(z, last_time, Time::new::<microsecond>(0.0))
+        .is_ok());
+}
+
+#[test]
+fn drift_lookup_out_of_range_errors() {
+    assert!(matches!(
+        DRIFT_TABLES.at(
+            Length::new::<meter>(0.0),
+            Time::new::<microsecond>(-0.001),
+            DEFAULT_EXTRAPOLATION_MARGIN
+        ),
+        Err(TryDriftLookupError::DriftTimeOutOfRange(_))
+    ));
+    let z = Length::new::<meter>(DETECTOR_LENGTH);
+    assert!(matches!(
+        DRIFT_TABLES.at(
+            z,
+            Time::new::<microsecond>(1.0),
+            DEFAULT_EXTRAPOLATION_MARGIN
+        ),
+        Err(TryDriftLookupError::AxialPositionOutOfRange(_))
+    ));
+    assert!(matches!(
+        DRIFT_TABLES.at(
+            -z,
+            Time::new::<microsecond>(1.0),
+            DEFAULT_EXTRAPOLATION_MARGIN
+        ),
+        Err(TryDriftLookupError::AxialPositionOutOfRange(_))
+    ));
+}
+
+#[test]
+fn drift_tables_time_range() {
+    for (table, upper_bound) in DRIFT_TABLES.0.iter() {
+        let range = DRIFT_TABLES.time_range(*upper_bound).unwrap();
+        assert_eq!(*range.start(), Time::new::<microsecond>(0.0));
+        assert_eq!(*range.end(), table.0[table.0.len() - 1].0);
+        assert!(*range.end() <= DRIFT_TABLES.max_drift_time());
+    }
+    assert!(DRIFT_TABLES
+        .time_range(Length::new::<meter>(DETECTOR_LENGTH))
+        .is_err());
+}
+
+#[test]
+fn drift_tables_radius_range() {
+    let range = DRIFT_TABLES.radius_range();
+
+    let wire_radius = Length::new::<meter>(ANODE_WIRES_RADIUS);
+    let inner_cathode_radius = Length::new::<meter>(INNER_CATHODE_RADIUS);
+    assert!((*range.end() - wire_radius).abs() < Length::new::<meter>(5e-4));
+    assert!((*range.start() - inner_cathode_radius).abs() < Length::new::<meter>(5e-4));
+}
@@ -0,0 +1,102 @@
+use crate::reconstruction::Coordinate;
+use alpha_g_detector::padwing::map::{CATHODE_PADS_RADIUS, DETECTOR_LENGTH};
+use uom::si::f64::Length;
+use uom::si::length::meter;
+
+/// Cylinder centered at the origin with its axis along the beamline (i.e. the
+/// `z` axis).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Cylinder {
+    /// Radius of the cylinder.
+    pub radius: Length,
+    /// Half of the axial length of the cylinder. The cylinder spans from
+    /// `z = -half_length` to `z = half_length`.
+    pub half_length: Length,
+}
+
+/// Fiducial volume of the rTPC, bounded by the outer (pads) cathode and the
+/// ends of the detector.
+pub const FIDUCIAL_CYLINDER: Cylinder = Cylinder {
+    radius: Length {
+        dimension: uom::lib::marker::PhantomData,
+        units: uom::lib::marker::PhantomData,
+        value: CATHODE_PADS_RADIUS,
+    },
+    half_length: Length {
+        dimension: uom::lib::marker::PhantomData,
+        units: uom::lib::marker::PhantomData,
+        value: DETECTOR_LENGTH / 2.0,
+    },
+};
+
+impl Cylinder {
+    /// Return the entry and exit points of the straight line that goes through
+    /// `a` and `b` (in that direction). The line is infinite i.e. `a` and `b`
+    /// do not need to be inside the cylinder.
+    ///
+    /// Returns [`None`] if the line misses the cylinder (or if `a` and `b` are
+    /// the same point). A line that grazes the cylinder returns the same point
+    /// as entry and exit.
+    pub fn intersections(&self, a: Coordinate, b: Coordinate) -> Option<(Coordinate, Coordinate)> {
+        let radius = self.radius.get::<meter>();
+        let half_length = self.half_length.get::<meter>();
+        let (ax, ay, az) = (a.x.get::<meter>(), a.y.get::<meter>(), a.z.get::<meter>());
+        let (dx, dy, dz) = (
+            b.x.get::<meter>() - ax,
+            b.y.get::<meter>() - ay,
+            b.z.get::<meter>() - az,
+        );
+        if dx == 0.0 && dy == 0.0 && dz == 0.0 {
+            return None;
+        }
+        // Points in the line are `a + s * (b - a)`. Find the range of `s` for
+        // which the line is inside the infinite cylinder, and the range for
+        // which it is in between the two end caps. The intersection of these
+        // two ranges is the segment inside the cylinder.
+        let (mut s_min, mut s_max) = (f64::NEG_INFINITY, f64::INFINITY);
+
+        let quad_a = dx * dx + dy * dy;
+        let quad_c = ax * ax + ay * ay - radius * radius;
+        if quad_a == 0.0 {
+            // Parallel to the axis.
+            if quad_c > 0.0 {
+                return None;
+            }
+        } else {
+            let quad_b = 2.0 * (ax * dx + ay * dy);
+            let discriminant = quad_b * quad_b - 4.0 * quad_a * quad_c;
+            if discriminant < 0.0 {
+                return None;
+            }
+            let sqrt_discriminant = discriminant.sqrt();
+            s_min = (-quad_b - sqrt_discriminant) / (2.0 * quad_a);
+            s_max = (-quad_b + sqrt_discriminant) / (2.0 * quad_a);
+        }
+
+        if dz == 0.0 {
+            // Perpendicular to the axis.
+            if az.abs() > half_length {
+                return None;
+            }
+        } else {
+            let s1 = (-half_length - az) / dz;
+            let s2 = (half_length - az) / dz;
+            s_min = s_min.max(s1.min(s2));
+            s_max = s_max.min(s1.max(s2));
+        }
+
+        if s_min > s_max {
+            return None;
+        }
+
+        let at = |s: f64| Coordinate {
+            x: Length::new::<meter>(ax + s * dx),
+            y: Length::new::<meter>(ay + s * dy),
+            z: Length::new::<meter>(az + s * dz),
+        };
+        Some((at(s_min), at(s_max)))
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,97 @@
+use super::*;
+
+fn coordinate(x: f64, y: f64, z: f64) -> Coordinate {
+    Coordinate {
+        x: Length::new::<meter>(x),
+        y: Length::new::<meter>(y),
+        z: Length::new::<meter>(z),
+    }
+}
+
+fn length(a: Coordinate, b: Coordinate) -> f64 {
+    let dx = (b.x - a.x).get::<meter>();
+    let dy = (b.y - a.y).get::<meter>();
+    let dz = (b.z - a.z).get::<meter>();
+    (dx * dx + dy * dy + dz * dz).sqrt()
+}
+
+fn unit_cylinder() -> Cylinder {
+    Cylinder {
+        radius: Length::new::<meter>(1.0),
+        half_length: Length::new::<meter>(2.0),
+    }
+}
+
+#[test]
+fn fiducial_cylinder() {
+    assert_eq!(FIDUCIAL_CYLINDER.radius.get::<meter>(), CATHODE_PADS_RADIUS);
+    assert_eq!(
+        FIDUCIAL_CYLINDER.half_length.get::<meter>(),
+        DETECTOR_LENGTH / 2.0
+    );
+}
+
+#[test]
+fn cylinder_intersections_miss() {
+    let cylinder = unit_cylinder();
+    // Parallel to the axis, outside the radius.
+    assert!(cylinder
+        .intersections(coordinate(1.5, 0.0, 0.0), coordinate(1.5, 0.0, 1.0))
+        .is_none());
+    // Perpendicular to the axis, beyond the end caps.
+    assert!(cylinder
+        .intersections(coordinate(0.0, 0.0, 2.5), coordinate(1.0, 0.0, 2.5))
+        .is_none());
+    // Skew line that passes outside the radius.
+    assert!(cylinder
+        .intersections(coordinate(-1.0, 1.5, 0.0), coordinate(1.0, 1.5, 1.0))
+        .is_none());
+    // Crosses the infinite cylinder only beyond the end caps.
+    assert!(cylinder
+        .intersections(coordinate(-1.0, 0.0, 3.0), coordinate(1.0, 0.0, 3.5))
+        .is_none());
+    // Degenerate line.
+    assert!(cylinder
+        .intersections(coordinate(0.0, 0.0, 0.0), coordinate(0.0, 0.0, 0.0))
+        .is_none());
+}
+
+#[test]
+fn cylinder_intersections_grazing() {
+    let cylinder = unit_cylinder();
+    let (entry, exit) = cylinder
+        .intersections(coordinate(1.0, -1.0, 0.5), coordinate(1.0, 1.0, 0.5))
+        .unwrap();
+    assert_eq!(length(entry, exit), 0.0);
+    assert_eq!(entry.x.get::<meter>(), 1.0);
+    assert_eq!(entry.y.get::<meter>(), 0.0);
+    assert_eq!(entry.z.get::<meter>(), 0.5);
+}
+
+#[test]
+fn cylinder_intersections_crossing() {
+    let cylinder = unit_cylinder();
+    // Perpendicular to the axis through the center.
+    let (entry, exit) = cylinder
+        .intersections(coordinate(0.0, 5.0, 0.0), coordinate(0.0, -5.0, 0.0))
+        .unwrap();
+    assert!((entry.y.get::<meter>() - 1.0).abs() < 1e-12);
+    assert!((exit.y.get::<meter>() + 1.0).abs() < 1e-12);
+    assert!((length(entry, exit) - 2.0).abs() < 1e-12);
+    // Along the axis, through both end caps.
+    let (entry, exit) = cylinder
+        .intersections(coordinate(0.5, 0.0, 0.0), coordinate(0.5, 0.0, -1.0))
+        .unwrap();
+    assert_eq!(entry.z.get::<meter>(), 2.0);
+    assert_eq!(exit.z.get::<meter>(), -2.0);
+    assert!((length(entry, exit) - 4.0).abs() < 1e-12);
+    // Enters through the barrel and exits through an end cap.
+    let (entry, exit) = cylinder
+        .intersections(coordinate(-1.0, 0.0, 0.0), coordinate(1.0, 0.0, 2.0))
+        .unwrap();
+    assert!((entry.x.get::<meter>() + 1.0).abs() < 1e-12);
+    assert!((entry.z.get::<meter>()).abs() < 1e-12);
+    assert!((exit.x.get::<meter>() - 1.0).abs() < 1e-12);
+    assert!((exit.z.get::<meter>() - 2.0).abs() < 1e-12);
+    assert!((length(entry, exit) - 8.0f64.sqrt()).abs() < 1e-12);
+}
@@ -1,32 +1,51 @@
 use crate::calibration::pads::baseline::try_pad_baseline;
+use crate::calibration::pads::column_offsets::pad_column_offsets;
 use crate::calibration::pads::delay::try_pad_delay;
 use crate::calibration::pads::gain::try_pad_gain;
+use crate::calibration::wires::alignment::wire_phi_offset;
 use crate::calibration::wires::baseline::try_wire_baseline;
 use crate::calibration::wires::delay::try_wire_delay;
 use crate::calibration::wires::gain::try_wire_gain;
 use crate::deconvolution::pads::pad_deconvolution;
-use crate::deconvolution::wires::{contiguous_ranges, wire_range_deconvolution};
+use crate::deconvolution::wires::{
+    clipped_samples, contiguous_ranges, saturated_inputs, wire_range_deconvolution,
+};
+use crate::detector_response::max_drift_time;
 use crate::drift::DRIFT_TABLES;
-use crate::matching::{match_column_inputs, pad_column_to_wires, wire_to_pad_column};
-use crate::reconstruction::{cluster_spacepoints, find_vertices, Coordinate};
+use crate::matching::{
+    match_column_inputs, pad_column_to_wires, wire_to_pad_column, ColumnMatches,
+};
+use crate::reconstruction::{
+    cluster_spacepoints, try_find_vertices_with_settings, Cluster, Coordinate, FieldConfiguration,
+    FindVerticesError, ReconstructionSettings, Track, TryTrackFromClusterError, VertexInfo,
+};
 use alpha_g_detector::alpha16::aw_map::{
     self, MapTpcWirePositionError, TpcWirePosition, TPC_ANODE_WIRES,
 };
-use alpha_g_detector::alpha16::{self, AdcPacket, TryAdcPacketFromSliceError};
+use alpha_g_detector::alpha16::{
+    self, Adc16ChannelId, AdcPacket, TryAdcPacketFromSliceError, ValidateBankPacketError,
+};
 use alpha_g_detector::midas::{
-    Adc32BankName, Alpha16BankName, MainEventBankName, ParseMainEventBankNameError,
+    Adc16BankName, Adc32BankName, Alpha16BankName, MainEventBankName, ParseMainEventBankNameError,
 };
 use alpha_g_detector::padwing::map::{
-    MapTpcPadPositionError, TpcPadPosition, TPC_PAD_COLUMNS, TPC_PAD_ROWS,
+    MapTpcPadPositionError, TpcPadPosition, TPC_PADS, TPC_PAD_COLUMNS, TPC_PAD_ROWS,
 };
 use alpha_g_detector::padwing::{
-    self, Chunk, PwbPacket, TryChunkFromSliceError, TryPwbPacketFromChunksError,
+    self, AfterId, FpnChannelId, GroupChunksError, TryChunkFromSliceError,
+    TryPwbPacketFromChunksError,
 };
+use alpha_g_detector::run::RunNumber;
 use alpha_g_detector::trigger::TryTrgPacketFromSliceError;
 use alpha_g_detector::trigger::{self, TrgPacket};
 use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::hash::Hash;
+use std::ops::{Range, RangeInclusive};
 use thiserror::Error;
+use uom::si::angle::radian;
 use uom::si::f64::*;
+use uom::si::time::second;
 use uom::typenum::P2;
 
 pub use crate::calibration::pads::baseline::MapPadBaselineError;
@@ -35,7 +54,7 @@ pub use crate::calibration::pads::gain::MapPadGainError;
 pub use crate::calibration::wires::baseline::MapWireBaselineError;
 pub use crate::calibration::wires::delay::MapWireDelayError;
 pub use crate::calibration::wires::gain::MapWireGainError;
-pub use crate::drift::TryDriftLookupError;
+pub use crate::drift::{TryDriftLookupError, DEFAULT_EXTRAPOLATION_MARGIN};
 
 // Calibration
 //
@@ -47,7 +66,12 @@ pub use crate::drift::TryDriftLookupError;
 //
 // If there is ever a compelling reason to expose calibration to the user, I
 // believe it should be moved to a separate `alpha_g_calibration` crate.
-mod calibration;
+//
+// The only exception is which calibration epoch is used for a given run. This
+// is provenance rather than calibration data, and analysis results have to be
+// able to record it.
+/// Provenance of the calibrations used to reconstruct a run.
+pub mod calibration;
 // Map, as a function of `z` (given that the B field is non-homogeneous through
 // the entire rTPC volume), a given drift time to a radius and Lorentz angle
 // correction.
@@ -56,8 +80,14 @@ mod drift;
 // signals.
 mod deconvolution;
 // Match wire and pad signals to obtain Avalanches.
+/// Barrel Veto pulses and hits.
+pub mod bv;
 /// Chronobox.
 pub mod chronobox;
+/// Expected timing of the detector signals (e.g. maximum drift time).
+pub mod detector_response;
+/// Detector geometry.
+pub mod geometry;
 mod matching;
 /// Vertex reconstruction.
 pub mod reconstruction;
@@ -66,7 +96,7 @@ pub mod reconstruction;
 /// surface.
 ///
 /// All avalanches happen at the same radius equal to [`ANODE_WIRES_RADIUS`].
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Avalanche {
     /// Time with respect to the first avalanche in the same event.
     pub t: Time,
@@ -85,6 +115,50 @@ pub struct Avalanche {
     pub wire_amplitude: f64,
     /// Same as `wire_amplitude`, but for the induced pad signal.
     pub pad_amplitude: f64,
+    /// Whether the anode wire signal was clipped at the ADC rails (see
+    /// [`ADC_MAX`](alpha_g_detector::alpha16::ADC_MAX) and
+    /// [`ADC_MIN`](alpha_g_detector::alpha16::ADC_MIN)) around the time of
+    /// this avalanche.
+    ///
+    /// The `wire_amplitude` of a saturated avalanche is underestimated (by an
+    /// unknown amount). Analyses that depend on the amplitude should cut on
+    /// this flag.
+    pub saturated: bool,
+}
+
+/// Reflection of the [`Avalanche`]s of an event (see [`MainEvent::mirror`]).
+///
+/// This is a cross-check of the reconstruction, not a physical
+/// transformation. The vertex distribution of mirrored events should be the
+/// mirror image of the original one, so any asymmetry points to e.g. a
+/// calibration or channel mapping bug.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Mirror {
+    /// Reflect the azimuthal angle i.e. `phi -> -phi`. The Lorentz correction
+    /// is still applied in the same direction when converting the avalanches
+    /// to [`SpacePoint`]s, so the spacepoints are not exactly mirrored.
+    Phi,
+    /// Reflect the axial position i.e. `z -> -z`. The drift tables are
+    /// symmetric in `z` (they only depend on `|z|`), so the spacepoints are
+    /// exactly mirrored too.
+    Z,
+}
+
+impl Mirror {
+    /// Return the mirror image of an [`Avalanche`]. Applying the same
+    /// reflection twice returns the original avalanche.
+    pub fn apply(self, avalanche: Avalanche) -> Avalanche {
+        match self {
+            Self::Phi => Avalanche {
+                phi: -avalanche.phi,
+                ..avalanche
+            },
+            Self::Z => Avalanche {
+                z: -avalanche.z,
+                ..avalanche
+            },
+        }
+    }
 }
 
 /// Radial position of the anode wires.
@@ -94,6 +168,9 @@ pub const ANODE_WIRES_RADIUS: Length = Length {
     value: aw_map::ANODE_WIRES_RADIUS,
 };
 
+/// Version of this crate (e.g. to record the provenance of analysis results).
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
 /// Frequency of the internal TRG clock.
 pub const TRG_CLOCK_FREQ: Frequency = Frequency {
     dimension: uom::lib::marker::PhantomData,
@@ -101,6 +178,25 @@ pub const TRG_CLOCK_FREQ: Frequency = Frequency {
     value: trigger::TRG_CLOCK_FREQ,
 };
 
+/// Return the range of drift times covered by the drift tables at a given
+/// axial position.
+///
+/// Converting an [`Avalanche`] to a [`SpacePoint`] linearly extrapolates drift
+/// times slightly beyond the end of this range (i.e. ionizations right at the
+/// inner cathode). Drift times past this small margin (see
+/// [`DEFAULT_EXTRAPOLATION_MARGIN`] and
+/// [`SpacePoint::try_from_avalanche_with_margin`]) fail with
+/// [`TryDriftLookupError::DriftTimeBeyondMaximum`].
+pub fn drift_time_range(z: Length) -> Result<RangeInclusive<Time>, TryDriftLookupError> {
+    DRIFT_TABLES.time_range(z)
+}
+
+/// Return the range of radii covered by the drift tables (at any axial
+/// position).
+pub fn drift_radius_range() -> RangeInclusive<Length> {
+    DRIFT_TABLES.radius_range()
+}
+
 /// Reconstructed ionization position.
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct SpacePoint {
@@ -117,7 +213,20 @@ impl TryFrom<Avalanche> for SpacePoint {
     type Error = TryDriftLookupError;
 
     fn try_from(avalanche: Avalanche) -> Result<Self, Self::Error> {
-        let (r, lorentz_correction) = DRIFT_TABLES.at(avalanche.z, avalanche.t)?;
+        Self::try_from_avalanche_with_margin(avalanche, DEFAULT_EXTRAPOLATION_MARGIN)
+    }
+}
+
+impl SpacePoint {
+    /// Same as [`SpacePoint::try_from`], but drift times are linearly
+    /// extrapolated up to `margin` beyond the end of the drift tables (instead
+    /// of [`DEFAULT_EXTRAPOLATION_MARGIN`]). A zero margin only accepts drift
+    /// times within [`drift_time_range`].
+    pub fn try_from_avalanche_with_margin(
+        avalanche: Avalanche,
+        margin: Time,
+    ) -> Result<Self, TryDriftLookupError> {
+        let (r, lorentz_correction) = DRIFT_TABLES.at(avalanche.z, avalanche.t, margin)?;
 
         Ok(SpacePoint {
             r,
@@ -125,9 +234,6 @@ impl TryFrom<Avalanche> for SpacePoint {
             z: avalanche.z,
         })
     }
-}
-
-impl SpacePoint {
     /// Return the `x` coordinate of the ionization position.
     pub fn x(self) -> Length {
         self.r * self.phi.cos()
@@ -168,6 +274,19 @@ pub enum TryMainEventFromDataBanksError {
     /// Duplicate anode wire data banks were found.
     #[error("duplicate anode wire data banks with name `{bank_name:?}`")]
     DuplicateWireBank { bank_name: Adc32BankName },
+    /// The `(board_id, channel_id)` pair in a Barrel Veto ADC packet does not
+    /// match the expected value from the bank name.
+    #[error("bv board_id/channel_id mismatch (expected {expected:?}, found {found:?})")]
+    BvIdMismatch {
+        expected: (alpha16::BoardId, Adc16ChannelId),
+        found: (alpha16::BoardId, Adc16ChannelId),
+    },
+    /// A Barrel Veto data bank has an anode wire channel_id.
+    #[error("bv data bank `{bank_name:?}` has an anode wire channel_id")]
+    BvBankWithWireChannel { bank_name: Adc16BankName },
+    /// Duplicate Barrel Veto data banks were found.
+    #[error("duplicate bv data banks with name `{bank_name:?}`")]
+    DuplicateBvBank { bank_name: Adc16BankName },
     /// The chunk data from a Padwing bank is invalid.
     #[error("bad padwing chunk data")]
     BadPadwingChunk(#[from] TryChunkFromSliceError),
@@ -217,6 +336,683 @@ pub enum TryMainEventFromDataBanksError {
     /// Pad gain calibration failed.
     #[error("pad gain calibration failed")]
     PadGainError(#[from] MapPadGainError),
+    /// Not all boards report the same event counter.
+    #[error("misbuilt event (boards disagree on the event counter)")]
+    MisbuiltEvent(MisbuiltEvent),
+}
+/// The error type returned when reconstructing the vertex of a [`MainEvent`]
+/// fails.
+#[derive(Error, Debug)]
+pub enum TryVertexError {
+    /// Some reconstructed [`SpacePoint`]s have a non-finite coordinate.
+    #[error("found `{count}` non-finite spacepoints")]
+    NonFiniteSpacePoints { count: usize },
+    /// Fitting a track failed.
+    #[error("bad track fit")]
+    BadTrack(#[from] TryTrackFromClusterError),
+    /// Fitting the vertex failed.
+    #[error("bad vertex fit")]
+    BadVertex(#[from] FindVerticesError),
+}
+// Report bank/packet mismatches with the same variants used for all other
+// Alpha16 errors.
+impl From<ValidateBankPacketError> for TryMainEventFromDataBanksError {
+    fn from(error: ValidateBankPacketError) -> Self {
+        match error {
+            ValidateBankPacketError::BvChannel { bank_name } => {
+                Self::WireBankWithBvChannel { bank_name }
+            }
+            ValidateBankPacketError::IdMismatch { expected, found } => {
+                Self::Alpha16IdMismatch { expected, found }
+            }
+        }
+    }
+}
+// Report chunk grouping errors with the same variants used for all other
+// Padwing errors.
+impl From<GroupChunksError> for TryMainEventFromDataBanksError {
+    fn from(error: GroupChunksError) -> Self {
+        match error {
+            GroupChunksError::BadChunk(error) => Self::BadPadwingChunk(error),
+            GroupChunksError::BoardIdMismatch { found, expected } => {
+                Self::PadwingBoardIdMismatch { expected, found }
+            }
+            GroupChunksError::BadPacket { source, .. } => Self::BadPadwing(source),
+        }
+    }
+}
+
+/// Boards that disagree on the event counter of a [`MainEvent`].
+///
+/// All packets from the same event are expected to report the same event
+/// counter. A disagreement means that the event builder glued together data
+/// from different triggers.
+///
+/// Only boards of the same type are compared against each other. The Alpha16
+/// `accepted_trigger` counter is only 16 bits wide, and the offset between it
+/// and the Padwing `event_counter` has not been established from data yet.
+/// Within each board type, a board disagrees if any of its packets has a
+/// counter different from the most common value in the event.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MisbuiltEvent {
+    /// Alpha16 boards that disagree on the `accepted_trigger` counter.
+    pub alpha16_boards: Vec<alpha16::BoardId>,
+    /// Padwing boards that disagree on the `event_counter`.
+    pub padwing_boards: Vec<padwing::BoardId>,
+}
+
+impl MisbuiltEvent {
+    // Return `None` if all boards of each type agree on the event counter.
+    fn try_new(
+        alpha16_counters: &[(alpha16::BoardId, u16)],
+        padwing_counters: &[(padwing::BoardId, u32)],
+    ) -> Option<Self> {
+        let mut alpha16_boards = disagreeing_boards(alpha16_counters);
+        let mut padwing_boards = disagreeing_boards(padwing_counters);
+        if alpha16_boards.is_empty() && padwing_boards.is_empty() {
+            return None;
+        }
+        alpha16_boards.sort_unstable_by_key(|board_id| board_id.name());
+        padwing_boards.sort_unstable_by_key(|board_id| board_id.name());
+
+        Some(Self {
+            alpha16_boards,
+            padwing_boards,
+        })
+    }
+}
+
+// Return all the boards that have a counter different from the most common
+// value. Ties are broken by the smallest counter to keep this deterministic.
+fn disagreeing_boards<B, C>(counters: &[(B, C)]) -> Vec<B>
+where
+    B: Copy + Eq + Hash,
+    C: Copy + Ord + Hash,
+{
+    let mut occurrences: HashMap<C, usize> = HashMap::new();
+    for &(_, counter) in counters {
+        *occurrences.entry(counter).or_default() += 1;
+    }
+    let Some(expected) = occurrences
+        .into_iter()
+        .max_by_key(|&(counter, n)| (n, std::cmp::Reverse(counter)))
+        .map(|(counter, _)| counter)
+    else {
+        return Vec::new();
+    };
+
+    let mut boards = Vec::new();
+    for &(board_id, counter) in counters {
+        if counter != expected && !boards.contains(&board_id) {
+            boards.push(board_id);
+        }
+    }
+    boards
+}
+
+/// Channels of a [`MainEvent`] with a waveform shorter than expected.
+///
+/// All wire (and, separately, all pad) waveforms in an event are expected to
+/// have the same number of samples. Occasionally the readout window of a board
+/// is shorter than configured, and the signals after the end of its waveforms
+/// are silently lost. The expected length is the most common length among all
+/// channels of the same type in the event (ties are broken by the longest), so
+/// an event in which all waveforms are equally short is not truncated.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TruncatedWaveforms {
+    /// Anode wires with a waveform shorter than expected.
+    pub wires: Vec<TpcWirePosition>,
+    /// Pads with a waveform shorter than expected.
+    pub pads: Vec<TpcPadPosition>,
+    /// Total number of wire and pad channels with a waveform in the event.
+    pub num_channels: usize,
+}
+
+impl TruncatedWaveforms {
+    /// Return the number of truncated channels.
+    pub fn count(&self) -> usize {
+        self.wires.len() + self.pads.len()
+    }
+    /// Return the fraction of channels with a waveform that are truncated.
+    /// This is `0.0` if there are no channels with a waveform.
+    pub fn fraction(&self) -> f64 {
+        if self.num_channels == 0 {
+            0.0
+        } else {
+            self.count() as f64 / self.num_channels as f64
+        }
+    }
+}
+
+// Most common length. Ties are broken by the longest length (a truncated
+// window can only be shorter).
+fn expected_length(lengths: impl IntoIterator<Item = usize>) -> Option<usize> {
+    let mut occurrences: HashMap<usize, usize> = HashMap::new();
+    for length in lengths {
+        *occurrences.entry(length).or_default() += 1;
+    }
+    occurrences
+        .into_iter()
+        .max_by_key(|&(length, n)| (n, length))
+        .map(|(length, _)| length)
+}
+
+/// Outcome of fitting a single [`Cluster`] to a [`Track`] (see
+/// [`ReconstructionReport::track_fits`]).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrackFit {
+    /// The cluster was fitted to a track.
+    Fitted,
+    /// Unable to produce initial fit parameters (see
+    /// [`TryTrackFromClusterError::NoInitialParameters`]). The cluster is just
+    /// ignored.
+    NoInitialParameters,
+    /// The minimizer failed (see [`TryTrackFromClusterError::FailedFit`]).
+    /// The reconstruction of the event stops here.
+    FailedFit,
+}
+
+impl From<&TryTrackFromClusterError> for TrackFit {
+    fn from(error: &TryTrackFromClusterError) -> Self {
+        match error {
+            TryTrackFromClusterError::NoInitialParameters => Self::NoInitialParameters,
+            TryTrackFromClusterError::FailedFit => Self::FailedFit,
+        }
+    }
+}
+
+impl fmt::Display for TrackFit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Fitted => write!(f, "fitted"),
+            Self::NoInitialParameters => write!(f, "no initial parameters"),
+            Self::FailedFit => write!(f, "failed fit"),
+        }
+    }
+}
+
+/// Status of the primary vertex fit of an event (see
+/// [`ReconstructionReport::vertex`]).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VertexStatus {
+    /// The reconstruction failed before fitting the vertex (i.e. non-finite
+    /// spacepoints or a failed track fit).
+    #[default]
+    NotAttempted,
+    /// There are no two tracks that are long enough and that meet close to the
+    /// beamline.
+    InsufficientTracks {
+        /// Number of fitted tracks in the event.
+        num_tracks: usize,
+    },
+    /// The minimizer failed to find a finite vertex position (see
+    /// [`FindVerticesError::FailedFit`]).
+    Diverged,
+    /// Primary vertex found.
+    Converged {
+        /// Number of tracks associated to the primary vertex.
+        num_tracks: usize,
+    },
+}
+
+impl fmt::Display for VertexStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NotAttempted => write!(f, "not attempted"),
+            Self::InsufficientTracks { num_tracks } => {
+                write!(f, "insufficient tracks ({num_tracks} fitted)")
+            }
+            Self::Diverged => write!(f, "diverged"),
+            Self::Converged { num_tracks } => write!(f, "converged ({num_tracks} tracks)"),
+        }
+    }
+}
+
+/// Earliest reconstruction stage that explains why an event has no primary
+/// vertex (see [`ReconstructionReport::no_vertex_reason`]).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NoVertexReason {
+    /// There are no spacepoints.
+    #[serde(rename = "no_spacepoints")]
+    NoSpacePoints,
+    /// Some spacepoints have a non-finite coordinate.
+    #[serde(rename = "non_finite_spacepoints")]
+    NonFiniteSpacePoints,
+    /// No group of spacepoints is large enough to be a [`Cluster`].
+    NoClusters,
+    /// No cluster could be fitted to a track.
+    NoTracks,
+    /// The fit of a cluster failed.
+    TrackFitFailed,
+    /// Not enough tracks for a vertex (see
+    /// [`VertexStatus::InsufficientTracks`]).
+    InsufficientTracks,
+    /// The vertex fit failed.
+    VertexFitDiverged,
+}
+
+impl NoVertexReason {
+    /// Return a short machine-readable code (e.g. `no_clusters`). This is the
+    /// same as the serialized value.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::NoSpacePoints => "no_spacepoints",
+            Self::NonFiniteSpacePoints => "non_finite_spacepoints",
+            Self::NoClusters => "no_clusters",
+            Self::NoTracks => "no_tracks",
+            Self::TrackFitFailed => "track_fit_failed",
+            Self::InsufficientTracks => "insufficient_tracks",
+            Self::VertexFitDiverged => "vertex_fit_diverged",
+        }
+    }
+}
+
+impl fmt::Display for NoVertexReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+/// Summary of every stage of the vertex reconstruction of a single event
+/// (see [`MainEvent::reconstruct_with_report`]).
+///
+/// Stages after the one at which the reconstruction fails (if any) are left
+/// empty.
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ReconstructionReport {
+    /// Number of [`SpacePoint`]s.
+    pub num_spacepoints: usize,
+    /// Number of [`SpacePoint`]s with a non-finite coordinate. Any of these
+    /// stops the reconstruction before clustering.
+    pub non_finite_spacepoints: usize,
+    /// Number of [`SpacePoint`]s in each [`Cluster`], in the order given by
+    /// [`cluster_spacepoints`].
+    pub cluster_sizes: Vec<usize>,
+    /// Number of [`SpacePoint`]s that are not part of any [`Cluster`].
+    pub num_unclustered: usize,
+    /// Outcome of fitting each [`Cluster`] (same order as `cluster_sizes`).
+    /// This is shorter than `cluster_sizes` if a fit fails with
+    /// [`TrackFit::FailedFit`].
+    pub track_fits: Vec<TrackFit>,
+    /// Status of the primary vertex fit.
+    pub vertex: VertexStatus,
+}
+
+impl ReconstructionReport {
+    /// Return the number of clusters fitted to a track.
+    pub fn num_tracks(&self) -> usize {
+        self.track_fits
+            .iter()
+            .filter(|fit| **fit == TrackFit::Fitted)
+            .count()
+    }
+    /// Return the earliest stage that explains why the event has no primary
+    /// vertex. Returns [`None`] if the vertex fit converged.
+    pub fn no_vertex_reason(&self) -> Option<NoVertexReason> {
+        match self.vertex {
+            VertexStatus::Converged { .. } => None,
+            VertexStatus::Diverged => Some(NoVertexReason::VertexFitDiverged),
+            VertexStatus::NotAttempted if self.non_finite_spacepoints > 0 => {
+                Some(NoVertexReason::NonFiniteSpacePoints)
+            }
+            VertexStatus::NotAttempted => Some(NoVertexReason::TrackFitFailed),
+            VertexStatus::InsufficientTracks { .. } => Some(if self.num_spacepoints == 0 {
+                NoVertexReason::NoSpacePoints
+            } else if self.cluster_sizes.is_empty() {
+                NoVertexReason::NoClusters
+            } else if self.num_tracks() == 0 {
+                NoVertexReason::NoTracks
+            } else {
+                NoVertexReason::InsufficientTracks
+            }),
+        }
+    }
+}
+
+impl fmt::Display for ReconstructionReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} spacepoints", self.num_spacepoints)?;
+        if self.non_finite_spacepoints > 0 {
+            write!(f, " ({} non-finite)", self.non_finite_spacepoints)?;
+        }
+        let sizes: Vec<_> = self.cluster_sizes.iter().map(ToString::to_string).collect();
+        write!(
+            f,
+            ", {} clusters [{}], {} unclustered",
+            self.cluster_sizes.len(),
+            sizes.join(", "),
+            self.num_unclustered
+        )?;
+        let failed: Vec<_> = self
+            .track_fits
+            .iter()
+            .filter(|fit| **fit != TrackFit::Fitted)
+            .map(ToString::to_string)
+            .collect();
+        write!(f, ", {} tracks", self.num_tracks())?;
+        if !failed.is_empty() {
+            write!(f, " ({} failed: {})", failed.len(), failed.join(", "))?;
+        }
+        write!(f, ", vertex {}", self.vertex)
+    }
+}
+
+/// Number of objects at every stage of the reconstruction of a [`MainEvent`]
+/// (see [`MainEvent::reconstruction_summary`]).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ReconstructionSummary {
+    /// Number of anode wire channels with a signal.
+    pub wire_channels: usize,
+    /// Number of cathode pad channels with a signal.
+    pub pad_channels: usize,
+    /// Number of [`Avalanche`]s.
+    pub avalanches: usize,
+    /// Everything after the avalanches (i.e. from the [`SpacePoint`]s to the
+    /// primary vertex).
+    pub report: ReconstructionReport,
+}
+
+/// Settings of an emulated region of interest (ROI) readout of the TPC (see
+/// [`MainEvent::roi_readout`]).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RoiSettings {
+    /// Number of samples kept before the first threshold crossing of a signal.
+    pub pre_samples: usize,
+    /// Number of samples kept from the first threshold crossing of a signal
+    /// onwards (including the crossing itself).
+    pub post_samples: usize,
+    /// Threshold on the absolute value of a calibrated signal. Same arbitrary
+    /// units as the signals from which the [`Avalanche`] amplitudes are
+    /// computed.
+    pub threshold: f64,
+}
+
+/// Number of TPC samples in an event with and without an emulated region of
+/// interest readout (see [`MainEvent::roi_readout`]).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RoiReduction {
+    /// Total number of wire and pad samples read out in the event.
+    pub full_samples: usize,
+    /// Number of those samples inside a region of interest.
+    pub roi_samples: usize,
+}
+
+impl RoiReduction {
+    /// Return the fraction of samples that are not read out with the region
+    /// of interest readout. Returns [`None`] if the event has no samples.
+    pub fn fraction(&self) -> Option<f64> {
+        (self.full_samples != 0).then(|| 1.0 - self.roi_samples as f64 / self.full_samples as f64)
+    }
+}
+
+/// Deconvolved input (i.e. a spike) of a wire or pad signal.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Spike {
+    /// Time with respect to the start of the signal.
+    pub t: Time,
+    /// Amplitude of the input in arbitrary units. Same caveats as
+    /// [`Avalanche::wire_amplitude`].
+    pub amplitude: f64,
+    /// Whether the input was matched into an [`Avalanche`].
+    pub matched: bool,
+}
+
+/// Calibrated signal of a single wire or pad channel together with its
+/// deconvolved spikes. See [`MainEvent::wire_deconvolution`] and
+/// [`MainEvent::pad_deconvolution`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct DeconvolvedSignal {
+    /// Calibrated (i.e. delay, baseline, and gain corrected) signal. Samples
+    /// are at a rate of [`ADC32_RATE`](alpha_g_detector::alpha16::ADC32_RATE)
+    /// for wires and [`PWB_RATE`](alpha_g_detector::padwing::PWB_RATE) for
+    /// pads.
+    pub signal: Vec<f64>,
+    /// All the (non-zero) deconvolved inputs, sorted by time.
+    pub spikes: Vec<Spike>,
+}
+
+// Non-zero inputs of a deconvolved signal sampled at `rate`.
+fn spikes(input: &[f64], rate: f64, matched: impl Fn(usize) -> bool) -> Vec<Spike> {
+    input
+        .iter()
+        .enumerate()
+        .filter(|(_, &amplitude)| amplitude > 0.0)
+        .map(|(sample, &amplitude)| Spike {
+            t: Time::new::<second>(sample as f64 / rate),
+            amplitude,
+            matched: matched(sample),
+        })
+        .collect()
+}
+
+/// Calibrated signals and deconvolved spikes of all the channels in an event.
+/// See [`MainEvent::deconvolution`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct EventDeconvolution {
+    /// Anode wires with a signal, sorted by index.
+    pub wires: Vec<(TpcWirePosition, DeconvolvedSignal)>,
+    /// Cathode pads with a signal, sorted by column and then by row.
+    pub pads: Vec<(TpcPadPosition, DeconvolvedSignal)>,
+}
+
+/// Raw waveforms of the PadWing Fixed Pattern Noise channels in an event, keyed
+/// by board, AFTER chip, and FPN channel. See [`MainEvent::fpn_signals`].
+pub type FpnSignals = HashMap<(padwing::BoardId, AfterId, FpnChannelId), Vec<i16>>;
+
+/// Baseline-subtracted waveforms of the Barrel Veto SiPM channels in an event,
+/// keyed by Alpha16 board and ADC16 channel. See [`MainEvent::bv_signals`].
+pub type BvSignals = HashMap<(alpha16::BoardId, Adc16ChannelId), Vec<f64>>;
+
+/// Calibration of all the channels in a run.
+///
+/// Creating a [`MainEvent`] with [`MainEvent::try_from_banks`] looks up the
+/// calibration of every channel in the event for its run number. Use
+/// [`MainEvent::try_from_banks_with`] to resolve it only once when
+/// reconstructing many events from the same run.
+///
+/// The calibration values themselves are an implementation detail and are not
+/// accessible.
+#[derive(Clone, Debug)]
+pub struct RunCalibration {
+    run_number: RunNumber,
+    // A `None` means that there is no calibration for that channel. The error
+    // is only built (by repeating the lookup) if the channel shows up in an
+    // event.
+    wire_baselines: Vec<Option<i16>>,
+    wire_gains: Vec<Option<f64>>,
+    wire_delay: Option<usize>,
+    // Indexed by `TpcPadPosition::index`.
+    pad_baselines: Vec<Option<i16>>,
+    pad_gains: Vec<Option<f64>>,
+    pad_delay: Option<usize>,
+    wire_phi_offset: Angle,
+    pad_column_offsets: [i32; TPC_PAD_COLUMNS],
+    field: FieldConfiguration,
+}
+
+impl RunCalibration {
+    /// Resolve the calibration of all the channels for a given run number.
+    /// This never fails; missing calibrations are reported by
+    /// [`MainEvent::try_from_banks_with`] only if they are needed.
+    pub fn new(run_number: RunNumber) -> Self {
+        let run = u32::from(run_number);
+        let wires = (0..TPC_ANODE_WIRES).map(|i| TpcWirePosition::try_from(i).unwrap());
+        let pads = (0..TPC_PADS).map(|i| TpcPadPosition::try_from(i).unwrap());
+
+        Self {
+            run_number,
+            wire_baselines: wires
+                .clone()
+                .map(|wire| try_wire_baseline(run, wire).ok())
+                .collect(),
+            wire_gains: wires.map(|wire| try_wire_gain(run, wire).ok()).collect(),
+            wire_delay: try_wire_delay(run).ok(),
+            pad_baselines: pads
+                .clone()
+                .map(|pad| try_pad_baseline(run, pad).ok())
+                .collect(),
+            pad_gains: pads.map(|pad| try_pad_gain(run, pad).ok()).collect(),
+            pad_delay: try_pad_delay(run).ok(),
+            wire_phi_offset: Angle::new::<radian>(wire_phi_offset(run)),
+            pad_column_offsets: pad_column_offsets(run),
+            field: FieldConfiguration::default(),
+        }
+    }
+    /// Set the magnetic field configuration of the run (the default is
+    /// [`FieldConfiguration::On`]). The tracks of all the events created with
+    /// this calibration are fitted with the corresponding
+    /// [`TrackModel`](crate::reconstruction::TrackModel).
+    ///
+    /// There is no record of the field configuration of each run, so field-off
+    /// runs have to be set explicitly.
+    pub fn with_field(self, field: FieldConfiguration) -> Self {
+        Self { field, ..self }
+    }
+    /// Return the run number of the calibration.
+    pub fn run_number(&self) -> RunNumber {
+        self.run_number
+    }
+    /// Return the magnetic field configuration of the run.
+    pub fn field(&self) -> FieldConfiguration {
+        self.field
+    }
+}
+
+// Source of the calibration while creating a `MainEvent`. A single event only
+// looks up the channels it has, so resolving the whole `RunCalibration` would
+// be slower.
+#[derive(Clone, Copy)]
+enum Calibration<'a> {
+    Lookup(RunNumber),
+    Cached(&'a RunCalibration),
+}
+
+impl Calibration<'_> {
+    fn run_number(&self) -> RunNumber {
+        match self {
+            Self::Lookup(run_number) => *run_number,
+            Self::Cached(calibration) => calibration.run_number,
+        }
+    }
+    // Cached values fall back to the lookup to get the appropriate error.
+    fn wire_baseline(&self, wire: TpcWirePosition) -> Result<i16, MapWireBaselineError> {
+        match self {
+            Self::Cached(calibration) => calibration.wire_baselines[usize::from(wire)],
+            Self::Lookup(_) => None,
+        }
+        .map_or_else(|| try_wire_baseline(self.run_number().into(), wire), Ok)
+    }
+    fn wire_gain(&self, wire: TpcWirePosition) -> Result<f64, MapWireGainError> {
+        match self {
+            Self::Cached(calibration) => calibration.wire_gains[usize::from(wire)],
+            Self::Lookup(_) => None,
+        }
+        .map_or_else(|| try_wire_gain(self.run_number().into(), wire), Ok)
+    }
+    fn wire_delay(&self) -> Result<usize, MapWireDelayError> {
+        match self {
+            Self::Cached(calibration) => calibration.wire_delay,
+            Self::Lookup(_) => None,
+        }
+        .map_or_else(|| try_wire_delay(self.run_number().into()), Ok)
+    }
+    fn pad_baseline(&self, pad: TpcPadPosition) -> Result<i16, MapPadBaselineError> {
+        match self {
+            Self::Cached(calibration) => calibration.pad_baselines[pad.index()],
+            Self::Lookup(_) => None,
+        }
+        .map_or_else(|| try_pad_baseline(self.run_number().into(), pad), Ok)
+    }
+    fn pad_gain(&self, pad: TpcPadPosition) -> Result<f64, MapPadGainError> {
+        match self {
+            Self::Cached(calibration) => calibration.pad_gains[pad.index()],
+            Self::Lookup(_) => None,
+        }
+        .map_or_else(|| try_pad_gain(self.run_number().into(), pad), Ok)
+    }
+    fn pad_delay(&self) -> Result<usize, MapPadDelayError> {
+        match self {
+            Self::Cached(calibration) => calibration.pad_delay,
+            Self::Lookup(_) => None,
+        }
+        .map_or_else(|| try_pad_delay(self.run_number().into()), Ok)
+    }
+    fn wire_phi_offset(&self) -> Angle {
+        match self {
+            Self::Cached(calibration) => calibration.wire_phi_offset,
+            Self::Lookup(run_number) => {
+                Angle::new::<radian>(wire_phi_offset(u32::from(*run_number)))
+            }
+        }
+    }
+    fn pad_column_offsets(&self) -> [i32; TPC_PAD_COLUMNS] {
+        match self {
+            Self::Cached(calibration) => calibration.pad_column_offsets,
+            Self::Lookup(run_number) => pad_column_offsets(u32::from(*run_number)),
+        }
+    }
+    fn field(&self) -> FieldConfiguration {
+        match self {
+            Self::Cached(calibration) => calibration.field,
+            Self::Lookup(_) => FieldConfiguration::default(),
+        }
+    }
+    // There is no Barrel Veto calibration yet. Until there is one, the
+    // baseline is the one calculated by the Alpha16 firmware for data
+    // suppression (average of the first 64 samples), and the gain is 1.
+    fn bv_baseline(&self, packet: &AdcPacket) -> i16 {
+        packet.suppression_baseline().unwrap_or_default()
+    }
+    fn bv_gain(&self, _board_id: alpha16::BoardId, _channel_id: Adc16ChannelId) -> f64 {
+        1.0
+    }
+}
+
+/// Options to convert data banks to a [`MainEvent`] (see
+/// [`MainEvent::try_from_banks_with`]). The options can be combined freely.
+///
+/// The default (all options off) is the same as [`MainEvent::try_from_banks`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DecodeOptions {
+    /// Skip errors that only affect the data of a single bank instead of
+    /// failing the whole event. The data of the offending bank (or PadWing
+    /// packet) is dropped, and the error is returned together with the event.
+    ///
+    /// The following errors are skipped:
+    /// - [`UnknownBank`](TryMainEventFromDataBanksError::UnknownBank).
+    /// - [`BadAlpha16`](TryMainEventFromDataBanksError::BadAlpha16),
+    ///   [`Alpha16IdMismatch`](TryMainEventFromDataBanksError::Alpha16IdMismatch),
+    ///   [`WireBankWithBvChannel`](TryMainEventFromDataBanksError::WireBankWithBvChannel),
+    ///   [`BvBankWithWireChannel`](TryMainEventFromDataBanksError::BvBankWithWireChannel),
+    ///   and [`BvIdMismatch`](TryMainEventFromDataBanksError::BvIdMismatch).
+    /// - [`BadPadwingChunk`](TryMainEventFromDataBanksError::BadPadwingChunk),
+    ///   [`PadwingBoardIdMismatch`](TryMainEventFromDataBanksError::PadwingBoardIdMismatch),
+    ///   and [`BadPadwing`](TryMainEventFromDataBanksError::BadPadwing) (e.g.
+    ///   a CRC failure).
+    /// - [`WirePositionError`](TryMainEventFromDataBanksError::WirePositionError)
+    ///   and
+    ///   [`PadPositionError`](TryMainEventFromDataBanksError::PadPositionError)
+    ///   (i.e. unknown channel IDs).
+    ///
+    /// Any other error (e.g. duplicate banks, or a bad or missing TRG bank)
+    /// means that the event itself is broken, and it is still returned as an
+    /// error.
+    pub lossy: bool,
+    /// Retain the waveforms of all the PadWing FPN channels in the event (see
+    /// [`MainEvent::fpn_signals`]).
+    pub keep_fpn: bool,
+    /// Decode the Barrel Veto data banks, and retain their waveforms (see
+    /// [`MainEvent::bv_signals`]).
+    ///
+    /// The BV banks are ignored otherwise. A bad BV bank is then an error just
+    /// like a bad anode wire bank (i.e. it fails the whole event unless
+    /// `lossy` is also set).
+    pub keep_bv: bool,
+    /// Return an error if the event is misbuilt (see [`MisbuiltEvent`]).
+    pub strict: bool,
 }
 
 /// ALPHA-g main event.
@@ -233,60 +1029,167 @@ pub struct MainEvent {
     // with a `TpcWirePosition` key. (As long as we are careful about the
     // 0th wire channel.)
     wire_signals: [Option<Vec<f64>>; TPC_ANODE_WIRES],
+    // Indices of the samples in `wire_signals` that are clipped at the ADC
+    // rails. Empty for most wires.
+    wire_clipped: [Vec<usize>; TPC_ANODE_WIRES],
+    // Time of the first sample of `wire_signals` relative to the trigger (i.e.
+    // the samples dropped by the wire delay calibration).
+    wire_signal_start: Time,
     pad_signals: [[Option<Vec<f64>>; TPC_PAD_ROWS]; TPC_PAD_COLUMNS],
     trigger_timestamp: u32,
+    misbuilt: Option<MisbuiltEvent>,
+    // Raw FPN waveforms. These are only kept if explicitly requested (they are
+    // not needed for reconstruction and can take a lot of memory).
+    fpn_signals: Option<FpnSignals>,
+    // Only kept if explicitly requested (same as the FPN signals). Unlike the
+    // wires, a channel that was read out but has an empty waveform (data
+    // suppression) is kept with an empty signal.
+    bv_signals: Option<BvSignals>,
+    // Azimuthal alignment of the anode wire plane (relative to the pads).
+    wire_phi_offset: Angle,
+    // Timing offset (in samples) of each pad column relative to the wires.
+    pad_column_offsets: [i32; TPC_PAD_COLUMNS],
+    // Reflections applied to the avalanches (see `MainEvent::mirror`).
+    mirror_phi: bool,
+    mirror_z: bool,
+    // Track model used by the reconstruction.
+    field: FieldConfiguration,
 }
 impl MainEvent {
     /// Given a run number, try to convert data banks to a [`MainEvent`]. The
     /// data banks are provided as an iterator over tuples of bank name and data
     /// slice.
+    ///
+    /// This uses the default [`DecodeOptions`]. Use
+    /// [`MainEvent::try_from_banks_with`] for any other options.
     pub fn try_from_banks<'a, I>(
+        run_number: RunNumber,
+        banks: I,
+    ) -> Result<Self, TryMainEventFromDataBanksError>
+    where
+        I: IntoIterator<Item = (&'a str, &'a [u8])>,
+    {
+        let (event, _) = Self::try_from_banks_impl(
+            Calibration::Lookup(run_number),
+            banks,
+            &DecodeOptions::default(),
+        )?;
+
+        Ok(event)
+    }
+    /// Same as [`MainEvent::try_from_banks`], but with the run number as a bare
+    /// `u32`.
+    #[deprecated(note = "use `MainEvent::try_from_banks` with a `RunNumber`")]
+    pub fn try_from_banks_u32<'a, I>(
         run_number: u32,
         banks: I,
     ) -> Result<Self, TryMainEventFromDataBanksError>
     where
         I: IntoIterator<Item = (&'a str, &'a [u8])>,
     {
+        Self::try_from_banks(RunNumber::from(run_number), banks)
+    }
+    /// Same as [`MainEvent::try_from_banks`], but using a calibration that was
+    /// already resolved for the run (see [`RunCalibration`]), and the given
+    /// [`DecodeOptions`]. This is faster when creating many events from the
+    /// same run.
+    ///
+    /// The event is returned together with the errors that were skipped (see
+    /// [`DecodeOptions::lossy`]). These are always empty unless `lossy` is
+    /// set.
+    pub fn try_from_banks_with<'a, I>(
+        calibration: &RunCalibration,
+        banks: I,
+        options: &DecodeOptions,
+    ) -> Result<(Self, Vec<TryMainEventFromDataBanksError>), TryMainEventFromDataBanksError>
+    where
+        I: IntoIterator<Item = (&'a str, &'a [u8])>,
+    {
+        Self::try_from_banks_impl(Calibration::Cached(calibration), banks, options)
+    }
+    // Errors that only affect a single bank are returned together with the
+    // event if `options.lossy` (see `DecodeOptions::lossy`).
+    fn try_from_banks_impl<'a, I>(
+        calibration: Calibration<'_>,
+        banks: I,
+        options: &DecodeOptions,
+    ) -> Result<(Self, Vec<TryMainEventFromDataBanksError>), TryMainEventFromDataBanksError>
+    where
+        I: IntoIterator<Item = (&'a str, &'a [u8])>,
+    {
+        let run_number = calibration.run_number();
+        let mut skipped = Vec::new();
+        // Returns the error back unless it can be skipped.
+        let mut skip = |error: TryMainEventFromDataBanksError| {
+            if options.lossy {
+                skipped.push(error);
+                Ok(())
+            } else {
+                Err(error)
+            }
+        };
         // I didn't find another way to initialize such large arrays.
         let mut wire_signals = [(); TPC_ANODE_WIRES].map(|_| None);
+        let mut wire_clipped = [(); TPC_ANODE_WIRES].map(|_| Vec::new());
+        let mut wire_signal_start = Time::new::<second>(0.0);
         let mut pad_signals = [(); TPC_PAD_COLUMNS].map(|_| [(); TPC_PAD_ROWS].map(|_| None));
         let mut trigger_timestamp = None;
-        // Need to group chunks by board and chip.
-        let mut pwb_chunks_map: HashMap<_, Vec<_>> = HashMap::new();
+        // Chunks are grouped by board and chip after all banks are seen.
+        let mut pwb_banks = Vec::new();
+        let mut alpha16_counters = Vec::new();
+        let mut padwing_counters = Vec::new();
+        let mut fpn_signals = options.keep_fpn.then(HashMap::new);
+        let mut bv_signals = options.keep_bv.then(HashMap::new);
 
         for (bank_name, data_slice) in banks {
-            match MainEventBankName::try_from(bank_name)? {
+            let name = match MainEventBankName::try_from(bank_name) {
+                Ok(name) => name,
+                Err(error) => {
+                    skip(error.into())?;
+                    continue;
+                }
+            };
+            match name {
                 MainEventBankName::Alpha16(Alpha16BankName::A32(bank_name)) => {
-                    let packet = AdcPacket::try_from(data_slice)?;
+                    let packet = match AdcPacket::try_from(data_slice) {
+                        Ok(packet) => packet,
+                        Err(error) => {
+                            skip(error.into())?;
+                            continue;
+                        }
+                    };
+                    // Empty waveforms still count towards the event builder
+                    // consistency check.
+                    alpha16_counters.push((bank_name.board_id(), packet.accepted_trigger()));
                     let waveform = packet.waveform();
                     if waveform.is_empty() {
                         continue;
                     }
-                    // Given that the waveform is not empty, we can safely
-                    // unwrap.
-                    let board_id = packet.board_id().unwrap();
-                    let alpha16::ChannelId::A32(channel_id) = packet.channel_id() else {
-                        return Err(TryMainEventFromDataBanksError::WireBankWithBvChannel {
-                            bank_name,
-                        });
-                    };
-                    if (bank_name.board_id(), bank_name.channel_id()) != (board_id, channel_id) {
-                        return Err(TryMainEventFromDataBanksError::Alpha16IdMismatch {
-                            expected: (bank_name.board_id(), bank_name.channel_id()),
-                            found: (board_id, channel_id),
-                        });
+                    if let Err(error) = alpha16::validate_bank_packet(bank_name, &packet) {
+                        skip(error.into())?;
+                        continue;
                     }
+                    // After validation, these are the same as in the packet.
+                    let (board_id, channel_id) = (bank_name.board_id(), bank_name.channel_id());
 
-                    let wire_position = TpcWirePosition::try_new(run_number, board_id, channel_id)?;
+                    let wire_position =
+                        match TpcWirePosition::try_new(run_number, board_id, channel_id) {
+                            Ok(wire_position) => wire_position,
+                            Err(error) => {
+                                skip(error.into())?;
+                                continue;
+                            }
+                        };
                     let wire_index = usize::from(wire_position);
                     if wire_signals[wire_index].is_some() {
                         return Err(TryMainEventFromDataBanksError::DuplicateWireBank {
                             bank_name,
                         });
                     } else {
-                        let baseline = try_wire_baseline(run_number, wire_position)?;
-                        let gain = try_wire_gain(run_number, wire_position)?;
-                        let delay = try_wire_delay(run_number)?;
+                        let baseline = calibration.wire_baseline(wire_position)?;
+                        let gain = calibration.wire_gain(wire_position)?;
+                        let delay = calibration.wire_delay()?;
+                        wire_signal_start = Time::new::<second>(delay as f64 / alpha16::ADC32_RATE);
 
                         let signal: Vec<_> = waveform
                             .iter()
@@ -296,21 +1199,50 @@ impl MainEvent {
                             .collect();
                         if !signal.is_empty() {
                             wire_signals[wire_index] = Some(signal);
+                            wire_clipped[wire_index] = clipped_samples(&waveform[delay..]);
                         }
                     }
                 }
-                MainEventBankName::Padwing(bank_name) => {
-                    let chunk = Chunk::try_from(data_slice)?;
-                    let key = (chunk.board_id(), chunk.after_id());
-                    if key.0 != bank_name.board_id() {
-                        return Err(TryMainEventFromDataBanksError::PadwingBoardIdMismatch {
-                            expected: bank_name.board_id(),
-                            found: key.0,
-                        });
+                MainEventBankName::Alpha16(Alpha16BankName::A16(bank_name)) => {
+                    // The BV is not needed for the TPC. Unless requested, its
+                    // banks are ignored (and they can't fail the event).
+                    let Some(bv_signals) = bv_signals.as_mut() else {
+                        continue;
+                    };
+                    let packet = match AdcPacket::try_from(data_slice) {
+                        Ok(packet) => packet,
+                        Err(error) => {
+                            skip(error.into())?;
+                            continue;
+                        }
+                    };
+                    let alpha16::ChannelId::A16(channel_id) = packet.channel_id() else {
+                        skip(TryMainEventFromDataBanksError::BvBankWithWireChannel { bank_name })?;
+                        continue;
+                    };
+                    // Packets with an empty waveform don't have a board_id.
+                    let board_id = packet.board_id().unwrap_or(bank_name.board_id());
+                    if (bank_name.board_id(), bank_name.channel_id()) != (board_id, channel_id) {
+                        skip(TryMainEventFromDataBanksError::BvIdMismatch {
+                            expected: (bank_name.board_id(), bank_name.channel_id()),
+                            found: (board_id, channel_id),
+                        })?;
+                        continue;
                     }
 
-                    pwb_chunks_map.entry(key).or_default().push(chunk);
+                    let baseline = calibration.bv_baseline(&packet);
+                    let gain = calibration.bv_gain(board_id, channel_id);
+                    let signal: Vec<_> = packet
+                        .waveform()
+                        .iter()
+                        // Convert to i32 to avoid overflow
+                        .map(|&v| f64::from(i32::from(v) - i32::from(baseline)) * gain)
+                        .collect();
+                    if bv_signals.insert((board_id, channel_id), signal).is_some() {
+                        return Err(TryMainEventFromDataBanksError::DuplicateBvBank { bank_name });
+                    }
                 }
+                MainEventBankName::Padwing(_) => pwb_banks.push((bank_name, data_slice)),
                 MainEventBankName::Trg(_) => {
                     let packet = TrgPacket::try_from(data_slice)?;
                     if trigger_timestamp.is_some() {
@@ -323,18 +1255,47 @@ impl MainEvent {
             }
         }
 
-        for chunks in pwb_chunks_map.into_values() {
-            let packet = PwbPacket::try_from(chunks)?;
+        let (pwb_packets, _) = padwing::group_chunks(pwb_banks);
+        for packet in pwb_packets {
+            let packet = match packet {
+                Ok(packet) => packet,
+                Err(error) => {
+                    skip(error.into())?;
+                    continue;
+                }
+            };
             let board_id = packet.board_id();
             let after_id = packet.after_id();
+            // Only available in newer packet versions.
+            if let Some(event_counter) = packet.event_counter() {
+                padwing_counters.push((board_id, event_counter));
+            }
             for &channel_id in packet.channels_sent() {
+                if let (padwing::ChannelId::Fpn(fpn_channel_id), Some(fpn_signals)) =
+                    (channel_id, &mut fpn_signals)
+                {
+                    // Chunks are grouped by board and AFTER chip, so there is
+                    // at most one waveform per FPN channel.
+                    let waveform = packet.waveform_at(channel_id).unwrap();
+                    fpn_signals.insert((board_id, after_id, fpn_channel_id), waveform.to_vec());
+                }
                 if let padwing::ChannelId::Pad(pad_channel_id) = channel_id {
                     // A waveform is guaranteed to exist and not be empty if the
                     // channel was sent. It is safe to unwrap.
                     let waveform = packet.waveform_at(channel_id).unwrap();
 
-                    let pad_position =
-                        TpcPadPosition::try_new(run_number, board_id, after_id, pad_channel_id)?;
+                    let pad_position = match TpcPadPosition::try_new(
+                        run_number,
+                        board_id,
+                        after_id,
+                        pad_channel_id,
+                    ) {
+                        Ok(pad_position) => pad_position,
+                        Err(error) => {
+                            skip(error.into())?;
+                            continue;
+                        }
+                    };
                     let pad_index = (
                         usize::from(pad_position.column),
                         usize::from(pad_position.row),
@@ -344,9 +1305,9 @@ impl MainEvent {
                             position: pad_position,
                         });
                     } else {
-                        let baseline = try_pad_baseline(run_number, pad_position)?;
-                        let gain = try_pad_gain(run_number, pad_position)?;
-                        let delay = try_pad_delay(run_number)?;
+                        let baseline = calibration.pad_baseline(pad_position)?;
+                        let gain = calibration.pad_gain(pad_position)?;
+                        let delay = calibration.pad_delay()?;
 
                         let signal: Vec<_> = waveform
                             .iter()
@@ -363,30 +1324,313 @@ impl MainEvent {
             }
         }
 
-        Ok(Self {
+        let trigger_timestamp =
+            trigger_timestamp.ok_or(TryMainEventFromDataBanksError::MissingTrgBank)?;
+        let misbuilt = MisbuiltEvent::try_new(&alpha16_counters, &padwing_counters);
+        if options.strict {
+            if let Some(misbuilt) = misbuilt {
+                return Err(TryMainEventFromDataBanksError::MisbuiltEvent(misbuilt));
+            }
+        }
+        let event = Self {
             wire_signals,
+            wire_clipped,
+            wire_signal_start,
             pad_signals,
-            trigger_timestamp: trigger_timestamp
-                .ok_or(TryMainEventFromDataBanksError::MissingTrgBank)?,
+            trigger_timestamp,
+            misbuilt,
+            fpn_signals,
+            bv_signals,
+            wire_phi_offset: calibration.wire_phi_offset(),
+            pad_column_offsets: calibration.pad_column_offsets(),
+            mirror_phi: false,
+            mirror_z: false,
+            field: calibration.field(),
+        };
+
+        Ok((event, skipped))
+    }
+    /// Return the boards that disagree on the event counter, or [`None`] if
+    /// the event is consistent.
+    pub fn misbuilt(&self) -> Option<&MisbuiltEvent> {
+        self.misbuilt.as_ref()
+    }
+    /// Return the number of anode wire channels with a signal in the event.
+    pub fn num_wire_signals(&self) -> usize {
+        self.wire_signals.iter().flatten().count()
+    }
+    /// Return the number of cathode pad channels with a signal in the event.
+    pub fn num_pad_signals(&self) -> usize {
+        self.pad_signals.iter().flatten().flatten().count()
+    }
+    /// Return the total charge (in arbitrary units) induced on each cathode
+    /// pad column, i.e. the sum of all the samples of the calibrated pad
+    /// signals in the column (with the sign flipped; pad signals are
+    /// negative).
+    ///
+    /// Unlike the reconstruction, this doesn't need any deconvolution. It is
+    /// cheap enough to e.g. monitor the pad charge of every event in a run.
+    pub fn pad_column_charge(&self) -> [f64; TPC_PAD_COLUMNS] {
+        std::array::from_fn(|column| {
+            -self.pad_signals[column]
+                .iter()
+                .flatten()
+                .flatten()
+                .sum::<f64>()
         })
     }
+    /// Return the wire and pad channels with a waveform shorter than the rest
+    /// of the channels of the same type in the event (see
+    /// [`TruncatedWaveforms`]).
+    pub fn truncated_waveforms(&self) -> TruncatedWaveforms {
+        let wire_length = expected_length(
+            self.wire_signals
+                .iter()
+                .flatten()
+                .map(|signal| signal.len()),
+        );
+        let wires = self
+            .wire_signals
+            .iter()
+            .enumerate()
+            .filter_map(|(index, signal)| {
+                let length = signal.as_ref()?.len();
+                (Some(length) < wire_length).then(|| TpcWirePosition::try_from(index).unwrap())
+            })
+            .collect();
+
+        let pad_length = expected_length(self.pad_signals.iter().flatten().flatten().map(Vec::len));
+        let mut pads = Vec::new();
+        for (column, signals) in self.pad_signals.iter().enumerate() {
+            for (row, signal) in signals.iter().enumerate() {
+                if let Some(signal) = signal {
+                    if Some(signal.len()) < pad_length {
+                        pads.push(TpcPadPosition {
+                            column: column.try_into().unwrap(),
+                            row: row.try_into().unwrap(),
+                        });
+                    }
+                }
+            }
+        }
+
+        let num_channels = self.wire_signals.iter().flatten().count()
+            + self.pad_signals.iter().flatten().flatten().count();
+        TruncatedWaveforms {
+            wires,
+            pads,
+            num_channels,
+        }
+    }
+    /// Return the raw (i.e. not calibrated) waveforms of all the PadWing FPN
+    /// channels in the event, keyed by board, AFTER chip, and FPN channel.
+    ///
+    /// Returns [`None`] unless the event was created with
+    /// [`DecodeOptions::keep_fpn`]. FPN channels are only in the
+    /// data if they were forced on in the DAQ (e.g. for noise diagnostics), so
+    /// the map can be empty.
+    pub fn fpn_signals(&self) -> Option<&FpnSignals> {
+        self.fpn_signals.as_ref()
+    }
+    /// Return the baseline-subtracted waveforms of all the Barrel Veto SiPM
+    /// channels in the event, keyed by board and channel. Samples are at
+    /// [`ADC16_RATE`](alpha16::ADC16_RATE), and the first sample is not
+    /// aligned with the wire or pad signals.
+    ///
+    /// Returns [`None`] unless the event was created with
+    /// [`DecodeOptions::keep_bv`]. Channels that were not read out
+    /// are not in the map. Channels that were read out but have an empty
+    /// waveform (data suppression) map to an empty signal.
+    pub fn bv_signals(&self) -> Option<&BvSignals> {
+        self.bv_signals.as_ref()
+    }
     /// Return the reconstructed primary vertex position.
     ///
     /// This is a convenience method for using [`MainEvent::avalanches`],
-    /// [`cluster_spacepoints`] and [`find_vertices`] with fewer imports and
-    /// without intermediate variables.
+    /// [`cluster_spacepoints`] and
+    /// [`find_vertices`](crate::reconstruction::find_vertices) with fewer
+    /// imports and without intermediate variables. Events that fail to
+    /// reconstruct (see [`MainEvent::try_vertex`]) have no vertex.
     pub fn vertex(&self) -> Option<Coordinate> {
-        let points = self
-            .avalanches()
+        self.try_vertex().ok().flatten()
+    }
+    /// Same as [`MainEvent::vertex`], but return an error if the event is
+    /// degenerate (e.g. non-finite spacepoints or a failed fit) instead of
+    /// treating it as an event without a vertex.
+    ///
+    /// Clusters without initial fit parameters
+    /// ([`TryTrackFromClusterError::NoInitialParameters`]) are common and are
+    /// just ignored.
+    pub fn try_vertex(&self) -> Result<Option<Coordinate>, TryVertexError> {
+        Ok(self.try_vertex_info()?.map(|info| info.position))
+    }
+    /// Same as [`MainEvent::vertex`], but return all the information about
+    /// the primary vertex (e.g. the uncertainty of its position, see
+    /// [`VertexInfo::covariance`]) instead of just its position.
+    pub fn vertex_info(&self) -> Option<VertexInfo> {
+        self.try_vertex_info().ok().flatten()
+    }
+    /// Same as [`MainEvent::try_vertex`], but return all the information
+    /// about the primary vertex (see [`MainEvent::vertex_info`]).
+    pub fn try_vertex_info(&self) -> Result<Option<VertexInfo>, TryVertexError> {
+        let tracks = self
+            .try_tracks()?
             .into_iter()
-            .filter_map(|avalanche| avalanche.try_into().ok())
+            .map(|(_, track)| track)
             .collect();
-        let tracks = cluster_spacepoints(points)
-            .clusters
-            .into_iter()
-            .filter_map(|cluster| cluster.try_into().ok())
+
+        primary_vertex(tracks)
+    }
+    /// Same as [`MainEvent::try_vertex_info`], but also return a
+    /// [`ReconstructionReport`] with the outcome of every stage of the
+    /// reconstruction (e.g. to find out why an event has no vertex). The report
+    /// is filled in along the way, so this is as fast as
+    /// [`MainEvent::try_vertex_info`].
+    pub fn reconstruct_with_report(
+        &self,
+    ) -> (
+        Result<Option<VertexInfo>, TryVertexError>,
+        ReconstructionReport,
+    ) {
+        vertex_info_with_report(self.spacepoints(), self.field)
+    }
+    /// Same as [`MainEvent::reconstruct_with_report`], but the report also
+    /// includes the number of channels with a signal and the number of
+    /// avalanches in the event.
+    pub fn reconstruction_summary(
+        &self,
+    ) -> (
+        Result<Option<VertexInfo>, TryVertexError>,
+        ReconstructionSummary,
+    ) {
+        self.reconstruction_summary_from_avalanches(&self.avalanches())
+    }
+    /// Same as [`MainEvent::reconstruction_summary`], but reuse the output of
+    /// [`MainEvent::avalanches`] (e.g. if the avalanches are also needed for
+    /// something else). Avalanches from a different event give a meaningless
+    /// summary.
+    pub fn reconstruction_summary_from_avalanches(
+        &self,
+        avalanches: &[Avalanche],
+    ) -> (
+        Result<Option<VertexInfo>, TryVertexError>,
+        ReconstructionSummary,
+    ) {
+        let points = avalanches
+            .iter()
+            .filter_map(|&avalanche| avalanche.try_into().ok())
+            .collect();
+        let (result, report) = vertex_info_with_report(points, self.field);
+        let summary = ReconstructionSummary {
+            wire_channels: self.num_wire_signals(),
+            pad_channels: self.num_pad_signals(),
+            avalanches: avalanches.len(),
+            report,
+        };
+
+        (result, summary)
+    }
+    /// Return all the reconstructed tracks in the event. Each [`Track`] is
+    /// paired with the [`Cluster`] of [`SpacePoint`]s it was fitted to.
+    ///
+    /// This is the same set of tracks used by [`MainEvent::vertex`]. Events
+    /// that fail to reconstruct (see [`MainEvent::try_vertex`]) have no
+    /// tracks.
+    ///
+    /// # Examples
+    ///
+    /// The following is equivalent to [`MainEvent::vertex`], but every
+    /// intermediate step is available for inspection:
+    ///
+    /// ```no_run
+    /// use alpha_g_physics::reconstruction::{cluster_spacepoints, find_vertices, Track};
+    /// use alpha_g_physics::{MainEvent, SpacePoint};
+    ///
+    /// fn inspect(event: &MainEvent) {
+    ///     let avalanches = event.avalanches();
+    ///     let spacepoints: Vec<SpacePoint> = avalanches
+    ///         .into_iter()
+    ///         .filter_map(|avalanche| avalanche.try_into().ok())
+    ///         .collect();
+    ///     let clusters = cluster_spacepoints(spacepoints).clusters;
+    ///     let tracks: Vec<Track> = clusters
+    ///         .into_iter()
+    ///         .filter_map(|cluster| Track::try_from(cluster).ok())
+    ///         .collect();
+    ///     for track in &tracks {
+    ///         println!("{:?} ({} points)", track.model(), track.num_points());
+    ///     }
+    ///     let vertices = find_vertices(tracks);
+    ///     println!("{:?}", vertices.primary.map(|info| info.position));
+    /// }
+    /// ```
+    pub fn tracks(&self) -> Vec<(Cluster, Track)> {
+        self.try_tracks().unwrap_or_default()
+    }
+    /// Same as [`MainEvent::tracks`], but reuse the output of
+    /// [`MainEvent::avalanches`] (see
+    /// [`MainEvent::reconstruction_summary_from_avalanches`]).
+    pub fn tracks_from_avalanches(&self, avalanches: &[Avalanche]) -> Vec<(Cluster, Track)> {
+        let points = avalanches
+            .iter()
+            .filter_map(|&avalanche| avalanche.try_into().ok())
             .collect();
-        find_vertices(tracks).primary.map(|info| info.position)
+        try_tracks_with_report(points, self.field, &mut ReconstructionReport::default())
+            .unwrap_or_default()
+    }
+    // Shared by `tracks` and `try_vertex`.
+    fn try_tracks(&self) -> Result<Vec<(Cluster, Track)>, TryVertexError> {
+        try_tracks_with_report(
+            self.spacepoints(),
+            self.field,
+            &mut ReconstructionReport::default(),
+        )
+    }
+    // Avalanches outside the drift tables don't have a spacepoint.
+    fn spacepoints(&self) -> Vec<SpacePoint> {
+        self.avalanches()
+            .into_iter()
+            .filter_map(|avalanche| avalanche.try_into().ok())
+            .collect()
+    }
+    /// Reflect all the avalanches of the event (and everything reconstructed
+    /// from them, e.g. the vertex) with the given [`Mirror`]. Mirroring the
+    /// same coordinate twice restores the original event.
+    ///
+    /// This is only meant as a consistency check of the reconstruction (see
+    /// [`Mirror`]); the signals of the event are not changed.
+    pub fn mirror(&mut self, mirror: Mirror) {
+        match mirror {
+            Mirror::Phi => self.mirror_phi = !self.mirror_phi,
+            Mirror::Z => self.mirror_z = !self.mirror_z,
+        }
+    }
+    /// Emulate a region of interest readout of the TPC. Every wire and pad
+    /// signal is truncated to a window around its first sample with an
+    /// absolute value of at least [`RoiSettings::threshold`]; signals that
+    /// never cross the threshold are dropped altogether. Samples outside the
+    /// window are set to zero (i.e. the baseline) instead of being removed, so
+    /// the timing of the signals is unchanged.
+    ///
+    /// Returns the truncated event (to reconstruct it exactly as the original
+    /// one) together with the number of samples in each of them. The
+    /// barrel veto signals are not changed.
+    pub fn roi_readout(&self, settings: &RoiSettings) -> (Self, RoiReduction) {
+        let mut event = self.clone();
+        let mut reduction = RoiReduction::default();
+        for (signal, clipped) in event
+            .wire_signals
+            .iter_mut()
+            .zip(event.wire_clipped.iter_mut())
+        {
+            let window = roi_truncate(signal, settings, &mut reduction);
+            clipped.retain(|i| window.as_ref().is_some_and(|window| window.contains(i)));
+        }
+        for signal in event.pad_signals.iter_mut().flatten() {
+            roi_truncate(signal, settings, &mut reduction);
+        }
+
+        (event, reduction)
     }
     /// Return the trigger timestamp of the event. This is a counter that
     /// increments at a frequency of [`TRG_CLOCK_FREQ`].
@@ -396,10 +1640,41 @@ impl MainEvent {
         self.trigger_timestamp
     }
     /// Return all reconstructed avalanches in the event.
+    ///
+    /// With the `rayon` feature enabled, pad columns are processed in
+    /// parallel. The output is identical in both cases.
     pub fn avalanches(&self) -> Vec<Avalanche> {
+        let (pad_columns, wire_inputs) = self.deconvolved_wires();
+        let column_avalanches = |column: usize| self.column_avalanches(column, &wire_inputs);
+        // Collecting a parallel iterator preserves the original (sorted) order
+        // of the pad columns. This keeps the output deterministic.
+        #[cfg(feature = "rayon")]
+        let per_column = {
+            use rayon::prelude::*;
+            pad_columns
+                .into_par_iter()
+                .map(column_avalanches)
+                .collect::<Vec<_>>()
+        };
+        #[cfg(not(feature = "rayon"))]
+        let per_column = pad_columns.into_iter().map(column_avalanches);
+
+        let mirrors = [(self.mirror_phi, Mirror::Phi), (self.mirror_z, Mirror::Z)];
+        per_column
+            .into_iter()
+            .flatten()
+            .map(|avalanche| {
+                mirrors
+                    .iter()
+                    .filter(|(enabled, _)| *enabled)
+                    .fold(avalanche, |avalanche, (_, mirror)| mirror.apply(avalanche))
+            })
+            .collect()
+    }
+    // Return the pad columns that need to be deconvolved (in a deterministic
+    // order), together with the deconvolved wire inputs.
+    fn deconvolved_wires(&self) -> (BTreeSet<usize>, [Vec<f64>; TPC_ANODE_WIRES]) {
         // We would only want to deconvolve pad columns that have wire signals.
-        // Furthermore, to make the output deterministic, we need to iterate
-        // over the pad columns in a deterministic order.
         let mut pad_columns = BTreeSet::new();
         // Deconvolution of wires needs to be done in chunks of contiguous wires.
         let mut wire_inputs = [(); TPC_ANODE_WIRES].map(|_| Vec::new());
@@ -410,24 +1685,332 @@ impl MainEvent {
             }
         }
 
-        let mut avalanches = Vec::new();
-        for column in pad_columns {
-            let mut pad_inputs_column = [(); TPC_PAD_ROWS].map(|_| Vec::new());
-            for (row, input) in pad_inputs_column.iter_mut().enumerate() {
-                if let Some(signal) = self.pad_signals[column][row].as_ref() {
-                    *input = pad_deconvolution(signal);
-                }
+        (pad_columns, wire_inputs)
+    }
+    // Avalanches from a single pad column. Columns are independent of each
+    // other.
+    fn column_avalanches(
+        &self,
+        column: usize,
+        wire_inputs: &[Vec<f64>; TPC_ANODE_WIRES],
+    ) -> Vec<Avalanche> {
+        self.column_matches(column, wire_inputs).0.avalanches
+    }
+    // Same as `column_avalanches`, but also return the matched inputs and the
+    // deconvolved pad inputs of the column.
+    fn column_matches(
+        &self,
+        column: usize,
+        wire_inputs: &[Vec<f64>; TPC_ANODE_WIRES],
+    ) -> (ColumnMatches, [Vec<f64>; TPC_PAD_ROWS]) {
+        let mut pad_inputs_column = [(); TPC_PAD_ROWS].map(|_| Vec::new());
+        for (row, input) in pad_inputs_column.iter_mut().enumerate() {
+            if let Some(signal) = self.pad_signals[column][row].as_ref() {
+                *input = pad_deconvolution(signal);
             }
+        }
 
+        let wire_indices = pad_column_to_wires(column);
+        let wire_saturated = wire_indices
+            .clone()
+            .map(|i| saturated_inputs(&self.wire_clipped[i], wire_inputs[i].len()))
+            .collect::<Vec<_>>();
+        let matches = match_column_inputs(
+            wire_indices.clone().collect::<Vec<_>>().try_into().unwrap(),
+            wire_inputs[wire_indices].try_into().unwrap(),
+            &wire_saturated.try_into().unwrap(),
+            &pad_inputs_column,
+            self.wire_phi_offset,
+            self.pad_column_offsets[column],
+        );
+
+        (matches, pad_inputs_column)
+    }
+    /// Return the calibrated signal of an anode wire together with its
+    /// deconvolved spikes, or [`None`] if the wire has no signal in the event.
+    ///
+    /// This is meant for diagnostics (e.g. to debug the deconvolution); every
+    /// call deconvolves all the wire signals in the event.
+    pub fn wire_deconvolution(&self, position: TpcWirePosition) -> Option<DeconvolvedSignal> {
+        let index = usize::from(position);
+        let signal = self.wire_signals[index].clone()?;
+        let (_, wire_inputs) = self.deconvolved_wires();
+        let (matches, _) = self.column_matches(wire_to_pad_column(index), &wire_inputs);
+        let spikes = spikes(&wire_inputs[index], alpha16::ADC32_RATE, |sample| {
+            matches.wire_inputs.contains(&(index, sample))
+        });
+
+        Some(DeconvolvedSignal { signal, spikes })
+    }
+    /// Same as [`MainEvent::wire_deconvolution`], but for a cathode pad.
+    pub fn pad_deconvolution(&self, position: TpcPadPosition) -> Option<DeconvolvedSignal> {
+        let (column, row) = (usize::from(position.column), usize::from(position.row));
+        let signal = self.pad_signals[column][row].clone()?;
+        let (_, wire_inputs) = self.deconvolved_wires();
+        let (matches, pad_inputs) = self.column_matches(column, &wire_inputs);
+        let spikes = spikes(&pad_inputs[row], padwing::PWB_RATE, |sample| {
+            matches.pad_inputs.contains(&(row, sample))
+        });
+
+        Some(DeconvolvedSignal { signal, spikes })
+    }
+    /// Return the calibrated signals and deconvolved spikes of all the anode
+    /// wires and cathode pads that have a signal in the event.
+    ///
+    /// This is the same as calling [`MainEvent::wire_deconvolution`] and
+    /// [`MainEvent::pad_deconvolution`] for every channel, but the event is
+    /// only deconvolved once.
+    pub fn deconvolution(&self) -> EventDeconvolution {
+        let (_, wire_inputs) = self.deconvolved_wires();
+        let mut wires = Vec::new();
+        let mut pads = Vec::new();
+        for column in 0..TPC_PAD_COLUMNS {
             let wire_indices = pad_column_to_wires(column);
-            avalanches.extend(match_column_inputs(
-                wire_indices.clone().collect::<Vec<_>>().try_into().unwrap(),
-                wire_inputs[wire_indices].try_into().unwrap(),
-                &pad_inputs_column,
-            ));
+            if wire_indices.clone().all(|i| self.wire_signals[i].is_none())
+                && self.pad_signals[column].iter().all(Option::is_none)
+            {
+                continue;
+            }
+
+            let (matches, pad_inputs) = self.column_matches(column, &wire_inputs);
+            for index in wire_indices {
+                let Some(signal) = self.wire_signals[index].clone() else {
+                    continue;
+                };
+                let spikes = spikes(&wire_inputs[index], alpha16::ADC32_RATE, |sample| {
+                    matches.wire_inputs.contains(&(index, sample))
+                });
+                wires.push((
+                    TpcWirePosition::try_from(index).unwrap(),
+                    DeconvolvedSignal { signal, spikes },
+                ));
+            }
+            for (row, signal) in self.pad_signals[column].iter().enumerate() {
+                let Some(signal) = signal.clone() else {
+                    continue;
+                };
+                let spikes = spikes(&pad_inputs[row], padwing::PWB_RATE, |sample| {
+                    matches.pad_inputs.contains(&(row, sample))
+                });
+                let position = TpcPadPosition {
+                    column: column.try_into().unwrap(),
+                    row: row.try_into().unwrap(),
+                };
+                pads.push((position, DeconvolvedSignal { signal, spikes }));
+            }
         }
+        wires.sort_unstable_by_key(|&(position, _)| usize::from(position));
 
-        avalanches
+        EventDeconvolution { wires, pads }
+    }
+    /// Return the earliest avalanche time, the latest avalanche time, and the
+    /// fraction of avalanches that arrive late in the drift window.
+    ///
+    /// The earliest and latest times are relative to the trigger, i.e. the
+    /// [`Avalanche::t`] plus the time of the samples dropped by the wire delay
+    /// calibration (see [`wire_delay`](crate::calibration::wire_delay)).
+    ///
+    /// The late fraction is defined as the number of avalanches with a drift
+    /// time (i.e. [`Avalanche::t`]) `t >= 0.9 * t_max`, divided by the total
+    /// number of avalanches; where `t_max` is the
+    /// [`max_drift_time`](crate::detector_response::max_drift_time) (i.e. the
+    /// drift time of an ionization at the inner cathode). All values are
+    /// [`None`] if there are no avalanches in the event.
+    pub fn drift_window_stats(&self) -> (Option<Time>, Option<Time>, Option<f64>) {
+        self.drift_window_stats_from_avalanches(&self.avalanches())
+    }
+    /// Same as [`MainEvent::drift_window_stats`], but reuse the output of
+    /// [`MainEvent::avalanches`] (e.g. if the avalanches are also needed to
+    /// reconstruct the vertex). Avalanches from a different event give
+    /// meaningless statistics.
+    pub fn drift_window_stats_from_avalanches(
+        &self,
+        avalanches: &[Avalanche],
+    ) -> (Option<Time>, Option<Time>, Option<f64>) {
+        let times: Vec<_> = avalanches.iter().map(|a| a.t).collect();
+        drift_window_stats(
+            &times,
+            max_drift_time(FieldConfiguration::On),
+            self.wire_signal_start,
+        )
+    }
+}
+
+// Avalanches in the last 10% of the drift window are considered late.
+const LATE_DRIFT_FRACTION: f64 = 0.9;
+
+// The `times` are drift times (relative to the start of the wire signals), and
+// the earliest and latest times are returned relative to the trigger.
+fn drift_window_stats(
+    times: &[Time],
+    max_drift_time: Time,
+    wire_signal_start: Time,
+) -> (Option<Time>, Option<Time>, Option<f64>) {
+    if times.is_empty() {
+        return (None, None, None);
+    }
+
+    let t_min = times
+        .iter()
+        .copied()
+        .reduce(|a, b| if b < a { b } else { a });
+    let t_max = times
+        .iter()
+        .copied()
+        .reduce(|a, b| if b > a { b } else { a });
+    let late = times
+        .iter()
+        .filter(|&&t| t >= max_drift_time * LATE_DRIFT_FRACTION)
+        .count();
+
+    (
+        t_min.map(|t| t + wire_signal_start),
+        t_max.map(|t| t + wire_signal_start),
+        Some(late as f64 / times.len() as f64),
+    )
+}
+
+// Window of samples read out around the first threshold crossing of a signal.
+fn roi_window(signal: &[f64], settings: &RoiSettings) -> Option<Range<usize>> {
+    let crossing = signal
+        .iter()
+        .position(|sample| sample.abs() >= settings.threshold)?;
+    let start = crossing.saturating_sub(settings.pre_samples);
+    let end = crossing
+        .saturating_add(settings.post_samples)
+        .min(signal.len());
+
+    Some(start..end)
+}
+
+// Zero all the samples of a signal outside of its ROI window (or drop the
+// signal if it never crosses the threshold), and return the window.
+fn roi_truncate(
+    signal: &mut Option<Vec<f64>>,
+    settings: &RoiSettings,
+    reduction: &mut RoiReduction,
+) -> Option<Range<usize>> {
+    let samples = signal.as_mut()?;
+    reduction.full_samples += samples.len();
+    let Some(window) = roi_window(samples, settings) else {
+        *signal = None;
+        return None;
+    };
+    reduction.roi_samples += window.len();
+    for (i, sample) in samples.iter_mut().enumerate() {
+        if !window.contains(&i) {
+            *sample = 0.0;
+        }
+    }
+
+    Some(window)
+}
+
+/// Same as [`MainEvent::try_vertex_info`], but starting from the
+/// [`SpacePoint`]s of an event. This is useful to reconstruct the vertex and
+/// still keep the [`Avalanche`]s and [`SpacePoint`]s (e.g. to write them to a
+/// file) without reconstructing the avalanches twice.
+///
+/// Tracks are fitted to helices (i.e. [`FieldConfiguration::On`]).
+pub fn try_vertex_info_from_spacepoints(
+    points: Vec<SpacePoint>,
+) -> Result<Option<VertexInfo>, TryVertexError> {
+    vertex_info_with_report(points, FieldConfiguration::On).0
+}
+
+/// Same as [`try_vertex_info_from_spacepoints`], but also return a
+/// [`ReconstructionReport`] (see [`MainEvent::reconstruct_with_report`]).
+pub fn try_vertex_info_from_spacepoints_with_report(
+    points: Vec<SpacePoint>,
+) -> (
+    Result<Option<VertexInfo>, TryVertexError>,
+    ReconstructionReport,
+) {
+    vertex_info_with_report(points, FieldConfiguration::On)
+}
+
+fn vertex_info_with_report(
+    points: Vec<SpacePoint>,
+    field: FieldConfiguration,
+) -> (
+    Result<Option<VertexInfo>, TryVertexError>,
+    ReconstructionReport,
+) {
+    let mut report = ReconstructionReport::default();
+    let result = try_tracks_with_report(points, field, &mut report).and_then(|tracks| {
+        let tracks = tracks.into_iter().map(|(_, track)| track).collect();
+        primary_vertex_with_report(tracks, &mut report)
+    });
+
+    (result, report)
+}
+
+// All the reconstruction paths go through here (and through
+// `primary_vertex_with_report`), so the report always matches the outcome.
+fn try_tracks_with_report(
+    points: Vec<SpacePoint>,
+    field: FieldConfiguration,
+    report: &mut ReconstructionReport,
+) -> Result<Vec<(Cluster, Track)>, TryVertexError> {
+    report.num_spacepoints = points.len();
+    let non_finite = points
+        .iter()
+        .filter(|p| !(p.r.is_finite() && p.phi.is_finite() && p.z.is_finite()))
+        .count();
+    report.non_finite_spacepoints = non_finite;
+    if non_finite > 0 {
+        return Err(TryVertexError::NonFiniteSpacePoints { count: non_finite });
+    }
+
+    let clustering = cluster_spacepoints(points);
+    report.cluster_sizes = clustering
+        .clusters
+        .iter()
+        .map(|cluster| cluster.points().len())
+        .collect();
+    report.num_unclustered = clustering.remainder.len();
+    let mut tracks = Vec::new();
+    for cluster in clustering.clusters {
+        match Track::try_from_cluster(cluster.clone(), field) {
+            Ok(track) => {
+                report.track_fits.push(TrackFit::Fitted);
+                tracks.push((cluster, track));
+            }
+            Err(error) => {
+                report.track_fits.push(TrackFit::from(&error));
+                if !matches!(error, TryTrackFromClusterError::NoInitialParameters) {
+                    return Err(error.into());
+                }
+            }
+        }
+    }
+
+    Ok(tracks)
+}
+
+fn primary_vertex(tracks: Vec<Track>) -> Result<Option<VertexInfo>, TryVertexError> {
+    primary_vertex_with_report(tracks, &mut ReconstructionReport::default())
+}
+
+fn primary_vertex_with_report(
+    tracks: Vec<Track>,
+    report: &mut ReconstructionReport,
+) -> Result<Option<VertexInfo>, TryVertexError> {
+    let num_tracks = tracks.len();
+    match try_find_vertices_with_settings(tracks, &ReconstructionSettings::default()) {
+        Ok(result) => {
+            report.vertex = match &result.primary {
+                Some(info) => VertexStatus::Converged {
+                    num_tracks: info.tracks.len(),
+                },
+                None => VertexStatus::InsufficientTracks { num_tracks },
+            };
+            Ok(result.primary)
+        }
+        Err(error) => {
+            report.vertex = VertexStatus::Diverged;
+            Err(error.into())
+        }
     }
 }
 
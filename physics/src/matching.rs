@@ -40,22 +40,30 @@ pub(crate) fn pad_column_to_wires(pad_column: usize) -> Range<usize> {
 
 #[derive(Clone, Copy, Debug)]
 struct WireHit {
+    wire: usize,
     phi: Angle,
     amplitude: f64,
+    saturated: bool,
 }
 
 fn wire_hits_at_t(
     wire_indices: [usize; WIRES_PER_COLUMN],
     wire_inputs: &[Vec<f64>; WIRES_PER_COLUMN],
+    wire_saturated: &[Vec<bool>; WIRES_PER_COLUMN],
+    phi_offset: Angle,
     t: usize,
 ) -> Vec<WireHit> {
     wire_indices
         .iter()
         .zip(wire_inputs)
-        .filter_map(|(index, input)| {
+        .zip(wire_saturated)
+        .filter_map(|((index, input), saturated)| {
             input.get(t).copied().filter(|v| v > &0.0).map(|v| WireHit {
-                phi: Angle::new::<radian>(TpcWirePosition::try_from(*index).unwrap().phi()),
+                wire: *index,
+                phi: Angle::new::<radian>(TpcWirePosition::try_from(*index).unwrap().phi())
+                    + phi_offset,
                 amplitude: v,
+                saturated: saturated.get(t).copied().unwrap_or(false),
             })
         })
         .collect()
@@ -63,6 +71,7 @@ fn wire_hits_at_t(
 
 #[derive(Clone, Copy, Debug)]
 struct PadHit {
+    row: usize,
     z: Length,
     amplitude: f64,
 }
@@ -83,7 +92,11 @@ fn pad_hits_at_t(pad_column_inputs: &[Vec<f64>; TPC_PAD_ROWS], t: usize) -> Vec<
                 + (sigma_squared / (2.0 * width)) * (last / first).ln();
 
             let amplitude = middle;
-            pad_hits.push(PadHit { z, amplitude });
+            pad_hits.push(PadHit {
+                row: row - 1,
+                z,
+                amplitude,
+            });
         }
 
         first = middle;
@@ -93,43 +106,75 @@ fn pad_hits_at_t(pad_column_inputs: &[Vec<f64>; TPC_PAD_ROWS], t: usize) -> Vec<
     pad_hits
 }
 
+// Avalanches from a pad column, together with the wire and pad inputs that
+// were matched to produce them.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct ColumnMatches {
+    pub(crate) avalanches: Vec<Avalanche>,
+    // `(wire index, sample)` of each matched wire input.
+    pub(crate) wire_inputs: Vec<(usize, usize)>,
+    // `(pad row, sample)` of each matched pad input. The sample is in the pad
+    // time frame i.e. before applying the pad offset.
+    pub(crate) pad_inputs: Vec<(usize, usize)>,
+}
+
 // Match the inputs from all the wires in a pad column to the input from the
-// pad column to reconstruct avalanches.
+// pad column to reconstruct avalanches. The matched inputs are also returned
+// (e.g. for diagnostics).
+//
+// The `phi_offset` (alignment of the wire plane relative to the pad plane) is
+// added to the nominal position of all wires. It only shifts the `phi` of the
+// avalanches; it doesn't change which wire and pad hits are matched together.
+//
+// The `wire_saturated` flags (same shape as `wire_inputs`) are just forwarded
+// to the avalanches. Saturated wire hits are matched exactly as any other hit.
+//
+// The `pad_offset` (in samples) is the timing skew of this pad column relative
+// to the wires. A pad hit at sample `t` is compared with the wire hits at
+// sample `t + pad_offset`. An offset of 0 means no correction.
 pub(crate) fn match_column_inputs(
     wire_indices: [usize; WIRES_PER_COLUMN],
     wire_inputs: &[Vec<f64>; WIRES_PER_COLUMN],
+    wire_saturated: &[Vec<bool>; WIRES_PER_COLUMN],
     pad_column_inputs: &[Vec<f64>; TPC_PAD_ROWS],
-) -> Vec<Avalanche> {
+    phi_offset: Angle,
+    pad_offset: i32,
+) -> ColumnMatches {
     let t_max = wire_inputs.iter().map(|input| input.len()).max().unwrap();
 
-    let mut avalanches = Vec::new();
+    let mut matches = ColumnMatches::default();
     for t in 0..t_max {
-        let mut wire_hits = wire_hits_at_t(wire_indices, wire_inputs, t);
+        let mut wire_hits =
+            wire_hits_at_t(wire_indices, wire_inputs, wire_saturated, phi_offset, t);
         if wire_hits.is_empty() {
             continue;
         }
-        let mut pad_hits = pad_hits_at_t(pad_column_inputs, t);
+        // Pad hits before the beginning of the pad signals (i.e. negative
+        // sample) are just empty.
+        let pad_t = usize::try_from(t as i64 - i64::from(pad_offset)).ok();
+        let mut pad_hits = pad_t.map_or_else(Vec::new, |t| pad_hits_at_t(pad_column_inputs, t));
         // Sort by amplitude (descending order) before matching. This matches
         // together largest avalanches first and tries to fix the ghosting
         // problem by taking into account the avalanches amplitudes.
         wire_hits.sort_unstable_by(|a, b| b.amplitude.partial_cmp(&a.amplitude).unwrap());
         pad_hits.sort_unstable_by(|a, b| b.amplitude.partial_cmp(&a.amplitude).unwrap());
 
-        avalanches.extend(
-            wire_hits
-                .into_iter()
-                .zip(pad_hits)
-                .map(|(wire_hit, pad_hit)| Avalanche {
-                    t: Time::new::<second>(t as f64 / ADC32_RATE),
-                    phi: wire_hit.phi,
-                    z: pad_hit.z,
-                    wire_amplitude: wire_hit.amplitude,
-                    pad_amplitude: pad_hit.amplitude,
-                }),
-        );
+        for (wire_hit, pad_hit) in wire_hits.into_iter().zip(pad_hits) {
+            matches.avalanches.push(Avalanche {
+                t: Time::new::<second>(t as f64 / ADC32_RATE),
+                phi: wire_hit.phi,
+                z: pad_hit.z,
+                wire_amplitude: wire_hit.amplitude,
+                pad_amplitude: pad_hit.amplitude,
+                saturated: wire_hit.saturated,
+            });
+            matches.wire_inputs.push((wire_hit.wire, t));
+            // There are no pad hits without a pad sample.
+            matches.pad_inputs.push((pad_hit.row, pad_t.unwrap()));
+        }
     }
 
-    avalanches
+    matches
 }
 
 #[cfg(test)]
@@ -1,5 +1,5 @@
 use super::*;
-use alpha_g_detector::padwing::map::TpcPadColumn;
+use alpha_g_detector::padwing::map::{TpcPadColumn, TpcPadPosition, PAD_PITCH_PHI};
 use std::collections::HashSet;
 use std::f64::consts::PI;
 
@@ -37,3 +37,181 @@ fn pad_column_index_to_wire_indices() {
 
     assert_eq!(seen.len(), TPC_ANODE_WIRES);
 }
+
+// Single wire hit at sample `5` (first wire of pad column 0), and a single pad
+// hit in row 3 at sample `pad_t`.
+fn single_hit_inputs(
+    pad_t: usize,
+) -> (
+    [usize; WIRES_PER_COLUMN],
+    [Vec<f64>; WIRES_PER_COLUMN],
+    [Vec<f64>; TPC_PAD_ROWS],
+) {
+    let wire_indices = pad_column_to_wires(0)
+        .collect::<Vec<_>>()
+        .try_into()
+        .unwrap();
+    let wire_inputs = std::array::from_fn(|i| {
+        let mut input = vec![0.0; 10];
+        if i == 0 {
+            input[5] = 10.0;
+        }
+        input
+    });
+    let pad_inputs = std::array::from_fn(|row| {
+        let mut input = vec![0.0; 10];
+        input[pad_t] = match row {
+            2 | 4 => 1.0,
+            3 => 2.0,
+            _ => 0.0,
+        };
+        input
+    });
+
+    (wire_indices, wire_inputs, pad_inputs)
+}
+
+fn expected_avalanche(wire_index: usize) -> Avalanche {
+    Avalanche {
+        t: Time::new::<second>(5.0 / ADC32_RATE),
+        phi: Angle::new::<radian>(TpcWirePosition::try_from(wire_index).unwrap().phi()),
+        z: Length::new::<meter>(TpcPadRow::try_from(3).unwrap().z()),
+        wire_amplitude: 10.0,
+        pad_amplitude: 2.0,
+        saturated: false,
+    }
+}
+
+// An offset of 0 has to match wire and pad hits at exactly the same sample
+// (i.e. the behavior before per column offsets were introduced).
+#[test]
+fn match_column_inputs_zero_pad_offset() {
+    let no_saturation = std::array::from_fn(|_| Vec::new());
+    let (wire_indices, wire_inputs, pad_inputs) = single_hit_inputs(5);
+
+    let avalanches = match_column_inputs(
+        wire_indices,
+        &wire_inputs,
+        &no_saturation,
+        &pad_inputs,
+        Angle::new::<radian>(0.0),
+        0,
+    )
+    .avalanches;
+    assert_eq!(avalanches, [expected_avalanche(wire_indices[0])]);
+
+    let (wire_indices, wire_inputs, pad_inputs) = single_hit_inputs(4);
+    let avalanches = match_column_inputs(
+        wire_indices,
+        &wire_inputs,
+        &no_saturation,
+        &pad_inputs,
+        Angle::new::<radian>(0.0),
+        0,
+    )
+    .avalanches;
+    assert!(avalanches.is_empty());
+}
+
+#[test]
+fn match_column_inputs_pad_offset() {
+    let no_saturation = std::array::from_fn(|_| Vec::new());
+    for (pad_t, pad_offset) in [(3, 2), (7, -2), (0, 5)] {
+        let (wire_indices, wire_inputs, pad_inputs) = single_hit_inputs(pad_t);
+
+        let avalanches = match_column_inputs(
+            wire_indices,
+            &wire_inputs,
+            &no_saturation,
+            &pad_inputs,
+            Angle::new::<radian>(0.0),
+            pad_offset,
+        )
+        .avalanches;
+        assert_eq!(avalanches, [expected_avalanche(wire_indices[0])]);
+    }
+}
+
+#[test]
+fn match_column_inputs_pad_offset_out_of_range() {
+    let no_saturation = std::array::from_fn(|_| Vec::new());
+    let (wire_indices, wire_inputs, pad_inputs) = single_hit_inputs(5);
+
+    for pad_offset in [6, 100, -100] {
+        let avalanches = match_column_inputs(
+            wire_indices,
+            &wire_inputs,
+            &no_saturation,
+            &pad_inputs,
+            Angle::new::<radian>(0.0),
+            pad_offset,
+        )
+        .avalanches;
+        assert!(avalanches.is_empty());
+    }
+}
+
+#[test]
+fn match_column_inputs_matched_inputs() {
+    let no_saturation = std::array::from_fn(|_| Vec::new());
+    let (wire_indices, wire_inputs, pad_inputs) = single_hit_inputs(3);
+
+    let matches = match_column_inputs(
+        wire_indices,
+        &wire_inputs,
+        &no_saturation,
+        &pad_inputs,
+        Angle::new::<radian>(0.0),
+        2,
+    );
+    assert_eq!(matches.wire_inputs, [(wire_indices[0], 5)]);
+    // The pad hit is at the middle row, in the pad time frame.
+    assert_eq!(matches.pad_inputs, [(3, 3)]);
+}
+
+// Avalanches have to be exactly where `alpha_g_detector` says the wires and
+// pads are (without any calibration offset).
+#[test]
+fn match_column_inputs_detector_positions() {
+    let no_saturation = std::array::from_fn(|_| Vec::new());
+    for column in 0..TPC_PAD_COLUMNS {
+        let wire_indices: [usize; WIRES_PER_COLUMN] = pad_column_to_wires(column)
+            .collect::<Vec<_>>()
+            .try_into()
+            .unwrap();
+        for (i, &wire_index) in wire_indices.iter().enumerate() {
+            // Sweep through all rows (except the first and last; they never
+            // have neighbors on both sides).
+            let row = (column * WIRES_PER_COLUMN + i) * 7 % (TPC_PAD_ROWS - 2) + 1;
+            let wire_inputs = std::array::from_fn(|j| if j == i { vec![10.0] } else { vec![0.0] });
+            let pad_inputs = std::array::from_fn(|r| match r.abs_diff(row) {
+                0 => vec![2.0],
+                1 => vec![1.0],
+                _ => vec![0.0],
+            });
+
+            let avalanches = match_column_inputs(
+                wire_indices,
+                &wire_inputs,
+                &no_saturation,
+                &pad_inputs,
+                Angle::new::<radian>(0.0),
+                0,
+            )
+            .avalanches;
+            assert_eq!(avalanches.len(), 1);
+
+            let wire = TpcWirePosition::try_from(wire_index).unwrap();
+            let pad = TpcPadPosition {
+                column: TpcPadColumn::try_from(column).unwrap(),
+                row: TpcPadRow::try_from(row).unwrap(),
+            };
+            assert_eq!(avalanches[0].phi.get::<radian>(), wire.phi());
+            assert!((avalanches[0].z.get::<meter>() - pad.z()).abs() < 1e-12);
+            // The wire is within the pad column.
+            let angle = (wire.phi() - pad.phi()).abs();
+            let angle = if angle > PI { 2.0 * PI - angle } else { angle };
+            assert!(angle < 0.5 * PAD_PITCH_PHI);
+        }
+    }
+}
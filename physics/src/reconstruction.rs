@@ -1,9 +1,13 @@
+use crate::geometry::Cylinder;
 use crate::SpacePoint;
 use core::slice::Iter;
 use std::f64::consts::PI;
 use thiserror::Error;
 use uom::si::angle::radian;
-use uom::si::f64::{Angle, Length, Ratio};
+use uom::si::electric_charge::coulomb;
+use uom::si::f64::{
+    Angle, Area, ElectricCharge, Length, MagneticFluxDensity, Momentum, Ratio, ReciprocalLength,
+};
 use uom::si::length::{centimeter, meter};
 use uom::si::ratio::ratio;
 use uom::typenum::P2;
@@ -20,13 +24,26 @@ mod vertex_fitting;
 /// A [`Cluster`] represents a group of [`SpacePoint`]s that are potentially
 /// part of the same track.
 #[derive(Clone, Debug)]
-pub struct Cluster(Vec<SpacePoint>);
+// The second field is the index of each SpacePoint in the input of
+// `cluster_spacepoints`.
+pub struct Cluster(Vec<SpacePoint>, Vec<usize>);
 
 impl Cluster {
     /// Return an iterator over the [`SpacePoint`]s.
     pub fn iter(&self) -> Iter<'_, SpacePoint> {
         self.0.iter()
     }
+
+    /// Return the [`SpacePoint`]s in the cluster.
+    pub fn points(&self) -> &[SpacePoint] {
+        &self.0
+    }
+
+    /// Return the index of each [`SpacePoint`] (in the same order as
+    /// [`Cluster::iter`]) in the input given to [`cluster_spacepoints`].
+    pub fn indices(&self) -> &[usize] {
+        &self.1
+    }
 }
 
 impl<'a> IntoIterator for &'a Cluster {
@@ -48,37 +65,98 @@ impl IntoIterator for Cluster {
 }
 
 /// Result of clustering [`SpacePoint`]s.
+///
+/// Every input [`SpacePoint`] ends up in exactly one place: either in one of
+/// the `clusters` or in the `remainder` (i.e. noise). All the members of the
+/// `clusters` together with the `remainder` are a permutation of the input.
 #[derive(Clone, Debug)]
 pub struct ClusteringResult {
     /// Each [`Cluster`] represents a group of [`SpacePoint`]s that are
     /// potentially part of the same track.
     pub clusters: Vec<Cluster>,
     /// Remaining [`SpacePoint`]s that have not been identified as part of a
-    /// [`Cluster`].
+    /// [`Cluster`]. These are in the same relative order as in the input.
     pub remainder: Vec<SpacePoint>,
+    /// Index of each `remainder` [`SpacePoint`] in the input.
+    pub remainder_indices: Vec<usize>,
+}
+
+/// Parameters used to cluster [`SpacePoint`]s (see
+/// [`cluster_spacepoints_with`]).
+///
+/// The [`Default`] options are the ones used by [`cluster_spacepoints`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ClusteringOptions {
+    /// Minimum number of [`SpacePoint`]s in a [`Cluster`]. Smaller groups are
+    /// left in the remainder. Fitting a [`Track`] needs at least 3 points to
+    /// get an initial guess of the helix, so it always fails for clusters
+    /// smaller than that.
+    pub min_points: usize,
+    /// Number of bins along `rho` in Hough space. The bins evenly split the
+    /// range from 0 to the largest possible `rho` of a point in the TPC.
+    pub rho_bins: u32,
+    /// Number of bins along `theta` in Hough space. Each bin covers an angle
+    /// of `2 * PI / theta_bins`.
+    pub theta_bins: u32,
+    /// Maximum (Euclidean) distance between a point and its nearest neighbor in
+    /// the same [`Cluster`]. A track with a larger gap between consecutive
+    /// points is split into separate clusters.
+    pub max_gap: Length,
+}
+
+impl Default for ClusteringOptions {
+    fn default() -> Self {
+        Self {
+            min_points: 13,
+            rho_bins: 250,
+            theta_bins: 230,
+            max_gap: Length::new::<centimeter>(3.0),
+        }
+    }
 }
 
 /// Given a collection of [`SpacePoint`]s, cluster them into groups that
 /// are potentially part of the same track.
+///
+/// This is the same as [`cluster_spacepoints_with`] with the default
+/// [`ClusteringOptions`].
 pub fn cluster_spacepoints(sp: Vec<SpacePoint>) -> ClusteringResult {
+    cluster_spacepoints_with(sp, &ClusteringOptions::default())
+}
+
+/// Given a collection of [`SpacePoint`]s, cluster them into groups that
+/// are potentially part of the same track using the given
+/// [`ClusteringOptions`].
+///
+/// Points are mapped into the conformal `u-v` plane, where tracks that come
+/// from close to the origin are straight lines. These lines are found with a
+/// Hough transform, and the points voting for the most popular bin are split
+/// into groups in which every point is at most `max_gap` away from another
+/// point of the same group. The largest group is taken as a [`Cluster`] if it
+/// has at least `min_points` points, its points are removed from the Hough
+/// accumulator, and the process is repeated.
+///
+/// The result is deterministic i.e. the same input (including its order)
+/// always produces the same clusters in the same order. Identical input
+/// points are interchangeable, and are assigned indices in input order.
+///
+/// Points with a non-finite coordinate or at `r = 0` are never clustered;
+/// they are always part of the remainder.
+pub fn cluster_spacepoints_with(
+    sp: Vec<SpacePoint>,
+    options: &ClusteringOptions,
+) -> ClusteringResult {
     track_finding::cluster_spacepoints(
         sp,
-        // Minimum number of SpacePoints per Cluster.
-        // We need at least 3 points to get an accurate initial guess for the
-        // helix through a cluster.
-        // Track fitting will panic if this is set to less than 3.
-        13,
-        // Number of bins along `rho` in Hough space.
-        250,
-        // Number of bins along `theta` in Hough space.
-        230,
-        // Maximum clustering distance in Euclidean space.
-        Length::new::<centimeter>(3.0),
+        options.min_points,
+        options.rho_bins,
+        options.theta_bins,
+        options.max_gap,
     )
 }
 
 /// A point in 3D space.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Coordinate {
     pub x: Length,
     pub y: Length,
@@ -228,6 +306,158 @@ impl Helix {
     }
 }
 
+// Straight line with parametric equation:
+//     x = ux * t + x0
+//     y = uy * t + y0
+//     z = uz * t + z0
+//
+// Where (ux, uy, uz) is a unit vector. Then `t` is the (signed) distance in
+// METERS from (x0, y0, z0).
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Line {
+    point: Coordinate,
+    direction: [f64; 3],
+}
+
+impl Line {
+    fn at(&self, t: f64) -> Coordinate {
+        let t = Length::new::<meter>(t);
+
+        Coordinate {
+            x: self.point.x + self.direction[0] * t,
+            y: self.point.y + self.direction[1] * t,
+            z: self.point.z + self.direction[2] * t,
+        }
+    }
+    // Given a SpacePoint, return the value of t that corresponds to the
+    // closest point on the line (i.e. the projection onto the direction).
+    fn closest_t(&self, p: SpacePoint) -> f64 {
+        let dx = (p.x() - self.point.x).get::<meter>();
+        let dy = (p.y() - self.point.y).get::<meter>();
+        let dz = (p.z - self.point.z).get::<meter>();
+
+        dx * self.direction[0] + dy * self.direction[1] + dz * self.direction[2]
+    }
+    // Return the coordinate of the closest point on the line to the beamline.
+    fn closest_to_beamline(&self) -> Coordinate {
+        let [ux, uy, _] = self.direction;
+        let transverse = ux.powi(2) + uy.powi(2);
+        // A line parallel to the beamline is equally close everywhere.
+        if transverse < f64::EPSILON {
+            return self.point;
+        }
+        let t = -(self.point.x.get::<meter>() * ux + self.point.y.get::<meter>() * uy) / transverse;
+
+        self.at(t)
+    }
+}
+
+// Internal model of a track. Each variant implements the same operations
+// needed by track and vertex fitting.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Model {
+    Helix(Helix),
+    Line(Line),
+}
+
+impl Model {
+    fn at(&self, t: f64) -> Coordinate {
+        match self {
+            Self::Helix(helix) => helix.at(t),
+            Self::Line(line) => line.at(t),
+        }
+    }
+    // See `Helix::closest_t` for details on the `tolerance` and
+    // `max_num_iter` parameters. These are ignored by a straight line (which
+    // has an exact solution).
+    fn closest_t(&self, p: SpacePoint, tolerance: f64, max_num_iter: usize) -> f64 {
+        match self {
+            Self::Helix(helix) => helix.closest_t(p, tolerance, max_num_iter),
+            Self::Line(line) => line.closest_t(p),
+        }
+    }
+    fn closest_to_beamline(&self) -> Coordinate {
+        match self {
+            Self::Helix(helix) => helix.closest_to_beamline(),
+            Self::Line(line) => line.closest_to_beamline(),
+        }
+    }
+    fn arc_length(&self, t1: f64, t2: f64) -> Length {
+        match self {
+            Self::Helix(helix) => helix.arc_length(t1, t2),
+            Self::Line(_) => Length::new::<meter>((t2 - t1).abs()),
+        }
+    }
+    // Distance of closest approach to the beamline.
+    fn beamline_dca(&self) -> Length {
+        match self {
+            Self::Helix(helix) => (helix.r - helix.x0.hypot(helix.y0)).abs(),
+            Self::Line(line) => {
+                let c = line.closest_to_beamline();
+                c.x.hypot(c.y)
+            }
+        }
+    }
+    // Radius of curvature in the transverse plane. A straight line is the
+    // limit of an infinite radius.
+    fn radius(&self) -> Length {
+        match self {
+            Self::Helix(helix) => helix.r,
+            Self::Line(_) => Length::new::<meter>(f64::INFINITY),
+        }
+    }
+}
+
+/// Magnetic field configuration of a run.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FieldConfiguration {
+    /// The solenoid is on. Tracks are helices with axis parallel to the
+    /// z-axis.
+    #[default]
+    On,
+    /// The solenoid is off. Tracks are straight lines.
+    Off,
+}
+
+// Elementary charge in COULOMB.
+const ELEMENTARY_CHARGE: f64 = 1.602_176_634e-19;
+
+/// Geometric model of a [`Track`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TrackModel {
+    /// Helix with axis parallel to the z-axis (see
+    /// [`FieldConfiguration::On`]).
+    Helix {
+        /// Curvature in the transverse plane i.e. the inverse of the radius.
+        curvature: ReciprocalLength,
+        /// Displacement along the z-axis per revolution. Its sign depends on
+        /// the direction of rotation.
+        pitch: Length,
+    },
+    /// Straight line (see [`FieldConfiguration::Off`]).
+    Line {
+        /// Unit vector along the line. It points from the inner towards the
+        /// outer cathode of the rTPC.
+        direction: [f64; 3],
+        /// A point on the line.
+        point: Coordinate,
+    },
+}
+
+impl TrackModel {
+    /// Return the transverse momentum of a singly charged particle given the
+    /// magnetic field along the z-axis. Return [`None`] for a straight line
+    /// (i.e. there is no curvature to estimate the momentum from).
+    pub fn transverse_momentum(&self, b_field: MagneticFluxDensity) -> Option<Momentum> {
+        match self {
+            Self::Helix { curvature, .. } => {
+                Some(ElectricCharge::new::<coulomb>(ELEMENTARY_CHARGE) * b_field.abs() / *curvature)
+            }
+            Self::Line { .. } => None,
+        }
+    }
+}
+
 /// Trajectory of a charged particle through the detector volume.
 ///
 /// The [`Coordinate`]s of a track are parametrized by a single variable `t`.
@@ -237,11 +467,16 @@ impl Helix {
 ///
 /// It is important to note that `t_inner` is not necessarily smaller than
 /// `t_outer` (`t` is an arbitrary parametrization).
+///
+/// Depending on the [`FieldConfiguration`] of the run, a track is either a
+/// helix or a straight line (see [`Track::model`]). Both models are handled
+/// the same way by [`find_vertices`].
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Track {
-    // Don't expose the helix. It is just an internal implementation detail that
-    // is bound to change at any time.
-    helix: Helix,
+    // Don't expose the internal model. It is just an implementation detail
+    // that is bound to change at any time. A summary is exposed through
+    // `TrackModel`.
+    model: Model,
     // These `t_inner` and `t_outer` are useful to "draw" the actual trajectory
     // of a particle through the detector volume. They tell us what is an
     // approximate range of `t`, and also in which direction it has to change if
@@ -251,12 +486,14 @@ pub struct Track {
     // It is just an arbitrary parametrization.
     t_inner: f64,
     t_outer: f64,
+    num_points: usize,
+    rms_residual: Length,
 }
 
 impl Track {
     /// Return the [`Coordinate`] of the track at a given `t`.
     pub fn at(&self, t: f64) -> Coordinate {
-        self.helix.at(t)
+        self.model.at(t)
     }
     /// Return a value of `t` for which the track is close to the inner cathode
     /// of the detector.
@@ -268,6 +505,105 @@ impl Track {
     pub fn t_outer(&self) -> f64 {
         self.t_outer
     }
+    /// Return the number of [`SpacePoint`]s the track was fitted to.
+    pub fn num_points(&self) -> usize {
+        self.num_points
+    }
+    /// Return the root mean square of the orthogonal distances between the
+    /// track and the [`SpacePoint`]s it was fitted to i.e. a goodness of fit.
+    pub fn rms_residual(&self) -> Length {
+        self.rms_residual
+    }
+    /// Return the azimuthal residual of a [`SpacePoint`] with respect to the
+    /// track i.e. the difference between the `phi` of the point and the `phi`
+    /// of the track at the same radius. The residual is in `[-pi, pi)`.
+    ///
+    /// Returns [`None`] if the track does not cross the radius of the point
+    /// between [`Track::t_inner`] and [`Track::t_outer`] (extended by a small
+    /// margin on both sides).
+    pub fn phi_residual(&self, point: &SpacePoint) -> Option<Angle> {
+        let radius = |t: f64| {
+            let c = self.at(t);
+            c.x.hypot(c.y) - point.r
+        };
+        // The innermost and outermost points of the track are right at the
+        // edges of the range. Extend it to not miss them due to rounding.
+        let margin = 0.1 * (self.t_outer - self.t_inner);
+        let (mut a, mut b) = (self.t_inner - margin, self.t_outer + margin);
+        let (fa, fb) = (radius(a), radius(b));
+        if fa.value * fb.value > 0.0 {
+            return None;
+        }
+        let sign_a = fa.value.is_sign_negative();
+        // Plenty of iterations to go well below the spatial resolution of the
+        // detector for any reasonable range of `t`.
+        for _ in 0..60 {
+            let mid = 0.5 * (a + b);
+            if radius(mid).value.is_sign_negative() == sign_a {
+                a = mid;
+            } else {
+                b = mid;
+            }
+        }
+        let c = self.at(0.5 * (a + b));
+        let track_phi = c.y.atan2(c.x);
+        let residual = (point.phi - track_phi).get::<radian>();
+
+        Some(Angle::new::<radian>(
+            (residual + PI).rem_euclid(2.0 * PI) - PI,
+        ))
+    }
+    /// Return the geometric model of the track.
+    pub fn model(&self) -> TrackModel {
+        match self.model {
+            Model::Helix(helix) => TrackModel::Helix {
+                curvature: 1.0 / helix.r.abs(),
+                pitch: helix.h,
+            },
+            Model::Line(line) => TrackModel::Line {
+                direction: line.direction,
+                point: line.point,
+            },
+        }
+    }
+    /// Try to fit a [`Cluster`] to a [`Track`] given the magnetic field
+    /// configuration of the run. With the field on, the track is a helix;
+    /// otherwise, it is a straight line.
+    pub fn try_from_cluster(
+        cluster: Cluster,
+        field: FieldConfiguration,
+    ) -> Result<Self, TryTrackFromClusterError> {
+        match field {
+            FieldConfiguration::On => track_fitting::fit_cluster_to_helix(
+                cluster,
+                // Maximum number of Nelder-Mead iterations.
+                100,
+                // Nelder-Mead standard deviation tolerance.
+                f64::EPSILON,
+                // Delta from the initial guess for each simplex vertex.
+                // I just stuck to the default value used by scipy's
+                // implementation of Nelder-Mead. It has worked well.
+                // See:
+                // https://github.com/scipy/scipy/blob/v1.11.2/scipy/optimize/_optimize.py#L833
+                0.05,
+                // Maximum number of iterations to find the closest point on
+                // the helix given a SpacePoint.
+                20,
+                // Tolerance for finding the `t` parameter of the closest point
+                // on the helix given a SpacePoint.
+                f64::EPSILON,
+            ),
+            FieldConfiguration::Off => track_fitting::fit_cluster_to_line(
+                cluster,
+                // Maximum number of power iterations to find the direction of
+                // the line.
+                100,
+                // Power iteration stops whenever no component of the direction
+                // changes by more than this tolerance.
+                f64::EPSILON,
+            ),
+        }
+    }
 }
 
 /// The error type returned when conversion from a [`Cluster`] to a [`Track`]
@@ -277,42 +613,137 @@ pub enum TryTrackFromClusterError {
     /// Unable to produce initial fit parameters.
     #[error("unable to produce initial fit parameters")]
     NoInitialParameters,
+    /// The minimizer failed to find finite fit parameters.
+    #[error("track fit failed")]
+    FailedFit,
 }
 
+// Same as `Track::try_from_cluster` with the field on.
 impl TryFrom<Cluster> for Track {
     type Error = TryTrackFromClusterError;
 
     fn try_from(cluster: Cluster) -> Result<Self, Self::Error> {
-        track_fitting::fit_cluster_to_helix(
-            cluster,
-            // Maximum number of Nelder-Mead iterations.
-            100,
-            // Nelder-Mead standard deviation tolerance.
-            f64::EPSILON,
-            // Delta from the initial guess for each simplex vertex.
-            // I just stuck to the default value used by scipy's implementation
-            // of Nelder-Mead. It has worked well.
-            // See:
-            // https://github.com/scipy/scipy/blob/v1.11.2/scipy/optimize/_optimize.py#L833
-            0.05,
-            // Maximum number of iterations to find the closest point on the
-            // helix given a SpacePoint.
-            20,
-            // Tolerance for finding the `t` parameter of the closest point on
-            // the helix given a SpacePoint.
-            f64::EPSILON,
+        Self::try_from_cluster(cluster, FieldConfiguration::On)
+    }
+}
+
+/// Straight line fit to the [`SpacePoint`]s of a single through-going track
+/// (see [`fit_single_track`]).
+#[derive(Clone, Debug, PartialEq)]
+pub struct StraightLineFit {
+    /// Unit vector along the line. The sign is chosen such that the `z`
+    /// component is not negative.
+    pub direction: [f64; 3],
+    /// Point on the line that is closest to the beamline.
+    pub point: Coordinate,
+    /// Orthogonal distance from each [`SpacePoint`] (in the same order as the
+    /// input of [`fit_single_track`]) to the line.
+    pub residuals: Vec<Length>,
+    /// Whether each [`SpacePoint`] (in the same order as the residuals) was
+    /// used in the final fit or rejected as an outlier.
+    pub inliers: Vec<bool>,
+}
+
+impl StraightLineFit {
+    /// Return the number of [`SpacePoint`]s used in the final fit.
+    pub fn num_inliers(&self) -> usize {
+        self.inliers.iter().filter(|&&inlier| inlier).count()
+    }
+    /// Return the mean residual of the [`SpacePoint`]s used in the final fit.
+    pub fn mean_residual(&self) -> Length {
+        let sum: Length = self
+            .residuals
+            .iter()
+            .zip(&self.inliers)
+            .filter_map(|(&r, &inlier)| inlier.then_some(r))
+            .sum();
+
+        sum / self.num_inliers() as f64
+    }
+    /// Return the length of the line in between its entry and exit points of
+    /// a [`Cylinder`] (e.g. the [`FIDUCIAL_CYLINDER`]).
+    ///
+    /// Returns [`None`] if the line misses the cylinder.
+    ///
+    /// [`FIDUCIAL_CYLINDER`]: crate::geometry::FIDUCIAL_CYLINDER
+    pub fn length_inside(&self, cylinder: &Cylinder) -> Option<Length> {
+        let other = Coordinate {
+            x: self.point.x + Length::new::<meter>(self.direction[0]),
+            y: self.point.y + Length::new::<meter>(self.direction[1]),
+            z: self.point.z + Length::new::<meter>(self.direction[2]),
+        };
+        let (entry, exit) = cylinder.intersections(self.point, other)?;
+
+        Some(
+            (exit.x - entry.x)
+                .hypot(exit.y - entry.y)
+                .hypot(exit.z - entry.z),
         )
     }
 }
 
+/// Fit all the [`SpacePoint`]s of an event to a single straight track that
+/// goes through the whole detector (e.g. a cosmic ray). This is an
+/// alternative to clustering the points into tracks that originate from a
+/// vertex.
+///
+/// Outliers (e.g. noise) are iteratively rejected; the line is fit again
+/// without all points that are more than 3 standard deviations away from it.
+/// The standard deviation is estimated from the median residual, and it is
+/// never smaller than 2 mm.
+///
+/// Returns [`None`] if any [`SpacePoint`] is not finite, or if the points
+/// don't define a line (e.g. fewer than 3 points, or all of them at the same
+/// position).
+pub fn fit_single_track(points: Vec<SpacePoint>) -> Option<StraightLineFit> {
+    track_fitting::fit_points_to_straight_line(
+        points,
+        // Maximum number of outlier rejection iterations.
+        10,
+        // Points further than this many standard deviations from the line are
+        // outliers.
+        3.0,
+        // Rough spatial resolution of the rTPC.
+        Length::new::<centimeter>(0.2),
+        // Same power iteration settings as `Track::try_from_cluster` with the
+        // field off.
+        100,
+        f64::EPSILON,
+    )
+}
+
 /// Information about a reconstructed vertex.
 #[derive(Clone, Debug)]
 pub struct VertexInfo {
     /// Position of the vertex.
     pub position: Coordinate,
+    /// Position of the vertex before applying a [`BeamConstraint`]. This is
+    /// only [`Some`] if the constraint was active.
+    pub unconstrained_position: Option<Coordinate>,
     /// [`Track`]s associated to the vertex. Each track is paired with the value
     /// of `t` at which it is closest to the vertex.
     pub tracks: Vec<(Track, f64)>,
+    /// Covariance matrix of `position` (in `x`, `y`, `z` order).
+    ///
+    /// This is estimated from the curvature of the cost function around its
+    /// minimum (i.e. linearizing each track around its closest point to the
+    /// vertex). The track resolution is estimated from the residuals of the
+    /// fit, and it is never taken to be smaller than the resolution of the
+    /// individual track fits (see [`Track::rms_residual`]). With only two
+    /// tracks, there is a single degree of freedom, and the estimate is
+    /// correspondingly rough. This is [`None`] if the tracks don't constrain
+    /// the vertex in every direction (e.g. parallel tracks).
+    pub covariance: Option<[[Area; 3]; 3]>,
+}
+
+impl VertexInfo {
+    /// Return the standard deviation of each coordinate (`x`, `y`, `z`) of
+    /// the vertex position i.e. the square root of the diagonal of
+    /// [`VertexInfo::covariance`].
+    pub fn sigma(&self) -> Option<[Length; 3]> {
+        self.covariance
+            .map(|covariance| [0, 1, 2].map(|i| covariance[i][i].sqrt()))
+    }
 }
 
 /// Result of reconstructing the vertices of an event from a set of [`Track`]s.
@@ -326,8 +757,71 @@ pub struct VertexingResult {
     pub remainder: Vec<Track>,
 }
 
+/// Constraint on the transverse position of a vertex.
+///
+/// Antiproton annihilations on residual gas happen close to the trap axis. The
+/// vertex fit can then be improved by pulling the vertex towards `r = 0`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BeamConstraint {
+    /// Standard deviation of the transverse distance between the vertex and
+    /// the beamline.
+    pub r_sigma: Length,
+}
+
+/// Settings used to reconstruct an event.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ReconstructionSettings {
+    /// Optional beam-axis constraint applied to the primary vertex fit.
+    ///
+    /// A `(r / r_sigma)^2` penalty is added to the vertex fit. The track
+    /// resolution is estimated from the distances between the tracks and the
+    /// unconstrained vertex, but it is never smaller than the uncertainty of
+    /// the tracks themselves (from their RMS residuals and number of points).
+    /// Hence, the constraint still pulls the vertex towards the beamline if all
+    /// tracks meet at a single point. The same resolution is used for the
+    /// covariance of the vertex.
+    pub beam_constraint: Option<BeamConstraint>,
+}
+
+/// The error type returned when reconstructing the vertices of an event fails.
+#[derive(Debug, Error)]
+pub enum FindVerticesError {
+    /// The minimizer failed to find a finite vertex position.
+    #[error("vertex fit failed")]
+    FailedFit,
+}
+
 /// Given a collection of [`Track`]s, reconstruct the vertices of an event.
+///
+/// This is the same as [`find_vertices_with_settings`] with the default
+/// [`ReconstructionSettings`].
 pub fn find_vertices(tracks: Vec<Track>) -> VertexingResult {
+    find_vertices_with_settings(tracks, &ReconstructionSettings::default())
+}
+
+/// Given a collection of [`Track`]s, reconstruct the vertices of an event
+/// using the given [`ReconstructionSettings`].
+///
+/// If the vertex fit fails (see [`try_find_vertices_with_settings`]), there
+/// is no primary vertex and all tracks are in the remainder.
+pub fn find_vertices_with_settings(
+    tracks: Vec<Track>,
+    settings: &ReconstructionSettings,
+) -> VertexingResult {
+    let remainder = tracks.clone();
+    try_find_vertices_with_settings(tracks, settings).unwrap_or(VertexingResult {
+        primary: None,
+        secondaries: Vec::new(),
+        remainder,
+    })
+}
+
+/// Same as [`find_vertices_with_settings`], but return an error if the vertex
+/// fit fails (e.g. degenerate tracks) instead of an empty result.
+pub fn try_find_vertices_with_settings(
+    tracks: Vec<Track>,
+    settings: &ReconstructionSettings,
+) -> Result<VertexingResult, FindVerticesError> {
     vertex_fitting::find_vertices(
         tracks,
         // Minimum track length to be considered for vertexing.
@@ -355,6 +849,7 @@ pub fn find_vertices(tracks: Vec<Track>) -> VertexingResult {
         100,
         // Nelder-Mead standard deviation tolerance.
         f64::EPSILON,
+        settings.beam_constraint,
     )
 }
 
@@ -4,6 +4,9 @@ use std::f64::consts::PI;
 use uom::si::angle::radian;
 use uom::si::f64::Angle;
 use uom::si::length::meter;
+use uom::si::magnetic_flux_density::tesla;
+use uom::si::momentum::kilogram_meter_per_second;
+use uom::si::reciprocal_length::reciprocal_meter;
 
 fn is_within_tpc_volume(p: &SpacePoint) -> bool {
     let detector_half_length = Length::new::<meter>(DETECTOR_LENGTH / 2.0);
@@ -129,6 +132,67 @@ fn two_on_top_tracks() {
     }
 }
 
+#[test]
+fn clustering_result_is_permutation_of_input() {
+    let mut raw_points = Vec::new();
+
+    let r = Length::new::<centimeter>(20.0);
+    let num_points = 2000;
+    for i in 0..num_points {
+        let theta = Angle::FULL_TURN * i as f64 / num_points as f64;
+        let x = r * theta.cos() + r;
+        let y = r * theta.sin();
+
+        let point = SpacePoint {
+            r: (x * x + y * y).sqrt(),
+            phi: y.atan2(x),
+            z: Length::new::<meter>(0.0),
+        };
+
+        if is_within_tpc_volume(&point) {
+            raw_points.push(point);
+        }
+    }
+    // Isolated points that can't form a cluster by themselves.
+    for i in 0..5 {
+        let noise = SpacePoint {
+            r: Length::new::<centimeter>(15.0 + 2.0 * i as f64),
+            phi: Angle::new::<radian>(-2.5 + 0.3 * i as f64),
+            z: Length::new::<meter>(0.2 * i as f64 - 0.5),
+        };
+        raw_points.insert(100 * i, noise);
+    }
+    // Repeated points are still assigned distinct indices.
+    raw_points.push(raw_points[1]);
+
+    let clustering_result = cluster_spacepoints(raw_points.clone());
+    assert_eq!(clustering_result.clusters.len(), 2);
+    assert_eq!(clustering_result.remainder.len(), 5);
+    assert_eq!(
+        clustering_result.remainder.len(),
+        clustering_result.remainder_indices.len()
+    );
+
+    let mut indices = Vec::new();
+    for cluster in clustering_result.clusters.iter() {
+        assert_eq!(cluster.points().len(), cluster.indices().len());
+        for (point, &index) in cluster.iter().zip(cluster.indices()) {
+            assert_eq!(*point, raw_points[index]);
+            indices.push(index);
+        }
+    }
+    for (point, &index) in clustering_result
+        .remainder
+        .iter()
+        .zip(&clustering_result.remainder_indices)
+    {
+        assert_eq!(*point, raw_points[index]);
+        indices.push(index);
+    }
+    indices.sort_unstable();
+    assert_eq!(indices, (0..raw_points.len()).collect::<Vec<_>>());
+}
+
 fn trivial_helix_fit(x0: Length, y0: Length, z0: Length, r: Length, phi0: Angle, h: Length) {
     let mut raw_points = Vec::new();
     let num_points = 2000;
@@ -252,6 +316,238 @@ fn trivial_track_fitting() {
     );
 }
 
+#[test]
+fn helix_track_model() {
+    let r = Length::new::<centimeter>(30.0);
+    let h = Length::new::<centimeter>(50.0);
+    let mut raw_points = Vec::new();
+    let num_points = 2000;
+    for i in 0..num_points {
+        let t = Angle::FULL_TURN * i as f64 / num_points as f64 - Angle::HALF_TURN;
+        let x =
+            r * (t + Angle::new::<radian>(-3.0 * PI / 4.0)).cos() + Length::new::<centimeter>(20.0);
+        let y =
+            r * (t + Angle::new::<radian>(-3.0 * PI / 4.0)).sin() + Length::new::<centimeter>(20.0);
+        let point = SpacePoint {
+            r: x.hypot(y),
+            phi: y.atan2(x),
+            z: (h / Angle::FULL_TURN) * t,
+        };
+
+        if is_within_tpc_volume(&point) {
+            raw_points.push(point);
+        }
+    }
+
+    for cluster in cluster_spacepoints(raw_points).clusters {
+        let track = Track::try_from_cluster(cluster, FieldConfiguration::On).unwrap();
+        let TrackModel::Helix { curvature, pitch } = track.model() else {
+            panic!("expected a helix");
+        };
+
+        assert!(((curvature * r).get::<ratio>() - 1.0).abs() < 1e-6);
+        assert!((pitch - h).abs() < Length::new::<centimeter>(1e-4));
+        assert!(track.rms_residual() < Length::new::<centimeter>(1e-3));
+    }
+}
+
+#[test]
+fn track_model_transverse_momentum() {
+    let helix = TrackModel::Helix {
+        curvature: ReciprocalLength::new::<reciprocal_meter>(2.0),
+        pitch: Length::new::<meter>(1.0),
+    };
+    // p = qBr
+    let expected = ELEMENTARY_CHARGE * 1.5 * 0.5;
+    for b in [1.5, -1.5] {
+        let pt = helix
+            .transverse_momentum(MagneticFluxDensity::new::<tesla>(b))
+            .unwrap()
+            .get::<kilogram_meter_per_second>();
+        assert!((pt / expected - 1.0).abs() < 1e-12);
+    }
+
+    let line = TrackModel::Line {
+        direction: [1.0, 0.0, 0.0],
+        point: Coordinate {
+            x: Length::new::<meter>(0.0),
+            y: Length::new::<meter>(0.0),
+            z: Length::new::<meter>(0.0),
+        },
+    };
+    assert!(line
+        .transverse_momentum(MagneticFluxDensity::new::<tesla>(1.0))
+        .is_none());
+}
+
+#[test]
+fn track_phi_residual() {
+    let p = Coordinate {
+        x: Length::new::<centimeter>(0.0),
+        y: Length::new::<centimeter>(0.0),
+        z: Length::new::<centimeter>(5.0),
+    };
+    let cluster = line_cluster(p, unit([1.0, 1.0, 0.2]));
+    let track = Track::try_from_cluster(cluster, FieldConfiguration::Off).unwrap();
+
+    for offset in [0.0, 0.01, -0.02] {
+        let point = SpacePoint {
+            r: Length::new::<centimeter>(15.0),
+            phi: Angle::new::<radian>(PI / 4.0 + offset),
+            z: Length::new::<centimeter>(0.0),
+        };
+        let residual = track.phi_residual(&point).unwrap().get::<radian>();
+        assert!((residual - offset).abs() < 1e-9);
+    }
+    // Wrapped around.
+    let point = SpacePoint {
+        r: Length::new::<centimeter>(15.0),
+        phi: Angle::new::<radian>(PI / 4.0 - 2.0 * PI + 0.01),
+        z: Length::new::<centimeter>(0.0),
+    };
+    let residual = track.phi_residual(&point).unwrap().get::<radian>();
+    assert!((residual - 0.01).abs() < 1e-9);
+    // Outside of the detector.
+    let point = SpacePoint {
+        r: Length::new::<centimeter>(50.0),
+        phi: Angle::new::<radian>(PI / 4.0),
+        z: Length::new::<centimeter>(0.0),
+    };
+    assert!(track.phi_residual(&point).is_none());
+}
+
+// Cluster with points along a straight line (inside the rTPC) through `p`.
+fn line_cluster(p: Coordinate, direction: [f64; 3]) -> Cluster {
+    let mut points = Vec::new();
+    let num_points = 200;
+    for i in 0..num_points {
+        let t = Length::new::<meter>(0.3 * i as f64 / num_points as f64);
+        let x = p.x + direction[0] * t;
+        let y = p.y + direction[1] * t;
+        let point = SpacePoint {
+            r: x.hypot(y),
+            phi: y.atan2(x),
+            z: p.z + direction[2] * t,
+        };
+
+        if is_within_tpc_volume(&point) {
+            points.push(point);
+        }
+    }
+
+    let indices = (0..points.len()).collect();
+    Cluster(points, indices)
+}
+
+fn unit(v: [f64; 3]) -> [f64; 3] {
+    let norm = v.iter().map(|x| x * x).sum::<f64>().sqrt();
+    v.map(|x| x / norm)
+}
+
+#[test]
+fn line_track_fitting() {
+    let p = Coordinate {
+        x: Length::new::<centimeter>(1.0),
+        y: Length::new::<centimeter>(-2.0),
+        z: Length::new::<centimeter>(5.0),
+    };
+    let direction = unit([0.6, 0.7, -0.3]);
+    let cluster = line_cluster(p, direction);
+    let points = cluster.points().to_vec();
+
+    let track = Track::try_from_cluster(cluster, FieldConfiguration::Off).unwrap();
+    let TrackModel::Line {
+        direction: fit_direction,
+        point,
+    } = track.model()
+    else {
+        panic!("expected a line");
+    };
+    for (a, b) in fit_direction.iter().zip(direction) {
+        assert!((a - b).abs() < 1e-9);
+    }
+    // The fitted point is on the true line.
+    let d = [
+        (point.x - p.x).get::<meter>(),
+        (point.y - p.y).get::<meter>(),
+        (point.z - p.z).get::<meter>(),
+    ];
+    let along = d.iter().zip(direction).map(|(a, b)| a * b).sum::<f64>();
+    let distance = (d.iter().map(|x| x * x).sum::<f64>() - along.powi(2))
+        .max(0.0)
+        .sqrt();
+    assert!(distance < 1e-9);
+
+    assert!(track.t_inner() < track.t_outer());
+    let inner = track.at(track.t_inner());
+    assert!((inner.x - points[0].x()).abs() < Length::new::<centimeter>(1e-6));
+    assert!((inner.y - points[0].y()).abs() < Length::new::<centimeter>(1e-6));
+    assert!((inner.z - points[0].z).abs() < Length::new::<centimeter>(1e-6));
+    let last = points[points.len() - 1];
+    let outer = track.at(track.t_outer());
+    assert!((outer.x - last.x()).abs() < Length::new::<centimeter>(1e-6));
+    assert!((outer.y - last.y()).abs() < Length::new::<centimeter>(1e-6));
+    assert!((outer.z - last.z).abs() < Length::new::<centimeter>(1e-6));
+
+    assert_eq!(track.num_points(), points.len());
+    assert!(track.rms_residual() < Length::new::<centimeter>(1e-6));
+}
+
+#[test]
+fn field_off_track_has_no_curvature() {
+    // Same collinear points that fail to fit to a helix.
+    let mut points = Vec::new();
+    for i in 0..100 {
+        points.push(SpacePoint {
+            r: Length::new::<centimeter>(10.0 + 0.09 * i as f64),
+            phi: Angle::new::<radian>(0.0),
+            z: Length::new::<centimeter>(0.0),
+        });
+    }
+    let cluster = Cluster(points, (0..100).collect());
+
+    let track = Track::try_from_cluster(cluster, FieldConfiguration::Off).unwrap();
+    assert!(matches!(track.model(), TrackModel::Line { .. }));
+    assert!(track
+        .model()
+        .transverse_momentum(MagneticFluxDensity::new::<tesla>(1.0))
+        .is_none());
+}
+
+#[test]
+fn line_track_fitting_repeated_points() {
+    let point = SpacePoint {
+        r: Length::new::<centimeter>(15.0),
+        phi: Angle::new::<radian>(0.0),
+        z: Length::new::<centimeter>(0.0),
+    };
+    let cluster = Cluster(vec![point; 100], (0..100).collect());
+
+    assert!(Track::try_from_cluster(cluster, FieldConfiguration::Off).is_err());
+}
+
+#[test]
+fn line_vertex_fitting() {
+    let z = Length::new::<centimeter>(12.0);
+    let origin = Coordinate {
+        x: Length::new::<meter>(0.0),
+        y: Length::new::<meter>(0.0),
+        z,
+    };
+    let tracks = [unit([1.0, 0.0, 0.2]), unit([-0.5, 1.0, -0.3])]
+        .into_iter()
+        .map(|direction| {
+            Track::try_from_cluster(line_cluster(origin, direction), FieldConfiguration::Off)
+                .unwrap()
+        })
+        .collect();
+
+    let vertex = find_vertices(tracks).primary.unwrap().position;
+    assert!(vertex.x.abs() < Length::new::<meter>(1e-6));
+    assert!(vertex.y.abs() < Length::new::<meter>(1e-6));
+    assert!((vertex.z - z).abs() < Length::new::<meter>(1e-6));
+}
+
 #[test]
 fn track_fitting_h_equal_zero_regression() {
     trivial_helix_fit(
@@ -294,6 +590,80 @@ fn track_fitting_bad_initial_parameters_regression_repeated_points() {
     assert!(Track::try_from(cluster).is_err());
 }
 
+// Used to panic when removing the point from the Hough accumulator (a NaN is not
+// equal to itself).
+#[test]
+fn track_finding_nan_spacepoint_regression() {
+    let nan = SpacePoint {
+        r: Length::new::<centimeter>(15.0),
+        phi: Angle::new::<radian>(0.0),
+        z: Length::new::<meter>(f64::NAN),
+    };
+    let clustering_result = cluster_spacepoints(vec![nan]);
+    assert!(clustering_result.clusters.is_empty());
+    assert_eq!(clustering_result.remainder_indices, [0]);
+
+    let mut points: Vec<_> = (0..100)
+        .map(|i| SpacePoint {
+            r: Length::new::<centimeter>(11.0 + 0.08 * i as f64),
+            phi: Angle::new::<radian>(0.5),
+            z: Length::new::<centimeter>(0.1 * i as f64),
+        })
+        .collect();
+    points.insert(
+        50,
+        SpacePoint {
+            phi: Angle::new::<radian>(f64::NAN),
+            ..points[50]
+        },
+    );
+    let clustering_result = cluster_spacepoints(points);
+    assert_eq!(clustering_result.clusters.len(), 1);
+    assert!(!clustering_result.clusters[0].indices().contains(&50));
+    assert!(clustering_result.remainder_indices.contains(&50));
+}
+
+// Points at (or very close to) the origin are outside the conformal mapping.
+#[test]
+fn track_finding_origin_spacepoint_regression() {
+    let points = [0.0, 1e-300]
+        .into_iter()
+        .map(|r| SpacePoint {
+            r: Length::new::<meter>(r),
+            phi: Angle::new::<radian>(1.0),
+            z: Length::new::<meter>(0.0),
+        })
+        .collect();
+
+    let clustering_result = cluster_spacepoints(points);
+    assert!(clustering_result.clusters.is_empty());
+    assert_eq!(clustering_result.remainder_indices, [0, 1]);
+}
+
+#[test]
+fn track_fitting_too_few_points_regression() {
+    let points = vec![
+        SpacePoint {
+            r: Length::new::<centimeter>(15.0),
+            phi: Angle::new::<radian>(0.0),
+            z: Length::new::<centimeter>(0.0),
+        },
+        SpacePoint {
+            r: Length::new::<centimeter>(16.0),
+            phi: Angle::new::<radian>(0.1),
+            z: Length::new::<centimeter>(1.0),
+        },
+    ];
+
+    for field in [FieldConfiguration::On, FieldConfiguration::Off] {
+        let cluster = Cluster(points.clone(), vec![0, 1]);
+        assert!(matches!(
+            Track::try_from_cluster(cluster, field),
+            Err(TryTrackFromClusterError::NoInitialParameters)
+        ));
+    }
+}
+
 #[test]
 fn track_fitting_bad_initial_parameters_regression_collinear_points() {
     let mut points = Vec::new();
@@ -355,3 +725,380 @@ fn trivial_vertex_fitting() {
     test_trivial_vertex(Length::new::<meter>(-0.5));
     test_trivial_vertex(Length::new::<meter>(-1.0));
 }
+
+// Almost straight track that goes through `p` (at `t = 0`) with the given
+// direction in the transverse plane.
+fn track_through(p: Coordinate, phi0: Angle) -> Track {
+    let r = Length::new::<meter>(1.0);
+    Track {
+        model: Model::Helix(Helix {
+            x0: p.x - r * phi0.cos(),
+            y0: p.y - r * phi0.sin(),
+            z0: p.z,
+            r,
+            phi0,
+            h: Length::new::<meter>(0.1),
+        }),
+        t_inner: -0.2,
+        t_outer: 0.2,
+        num_points: 20,
+        rms_residual: Length::new::<meter>(0.0),
+    }
+}
+
+// Two (almost) perpendicular tracks that miss each other by 2 mm in `z` around
+// the given true vertex.
+fn two_track_vertex(x: Length, settings: &ReconstructionSettings) -> VertexInfo {
+    let offset = Length::new::<centimeter>(0.1);
+    let zero = Length::new::<meter>(0.0);
+    let track_a = track_through(
+        Coordinate {
+            x,
+            y: zero,
+            z: offset,
+        },
+        Angle::new::<radian>(0.0),
+    );
+    let track_b = track_through(
+        Coordinate {
+            x,
+            y: zero,
+            z: -offset,
+        },
+        Angle::new::<radian>(PI / 2.0),
+    );
+
+    find_vertices_with_settings(vec![track_a, track_b], settings)
+        .primary
+        .unwrap()
+}
+
+#[test]
+fn vertex_fitting_without_beam_constraint() {
+    let x = Length::new::<centimeter>(1.0);
+    let vertex = two_track_vertex(x, &ReconstructionSettings::default());
+
+    assert!(vertex.unconstrained_position.is_none());
+    assert!((vertex.position.x - x).abs() < Length::new::<centimeter>(0.01));
+    assert!(vertex.position.y.abs() < Length::new::<centimeter>(0.01));
+    assert!(vertex.position.z.abs() < Length::new::<centimeter>(0.01));
+}
+
+#[test]
+fn vertex_fitting_beam_constraint_on_axis() {
+    let settings = ReconstructionSettings {
+        beam_constraint: Some(BeamConstraint {
+            r_sigma: Length::new::<centimeter>(0.1),
+        }),
+    };
+    let x = Length::new::<meter>(0.0);
+    let vertex = two_track_vertex(x, &settings);
+
+    let unconstrained = vertex.unconstrained_position.unwrap();
+    let diff = (vertex.position.x - unconstrained.x)
+        .hypot(vertex.position.y - unconstrained.y)
+        .hypot(vertex.position.z - unconstrained.z);
+    assert!(diff < Length::new::<centimeter>(0.01));
+}
+
+#[test]
+fn vertex_fitting_beam_constraint_off_axis() {
+    let settings = ReconstructionSettings {
+        beam_constraint: Some(BeamConstraint {
+            r_sigma: Length::new::<centimeter>(0.1),
+        }),
+    };
+    let x = Length::new::<centimeter>(1.0);
+    let vertex = two_track_vertex(x, &settings);
+
+    let unconstrained = vertex.unconstrained_position.unwrap();
+    assert!((unconstrained.x - x).abs() < Length::new::<centimeter>(0.01));
+    // With a track resolution of ~1 mm and `r_sigma = 1 mm`, the vertex is
+    // pulled roughly halfway towards the beamline.
+    let r = vertex.position.x.hypot(vertex.position.y);
+    assert!(r < 0.75 * x);
+    assert!(r > 0.25 * x);
+}
+
+#[test]
+fn vertex_fitting_beam_constraint_intersecting_tracks() {
+    let settings = ReconstructionSettings {
+        beam_constraint: Some(BeamConstraint {
+            r_sigma: Length::new::<centimeter>(0.01),
+        }),
+    };
+    let x = Length::new::<centimeter>(1.0);
+    let zero = Length::new::<meter>(0.0);
+    let vertex = Coordinate {
+        x,
+        y: zero,
+        z: zero,
+    };
+    // Both tracks go exactly through the vertex (i.e. the residuals of the
+    // unconstrained fit are ~0), but each has a 1 mm RMS residual.
+    let tracks = [0.0, PI / 2.0]
+        .map(|phi0| Track {
+            rms_residual: Length::new::<centimeter>(0.1),
+            ..track_through(vertex, Angle::new::<radian>(phi0))
+        })
+        .to_vec();
+    let vertex = find_vertices_with_settings(tracks, &settings)
+        .primary
+        .unwrap();
+
+    let unconstrained = vertex.unconstrained_position.unwrap();
+    assert!((unconstrained.x - x).abs() < Length::new::<centimeter>(0.01));
+    // The track resolution is sqrt(1 mm^2 / 20 points) ~ 0.22 mm, so the beam
+    // weight is ~5 and the vertex is pulled most of the way to the beamline.
+    let r = vertex.position.x.hypot(vertex.position.y);
+    assert!(r < 0.5 * x);
+}
+
+#[test]
+fn two_track_vertex_sigma() {
+    let vertex = two_track_vertex(
+        Length::new::<meter>(0.0),
+        &ReconstructionSettings::default(),
+    );
+
+    let sigma = vertex.sigma().unwrap();
+    for s in sigma {
+        assert!(s.is_finite());
+        assert!(s > Length::new::<meter>(0.0));
+    }
+}
+
+// Tracks in a star pattern around the origin, alternately missing it by 1 mm
+// above and below in `z`.
+fn star_vertex_sigma(num_tracks: usize) -> [Length; 3] {
+    let offset = Length::new::<centimeter>(0.1);
+    let zero = Length::new::<meter>(0.0);
+    let tracks = (0..num_tracks)
+        .map(|i| {
+            let z = if i % 2 == 0 { offset } else { -offset };
+            let phi0 = Angle::new::<radian>(PI * i as f64 / num_tracks as f64);
+            track_through(
+                Coordinate {
+                    x: zero,
+                    y: zero,
+                    z,
+                },
+                phi0,
+            )
+        })
+        .collect();
+
+    find_vertices(tracks).primary.unwrap().sigma().unwrap()
+}
+
+#[test]
+fn vertex_sigma_decreases_with_more_tracks() {
+    let two = star_vertex_sigma(2);
+    let eight = star_vertex_sigma(8);
+
+    for (a, b) in eight.into_iter().zip(two) {
+        assert!(a < b);
+    }
+}
+
+// Points along a straight line (inside the rTPC) that crosses the whole
+// detector through `p`.
+fn through_going_points(p: Coordinate, direction: [f64; 3]) -> Vec<SpacePoint> {
+    (-200..200)
+        .map(|i| {
+            let t = Length::new::<meter>(0.4 * i as f64 / 200.0);
+            let x = p.x + direction[0] * t;
+            let y = p.y + direction[1] * t;
+            SpacePoint {
+                r: x.hypot(y),
+                phi: y.atan2(x),
+                z: p.z + direction[2] * t,
+            }
+        })
+        .filter(is_within_tpc_volume)
+        .collect()
+}
+
+#[test]
+fn fit_single_track_through_going() {
+    let p = Coordinate {
+        x: Length::new::<centimeter>(3.0),
+        y: Length::new::<centimeter>(0.0),
+        z: Length::new::<centimeter>(-10.0),
+    };
+    let direction = unit([0.0, 0.9, -0.4]);
+    let mut points = through_going_points(p, direction);
+    let num_track_points = points.len();
+    // Noise far away from the track.
+    for phi in [0.5, 2.0, 4.0] {
+        points.push(SpacePoint {
+            r: Length::new::<centimeter>(15.0),
+            phi: Angle::new::<radian>(phi),
+            z: Length::new::<centimeter>(5.0),
+        });
+    }
+
+    let fit = fit_single_track(points).unwrap();
+    // Oriented such that the `z` component is positive.
+    for (a, b) in fit.direction.iter().zip(direction) {
+        assert!((a + b).abs() < 1e-9);
+    }
+    assert!((fit.point.x - p.x).abs() < Length::new::<centimeter>(1e-6));
+    assert!((fit.point.y - p.y).abs() < Length::new::<centimeter>(1e-6));
+    assert!((fit.point.z - p.z).abs() < Length::new::<centimeter>(1e-6));
+
+    assert_eq!(fit.residuals.len(), num_track_points + 3);
+    assert_eq!(fit.num_inliers(), num_track_points);
+    assert!(fit.inliers[..num_track_points].iter().all(|&inlier| inlier));
+    assert!(fit.residuals[num_track_points..]
+        .iter()
+        .all(|&r| r > Length::new::<centimeter>(5.0)));
+    assert!(fit.mean_residual() < Length::new::<centimeter>(1e-6));
+}
+
+#[test]
+fn fit_single_track_keeps_points_within_resolution() {
+    let p = Coordinate {
+        x: Length::new::<centimeter>(0.0),
+        y: Length::new::<centimeter>(0.0),
+        z: Length::new::<centimeter>(0.0),
+    };
+    let mut points = through_going_points(p, [1.0, 0.0, 0.0]);
+    // Small (alternating) offsets well within the resolution of the detector.
+    for (i, point) in points.iter_mut().enumerate() {
+        let offset = Length::new::<centimeter>(0.1);
+        point.z = if i % 2 == 0 { offset } else { -offset };
+    }
+    let num_points = points.len();
+
+    let fit = fit_single_track(points).unwrap();
+    assert_eq!(fit.num_inliers(), num_points);
+    let difference = fit.mean_residual() - Length::new::<centimeter>(0.1);
+    assert!(difference.abs() < Length::new::<centimeter>(0.01));
+}
+
+#[test]
+fn straight_line_fit_length_inside() {
+    let cylinder = Cylinder {
+        radius: Length::new::<meter>(1.0),
+        half_length: Length::new::<meter>(2.0),
+    };
+    let fit = |point: [f64; 3], direction: [f64; 3]| StraightLineFit {
+        direction: unit(direction),
+        point: Coordinate {
+            x: Length::new::<meter>(point[0]),
+            y: Length::new::<meter>(point[1]),
+            z: Length::new::<meter>(point[2]),
+        },
+        residuals: Vec::new(),
+        inliers: Vec::new(),
+    };
+    // Perpendicular to the axis through the center.
+    let length = fit([0.0; 3], [1.0, 0.0, 0.0]).length_inside(&cylinder);
+    assert!((length.unwrap().get::<meter>() - 2.0).abs() < 1e-12);
+    // Enters through the barrel and exits through an end cap.
+    let length = fit([0.0; 3], [1.0, 0.0, 1.0]).length_inside(&cylinder);
+    assert!((length.unwrap().get::<meter>() - 2.0f64.sqrt()).abs() < 1e-12);
+    // Misses the cylinder.
+    assert!(fit([0.0, 1.5, 0.0], [1.0, 0.0, 0.0])
+        .length_inside(&cylinder)
+        .is_none());
+}
+
+#[test]
+fn fit_single_track_degenerate() {
+    let point = SpacePoint {
+        r: Length::new::<centimeter>(15.0),
+        phi: Angle::new::<radian>(0.0),
+        z: Length::new::<centimeter>(0.0),
+    };
+
+    assert!(fit_single_track(Vec::new()).is_none());
+    assert!(fit_single_track(vec![point; 2]).is_none());
+    assert!(fit_single_track(vec![point; 100]).is_none());
+
+    let mut points = through_going_points(
+        Coordinate {
+            x: point.x(),
+            y: point.y(),
+            z: point.z,
+        },
+        [0.0, 1.0, 0.0],
+    );
+    points.push(SpacePoint {
+        z: Length::new::<meter>(f64::NAN),
+        ..point
+    });
+    assert!(fit_single_track(points).is_none());
+}
+
+#[test]
+fn clustering_options_default() {
+    let options = ClusteringOptions::default();
+
+    assert_eq!(options.min_points, 13);
+    assert_eq!(options.max_gap, Length::new::<centimeter>(3.0));
+}
+
+// Two radial segments (same `r` and `phi`, i.e. the same Hough bin) separated
+// by a 5 cm gap along `z`.
+fn segments_with_gap() -> Vec<SpacePoint> {
+    [0.0, 5.0]
+        .into_iter()
+        .flat_map(|z| {
+            (0..20).map(move |i| SpacePoint {
+                r: Length::new::<centimeter>(11.0 + 0.15 * f64::from(i)),
+                phi: Angle::new::<radian>(0.5),
+                z: Length::new::<centimeter>(z),
+            })
+        })
+        .collect()
+}
+
+#[test]
+fn cluster_spacepoints_with_max_gap_splits_tracks() {
+    let points = segments_with_gap();
+    let options = ClusteringOptions {
+        max_gap: Length::new::<centimeter>(3.0),
+        ..Default::default()
+    };
+    let clustering_result = cluster_spacepoints_with(points.clone(), &options);
+
+    assert!(clustering_result.remainder.is_empty());
+    assert_eq!(clustering_result.clusters.len(), 2);
+    for cluster in &clustering_result.clusters {
+        assert_eq!(cluster.points().len(), 20);
+        let z = cluster.points()[0].z;
+        assert!(cluster.iter().all(|p| p.z == z));
+    }
+    // Same as the default options.
+    assert_eq!(cluster_spacepoints(points).clusters.len(), 2);
+}
+
+#[test]
+fn cluster_spacepoints_with_max_gap_merges_tracks() {
+    let options = ClusteringOptions {
+        max_gap: Length::new::<centimeter>(6.0),
+        ..Default::default()
+    };
+    let clustering_result = cluster_spacepoints_with(segments_with_gap(), &options);
+
+    assert!(clustering_result.remainder.is_empty());
+    assert_eq!(clustering_result.clusters.len(), 1);
+    assert_eq!(clustering_result.clusters[0].points().len(), 40);
+}
+
+#[test]
+fn cluster_spacepoints_with_min_points() {
+    let options = ClusteringOptions {
+        min_points: 21,
+        ..Default::default()
+    };
+    let clustering_result = cluster_spacepoints_with(segments_with_gap(), &options);
+
+    assert!(clustering_result.clusters.is_empty());
+    assert_eq!(
+        clustering_result.remainder_indices,
+        (0..40).collect::<Vec<_>>()
+    );
+}
@@ -3,6 +3,7 @@ use crate::SpacePoint;
 use alpha_g_detector::alpha16::aw_map::INNER_CATHODE_RADIUS;
 use indexmap::IndexMap;
 use uom::si::f64::{Angle, Length, ReciprocalLength};
+use uom::si::length::meter;
 use uom::si::ratio::ratio;
 use uom::typenum::P2;
 
@@ -18,7 +19,7 @@ use uom::typenum::P2;
 // We can filter potential annihilation tracks (which originate close to the
 // origin) by finding straight lines in the u-v plane.
 pub(crate) fn cluster_spacepoints(
-    mut sp: Vec<SpacePoint>,
+    sp: Vec<SpacePoint>,
     min_num_points_per_cluster: usize,
     rho_bins: u32,
     theta_bins: u32,
@@ -29,7 +30,11 @@ pub(crate) fn cluster_spacepoints(
         theta_bins,
         accumulator: IndexMap::new(),
     };
-    for &point in sp.iter() {
+    // The conformal transformation is undefined for points at `r = 0`, and
+    // the accumulator relies on points being equal to themselves to remove
+    // them (i.e. no NaNs). These points are never clustered, so they just end
+    // up in the remainder.
+    for &point in sp.iter().filter(|p| is_clusterable(p)) {
         accumulator.add(point);
     }
     // Given an accumulator in a particular state, identify the best cluster of
@@ -69,22 +74,53 @@ pub(crate) fn cluster_spacepoints(
             break;
         }
 
-        clusters.push(Cluster(cluster));
+        clusters.push(cluster);
     }
+    // Recover the index in the input of each clustered point. Identical points
+    // are interchangeable, so just take the first one that is still unused.
+    let mut used = vec![false; sp.len()];
+    let clusters = clusters
+        .into_iter()
+        .map(|cluster| {
+            let indices = cluster
+                .iter()
+                .map(|&point| {
+                    // All points clustered are guaranteed to come from the
+                    // original set of SpacePoints; hence it is safe to unwrap.
+                    let index = sp
+                        .iter()
+                        .zip(used.iter())
+                        .position(|(&p, &used)| !used && p == point)
+                        .unwrap();
+                    used[index] = true;
+                    index
+                })
+                .collect();
+
+            Cluster(cluster, indices)
+        })
+        .collect();
     // The remainder is the set of points that were not clustered.
-    for &point in clusters.iter().flatten() {
-        // All points clustered are guaranteed to come from the original set of
-        // SpacePoints; hence it is safe to unwrap.
-        let index = sp.iter().position(|&p| p == point).unwrap();
-        sp.swap_remove(index);
-    }
+    let (remainder_indices, remainder) = sp
+        .into_iter()
+        .enumerate()
+        .filter(|&(index, _)| !used[index])
+        .unzip();
 
     ClusteringResult {
         clusters,
-        remainder: sp,
+        remainder,
+        remainder_indices,
     }
 }
 
+fn is_clusterable(point: &SpacePoint) -> bool {
+    point.r.is_finite()
+        && point.r.get::<meter>() > 0.0
+        && point.phi.is_finite()
+        && point.z.is_finite()
+}
+
 // The maximum possible `rho` in Hough space is the maximum distance from the
 // origin to any point in the u-v plane.
 const RHO_MAX: ReciprocalLength = ReciprocalLength {
@@ -139,7 +175,11 @@ impl HoughSpaceAccumulator {
             // of rho and different theta.
             if !rho_bin.is_negative() || !prev_rho_bin.is_negative() {
                 let min_bin = prev_rho_bin.min(rho_bin);
-                let max_bin = prev_rho_bin.max(rho_bin);
+                // Points inside the inner cathode can have a `rho` larger than
+                // `RHO_MAX`. Votes beyond the last bin can't be part of any
+                // track, and a point very close to the origin would otherwise
+                // vote for billions of bins.
+                let max_bin = prev_rho_bin.max(rho_bin).min(self.rho_bins as i32);
                 for bin in min_bin.max(0)..=max_bin {
                     bins.push((theta_bin - 1, bin.try_into().unwrap()));
                 }
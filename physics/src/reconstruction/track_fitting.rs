@@ -1,12 +1,13 @@
 use crate::reconstruction::{
-    angle_between_vectors, Cluster, Coordinate, Helix, Track, TryTrackFromClusterError,
+    angle_between_vectors, Cluster, Coordinate, Helix, Line, Model, StraightLineFit, Track,
+    TryTrackFromClusterError,
 };
 use crate::SpacePoint;
 use argmin::core::{CostFunction, Error, Executor};
 use argmin::solver::neldermead::NelderMead;
 use itertools::Itertools;
 use num_complex::Complex;
-use std::f64::consts::PI;
+use std::f64::consts::{LN_2, PI};
 use uom::si::angle::radian;
 use uom::si::area::square_meter;
 use uom::si::f64::{Angle, Area, Length};
@@ -32,9 +33,6 @@ pub(crate) fn fit_cluster_to_helix(
     closest_t_tolerance: f64,
 ) -> Result<Track, TryTrackFromClusterError> {
     let sp = cluster.0;
-    // This assert is here just to make sure we don't accidentally change the
-    // minimum number of points required in a cluster.
-    assert!(sp.len() >= 3);
     // Three points are enough to get a reasonable first guess for the helix
     // parameters.
     let (first, middle, last) = three_template_points(&sp)?;
@@ -94,6 +92,7 @@ pub(crate) fn fit_cluster_to_helix(
         initial_simplex.push(new_point);
     }
 
+    let num_points = sp.len();
     let problem = Problem {
         points: sp,
         tolerance: closest_t_tolerance,
@@ -105,8 +104,12 @@ pub(crate) fn fit_cluster_to_helix(
     let res = Executor::new(problem, solver)
         .configure(|state| state.max_iters(max_num_solver_iter))
         .run()
-        .unwrap();
-    let best_params = res.state.best_param.unwrap();
+        .map_err(|_| TryTrackFromClusterError::FailedFit)?;
+    let best_params = res
+        .state
+        .best_param
+        .filter(|params| params.iter().all(|p| p.is_finite()))
+        .ok_or(TryTrackFromClusterError::FailedFit)?;
     // Again, remember that the f64s in the `cost_function` are in `METER` and
     // `RADIAN`.
     let helix = Helix {
@@ -118,12 +121,209 @@ pub(crate) fn fit_cluster_to_helix(
         h: Length::new::<meter>(best_params[5]),
     };
     Ok(Track {
-        helix,
+        model: Model::Helix(helix),
         t_inner: helix.closest_t(first, closest_t_tolerance, max_num_closest_t_iter),
         t_outer: helix.closest_t(last, closest_t_tolerance, max_num_closest_t_iter),
+        num_points,
+        // The cost is the sum of squared distances in `SQUARE_METER`.
+        rms_residual: Length::new::<meter>((res.state.best_cost / num_points as f64).sqrt()),
+    })
+}
+
+// Without a magnetic field, the full track is a straight line.
+// The line that minimizes the orthogonal distance to the SpacePoints goes
+// through their center of mass, and its direction is the eigenvector of the
+// covariance matrix with the largest eigenvalue. This is found with power
+// iteration, starting from the direction between the innermost and outermost
+// points (which is already a very good guess).
+pub(crate) fn fit_cluster_to_line(
+    cluster: Cluster,
+    max_num_iter: usize,
+    // Stop whenever no component of the (unit) direction changes by more than
+    // this value.
+    tolerance: f64,
+) -> Result<Track, TryTrackFromClusterError> {
+    let sp = cluster.0;
+    let line = fit_line(&sp, max_num_iter, tolerance)?;
+    let (&first, &last) = sp.iter().minmax_by_key(|p| p.r).into_option().unwrap();
+
+    let sum_sqr: Area = sp
+        .iter()
+        .map(|&p| norm_sqr(p, line.at(line.closest_t(p))))
+        .sum();
+    Ok(Track {
+        model: Model::Line(line),
+        t_inner: line.closest_t(first),
+        t_outer: line.closest_t(last),
+        num_points: sp.len(),
+        rms_residual: (sum_sqr / sp.len() as f64).sqrt(),
     })
 }
 
+// Orthogonal distance regression of the SpacePoints to a line (see
+// `fit_cluster_to_line`). The direction points from the innermost towards the
+// outermost point.
+fn fit_line(
+    sp: &[SpacePoint],
+    max_num_iter: usize,
+    tolerance: f64,
+) -> Result<Line, TryTrackFromClusterError> {
+    // Same as the helix, we need at least 3 points (otherwise the covariance
+    // matrix is degenerate).
+    if sp.len() < 3 {
+        return Err(TryTrackFromClusterError::NoInitialParameters);
+    }
+    let (&first, &last) = sp.iter().minmax_by_key(|p| p.r).into_option().unwrap();
+    // Work with f64 in `METER`.
+    let initial_guess = [
+        (last.x() - first.x()).get::<meter>(),
+        (last.y() - first.y()).get::<meter>(),
+        (last.z - first.z).get::<meter>(),
+    ];
+    // If all points are at the same `r`, there is no natural orientation for
+    // the line.
+    let Some(mut direction) = normalized(initial_guess) else {
+        return Err(TryTrackFromClusterError::NoInitialParameters);
+    };
+
+    let cm = center_of_mass(sp);
+    let mut covariance = [[0.0; 3]; 3];
+    for p in sp {
+        let d = [
+            (p.x() - cm.x).get::<meter>(),
+            (p.y() - cm.y).get::<meter>(),
+            (p.z - cm.z).get::<meter>(),
+        ];
+        for (row, di) in covariance.iter_mut().zip(d) {
+            for (element, dj) in row.iter_mut().zip(d) {
+                *element += di * dj;
+            }
+        }
+    }
+    for _ in 0..max_num_iter {
+        let product = covariance.map(|row| row.iter().zip(direction).map(|(a, b)| a * b).sum());
+        // The covariance matrix is positive semi-definite, so the direction
+        // never flips. A zero product means that all points are the same.
+        let Some(next) = normalized(product) else {
+            return Err(TryTrackFromClusterError::NoInitialParameters);
+        };
+        let change = next
+            .iter()
+            .zip(direction)
+            .map(|(a, b)| (a - b).abs())
+            .fold(0.0, f64::max);
+        direction = next;
+
+        if change <= tolerance {
+            break;
+        }
+    }
+    // Make the direction point from the inner towards the outer cathode.
+    if direction
+        .iter()
+        .zip(initial_guess)
+        .map(|(a, b)| a * b)
+        .sum::<f64>()
+        < 0.0
+    {
+        direction = direction.map(|u| -u);
+    }
+
+    Ok(Line {
+        point: cm,
+        direction,
+    })
+}
+
+// A through-going track (e.g. a cosmic ray) is a single straight line through
+// all the SpacePoints of an event. A few noise points are enough to pull the
+// orthogonal distance regression away from the track, so the line is fit
+// again after rejecting all points further than `num_sigma` times a robust
+// estimate of the spread of the residuals.
+// The spread is estimated from the median residual of the current inliers.
+// If both transverse components of the residual are Gaussian (with the same
+// standard deviation), the distance to the line follows a Rayleigh
+// distribution with median `sigma * sqrt(2 ln 2)`.
+pub(crate) fn fit_points_to_straight_line(
+    sp: Vec<SpacePoint>,
+    // Maximum number of times that outliers are rejected and the line is fit
+    // again.
+    max_num_rejections: usize,
+    num_sigma: f64,
+    // Lower bound for the spread of the residuals. Otherwise, a (nearly)
+    // perfect fit would reject good points that are just a little bit off.
+    min_sigma: Length,
+    // Same as `fit_cluster_to_line`.
+    max_num_iter: usize,
+    tolerance: f64,
+) -> Option<StraightLineFit> {
+    if sp
+        .iter()
+        .any(|p| !(p.r.is_finite() && p.phi.is_finite() && p.z.is_finite()))
+    {
+        return None;
+    }
+    let residuals = |line: &Line| -> Vec<Length> {
+        sp.iter()
+            .map(|&p| norm_sqr(p, line.at(line.closest_t(p))).sqrt())
+            .collect()
+    };
+
+    let mut inliers = vec![true; sp.len()];
+    let mut line = fit_line(&sp, max_num_iter, tolerance).ok()?;
+    for _ in 0..max_num_rejections {
+        let current = residuals(&line);
+        let mut inlier_residuals: Vec<_> = current
+            .iter()
+            .zip(&inliers)
+            .filter_map(|(&r, &inlier)| inlier.then_some(r))
+            .collect();
+        inlier_residuals.sort_by(|a, b| a.get::<meter>().total_cmp(&b.get::<meter>()));
+        let sigma = inlier_residuals[inlier_residuals.len() / 2] / (2.0 * LN_2).sqrt();
+        let cut = num_sigma * sigma.max(min_sigma);
+
+        let next: Vec<_> = current.iter().map(|&r| r <= cut).collect();
+        if next == inliers {
+            break;
+        }
+        let points: Vec<_> = sp
+            .iter()
+            .zip(&next)
+            .filter_map(|(&p, &inlier)| inlier.then_some(p))
+            .collect();
+        // Keep the previous fit if there are not enough points left to define
+        // a line.
+        let Ok(next_line) = fit_line(&points, max_num_iter, tolerance) else {
+            break;
+        };
+        line = next_line;
+        inliers = next;
+    }
+    // The orientation given by `fit_line` (inner to outer) is meaningless for
+    // a track that crosses the whole detector.
+    if line.direction[2] < 0.0 {
+        line.direction = line.direction.map(|u| -u);
+    }
+
+    Some(StraightLineFit {
+        direction: line.direction,
+        point: line.closest_to_beamline(),
+        residuals: residuals(&line),
+        inliers,
+    })
+}
+
+// Return the unit vector in the same direction. Return `None` if the vector is
+// zero (or not finite).
+fn normalized(v: [f64; 3]) -> Option<[f64; 3]> {
+    let norm = v.iter().map(|x| x.powi(2)).sum::<f64>().sqrt();
+    if norm > 0.0 && norm.is_finite() {
+        Some(v.map(|x| x / norm))
+    } else {
+        None
+    }
+}
+
 // With 3 spread out points, we can get a decent first guess on the helix
 // parameters.
 fn three_template_points(
@@ -134,6 +334,10 @@ fn three_template_points(
     // Return the:
     // (Smallest r, Middle r, Largest r)
 ) -> Result<(SpacePoint, SpacePoint, SpacePoint), TryTrackFromClusterError> {
+    // With fewer points, at least two of the template points are the same.
+    if points.len() < 3 {
+        return Err(TryTrackFromClusterError::NoInitialParameters);
+    }
     let (&first, &last) = points.iter().minmax_by_key(|p| p.r).into_option().unwrap();
 
     let middle_r = (first.r + last.r) / 2.0;
@@ -142,8 +346,8 @@ fn three_template_points(
         .min_by(|a, b| {
             (a.r - middle_r)
                 .abs()
-                .partial_cmp(&(b.r - middle_r).abs())
-                .unwrap()
+                .get::<meter>()
+                .total_cmp(&(b.r - middle_r).abs().get::<meter>())
         })
         .copied()
         .unwrap();
@@ -251,22 +455,23 @@ impl CostFunction for Problem {
             h: Length::new::<meter>(p[5]),
         };
 
-        Ok(self
+        let cost = self
             .points
             .iter()
             .map(|&p| {
                 let t = helix.closest_t(p, self.tolerance, self.max_num_iter);
                 let closest_point = helix.at(t);
 
-                let val = norm_sqr(p, closest_point);
-                // Argmin needs non-NaN values to work properly.
-                // If we got NaN at this point, there is a bug somewhere that
-                // needs to be fixed.
-                assert!(!val.is_nan(), "found NaN in track_fitting::cost_function");
-
-                val
+                norm_sqr(p, closest_point)
             })
             .sum::<Area>()
-            .get::<square_meter>())
+            .get::<square_meter>();
+        // Argmin needs non-NaN values to work properly. Stop the minimization
+        // instead; a degenerate cluster is not worth crashing over.
+        if cost.is_nan() {
+            return Err(Error::msg("found NaN in track_fitting::cost_function"));
+        }
+
+        Ok(cost)
     }
 }
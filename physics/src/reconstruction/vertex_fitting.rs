@@ -1,4 +1,6 @@
-use crate::reconstruction::{Coordinate, Track, VertexInfo, VertexingResult};
+use crate::reconstruction::{
+    BeamConstraint, Coordinate, FindVerticesError, Track, VertexInfo, VertexingResult,
+};
 use crate::SpacePoint;
 use argmin::core::{CostFunction, Error, Executor};
 use argmin::solver::neldermead::NelderMead;
@@ -27,13 +29,12 @@ pub(crate) fn find_vertices(
     // Nelder-Mead stops whenever the standard deviation between the cost at all
     // simplex vertices is below this threshold.
     nelder_mead_sd_tolerance: f64,
-) -> VertexingResult {
+    beam_constraint: Option<BeamConstraint>,
+) -> Result<VertexingResult, FindVerticesError> {
     let primary_tracks = tracks
         .iter()
-        .filter(|track| track.helix.arc_length(track.t_inner(), track.t_outer()) > min_track_length)
-        .filter(|track| {
-            (track.helix.r - track.helix.x0.hypot(track.helix.y0)).abs() < max_track_beamline_dca
-        })
+        .filter(|track| track.model.arc_length(track.t_inner(), track.t_outer()) > min_track_length)
+        .filter(|track| track.model.beamline_dca() < max_track_beamline_dca)
         .copied()
         .collect();
 
@@ -43,11 +44,13 @@ pub(crate) fn find_vertices(
         .max_set_by_key(|(cluster, _)| cluster.len())
         .into_iter()
         .max_by(|(c_a, _), (c_b, _)| {
-            c_a.iter()
-                .map(|track| track.helix.r)
-                .sum::<Length>()
-                .partial_cmp(&c_b.iter().map(|track| track.helix.r).sum::<Length>())
-                .unwrap()
+            let radii = |c: &Vec<Track>| {
+                c.iter()
+                    .map(|track| track.model.radius())
+                    .sum::<Length>()
+                    .get::<meter>()
+            };
+            radii(c_a).total_cmp(&radii(c_b))
         })
         .map(|(tracks, mean_z)| {
             // Argmin needs all parameters to be same type. Work with internal
@@ -55,63 +58,85 @@ pub(crate) fn find_vertices(
             // It has to be in `METER` because that is what the `cost_function`
             // expects internally.
             let initial_guess = vec![0.0, 0.0, mean_z.get::<meter>()];
-            let mut initial_simplex = vec![initial_guess.clone()];
-            for i in 0..initial_guess.len() {
-                let mut new_point = initial_guess.clone();
-                if new_point[i] == 0.0 {
-                    // Default value from scipy's implementation.
-                    // I don't think this is important enough to make it a parameter.
-                    new_point[i] = 0.00025;
-                } else {
-                    new_point[i] *= 1.0 + initial_simplex_delta;
-                }
-                initial_simplex.push(new_point);
-            }
-
             let problem = Problem {
                 tracks: tracks.clone(),
                 tolerance: closest_t_tolerance,
                 max_num_iter: max_num_closest_t_iter,
+                beam_weight: None,
             };
-            let solver = NelderMead::new(initial_simplex)
-                .with_sd_tolerance(nelder_mead_sd_tolerance)
-                .unwrap();
-            let res = Executor::new(problem, solver)
-                .configure(|state| state.max_iters(max_num_solver_iter))
-                .run()
-                .unwrap();
-            let best_params = res.state.best_param.unwrap();
+            let (best_params, best_cost) = minimize(
+                problem,
+                initial_guess,
+                initial_simplex_delta,
+                nelder_mead_sd_tolerance,
+                max_num_solver_iter,
+            )?;
             // Again, remember that the f64s in the `cost_function` are in
             // `METER`
-            let position = Coordinate {
+            let mut position = Coordinate {
                 x: Length::new::<meter>(best_params[0]),
                 y: Length::new::<meter>(best_params[1]),
                 z: Length::new::<meter>(best_params[2]),
             };
 
-            VertexInfo {
-                position,
-                tracks: tracks
-                    .into_iter()
-                    .map(|track| {
-                        // There is already a method in `Track` to calculate the
-                        // closest_t to a SpacePoint. Just use that.
-                        let sp = SpacePoint {
-                            r: position.x.hypot(position.y),
-                            phi: position.y.atan2(position.x),
-                            z: position.z,
-                        };
-
-                        let t =
-                            track
-                                .helix
-                                .closest_t(sp, closest_t_tolerance, max_num_closest_t_iter);
-
-                        (track, t)
-                    })
-                    .collect(),
+            // The cost function is a sum of squared distances, i.e. a chi2
+            // (times sigma^2) with an unknown track resolution. The same
+            // estimate is used to weight the beam constraint and for the
+            // covariance of the vertex.
+            let sigma_sqr = track_sigma_sqr(&tracks, best_cost);
+            let mut unconstrained_position = None;
+            let mut beam_weight = None;
+            if let Some(BeamConstraint { r_sigma }) = beam_constraint {
+                beam_weight = Some(sigma_sqr / r_sigma.get::<meter>().powi(2));
+                let problem = Problem {
+                    tracks: tracks.clone(),
+                    tolerance: closest_t_tolerance,
+                    max_num_iter: max_num_closest_t_iter,
+                    beam_weight,
+                };
+                let (best_params, _) = minimize(
+                    problem,
+                    best_params,
+                    initial_simplex_delta,
+                    nelder_mead_sd_tolerance,
+                    max_num_solver_iter,
+                )?;
+
+                unconstrained_position = Some(position);
+                position = Coordinate {
+                    x: Length::new::<meter>(best_params[0]),
+                    y: Length::new::<meter>(best_params[1]),
+                    z: Length::new::<meter>(best_params[2]),
+                };
             }
-        });
+
+            let tracks: Vec<_> = tracks
+                .into_iter()
+                .map(|track| {
+                    // There is already a method in `Track` to calculate the
+                    // closest_t to a SpacePoint. Just use that.
+                    let sp = SpacePoint {
+                        r: position.x.hypot(position.y),
+                        phi: position.y.atan2(position.x),
+                        z: position.z,
+                    };
+
+                    let t = track
+                        .model
+                        .closest_t(sp, closest_t_tolerance, max_num_closest_t_iter);
+
+                    (track, t)
+                })
+                .collect();
+
+            Ok(VertexInfo {
+                position,
+                unconstrained_position,
+                covariance: covariance(&tracks, sigma_sqr, beam_weight),
+                tracks,
+            })
+        })
+        .transpose()?;
     // The remainder is the set of tracks that are not associated with the
     // primary vertex.
     for (track, _) in vertex.iter().flat_map(|v| v.tracks.iter()) {
@@ -121,11 +146,133 @@ pub(crate) fn find_vertices(
         tracks.swap_remove(index);
     }
 
-    VertexingResult {
+    Ok(VertexingResult {
         primary: vertex,
         secondaries: Vec::new(),
         remainder: tracks,
+    })
+}
+
+// Minimize the cost function with Nelder-Mead, starting from the given initial
+// guess. Return the best parameters and their cost.
+fn minimize(
+    problem: Problem,
+    initial_guess: Vec<f64>,
+    initial_simplex_delta: f64,
+    nelder_mead_sd_tolerance: f64,
+    max_num_solver_iter: u64,
+) -> Result<(Vec<f64>, f64), FindVerticesError> {
+    let mut initial_simplex = vec![initial_guess.clone()];
+    for i in 0..initial_guess.len() {
+        let mut new_point = initial_guess.clone();
+        if new_point[i] == 0.0 {
+            // Default value from scipy's implementation.
+            // I don't think this is important enough to make it a parameter.
+            new_point[i] = 0.00025;
+        } else {
+            new_point[i] *= 1.0 + initial_simplex_delta;
+        }
+        initial_simplex.push(new_point);
+    }
+
+    let solver = NelderMead::new(initial_simplex)
+        .with_sd_tolerance(nelder_mead_sd_tolerance)
+        .unwrap();
+    let res = Executor::new(problem, solver)
+        .configure(|state| state.max_iters(max_num_solver_iter))
+        .run()
+        .map_err(|_| FindVerticesError::FailedFit)?;
+    let best_params = res
+        .state
+        .best_param
+        .filter(|params| params.iter().all(|p| p.is_finite()))
+        .ok_or(FindVerticesError::FailedFit)?;
+
+    Ok((best_params, res.state.best_cost))
+}
+
+// Unit vector tangent to a track at a given `t`.
+fn tangent(track: &Track, t: f64) -> Option<[f64; 3]> {
+    // Central difference. The parameter `t` is either an angle (helix) or a
+    // length (line), and both are smooth enough for this step size.
+    let delta = 1e-6;
+    let a = track.at(t - delta);
+    let b = track.at(t + delta);
+    let d = [
+        (b.x - a.x).get::<meter>(),
+        (b.y - a.y).get::<meter>(),
+        (b.z - a.z).get::<meter>(),
+    ];
+    let norm = d.iter().map(|x| x * x).sum::<f64>().sqrt();
+
+    (norm > 0.0 && norm.is_finite()).then(|| d.map(|x| x / norm))
+}
+
+fn invert(m: [[f64; 3]; 3]) -> Option<[[f64; 3]; 3]> {
+    let cofactor = |i: usize, j: usize| {
+        let (r0, r1) = ((i + 1) % 3, (i + 2) % 3);
+        let (c0, c1) = ((j + 1) % 3, (j + 2) % 3);
+        m[r0][c0] * m[r1][c1] - m[r0][c1] * m[r1][c0]
+    };
+    let det = (0..3).map(|j| m[0][j] * cofactor(0, j)).sum::<f64>();
+    // The matrices inverted here are sums of projectors, so their trace is a
+    // good measure of their scale.
+    let trace = m[0][0] + m[1][1] + m[2][2];
+    if !(det.is_finite() && det > 1e-9 * trace.powi(3)) {
+        return None;
     }
+    // The inverse is the transpose of the cofactor matrix over the
+    // determinant.
+    Some([0, 1, 2].map(|i| [0, 1, 2].map(|j| cofactor(j, i) / det)))
+}
+
+// Squared resolution of the tracks of a vertex, estimated from the minimum of
+// the unconstrained cost function.
+fn track_sigma_sqr(tracks: &[Track], best_cost: f64) -> f64 {
+    // Each track constrains the 2 directions perpendicular to it.
+    let dof = (2 * tracks.len()).saturating_sub(3).max(1) as f64;
+    // The residuals of a two track vertex can be arbitrarily small (e.g. the
+    // tracks happen to intersect). Never assume a better resolution than the
+    // uncertainty of the position of the tracks themselves.
+    let track_resolution_sqr = tracks
+        .iter()
+        .map(|track| track.rms_residual.get::<meter>().powi(2) / track.num_points.max(1) as f64)
+        .sum::<f64>()
+        / tracks.len() as f64;
+
+    (best_cost / dof).max(track_resolution_sqr)
+}
+
+// Covariance of the vertex position given the tracks (and their closest `t`
+// to the vertex), the squared track resolution (see `track_sigma_sqr`), and the
+// weight of the beam constraint (if any).
+//
+// Near the vertex, each track is approximately a straight line with direction
+// `u`, and its squared distance to the vertex is `|(I - u u^T) dv|^2`. Then,
+// with a track resolution `sigma`, the covariance is
+// `sigma^2 (sum_i (I - u_i u_i^T) + beam_weight diag(1, 1, 0))^-1`.
+fn covariance(
+    tracks: &[(Track, f64)],
+    sigma_sqr: f64,
+    beam_weight: Option<f64>,
+) -> Option<[[Area; 3]; 3]> {
+    let mut matrix = [[0.0; 3]; 3];
+    for (track, t) in tracks {
+        let u = tangent(track, *t)?;
+        for (i, row) in matrix.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                let identity = if i == j { 1.0 } else { 0.0 };
+                *value += identity - u[i] * u[j];
+            }
+        }
+    }
+    if let Some(weight) = beam_weight {
+        matrix[0][0] += weight;
+        matrix[1][1] += weight;
+    }
+    let inverse = invert(matrix)?;
+
+    Some(inverse.map(|row| row.map(|v| Area::new::<square_meter>(sigma_sqr * v))))
 }
 
 // Cluster tracks by the `z` coordinate of their closest approach to the
@@ -141,22 +288,19 @@ fn beamline_clusters(
     }
 
     tracks.sort_unstable_by(|a, b| {
-        a.helix
-            .closest_to_beamline()
-            .z
-            .partial_cmp(&b.helix.closest_to_beamline().z)
-            .unwrap()
+        let z = |track: &Track| track.model.closest_to_beamline().z.get::<meter>();
+        z(a).total_cmp(&z(b))
     });
 
     let mut clusters = vec![vec![tracks[0]]];
     for track in tracks.into_iter().skip(1) {
-        let current_z = track.helix.closest_to_beamline().z;
+        let current_z = track.model.closest_to_beamline().z;
         let last_z = clusters
             .last()
             .unwrap()
             .last()
             .unwrap()
-            .helix
+            .model
             .closest_to_beamline()
             .z;
 
@@ -172,7 +316,7 @@ fn beamline_clusters(
         .map(|tracks| {
             let z = tracks
                 .iter()
-                .map(|track| track.helix.closest_to_beamline().z)
+                .map(|track| track.model.closest_to_beamline().z)
                 .sum::<Length>()
                 / tracks.len() as f64;
             (tracks, z)
@@ -184,9 +328,11 @@ fn beamline_clusters(
 // sum of squared distances to all tracks.
 struct Problem {
     tracks: Vec<Track>,
-    // Parameters required to calculate the distance between a point and a helix.
+    // Parameters required to calculate the distance between a point and a track.
     tolerance: f64,
     max_num_iter: usize,
+    // If set, add `beam_weight * r^2` to the cost (pull towards the beamline).
+    beam_weight: Option<f64>,
 }
 
 // Calculate the squared distance between a SpacePoint and a Coordinate.
@@ -217,22 +363,26 @@ impl CostFunction for Problem {
             z,
         };
 
-        Ok(self
+        let cost = self
             .tracks
             .iter()
             .map(|track| {
-                let t = track.helix.closest_t(sp, self.tolerance, self.max_num_iter);
+                let t = track.model.closest_t(sp, self.tolerance, self.max_num_iter);
                 let closest_point = track.at(t);
 
-                let val = norm_sqr(sp, closest_point);
-                // Argmin needs non-NaN values to work properly.
-                // If we got NaN at this point, there is a bug somewhere that
-                // needs to be fixed.
-                assert!(!val.is_nan(), "found NaN in vertex_fitting::cost_function");
-
-                val
+                norm_sqr(sp, closest_point)
             })
             .sum::<Area>()
-            .get::<square_meter>())
+            .get::<square_meter>();
+        // Argmin needs non-NaN values to work properly. Stop the minimization
+        // instead; the event just doesn't get a vertex.
+        if cost.is_nan() {
+            return Err(Error::msg("found NaN in vertex_fitting::cost_function"));
+        }
+
+        match self.beam_weight {
+            Some(weight) => Ok(cost + weight * (p[0].powi(2) + p[1].powi(2))),
+            None => Ok(cost),
+        }
     }
 }
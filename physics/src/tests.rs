@@ -1,7 +1,12 @@
 use super::*;
+use crate::reconstruction::TrackModel;
+use alpha_g_detector::padwing::map::{
+    TpcPwbPosition, CATHODE_PADS_RADIUS, TPC_PWB_COLUMNS, TPC_PWB_ROWS,
+};
 use uom::si::angle::radian;
 use uom::si::frequency::megahertz;
-use uom::si::length::millimeter;
+use uom::si::length::{meter, millimeter};
+use uom::si::time::microsecond;
 
 #[test]
 fn anode_wires_radius() {
@@ -66,3 +71,1276 @@ fn spacepoint_distance() {
     let diff = p1.distance(p3) - Length::new::<millimeter>(5.0);
     assert!(diff.abs() < Length::new::<millimeter>(1e-6));
 }
+
+#[test]
+fn drift_window_stats_empty() {
+    let max_drift_time = Time::new::<microsecond>(5.0);
+    let start = Time::new::<microsecond>(2.0);
+    assert_eq!(
+        drift_window_stats(&[], max_drift_time, start),
+        (None, None, None)
+    );
+}
+
+#[test]
+fn drift_window_stats_single() {
+    let max_drift_time = Time::new::<microsecond>(5.0);
+    let t = Time::new::<microsecond>(1.0);
+
+    let start = Time::new::<microsecond>(2.0);
+    let (t_min, t_max, late_fraction) = drift_window_stats(&[t], max_drift_time, start);
+    assert_eq!(t_min, Some(Time::new::<microsecond>(3.0)));
+    assert_eq!(t_max, Some(Time::new::<microsecond>(3.0)));
+    assert_eq!(late_fraction, Some(0.0));
+}
+
+#[test]
+fn drift_window_stats_late_fraction() {
+    let max_drift_time = Time::new::<microsecond>(5.0);
+    let times = [3.0, 4.6, 0.5, 4.9, 2.0].map(Time::new::<microsecond>);
+
+    let start = Time::new::<microsecond>(0.0);
+    let (t_min, t_max, late_fraction) = drift_window_stats(&times, max_drift_time, start);
+    assert_eq!(t_min, Some(Time::new::<microsecond>(0.5)));
+    assert_eq!(t_max, Some(Time::new::<microsecond>(4.9)));
+    assert_eq!(late_fraction, Some(0.4));
+}
+
+// Wire signals only in the first few pad columns (to keep the test fast), and
+// pad signals everywhere. Pad amplitudes have a local maximum every few rows
+// such that there are plenty of pad hits to match.
+fn busy_main_event() -> MainEvent {
+    let pulse = |t0: f64, amplitude: f64| -> Option<Vec<f64>> {
+        Some(
+            (0..400)
+                .map(|i| {
+                    let x = (f64::from(i) - t0) / 8.0;
+                    amplitude * (-x * x).exp()
+                })
+                .collect(),
+        )
+    };
+
+    MainEvent {
+        wire_signals: std::array::from_fn(|i| {
+            if wire_to_pad_column(i) < 4 {
+                pulse(100.0 + (i % 5) as f64 * 30.0, 1000.0)
+            } else {
+                None
+            }
+        }),
+        wire_clipped: std::array::from_fn(|_| Vec::new()),
+        wire_signal_start: Time::new::<second>(0.0),
+        pad_signals: std::array::from_fn(|_| {
+            std::array::from_fn(|row| pulse(80.0, -100.0 * [1.0, 2.0, 4.0, 2.0, 1.0][row % 5]))
+        }),
+        trigger_timestamp: 0,
+        misbuilt: None,
+        fpn_signals: None,
+        bv_signals: None,
+        wire_phi_offset: Angle::new::<radian>(0.0),
+        pad_column_offsets: [0; TPC_PAD_COLUMNS],
+        mirror_phi: false,
+        mirror_z: false,
+        field: FieldConfiguration::On,
+    }
+}
+
+// The wire alignment offset has to rigidly rotate everything around the
+// detector axis.
+#[test]
+fn wire_phi_offset_rotates_avalanches() {
+    let event = busy_main_event();
+    let offset = 0.01;
+    let rotated = MainEvent {
+        wire_phi_offset: Angle::new::<radian>(offset),
+        ..event.clone()
+    };
+
+    let avalanches = event.avalanches();
+    let rotated_avalanches = rotated.avalanches();
+    assert!(!avalanches.is_empty());
+    assert_eq!(avalanches.len(), rotated_avalanches.len());
+
+    let mut spacepoints = 0;
+    for (avalanche, rotated_avalanche) in avalanches.into_iter().zip(rotated_avalanches) {
+        assert_eq!(avalanche.t, rotated_avalanche.t);
+        assert_eq!(avalanche.z, rotated_avalanche.z);
+        assert_eq!(avalanche.wire_amplitude, rotated_avalanche.wire_amplitude);
+        assert_eq!(avalanche.pad_amplitude, rotated_avalanche.pad_amplitude);
+        let delta = (rotated_avalanche.phi - avalanche.phi).get::<radian>();
+        assert!((delta - offset).abs() < 1e-12);
+
+        let (Ok(point), Ok(rotated_point)) = (
+            SpacePoint::try_from(avalanche),
+            SpacePoint::try_from(rotated_avalanche),
+        ) else {
+            continue;
+        };
+        spacepoints += 1;
+        let (x, y) = (point.x().get::<millimeter>(), point.y().get::<millimeter>());
+        let expected_x = x * offset.cos() - y * offset.sin();
+        let expected_y = x * offset.sin() + y * offset.cos();
+        assert!((rotated_point.x().get::<millimeter>() - expected_x).abs() < 1e-9);
+        assert!((rotated_point.y().get::<millimeter>() - expected_y).abs() < 1e-9);
+        assert_eq!(point.z, rotated_point.z);
+    }
+    assert!(spacepoints > 0);
+}
+
+// The signal windows have to be consistent with the reconstruction (i.e. no
+// avalanche is reconstructed where no signal is expected).
+#[test]
+fn avalanches_within_signal_windows() {
+    let (wire_start, wire_end) = detector_response::wire_signal_window();
+    let (pad_start, pad_end) = detector_response::pad_signal_window();
+
+    let avalanches = busy_main_event().avalanches();
+    assert!(!avalanches.is_empty());
+    for avalanche in avalanches {
+        assert!(wire_start <= avalanche.t && avalanche.t <= wire_end);
+        assert!(pad_start <= avalanche.t && avalanche.t <= pad_end);
+    }
+}
+
+// Regardless of the `rayon` feature, the output has to be the same as just
+// processing each column one after the other.
+#[test]
+fn avalanches_same_as_serial_columns() {
+    let event = busy_main_event();
+
+    let (pad_columns, wire_inputs) = event.deconvolved_wires();
+    let serial: Vec<_> = pad_columns
+        .into_iter()
+        .flat_map(|column| event.column_avalanches(column, &wire_inputs))
+        .collect();
+    assert_eq!(event.avalanches(), serial);
+}
+
+#[test]
+fn misbuilt_event_consistent() {
+    assert_eq!(MisbuiltEvent::try_new(&[], &[]), None);
+
+    let a09 = alpha16::BoardId::try_from("09").unwrap();
+    let a10 = alpha16::BoardId::try_from("10").unwrap();
+    let p00 = padwing::BoardId::try_from("00").unwrap();
+    let p01 = padwing::BoardId::try_from("01").unwrap();
+    // The counters of different board types are not compared.
+    assert_eq!(
+        MisbuiltEvent::try_new(&[(a09, 7), (a09, 7), (a10, 7)], &[(p00, 3), (p01, 3)]),
+        None
+    );
+}
+
+#[test]
+fn misbuilt_event_inconsistent() {
+    let a09 = alpha16::BoardId::try_from("09").unwrap();
+    let a10 = alpha16::BoardId::try_from("10").unwrap();
+    let a11 = alpha16::BoardId::try_from("11").unwrap();
+    let p00 = padwing::BoardId::try_from("00").unwrap();
+    let p01 = padwing::BoardId::try_from("01").unwrap();
+    let p02 = padwing::BoardId::try_from("02").unwrap();
+
+    let misbuilt = MisbuiltEvent::try_new(
+        &[(a11, 8), (a09, 7), (a10, 7), (a11, 7), (a09, 7)],
+        &[(p02, 4), (p00, 3), (p01, 3), (p00, 3)],
+    )
+    .unwrap();
+    assert_eq!(misbuilt.alpha16_boards, [a11]);
+    assert_eq!(misbuilt.padwing_boards, [p02]);
+
+    let misbuilt = MisbuiltEvent::try_new(&[(a09, 7)], &[(p01, 4), (p00, 3)]).unwrap();
+    assert!(misbuilt.alpha16_boards.is_empty());
+    // Ties are broken by the smallest counter.
+    assert_eq!(misbuilt.padwing_boards, [p01]);
+}
+
+#[test]
+fn truncated_waveforms_none() {
+    let event = busy_main_event();
+    let truncated = event.truncated_waveforms();
+
+    assert!(truncated.wires.is_empty());
+    assert!(truncated.pads.is_empty());
+    assert_eq!(truncated.count(), 0);
+    assert_eq!(truncated.fraction(), 0.0);
+    assert!(truncated.num_channels > 0);
+}
+
+#[test]
+fn truncated_waveforms_single_channel() {
+    let mut event = busy_main_event();
+    let wire_index = event.wire_signals.iter().position(Option::is_some).unwrap();
+    event.wire_signals[wire_index]
+        .as_mut()
+        .unwrap()
+        .truncate(250);
+    event.pad_signals[3][7].as_mut().unwrap().truncate(100);
+    let truncated = event.truncated_waveforms();
+
+    assert_eq!(
+        truncated.wires,
+        [TpcWirePosition::try_from(wire_index).unwrap()]
+    );
+    assert_eq!(
+        truncated.pads,
+        [TpcPadPosition {
+            column: 3usize.try_into().unwrap(),
+            row: 7usize.try_into().unwrap(),
+        }]
+    );
+    assert_eq!(truncated.count(), 2);
+    assert_eq!(truncated.fraction(), 2.0 / truncated.num_channels as f64);
+}
+
+#[test]
+fn truncated_waveforms_globally_shorter() {
+    let mut event = busy_main_event();
+    for signal in event.wire_signals.iter_mut().flatten() {
+        signal.truncate(250);
+    }
+    for signal in event.pad_signals.iter_mut().flatten().flatten() {
+        signal.truncate(100);
+    }
+
+    // All channels agree on the (shorter) length.
+    assert_eq!(event.truncated_waveforms().count(), 0);
+}
+
+#[test]
+fn expected_length_majority() {
+    assert_eq!(expected_length([]), None);
+    assert_eq!(expected_length([10, 5, 10]), Some(10));
+    assert_eq!(expected_length([5, 10, 5]), Some(5));
+    // Ties are broken by the longest length.
+    assert_eq!(expected_length([5, 10]), Some(10));
+}
+
+// Board 00, AFTER D. Channels sent (readout index): 57 (pad), 67 (FPN 4), and
+// 73 (pad).
+const PWB_CHUNK_WITH_FPN: [u8; 128] = [
+    236, 40, 255, 135, 2, 0, 0, 0, 2, 0, 3, 1, 0, 0, 104, 0, 240, 152, 78, 132, 2, 68, 0, 0, 236,
+    40, 255, 135, 84, 2, 1, 0, 2, 0, 0, 0, 0, 0, 0, 0, 3, 0, 5, 0, 0, 0, 0, 0, 0, 0, 0, 1, 4, 1, 1,
+    1, 1, 0, 0, 0, 0, 0, 0, 0, 4, 0, 0, 0, 5, 0, 6, 7, 57, 0, 5, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10,
+    0, 0, 67, 0, 5, 0, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 0, 0, 73, 0, 5, 0, 21, 22, 23, 24,
+    25, 26, 27, 28, 29, 30, 0, 0, 204, 204, 204, 204, 183, 97, 50, 1,
+];
+
+const TRG_V3_PACKET: [u8; 80] = [
+    255, 0, 0, 0, 0, 0, 0, 128, 254, 0, 0, 0, 0, 0, 0, 0, 3, 0, 0, 0, 4, 0, 0, 0, 5, 0, 0, 0, 6, 0,
+    0, 0, 7, 0, 0, 0, 8, 0, 0, 128, 2, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 9, 0, 10, 0, 11, 0, 0, 0,
+    0, 0, 0, 0, 12, 0, 0, 0, 13, 0, 0, 0, 14, 0, 0, 0, 0, 0, 0, 224,
+];
+
+#[test]
+fn main_event_fpn_signals_dropped_by_default() {
+    let banks = [
+        ("PC00", &PWB_CHUNK_WITH_FPN[..]),
+        ("ATAT", &TRG_V3_PACKET[..]),
+    ];
+    let event = MainEvent::try_from_banks(RunNumber::SIMULATION, banks).unwrap();
+
+    assert!(event.fpn_signals().is_none());
+}
+
+#[test]
+fn main_event_fpn_signals_retained() {
+    let banks = [
+        ("PC00", &PWB_CHUNK_WITH_FPN[..]),
+        ("ATAT", &TRG_V3_PACKET[..]),
+    ];
+    let options = DecodeOptions {
+        keep_fpn: true,
+        ..Default::default()
+    };
+    let (event, _) = decode(banks, options).unwrap();
+
+    let fpn_signals = event.fpn_signals().unwrap();
+    assert_eq!(fpn_signals.len(), 1);
+    let key = (
+        padwing::BoardId::try_from("00").unwrap(),
+        AfterId::D,
+        FpnChannelId::try_from(4).unwrap(),
+    );
+    assert_eq!(fpn_signals[&key], [3083, 3597, 4111, 4625, 5139]);
+}
+
+// Convert the banks with the calibration of the simulation.
+fn decode<'a, I>(
+    banks: I,
+    options: DecodeOptions,
+) -> Result<(MainEvent, Vec<TryMainEventFromDataBanksError>), TryMainEventFromDataBanksError>
+where
+    I: IntoIterator<Item = (&'a str, &'a [u8])>,
+{
+    let calibration = RunCalibration::new(RunNumber::SIMULATION);
+    MainEvent::try_from_banks_with(&calibration, banks, &options)
+}
+
+const KEEP_BV: DecodeOptions = DecodeOptions {
+    lossy: false,
+    keep_fpn: false,
+    keep_bv: true,
+    strict: false,
+};
+
+const LOSSY: DecodeOptions = DecodeOptions {
+    lossy: true,
+    keep_fpn: false,
+    keep_bv: false,
+    strict: false,
+};
+
+// Barrel Veto packet from board 18 without data suppression.
+fn bv_packet(channel: u8, waveform: &[i16]) -> Vec<u8> {
+    let baseline = waveform[..64]
+        .iter()
+        .map(|&v| i32::from(v))
+        .sum::<i32>()
+        .div_euclid(64);
+    let mut packet = vec![1, 3, 0, 1, 2, channel];
+    packet.extend_from_slice(&u16::try_from(waveform.len() + 2).unwrap().to_be_bytes());
+    packet.extend_from_slice(&[
+        0, 0, 0, 4, 0, 0, 216, 128, 57, 104, 142, 82, 0, 0, 0, 0, 0, 0, 0, 5, 0, 0, 0, 6,
+    ]);
+    packet.extend(waveform.iter().flat_map(|v| v.to_be_bytes()));
+    packet.extend_from_slice(&[0, 0]);
+    packet.extend_from_slice(&i16::try_from(baseline).unwrap().to_be_bytes());
+    packet
+}
+
+// Barrel Veto packet (channel 4) with an empty waveform after data
+// suppression.
+const SUPPRESSED_BV_PACKET: [u8; 16] = [1, 3, 0, 1, 2, 4, 2, 187, 0, 0, 0, 4, 224, 0, 0, 0];
+
+#[test]
+fn main_event_bv_signals() {
+    let waveform: Vec<i16> = (0..100).map(|i| if i < 64 { 10 } else { 110 }).collect();
+    let read_out = bv_packet(3, &waveform);
+    let banks = [
+        ("B183", &read_out[..]),
+        ("B184", &SUPPRESSED_BV_PACKET[..]),
+        ("ATAT", &TRG_V3_PACKET[..]),
+    ];
+    let (event, _) = decode(banks, KEEP_BV).unwrap();
+
+    let board_id = alpha16::BoardId::try_from("18").unwrap();
+    let key = |channel: u8| (board_id, Adc16ChannelId::try_from(channel).unwrap());
+    let bv_signals = event.bv_signals().unwrap();
+    assert_eq!(bv_signals.len(), 2);
+    assert_eq!(bv_signals[&key(3)][..64], [0.0; 64]);
+    assert_eq!(bv_signals[&key(3)][64..], [100.0; 36]);
+    assert!(bv_signals[&key(4)].is_empty());
+    assert!(!bv_signals.contains_key(&key(5)));
+    // The BV has nothing to do with the TPC.
+    assert!(event.wire_signals.iter().all(Option::is_none));
+}
+
+#[test]
+fn main_event_bv_signals_ignored_by_default() {
+    let read_out = bv_packet(3, &[0; 100]);
+    let wire = bv_packet(128 + 3, &[0; 100]);
+    let banks = [
+        ("B183", &read_out[..]),
+        ("B183", &read_out[..]),
+        ("B093", &read_out[..]),
+        ("B184", &wire[..]),
+        ("B185", &[0; 3][..]),
+        ("ATAT", &TRG_V3_PACKET[..]),
+    ];
+    let event = MainEvent::try_from_banks(RunNumber::SIMULATION, banks).unwrap();
+
+    assert!(event.bv_signals().is_none());
+    // BV packets don't take part in the event builder consistency check.
+    assert!(event.misbuilt().is_none());
+}
+
+#[test]
+fn main_event_bv_signals_errors() {
+    let read_out = bv_packet(3, &[0; 100]);
+    let wire = bv_packet(128 + 3, &[0; 100]);
+    let trg = ("ATAT", &TRG_V3_PACKET[..]);
+
+    assert!(matches!(
+        decode(
+            [("B183", &read_out[..]), ("B183", &read_out[..]), trg],
+            KEEP_BV
+        ),
+        Err(TryMainEventFromDataBanksError::DuplicateBvBank { .. })
+    ));
+    assert!(matches!(
+        decode([("B093", &read_out[..]), trg], KEEP_BV),
+        Err(TryMainEventFromDataBanksError::BvIdMismatch { .. })
+    ));
+    assert!(matches!(
+        decode([("B183", &wire[..]), trg], KEEP_BV),
+        Err(TryMainEventFromDataBanksError::BvBankWithWireChannel { .. })
+    ));
+}
+
+#[test]
+fn main_event_wire_bank_packet_mismatch() {
+    // Same packet format as the BV, but from an anode wire channel.
+    let wire = bv_packet(128 + 3, &[0; 100]);
+    let trg = ("ATAT", &TRG_V3_PACKET[..]);
+
+    for name in ["C093", "C184"] {
+        assert!(matches!(
+            MainEvent::try_from_banks(RunNumber::SIMULATION, [(name, &wire[..]), trg]),
+            Err(TryMainEventFromDataBanksError::Alpha16IdMismatch { .. })
+        ));
+    }
+    let (event, skipped) = decode([("C093", &wire[..]), trg], LOSSY).unwrap();
+    assert!(event.wire_signals.iter().all(Option::is_none));
+    assert_eq!(skipped.len(), 1);
+}
+
+// Pad-heavy event: the same chunk as `PWB_CHUNK_WITH_FPN` from every AFTER
+// chip of every board in the simulation map.
+fn pad_heavy_banks() -> Vec<(String, Vec<u8>)> {
+    let after_ids = [AfterId::A, AfterId::B, AfterId::C, AfterId::D];
+    let mut banks: Vec<_> = (0..)
+        .map_while(padwing::BoardId::from_index)
+        .filter(|&board_id| TpcPwbPosition::try_new(RunNumber::SIMULATION, board_id).is_ok())
+        .flat_map(|board_id| after_ids.map(|after_id| (board_id, after_id)))
+        .map(|(board_id, after_id)| {
+            let mut chunk = PWB_CHUNK_WITH_FPN;
+            chunk[..4].copy_from_slice(&board_id.device_id().to_le_bytes());
+            chunk[10] = after_id as u8;
+            chunk[21] = b'A' + after_id as u8;
+            chunk[24..30].copy_from_slice(&board_id.mac_address());
+            let crc = !crc32c::crc32c(&chunk[0..16]);
+            chunk[16..20].copy_from_slice(&crc.to_le_bytes());
+            let crc = !crc32c::crc32c(&chunk[20..124]);
+            chunk[124..].copy_from_slice(&crc.to_le_bytes());
+
+            (format!("PC{}", board_id.name()), chunk.to_vec())
+        })
+        .collect();
+    banks.push((String::from("ATAT"), TRG_V3_PACKET.to_vec()));
+
+    banks
+}
+
+#[test]
+fn main_event_pad_heavy() {
+    let banks = pad_heavy_banks();
+    let options = DecodeOptions {
+        keep_fpn: true,
+        ..Default::default()
+    };
+    let (event, _) = decode(
+        banks.iter().map(|(name, data)| (name.as_str(), &data[..])),
+        options,
+    )
+    .unwrap();
+    // One FPN channel per chunk.
+    assert_eq!(event.fpn_signals().unwrap().len(), banks.len() - 1);
+    assert_eq!(banks.len() - 1, TPC_PWB_COLUMNS * TPC_PWB_ROWS * 4);
+}
+
+#[test]
+fn pad_column_charge_sum_of_pad_signals() {
+    let banks = pad_heavy_banks();
+    let event = MainEvent::try_from_banks(
+        RunNumber::SIMULATION,
+        banks.iter().map(|(name, data)| (name.as_str(), &data[..])),
+    )
+    .unwrap();
+
+    let charge = event.pad_column_charge();
+    for (column, signals) in event.pad_signals.iter().enumerate() {
+        let expected: f64 = signals.iter().flatten().flatten().sum();
+        assert_eq!(charge[column], -expected);
+    }
+    assert!(charge.iter().any(|&charge| charge != 0.0));
+    // No pad signals at all.
+    let trg_only =
+        MainEvent::try_from_banks(RunNumber::SIMULATION, [("ATAT", &TRG_V3_PACKET[..])]).unwrap();
+    assert_eq!(trg_only.pad_column_charge(), [0.0; TPC_PAD_COLUMNS]);
+}
+
+#[test]
+fn decode_options_lossy_corrupted_chunk() {
+    let mut banks = pad_heavy_banks();
+    let (name, mut corrupted) = banks.remove(5);
+    // Waveform sample i.e. the chunk fails its payload CRC check.
+    corrupted[80] ^= 0xFF;
+    let clean = MainEvent::try_from_banks(
+        RunNumber::SIMULATION,
+        banks.iter().map(|(name, data)| (name.as_str(), &data[..])),
+    )
+    .unwrap();
+    assert!(clean.pad_signals.iter().flatten().any(Option::is_some));
+
+    banks.insert(5, (name, corrupted));
+    banks.push((String::from("ZZZZ"), Vec::new()));
+    let banks = || banks.iter().map(|(name, data)| (name.as_str(), &data[..]));
+    assert!(MainEvent::try_from_banks(RunNumber::SIMULATION, banks()).is_err());
+
+    let (lossy, skipped) = decode(banks(), LOSSY).unwrap();
+    assert_eq!(skipped.len(), 2);
+    assert!(matches!(
+        skipped[0],
+        TryMainEventFromDataBanksError::UnknownBank(_)
+    ));
+    assert!(matches!(
+        skipped[1],
+        TryMainEventFromDataBanksError::BadPadwingChunk(_)
+    ));
+    // Everything downstream (avalanches, vertex, etc.) is computed from these
+    // signals, so it is the same as if the bad chunk was never there.
+    assert_eq!(lossy.pad_signals, clean.pad_signals);
+    assert_eq!(lossy.wire_signals, clean.wire_signals);
+
+    // Not lossy, but with a single chunk failing the whole event.
+    let calibration = RunCalibration::new(RunNumber::SIMULATION);
+    assert!(
+        MainEvent::try_from_banks_with(&calibration, banks(), &DecodeOptions::default()).is_err()
+    );
+}
+
+#[test]
+fn try_from_banks_with_same_as_try_from_banks() {
+    let banks = pad_heavy_banks();
+    let banks = || banks.iter().map(|(name, data)| (name.as_str(), &data[..]));
+    let calibration = RunCalibration::new(RunNumber::SIMULATION);
+    assert_eq!(calibration.run_number(), RunNumber::SIMULATION);
+
+    let event = MainEvent::try_from_banks(RunNumber::SIMULATION, banks()).unwrap();
+    let (cached, skipped) =
+        MainEvent::try_from_banks_with(&calibration, banks(), &DecodeOptions::default()).unwrap();
+    assert!(skipped.is_empty());
+    assert!(event.pad_signals.iter().flatten().any(Option::is_some));
+    assert_eq!(cached.pad_signals, event.pad_signals);
+    assert_eq!(cached.wire_signals, event.wire_signals);
+    assert_eq!(cached.wire_phi_offset, event.wire_phi_offset);
+    assert_eq!(cached.pad_column_offsets, event.pad_column_offsets);
+    assert_eq!(cached.trigger_timestamp, event.trigger_timestamp);
+}
+
+#[test]
+#[allow(deprecated)]
+fn try_from_banks_u32_same_as_try_from_banks() {
+    let banks = pad_heavy_banks();
+    let banks = || banks.iter().map(|(name, data)| (name.as_str(), &data[..]));
+
+    let event = MainEvent::try_from_banks(RunNumber::SIMULATION, banks()).unwrap();
+    let shim = MainEvent::try_from_banks_u32(RunNumber::SIMULATION.into(), banks()).unwrap();
+    assert_eq!(shim.pad_signals, event.pad_signals);
+    assert_eq!(shim.wire_signals, event.wire_signals);
+}
+
+#[test]
+fn run_calibration_field() {
+    let banks = pad_heavy_banks();
+    let banks = || banks.iter().map(|(name, data)| (name.as_str(), &data[..]));
+    let calibration = RunCalibration::new(RunNumber::SIMULATION);
+    assert_eq!(calibration.field(), FieldConfiguration::On);
+    let (event, _) =
+        MainEvent::try_from_banks_with(&calibration, banks(), &DecodeOptions::default()).unwrap();
+    assert_eq!(event.field, FieldConfiguration::On);
+
+    let calibration = calibration.with_field(FieldConfiguration::Off);
+    assert_eq!(calibration.field(), FieldConfiguration::Off);
+    let (event, _) =
+        MainEvent::try_from_banks_with(&calibration, banks(), &DecodeOptions::default()).unwrap();
+    assert_eq!(event.field, FieldConfiguration::Off);
+}
+
+// A field-off event must not produce a (bogus) helix.
+#[test]
+fn field_off_tracks_are_lines() {
+    let event = busy_main_event();
+    let avalanches = event.avalanches();
+    let tracks = event.tracks_from_avalanches(&avalanches);
+    assert_eq!(tracks.len(), event.tracks().len());
+    assert!(tracks
+        .iter()
+        .all(|(_, track)| matches!(track.model(), TrackModel::Helix { .. })));
+
+    let event = MainEvent {
+        field: FieldConfiguration::Off,
+        ..event
+    };
+    assert!(event
+        .tracks_from_avalanches(&avalanches)
+        .iter()
+        .all(|(_, track)| matches!(track.model(), TrackModel::Line { .. })));
+}
+
+#[test]
+fn try_from_banks_with_missing_calibration() {
+    // No channel has a calibration for this run. This is only an error if a
+    // channel shows up in an event.
+    let calibration = RunCalibration::new(RunNumber::new(0));
+    let trg = ("ATAT", &TRG_V3_PACKET[..]);
+
+    assert!(MainEvent::try_from_banks_with(&calibration, [trg], &DecodeOptions::default()).is_ok());
+}
+
+#[test]
+fn decode_options_lossy_fatal_errors() {
+    let chunk = ("PC00", &PWB_CHUNK_WITH_FPN[..]);
+    let trg = ("ATAT", &TRG_V3_PACKET[..]);
+
+    assert!(matches!(
+        decode([chunk], LOSSY),
+        Err(TryMainEventFromDataBanksError::MissingTrgBank)
+    ));
+    assert!(matches!(
+        decode([chunk, trg, trg], LOSSY),
+        Err(TryMainEventFromDataBanksError::DuplicateTrgBank)
+    ));
+
+    let (_, skipped) = decode([chunk, trg], LOSSY).unwrap();
+    assert!(skipped.is_empty());
+}
+
+#[test]
+fn decode_options_strict() {
+    // Anode wire packet (channel 3) with an empty waveform after data
+    // suppression, and the given accepted trigger counter.
+    let wire = |counter: u8| [1, 3, 0, counter, 2, 131, 2, 187, 0, 0, 0, 4, 224, 0, 0, 0];
+    let (first, second) = (wire(1), wire(2));
+    let trg = ("ATAT", &TRG_V3_PACKET[..]);
+    let misbuilt = [("C093", &first[..]), ("C183", &second[..]), trg];
+
+    let (event, _) = decode(misbuilt, DecodeOptions::default()).unwrap();
+    assert!(event.misbuilt().is_some());
+    let strict = DecodeOptions {
+        strict: true,
+        ..Default::default()
+    };
+    assert!(matches!(
+        decode(misbuilt, strict),
+        Err(TryMainEventFromDataBanksError::MisbuiltEvent(_))
+    ));
+}
+
+#[test]
+fn decode_options_combined() {
+    let chunk = ("PC00", &PWB_CHUNK_WITH_FPN[..]);
+    let trg = ("ATAT", &TRG_V3_PACKET[..]);
+    let banks = [
+        chunk,
+        ("ZZZZ", &[][..]),
+        ("B184", &SUPPRESSED_BV_PACKET[..]),
+        trg,
+    ];
+    let options = DecodeOptions {
+        lossy: true,
+        keep_fpn: true,
+        keep_bv: true,
+        strict: true,
+    };
+
+    let (event, skipped) = decode(banks, options).unwrap();
+    assert!(event.misbuilt().is_none());
+    assert_eq!(event.fpn_signals().unwrap().len(), 1);
+    assert_eq!(event.bv_signals().unwrap().len(), 1);
+    assert!(matches!(
+        skipped[..],
+        [TryMainEventFromDataBanksError::UnknownBank(_)]
+    ));
+}
+
+#[test]
+fn spacepoint_extrapolation_margin() {
+    let avalanche = busy_main_event().avalanches()[0];
+    let z = Length::new::<meter>(0.0);
+    let last_time = *drift_time_range(z).unwrap().end();
+    let avalanche = Avalanche {
+        t: last_time + DEFAULT_EXTRAPOLATION_MARGIN / 2.0,
+        z,
+        ..avalanche
+    };
+
+    let edge = Avalanche {
+        t: last_time,
+        ..avalanche
+    };
+    // Extrapolated towards the inner cathode.
+    let point = SpacePoint::try_from(avalanche).unwrap();
+    assert!(point.r < SpacePoint::try_from(edge).unwrap().r);
+    assert_eq!(
+        SpacePoint::try_from_avalanche_with_margin(avalanche, DEFAULT_EXTRAPOLATION_MARGIN)
+            .unwrap(),
+        point
+    );
+    assert!(matches!(
+        SpacePoint::try_from_avalanche_with_margin(avalanche, Time::new::<microsecond>(0.0)),
+        Err(TryDriftLookupError::DriftTimeBeyondMaximum { .. })
+    ));
+    assert!(
+        SpacePoint::try_from_avalanche_with_margin(edge, Time::new::<microsecond>(0.0)).is_ok()
+    );
+}
+
+#[test]
+fn saturated_avalanches() {
+    let event = busy_main_event();
+    let avalanches = event.avalanches();
+    assert!(!avalanches.is_empty());
+    assert!(avalanches.iter().all(|avalanche| !avalanche.saturated));
+    // Saturation is only flagged; it doesn't change the reconstruction.
+    let clipped = MainEvent {
+        wire_clipped: std::array::from_fn(|i| {
+            event.wire_signals[i]
+                .as_ref()
+                .map_or(Vec::new(), |signal| (0..signal.len()).collect())
+        }),
+        ..event.clone()
+    };
+    let clipped_avalanches = clipped.avalanches();
+    assert_eq!(avalanches.len(), clipped_avalanches.len());
+    for (avalanche, clipped_avalanche) in avalanches.into_iter().zip(clipped_avalanches) {
+        assert!(clipped_avalanche.saturated);
+        assert_eq!(
+            Avalanche {
+                saturated: true,
+                ..avalanche
+            },
+            clipped_avalanche
+        );
+    }
+}
+
+#[test]
+fn deconvolution_diagnostics_no_signal() {
+    let event = busy_main_event();
+    // Wire signals only in the first few pad columns.
+    let wire = (0..TPC_ANODE_WIRES)
+        .find(|&i| wire_to_pad_column(i) >= 4)
+        .unwrap();
+    assert_eq!(
+        event.wire_deconvolution(TpcWirePosition::try_from(wire).unwrap()),
+        None
+    );
+}
+
+// All the matched spikes have to add up to the avalanches.
+#[test]
+fn deconvolution_diagnostics_matched_spikes() {
+    let event = busy_main_event();
+    let avalanches = event.avalanches();
+
+    let mut matched_wire_spikes = 0;
+    for i in 0..TPC_ANODE_WIRES {
+        let Some(deconvolved) = event.wire_deconvolution(TpcWirePosition::try_from(i).unwrap())
+        else {
+            continue;
+        };
+        assert_eq!(Some(&deconvolved.signal), event.wire_signals[i].as_ref());
+        assert!(deconvolved.spikes.iter().all(|spike| spike.amplitude > 0.0));
+        assert!(deconvolved.spikes.windows(2).all(|w| w[0].t < w[1].t));
+        for spike in deconvolved.spikes.iter().filter(|spike| spike.matched) {
+            matched_wire_spikes += 1;
+            assert!(avalanches
+                .iter()
+                .any(|a| a.t == spike.t && a.wire_amplitude == spike.amplitude));
+        }
+    }
+    assert_eq!(matched_wire_spikes, avalanches.len());
+
+    // Deconvolving all the pads is slow, so only check the first few rows.
+    // Every 5th row is a local maximum.
+    let mut matched_pad_spikes = 0;
+    for row in 0..5usize {
+        let position = TpcPadPosition {
+            column: 0usize.try_into().unwrap(),
+            row: row.try_into().unwrap(),
+        };
+        let deconvolved = event.pad_deconvolution(position).unwrap();
+        for spike in deconvolved.spikes.iter().filter(|spike| spike.matched) {
+            matched_pad_spikes += 1;
+            assert!(avalanches
+                .iter()
+                .any(|a| a.pad_amplitude == spike.amplitude));
+        }
+    }
+    assert!(matched_pad_spikes > 0);
+}
+
+#[test]
+fn deconvolution_same_as_single_channels() {
+    let event = busy_main_event();
+    let deconvolution = event.deconvolution();
+
+    let wires: Vec<_> = (0..TPC_ANODE_WIRES)
+        .filter_map(|i| {
+            let position = TpcWirePosition::try_from(i).unwrap();
+            event
+                .wire_deconvolution(position)
+                .map(|deconvolved| (position, deconvolved))
+        })
+        .collect();
+    assert_eq!(deconvolution.wires, wires);
+
+    assert_eq!(deconvolution.pads.len(), TPC_PAD_COLUMNS * TPC_PAD_ROWS);
+    // Deconvolving single pads is slow, so only check a few of them.
+    for index in [0, 1, TPC_PAD_ROWS, 5 * TPC_PAD_ROWS + 7] {
+        let (position, deconvolved) = &deconvolution.pads[index];
+        assert_eq!(usize::from(position.column), index / TPC_PAD_ROWS);
+        assert_eq!(usize::from(position.row), index % TPC_PAD_ROWS);
+        assert_eq!(
+            event.pad_deconvolution(*position).as_ref(),
+            Some(deconvolved)
+        );
+    }
+}
+
+#[test]
+fn try_vertex_info_from_spacepoints_no_points() {
+    assert!(try_vertex_info_from_spacepoints(Vec::new())
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn try_vertex_info_from_spacepoints_non_finite() {
+    let point = SpacePoint {
+        r: Length::new::<millimeter>(f64::NAN),
+        phi: Angle::new::<radian>(0.0),
+        z: Length::new::<millimeter>(0.0),
+    };
+
+    assert!(matches!(
+        try_vertex_info_from_spacepoints(vec![point; 3]),
+        Err(TryVertexError::NonFiniteSpacePoints { count: 3 })
+    ));
+}
+
+// Spacepoints along a circle (in the transverse plane) that goes through the
+// origin. Only the points within the drift region are kept, which splits the
+// circle into two arcs (i.e. two tracks that meet at the origin).
+fn tracks_through_origin() -> Vec<SpacePoint> {
+    let radius = Length::new::<millimeter>(200.0);
+    let num_points = 2000;
+    (0..num_points)
+        .map(|i| {
+            let theta = Angle::FULL_TURN * f64::from(i) / f64::from(num_points);
+            let x = radius * theta.cos() + radius;
+            let y = radius * theta.sin();
+            SpacePoint {
+                r: (x * x + y * y).sqrt(),
+                phi: y.atan2(x),
+                z: Length::new::<millimeter>(0.0),
+            }
+        })
+        .filter(|p| {
+            p.r > Length::new::<millimeter>(109.2)
+                && p.r < Length::new::<meter>(CATHODE_PADS_RADIUS)
+        })
+        .collect()
+}
+
+#[test]
+fn reconstruction_report_no_spacepoints() {
+    let (result, report) = try_vertex_info_from_spacepoints_with_report(Vec::new());
+
+    assert!(result.unwrap().is_none());
+    assert_eq!(
+        report,
+        ReconstructionReport {
+            vertex: VertexStatus::InsufficientTracks { num_tracks: 0 },
+            ..Default::default()
+        }
+    );
+    assert_eq!(
+        report.no_vertex_reason(),
+        Some(NoVertexReason::NoSpacePoints)
+    );
+}
+
+#[test]
+fn reconstruction_report_non_finite_spacepoints() {
+    let mut points = tracks_through_origin();
+    points[0].z = Length::new::<millimeter>(f64::NAN);
+    let num_points = points.len();
+    let (result, report) = try_vertex_info_from_spacepoints_with_report(points);
+
+    assert!(matches!(
+        result,
+        Err(TryVertexError::NonFiniteSpacePoints { count: 1 })
+    ));
+    assert_eq!(
+        report,
+        ReconstructionReport {
+            num_spacepoints: num_points,
+            non_finite_spacepoints: 1,
+            ..Default::default()
+        }
+    );
+    assert_eq!(
+        report.no_vertex_reason(),
+        Some(NoVertexReason::NonFiniteSpacePoints)
+    );
+}
+
+#[test]
+fn reconstruction_report_no_clusters() {
+    // Fewer points than the minimum cluster size.
+    let points = tracks_through_origin()[..5].to_vec();
+    let (result, report) = try_vertex_info_from_spacepoints_with_report(points);
+
+    assert!(result.unwrap().is_none());
+    assert!(report.cluster_sizes.is_empty());
+    assert_eq!(report.num_unclustered, 5);
+    assert!(report.track_fits.is_empty());
+    assert_eq!(report.no_vertex_reason(), Some(NoVertexReason::NoClusters));
+}
+
+#[test]
+fn reconstruction_report_single_track() {
+    let points: Vec<_> = tracks_through_origin()
+        .into_iter()
+        .filter(|p| p.phi > Angle::new::<radian>(0.0))
+        .collect();
+    let (result, report) = try_vertex_info_from_spacepoints_with_report(points);
+
+    assert!(result.unwrap().is_none());
+    assert_eq!(report.cluster_sizes.len(), 1);
+    assert_eq!(report.track_fits, [TrackFit::Fitted]);
+    assert_eq!(
+        report.vertex,
+        VertexStatus::InsufficientTracks { num_tracks: 1 }
+    );
+    assert_eq!(
+        report.no_vertex_reason(),
+        Some(NoVertexReason::InsufficientTracks)
+    );
+}
+
+#[test]
+fn reconstruction_report_vertex() {
+    let points = tracks_through_origin();
+    let num_points = points.len();
+    let (result, report) = try_vertex_info_from_spacepoints_with_report(points);
+    let vertex = result.unwrap().unwrap();
+
+    assert_eq!(report.num_spacepoints, num_points);
+    assert_eq!(
+        report.cluster_sizes.iter().sum::<usize>() + report.num_unclustered,
+        num_points
+    );
+    assert_eq!(report.track_fits.len(), report.cluster_sizes.len());
+    assert_eq!(
+        report.vertex,
+        VertexStatus::Converged {
+            num_tracks: vertex.tracks.len()
+        }
+    );
+    assert_eq!(report.no_vertex_reason(), None);
+}
+
+#[test]
+fn reconstruct_with_report_same_as_try_vertex_info() {
+    let event = busy_main_event();
+    let (result, report) = event.reconstruct_with_report();
+
+    assert_eq!(
+        result.ok().flatten().map(|info| info.position),
+        event
+            .try_vertex_info()
+            .ok()
+            .flatten()
+            .map(|info| info.position)
+    );
+    assert_eq!(report.num_spacepoints, event.spacepoints().len());
+}
+
+#[test]
+fn reconstruction_summary_busy_main_event() {
+    let event = busy_main_event();
+    let avalanches = event.avalanches();
+    let (result, summary) = event.reconstruction_summary();
+
+    assert_eq!(
+        summary.wire_channels,
+        (0..TPC_ANODE_WIRES)
+            .filter(|&i| wire_to_pad_column(i) < 4)
+            .count()
+    );
+    assert_eq!(summary.pad_channels, TPC_PAD_COLUMNS * TPC_PAD_ROWS);
+    assert_eq!(summary.avalanches, avalanches.len());
+    assert_eq!(summary.report, event.reconstruct_with_report().1);
+    assert_eq!(
+        result.ok().flatten().map(|info| info.position),
+        event.vertex()
+    );
+
+    let (_, from_avalanches) = event.reconstruction_summary_from_avalanches(&avalanches);
+    assert_eq!(from_avalanches, summary);
+}
+
+#[test]
+fn main_event_num_signals() {
+    let event = busy_main_event();
+    let (_, summary) = event.reconstruction_summary();
+    assert_eq!(event.num_wire_signals(), summary.wire_channels);
+    assert_eq!(event.num_pad_signals(), summary.pad_channels);
+
+    let empty = MainEvent {
+        wire_signals: std::array::from_fn(|_| None),
+        pad_signals: std::array::from_fn(|_| std::array::from_fn(|_| None)),
+        ..event
+    };
+    assert_eq!(empty.num_wire_signals(), 0);
+    assert_eq!(empty.num_pad_signals(), 0);
+}
+
+// Track and vertex fit failures can't be reliably engineered from spacepoints.
+#[test]
+fn no_vertex_reason_failed_fits() {
+    let report = ReconstructionReport {
+        num_spacepoints: 40,
+        cluster_sizes: vec![20, 15],
+        num_unclustered: 5,
+        ..Default::default()
+    };
+
+    let failed_track = ReconstructionReport {
+        track_fits: vec![TrackFit::Fitted, TrackFit::FailedFit],
+        ..report.clone()
+    };
+    assert_eq!(
+        failed_track.no_vertex_reason(),
+        Some(NoVertexReason::TrackFitFailed)
+    );
+
+    let no_tracks = ReconstructionReport {
+        track_fits: vec![TrackFit::NoInitialParameters; 2],
+        vertex: VertexStatus::InsufficientTracks { num_tracks: 0 },
+        ..report.clone()
+    };
+    assert_eq!(no_tracks.no_vertex_reason(), Some(NoVertexReason::NoTracks));
+
+    let diverged = ReconstructionReport {
+        track_fits: vec![TrackFit::Fitted; 2],
+        vertex: VertexStatus::Diverged,
+        ..report
+    };
+    assert_eq!(
+        diverged.no_vertex_reason(),
+        Some(NoVertexReason::VertexFitDiverged)
+    );
+}
+
+#[test]
+fn reconstruction_report_display() {
+    let report = ReconstructionReport {
+        num_spacepoints: 40,
+        non_finite_spacepoints: 0,
+        cluster_sizes: vec![18, 15],
+        num_unclustered: 7,
+        track_fits: vec![TrackFit::Fitted, TrackFit::NoInitialParameters],
+        vertex: VertexStatus::InsufficientTracks { num_tracks: 1 },
+    };
+
+    assert_eq!(
+        report.to_string(),
+        "40 spacepoints, 2 clusters [18, 15], 7 unclustered, 1 tracks (1 failed: no initial parameters), vertex insufficient tracks (1 fitted)"
+    );
+}
+
+#[test]
+fn reconstruction_report_serde() {
+    let report = ReconstructionReport {
+        num_spacepoints: 40,
+        non_finite_spacepoints: 0,
+        cluster_sizes: vec![20, 15],
+        num_unclustered: 5,
+        track_fits: vec![TrackFit::Fitted; 2],
+        vertex: VertexStatus::Converged { num_tracks: 2 },
+    };
+    let json = serde_json::to_string(&report).unwrap();
+
+    assert_eq!(
+        serde_json::from_str::<ReconstructionReport>(&json).unwrap(),
+        report
+    );
+    for reason in [
+        NoVertexReason::NoSpacePoints,
+        NoVertexReason::NonFiniteSpacePoints,
+        NoVertexReason::NoClusters,
+        NoVertexReason::NoTracks,
+        NoVertexReason::TrackFitFailed,
+        NoVertexReason::InsufficientTracks,
+        NoVertexReason::VertexFitDiverged,
+    ] {
+        assert_eq!(
+            serde_json::to_string(&reason).unwrap(),
+            format!("\"{}\"", reason.code())
+        );
+    }
+}
+
+#[test]
+fn mirror_avalanches() {
+    let event = busy_main_event();
+    let avalanches = event.avalanches();
+    assert!(!avalanches.is_empty());
+
+    let mut mirrored = event.clone();
+    mirrored.mirror(Mirror::Phi);
+    for (avalanche, mirrored) in avalanches.iter().zip(mirrored.avalanches()) {
+        assert_eq!(mirrored.phi, -avalanche.phi);
+        assert_eq!(Mirror::Phi.apply(mirrored), *avalanche);
+    }
+    let mut mirrored = event.clone();
+    mirrored.mirror(Mirror::Z);
+    for (avalanche, mirrored) in avalanches.iter().zip(mirrored.avalanches()) {
+        assert_eq!(mirrored.z, -avalanche.z);
+        assert_eq!(Mirror::Z.apply(mirrored), *avalanche);
+    }
+}
+
+#[test]
+fn mirror_twice_same_vertex() {
+    let event = busy_main_event();
+    let vertex = event
+        .try_vertex_info()
+        .ok()
+        .flatten()
+        .map(|info| info.position);
+
+    for mirror in [Mirror::Phi, Mirror::Z] {
+        let mut mirrored = event.clone();
+        mirrored.mirror(mirror);
+        mirrored.mirror(mirror);
+        assert_eq!(mirrored.avalanches(), event.avalanches());
+        assert_eq!(
+            mirrored
+                .try_vertex_info()
+                .ok()
+                .flatten()
+                .map(|info| info.position),
+            vertex
+        );
+    }
+}
+
+// The drift tables only depend on `|z|`, so mirroring `z` mirrors the
+// spacepoints exactly.
+#[test]
+fn mirror_z_spacepoints() {
+    let event = busy_main_event();
+    let mut mirrored = event.clone();
+    mirrored.mirror(Mirror::Z);
+
+    let spacepoints = event.spacepoints();
+    let mirrored_spacepoints = mirrored.spacepoints();
+    assert!(!spacepoints.is_empty());
+    assert_eq!(spacepoints.len(), mirrored_spacepoints.len());
+    for (point, mirrored) in spacepoints.into_iter().zip(mirrored_spacepoints) {
+        assert_eq!(
+            mirrored,
+            SpacePoint {
+                z: -point.z,
+                ..point
+            }
+        );
+    }
+}
+
+#[test]
+fn roi_window_first_crossing() {
+    let settings = RoiSettings {
+        pre_samples: 2,
+        post_samples: 3,
+        threshold: 10.0,
+    };
+    let signal = [0.0, 1.0, 2.0, 5.0, -12.0, 20.0, 3.0, 0.0, 15.0, 0.0];
+    assert_eq!(roi_window(&signal, &settings), Some(2..7));
+    // Close to the edges of the signal.
+    assert_eq!(roi_window(&signal[3..], &settings), Some(0..4));
+    assert_eq!(roi_window(&signal[6..], &settings), Some(0..4));
+
+    assert_eq!(roi_window(&[9.9; 10], &settings), None);
+    assert_eq!(roi_window(&[], &settings), None);
+}
+
+#[test]
+fn roi_readout_truncates_signals() {
+    let mut event = busy_main_event();
+    // Wire 8 is in the first pad column, and its pulse peaks at sample 190.
+    event.wire_clipped[8] = vec![0, 180];
+    let settings = RoiSettings {
+        pre_samples: 10,
+        post_samples: 40,
+        threshold: 50.0,
+    };
+    let (roi, reduction) = event.roi_readout(&settings);
+
+    let full_wires = event.wire_signals.iter().flatten().count();
+    let full_pads = event.pad_signals.iter().flatten().flatten().count();
+    assert_eq!(reduction.full_samples, 400 * (full_wires + full_pads));
+    // Every wire crosses the threshold, but the smallest pads (amplitude 100)
+    // are barely above it.
+    assert_eq!(roi.wire_signals.iter().flatten().count(), full_wires);
+    assert_eq!(
+        roi.pad_signals.iter().flatten().flatten().count(),
+        full_pads
+    );
+    assert_eq!(reduction.roi_samples, 50 * (full_wires + full_pads));
+    assert!((reduction.fraction().unwrap() - 0.875).abs() < 1e-12);
+
+    let original = event.wire_signals[8].as_ref().unwrap();
+    let truncated = roi.wire_signals[8].as_ref().unwrap();
+    assert_eq!(truncated.len(), original.len());
+    let crossing = original.iter().position(|&v| v >= 50.0).unwrap();
+    for (i, (&truncated, &original)) in truncated.iter().zip(original).enumerate() {
+        if (crossing - 10..crossing + 40).contains(&i) {
+            assert_eq!(truncated, original);
+        } else {
+            assert_eq!(truncated, 0.0);
+        }
+    }
+    assert_eq!(roi.wire_clipped[8], vec![180]);
+}
+
+#[test]
+fn roi_readout_drops_signals_below_threshold() {
+    let event = busy_main_event();
+    let settings = RoiSettings {
+        pre_samples: 10,
+        post_samples: 40,
+        threshold: 150.0,
+    };
+    let (roi, reduction) = event.roi_readout(&settings);
+
+    // Only the pad rows with amplitudes 200 and 400 are left.
+    let roi_pads = roi.pad_signals.iter().flatten().flatten().count();
+    let rows = (0..TPC_PAD_ROWS).filter(|row| (1..=3).contains(&(row % 5)));
+    assert_eq!(roi_pads, TPC_PAD_COLUMNS * rows.count());
+    for (row, signal) in roi.pad_signals[0].iter().enumerate() {
+        assert_eq!(signal.is_some(), row % 5 != 0 && row % 5 != 4);
+    }
+    let wires = roi.wire_signals.iter().flatten().count();
+    assert_eq!(reduction.roi_samples, 50 * (wires + roi_pads));
+
+    let empty = MainEvent {
+        wire_signals: std::array::from_fn(|_| None),
+        pad_signals: std::array::from_fn(|_| std::array::from_fn(|_| None)),
+        ..event
+    };
+    let (_, reduction) = empty.roi_readout(&settings);
+    assert_eq!(reduction, RoiReduction::default());
+    assert_eq!(reduction.fraction(), None);
+}
+
+#[test]
+fn roi_readout_full_window_same_avalanches() {
+    let event = busy_main_event();
+    let settings = RoiSettings {
+        pre_samples: usize::MAX,
+        post_samples: usize::MAX,
+        threshold: 0.0,
+    };
+    let (roi, reduction) = event.roi_readout(&settings);
+
+    assert_eq!(reduction.roi_samples, reduction.full_samples);
+    assert_eq!(reduction.fraction(), Some(0.0));
+    assert_eq!(roi.avalanches(), event.avalanches());
+}
@@ -0,0 +1,27 @@
+#!/usr/bin/env bash
+#
+# The library crates are used by downstream services that only need parsing and
+# reconstruction. Make sure that they never depend (directly or transitively) on
+# any of the GUI/plotting crates used by the analysis binaries.
+#
+# Usage: ./scripts/check-library-dependencies.sh
+
+set -euo pipefail
+
+FORBIDDEN=(cursive opener pgfplots tempfile)
+
+status=0
+for package in alpha_g_detector alpha_g_physics; do
+    tree=$(cargo tree --package "$package" --edges normal,build --prefix none)
+    for crate in "${FORBIDDEN[@]}"; do
+        if grep -q "^$crate v" <<< "$tree"; then
+            echo "error: \`$package\` depends on \`$crate\`" >&2
+            status=1
+        fi
+    done
+done
+
+if [ "$status" -eq 0 ]; then
+    echo "ok: no GUI/plotting dependencies in the library crates"
+fi
+exit "$status"
@@ -0,0 +1,372 @@
+use alpha_g_detector::alpha16::aw_map::{TpcWirePosition, ANODE_WIRE_PITCH_PHI, TPC_ANODE_WIRES};
+use alpha_g_detector::padwing::map::TPC_PAD_COLUMNS;
+use anyhow::{bail, ensure, Context, Result};
+use clap::{Args, Parser, Subcommand};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Gains outside this range are almost certainly a problem with the
+/// calibration procedure (the nominal gain is 1).
+const WIRE_GAIN_RANGE: RangeInclusive<f64> = 0.5..=2.0;
+/// Pad column timing offsets (in samples) outside this range are more likely
+/// a problem with the pad delay than a clock distribution skew.
+const PAD_COLUMN_OFFSET_RANGE: RangeInclusive<i32> = -16..=16;
+
+#[derive(Parser)]
+/// Development tasks for the ALPHA-g workspace
+struct Cli {
+    #[command(subcommand)]
+    task: Task,
+}
+
+#[derive(Subcommand)]
+enum Task {
+    /// Add a new anode wire gain calibration epoch to `alpha_g_physics`
+    IngestWireGain {
+        #[command(flatten)]
+        common: IngestArgs,
+        /// Accept a calibration that is missing some wires
+        #[arg(long)]
+        allow_missing: bool,
+    },
+    /// Add a new anode wire alignment (phi offset) epoch to `alpha_g_physics`
+    IngestWireAlignment {
+        #[command(flatten)]
+        common: IngestArgs,
+    },
+    /// Add a new pad column timing offsets epoch to `alpha_g_physics`
+    IngestPadColumnOffsets {
+        #[command(flatten)]
+        common: IngestArgs,
+    },
+}
+
+#[derive(Args)]
+struct IngestArgs {
+    /// Calibration file
+    file: PathBuf,
+    /// First run number for which the calibration is valid
+    #[arg(long, value_name = "RUN_NUMBER")]
+    valid_from_run: u32,
+    /// Run number from which the calibration was derived [default:
+    /// `<VALID_FROM_RUN>`]
+    #[arg(long, value_name = "RUN_NUMBER")]
+    source_run: Option<u32>,
+    /// Name of the calibration file in the data directory [default:
+    /// `<VALID_FROM_RUN>_complete.json`]
+    #[arg(long)]
+    name: Option<String>,
+    /// Note about the calibration (written as a comment in the generated
+    /// module)
+    #[arg(long)]
+    note: Option<String>,
+    /// Don't run the calibration unit tests after ingesting the file
+    #[arg(long)]
+    skip_tests: bool,
+}
+
+// Location of a calibration that is split in epochs within `alpha_g_physics`.
+struct Calibration {
+    // Name of the xtask that updates this calibration.
+    task: &'static str,
+    // Relative to the `physics` directory.
+    data_dir: &'static str,
+    // Relative to the `physics` directory.
+    module: &'static str,
+    // Test filter that selects the unit tests of this calibration.
+    tests: &'static str,
+}
+
+const WIRE_GAIN: Calibration = Calibration {
+    task: "ingest-wire-gain",
+    data_dir: "data/calibration/wires/gain/",
+    module: "src/calibration/wires/gain/epochs.rs",
+    tests: "calibration::wires::gain",
+};
+
+const WIRE_ALIGNMENT: Calibration = Calibration {
+    task: "ingest-wire-alignment",
+    data_dir: "data/calibration/wires/alignment/",
+    module: "src/calibration/wires/alignment/epochs.rs",
+    tests: "calibration::wires::alignment",
+};
+
+const PAD_COLUMN_OFFSETS: Calibration = Calibration {
+    task: "ingest-pad-column-offsets",
+    data_dir: "data/calibration/pads/column_offsets/",
+    module: "src/calibration/pads/column_offsets/epochs.rs",
+    tests: "calibration::pads::column_offsets",
+};
+
+// Entry of the `epochs.json` index in the data directory. This index is the
+// source of truth from which the `epochs.rs` module is generated.
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+struct Epoch {
+    valid_from_run: u32,
+    source_run: u32,
+    file: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    note: Option<String>,
+}
+
+// Parse and validate a wire gain calibration. Return the wires without a gain.
+fn validate_wire_gain(contents: &[u8]) -> Result<(BTreeMap<TpcWirePosition, f64>, Vec<usize>)> {
+    let map: BTreeMap<TpcWirePosition, f64> =
+        serde_json::from_slice(contents).context("bad wire gain calibration format")?;
+    for (wire, gain) in &map {
+        ensure!(
+            WIRE_GAIN_RANGE.contains(gain),
+            "gain `{gain}` of wire `{}` is outside the expected range `{WIRE_GAIN_RANGE:?}`",
+            usize::from(*wire)
+        );
+    }
+    let missing = (0..TPC_ANODE_WIRES)
+        .filter(|&i| !map.contains_key(&TpcWirePosition::try_from(i).unwrap()))
+        .collect();
+
+    Ok((map, missing))
+}
+
+#[derive(Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(deny_unknown_fields)]
+struct WireAlignment {
+    // Radians.
+    phi_offset: f64,
+}
+
+// Parse and validate a wire alignment calibration.
+fn validate_wire_alignment(contents: &[u8]) -> Result<WireAlignment> {
+    let alignment: WireAlignment =
+        serde_json::from_slice(contents).context("bad wire alignment calibration format")?;
+    // A rotation of a full wire pitch (or more) is more likely a mistake in
+    // the wire mapping than a misalignment.
+    ensure!(
+        alignment.phi_offset.abs() < ANODE_WIRE_PITCH_PHI,
+        "phi offset `{}` is not smaller than the wire pitch `{ANODE_WIRE_PITCH_PHI}`",
+        alignment.phi_offset
+    );
+
+    Ok(alignment)
+}
+
+#[derive(Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(deny_unknown_fields)]
+struct PadColumnOffsets {
+    // Samples. One per pad column.
+    sample_offsets: Vec<i32>,
+}
+
+// Parse and validate a pad column timing offsets calibration.
+fn validate_pad_column_offsets(contents: &[u8]) -> Result<PadColumnOffsets> {
+    let offsets: PadColumnOffsets =
+        serde_json::from_slice(contents).context("bad pad column offsets calibration format")?;
+    ensure!(
+        offsets.sample_offsets.len() == TPC_PAD_COLUMNS,
+        "expected `{TPC_PAD_COLUMNS}` offsets (one per pad column), found `{}`",
+        offsets.sample_offsets.len()
+    );
+    for (column, offset) in offsets.sample_offsets.iter().enumerate() {
+        ensure!(
+            PAD_COLUMN_OFFSET_RANGE.contains(offset),
+            "offset `{offset}` of pad column `{column}` is outside the expected range \
+            `{PAD_COLUMN_OFFSET_RANGE:?}`"
+        );
+    }
+
+    Ok(offsets)
+}
+
+// Insert a new epoch keeping the index sorted by run number.
+fn insert_epoch(epochs: &mut Vec<Epoch>, epoch: Epoch) -> Result<()> {
+    ensure!(
+        epoch.valid_from_run != u32::MAX,
+        "run number `{}` is reserved for simulation",
+        u32::MAX
+    );
+    if let Some(existing) = epochs
+        .iter()
+        .find(|e| e.valid_from_run == epoch.valid_from_run || e.file == epoch.file)
+    {
+        bail!(
+            "conflict with existing epoch starting at run `{}` (`{}`)",
+            existing.valid_from_run,
+            existing.file
+        );
+    }
+    let index = epochs.partition_point(|e| e.valid_from_run < epoch.valid_from_run);
+    epochs.insert(index, epoch);
+
+    Ok(())
+}
+
+// Source code of the `epochs.rs` module. The output is already formatted with
+// `rustfmt` (checked by unit tests against the committed modules).
+fn epochs_module(calibration: &Calibration, epochs: &[Epoch]) -> String {
+    let Calibration { task, data_dir, .. } = calibration;
+    let mut module = format!(
+        "// @generated by `cargo xtask {task}` from
+// `{data_dir}epochs.json`. Do not edit by hand.
+
+// First run number for which each calibration is valid (sorted in increasing
+// order), the run number from which it was derived, and the contents of the
+// calibration file.
+pub(super) const EPOCHS: &[(u32, u32, &[u8])] = &["
+    );
+    if epochs.is_empty() {
+        module.push_str("];\n");
+        return module;
+    }
+    module.push('\n');
+    for epoch in epochs {
+        if let Some(note) = &epoch.note {
+            for line in wrap_comment(note, 80 - "    // ".len()) {
+                writeln!(module, "    // {line}").unwrap();
+            }
+        }
+        writeln!(
+            module,
+            "    (
+        {},
+        {},
+        include_bytes!(\"../../../../{data_dir}{}\"),
+    ),",
+            epoch.valid_from_run, epoch.source_run, epoch.file
+        )
+        .unwrap();
+    }
+    module.push_str("];\n");
+
+    module
+}
+
+fn wrap_comment(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        if !line.is_empty() && line.len() + 1 + word.len() > width {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+fn workspace_root() -> &'static Path {
+    Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap()
+}
+
+// Add an (already validated) calibration file as a new epoch. Regenerates the
+// index and module, and runs the calibration unit tests.
+fn ingest(calibration: &Calibration, args: IngestArgs, contents: String) -> Result<()> {
+    let physics = workspace_root().join("physics");
+    let data_dir = physics.join(calibration.data_dir);
+
+    let index_path = data_dir.join("epochs.json");
+    let mut epochs: Vec<Epoch> = serde_json::from_slice(
+        &std::fs::read(&index_path)
+            .with_context(|| format!("failed to read `{}`", index_path.display()))?,
+    )
+    .with_context(|| format!("failed to parse `{}`", index_path.display()))?;
+    let name = args
+        .name
+        .unwrap_or_else(|| format!("{}_complete.json", args.valid_from_run));
+    let output = data_dir.join(&name);
+    ensure!(!output.exists(), "`{}` already exists", output.display());
+    insert_epoch(
+        &mut epochs,
+        Epoch {
+            valid_from_run: args.valid_from_run,
+            source_run: args.source_run.unwrap_or(args.valid_from_run),
+            file: name,
+            note: args.note,
+        },
+    )?;
+
+    std::fs::write(&output, contents)
+        .with_context(|| format!("failed to write `{}`", output.display()))?;
+    eprintln!("Created `{}`", output.display());
+    std::fs::write(&index_path, serde_json::to_string_pretty(&epochs)? + "\n")
+        .with_context(|| format!("failed to write `{}`", index_path.display()))?;
+    eprintln!("Updated `{}`", index_path.display());
+    let module = physics.join(calibration.module);
+    std::fs::write(&module, epochs_module(calibration, &epochs))
+        .with_context(|| format!("failed to write `{}`", module.display()))?;
+    eprintln!("Updated `{}`", module.display());
+
+    eprintln!("Calibration epochs:");
+    for epoch in &epochs {
+        eprintln!(
+            "  {}.. => {} (from run {})",
+            epoch.valid_from_run, epoch.file, epoch.source_run
+        );
+    }
+
+    if !args.skip_tests {
+        let cargo = std::env::var("CARGO").unwrap_or_else(|_| String::from("cargo"));
+        let status = Command::new(cargo)
+            .args(["test", "--package", "alpha_g_physics", calibration.tests])
+            .status()
+            .context("failed to run calibration unit tests")?;
+        ensure!(status.success(), "calibration unit tests failed");
+    }
+
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    match cli.task {
+        Task::IngestWireGain {
+            common,
+            allow_missing,
+        } => {
+            let file = &common.file;
+            let contents = std::fs::read(file)
+                .with_context(|| format!("failed to read `{}`", file.display()))?;
+            let (map, missing) = validate_wire_gain(&contents)
+                .with_context(|| format!("failed to validate `{}`", file.display()))?;
+            if !missing.is_empty() {
+                ensure!(
+                    allow_missing,
+                    "missing gain for wires `{missing:?}` (use `--allow-missing` to ingest anyway)"
+                );
+                eprintln!("Warning: missing gain for wires `{missing:?}`");
+            }
+            // Re-serialize the map such that the stored file is ordered by
+            // wire (easier to diff between calibrations).
+            ingest(&WIRE_GAIN, common, serde_json::to_string(&map)?)
+        }
+        Task::IngestWireAlignment { common } => {
+            let file = &common.file;
+            let contents = std::fs::read(file)
+                .with_context(|| format!("failed to read `{}`", file.display()))?;
+            let alignment = validate_wire_alignment(&contents)
+                .with_context(|| format!("failed to validate `{}`", file.display()))?;
+            ingest(&WIRE_ALIGNMENT, common, serde_json::to_string(&alignment)?)
+        }
+        Task::IngestPadColumnOffsets { common } => {
+            let file = &common.file;
+            let contents = std::fs::read(file)
+                .with_context(|| format!("failed to read `{}`", file.display()))?;
+            let offsets = validate_pad_column_offsets(&contents)
+                .with_context(|| format!("failed to validate `{}`", file.display()))?;
+            ingest(
+                &PAD_COLUMN_OFFSETS,
+                common,
+                serde_json::to_string(&offsets)?,
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,130 @@
+use super::*;
+
+fn epoch(valid_from_run: u32, file: &str) -> Epoch {
+    Epoch {
+        valid_from_run,
+        source_run: valid_from_run,
+        file: file.to_string(),
+        note: None,
+    }
+}
+
+#[test]
+fn validate_wire_gain_complete() {
+    let map: BTreeMap<_, _> = (0..TPC_ANODE_WIRES)
+        .map(|i| (TpcWirePosition::try_from(i).unwrap(), 1.0))
+        .collect();
+    let contents = serde_json::to_vec(&map).unwrap();
+
+    let (parsed, missing) = validate_wire_gain(&contents).unwrap();
+    assert_eq!(parsed, map);
+    assert!(missing.is_empty());
+}
+
+#[test]
+fn validate_wire_gain_missing() {
+    let (_, missing) = validate_wire_gain(br#"{"0": 1.0, "2": 1.5}"#).unwrap();
+    assert_eq!(missing.len(), TPC_ANODE_WIRES - 2);
+    assert_eq!(missing[..2], [1, 3]);
+}
+
+#[test]
+fn validate_wire_gain_bad() {
+    // Bad schema.
+    assert!(validate_wire_gain(br#"{"0": "1.0"}"#).is_err());
+    assert!(validate_wire_gain(br#"[1.0, 1.0]"#).is_err());
+    // Bad wire.
+    assert!(validate_wire_gain(br#"{"256": 1.0}"#).is_err());
+    // Bad values.
+    assert!(validate_wire_gain(br#"{"0": 0.0}"#).is_err());
+    assert!(validate_wire_gain(br#"{"0": 2.5}"#).is_err());
+    assert!(validate_wire_gain(br#"{"0": -1.0}"#).is_err());
+}
+
+#[test]
+fn insert_epoch_sorted() {
+    let mut epochs = vec![epoch(100, "a.json"), epoch(300, "c.json")];
+
+    insert_epoch(&mut epochs, epoch(200, "b.json")).unwrap();
+    insert_epoch(&mut epochs, epoch(400, "d.json")).unwrap();
+    insert_epoch(&mut epochs, epoch(50, "e.json")).unwrap();
+    let runs: Vec<_> = epochs.iter().map(|e| e.valid_from_run).collect();
+    assert_eq!(runs, [50, 100, 200, 300, 400]);
+}
+
+#[test]
+fn insert_epoch_conflict() {
+    let mut epochs = vec![epoch(100, "a.json")];
+
+    assert!(insert_epoch(&mut epochs, epoch(100, "b.json")).is_err());
+    assert!(insert_epoch(&mut epochs, epoch(200, "a.json")).is_err());
+    assert!(insert_epoch(&mut epochs, epoch(u32::MAX, "b.json")).is_err());
+    assert_eq!(epochs, [epoch(100, "a.json")]);
+}
+
+#[test]
+fn validate_wire_alignment_good() {
+    let alignment = validate_wire_alignment(br#"{"phi_offset": -0.001}"#).unwrap();
+    assert_eq!(alignment, WireAlignment { phi_offset: -0.001 });
+}
+
+#[test]
+fn validate_wire_alignment_bad() {
+    // Bad schema.
+    assert!(validate_wire_alignment(br#"{"phi": 0.001}"#).is_err());
+    assert!(validate_wire_alignment(br#"{"phi_offset": 0.001, "extra": 1}"#).is_err());
+    assert!(validate_wire_alignment(br#"0.001"#).is_err());
+    // Bad values.
+    assert!(validate_wire_alignment(br#"{"phi_offset": 0.1}"#).is_err());
+    assert!(validate_wire_alignment(br#"{"phi_offset": -0.1}"#).is_err());
+}
+
+#[test]
+fn validate_pad_column_offsets_good() {
+    let contents = serde_json::to_vec(&serde_json::json!({
+        "sample_offsets": (0..TPC_PAD_COLUMNS as i32).map(|i| i % 5 - 2).collect::<Vec<_>>()
+    }))
+    .unwrap();
+
+    let offsets = validate_pad_column_offsets(&contents).unwrap();
+    assert_eq!(offsets.sample_offsets.len(), TPC_PAD_COLUMNS);
+    assert_eq!(offsets.sample_offsets[..6], [-2, -1, 0, 1, 2, -2]);
+}
+
+#[test]
+fn validate_pad_column_offsets_bad() {
+    let offsets = |offsets: Vec<i32>| {
+        serde_json::to_vec(&serde_json::json!({ "sample_offsets": offsets })).unwrap()
+    };
+    // Bad schema.
+    assert!(validate_pad_column_offsets(br#"{"offsets": []}"#).is_err());
+    assert!(validate_pad_column_offsets(br#"{"sample_offsets": [0.5]}"#).is_err());
+    // Bad number of columns.
+    assert!(validate_pad_column_offsets(&offsets(vec![0; TPC_PAD_COLUMNS - 1])).is_err());
+    assert!(validate_pad_column_offsets(&offsets(vec![0; TPC_PAD_COLUMNS + 1])).is_err());
+    // Bad values.
+    let mut values = vec![0; TPC_PAD_COLUMNS];
+    values[7] = 17;
+    assert!(validate_pad_column_offsets(&offsets(values.clone())).is_err());
+    values[7] = -17;
+    assert!(validate_pad_column_offsets(&offsets(values)).is_err());
+}
+
+#[test]
+fn epochs_module_empty() {
+    assert!(epochs_module(&WIRE_ALIGNMENT, &[]).ends_with("= &[];\n"));
+}
+
+#[test]
+fn epochs_modules_up_to_date() {
+    let physics = workspace_root().join("physics");
+    for calibration in [WIRE_GAIN, WIRE_ALIGNMENT, PAD_COLUMN_OFFSETS] {
+        let epochs: Vec<Epoch> = serde_json::from_slice(
+            &std::fs::read(physics.join(calibration.data_dir).join("epochs.json")).unwrap(),
+        )
+        .unwrap();
+        let module = std::fs::read_to_string(physics.join(calibration.module)).unwrap();
+
+        assert_eq!(epochs_module(&calibration, &epochs), module);
+    }
+}